@@ -1,13 +1,102 @@
+//! Criterion benchmarks of the passive detection path: provider header/body matchers,
+//! confidence scoring and a whole passive-only `detect_all` over the recorded fixtures.
+//!
+//! Run with `cargo bench`; a passive scan should stay well under 100 ms.
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use waf_detector::confidence::AdvancedScoring;
+use waf_detector::http::HttpResponse;
+use waf_detector::providers::{default_providers, Provider};
+use waf_detector::registry::ProviderRegistry;
+use waf_detector::testing::{load_fixtures, Fixture};
+use waf_detector::{DetectionContext, DetectionOptions};
+
+const URL: &str = "https://example.com/";
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+}
+
+fn fixtures() -> Vec<Fixture> {
+    load_fixtures(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")).unwrap()
+}
+
+fn fixture_response(provider: &str) -> HttpResponse {
+    let fixture = fixtures().into_iter().find(|f| f.provider.as_deref() == Some(provider)).unwrap();
+    fixture.response(URL).unwrap()
+}
+
+fn provider(name: &str) -> Provider {
+    default_providers().into_iter().find(|p| p.name() == name).unwrap()
+}
+
+/// Every provider's passive checks against a protected and an unprotected response
+fn bench_header_matchers(c: &mut Criterion) {
+    let rt = runtime();
+    let providers = default_providers();
+    let protected = fixture_response("CloudFlare");
+    let origin = fixtures().into_iter().find(|f| f.provider.is_none()).unwrap().response(URL).unwrap();
 
-fn benchmark_placeholder(c: &mut Criterion) {
-    c.bench_function("placeholder", |b| {
+    let mut group = c.benchmark_group("matchers");
+    group.bench_function("cloudflare_passive_detect", |b| {
+        let cloudflare = provider("CloudFlare");
+        b.iter(|| rt.block_on(cloudflare.passive_detect(black_box(&protected))).unwrap())
+    });
+    group.bench_function("all_providers_protected", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for provider in &providers {
+                    black_box(provider.passive_detect(&protected).await.unwrap());
+                }
+            })
+        })
+    });
+    group.bench_function("all_providers_origin", |b| {
         b.iter(|| {
-            // Placeholder benchmark - will be replaced with real benchmarks later
-            black_box(1 + 1)
+            rt.block_on(async {
+                for provider in &providers {
+                    black_box(provider.passive_detect(&origin).await.unwrap());
+                }
+            })
         })
     });
+    group.finish();
+}
+
+/// Confidence scoring of the evidence the CloudFlare fixture produces
+fn bench_scoring(c: &mut Criterion) {
+    let rt = runtime();
+    let response = fixture_response("CloudFlare");
+    let evidence = rt.block_on(provider("CloudFlare").passive_detect(&response)).unwrap();
+    let scoring = AdvancedScoring::new();
+
+    c.bench_function("scoring/calculate_confidence", |b| {
+        b.iter(|| scoring.calculate_confidence(black_box("CloudFlare"), black_box(&evidence), &response.headers))
+    });
+}
+
+/// A whole passive-only scan of a response already fetched: every provider, the
+/// block-page match and scoring, with no analyzer futures built
+fn bench_passive_scan(c: &mut Criterion) {
+    let rt = runtime();
+    let registry = ProviderRegistry::new();
+    for provider in default_providers() {
+        registry.register_provider(provider).unwrap();
+    }
+    let options = DetectionOptions::offline();
+
+    let mut group = c.benchmark_group("passive_scan");
+    for name in ["CloudFlare", "Akamai", "Fastly"] {
+        let context = DetectionContext {
+            url: URL.to_string(),
+            response: Some(fixture_response(name)),
+            dns_info: None,
+            user_agent: String::new(),
+        };
+        group.bench_function(name, |b| b.iter(|| rt.block_on(registry.detect_all_with_options(black_box(&context), &options)).unwrap()));
+    }
+    group.finish();
 }
 
-criterion_group!(benches, benchmark_placeholder);
-criterion_main!(benches); 
\ No newline at end of file
+criterion_group!(benches, bench_header_matchers, bench_scoring, bench_passive_scan);
+criterion_main!(benches);
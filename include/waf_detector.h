@@ -0,0 +1,34 @@
+/*
+ * C interface of the waf-detector library (`ffi` feature).
+ *
+ * Build: cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Each call returns a NUL-terminated JSON string owned by the library:
+ *   {"success": true, "result": {...}}  or  {"success": false, "error": "..."}
+ * Release it with waf_string_free. Calls block until detection finishes and may be
+ * made from any thread.
+ */
+#ifndef WAF_DETECTOR_H
+#define WAF_DETECTOR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Full detection of a URL or bare host name (scanned over https). */
+char *waf_detect_url(const char *url);
+
+/* Passive detection of a raw HTTP response received from `url`; no network I/O. */
+char *waf_detect_response(const char *url, const uint8_t *response, size_t response_len);
+
+/* Release a string returned by the functions above; NULL is ignored. */
+void waf_string_free(char *json);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WAF_DETECTOR_H */
@@ -0,0 +1,244 @@
+//! Block-page classification by fuzzy hashing
+//!
+//! Sites restyle vendor block pages (a logo, a support address, translated text), so
+//! exact markers miss them. Each body is reduced to a 64-bit simhash over three-token
+//! shingles of its markup and text, with numbers folded together so incident and Ray
+//! IDs do not count, and compared with the hashes of a bundled corpus of known pages.
+
+use crate::http::HttpResponse;
+use crate::{Evidence, MethodType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+/// Bodies with fewer tokens than this hash too coarsely to compare
+const MIN_TOKENS: usize = 12;
+
+/// Tokens per shingle
+const SHINGLE: usize = 3;
+
+/// Lowest similarity reported as a match
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+macro_rules! pages {
+    ($(($vendor:expr, $name:expr, $confidence:expr, $path:expr)),* $(,)?) => {
+        &[$(KnownPage { vendor: $vendor, name: $name, confidence: $confidence, body: include_str!(concat!("pages/", $path)) }),*]
+    };
+}
+
+/// A vendor block page from the bundled corpus
+#[derive(Debug, Clone, Copy)]
+pub struct KnownPage {
+    pub vendor: &'static str,
+    pub name: &'static str,
+    /// Confidence of an exact match; scaled by similarity for near matches
+    pub confidence: f64,
+    pub body: &'static str,
+}
+
+pub const CORPUS: &[KnownPage] = pages![
+    ("Cloudflare", "blocked", 0.95, "cloudflare_blocked.html"),
+    ("Akamai", "access-denied", 0.90, "akamai_access_denied.html"),
+    ("CloudFront", "request-blocked", 0.90, "cloudfront_request_blocked.html"),
+    ("Imperva", "incident", 0.90, "imperva_incident.html"),
+    ("Sucuri", "access-denied", 0.95, "sucuri_access_denied.html"),
+    ("F5 BIG-IP ASM", "rejected", 0.90, "f5_rejected.html"),
+    ("ModSecurity", "not-acceptable", 0.80, "modsecurity_not_acceptable.html"),
+    ("Wordfence", "blocked", 0.90, "wordfence_blocked.html"),
+    ("FortiWeb", "blocked", 0.90, "fortiweb_blocked.html"),
+    ("Azure Front Door", "blocked", 0.85, "azure_front_door_blocked.html"),
+];
+
+/// A block page added at runtime, e.g. from a signature bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomPage {
+    pub vendor: String,
+    pub name: String,
+    pub confidence: f64,
+    pub body: String,
+}
+
+/// A body that resembles a known block page
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockPageMatch {
+    pub vendor: String,
+    pub page: String,
+    /// 1.0 for identical hashes, falling by 1/64 per differing bit
+    pub similarity: f64,
+    pub confidence: f64,
+}
+
+/// Lowercased alphanumeric tokens of markup and text; a run of tokens with digits
+/// (IDs, timestamps, numeric entities) becomes a single `#`
+fn tokens(body: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for token in body.split(|c: char| !c.is_alphanumeric()).filter(|token| !token.is_empty()) {
+        if !token.chars().any(|c| c.is_ascii_digit()) {
+            tokens.push(token.to_lowercase());
+        } else if tokens.last().is_none_or(|last| last != "#") {
+            tokens.push("#".to_string());
+        }
+    }
+    tokens
+}
+
+/// FNV-1a, stable across builds unlike the std hasher
+fn fnv1a(parts: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(b' ')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// 64-bit simhash of a body, `None` if it is too short to compare
+pub fn simhash(body: &str) -> Option<u64> {
+    let tokens = tokens(body);
+    if tokens.len() < MIN_TOKENS {
+        return None;
+    }
+    // Each distinct shingle votes once, so boilerplate repeated down a page cannot
+    // outweigh the rest
+    let shingles: HashSet<u64> = tokens.windows(SHINGLE).map(fnv1a).collect();
+    let mut weights = [0i64; 64];
+    for hash in shingles {
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    Some(weights.iter().enumerate().fold(0u64, |hash, (bit, weight)| if *weight > 0 { hash | 1 << bit } else { hash }))
+}
+
+pub fn similarity(a: u64, b: u64) -> f64 {
+    1.0 - (a ^ b).count_ones() as f64 / 64.0
+}
+
+/// Compares bodies against the block-page corpus
+#[derive(Debug, Clone)]
+pub struct BlockPageClassifier {
+    threshold: f64,
+    custom: Arc<Vec<(CustomPage, u64)>>,
+}
+
+impl BlockPageClassifier {
+    pub fn new() -> Self {
+        Self { threshold: DEFAULT_THRESHOLD, custom: Arc::new(Vec::new()) }
+    }
+
+    /// Also compare with `pages`; pages too short to hash are dropped
+    pub fn with_pages(mut self, pages: Vec<CustomPage>) -> Self {
+        let mut custom = (*self.custom).clone();
+        custom.extend(pages.into_iter().filter_map(|page| {
+            let hash = simhash(&page.body)?;
+            Some((page, hash))
+        }));
+        self.custom = Arc::new(custom);
+        self
+    }
+
+    /// Lowest similarity that counts as a match
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    fn corpus_hashes() -> &'static [(KnownPage, u64)] {
+        static HASHES: OnceLock<Vec<(KnownPage, u64)>> = OnceLock::new();
+        HASHES.get_or_init(|| CORPUS.iter().filter_map(|page| Some((*page, simhash(page.body)?))).collect())
+    }
+
+    /// Most similar known page at or above the threshold
+    pub fn classify(&self, body: &str) -> Option<BlockPageMatch> {
+        let hash = simhash(body)?;
+        let bundled = Self::corpus_hashes().iter().map(|(page, known)| (page.vendor, page.name, page.confidence, *known));
+        let custom = self.custom.iter().map(|(page, known)| (page.vendor.as_str(), page.name.as_str(), page.confidence, *known));
+        bundled
+            .chain(custom)
+            .map(|(vendor, name, confidence, known)| (vendor, name, confidence, similarity(hash, known)))
+            .filter(|(.., similarity)| *similarity >= self.threshold)
+            .max_by(|a, b| a.3.total_cmp(&b.3))
+            .map(|(vendor, name, confidence, similarity)| BlockPageMatch {
+                vendor: vendor.to_string(),
+                page: name.to_string(),
+                similarity,
+                confidence: confidence * similarity,
+            })
+    }
+
+    pub fn analyze(&self, response: &HttpResponse) -> Vec<Evidence> {
+        self.classify(&response.body)
+            .map(|found| Evidence {
+                method_type: MethodType::Body("block-page".to_string()),
+                confidence: found.confidence,
+                description: format!("Body matches the {} {} block page ({:.0}% similar)", found.vendor, found.page, found.similarity * 100.0),
+                raw_data: format!("{:.3}", found.similarity),
+                signature_matched: found.vendor,
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+impl Default for BlockPageClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_pages_match_themselves_and_nothing_else() {
+        let classifier = BlockPageClassifier::new();
+        for page in CORPUS {
+            let found = classifier.classify(page.body).unwrap_or_else(|| panic!("{} did not hash", page.vendor));
+            assert_eq!((found.vendor.as_str(), found.similarity), (page.vendor, 1.0));
+        }
+        for (i, (_, a)) in BlockPageClassifier::corpus_hashes().iter().enumerate() {
+            for (page, b) in &BlockPageClassifier::corpus_hashes()[i + 1..] {
+                assert!(similarity(*a, *b) < DEFAULT_THRESHOLD, "{} collides", page.vendor);
+            }
+        }
+    }
+
+    #[test]
+    fn test_customized_block_page_still_matches() {
+        let page = CORPUS.iter().find(|page| page.vendor == "Cloudflare").unwrap().body
+            .replace("example.com", "shop.example.org")
+            .replace("8a1b2c3d4e5f6a7b", "7f00aa11bb22cc33")
+            .replace("<body>", "<body><img src=\"/logo.png\" alt=\"Example Shop\">");
+        let found = BlockPageClassifier::new().classify(&page).unwrap();
+        assert_eq!(found.vendor, "Cloudflare");
+        assert!(found.similarity < 1.0 && found.confidence > 0.8);
+
+        let reworded = page
+            .replace("Sorry, you have been blocked", "Access to this shop was blocked")
+            .replace("You can email the site owner", "Please contact support@example.org");
+        assert_eq!(BlockPageClassifier::new().classify(&reworded).unwrap().vendor, "Cloudflare");
+    }
+
+    #[test]
+    fn test_ordinary_pages_do_not_match() {
+        let classifier = BlockPageClassifier::new();
+        let page = "<!DOCTYPE html><html><head><title>Example Shop</title></head><body><h1>Welcome to our shop</h1>\
+            <p>Browse the latest products, read our blog and sign up for the newsletter to get weekly offers.</p></body></html>";
+        assert_eq!(classifier.classify(page), None);
+        assert_eq!(classifier.classify("Forbidden"), None);
+    }
+
+    #[test]
+    fn test_custom_pages() {
+        let body = "<html><head><title>Request rejected</title></head><body><h1>Acme Shield</h1>\
+            <p>Your request was rejected by the Acme Shield web application firewall. Reference #123456.</p></body></html>";
+        assert_eq!(BlockPageClassifier::new().classify(body), None);
+        let page = CustomPage { vendor: "Acme Shield".to_string(), name: "rejected".to_string(), confidence: 0.9, body: body.to_string() };
+        let classifier = BlockPageClassifier::new().with_pages(vec![page]);
+        let found = classifier.classify(&body.replace("123456", "987654")).unwrap();
+        assert_eq!((found.vendor.as_str(), found.page.as_str(), found.similarity), ("Acme Shield", "rejected", 1.0));
+    }
+}
@@ -0,0 +1,133 @@
+//! Headless browser fallback for JS challenges (`--browser-fallback`)
+//!
+//! A challenge interstitial (Cloudflare "Just a moment...", Akamai/DataDome/PerimeterX
+//! captcha pages) hides the page and most of the stack behind it. With the `browser`
+//! feature the engine loads such a page in headless Chrome, waits until the challenge
+//! clears, and detects on the document the browser ends up with. Cookies the browser
+//! holds afterwards (`cf_clearance`, `__cf_bm`, `datadome`, ...) are presented to the
+//! providers as `Set-Cookie` headers. Browser requests bypass [`HttpClient`], so they
+//! are not rate limited or counted.
+//!
+//! [`HttpClient`]: crate::http::HttpClient
+
+use crate::http::HttpResponse;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long the browser may take to load the page and clear the challenge
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The page as the headless browser saw it
+#[derive(Debug, Clone)]
+pub struct BrowserFetch {
+    /// Main document, with the browser's cookies as `set-cookie` headers
+    pub response: HttpResponse,
+    /// The challenge cleared before the timeout
+    pub solved: bool,
+    pub cookies: Vec<String>,
+}
+
+/// What the fallback did, recorded on the result
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BrowserFallback {
+    /// Detection ran on the page behind the challenge
+    pub solved: bool,
+    /// Status of the document the browser ended on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Names of the cookies the browser held afterwards
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cookies: Vec<String>,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BrowserFallback {
+    pub fn from_fetch(fetch: &BrowserFetch, duration: Duration) -> Self {
+        Self {
+            solved: fetch.solved,
+            status: Some(fetch.response.status),
+            cookies: fetch.cookies.clone(),
+            duration_ms: duration.as_millis() as u64,
+            error: None,
+        }
+    }
+
+    pub fn failed(error: &anyhow::Error, duration: Duration) -> Self {
+        Self { duration_ms: duration.as_millis() as u64, error: Some(error.to_string()), ..Self::default() }
+    }
+}
+
+/// Load `url` in headless Chrome and wait up to `timeout` for a challenge to clear
+#[cfg(feature = "browser")]
+pub async fn fetch_past_challenge(url: &str, timeout: Duration) -> Result<BrowserFetch> {
+    use anyhow::anyhow;
+    use chromiumoxide::browser::{Browser, BrowserConfig};
+    use chromiumoxide::cdp::browser_protocol::network::{EventResponseReceived, ResourceType};
+    use futures::{FutureExt, StreamExt};
+
+    let config = BrowserConfig::builder().request_timeout(timeout).build().map_err(|e| anyhow!("browser config: {}", e))?;
+    let (mut browser, mut handler) = Browser::launch(config).await?;
+    let events = tokio::spawn(async move { while let Some(Ok(_)) = handler.next().await {} });
+
+    let outcome = async {
+        let page = browser.new_page("about:blank").await?;
+        if let Some(profile) = crate::profiles::select() {
+            page.set_user_agent(profile.user_agent()).await?;
+        }
+        let mut responses = page.event_listener::<EventResponseReceived>().await?;
+        page.goto(url).await?;
+
+        // The challenge page reloads or redirects to the real document once solved
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut document: Option<(u16, Vec<(String, String)>)> = None;
+        loop {
+            while let Some(Some(event)) = responses.next().now_or_never() {
+                if event.r#type == ResourceType::Document {
+                    let headers = event
+                        .response
+                        .headers
+                        .inner()
+                        .as_object()
+                        .map(|object| {
+                            object
+                                .iter()
+                                .flat_map(|(name, value)| {
+                                    // CDP joins repeated headers with newlines
+                                    value.as_str().unwrap_or_default().lines().map(|line| (name.clone(), line.to_string())).collect::<Vec<_>>()
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    document = Some((event.response.status as u16, headers));
+                }
+            }
+            let (status, headers) = document.clone().unwrap_or_default();
+            let body = page.content().await?;
+            let current = HttpResponse::from_parts(status, headers, body, url);
+            let solved = crate::challenge::detect_challenge(&current).is_none_or(|challenge| !challenge.interstitial);
+            if solved || tokio::time::Instant::now() >= deadline {
+                let cookies = page.get_cookies().await?;
+                let mut response = current;
+                for cookie in &cookies {
+                    response.headers.append("set-cookie", format!("{}={}", cookie.name, cookie.value));
+                }
+                let cookies = cookies.into_iter().map(|cookie| cookie.name).collect();
+                return Ok::<_, anyhow::Error>(BrowserFetch { response, solved, cookies });
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+    .await;
+
+    let _ = browser.close().await;
+    events.abort();
+    outcome
+}
+
+#[cfg(not(feature = "browser"))]
+pub async fn fetch_past_challenge(_url: &str, _timeout: Duration) -> Result<BrowserFetch> {
+    Err(anyhow::anyhow!("built without the `browser` feature"))
+}
@@ -0,0 +1,335 @@
+//! Cache-behavior fingerprints
+//!
+//! CDNs that strip their vendor headers still cache the way their software does. The
+//! analyzer requests the page under a fresh cache-busting query string twice, once
+//! more under another one, then with `If-None-Match`, with `Range` and with the debug
+//! request headers Akamai and Fastly answer. What changes between those responses
+//! (`Age`, `X-Cache`, `X-Cache-Hits`, `ETag`) tells the caching layers apart: Varnish
+//! and Fastly send `Age: 0` on a miss where CloudFront and Cloudflare send no `Age`,
+//! and CloudFront's legacy cache settings leave the query string out of the cache key.
+
+use crate::http::{HttpClient, HttpResponse};
+use crate::{Evidence, MethodType};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use url::Url;
+
+/// Query parameter that gives each probe its own cache key
+const BUST_PARAMETER: &str = "wafd_cache";
+
+/// Request headers that make Akamai and Fastly describe their cache in the response
+const DEBUG_HEADERS: &[(&str, &str)] = &[
+    ("Pragma", "akamai-x-cache-on, akamai-x-get-cache-key, akamai-x-check-cacheable"),
+    ("Fastly-Debug", "1"),
+];
+
+/// Hit or miss, as a cache reported it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheState {
+    Hit,
+    Miss,
+    /// Passed to the origin without a lookup (`BYPASS`, `DYNAMIC`, `PASS`)
+    Pass,
+}
+
+impl CacheState {
+    /// State in `X-Cache` (`Hit from cloudfront`, `TCP_MISS`, `MISS, HIT`),
+    /// `CF-Cache-Status` or `X-Cache-Status`; the last cache in a chain is the one nearest the client
+    pub fn from_response(response: &HttpResponse) -> Option<Self> {
+        let value = ["x-cache", "cf-cache-status", "x-cache-status", "x-proxy-cache"]
+            .iter()
+            .find_map(|name| response.headers.get(name))?
+            .to_ascii_uppercase();
+        let last = value.rsplit(',').next().unwrap_or_default();
+        if last.contains("HIT") {
+            Some(CacheState::Hit)
+        } else if last.contains("MISS") || last.contains("EXPIRED") {
+            Some(CacheState::Miss)
+        } else if ["BYPASS", "DYNAMIC", "PASS", "NOCACHE"].iter().any(|state| last.contains(state)) {
+            Some(CacheState::Pass)
+        } else {
+            None
+        }
+    }
+}
+
+/// What one response showed about the cache in front of the target
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheObservation {
+    pub status: u16,
+    pub age: Option<u64>,
+    pub state: Option<CacheState>,
+    /// `X-Cache-Hits`, per cache in the chain (Fastly)
+    pub hits: Option<String>,
+    /// `X-Varnish`: one transaction id on a miss, two on a hit
+    pub varnish_ids: usize,
+    pub etag: Option<String>,
+    /// Names of cache debug headers in the response
+    pub debug_headers: Vec<String>,
+}
+
+impl CacheObservation {
+    pub fn from_response(response: &HttpResponse) -> Self {
+        let debug_headers = ["x-cache-key", "x-check-cacheable", "x-true-cache-key", "fastly-debug-path", "fastly-debug-ttl"]
+            .iter()
+            .filter(|name| response.headers.contains_key(name))
+            .map(|name| name.to_string())
+            .collect();
+        Self {
+            status: response.status,
+            age: response.headers.get("age").and_then(|age| age.trim().parse().ok()),
+            state: CacheState::from_response(response),
+            hits: response.headers.get("x-cache-hits").cloned(),
+            varnish_ids: response.headers.get("x-varnish").map_or(0, |ids| ids.split_whitespace().count()),
+            etag: response.headers.get("etag").cloned(),
+            debug_headers,
+        }
+    }
+
+    /// Served from cache, by the reported state or by a non-zero `Age`
+    pub fn is_hit(&self) -> bool {
+        match self.state {
+            Some(state) => state == CacheState::Hit,
+            None => self.age.is_some_and(|age| age > 0) || self.varnish_ids > 1,
+        }
+    }
+}
+
+/// Responses to the cache probes of one target
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheProfile {
+    /// First request under a fresh cache key
+    pub first: CacheObservation,
+    /// The same URL again
+    pub repeat: CacheObservation,
+    /// First request under a second fresh cache key
+    pub other_key: CacheObservation,
+    /// Status of a request with `If-None-Match` set to the first ETag, if there was one
+    pub conditional_status: Option<u16>,
+    /// Status of a `Range: bytes=0-0` request
+    pub range_status: Option<u16>,
+    /// Response to the debug request headers
+    pub debug: CacheObservation,
+}
+
+impl CacheProfile {
+    /// Whether a cache served the repeat request
+    pub fn caches(&self) -> bool {
+        !self.first.is_hit() && self.repeat.is_hit()
+    }
+
+    /// A fresh query string was still a hit: the cache key leaves the query out
+    pub fn ignores_query(&self) -> bool {
+        self.caches() && self.other_key.is_hit()
+    }
+
+    /// Cache states and ages in request order, for correlating targets
+    pub fn fingerprint(&self) -> String {
+        let describe = |observation: &CacheObservation| {
+            let state = match (observation.state, observation.is_hit()) {
+                (Some(CacheState::Pass), _) => "pass",
+                (_, true) => "hit",
+                (Some(_), false) => "miss",
+                (None, false) => "-",
+            };
+            match observation.age {
+                Some(age) => format!("{}/age={}", state, age),
+                None => state.to_string(),
+            }
+        };
+        let status = |status: Option<u16>| status.map_or("-".to_string(), |status| status.to_string());
+        format!(
+            "first={} repeat={} other={} if-none-match={} range={}",
+            describe(&self.first),
+            describe(&self.repeat),
+            describe(&self.other_key),
+            status(self.conditional_status),
+            status(self.range_status)
+        )
+    }
+
+    pub fn to_evidence(&self) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+        let mut push = |signal: &str, confidence: f64, description: &str, raw_data: String, signature: &str| {
+            evidence.push(Evidence {
+                method_type: MethodType::Cache(signal.to_string()),
+                confidence,
+                description: description.to_string(),
+                raw_data,
+                signature_matched: signature.to_string(),
+            });
+        };
+
+        let debug = &self.debug.debug_headers;
+        if debug.iter().any(|name| name.starts_with("fastly-debug")) {
+            push("debug", 0.9, "Fastly-Debug request header answered with Fastly's debug headers", debug.join(","), "Fastly");
+        }
+        if debug.iter().any(|name| !name.starts_with("fastly-debug")) {
+            push("debug", 0.9, "Akamai Pragma debug headers answered with the cache key", debug.join(","), "Akamai");
+        }
+
+        if !self.caches() {
+            return evidence;
+        }
+        push("behavior", 0.1, "Repeated request served from an edge cache", self.fingerprint(), "edge-cache");
+        if self.first.age == Some(0) && self.repeat.age.is_some() {
+            push("age", 0.4, "Age: 0 sent on a cache miss, as Varnish-based caches (Fastly) do", self.fingerprint(), "Fastly");
+        }
+        if self.first.age.is_none() && self.repeat.age.is_some() {
+            push("age", 0.2, "Age sent only on cache hits, as CloudFront and Cloudflare do", self.fingerprint(), "age-on-hit-only");
+        }
+        if self.repeat.varnish_ids == 2 {
+            push("varnish", 0.5, "X-Varnish carries two transaction ids on the hit", self.fingerprint(), "Varnish");
+        }
+        if self.first.hits.is_some() && self.first.hits != self.repeat.hits {
+            let hits = format!("{} -> {}", self.first.hits.as_deref().unwrap_or_default(), self.repeat.hits.as_deref().unwrap_or_default());
+            push("hits", 0.6, "X-Cache-Hits counts up between requests, as Fastly's does", hits, "Fastly");
+        }
+        if self.ignores_query() {
+            push("query", 0.4, "A new query string was still a cache hit: the cache key leaves out the query, as CloudFront's legacy cache settings do", self.fingerprint(), "CloudFront");
+        }
+        evidence
+    }
+}
+
+/// Fingerprints the caching layers in front of a target
+#[derive(Debug, Clone)]
+pub struct CacheAnalyzer {
+    http_client: Arc<HttpClient>,
+}
+
+impl CacheAnalyzer {
+    pub fn new() -> Self {
+        Self { http_client: Arc::new(HttpClient::default()) }
+    }
+
+    /// Send the cache probes through `client` (timeout, proxy, rate limit)
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.http_client = Arc::new(client);
+        self
+    }
+
+    pub async fn analyze(&self, url: &str) -> Result<Vec<Evidence>> {
+        Ok(self.profile(url).await?.to_evidence())
+    }
+
+    /// Six requests: two under one fresh cache key, one under another, a conditional,
+    /// a range and a debug request
+    pub async fn profile(&self, url: &str) -> Result<CacheProfile> {
+        let busted = bust(url)?;
+        let first = self.http_client.get(&busted).await?;
+        let repeat = self.http_client.get(&busted).await?;
+        let other_key = self.http_client.get(&bust(url)?).await?;
+
+        let first = CacheObservation::from_response(&first);
+        let conditional_status = match &first.etag {
+            Some(etag) => Some(self.http_client.get_with_headers(&busted, &[("If-None-Match", etag)]).await?.status),
+            None => None,
+        };
+        let range_status = self.http_client.get_with_headers(&busted, &[("Range", "bytes=0-0")]).await?.status;
+        let debug = self.http_client.get_with_headers(&busted, DEBUG_HEADERS).await?;
+
+        Ok(CacheProfile {
+            first,
+            repeat: CacheObservation::from_response(&repeat),
+            other_key: CacheObservation::from_response(&other_key),
+            conditional_status,
+            range_status: Some(range_status),
+            debug: CacheObservation::from_response(&debug),
+        })
+    }
+}
+
+impl Default for CacheAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `url` with a cache-busting parameter no cache has seen
+fn bust(url: &str) -> Result<String> {
+    let mut url = Url::parse(url)?;
+    let token = format!("{:016x}", RandomState::new().build_hasher().finish());
+    url.query_pairs_mut().append_pair(BUST_PARAMETER, &token);
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(headers: &[(&str, &str)]) -> HttpResponse {
+        HttpResponse::from_parts(200, headers.iter().copied(), "<html></html>", "https://example.com/")
+    }
+
+    fn signatures(evidence: &[Evidence]) -> Vec<&str> {
+        evidence.iter().map(|e| e.signature_matched.as_str()).collect()
+    }
+
+    #[test]
+    fn test_cache_state() {
+        let state = |headers: &[(&str, &str)]| CacheState::from_response(&response(headers));
+        assert_eq!(state(&[("x-cache", "Hit from cloudfront")]), Some(CacheState::Hit));
+        assert_eq!(state(&[("x-cache", "MISS, HIT")]), Some(CacheState::Hit));
+        assert_eq!(state(&[("x-cache", "HIT, MISS")]), Some(CacheState::Miss));
+        assert_eq!(state(&[("cf-cache-status", "DYNAMIC")]), Some(CacheState::Pass));
+        assert_eq!(state(&[("x-cache", "TCP_MISS from a23-1-2-3.deploy.akamaitechnologies.com")]), Some(CacheState::Miss));
+        assert_eq!(state(&[]), None);
+    }
+
+    #[test]
+    fn test_stripped_fastly_and_cloudfront_differ() {
+        // Vendor headers stripped, only Age left
+        let fastly = CacheProfile {
+            first: CacheObservation::from_response(&response(&[("age", "0")])),
+            repeat: CacheObservation::from_response(&response(&[("age", "3")])),
+            other_key: CacheObservation::from_response(&response(&[("age", "0")])),
+            ..CacheProfile::default()
+        };
+        assert_eq!(signatures(&fastly.to_evidence()), ["edge-cache", "Fastly"]);
+
+        let cloudfront = CacheProfile {
+            first: CacheObservation::from_response(&response(&[])),
+            repeat: CacheObservation::from_response(&response(&[("age", "3")])),
+            other_key: CacheObservation::from_response(&response(&[("age", "9")])),
+            ..CacheProfile::default()
+        };
+        assert!(cloudfront.ignores_query());
+        assert_eq!(signatures(&cloudfront.to_evidence()), ["edge-cache", "age-on-hit-only", "CloudFront"]);
+    }
+
+    #[test]
+    fn test_uncached_and_debug_responses() {
+        let dynamic = CacheObservation::from_response(&response(&[("cf-cache-status", "DYNAMIC")]));
+        let profile = CacheProfile {
+            first: dynamic.clone(),
+            repeat: dynamic.clone(),
+            other_key: dynamic,
+            debug: CacheObservation::from_response(&response(&[("x-cache-key", "S/L/1234/567/1d/www.example.com/"), ("x-check-cacheable", "NO")])),
+            ..CacheProfile::default()
+        };
+        assert!(!profile.caches());
+        assert_eq!(signatures(&profile.to_evidence()), ["Akamai"]);
+    }
+
+    #[tokio::test]
+    async fn test_profile_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let busted = mockito::Matcher::Regex(format!("{}=[0-9a-f]{{16}}", BUST_PARAMETER));
+        let _debug = server.mock("GET", "/").match_query(busted.clone()).match_header("fastly-debug", "1")
+            .with_header("fastly-debug-path", "(D cache-fra-1 1700000000) (F cache-fra-2 1700000000)").create_async().await;
+        let _conditional = server.mock("GET", "/").match_query(busted.clone()).match_header("if-none-match", "\"v1\"").with_status(304).create_async().await;
+        let _range = server.mock("GET", "/").match_query(busted.clone()).match_header("range", "bytes=0-0").with_status(206).create_async().await;
+        let _page = server.mock("GET", "/").match_query(busted).with_header("etag", "\"v1\"").with_header("x-cache", "MISS").create_async().await;
+
+        let profile = CacheAnalyzer::new().profile(&server.url()).await.unwrap();
+        assert_eq!((profile.conditional_status, profile.range_status), (Some(304), Some(206)));
+        assert_eq!(profile.first.state, Some(CacheState::Miss));
+        assert!(!profile.caches());
+        assert_eq!(signatures(&profile.to_evidence()), ["Fastly"]);
+    }
+}
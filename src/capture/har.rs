@@ -0,0 +1,137 @@
+//! HTTP Archive (HAR 1.2) parsing
+//!
+//! Only what passive detection needs is read: request URL, response status, headers
+//! and text body. Base64-encoded bodies (images, fonts) are left empty.
+
+use super::CapturedResponse;
+use crate::http::HttpResponse;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Deserialize)]
+struct Har {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+struct HarEntry {
+    request: HarRequest,
+    response: HarResponse,
+}
+
+#[derive(Deserialize)]
+struct HarRequest {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct HarResponse {
+    /// 0 (or -1) for requests that never got a response
+    status: i64,
+    #[serde(default)]
+    headers: Vec<HarHeader>,
+    #[serde(default)]
+    content: Option<HarContent>,
+}
+
+#[derive(Deserialize)]
+struct HarHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct HarContent {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    encoding: Option<String>,
+}
+
+/// Captured responses of a HAR document, plus the number of entries without a usable response
+pub fn parse_har(content: &str) -> Result<(Vec<CapturedResponse>, usize)> {
+    let har: Har = serde_json::from_str(content).map_err(|e| anyhow!("Invalid HAR file: {}", e))?;
+    let mut captured = Vec::new();
+    let mut skipped = 0;
+
+    for entry in har.log.entries {
+        let Ok(status) = u16::try_from(entry.response.status) else {
+            skipped += 1;
+            continue;
+        };
+        if !(100..=999).contains(&status) {
+            skipped += 1;
+            continue;
+        }
+
+        // HTTP/2 captures list pseudo-headers (":status") alongside the real ones
+        let headers = entry.response.headers
+            .into_iter()
+            .filter(|header| !header.name.starts_with(':'))
+            .map(|header| (header.name, header.value));
+        let body = entry.response.content
+            .filter(|content| content.encoding.is_none())
+            .and_then(|content| content.text)
+            .unwrap_or_default();
+
+        captured.push(CapturedResponse {
+            response: HttpResponse::from_parts(status, headers, body, entry.request.url.clone()),
+            url: entry.request.url,
+        });
+    }
+
+    Ok((captured, skipped))
+}
+
+pub fn load_har(path: impl AsRef<Path>) -> Result<(Vec<CapturedResponse>, usize)> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    parse_har(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAR: &str = r#"{"log": {"version": "1.2", "entries": [
+        {"request": {"method": "GET", "url": "https://www.example.com/"},
+         "response": {"status": 200, "headers": [
+             {"name": ":status", "value": "200"},
+             {"name": "Server", "value": "cloudflare"},
+             {"name": "Set-Cookie", "value": "__cf_bm=abc"},
+             {"name": "Set-Cookie", "value": "lang=en"}],
+          "content": {"mimeType": "text/html", "text": "<html>Welcome</html>"}}},
+        {"request": {"method": "GET", "url": "https://cdn.example.com/logo.png"},
+         "response": {"status": 200, "headers": [], "content": {"mimeType": "image/png", "text": "iVBORw0KGgo=", "encoding": "base64"}}},
+        {"request": {"method": "GET", "url": "https://ads.example.net/pixel"},
+         "response": {"status": 0, "headers": [], "content": {}}}
+    ]}}"#;
+
+    #[test]
+    fn test_parse_har_entries() {
+        let (captured, skipped) = parse_har(HAR).unwrap();
+
+        assert_eq!(skipped, 1);
+        assert_eq!(captured.len(), 2);
+        let page = &captured[0].response;
+        assert_eq!(page.status, 200);
+        assert_eq!(page.headers["server"], "cloudflare");
+        assert_eq!(page.headers.get_all("set-cookie").collect::<Vec<_>>(), ["__cf_bm=abc", "lang=en"]);
+        assert!(!page.headers.contains_key(":status"));
+        assert_eq!(page.body, "<html>Welcome</html>");
+        assert_eq!(captured[1].response.body, "");
+    }
+
+    #[test]
+    fn test_invalid_har_is_rejected() {
+        assert!(parse_har("{\"entries\": []}").is_err());
+    }
+}
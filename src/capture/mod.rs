@@ -0,0 +1,240 @@
+//! Offline analysis of captured traffic
+//!
+//! Responses recorded elsewhere (HAR files from browser devtools or intercepting
+//! proxies) are run through passive detection one by one and the results are
+//! aggregated per host, so a single page load reveals the protection of every origin
+//! it touched. Nothing is sent over the network.
+//!
+//! With the `pcap` feature, packet captures are accepted too: plaintext HTTP
+//! responses are reassembled from TCP streams and TLS handshakes contribute the
+//! hosts (SNI) and protocol metadata seen on encrypted connections.
+
+pub mod har;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+
+use crate::engine::DetectionEngine;
+use crate::http::HttpResponse;
+use crate::{DetectionResult, ProviderDetection};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// One captured response and the URL it was requested from
+#[derive(Debug, Clone)]
+pub struct CapturedResponse {
+    pub url: String,
+    pub response: HttpResponse,
+}
+
+/// Contents of a capture file
+#[derive(Debug, Clone, Default)]
+pub struct Capture {
+    pub responses: Vec<CapturedResponse>,
+    /// Entries or streams without a usable response
+    pub skipped: usize,
+    /// Encrypted connections, known only from their TLS handshake
+    pub tls: Vec<TlsHost>,
+}
+
+/// TLS handshake metadata for one server name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TlsHost {
+    /// Server name from the ClientHello, or the server address when no SNI was sent
+    pub host: String,
+    /// Server addresses (`ip:port`) the name was seen on
+    pub addresses: Vec<String>,
+    pub connections: usize,
+    /// Negotiated protocol versions ("TLS 1.3")
+    pub versions: Vec<String>,
+    /// Application protocols selected by the server ("h2")
+    pub alpn: Vec<String>,
+}
+
+/// A provider seen in a host's responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostProvider {
+    pub name: String,
+    /// Highest confidence over the host's responses
+    pub confidence: f64,
+    /// Responses this provider had evidence in
+    pub responses: usize,
+    /// Distinct signatures matched across those responses
+    pub signatures: Vec<String>,
+}
+
+/// Aggregated detection for one host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostReport {
+    pub host: String,
+    pub responses: usize,
+    pub waf: Option<ProviderDetection>,
+    pub cdn: Option<ProviderDetection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_manager: Option<ProviderDetection>,
+    /// Every provider with evidence, most confident first
+    pub providers: Vec<HostProvider>,
+}
+
+/// Per-host report for a capture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficReport {
+    pub source: String,
+    /// Responses analyzed
+    pub responses: usize,
+    /// Entries without a usable response (aborted requests, invalid URLs)
+    pub skipped: usize,
+    /// Hosts in alphabetical order
+    pub hosts: Vec<HostReport>,
+    /// Encrypted connections seen in a packet capture (handshake metadata only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tls: Vec<TlsHost>,
+}
+
+/// Load a HAR file or, with the `pcap` feature, a libpcap capture (detected by content)
+pub fn load_capture(path: impl AsRef<Path>) -> Result<Capture> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).map_err(|e| anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    if is_packet_capture(&data) {
+        return load_packet_capture(path, &data);
+    }
+
+    let content = String::from_utf8(data).map_err(|_| anyhow!("{}: not a HAR or pcap file", path.display()))?;
+    let (responses, skipped) = har::parse_har(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    Ok(Capture { responses, skipped, tls: Vec::new() })
+}
+
+/// libpcap (either byte order, micro- or nanosecond) and pcapng magic numbers
+fn is_packet_capture(data: &[u8]) -> bool {
+    matches!(data.get(..4), Some([0xd4, 0xc3, 0xb2, 0xa1] | [0xa1, 0xb2, 0xc3, 0xd4] | [0x4d, 0x3c, 0xb2, 0xa1] | [0xa1, 0xb2, 0x3c, 0x4d] | [0x0a, 0x0d, 0x0d, 0x0a]))
+}
+
+#[cfg(feature = "pcap")]
+fn load_packet_capture(path: &Path, data: &[u8]) -> Result<Capture> {
+    pcap::parse_pcap(data).map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+#[cfg(not(feature = "pcap"))]
+fn load_packet_capture(path: &Path, _data: &[u8]) -> Result<Capture> {
+    Err(anyhow!("{}: packet captures need the `pcap` feature (cargo build --features pcap)", path.display()))
+}
+
+/// A single raw HTTP response as an intercepting proxy shows it: status line, headers,
+/// a blank line and the body. Bare `\n` line endings are accepted; compressed bodies
+/// are dropped since they are useless to signature matching.
+pub fn parse_raw_response(data: &[u8], url: &str) -> Result<HttpResponse> {
+    let (head, body) = match data.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(end) => (&data[..end], &data[end + 4..]),
+        None => match data.windows(2).position(|w| w == b"\n\n") {
+            Some(end) => (&data[..end], &data[end + 2..]),
+            None => (data, &[][..]),
+        },
+    };
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.lines();
+    let start_line = lines.next().unwrap_or_default();
+    let status = start_line
+        .strip_prefix("HTTP/")
+        .and_then(|rest| rest.split_whitespace().nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("not an HTTP response: '{}'", start_line.chars().take(40).collect::<String>()))?;
+    let headers: Vec<(&str, &str)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect();
+    let compressed = headers
+        .iter()
+        .any(|(name, value)| name.eq_ignore_ascii_case("content-encoding") && !value.eq_ignore_ascii_case("identity"));
+    let body = if compressed { String::new() } else { String::from_utf8_lossy(body).into_owned() };
+    Ok(HttpResponse::from_parts(status, headers, body, url))
+}
+
+/// Run passive detection on every captured response and aggregate the results per host
+pub async fn analyze_captured(engine: &DetectionEngine, source: &str, captured: Vec<CapturedResponse>, skipped: usize) -> Result<TrafficReport> {
+    let mut by_host: BTreeMap<String, Vec<DetectionResult>> = BTreeMap::new();
+    let mut skipped = skipped;
+    let mut responses = 0;
+
+    for entry in captured {
+        let Some(host) = url::Url::parse(&entry.url).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase())) else {
+            skipped += 1;
+            continue;
+        };
+        let result = engine.detect_from_response(&entry.url, entry.response).await?;
+        by_host.entry(host).or_default().push(result);
+        responses += 1;
+    }
+
+    Ok(TrafficReport {
+        source: source.to_string(),
+        responses,
+        skipped,
+        hosts: by_host.into_iter().map(|(host, results)| aggregate_host(host, &results)).collect(),
+        tls: Vec::new(),
+    })
+}
+
+fn aggregate_host(host: String, results: &[DetectionResult]) -> HostReport {
+    let mut providers: HashMap<&str, (f64, usize, BTreeSet<&str>)> = HashMap::new();
+    for result in results {
+        for (name, evidence) in &result.evidence_map {
+            let score = result.provider_scores.get(name).copied().unwrap_or(0.0);
+            if evidence.is_empty() || score <= 0.0 {
+                continue;
+            }
+            let entry = providers.entry(name.as_str()).or_insert((0.0, 0, BTreeSet::new()));
+            entry.0 = entry.0.max(score);
+            entry.1 += 1;
+            entry.2.extend(evidence.iter().map(|e| e.signature_matched.as_str()));
+        }
+    }
+
+    let mut providers: Vec<HostProvider> = providers
+        .into_iter()
+        .map(|(name, (confidence, responses, signatures))| HostProvider {
+            name: name.to_string(),
+            confidence,
+            responses,
+            signatures: signatures.into_iter().map(|s| s.to_string()).collect(),
+        })
+        .collect();
+    providers.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal).then(a.name.cmp(&b.name)));
+
+    HostReport {
+        host,
+        responses: results.len(),
+        waf: best(results.iter().filter_map(|r| r.detected_waf.as_ref())),
+        cdn: best(results.iter().filter_map(|r| r.detected_cdn.as_ref())),
+        bot_manager: best(results.iter().filter_map(|r| r.detected_bot_manager.as_ref())),
+        providers,
+    }
+}
+
+/// Most confident detection over a host's responses
+fn best<'a>(detections: impl Iterator<Item = &'a ProviderDetection>) -> Option<ProviderDetection> {
+    detections
+        .max_by(|a, b| a.confidence.partial_cmp(&b.confidence).unwrap_or(std::cmp::Ordering::Equal))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_raw_response() {
+        let raw = b"HTTP/2 403 Forbidden\r\nServer: cloudflare\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\r\n<title>Attention Required!</title>";
+        let response = parse_raw_response(raw, "https://example.com/").unwrap();
+        assert_eq!(response.status, 403);
+        assert_eq!(response.headers.get_all("set-cookie").count(), 2);
+        assert_eq!(response.body, "<title>Attention Required!</title>");
+        assert_eq!(response.url, "https://example.com/");
+
+        let bare = parse_raw_response(b"HTTP/1.1 200 OK\nContent-Encoding: gzip\n\n\x1f\x8b", "https://example.com/").unwrap();
+        assert_eq!(bare.status, 200);
+        assert!(bare.body.is_empty());
+
+        assert!(parse_raw_response(b"GET / HTTP/1.1\r\n\r\n", "https://example.com/").is_err());
+    }
+}
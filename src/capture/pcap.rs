@@ -0,0 +1,651 @@
+//! libpcap capture parsing (`pcap` feature)
+//!
+//! TCP streams are reassembled per direction. Plaintext HTTP/1.x responses are paired
+//! with the requests on the opposite direction to recover their URL; for TLS only the
+//! handshake is readable (SNI, negotiated version and ALPN). IP fragments and gaps in
+//! a stream end parsing of that stream. pcapng must be converted first
+//! (`editcap -F pcap in.pcapng out.pcap`).
+
+use super::{Capture, CapturedResponse, TlsHost};
+use crate::http::HttpResponse;
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+/// DLT_RAW as written by some BSDs instead of LINKTYPE_RAW
+const DLT_RAW: [u32; 2] = [12, 14];
+
+const FILE_HEADER_LEN: usize = 24;
+const RECORD_HEADER_LEN: usize = 16;
+
+const TCP_SYN: u8 = 0x02;
+
+/// Parse a libpcap capture into HTTP responses and TLS handshake metadata
+pub fn parse_pcap(data: &[u8]) -> Result<Capture> {
+    let header = data.get(..FILE_HEADER_LEN).ok_or_else(|| anyhow!("Truncated pcap header"))?;
+    let big_endian = match header[..4] {
+        [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => false,
+        [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => true,
+        [0x0a, 0x0d, 0x0d, 0x0a] => return Err(anyhow!("pcapng is not supported, convert with `editcap -F pcap`")),
+        _ => return Err(anyhow!("Not a pcap file")),
+    };
+    let read_u32 = |bytes: &[u8]| {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) }
+    };
+    let linktype = read_u32(&header[20..24]) & 0x0fff_ffff;
+
+    let mut streams = Streams::default();
+    let mut offset = FILE_HEADER_LEN;
+    while let Some(record) = data.get(offset..offset + RECORD_HEADER_LEN) {
+        let captured_len = read_u32(&record[8..12]) as usize;
+        let start = offset + RECORD_HEADER_LEN;
+        // A capture cut off mid-record still yields the packets before it
+        let Some(packet) = data.get(start..start + captured_len) else { break };
+        if let Some(segment) = link_payload(linktype, packet).and_then(ip_payload).and_then(|(src, dst, tcp)| tcp_segment(src, dst, tcp)) {
+            streams.add(segment);
+        }
+        offset = start + captured_len;
+    }
+
+    Ok(streams.into_capture())
+}
+
+/// Strip the link-layer header, returning the IP packet
+fn link_payload(linktype: u32, packet: &[u8]) -> Option<&[u8]> {
+    match linktype {
+        LINKTYPE_ETHERNET => {
+            let mut ethertype = u16::from_be_bytes([*packet.get(12)?, *packet.get(13)?]);
+            let mut offset = 14;
+            // 802.1Q / 802.1ad VLAN tags
+            while ethertype == 0x8100 || ethertype == 0x88a8 {
+                ethertype = u16::from_be_bytes([*packet.get(offset + 2)?, *packet.get(offset + 3)?]);
+                offset += 4;
+            }
+            matches!(ethertype, 0x0800 | 0x86dd).then(|| packet.get(offset..)).flatten()
+        }
+        LINKTYPE_NULL => packet.get(4..),
+        LINKTYPE_LINUX_SLL => packet.get(16..),
+        LINKTYPE_LINUX_SLL2 => packet.get(20..),
+        LINKTYPE_RAW => Some(packet),
+        other if DLT_RAW.contains(&other) => Some(packet),
+        _ => None,
+    }
+}
+
+/// Source, destination and TCP payload of an unfragmented IPv4/IPv6 packet
+fn ip_payload(packet: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    match packet.first()? >> 4 {
+        4 => {
+            let header_len = ((packet[0] & 0x0f) as usize) * 4;
+            let total_len = u16::from_be_bytes([*packet.get(2)?, *packet.get(3)?]) as usize;
+            let fragment = u16::from_be_bytes([*packet.get(6)?, *packet.get(7)?]);
+            // More-fragments flag or a non-zero offset
+            if *packet.get(9)? != 6 || fragment & 0x3fff != 0 {
+                return None;
+            }
+            let src: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let dst: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            // Ethernet pads short frames, so trust the IP length over the capture length
+            let end = total_len.min(packet.len());
+            Some((Ipv4Addr::from(src).into(), Ipv4Addr::from(dst).into(), packet.get(header_len..end)?))
+        }
+        6 => {
+            let payload_len = u16::from_be_bytes([*packet.get(4)?, *packet.get(5)?]) as usize;
+            // Extension headers are not followed
+            if *packet.get(6)? != 6 {
+                return None;
+            }
+            let src: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let dst: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            let end = (40 + payload_len).min(packet.len());
+            Some((Ipv6Addr::from(src).into(), Ipv6Addr::from(dst).into(), packet.get(40..end)?))
+        }
+        _ => None,
+    }
+}
+
+struct Segment {
+    src: SocketAddr,
+    dst: SocketAddr,
+    seq: u32,
+    syn: bool,
+    payload: Vec<u8>,
+}
+
+fn tcp_segment(src: IpAddr, dst: IpAddr, tcp: &[u8]) -> Option<Segment> {
+    let src_port = u16::from_be_bytes([*tcp.first()?, *tcp.get(1)?]);
+    let dst_port = u16::from_be_bytes([*tcp.get(2)?, *tcp.get(3)?]);
+    let seq = u32::from_be_bytes(tcp.get(4..8)?.try_into().ok()?);
+    let data_offset = ((*tcp.get(12)? >> 4) as usize) * 4;
+    let flags = *tcp.get(13)?;
+    Some(Segment {
+        src: SocketAddr::new(src, src_port),
+        dst: SocketAddr::new(dst, dst_port),
+        seq,
+        syn: flags & TCP_SYN != 0,
+        payload: tcp.get(data_offset..)?.to_vec(),
+    })
+}
+
+/// One direction of a TCP connection
+#[derive(Default)]
+struct Stream {
+    /// Sequence number of the first payload byte
+    base: Option<u32>,
+    /// Payload by offset from `base`; retransmissions keep the longest copy
+    segments: BTreeMap<u32, Vec<u8>>,
+}
+
+impl Stream {
+    fn add(&mut self, segment: Segment) {
+        if segment.syn {
+            self.base = Some(segment.seq.wrapping_add(1));
+        }
+        if segment.payload.is_empty() {
+            return;
+        }
+        let base = *self.base.get_or_insert(segment.seq);
+        let offset = segment.seq.wrapping_sub(base);
+        // Data from before the capture started wraps around to a huge offset
+        if offset > u32::MAX / 2 {
+            return;
+        }
+        let existing = self.segments.entry(offset).or_default();
+        if segment.payload.len() > existing.len() {
+            *existing = segment.payload;
+        }
+    }
+
+    /// Contiguous bytes from the start of the stream up to the first gap
+    fn reassemble(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (&offset, payload) in &self.segments {
+            let offset = offset as usize;
+            if offset > data.len() {
+                break;
+            }
+            if offset + payload.len() > data.len() {
+                data.extend_from_slice(&payload[data.len() - offset..]);
+            }
+        }
+        data
+    }
+}
+
+#[derive(Default)]
+struct Streams {
+    streams: HashMap<(SocketAddr, SocketAddr), Stream>,
+    /// Directions in order of first appearance, so output follows the capture
+    order: Vec<(SocketAddr, SocketAddr)>,
+}
+
+impl Streams {
+    fn add(&mut self, segment: Segment) {
+        let key = (segment.src, segment.dst);
+        if !self.streams.contains_key(&key) {
+            self.order.push(key);
+        }
+        self.streams.entry(key).or_default().add(segment);
+    }
+
+    fn into_capture(self) -> Capture {
+        let data: HashMap<(SocketAddr, SocketAddr), Vec<u8>> =
+            self.streams.iter().map(|(key, stream)| (*key, stream.reassemble())).collect();
+        let mut capture = Capture::default();
+        let mut tls: BTreeMap<String, TlsAccumulator> = BTreeMap::new();
+
+        for key in &self.order {
+            let (src, dst) = *key;
+            let stream = &data[key];
+            let reverse = data.get(&(dst, src)).map(Vec::as_slice).unwrap_or_default();
+
+            if stream.starts_with(b"HTTP/1.") {
+                let requests = parse_requests(reverse);
+                let (responses, skipped) = parse_responses(stream, &requests, src);
+                capture.responses.extend(responses);
+                capture.skipped += skipped;
+            } else if let Some(sni) = client_hello_sni(stream) {
+                let server_hello = server_hello(reverse);
+                let host = sni.unwrap_or_else(|| dst.ip().to_string());
+                let entry = tls.entry(host).or_default();
+                entry.addresses.insert(dst.to_string());
+                entry.connections += 1;
+                if let Some((version, alpn)) = server_hello {
+                    entry.versions.insert(version);
+                    entry.alpn.extend(alpn);
+                }
+            }
+        }
+
+        capture.tls = tls
+            .into_iter()
+            .map(|(host, acc)| TlsHost {
+                host,
+                addresses: acc.addresses.into_iter().collect(),
+                connections: acc.connections,
+                versions: acc.versions.into_iter().collect(),
+                alpn: acc.alpn.into_iter().collect(),
+            })
+            .collect();
+        capture
+    }
+}
+
+#[derive(Default)]
+struct TlsAccumulator {
+    addresses: BTreeSet<String>,
+    connections: usize,
+    versions: BTreeSet<String>,
+    alpn: BTreeSet<String>,
+}
+
+struct Request {
+    method: String,
+    host: Option<String>,
+    target: String,
+}
+
+struct Message<'a> {
+    start_line: &'a str,
+    headers: Vec<(&'a str, &'a str)>,
+    /// Bytes after the header block
+    rest: &'a [u8],
+}
+
+/// Split off one message head (start line and headers)
+fn parse_head(data: &[u8]) -> Option<Message<'_>> {
+    let end = data.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&data[..end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let start_line = lines.next()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect();
+    Some(Message { start_line, headers, rest: &data[end + 4..] })
+}
+
+fn header<'a>(headers: &[(&'a str, &'a str)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| *v)
+}
+
+/// Body of a message and the bytes following it. Without a length the body runs to
+/// the end of the stream.
+fn split_body<'a>(headers: &[(&str, &str)], data: &'a [u8]) -> (Vec<u8>, &'a [u8]) {
+    if header(headers, "transfer-encoding").is_some_and(|te| te.to_ascii_lowercase().contains("chunked")) {
+        return decode_chunked(data);
+    }
+    match header(headers, "content-length").and_then(|len| len.parse::<usize>().ok()) {
+        Some(len) => {
+            let len = len.min(data.len());
+            (data[..len].to_vec(), &data[len..])
+        }
+        None => (data.to_vec(), &[]),
+    }
+}
+
+fn decode_chunked(mut data: &[u8]) -> (Vec<u8>, &[u8]) {
+    let mut body = Vec::new();
+    loop {
+        let Some(line_end) = data.windows(2).position(|w| w == b"\r\n") else { return (body, &[]) };
+        let size = std::str::from_utf8(&data[..line_end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next().unwrap_or("").trim(), 16).ok());
+        data = &data[line_end + 2..];
+        match size {
+            Some(0) => {
+                // Skip trailers up to the blank line
+                let end = data.windows(2).position(|w| w == b"\r\n");
+                let rest = match end {
+                    Some(0) => &data[2..],
+                    _ => data.windows(4).position(|w| w == b"\r\n\r\n").map(|p| &data[p + 4..]).unwrap_or(&[]),
+                };
+                return (body, rest);
+            }
+            Some(size) if size <= data.len() => {
+                body.extend_from_slice(&data[..size]);
+                data = data.get(size + 2..).unwrap_or_default();
+            }
+            _ => {
+                body.extend_from_slice(data);
+                return (body, &[]);
+            }
+        }
+    }
+}
+
+fn parse_requests(mut data: &[u8]) -> Vec<Request> {
+    let mut requests = Vec::new();
+    while let Some(message) = parse_head(data) {
+        let mut parts = message.start_line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else { break };
+        requests.push(Request {
+            method: method.to_string(),
+            host: header(&message.headers, "host").map(|h| h.to_string()),
+            target: target.to_string(),
+        });
+        // Request bodies only have a length when framed; anything else ends the stream
+        data = if header(&message.headers, "content-length").is_some() || header(&message.headers, "transfer-encoding").is_some() {
+            split_body(&message.headers, message.rest).1
+        } else {
+            message.rest
+        };
+    }
+    requests
+}
+
+/// Responses on a server-to-client stream, with URLs taken from the matching requests
+fn parse_responses(mut data: &[u8], requests: &[Request], server: SocketAddr) -> (Vec<CapturedResponse>, usize) {
+    let mut responses = Vec::new();
+    let mut requests = requests.iter();
+    while !data.is_empty() {
+        let Some(message) = parse_head(data) else { return (responses, 1) };
+        let Some(status) = message.start_line.split_whitespace().nth(1).and_then(|s| s.parse::<u16>().ok()) else {
+            return (responses, 1);
+        };
+        // Interim responses (100 Continue) share the request of the final one
+        if (100..200).contains(&status) {
+            data = message.rest;
+            continue;
+        }
+
+        let request = requests.next();
+        let no_body = status == 204 || status == 304 || request.is_some_and(|r| r.method.eq_ignore_ascii_case("HEAD"));
+        let (body, rest) = if no_body { (Vec::new(), message.rest) } else { split_body(&message.headers, message.rest) };
+        // Compressed bodies are useless to signature matching
+        let body = if header(&message.headers, "content-encoding").is_some_and(|e| !e.eq_ignore_ascii_case("identity")) {
+            String::new()
+        } else {
+            String::from_utf8_lossy(&body).into_owned()
+        };
+
+        let url = request_url(request, server);
+        responses.push(CapturedResponse {
+            response: HttpResponse::from_parts(status, message.headers.iter().copied(), body, url.clone()),
+            url,
+        });
+        data = rest;
+    }
+    (responses, 0)
+}
+
+fn request_url(request: Option<&Request>, server: SocketAddr) -> String {
+    let authority = request
+        .and_then(|r| r.host.clone())
+        .unwrap_or_else(|| match server {
+            SocketAddr::V6(addr) => format!("[{}]:{}", addr.ip(), addr.port()),
+            SocketAddr::V4(addr) => addr.to_string(),
+        });
+    match request {
+        // Proxy requests carry the absolute URL
+        Some(request) if request.target.starts_with("http://") => request.target.clone(),
+        Some(request) => format!("http://{}{}", authority, request.target),
+        None => format!("http://{}/", authority),
+    }
+}
+
+/// Body of the first TLS handshake message, joined across records
+fn handshake(data: &[u8], handshake_type: u8) -> Option<Vec<u8>> {
+    let mut message = Vec::new();
+    let mut data = data;
+    while data.first() == Some(&0x16) && data.get(1) == Some(&0x03) {
+        let len = u16::from_be_bytes([*data.get(3)?, *data.get(4)?]) as usize;
+        let record = data.get(5..5 + len).unwrap_or(&data[5.min(data.len())..]);
+        message.extend_from_slice(record);
+        if message.len() >= 4 {
+            if message[0] != handshake_type {
+                return None;
+            }
+            let body_len = u32::from_be_bytes([0, message[1], message[2], message[3]]) as usize;
+            if message.len() >= 4 + body_len {
+                message.truncate(4 + body_len);
+                return Some(message.split_off(4));
+            }
+        }
+        data = data.get(5 + len..)?;
+    }
+    None
+}
+
+/// Bounds-checked reader over a handshake message
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let (head, tail) = (self.data.get(..n)?, self.data.get(n..)?);
+        self.data = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    /// Length-prefixed vector with a one or two byte length
+    fn vec8(&mut self) -> Option<&'a [u8]> {
+        let len = self.u8()? as usize;
+        self.take(len)
+    }
+
+    fn vec16(&mut self) -> Option<&'a [u8]> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+
+    /// Extensions as (type, data) pairs; empty when the message has none
+    fn extensions(&mut self) -> Vec<(u16, &'a [u8])> {
+        let mut extensions = Vec::new();
+        let Some(data) = self.vec16() else { return extensions };
+        let mut reader = Reader { data };
+        while let (Some(kind), Some(data)) = (reader.u16(), reader.vec16()) {
+            extensions.push((kind, data));
+        }
+        extensions
+    }
+}
+
+const EXT_SERVER_NAME: u16 = 0x0000;
+const EXT_ALPN: u16 = 0x0010;
+const EXT_SUPPORTED_VERSIONS: u16 = 0x002b;
+
+/// `Some(sni)` when the stream opens with a ClientHello; the inner value is `None`
+/// when the client sent no server name
+fn client_hello_sni(data: &[u8]) -> Option<Option<String>> {
+    let message = handshake(data, 1)?;
+    let mut reader = Reader { data: &message };
+    reader.take(2 + 32)?; // legacy version, random
+    reader.vec8()?; // session id
+    reader.vec16()?; // cipher suites
+    reader.vec8()?; // compression methods
+
+    let sni = reader.extensions().into_iter().find(|(kind, _)| *kind == EXT_SERVER_NAME).and_then(|(_, data)| {
+        let mut list = Reader { data: Reader { data }.vec16()? };
+        while let (Some(name_type), Some(name)) = (list.u8(), list.vec16()) {
+            if name_type == 0 {
+                return std::str::from_utf8(name).ok().map(|name| name.to_ascii_lowercase());
+            }
+        }
+        None
+    });
+    Some(sni)
+}
+
+/// Negotiated version and selected ALPN protocol from a ServerHello
+fn server_hello(data: &[u8]) -> Option<(String, Option<String>)> {
+    let message = handshake(data, 2)?;
+    let mut reader = Reader { data: &message };
+    let legacy_version = reader.u16()?;
+    reader.take(32)?; // random
+    reader.vec8()?; // session id
+    reader.u16()?; // cipher suite
+    reader.u8()?; // compression method
+
+    let mut version = legacy_version;
+    let mut alpn = None;
+    for (kind, data) in reader.extensions() {
+        match kind {
+            EXT_SUPPORTED_VERSIONS if data.len() == 2 => version = u16::from_be_bytes([data[0], data[1]]),
+            EXT_ALPN => {
+                let mut list = Reader { data };
+                alpn = list.vec16().and_then(|protocols| Reader { data: protocols }.vec8()).and_then(|p| std::str::from_utf8(p).ok()).map(|p| p.to_string());
+            }
+            _ => {}
+        }
+    }
+    Some((version_name(version), alpn))
+}
+
+fn version_name(version: u16) -> String {
+    match version {
+        0x0300 => "SSL 3.0".to_string(),
+        0x0301 => "TLS 1.0".to_string(),
+        0x0302 => "TLS 1.1".to_string(),
+        0x0303 => "TLS 1.2".to_string(),
+        0x0304 => "TLS 1.3".to_string(),
+        other => format!("0x{:04x}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian, microsecond pcap with Ethernet frames
+    fn pcap(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&4u16.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&65535u32.to_le_bytes());
+        data.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+        for packet in packets {
+            data.extend_from_slice(&[0; 8]);
+            data.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            data.extend_from_slice(packet);
+        }
+        data
+    }
+
+    fn frame(src: ([u8; 4], u16), dst: ([u8; 4], u16), seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let mut tcp = Vec::new();
+        tcp.extend_from_slice(&src.1.to_be_bytes());
+        tcp.extend_from_slice(&dst.1.to_be_bytes());
+        tcp.extend_from_slice(&seq.to_be_bytes());
+        tcp.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        tcp.extend_from_slice(payload);
+
+        let mut ip = vec![0x45, 0];
+        ip.extend_from_slice(&((20 + tcp.len()) as u16).to_be_bytes());
+        ip.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0]);
+        ip.extend_from_slice(&src.0);
+        ip.extend_from_slice(&dst.0);
+        ip.extend_from_slice(&tcp);
+
+        let mut frame = vec![0; 12];
+        frame.extend_from_slice(&[0x08, 0x00]);
+        frame.extend_from_slice(&ip);
+        frame
+    }
+
+    const CLIENT: ([u8; 4], u16) = ([10, 0, 0, 2], 50000);
+    const SERVER: ([u8; 4], u16) = ([203, 0, 113, 7], 80);
+
+    #[test]
+    fn test_http_response_reassembled_out_of_order() {
+        let request = b"GET /login HTTP/1.1\r\nHost: shop.example.com\r\n\r\n";
+        let response = b"HTTP/1.1 403 Forbidden\r\nServer: cloudflare\r\nContent-Length: 13\r\n\r\nAccess denied";
+        let (first, second) = response.split_at(30);
+        let data = pcap(&[
+            frame(CLIENT, SERVER, 100, TCP_SYN, b""),
+            frame(SERVER, CLIENT, 500, TCP_SYN | 0x10, b""),
+            frame(CLIENT, SERVER, 101, 0x18, request),
+            frame(SERVER, CLIENT, 501 + 30, 0x18, second),
+            frame(SERVER, CLIENT, 501, 0x18, first),
+            // Retransmission of the first segment
+            frame(SERVER, CLIENT, 501, 0x18, first),
+        ]);
+
+        let capture = parse_pcap(&data).unwrap();
+
+        assert_eq!(capture.skipped, 0);
+        assert_eq!(capture.responses.len(), 1);
+        let captured = &capture.responses[0];
+        assert_eq!(captured.url, "http://shop.example.com/login");
+        assert_eq!(captured.response.status, 403);
+        assert_eq!(captured.response.headers["server"], "cloudflare");
+        assert_eq!(captured.response.body, "Access denied");
+    }
+
+    #[test]
+    fn test_keep_alive_chunked_and_head_responses() {
+        let requests = b"HEAD / HTTP/1.1\r\nHost: a.example\r\n\r\nGET /x HTTP/1.1\r\nHost: a.example\r\n\r\n";
+        let responses = b"HTTP/1.1 200 OK\r\nContent-Length: 99\r\n\r\nHTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        let data = pcap(&[
+            frame(CLIENT, SERVER, 1, 0x18, requests),
+            frame(SERVER, CLIENT, 1, 0x18, responses),
+        ]);
+
+        let capture = parse_pcap(&data).unwrap();
+
+        assert_eq!(capture.responses.len(), 2);
+        assert_eq!(capture.responses[0].response.body, "");
+        assert_eq!(capture.responses[1].url, "http://a.example/x");
+        assert_eq!(capture.responses[1].response.body, "hello world");
+    }
+
+    #[test]
+    fn test_tls_client_hello_sni() {
+        let mut extension = Vec::new();
+        let name = b"secure.example.com";
+        extension.extend_from_slice(&((name.len() + 3) as u16).to_be_bytes());
+        extension.push(0);
+        extension.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        extension.extend_from_slice(name);
+
+        let mut body = vec![0x03, 0x03];
+        body.extend_from_slice(&[0; 32]);
+        body.push(0); // session id
+        body.extend_from_slice(&[0, 2, 0x13, 0x01]); // cipher suites
+        body.extend_from_slice(&[1, 0]); // compression
+        body.extend_from_slice(&((extension.len() + 4) as u16).to_be_bytes());
+        body.extend_from_slice(&EXT_SERVER_NAME.to_be_bytes());
+        body.extend_from_slice(&(extension.len() as u16).to_be_bytes());
+        body.extend_from_slice(&extension);
+
+        let mut hello = vec![1, 0];
+        hello.extend_from_slice(&(body.len() as u16).to_be_bytes());
+        hello.extend_from_slice(&body);
+        let mut record = vec![0x16, 0x03, 0x01];
+        record.extend_from_slice(&(hello.len() as u16).to_be_bytes());
+        record.extend_from_slice(&hello);
+
+        let server = ([203, 0, 113, 7], 443);
+        let capture = parse_pcap(&pcap(&[frame(CLIENT, server, 1, 0x18, &record)])).unwrap();
+
+        assert!(capture.responses.is_empty());
+        assert_eq!(capture.tls.len(), 1);
+        assert_eq!(capture.tls[0].host, "secure.example.com");
+        assert_eq!(capture.tls[0].addresses, vec!["203.0.113.7:443"]);
+        assert_eq!(capture.tls[0].connections, 1);
+    }
+
+    #[test]
+    fn test_pcapng_is_rejected() {
+        let error = parse_pcap(&[0x0a, 0x0d, 0x0d, 0x0a, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap_err();
+        assert!(error.to_string().contains("pcapng"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Browser-verification / JS challenge detection
+//!
+//! Bot-management products answer suspicious clients with a JavaScript challenge,
+//! a captcha interstitial or a sensor script instead of the page. These are not WAF
+//! blocks and not CDN features, so they are recognized from the initial response
+//! and reported on their own. No extra requests are sent.
+
+use crate::http::HttpResponse;
+use crate::providers::set_cookie_names;
+use serde::{Deserialize, Serialize};
+
+/// What kind of verification the client was given
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeKind {
+    /// Vendor-chosen challenge that may or may not need interaction (Cloudflare Managed Challenge)
+    ManagedChallenge,
+    /// Non-interactive JavaScript proof-of-work or fingerprinting page
+    JsChallenge,
+    /// Interactive captcha interstitial
+    Captcha,
+    /// Sensor script collecting browser telemetry alongside the normal page
+    SensorScript,
+}
+
+impl std::fmt::Display for ChallengeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChallengeKind::ManagedChallenge => "managed challenge",
+            ChallengeKind::JsChallenge => "JS challenge",
+            ChallengeKind::Captcha => "captcha",
+            ChallengeKind::SensorScript => "sensor script",
+        })
+    }
+}
+
+/// A recognized bot-management challenge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotChallenge {
+    pub vendor: String,
+    pub kind: ChallengeKind,
+    pub confidence: f64,
+    /// The challenge replaced the requested page (an interstitial, not an embedded script)
+    pub interstitial: bool,
+    /// Markers the challenge was recognized from
+    pub evidence: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Marker {
+    Body(&'static str),
+    Header(&'static str),
+    HeaderValue(&'static str, &'static str),
+    Cookie(&'static str),
+}
+
+struct Signature {
+    vendor: &'static str,
+    kind: ChallengeKind,
+    marker: Marker,
+    confidence: f64,
+    interstitial: bool,
+}
+
+const fn signature(vendor: &'static str, kind: ChallengeKind, marker: Marker, confidence: f64, interstitial: bool) -> Signature {
+    Signature { vendor, kind, marker, confidence, interstitial }
+}
+
+const SIGNATURES: &[Signature] = &[
+    // Cloudflare
+    signature("CloudFlare", ChallengeKind::ManagedChallenge, Marker::HeaderValue("cf-mitigated", "challenge"), 0.95, true),
+    signature("CloudFlare", ChallengeKind::ManagedChallenge, Marker::Body("window._cf_chl_opt"), 0.90, true),
+    signature("CloudFlare", ChallengeKind::JsChallenge, Marker::Body("jschl_vc"), 0.85, true),
+    signature("CloudFlare", ChallengeKind::Captcha, Marker::Body("cf_chl_captcha_tk"), 0.85, true),
+    // Akamai Bot Manager
+    signature("Akamai", ChallengeKind::SensorScript, Marker::Cookie("_abck"), 0.85, false),
+    signature("Akamai", ChallengeKind::SensorScript, Marker::Cookie("bm_sz"), 0.75, false),
+    signature("Akamai", ChallengeKind::JsChallenge, Marker::Body("bm-verify"), 0.85, true),
+    // PerimeterX / HUMAN
+    signature("PerimeterX", ChallengeKind::Captcha, Marker::Body("captcha.px-cdn.net"), 0.90, true),
+    signature("PerimeterX", ChallengeKind::Captcha, Marker::Body("px-captcha"), 0.85, true),
+    signature("PerimeterX", ChallengeKind::SensorScript, Marker::Body("window._pxAppId"), 0.85, false),
+    signature("PerimeterX", ChallengeKind::SensorScript, Marker::Cookie("_pxhd"), 0.80, false),
+    // DataDome
+    signature("DataDome", ChallengeKind::Captcha, Marker::Body("geo.captcha-delivery.com"), 0.95, true),
+    signature("DataDome", ChallengeKind::Captcha, Marker::Body("ct.captcha-delivery.com"), 0.90, true),
+    signature("DataDome", ChallengeKind::SensorScript, Marker::Header("x-datadome"), 0.85, false),
+    signature("DataDome", ChallengeKind::SensorScript, Marker::Cookie("datadome"), 0.80, false),
+    // Kasada
+    signature("Kasada", ChallengeKind::JsChallenge, Marker::Header("x-kpsdk-ct"), 0.90, true),
+    signature("Kasada", ChallengeKind::JsChallenge, Marker::Body("KPSDK"), 0.85, true),
+];
+
+impl Marker {
+    fn matches(&self, response: &HttpResponse, cookies: &[String]) -> bool {
+        match self {
+            Marker::Body(marker) => response.body.contains(marker),
+            Marker::Header(name) => response.headers.contains_key(name),
+            Marker::HeaderValue(name, value) => response.headers.get_all(name).any(|v| v.eq_ignore_ascii_case(value)),
+            Marker::Cookie(name) => cookies.iter().any(|cookie| cookie == name),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Marker::Body(marker) => format!("body: {}", marker),
+            Marker::Header(name) => format!("header: {}", name),
+            Marker::HeaderValue(name, value) => format!("header: {}: {}", name, value),
+            Marker::Cookie(name) => format!("cookie: {}", name),
+        }
+    }
+}
+
+/// Recognize a bot-management challenge in `response`.
+///
+/// When several vendors match, interstitials win over embedded sensor scripts, then
+/// the most confident signature wins. Evidence lists every marker of that vendor.
+pub fn detect_challenge(response: &HttpResponse) -> Option<BotChallenge> {
+    let cookies = set_cookie_names(response);
+    let matched: Vec<&Signature> = SIGNATURES
+        .iter()
+        .filter(|signature| signature.marker.matches(response, &cookies))
+        .collect();
+
+    let best = matched.iter().max_by(|a, b| {
+        (a.interstitial, a.confidence)
+            .partial_cmp(&(b.interstitial, b.confidence))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })?;
+
+    Some(BotChallenge {
+        vendor: best.vendor.to_string(),
+        kind: best.kind,
+        confidence: best.confidence,
+        interstitial: best.interstitial,
+        evidence: matched
+            .iter()
+            .filter(|signature| signature.vendor == best.vendor)
+            .map(|signature| signature.marker.describe())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: headers.iter().copied().collect(),
+            body: body.to_string(),
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cloudflare_managed_challenge() {
+        let challenge = detect_challenge(&response(
+            403,
+            &[("cf-mitigated", "challenge"), ("cf-ray", "8a1b2c3d4e5f6a7b-AMS")],
+            "<script>window._cf_chl_opt={cType: 'managed'}</script>",
+        )).unwrap();
+
+        assert_eq!(challenge.vendor, "CloudFlare");
+        assert_eq!(challenge.kind, ChallengeKind::ManagedChallenge);
+        assert!(challenge.interstitial);
+        assert_eq!(challenge.evidence, vec!["header: cf-mitigated: challenge", "body: window._cf_chl_opt"]);
+    }
+
+    #[test]
+    fn test_interstitial_wins_over_sensor_script() {
+        // Akamai sensor cookies on a DataDome captcha page (e.g. a CDN in front of DataDome)
+        let challenge = detect_challenge(&response(
+            403,
+            &[("set-cookie", "_abck=abc; Path=/\ndatadome=xyz")],
+            "<script src=\"https://ct.captcha-delivery.com/c.js\"></script>",
+        )).unwrap();
+
+        assert_eq!(challenge.vendor, "DataDome");
+        assert_eq!(challenge.kind, ChallengeKind::Captcha);
+        assert_eq!(challenge.evidence, vec!["body: ct.captcha-delivery.com", "cookie: datadome"]);
+    }
+
+    #[test]
+    fn test_plain_page_has_no_challenge() {
+        assert!(detect_challenge(&response(200, &[("server", "nginx")], "<html>Welcome</html>")).is_none());
+    }
+}
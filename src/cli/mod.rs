@@ -1,17 +1,68 @@
 //! Simple CLI Interface - Modern and intuitive WAF detection
 
+pub mod output;
+
 use crate::engine::DetectionEngine;
-use crate::providers::{Provider, cloudflare::CloudFlareProvider, akamai::AkamaiProvider, aws::AwsProvider, fastly::FastlyProvider, vercel::VercelProvider};
-use crate::registry::ProviderRegistry;
-use crate::payload::waf_smoke_test::{WafSmokeTest, SmokeTestConfig};
-use crate::DetectionResult;
+use crate::providers::default_providers;
+use crate::dns::{passive, AddressFamily};
+use crate::detector::DEFAULT_TIMEOUT;
+use crate::http::{pool, BodyLimit, HttpClient, RetryPolicy};
+use crate::ratelimit;
+use crate::recon;
+use crate::favicon;
+use crate::edge;
+use crate::ct::CrtSh;
+use crate::exporters::Exporters;
+use crate::logging;
+use crate::idn;
+use crate::profiles;
+use crate::signatures;
+use crate::utils::{self, table::BoxTable};
+use output::status;
+use crate::agents::{self, Agent, AgentEndpoint, Coordinator, VantageReport};
+use crate::web::auth::AuthConfig;
+use crate::web::projects::ProjectStore;
+use crate::payload::combined::{self, EffectivenessError};
+use crate::script_executor::{ScriptExecutor, ScriptSandbox};
+use crate::web::schedules::ScheduleStore;
+use crate::policy::{self, FailCondition, FailPolicy, PolicyReport};
+use crate::enumeration::{self, CoverageMatrix, SubdomainEnumerator};
+use crate::paths::{self, PathCoverageReport, PathScanner};
+use crate::registry::{Analyzer, ProviderRegistry};
+use crate::payload::waf_smoke_test::{WafSmokeTest, SmokeTestConfig, PayloadLocation};
+use crate::payload::PayloadCorpus;
+use crate::payload::export::ExportFormat;
+use crate::testing::{self, Benchmark, BenchmarkTargets, ValidationFramework, ValidationReport};
+use crate::confidence::{decay::ConfidenceDecay, ScoringConfig};
+use crate::capture::{self, TrafficReport};
+use crate::replay::{Session, Tape};
+use crate::{DetectionOptions, DetectionResult};
 use anyhow::{Result, anyhow};
 use clap::{Arg, ArgMatches, Command};
 use std::time::Instant;
 use std::fs;
+use std::path::PathBuf;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use url::Url;
 
+/// The `--record`/`--replay` tape and its file, finished when the CLI returns or exits early
+static TAPE: OnceLock<(Tape, String)> = OnceLock::new();
+
+/// Save a recorded session, or report how much of a replayed one was used
+fn finish_tape() {
+    let Some((tape, path)) = TAPE.get() else { return };
+    let session = tape.session();
+    if tape.is_replaying() {
+        status!("🔁 Replayed {} of {} recorded interaction(s) from {}", tape.replayed(), session.interactions.len(), path);
+        return;
+    }
+    match session.save(path) {
+        Ok(()) => status!("📼 Recorded {} interaction(s) to {}", session.interactions.len(), path),
+        Err(e) => eprintln!("❌ {}", e),
+    }
+}
+
 pub struct SimpleCliApp {
     engine: DetectionEngine,
 }
@@ -21,11 +72,9 @@ impl SimpleCliApp {
         let registry = ProviderRegistry::new();
         
         // Register providers
-        registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new()))?;
-        registry.register_provider(Provider::Akamai(AkamaiProvider::new()))?;
-        registry.register_provider(Provider::AWS(AwsProvider::new()))?;
-        registry.register_provider(Provider::Fastly(FastlyProvider::new()))?;
-        registry.register_provider(Provider::Vercel(VercelProvider::new()))?;
+        for provider in default_providers() {
+            registry.register_provider(provider)?;
+        }
         
         let engine = DetectionEngine::new(registry)
             .with_waf_mode_detection();
@@ -34,12 +83,33 @@ impl SimpleCliApp {
     }
 
     pub async fn run(&self) -> Result<()> {
+        let result = self.run_command().await;
+        finish_tape();
+        result
+    }
+
+    async fn run_command(&self) -> Result<()> {
         let matches = build_simple_cli().get_matches();
+        logging::init(matches.get_one::<String>("log-level").map(String::as_str), matches.get_flag("log-json"))?;
+        output::configure(matches.get_flag("quiet"), matches.get_flag("no-color"));
+        match matches.subcommand() {
+            Some(("completions", completions_matches)) => return self.run_completions(completions_matches),
+            Some(("man", man_matches)) => return self.run_man(man_matches),
+            Some(("update-signatures", update_matches)) => return self.run_update_signatures(&matches, update_matches).await,
+            _ => {}
+        }
+        self.apply_installed_signatures(&matches);
+        self.apply_rate_limits(&matches);
+        if let Some(name) = matches.get_one::<String>("impersonate") {
+            profiles::configure(Some(profiles::Impersonation::parse(name)?));
+        }
         
         // Handle special commands first
         if matches.get_flag("web") {
-            let port = matches.get_one::<u16>("port").copied().unwrap_or(8080);
-            return self.start_web_server(port).await;
+            return self.start_web_server(&matches).await;
+        }
+        if matches.get_flag("api-only") {
+            return self.start_api_server(&matches).await;
         }
         
         if matches.get_flag("list") {
@@ -51,11 +121,75 @@ impl SimpleCliApp {
             return self.run_smoke_test(&matches).await;
         }
 
-        // Get targets to scan
-        let targets = self.parse_targets(&matches)?;
+        // Select which providers and analyzers run
+        self.apply_technique_selection(&matches)?;
+        self.apply_scoring_config(&matches)?;
+        self.apply_favicon_db(&matches)?;
+        self.apply_ct_url(&matches)?;
+        self.apply_passive_dns(&matches)?;
+        self.apply_intel(&matches)?;
+        if let Some(("scoring", scoring_matches)) = matches.subcommand() {
+            return self.run_scoring(scoring_matches);
+        }
+        if let Some(("signatures", signatures_matches)) = matches.subcommand() {
+            return self.run_signatures(signatures_matches);
+        }
+        let tape = self.tape(&matches)?;
+        let mut options = self.detection_options(&matches);
+        if tape.as_ref().is_some_and(Tape::is_replaying) {
+            options.disable_unreplayable();
+        }
+        let mut engine = self.engine.clone().with_options(options);
+        // The engine's client was built before the pool settings were known
+        if self.apply_pool_config(&matches) || matches.get_flag("ipv4-only") {
+            let family = if matches.get_flag("ipv4-only") { AddressFamily::Ipv4 } else { AddressFamily::Any };
+            engine = engine.with_http_client(HttpClient::with_address_family(DEFAULT_TIMEOUT, None, family)?);
+        }
+        if let Some(retries) = matches.get_one::<u32>("retries") {
+            engine = engine.with_retry_policy(RetryPolicy::with_retries(*retries));
+        }
+        if let Some(body_limit) = self.body_limit(&matches) {
+            engine = engine.with_body_limit(body_limit);
+        }
+        if let Some(runs) = matches.get_one::<u64>("runs") {
+            let interval = matches.get_one::<u64>("run-interval").copied().unwrap_or(0);
+            engine = engine.with_runs(*runs as usize, std::time::Duration::from_secs(interval));
+        }
+        if matches.get_flag("session") {
+            engine = engine.with_cookie_session();
+        }
+        if let Some(ips) = matches.get_many::<std::net::IpAddr>("origin-ip") {
+            engine = engine.with_origin_candidates(ips.copied().collect());
+        }
+        if let Some(tape) = tape {
+            engine = engine.with_tape(tape);
+        }
+        let app = SimpleCliApp { engine };
+
+        if let Some(("enum", enum_matches)) = matches.subcommand() {
+            return app.run_enum(enum_matches).await;
+        }
+        if let Some(("analyze", analyze_matches)) = matches.subcommand() {
+            return app.run_analyze(analyze_matches).await;
+        }
+        if let Some(("validate", validate_matches)) = matches.subcommand() {
+            return app.run_validate(validate_matches).await;
+        }
+        if let Some(("benchmark", benchmark_matches)) = matches.subcommand() {
+            return app.run_benchmark(&matches, benchmark_matches).await;
+        }
+        if let Some(("agent", agent_matches)) = matches.subcommand() {
+            return app.run_agent(agent_matches).await;
+        }
+
+        // Get targets to scan; a replay without targets rescans the recorded ones
+        let mut targets = app.parse_targets(&matches)?;
+        if let Some(tape) = app.engine.tape().filter(|tape| targets.is_empty() && tape.is_replaying()) {
+            targets = tape.session().targets;
+        }
         
         if targets.is_empty() {
-            println!("❌ No targets specified. Use --help for usage.");
+            eprintln!("❌ No targets specified. Use --help for usage.");
             return Ok(());
         }
 
@@ -63,12 +197,362 @@ impl SimpleCliApp {
         let format = self.determine_format(&matches);
         let debug = matches.get_flag("debug");
         let verbose = matches.get_flag("verbose");
+        let explain = matches.get_flag("explain");
+
+        if let Some(coordinator) = self.coordinator(&matches)? {
+            return app.scan_from_agents(&coordinator, &targets, &format).await;
+        }
+
+        if let Some(path_list) = matches.get_one::<String>("paths") {
+            return app.scan_paths(&targets, path_list, &matches, &format).await;
+        }
+
+        if matches.get_flag("combined") {
+            let legacy = self.legacy_script_executor(&matches)?;
+            return app.run_combined(&targets, &format, legacy.as_ref()).await;
+        }
+
+        let policy = self.fail_policy(&matches);
+        policy.validate_for_detection()?;
+        let exporters = self.exporters(&matches)?;
 
         // Scan targets
-        if targets.len() == 1 {
-            self.scan_single(&targets[0], &format, debug, verbose).await
+        let results = if targets.len() == 1 {
+            match app.scan_single(&targets[0], &format, debug, explain, verbose).await {
+                Ok(result) => vec![result],
+                Err(e) if !policy.is_empty() => {
+                    eprintln!("❌ Scan of {} failed: {}", targets[0], e);
+                    finish_tape();
+                    std::process::exit(policy::EXIT_SCAN_FAILED);
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            app.scan_batch(&targets, &format, debug, explain, verbose).await?
+        };
+        exporters.scans(&results).await;
+
+        if !policy.is_empty() {
+            let thresholds = app.engine.registry().advanced_scoring().thresholds().clone();
+            self.exit_on_policy(&policy.check_detections(&results, &thresholds)?);
+        }
+        Ok(())
+    }
+
+    /// The deprecated test script, when `--legacy-script` asks for it
+    fn legacy_script_executor(&self, matches: &ArgMatches) -> Result<Option<ScriptExecutor>> {
+        if !matches.get_flag("legacy-script") {
+            return Ok(None);
+        }
+        eprintln!("⚠️  --legacy-script is deprecated: effectiveness tests run natively without it");
+        let mut sandbox = ScriptSandbox::default();
+        if let Some(&secs) = matches.get_one::<u64>("script-timeout") {
+            sandbox.timeout = std::time::Duration::from_secs(secs);
+        }
+        if let Some(&bytes) = matches.get_one::<usize>("script-max-output") {
+            sandbox.max_output_bytes = bytes;
+        }
+        sandbox.working_dir = matches.get_one::<String>("script-workdir").map(Into::into);
+        Ok(Some(ScriptExecutor::new()?.with_sandbox(sandbox)))
+    }
+
+    /// Exporters from `--exporters`, none without it
+    fn exporters(&self, matches: &ArgMatches) -> Result<Exporters> {
+        match matches.get_one::<String>("exporters") {
+            Some(path) => Exporters::from_file(path),
+            None => Ok(Exporters::default()),
+        }
+    }
+
+    /// `--fail-on` conditions
+    fn fail_policy(&self, matches: &ArgMatches) -> FailPolicy {
+        FailPolicy::new(matches.get_many::<FailCondition>("fail-on").map(|c| c.copied().collect()).unwrap_or_default())
+    }
+
+    /// Report policy violations and scan failures on stderr and exit with the matching code
+    fn exit_on_policy(&self, report: &PolicyReport) {
+        for violation in &report.violations {
+            eprintln!("❌ --fail-on {}: {} ({})", violation.condition, violation.url, violation.message);
+        }
+        for url in &report.scan_failures {
+            eprintln!("❌ Scan failed, protection unknown: {}", url);
+        }
+        let code = report.exit_code();
+        if code != policy::EXIT_OK {
+            finish_tape();
+            std::process::exit(code);
+        }
+    }
+
+    /// The tape for `--record` or `--replay`, registered to be finished on exit
+    fn tape(&self, matches: &ArgMatches) -> Result<Option<Tape>> {
+        let (tape, path) = if let Some(path) = matches.get_one::<String>("record") {
+            (Tape::record(), path)
+        } else if let Some(path) = matches.get_one::<String>("replay") {
+            let session = Session::load(path)?;
+            status!("🔁 Replaying {} interaction(s) recorded {} by waf-detector {}", session.interactions.len(), session.recorded_at.format("%Y-%m-%d %H:%M UTC"), session.tool_version);
+            (Tape::replay(session), path)
         } else {
-            self.scan_batch(&targets, &format, debug, verbose).await
+            return Ok(None);
+        };
+        let _ = TAPE.set((tape.clone(), path.clone()));
+        Ok(Some(tape))
+    }
+
+    /// Build per-scan detection options; probing techniques are opt-in
+    fn detection_options(&self, matches: &ArgMatches) -> DetectionOptions {
+        DetectionOptions {
+            dns: !matches.get_flag("skip-dns"),
+            timing: matches.get_flag("timing"),
+            payload: matches.get_flag("payload"),
+            active_probes: matches.get_flag("active-probes"),
+            preflight: matches.get_flag("preflight"),
+            respect_robots: matches.get_flag("respect-robots"),
+            fingerprint: matches.get_flag("fingerprint"),
+            mode_analysis: matches.get_flag("mode-analysis"),
+            ratelimit_probe: matches.get_flag("ratelimit-probe"),
+            protocol: matches.get_flag("protocol"),
+            header_order: matches.get_flag("header-order"),
+            block_page: !matches.get_flag("skip-block-page"),
+            favicon: matches.get_flag("favicon"),
+            cache: matches.get_flag("cache-probe"),
+            dual_stack: matches.get_flag("ipv6"),
+            browser_fallback: matches.get_flag("browser-fallback"),
+            origin_check: matches.get_flag("origin-check") || matches.contains_id("origin-ip"),
+            ct: matches.get_flag("ct"),
+            host_intel: matches.contains_id("intel"),
+        }
+    }
+
+    /// Configure the shared per-host rate limiter used by every outgoing request
+    fn apply_rate_limits(&self, matches: &ArgMatches) {
+        let mut config = ratelimit::global().config();
+        if let Some(rps) = matches.get_one::<f64>("rps") {
+            config.requests_per_second = Some(*rps);
+        }
+        if let Some(max) = matches.get_one::<usize>("max-requests-per-host") {
+            config.max_requests_per_host = Some(*max);
+        }
+        ratelimit::global().configure(config);
+    }
+
+    fn apply_technique_selection(&self, matches: &ArgMatches) -> Result<()> {
+        let registry = self.engine.registry();
+
+        if let Some(providers) = matches.get_many::<String>("providers") {
+            let names: Vec<&String> = providers.collect();
+            registry.enable_only_providers(&names)?;
+        }
+
+        if matches.get_flag("passive") {
+            registry.set_strict_passive(true);
+        }
+        if matches.get_flag("passive-only") {
+            registry.set_passive_only();
+        }
+        if matches.get_flag("skip-timing") {
+            registry.set_analyzer_enabled(Analyzer::Timing, false);
+        }
+        if matches.get_flag("skip-dns") {
+            registry.set_analyzer_enabled(Analyzer::Dns, false);
+        }
+        if matches.get_flag("skip-payload") {
+            registry.set_analyzer_enabled(Analyzer::Payload, false);
+        }
+
+        Ok(())
+    }
+
+    /// Point CT enrichment and subdomain enumeration at `--ct-url`
+    fn apply_ct_url(&self, matches: &ArgMatches) -> Result<()> {
+        if let Some(url) = matches.get_one::<String>("ct-url") {
+            self.engine.registry().set_ct_source(std::sync::Arc::new(CrtSh::new()?.with_base_url(url)));
+        }
+        Ok(())
+    }
+
+    /// Passive DNS services from `--pdns`
+    fn apply_passive_dns(&self, matches: &ArgMatches) -> Result<()> {
+        if let Some(specs) = matches.get_many::<String>("pdns") {
+            let sources = specs.map(|spec| passive::source_from_spec(spec)).collect::<Result<Vec<_>>>()?;
+            self.engine.registry().set_passive_dns_sources(sources);
+        }
+        Ok(())
+    }
+
+    /// Shodan/Censys sources from `--intel`
+    fn apply_intel(&self, matches: &ArgMatches) -> Result<()> {
+        if let Some(specs) = matches.get_many::<String>("intel") {
+            let sources = specs.map(|spec| crate::intel::source_from_spec(spec)).collect::<Result<Vec<_>>>()?;
+            self.engine.registry().set_intel_sources(sources);
+        }
+        Ok(())
+    }
+
+    /// Load `--favicon-db` hashes into the registry
+    fn apply_favicon_db(&self, matches: &ArgMatches) -> Result<()> {
+        if let Some(path) = matches.get_one::<String>("favicon-db") {
+            self.engine.registry().set_known_favicons(favicon::load_known_favicons(path)?);
+        }
+        Ok(())
+    }
+
+    /// Apply `--scoring` weight/threshold overrides to the registry
+    fn apply_scoring_config(&self, matches: &ArgMatches) -> Result<()> {
+        if let Some(path) = matches.get_one::<String>("scoring") {
+            let config = ScoringConfig::from_file(path)?;
+            let registry = self.engine.registry();
+            let scoring = (*registry.advanced_scoring()).clone().with_config(config)?;
+            registry.set_advanced_scoring(scoring);
+        }
+        Ok(())
+    }
+
+    /// `--max-idle-per-host` / `--dns-cache-ttl`; true when either is given
+    fn apply_pool_config(&self, matches: &ArgMatches) -> bool {
+        let mut config = pool::config();
+        let max_idle = matches.get_one::<usize>("max-idle-per-host");
+        let dns_ttl = matches.get_one::<u64>("dns-cache-ttl");
+        if let Some(max_idle) = max_idle {
+            config.max_idle_per_host = *max_idle;
+        }
+        if let Some(secs) = dns_ttl {
+            config.dns_ttl = std::time::Duration::from_secs(*secs);
+        }
+        pool::configure(config);
+        max_idle.is_some() || dns_ttl.is_some()
+    }
+
+    /// `--max-body-size` / `--body-tail`, when either is given
+    fn body_limit(&self, matches: &ArgMatches) -> Option<BodyLimit> {
+        let max_bytes = matches.get_one::<usize>("max-body-size").copied();
+        let tail_bytes = matches.get_one::<usize>("body-tail").copied();
+        if max_bytes.is_none() && tail_bytes.is_none() {
+            return None;
+        }
+        let default = BodyLimit::default();
+        Some(BodyLimit {
+            max_bytes: max_bytes.unwrap_or(default.max_bytes),
+            tail_bytes: tail_bytes.unwrap_or(default.tail_bytes),
+        })
+    }
+
+    /// `--data-dir`, else the platform data directory
+    fn data_dir(&self, matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<String>("data-dir").map(PathBuf::from).or_else(signatures::data_dir)
+    }
+
+    /// Extend the providers with the bundle installed by `update-signatures`. A broken
+    /// bundle is reported and skipped so scans still run with the built-in signatures.
+    fn apply_installed_signatures(&self, matches: &ArgMatches) {
+        let Some(dir) = self.data_dir(matches) else { return };
+        let applied = signatures::load_installed(&dir).and_then(|bundle| match bundle {
+            Some(bundle) => self.engine.registry().apply_signatures(&bundle).map(|_| Some(bundle.version)),
+            None => Ok(None),
+        });
+        match applied {
+            Ok(Some(version)) => tracing::debug!(version = %version, dir = %dir.display(), "loaded signature bundle"),
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️  Ignoring installed signatures: {:#}", e),
+        }
+    }
+
+    /// `waf-detect update-signatures`: download, verify and install the signature bundle
+    async fn run_update_signatures(&self, matches: &ArgMatches, update_matches: &ArgMatches) -> Result<()> {
+        let dir = self.data_dir(matches).ok_or_else(|| anyhow!("No data directory (pass --data-dir or set WAF_DETECTOR_DATA_DIR)"))?;
+        let mut config = signatures::update::UpdateConfig::new(
+            update_matches.get_one::<String>("url").map(String::as_str),
+            update_matches.get_one::<String>("public-key").map(String::as_str),
+        )?;
+        config.signature_url = update_matches.get_one::<String>("signature-url").cloned();
+        config.force = update_matches.get_flag("force");
+
+        status!("📥 Downloading signatures from {}", config.url);
+        let outcome = signatures::update::update(&dir, &config).await?;
+        let bundle = &outcome.bundle;
+        match &outcome.previous {
+            Some(previous) if *previous == bundle.version => status!("✅ Signatures {} reinstalled", bundle.version),
+            Some(previous) => status!("✅ Signatures updated {} → {}", previous, bundle.version),
+            None => status!("✅ Signatures {} installed", bundle.version),
+        }
+        status!(
+            "   {} provider(s), {} DNS pattern(s), {} block page(s), {} IP range(s) in {}",
+            bundle.providers.len(),
+            bundle.dns_patterns.len(),
+            bundle.block_pages.len(),
+            bundle.ip_ranges.len(),
+            outcome.path.display()
+        );
+        Ok(())
+    }
+
+    /// `waf-detect completions <shell>`: print a completion script for `shell`
+    fn run_completions(&self, matches: &ArgMatches) -> Result<()> {
+        let shell = *matches.get_one::<clap_complete::Shell>("shell").expect("shell is required");
+        let mut cli = build_simple_cli();
+        clap_complete::generate(shell, &mut cli, "waf-detect", &mut std::io::stdout());
+        Ok(())
+    }
+
+    /// `waf-detect man`: print the man page, or write one per subcommand with --out-dir
+    fn run_man(&self, matches: &ArgMatches) -> Result<()> {
+        match matches.get_one::<String>("out-dir") {
+            Some(dir) => {
+                fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create '{}': {}", dir, e))?;
+                clap_mangen::generate_to(build_simple_cli(), dir)
+                    .map_err(|e| anyhow!("Failed to write man pages to '{}': {}", dir, e))?;
+                status!("📖 Wrote man pages to {}", dir);
+            }
+            None => clap_mangen::Man::new(build_simple_cli()).render(&mut std::io::stdout())?,
+        }
+        Ok(())
+    }
+
+    /// `waf-detect scoring dump`: print the effective weights and thresholds
+    fn run_scoring(&self, matches: &ArgMatches) -> Result<()> {
+        match matches.subcommand() {
+            Some(("dump", dump_matches)) => {
+                let config = self.engine.registry().advanced_scoring().to_config();
+                if dump_matches.get_flag("json") {
+                    println!("{}", serde_json::to_string_pretty(&config)?);
+                } else {
+                    print!("{}", serde_yaml::to_string(&config)?);
+                }
+                Ok(())
+            }
+            _ => Err(anyhow!("Unknown scoring command (try `waf-detect scoring dump`)")),
+        }
+    }
+
+    /// `waf-detect signatures list`: the bundle in effect and the rules of every provider;
+    /// `waf-detect signatures test <file>`: check a signature file before publishing it
+    fn run_signatures(&self, matches: &ArgMatches) -> Result<()> {
+        match matches.subcommand() {
+            Some(("list", list_matches)) => {
+                let inventory = self.engine.registry().signature_inventory();
+                if list_matches.get_flag("json") {
+                    println!("{}", serde_json::to_string_pretty(&inventory)?);
+                } else {
+                    print_signature_inventory(&inventory);
+                }
+                Ok(())
+            }
+            Some(("test", test_matches)) => {
+                let path = test_matches.get_one::<String>("file").expect("file is required");
+                let data = fs::read(path).map_err(|e| anyhow!("Failed to read '{}': {}", path, e))?;
+                let budget = std::time::Duration::from_millis(*test_matches.get_one::<u64>("budget-ms").expect("budget-ms has a default"));
+                let report = signatures::lint::lint_file(&data, budget)?;
+                if test_matches.get_flag("json") {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    print_lint_report(&report);
+                }
+                if report.errors() > 0 {
+                    return Err(anyhow!("{} has {} error(s)", path, report.errors()));
+                }
+                Ok(())
+            }
+            _ => Err(anyhow!("Unknown signatures command (try `waf-detect signatures list`)")),
         }
     }
 
@@ -97,22 +581,30 @@ impl SimpleCliApp {
             }
         }
 
-        Ok(targets)
-    }
-
-    fn normalize_url(&self, input: &str) -> Result<String> {
-        // If it's already a valid URL, use it
-        if let Ok(url) = Url::parse(input) {
-            return Ok(url.to_string());
+        // Web ports from Nmap XML / masscan JSON
+        if let Some(paths) = matches.get_many::<String>("from-nmap") {
+            for path in paths {
+                let urls = recon::load_scan_targets(path)?;
+                status!("📥 {} web target(s) from {}", urls.len(), path);
+                targets.extend(urls);
+            }
         }
 
-        // Try adding https://
-        let with_https = format!("https://{}", input);
-        if let Ok(url) = Url::parse(&with_https) {
-            return Ok(url.to_string());
+        if matches.get_flag("keep-duplicates") {
+            return Ok(targets);
+        }
+        let report = crate::targets::dedup(targets, matches.get_flag("merge-www"));
+        if !report.collapsed.is_empty() {
+            status!("🧹 Collapsed {} duplicate target(s):", report.collapsed.len());
+            for collapsed in &report.collapsed {
+                status!("   • {} → {}", collapsed.target, collapsed.kept);
+            }
         }
+        Ok(report.targets)
+    }
 
-        Err(anyhow!("Invalid URL or domain: {}", input))
+    fn normalize_url(&self, input: &str) -> Result<String> {
+        idn::normalize_url(input)
     }
 
     fn determine_format(&self, matches: &ArgMatches) -> String {
@@ -120,21 +612,33 @@ impl SimpleCliApp {
             "json".to_string()
         } else if matches.get_flag("yaml") {
             "yaml".to_string()
-        } else if matches.get_flag("compact") {
+        } else if matches.get_flag("compact") || output::is_quiet() {
+            // --quiet keeps the results only: one line per target instead of the boxed table
             "compact".to_string()
         } else {
             "table".to_string()
         }
     }
 
-    async fn scan_single(&self, url: &str, format: &str, debug: bool, verbose: bool) -> Result<()> {
+    /// Shape a result for output: the narrative with --explain, evidence groups only with --debug
+    fn prepare_result(result: &mut DetectionResult, debug: bool, explain: bool) {
+        if explain {
+            result.explanation = Some(result.explain());
+        }
+        if !debug {
+            result.evidence_groups.clear();
+        }
+    }
+
+    async fn scan_single(&self, url: &str, format: &str, debug: bool, explain: bool, verbose: bool) -> Result<DetectionResult> {
         if verbose {
-            println!("🔍 Scanning: {}", url);
+            status!("🔍 Scanning: {}", url);
         }
 
         let start_time = Instant::now();
-        let detection_result = self.engine.detect(url).await?;
+        let mut detection_result = self.engine.detect(url).await?;
         let scan_time = start_time.elapsed();
+        Self::prepare_result(&mut detection_result, debug, explain);
 
         match format {
             "json" => {
@@ -152,18 +656,54 @@ impl SimpleCliApp {
         }
 
         if verbose {
-            println!("⏱️  Scan completed in {:.2}ms", scan_time.as_millis());
+            status!("⏱️  Scan completed in {:.2}ms", scan_time.as_millis());
+        }
+
+        Ok(detection_result)
+    }
+
+    /// Detection followed by an effectiveness smoke test, summarized per target
+    async fn run_combined(&self, urls: &[String], format: &str, legacy: Option<&ScriptExecutor>) -> Result<()> {
+        let mut results = Vec::new();
+        for url in urls {
+            let start_time = Instant::now();
+            let detection = self.engine.detect(url).await?;
+            let effectiveness = match legacy {
+                Some(executor) => executor.execute_test(url).await.map_err(|e| EffectivenessError::from(&e)),
+                None => combined::test_effectiveness(url, combined::smoke_test_config())
+                    .await
+                    .map_err(|e| EffectivenessError::from_error(&e)),
+            };
+            let result = combined::combine(detection, effectiveness, start_time.elapsed().as_millis() as u64);
+
+            if format == "table" || format == "compact" {
+                println!("🎯 {}", result.url);
+                for line in result.analysis_summary.lines() {
+                    println!("   {}", line);
+                }
+                for recommendation in &result.recommendations {
+                    println!("   • {}", recommendation);
+                }
+                println!("   ⏱️  {}ms\n", result.total_time_ms);
+            }
+            results.push(result);
         }
 
+        match format {
+            "json" => println!("{}", serde_json::to_string_pretty(&results)?),
+            "yaml" => println!("{}", serde_yaml::to_string(&results)?),
+            _ => {}
+        }
         Ok(())
     }
 
-    async fn scan_batch(&self, urls: &[String], format: &str, debug: bool, verbose: bool) -> Result<()> {
+    async fn scan_batch(&self, urls: &[String], format: &str, debug: bool, explain: bool, verbose: bool) -> Result<Vec<DetectionResult>> {
         if verbose {
-            println!("🔍 Scanning {} targets...", urls.len());
+            status!("🔍 Scanning {} targets...", urls.len());
         }
 
         let total_start = Instant::now();
+        let connections_before = pool::stats();
         
         // Use parallel batch detection with rate limiting (max 3 concurrent requests)
         let url_refs: Vec<&str> = urls.iter().map(|s| s.as_str()).collect();
@@ -173,11 +713,13 @@ impl SimpleCliApp {
         let mut results = Vec::new();
         for (i, url) in urls.iter().enumerate() {
             if verbose {
-                println!("({}/{}) {} - Processing...", i + 1, urls.len(), url);
+                status!("({}/{}) {} - Processing...", i + 1, urls.len(), url);
             }
             
             if let Some(result) = batch_results.get(url) {
-                results.push(result.clone());
+                let mut result = result.clone();
+                Self::prepare_result(&mut result, debug, explain);
+                results.push(result);
             }
         }
 
@@ -206,110 +748,626 @@ impl SimpleCliApp {
         }
 
         if verbose {
-            println!("\n⏱️  Total scan time: {:.2}s", total_time.as_secs_f64());
+            status!("\n⏱️  Total scan time: {:.2}s", total_time.as_secs_f64());
+        }
+        let served_from = edge::summarize(results.iter().filter_map(|result| result.edge_location.as_ref()));
+        if !served_from.is_empty() {
+            let pops: Vec<String> = served_from.iter().map(|(code, count)| format!("{} ({})", code, count)).collect();
+            status!("📍 Traffic served from {}", pops.join(", "));
+        }
+        let connections = pool::stats().since(&connections_before);
+        if connections.requests > 0 {
+            status!(
+                "🔁 {} connection(s) for {} request(s) ({:.0}% reused), DNS cache hit rate {:.0}%",
+                connections.connections,
+                connections.requests,
+                connections.reuse_rate() * 100.0,
+                connections.dns_hit_rate() * 100.0
+            );
         }
 
-        Ok(())
+        Ok(results)
     }
 
-    fn print_compact(&self, result: &DetectionResult) {
-        let url_short = if result.url.len() > 40 {
-            format!("{}...", &result.url[..37])
+    /// `--paths`: compare protection across several paths of each target
+    async fn scan_paths(&self, targets: &[String], path_list: &str, matches: &ArgMatches, format: &str) -> Result<()> {
+        let paths = if path_list.is_empty() {
+            paths::DEFAULT_PATHS.iter().map(|p| p.to_string()).collect()
+        } else if let Some(filename) = path_list.strip_prefix('@') {
+            let content = fs::read_to_string(filename)
+                .map_err(|e| anyhow!("Failed to read path list '{}': {}", filename, e))?;
+            paths::parse_path_list(&content)
         } else {
-            result.url.clone()
+            path_list.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect()
         };
 
-        match (&result.detected_waf, &result.detected_cdn) {
-            (Some(waf), Some(cdn)) if waf.name == cdn.name => {
-                println!("{:<40} {} ({:.1}%)", url_short, waf.name, waf.confidence * 100.0);
+        let payloads = matches.get_one::<usize>("path-payloads").copied().unwrap_or(1);
+        let mut scanner = PathScanner::new(&self.engine);
+        if payloads > 0 {
+            scanner = scanner.with_smoke_test(payloads)?;
+        }
+
+        let mut reports = Vec::new();
+        for target in targets {
+            reports.push(scanner.scan(target, &paths).await?);
+        }
+
+        match format {
+            "json" => println!("{}", serde_json::to_string_pretty(&reports)?),
+            "yaml" => println!("{}", serde_yaml::to_string(&reports)?),
+            _ => {
+                for report in &reports {
+                    self.print_path_report(report);
+                }
             }
-            (Some(waf), Some(cdn)) => {
-                println!("{:<40} WAF: {}, CDN: {} ({:.1}%/{:.1}%)", 
-                        url_short, waf.name, cdn.name, waf.confidence * 100.0, cdn.confidence * 100.0);
+        }
+        Ok(())
+    }
+
+    fn print_path_report(&self, report: &PathCoverageReport) {
+        println!();
+        println!("🧭 Per-path coverage for {}", report.base_url);
+        println!("{:<30} {:<14} {:<14} {:<10} ALLOWED CATEGORIES", "PATH", "WAF", "CDN", "BLOCKED");
+        for path in &report.paths {
+            if let Some(error) = &path.error {
+                println!("{:<30} scan failed ({:?}): {}", path.path, error.class, error.message);
+                continue;
             }
-            (Some(waf), None) => {
-                println!("{:<40} WAF: {} ({:.1}%)", url_short, waf.name, waf.confidence * 100.0);
+            println!("{:<30} {:<14} {:<14} {:<10} {}",
+                     path.path,
+                     path.detected_waf.as_deref().unwrap_or("-"),
+                     path.detected_cdn.as_deref().unwrap_or("-"),
+                     path.effectiveness_percentage.map(|e| format!("{:.0}%", e)).unwrap_or_else(|| "-".to_string()),
+                     if path.allowed_categories.is_empty() { "-".to_string() } else { path.allowed_categories.join(", ") });
+        }
+
+        println!();
+        if report.differences.is_empty() {
+            println!("✅ All paths are protected the same way");
+        } else {
+            println!("⚠️  Protection differs between paths:");
+            for difference in &report.differences {
+                println!("   • {}", difference);
             }
-            (None, Some(cdn)) => {
-                println!("{:<40} CDN: {} ({:.1}%)", url_short, cdn.name, cdn.confidence * 100.0);
+        }
+    }
+
+    /// `waf-detect enum <domain>`: enumerate subdomains and scan each of them
+    /// Serve scans for a coordinator until interrupted
+    async fn run_agent(&self, matches: &ArgMatches) -> Result<()> {
+        let region = matches.get_one::<String>("region").expect("region is required");
+        let listen = matches.get_one::<String>("listen").map(String::as_str).unwrap_or(agents::DEFAULT_LISTEN);
+        let mut agent = Agent::new(self.engine.clone(), region.clone()).with_quiet(output::is_quiet());
+        if let Some(token) = matches.get_one::<String>("token") {
+            agent = agent.with_token(token.clone());
+        }
+        agent.serve(listen).await
+    }
+
+    /// Agents from `--agent` and `--agents`, plus direct and `--vantage-proxy` scans
+    /// with `--compare-vantage`, or `None` to scan locally
+    fn coordinator(&self, matches: &ArgMatches) -> Result<Option<Coordinator>> {
+        let mut endpoints = Vec::new();
+        if let Some(path) = matches.get_one::<String>("agents") {
+            endpoints.extend(agents::load_agents(path)?);
+        }
+        for spec in matches.get_many::<String>("agent").into_iter().flatten() {
+            endpoints.push(AgentEndpoint::parse(spec)?);
+        }
+        let compare = matches.get_flag("compare-vantage");
+        if endpoints.is_empty() && !compare {
+            return Ok(None);
+        }
+        if let Some(token) = matches.get_one::<String>("agent-token") {
+            for endpoint in endpoints.iter_mut().filter(|endpoint| endpoint.token.is_none()) {
+                endpoint.token = Some(token.clone());
             }
-            (None, None) => {
-                println!("{:<40} Not Detected", url_short);
+        }
+        let mut coordinator = Coordinator::new(endpoints);
+        if compare {
+            let proxies: Vec<String> = matches.get_many::<String>("vantage-proxy").into_iter().flatten().cloned().collect();
+            coordinator = coordinator.with_local(self.engine.clone()).with_proxies(proxies);
+            if coordinator.vantage_count() < 2 {
+                return Err(anyhow!("--compare-vantage needs at least one --vantage-proxy, --agent or --agents"));
             }
         }
+        Ok(Some(coordinator))
     }
 
-    fn print_table_format(&self, result: &DetectionResult, debug: bool) {
-        if debug {
-            self.print_debug_info(result);
+    /// Scan each target from every agent and report how the regions differ
+    async fn scan_from_agents(&self, coordinator: &Coordinator, targets: &[String], format: &str) -> Result<()> {
+        let mut reports = Vec::new();
+        for target in targets {
+            status!("🛰️  Scanning {} from {} vantage points...", target, coordinator.vantage_count());
+            reports.push(coordinator.scan(target, Some(self.engine.options())).await);
         }
 
-        // Clean table format (reuse from existing CLI)
-        println!("┌─────────────────────────────────────────────────────────────────────────┐");
-        println!("│                            WAF/CDN Detection Results                    │");
-        println!("├─────────────────────────────────────────────────────────────────────────┤");
-        
-        // URL (truncate if too long)
-        let url_display = if result.url.len() > 67 {
-            format!("{}...", &result.url[..64])
-        } else {
-            result.url.clone()
+        match format {
+            "json" => println!("{}", serde_json::to_string_pretty(&reports)?),
+            "yaml" => println!("{}", serde_yaml::to_string(&reports)?),
+            _ => {
+                for report in &reports {
+                    self.print_vantage_report(report);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn print_vantage_report(&self, report: &VantageReport) {
+        println!("\n🌍 {}", report.url);
+        for scan in &report.regions {
+            let summary = match (&scan.result, &scan.error) {
+                (Some(_), _) if scan.providers().is_empty() => "no WAF or CDN detected".to_string(),
+                (Some(_), _) => scan.providers().join(" → "),
+                (None, error) => format!("failed: {}", error.as_deref().unwrap_or("no result")),
+            };
+            let blocked = if scan.blocked() { " (blocked)" } else { "" };
+            let edge = scan
+                .result
+                .as_ref()
+                .and_then(|result| result.edge_location.as_ref())
+                .map(|edge| format!(" @ {}", edge.label()))
+                .unwrap_or_default();
+            println!("   {:<16} {}{}{}", scan.region, summary, edge, blocked);
+        }
+        if report.consistent() {
+            println!("   ✅ All regions agree");
+        }
+        for difference in &report.differences {
+            println!("   ⚠️  {}", difference);
+        }
+        let expected = match report.majority_waf() {
+            Some(Some(waf)) => format!("most regions get {}", waf),
+            Some(None) => "most regions get no WAF".to_string(),
+            None => "regions disagree".to_string(),
         };
-        println!("│ URL: {:<67} │", url_display);
-        println!("├─────────────────────────────────────────────────────────────────────────┤");
-        
-        // WAF Detection
-        if let Some(waf_detection) = &result.detected_waf {
-            println!("│ WAF: {:<20} Confidence: {:<6.1}%                    │", 
-                    waf_detection.name, waf_detection.confidence * 100.0);
+        for route in report.geo_routing.iter().filter(|route| route.divergent) {
+            let edge = route.edge.as_ref().map(|edge| format!(" via {}", edge.label())).unwrap_or_default();
+            println!("   🧭 {} is served by {}{} ({})", route.region, route.waf.as_deref().unwrap_or("no WAF"), edge, expected);
+        }
+    }
+
+    async fn run_enum(&self, matches: &ArgMatches) -> Result<()> {
+        let domain = matches.get_one::<String>("domain").expect("domain is required");
+        let domain = Url::parse(domain)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_string()))
+            .unwrap_or_else(|| domain.clone());
+
+        let mut enumerator = SubdomainEnumerator::new()?
+            .with_certificate_transparency(!matches.get_flag("no-ct"))
+            .with_ct_source(self.engine.registry().ct_source());
+        if let Some(wordlist) = matches.get_one::<String>("wordlist") {
+            let content = fs::read_to_string(wordlist)
+                .map_err(|e| anyhow!("Failed to read wordlist '{}': {}", wordlist, e))?;
+            enumerator = enumerator.with_wordlist(content.lines().map(|l| l.to_string()).collect());
+        }
+        if let Some(limit) = matches.get_one::<usize>("limit") {
+            enumerator = enumerator.with_limit(*limit);
+        }
+        let workers = matches.get_one::<usize>("workers").copied().unwrap_or(3);
+
+        let json = matches.get_flag("json");
+        status!("🔎 Enumerating subdomains of {}...", domain);
+        let matrix = enumeration::scan_domain(&self.engine, &enumerator, &domain, workers).await?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&matrix)?);
         } else {
-            println!("│ WAF: Not Detected                                                      │");
+            self.print_coverage_matrix(&matrix);
         }
-        
-        // CDN Detection
-        if let Some(cdn_detection) = &result.detected_cdn {
-            println!("│ CDN: {:<20} Confidence: {:<6.1}%                    │", 
-                    cdn_detection.name, cdn_detection.confidence * 100.0);
+        Ok(())
+    }
+
+    /// `waf-detect analyze traffic.har`: offline per-host detection over captured traffic
+    async fn run_analyze(&self, matches: &ArgMatches) -> Result<()> {
+        let path = matches.get_one::<String>("file").expect("file is required");
+        let captured = capture::load_capture(path)?;
+        let mut report = capture::analyze_captured(&self.engine, path, captured.responses, captured.skipped).await?;
+        report.tls = captured.tls;
+
+        if matches.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&report)?);
         } else {
-            println!("│ CDN: Not Detected                                                      │");
+            self.print_traffic_report(&report);
         }
-        
-        println!("├─────────────────────────────────────────────────────────────────────────┤");
-        println!("│ Detection Time: {:<8} ms                                          │", 
-                result.detection_time_ms);
-        
-        if !result.evidence_map.is_empty() {
-            println!("├─────────────────────────────────────────────────────────────────────────┤");
-            println!("│ Evidence Summary:                                                       │");
-            
-            for (provider_name, evidence_list) in &result.evidence_map {
-                if !evidence_list.is_empty() {
-                    println!("│ • {:<20} Evidence Count: {:<3}                          │", 
-                            provider_name, evidence_list.len());
-                    
-                    for (i, evidence) in evidence_list.iter().enumerate() {
-                        if i < 3 {
-                            let desc = if evidence.description.len() > 45 {
-                                format!("{}...", &evidence.description[..42])
-                            } else {
-                                evidence.description.clone()
-                            };
-                            println!("│   - {:<45} ({:.0}%) │", desc, evidence.confidence * 100.0);
-                            if !evidence.raw_data.is_empty() && evidence.raw_data.len() <= 60 {
-                                println!("│     Data: {:<57} │", evidence.raw_data);
-                            }
-                        }
-                    }
-                    
-                    if evidence_list.len() > 3 {
-                        println!("│   ... and {} more evidence items                             │", 
-                                evidence_list.len() - 3);
-                    }
-                }
+        Ok(())
+    }
+
+    fn print_traffic_report(&self, report: &TrafficReport) {
+        println!();
+        println!("🗂️  Traffic analysis of {} ({} responses, {} hosts{})",
+                 report.source,
+                 report.responses,
+                 report.hosts.len(),
+                 if report.skipped > 0 { format!(", {} entries skipped", report.skipped) } else { String::new() });
+        println!("{:<40} {:>5} {:<14} {:<14} OTHER PROVIDERS", "HOST", "RESP", "WAF", "CDN");
+        for host in &report.hosts {
+            let shown: Vec<&str> = [&host.waf, &host.cdn].iter().filter_map(|d| d.as_ref().map(|d| d.name.as_str())).collect();
+            let others: Vec<String> = host.providers
+                .iter()
+                .filter(|p| !shown.contains(&p.name.as_str()))
+                .map(|p| format!("{} ({:.0}%)", p.name, p.confidence * 100.0))
+                .collect();
+            println!("{} {:>5} {:<14} {:<14} {}",
+                     utils::pad_display(&host.host, 40),
+                     host.responses,
+                     host.waf.as_ref().map(|d| d.name.as_str()).unwrap_or("-"),
+                     host.cdn.as_ref().map(|d| d.name.as_str()).unwrap_or("-"),
+                     if others.is_empty() { "-".to_string() } else { others.join(", ") });
+        }
+
+        if !report.tls.is_empty() {
+            println!();
+            println!("🔒 TLS connections (handshake only, no response content visible)");
+            println!("{:<40} {:>5} {:<16} {:<10} ADDRESSES", "SERVER NAME", "CONN", "VERSION", "ALPN");
+            for host in &report.tls {
+                println!("{} {:>5} {:<16} {:<10} {}",
+                         utils::pad_display(&host.host, 40),
+                         host.connections,
+                         if host.versions.is_empty() { "-".to_string() } else { host.versions.join(", ") },
+                         if host.alpn.is_empty() { "-".to_string() } else { host.alpn.join(", ") },
+                         host.addresses.join(", "));
+            }
+        }
+    }
+
+    /// `waf-detect validate --ground-truth truth.yaml`: measure accuracy and calibration on labelled targets
+    async fn run_validate(&self, matches: &ArgMatches) -> Result<()> {
+        let path = matches.get_one::<String>("ground-truth").expect("ground-truth is required");
+        let ground_truth = testing::load_ground_truth(path)?;
+        let json = matches.get_flag("json");
+        status!("🧪 Validating against {} labelled targets from {}...", ground_truth.len(), path);
+
+        let report = ValidationFramework::with_ground_truth(self.engine.clone(), ground_truth)
+            .validate_all()
+            .await?;
+
+        if let Some(weights_path) = matches.get_one::<String>("weights-out") {
+            self.write_weight_suggestions(&report, weights_path)?;
+        }
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            report.print_report();
+        }
+        Ok(())
+    }
+
+    /// `waf-detect benchmark`: scan the curated target list and print a markdown scoreboard
+    async fn run_benchmark(&self, matches: &ArgMatches, benchmark_matches: &ArgMatches) -> Result<()> {
+        let dir = self.data_dir(matches);
+        if benchmark_matches.get_flag("refresh") {
+            let dir = dir.as_deref().ok_or_else(|| anyhow!("No data directory (pass --data-dir or set WAF_DETECTOR_DATA_DIR)"))?;
+            let url = benchmark_matches
+                .get_one::<String>("url")
+                .cloned()
+                .or_else(|| std::env::var(testing::benchmark::URL_VARIABLE).ok())
+                .filter(|url| !url.trim().is_empty())
+                .ok_or_else(|| anyhow!("no benchmark target list url: pass --url or set {}", testing::benchmark::URL_VARIABLE))?;
+            status!("📥 Downloading benchmark targets from {}", url);
+            let (list, path) = testing::benchmark::refresh(dir, &url).await?;
+            status!("✅ Benchmark targets {} installed ({} target(s)) in {}", list.version, list.targets.len(), path.display());
+        }
+        let targets = match benchmark_matches.get_one::<String>("targets") {
+            Some(path) => BenchmarkTargets::load(path)?,
+            None => BenchmarkTargets::installed_or_bundled(dir.as_deref())?,
+        };
+        status!("🏁 Benchmarking {} target(s) from target list {}...", targets.targets.len(), targets.version);
+
+        let report = Benchmark::new(self.engine.clone(), targets)
+            .run(|case| {
+                let mark = if case.passed() { "✅" } else { "❌" };
+                status!("{} {} ({} ms)", mark, case.url, case.duration_ms);
+            })
+            .await;
+        let markdown = report.to_markdown();
+        if let Some(path) = benchmark_matches.get_one::<String>("output") {
+            fs::write(path, &markdown).map_err(|e| anyhow!("Failed to write scoreboard '{}': {}", path, e))?;
+            status!("📝 Wrote the scoreboard to {}", path);
+        }
+        if benchmark_matches.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{}", markdown);
+        }
+
+        if let Some(&min) = benchmark_matches.get_one::<f64>("min-accuracy") {
+            if report.accuracy * 100.0 < min {
+                eprintln!("❌ Benchmark accuracy {:.1}% is below --min-accuracy {}%", report.accuracy * 100.0, min);
+                finish_tape();
+                std::process::exit(policy::EXIT_POLICY_VIOLATION);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_weight_suggestions(&self, report: &ValidationReport, path: &str) -> Result<()> {
+        let config = ScoringConfig {
+            evidence_weights: report.adjusted_weights(&self.engine.registry().advanced_scoring()),
+            ..ScoringConfig::default()
+        };
+        fs::write(path, serde_yaml::to_string(&config)?)
+            .map_err(|e| anyhow!("Failed to write weights '{}': {}", path, e))?;
+        status!("⚖️  Wrote {} suggested weight adjustments to {} (load with --scoring)", config.evidence_weights.len(), path);
+        Ok(())
+    }
+
+    fn print_coverage_matrix(&self, matrix: &CoverageMatrix) {
+        println!();
+        println!("🗺️  WAF coverage for {} ({} subdomains)", matrix.domain, matrix.rows.len());
+        println!("{:<40} {:<8} {:<14} {:<14} STATUS", "SUBDOMAIN", "SOURCE", "WAF", "CDN");
+        for row in &matrix.rows {
+            let source = match row.source {
+                enumeration::SubdomainSource::Apex => "apex",
+                enumeration::SubdomainSource::Wordlist => "wordlist",
+                enumeration::SubdomainSource::CertificateTransparency => "ct",
+            };
+            let status = match (&row.error, row.protected) {
+                (Some(error), _) => format!("scan failed ({:?})", error.class),
+                (None, true) => "✅ protected".to_string(),
+                (None, false) => "⚠️  no WAF".to_string(),
+            };
+            println!("{} {:<8} {:<14} {:<14} {}",
+                     utils::pad_display(&row.subdomain, 40),
+                     source,
+                     row.waf.as_deref().unwrap_or("-"),
+                     row.cdn.as_deref().unwrap_or("-"),
+                     status);
+        }
+
+        let unprotected = matrix.unprotected();
+        println!();
+        if unprotected.is_empty() {
+            println!("✅ Every reachable subdomain is behind a WAF");
+        } else {
+            println!("⚠️  {} subdomain(s) bypass the WAF entirely:", unprotected.len());
+            for row in unprotected {
+                println!("   • {}", row.subdomain);
+            }
+        }
+    }
+
+    fn print_compact(&self, result: &DetectionResult) {
+        let url_short = utils::pad_display(&utils::truncate_display(&result.url, 40), 40);
+
+        if let Some(error) = &result.error {
+            println!("{} Scan failed ({:?}): {}", url_short, error.class, error.message);
+            return;
+        }
+
+        match (&result.detected_waf, &result.detected_cdn) {
+            (Some(waf), Some(cdn)) if waf.name == cdn.name => {
+                println!("{} {} ({:.1}%)", url_short, waf.name, waf.confidence * 100.0);
+            }
+            (Some(waf), Some(cdn)) => {
+                println!("{} WAF: {}, CDN: {} ({:.1}%/{:.1}%)", 
+                        url_short, waf.name, cdn.name, waf.confidence * 100.0, cdn.confidence * 100.0);
+            }
+            (Some(waf), None) => {
+                println!("{} WAF: {} ({:.1}%)", url_short, waf.name, waf.confidence * 100.0);
+            }
+            (None, Some(cdn)) => {
+                println!("{} CDN: {} ({:.1}%)", url_short, cdn.name, cdn.confidence * 100.0);
+            }
+            (None, None) => match &result.detected_bot_manager {
+                Some(bot) => println!("{} Bot: {} ({:.1}%)", url_short, bot.name, bot.confidence * 100.0),
+                None => println!("{} Not Detected", url_short),
+            },
+        }
+        if let Some(consensus) = &result.consensus {
+            if !consensus.unanimous() {
+                println!("    ↳ runs disagree: {}", consensus
+                    .providers
+                    .iter()
+                    .map(|p| format!("{} {}/{}", p.name, p.seen_in, consensus.runs))
+                    .collect::<Vec<_>>()
+                    .join(", "));
+            }
+        }
+        if let Some(explanation) = &result.explanation {
+            for paragraph in explanation.lines() {
+                println!("    ↳ {}", paragraph);
+            }
+        }
+    }
+
+    fn print_features(&self, table: &mut BoxTable, features: &[crate::ProviderFeature]) {
+        for feature in features {
+            table.row(format!("  + {}: {} ({:.0}%)", feature.category, feature.value, feature.confidence * 100.0));
+        }
+    }
+
+    fn print_table_format(&self, result: &DetectionResult, debug: bool) {
+        if debug {
+            self.print_debug_info(result);
+        }
+
+        let mut table = BoxTable::new("WAF/CDN Detection Results");
+        table.row(format!("URL: {}", result.url));
+        if let Some(host) = &result.idn {
+            table.row(format!("Host: {} ({})", host.unicode, host.ascii));
+        }
+        table.separator();
+
+        if let Some(error) = &result.error {
+            table.row(format!("Scan failed: {:?}", error.class));
+            print!("{}", table.render());
+            println!("   {}", error.message);
+            return;
+        }
+        
+        // WAF Detection
+        if let Some(waf_detection) = &result.detected_waf {
+            table.row(format!("WAF: {:<20} Confidence: {:.1}%", waf_detection.name, waf_detection.confidence * 100.0));
+            self.print_features(&mut table, &waf_detection.features);
+        } else {
+            table.row("WAF: Not Detected");
+        }
+        
+        // CDN Detection
+        if let Some(cdn_detection) = &result.detected_cdn {
+            table.row(format!("CDN: {:<20} Confidence: {:.1}%", cdn_detection.name, cdn_detection.confidence * 100.0));
+            self.print_features(&mut table, &cdn_detection.features);
+        } else {
+            table.row("CDN: Not Detected");
+        }
+
+        if let Some(bot_detection) = &result.detected_bot_manager {
+            table.row(format!("Bot: {:<20} Confidence: {:.1}%", bot_detection.name, bot_detection.confidence * 100.0));
+            self.print_features(&mut table, &bot_detection.features);
+        }
+
+        if result.detected_stack.len() > 1 {
+            let chain: Vec<String> = result.detected_stack
+                .iter()
+                .map(|d| format!("{} ({})", d.name, d.layer.map(|l| l.to_string()).unwrap_or_default()))
+                .collect();
+            table.row(format!("Stack: {}", chain.join(" → ")));
+        }
+
+        if let Some(edge) = &result.edge_location {
+            table.row(format!("Edge Location: {} via {}", edge.label(), edge.provider));
+        }
+        if let Some(challenge) = &result.bot_challenge {
+            let kind = if challenge.interstitial { format!("{} (interstitial)", challenge.kind) } else { challenge.kind.to_string() };
+            table.row(format!("Bot Challenge: {} {}", challenge.vendor, kind));
+        }
+        if let Some(origin) = &result.origin_bypass {
+            if origin.candidates.is_empty() {
+                table.row("Origin: no candidate origin addresses found");
+            } else if origin.bypassable() {
+                for probe in origin.bypasses() {
+                    table.row(format!(
+                        "⚠️  Origin {} ({}) serves the site without the WAF (HTTP {})",
+                        probe.candidate.ip,
+                        probe.candidate.source,
+                        probe.status.unwrap_or(0)
+                    ));
+                }
+            } else {
+                table.row(format!("Origin: {} candidates checked, none bypasses the WAF", origin.candidates.len()));
+            }
+        }
+        if let Some(session) = &result.session {
+            let stateful = session.stateful_status.map_or_else(|| "failed".to_string(), |status| status.to_string());
+            let verdict = if session.differs() { "⚠️  returning clients are treated differently" } else { "same answer" };
+            table.row(format!("Session: HTTP {} without cookies, {} with them ({})", session.stateless_status, stateful, verdict));
+            if !session.cookies.is_empty() {
+                table.row(format!("  Cookies: {}", session.cookies.join(", ")));
+            }
+        }
+        if let Some(fallback) = &result.browser_fallback {
+            match (&fallback.error, fallback.solved) {
+                (Some(error), _) => table.row(format!("Browser: fallback failed ({})", error)),
+                (None, true) => table.row(format!("Browser: detected past the challenge ({} ms)", fallback.duration_ms)),
+                (None, false) => table.row(format!("Browser: challenge not cleared within {} ms", fallback.duration_ms)),
+            };
+            if !fallback.cookies.is_empty() {
+                table.row(format!("  Cookies: {}", fallback.cookies.join(", ")));
+            }
+        }
+        
+        if let Some(ruleset) = &result.ruleset_info {
+            let mut summary = ruleset.ruleset.clone().unwrap_or_else(|| "unknown".to_string());
+            if let Some(generation) = &ruleset.generation {
+                summary.push_str(&format!(" {}", generation));
+            }
+            if let Some(level) = ruleset.paranoia_level {
+                summary.push_str(&format!(" (PL{})", level));
+            }
+            table.row(format!("Ruleset: {}", summary));
+        }
+
+        if let Some(mode) = &result.waf_mode {
+            table.row(format!("WAF Mode: {} ({:.0}% confidence)", mode.mode, mode.confidence * 100.0));
+            for category in &mode.category_modes {
+                table.row(format!(
+                    "  {:<24} {:<11} {}/{} blocked",
+                    category.payload_type.to_string(), category.mode.to_string(), category.blocked, category.tested
+                ));
+            }
+        }
+
+        if let Some(rate_limit) = &result.rate_limit {
+            let summary = match (rate_limit.threshold_rps, rate_limit.limit_status) {
+                (Some(rps), Some(status)) => format!(
+                    "HTTP {} at {} req/s after {} requests", status, rps, rate_limit.requests_before_limit.unwrap_or(0)
+                ),
+                _ => format!("not limited ({} requests)", rate_limit.requests_sent),
+            };
+            table.row(format!("Rate Limit: {}", summary));
+            if rate_limit.limited {
+                let ban = rate_limit.ban_duration_secs.map(|s| format!("{}s", s)).unwrap_or_else(|| "not lifted".to_string());
+                table.row(format!("  Scope: {:?}, ban: {}", rate_limit.scope, ban));
+            }
+        }
+
+        if let Some(dual_stack) = &result.dual_stack {
+            let describe = |scan: &Option<crate::dns::FamilyScan>| match scan {
+                None => "no address".to_string(),
+                Some(scan) if scan.error.is_some() => format!("failed: {}", scan.error.as_deref().unwrap_or_default()),
+                Some(scan) => format!("HTTP {}, WAF: {}", scan.status.unwrap_or(0), scan.waf.as_deref().unwrap_or("none")),
+            };
+            table.row(format!("IPv4: {}", describe(&dual_stack.ipv4)));
+            table.row(format!("IPv6: {}", describe(&dual_stack.ipv6)));
+            if dual_stack.ipv6_bypasses_waf {
+                table.row("⚠️  IPv6 path bypasses the WAF (AAAA record likely points at the origin)");
+            }
+        }
+
+        if let Some(consensus) = &result.consensus {
+            let failed = if consensus.failed_runs > 0 { format!(", {} failed", consensus.failed_runs) } else { String::new() };
+            let agreement = if consensus.unanimous() { "unanimous" } else { "runs disagree" };
+            table.row(format!("Consensus: {} runs{} ({})", consensus.runs, failed, agreement));
+            for provider in &consensus.providers {
+                table.row(format!(
+                    "  {:<20} seen {}/{}  {:.1}% ± {:.1}%",
+                    provider.name,
+                    provider.seen_in,
+                    consensus.runs,
+                    provider.mean * 100.0,
+                    provider.std_dev() * 100.0
+                ));
+            }
+        }
+        
+        table.separator();
+        table.row(format!("Detection Time: {} ms", result.detection_time_ms));
+        
+        if !result.evidence_map.is_empty() {
+            table.separator();
+            table.row("Evidence Summary:");
+            
+            for (provider_name, evidence_list) in &result.evidence_map {
+                if !evidence_list.is_empty() {
+                    table.row(format!("• {:<20} Evidence Count: {}", provider_name, evidence_list.len()));
+                    
+                    for evidence in evidence_list.iter().take(3) {
+                        table.row(format!(
+                            "  - {} ({:.0}%)",
+                            utils::pad_display(&utils::truncate_display(&evidence.description, 45), 45),
+                            evidence.confidence * 100.0
+                        ));
+                        if !evidence.raw_data.is_empty() && utils::display_width(&evidence.raw_data) <= 60 {
+                            table.row(format!("    Data: {}", evidence.raw_data));
+                        }
+                    }
+                    
+                    if evidence_list.len() > 3 {
+                        table.row(format!("  ... and {} more evidence items", evidence_list.len() - 3));
+                    }
+                }
+            }
+        }
+        
+        print!("{}", table.render());
+        if let Some(explanation) = &result.explanation {
+            for paragraph in explanation.lines() {
+                println!("📝 {}", paragraph);
             }
         }
-        
-        println!("└─────────────────────────────────────────────────────────────────────────┘");
     }
 
     fn print_debug_info(&self, result: &DetectionResult) {
@@ -318,6 +1376,17 @@ impl SimpleCliApp {
         println!("URL: {}", result.url);
         println!("Detection Time: {}ms", result.detection_time_ms);
         println!("Timestamp: {}", result.metadata.timestamp.format("%Y-%m-%d %H:%M:%S UTC"));
+        println!("Version: {}", result.metadata.version);
+        if let Some(truncation) = &result.metadata.body_truncation {
+            println!("Body: truncated to {} of {} bytes read", truncation.kept_bytes, truncation.read_bytes);
+        }
+        if let Some(bundle) = &result.metadata.signature_bundle {
+            let rules: usize = result.metadata.pattern_counts.values().sum();
+            println!("Signatures: {} ({} rule(s) across {} provider(s))", bundle, rules, result.metadata.pattern_counts.len());
+        }
+        if result.metadata.retries > 0 {
+            println!("Retries: {}", result.metadata.retries);
+        }
         println!();
         
         println!("🔍 Provider Scores:");
@@ -329,10 +1398,74 @@ impl SimpleCliApp {
             }
         }
         println!();
+
+        if !result.confidence_details.is_empty() {
+            println!("📐 Confidence Details:");
+            let mut details: Vec<_> = result.confidence_details.iter().collect();
+            details.sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(b.0)));
+            for (provider, detail) in details {
+                println!("  {}: {:.1}% ({:?}), {} positive / {} negative",
+                         provider, detail.score * 100.0, detail.level,
+                         detail.positive_evidence_count, detail.negative_evidence_count);
+                let mut breakdown: Vec<_> = detail.evidence_breakdown.iter().filter(|(_, score)| **score > 0.0).collect();
+                breakdown.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal));
+                if !breakdown.is_empty() {
+                    let parts: Vec<String> = breakdown.iter().map(|(category, score)| format!("{:?} {:.3}", category, score)).collect();
+                    println!("    Breakdown: {}", parts.join(", "));
+                }
+                for line in detail.explanation.lines().filter(|line| !line.trim().is_empty()) {
+                    println!("    {}", line);
+                }
+                if !detail.missing_evidence.is_empty() {
+                    println!("    Missing: {}", detail.missing_evidence.join(", "));
+                }
+            }
+            println!();
+        }
+
+        if !result.negative_evidence.is_empty() {
+            println!("🚫 Negative Evidence:");
+            for (provider, hits) in &result.negative_evidence {
+                println!("  {}:", provider);
+                for hit in hits {
+                    println!("    {}: {} — {} (score ×{:.1})", hit.header, hit.value, hit.reason, hit.penalty);
+                }
+            }
+            println!();
+        }
+        
+        println!("⏱️  Diagnostics ({} requests total):", result.diagnostics.total_requests);
+        if result.diagnostics.techniques.is_empty() {
+            println!("  No providers or analyzers ran");
+        } else {
+            let mut techniques: Vec<_> = result.diagnostics.techniques.iter().collect();
+            techniques.sort_by_key(|t| std::cmp::Reverse(t.duration_ms));
+            for technique in techniques {
+                println!("  {:<20} {:>6}ms  {:>3} requests{}",
+                         technique.name,
+                         technique.duration_ms,
+                         technique.requests,
+                         technique.error.as_ref().map(|e| format!("  ❌ {}", e)).unwrap_or_default());
+            }
+        }
+        println!();
         
         println!("📝 Evidence Details:");
         for (provider, evidence_list) in &result.evidence_map {
-            if !evidence_list.is_empty() {
+            if let Some(groups) = result.evidence_groups.get(provider) {
+                // Everything the provider reported, not just the condensed list
+                println!("  {}:", provider);
+                for group in groups {
+                    println!("    [{}] {} item(s)", group.method, group.count());
+                    for (i, evidence) in std::iter::once(&group.primary).chain(&group.related).enumerate() {
+                        let marker = if i == 0 { "★" } else { "-" };
+                        println!("      {} {} (Confidence: {:.1}%)", marker, evidence.description, evidence.confidence * 100.0);
+                        println!("        Data: {}", evidence.raw_data);
+                        println!("        Signature: {}", evidence.signature_matched);
+                    }
+                }
+                println!();
+            } else if !evidence_list.is_empty() {
                 println!("  {}:", provider);
                 for (i, evidence) in evidence_list.iter().enumerate() {
                     println!("    {}. {} (Confidence: {:.1}%)", 
@@ -352,7 +1485,46 @@ impl SimpleCliApp {
             println!("    • The site uses a WAF/CDN not supported by this tool");
             println!("    • The WAF/CDN is configured to hide its presence");
         }
-        
+
+        if let Some(preflight) = &result.metadata.preflight {
+            println!();
+            println!("🤖 Pre-flight:");
+            match &preflight.robots_txt {
+                Some(robots) => println!("  robots.txt: {} group(s), target path {}",
+                                         robots.groups.len(),
+                                         if preflight.target_allowed { "allowed" } else { "disallowed" }),
+                None => println!("  robots.txt: not found"),
+            }
+            match &preflight.security_txt {
+                Some(security) if !security.contact.is_empty() => {
+                    println!("  security.txt contact: {}", security.contact.join(", "))
+                }
+                Some(_) => println!("  security.txt: present (no contact)"),
+                None => println!("  security.txt: not found"),
+            }
+            if preflight.probing_skipped {
+                println!("  Probing skipped: path disallowed by robots.txt");
+            }
+        }
+
+        if let Some(ruleset) = &result.ruleset_info {
+            println!();
+            println!("🧬 Ruleset Fingerprint ({}):", ruleset.vendor);
+            println!("  Ruleset: {}", ruleset.ruleset.as_deref().unwrap_or("unknown"));
+            if let Some(generation) = &ruleset.generation {
+                println!("  Generation: {}", generation);
+            }
+            if let Some(level) = ruleset.paranoia_level {
+                println!("  Paranoia level: {}", level);
+            }
+            if !ruleset.rule_ids.is_empty() {
+                println!("  Rule IDs: {}", ruleset.rule_ids.join(", "));
+            }
+            for probe in &ruleset.probes {
+                println!("  {:<16} {} {}", probe.name, probe.status, if probe.blocked { "blocked" } else { "passed" });
+            }
+        }
+
         println!("─────────────────────────────────────────────────────────────────────────────────────");
         println!();
     }
@@ -382,10 +1554,70 @@ impl SimpleCliApp {
         Ok(())
     }
 
-    async fn start_web_server(&self, port: u16) -> Result<()> {
-        println!("🌐 Starting WAF Detector Web Server...");
-        
-        let web_server = crate::web::WebServer::new(self.engine.clone());
+    /// `--auth-config`, plus keys from `--api-key` and the `--audit-log` path
+    fn web_auth(&self, matches: &ArgMatches) -> Result<AuthConfig> {
+        let mut auth = match matches.get_one::<String>("auth-config") {
+            Some(path) => AuthConfig::from_file(path)?,
+            None => AuthConfig::default(),
+        };
+        for key in matches.get_many::<String>("api-key").into_iter().flatten() {
+            auth.add_key(key);
+        }
+        if let Some(path) = matches.get_one::<String>("audit-log") {
+            auth.audit_log = Some(path.into());
+        }
+        Ok(auth)
+    }
+
+    /// `--api-only`: the localhost tool-integration API, no dashboard
+    async fn start_api_server(&self, matches: &ArgMatches) -> Result<()> {
+        let port = matches.get_one::<u16>("port").copied().unwrap_or(8080);
+        crate::web::WebServer::new(self.engine.clone())
+            .with_auth(self.web_auth(matches)?)?
+            .with_quiet(output::is_quiet())
+            .start_api_only(port)
+            .await
+    }
+
+    async fn start_web_server(&self, matches: &ArgMatches) -> Result<()> {
+        status!("🌐 Starting WAF Detector Web Server...");
+        let port = matches.get_one::<u16>("port").copied().unwrap_or(8080);
+        let mut web_server = crate::web::WebServer::new(self.engine.clone()).with_auth(self.web_auth(matches)?)?.with_quiet(output::is_quiet());
+        if let Some(path) = matches.get_one::<String>("schedules") {
+            web_server = web_server.with_schedule_store(ScheduleStore::open(path)?);
+            status!("🗓️  Scheduled scans stored in {}", path);
+        }
+        let exporters = self.exporters(matches)?;
+        if !exporters.is_empty() {
+            web_server = web_server.with_exporters(exporters);
+        }
+        if let Some(executor) = self.legacy_script_executor(matches)? {
+            web_server = web_server.with_script_executor(executor);
+        }
+        if let Some(path) = matches.get_one::<String>("projects") {
+            web_server = web_server.with_project_store(ProjectStore::open(path)?);
+            status!("📁 Projects stored in {}", path);
+        }
+        let decay = match matches.get_one::<u64>("history-half-life") {
+            Some(hours) => ConfidenceDecay::new(std::time::Duration::from_secs(hours * 3600)),
+            None => ConfidenceDecay::default(),
+        };
+        let history = match matches.get_one::<String>("history") {
+            #[cfg(feature = "sqlite")]
+            Some(path) => {
+                status!("🕘 Scan history stored in {}", path);
+                crate::web::history::HistoryStore::open(path)?
+            }
+            #[cfg(not(feature = "sqlite"))]
+            Some(path) => {
+                if matches.value_source("history") == Some(clap::parser::ValueSource::CommandLine) {
+                    status!("🕘 Scan history kept in memory; {} needs the `sqlite` feature", path);
+                }
+                crate::web::history::HistoryStore::in_memory()
+            }
+            None => crate::web::history::HistoryStore::in_memory(),
+        };
+        web_server = web_server.with_history_store(history.with_confidence_decay(decay));
         web_server.start(port).await?;
         
         Ok(())
@@ -398,6 +1630,14 @@ impl SimpleCliApp {
 
         let normalized_url = self.normalize_url(url)?;
 
+        // Without --fail-on the smoke test keeps failing below 50% effectiveness
+        let mut policy = self.fail_policy(matches);
+        if policy.is_empty() {
+            policy = FailPolicy::new(vec![FailCondition::Effectiveness(policy::DEFAULT_SMOKE_TEST_EFFECTIVENESS)]);
+        }
+        policy.validate_for_smoke_test()?;
+        let exporters = self.exporters(matches)?;
+
         // Parse custom headers
         let mut custom_headers = HashMap::new();
         if let Some(headers) = matches.get_many::<String>("headers") {
@@ -413,36 +1653,64 @@ impl SimpleCliApp {
         // Configure smoke test
         let mut config = SmokeTestConfig::default();
         config.custom_headers = custom_headers;
+        config.quiet = output::is_quiet();
+        config.color = output::use_color();
+        config.session = matches.get_flag("session");
+
+        if let Some(locations) = matches.get_many::<PayloadLocation>("payload-locations") {
+            config.payload_locations = locations.copied().collect();
+        }
 
         if matches.get_flag("aggressive") {
             config.include_advanced_payloads = true;
             config.delay_between_requests_ms = 50; // Faster for aggressive mode
         }
 
+        if let Some(concurrency) = matches.get_one::<usize>("concurrency") {
+            config.max_concurrent_requests = (*concurrency).max(1);
+        }
+
         // Create and run smoke test
-        let smoke_test = WafSmokeTest::new(config)?;
+        let mut smoke_test = WafSmokeTest::new(config)?;
+        if let Some(path) = matches.get_one::<String>("payloads") {
+            let corpus = PayloadCorpus::load(path)?;
+            status!("📚 Loaded {} payloads from {}", corpus.len(), path);
+            smoke_test = smoke_test.with_corpus(&corpus);
+        }
         
-        println!("🚀 Starting WAF Smoke Test...");
-        println!("═══════════════════════════════════════════════════════════════");
-        println!("📊 Test Type │ Payload                        │ Result       │ Code │ Time");
-        println!("─────────────┼────────────────────────────────┼──────────────┼──────┼──────");
+        status!("🚀 Starting WAF Smoke Test...");
+        status!("═══════════════════════════════════════════════════════════════");
+        status!("📊 Test Type │ Payload                        │ Result       │ Code │ Time");
+        status!("─────────────┼────────────────────────────────┼──────────────┼──────┼──────");
 
-        let result = smoke_test.run_test(&normalized_url).await?;
+        // Ctrl-C stops sending payloads; the completed part is still summarized and exported
+        let cancel = async {
+            let _ = tokio::signal::ctrl_c().await;
+        };
+        let result = smoke_test.run_test_until(&normalized_url, cancel).await?;
 
         // Print summary
         smoke_test.print_summary(&result);
 
-        // Export to JSON if requested
+        // Export if requested; --format wins over the file extension, JSON is the fallback
         if let Some(output_file) = matches.get_one::<String>("output") {
-            smoke_test.export_json(&result, output_file)?;
+            let format = matches.get_one::<ExportFormat>("format").copied()
+                .or_else(|| ExportFormat::from_path(output_file))
+                .unwrap_or_default();
+            smoke_test.export(&result, output_file, format)?;
+        }
+        exporters.smoke_tests(std::slice::from_ref(&result)).await;
+
+        if result.cancelled {
+            std::process::exit(policy::EXIT_INTERRUPTED);
         }
 
-        // Exit with non-zero code if effectiveness is low
-        if result.summary.effectiveness_percentage < 50.0 {
-            println!("\n⚠️  WARNING: Low WAF effectiveness detected ({:.1}%)", 
+        let report = policy.check_smoke_test(&result)?;
+        if report.violations.iter().any(|v| matches!(v.condition, FailCondition::Effectiveness(_))) {
+            eprintln!("\n⚠️  WARNING: Low WAF effectiveness detected ({:.1}%)",
                     result.summary.effectiveness_percentage);
-            std::process::exit(1);
         }
+        self.exit_on_policy(&report);
 
         Ok(())
     }
@@ -462,15 +1730,70 @@ DETECTION USAGE:
   waf-detect @urls.txt                         # Scan from file
   waf-detect cloudflare.com --json             # JSON output
 
+TECHNIQUE SELECTION (default: response headers + DNS only):
+  waf-detect site.com --timing --payload             # Opt in to timing and payload probing
+  waf-detect site.com --active-probes                # Opt in to provider active probes
+  waf-detect site.com --providers cloudflare,akamai  # Only run selected providers
+  waf-detect site.com --skip-dns --skip-block-page  # Skip default analyzers
+  waf-detect site.com --passive                      # Strict: one GET per target, no retries or redirects
+  waf-detect site.com --preflight                    # Record robots.txt and security.txt
+  waf-detect site.com --payload --respect-robots     # No probing on disallowed paths
+  waf-detect site.com --fingerprint                  # Ruleset generation / CRS paranoia level
+  waf-detect site.com --mode-analysis                # Blocking vs. monitoring per payload category
+  waf-detect site.com --ratelimit-probe              # Rate-limit threshold, ban duration and scope
+
+RATE LIMITING (default: 10 requests/second per host):
+  waf-detect site.com --timing --rps 2               # Slow down probing
+  waf-detect --smoke-test site.com --max-requests-per-host 50  # Cap total requests
+
 SMOKE TESTING:
   waf-detect --smoke-test cloudflare.com       # Test WAF effectiveness
   waf-detect --smoke-test example.com -o results.json  # Export results
+  waf-detect --smoke-test example.com -o report.html   # HTML report (or --format csv)
   waf-detect --smoke-test site.com -H "Authorization: Bearer token"  # Custom headers
   waf-detect --smoke-test site.com --aggressive  # More thorough testing
+  waf-detect --smoke-test site.com --concurrency 8  # Parallel payloads (Ctrl-C keeps partial results)
+  waf-detect --smoke-test site.com --payload-locations query,json,graphql  # Body payloads
+  waf-detect --smoke-test site.com --payload-locations query,cookie,path   # Header/cookie/path payloads
+  waf-detect --smoke-test site.com --payloads ./my-payloads/  # Custom payload corpus
+  waf-detect --combined site.com               # Detection plus effectiveness summary
 
 WEB SERVER:
   waf-detect --web                             # Start web server
   waf-detect --web --port 3000                 # Web server on port 3000
+  waf-detect --web --api-key "$KEY"            # Require X-API-Key / Bearer auth
+  waf-detect --web --auth-config auth.yaml     # Keys, users, rate limits, audit log
+
+PATH COVERAGE:
+  waf-detect site.com --paths                        # /, /api, /admin, /login, ...
+  waf-detect site.com --paths /,/api,/admin          # Explicit path list
+  waf-detect site.com --paths @paths.txt --json      # Path wordlist
+
+SUBDOMAIN COVERAGE:
+  waf-detect enum example.com                  # Wordlist + certificate transparency
+  waf-detect enum example.com --wordlist subs.txt --no-ct --json
+
+OFFLINE ANALYSIS:
+  waf-detect analyze traffic.har               # Per-host WAF/CDN report from a HAR capture
+  waf-detect analyze traffic.har --json
+  waf-detect analyze capture.pcap              # Packet capture: plaintext HTTP + TLS SNI (--features pcap)
+
+ACCURACY VALIDATION:
+  waf-detect validate --ground-truth truth.yaml                   # Precision/recall/Brier per provider
+  waf-detect validate --ground-truth truth.yaml --weights-out w.yaml  # Suggested scoring weights
+
+SCORING WEIGHTS:
+  waf-detect scoring dump > weights.yaml       # Export the default weights and thresholds
+  waf-detect example.com --scoring weights.yaml  # Scan with tuned weights
+
+CI GATES (exit 3 = policy violation, 4 = target could not be scanned):
+  waf-detect --fail-on no-waf prod.example.com                 # Fail the build if the WAF disappears
+  waf-detect --fail-on low-confidence<80 @targets.txt --compact
+  waf-detect --smoke-test --fail-on effectiveness<90 staging.example.com
+
+RECON INPUT:
+  waf-detect --from-nmap scan.xml --compact    # Every open 80/443 (and other HTTP) port from nmap -oX
+  waf-detect --from-masscan scan.json          # masscan -oJ output
 
 OTHER:
   waf-detect --list                            # List providers
@@ -484,6 +1807,42 @@ The tool automatically adds https:// if needed and supports both domain names an
                 .action(clap::ArgAction::Append)
                 .num_args(0..)
         )
+        .arg(
+            Arg::new("fail-on")
+                .long("fail-on")
+                .help("Exit non-zero when a condition holds: no-waf, low-confidence[<N], effectiveness<N (smoke test). Repeatable")
+                .value_name("CONDITION")
+                .value_parser(|value: &str| value.parse::<FailCondition>())
+                .action(clap::ArgAction::Append)
+                .conflicts_with("paths")
+        )
+        .arg(
+            Arg::new("from-nmap")
+                .long("from-nmap")
+                .visible_alias("from-masscan")
+                .help("Scan the open HTTP(S) ports found by Nmap (-oX XML) or masscan (-oJ JSON)")
+                .value_name("FILE")
+                .action(clap::ArgAction::Append)
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .help("Explain each detection in plain sentences: strongest signal, corroboration, contradictions (for reports)")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("merge-www")
+                .long("merge-www")
+                .help("Treat www.example.com and example.com as the same target when collapsing duplicates")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("keep-duplicates")
+                .long("keep-duplicates")
+                .help("Scan every target as given, even when several name the same site")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("merge-www")
+        )
         .arg(
             Arg::new("json")
                 .long("json")
@@ -518,57 +1877,912 @@ The tool automatically adds https:// if needed and supports both domain names an
                 .action(clap::ArgAction::SetTrue)
         )
         .arg(
-            Arg::new("web")
-                .long("web")
-                .short('w')
-                .help("Start web server mode with beautiful dashboard")
-                .action(clap::ArgAction::SetTrue)
+            Arg::new("providers")
+                .long("providers")
+                .help("Comma-separated list of providers to run (e.g. cloudflare,akamai)")
+                .value_name("NAMES")
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append)
         )
         .arg(
-            Arg::new("port")
-                .long("port")
-                .short('p')
-                .help("Port for web server (default: 8080)")
-                .value_name("PORT")
-                .value_parser(clap::value_parser!(u16))
-                .default_value("8080")
+            Arg::new("timing")
+                .long("timing")
+                .help("Enable timing analysis (sends extra requests)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["skip-timing", "passive", "passive-only"])
         )
         .arg(
-            Arg::new("list")
-                .long("list")
-                .help("List available detection providers")
+            Arg::new("ipv6")
+                .long("ipv6")
+                .help("Dual-stack scan: repeat the request over IPv4 and IPv6 and flag an IPv6 path that bypasses the WAF")
                 .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["ipv4-only", "passive"])
         )
         .arg(
-            Arg::new("smoke-test")
-                .long("smoke-test")
-                .help("Run comprehensive WAF effectiveness smoke test")
+            Arg::new("session")
+                .long("session")
+                .help("Keep a cookie session per scan so probes and smoke-test payloads return the cookies the target sets; compares first-time and returning clients")
                 .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
         )
         .arg(
-            Arg::new("output")
-                .long("output")
-                .short('o')
-                .help("Export results to JSON file")
+            Arg::new("record")
+                .long("record")
                 .value_name("FILE")
-                .requires("smoke-test")
+                .help("Record every HTTP exchange and DNS lookup of the scans to a JSON session file")
         )
         .arg(
-            Arg::new("headers")
-                .long("header")
-                .short('H')
-                .help("Custom headers for smoke test (format: 'Key: Value')")
-                .value_name("HEADER")
-                .action(clap::ArgAction::Append)
-                .requires("smoke-test")
+            Arg::new("replay")
+                .long("replay")
+                .value_name("FILE")
+                .help("Answer every HTTP request and DNS lookup from a session recorded with --record, without network access; scans the recorded targets when none are given")
+                .conflicts_with("record")
         )
         .arg(
-            Arg::new("aggressive")
-                .long("aggressive")
-                .help("Enable aggressive testing mode (more payloads, faster)")
+            Arg::new("origin-check")
+                .long("origin-check")
+                .help("Send the same benign request to candidate origin addresses (origin.*, direct.*, mail.*, ... and --origin-ip) and report origins reachable without the WAF")
                 .action(clap::ArgAction::SetTrue)
-                .requires("smoke-test")
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("origin-ip")
+                .long("origin-ip")
+                .help("Suspected origin address to include in the origin check (implies --origin-check); repeatable")
+                .value_name("IP")
+                .value_parser(clap::value_parser!(std::net::IpAddr))
+                .action(clap::ArgAction::Append)
+                .conflicts_with("passive")
         )
+        .arg(
+            Arg::new("browser-fallback")
+                .long("browser-fallback")
+                .help("On a JS challenge, load the page in headless Chrome and detect the stack behind the challenge (needs the `browser` feature)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("ipv4-only")
+                .long("ipv4-only")
+                .help("Only connect to IPv4 addresses")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("max-idle-per-host")
+                .long("max-idle-per-host")
+                .help("Idle connections kept open per host for later probes (default: 10)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+        )
+        .arg(
+            Arg::new("dns-cache-ttl")
+                .long("dns-cache-ttl")
+                .help("Seconds a resolved host is reused across requests and targets; 0 resolves for every new connection (default: 300)")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64))
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Results only: no banners or progress on stderr, compact lines unless --json/--yaml")
+                .action(clap::ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .help("Disable colored output (also honors the NO_COLOR environment variable)")
+                .action(clap::ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("log-level")
+                .long("log-level")
+                .help("Diagnostics written to stderr: error, warn, info, debug, trace or a filter like waf_detector::registry=debug (default: RUST_LOG, then warn)")
+                .value_name("LEVEL")
+                .global(true)
+        )
+        .arg(
+            Arg::new("log-json")
+                .long("log-json")
+                .help("Write diagnostics as JSON lines, with the scan and technique spans they came from")
+                .action(clap::ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("agent")
+                .long("agent")
+                .help("Scan from a remote agent instead of locally; repeat for each region")
+                .value_name("REGION=URL")
+                .action(clap::ArgAction::Append)
+        )
+        .arg(
+            Arg::new("agents")
+                .long("agents")
+                .help("YAML list of agents ({region, url, token}) to scan from")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("compare-vantage")
+                .long("compare-vantage")
+                .help("Scan directly, through each --vantage-proxy and from each agent, and diff the results")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("vantage-proxy")
+                .long("vantage-proxy")
+                .help("Proxy to compare against with --compare-vantage; repeat for several")
+                .value_name("URL")
+                .action(clap::ArgAction::Append)
+                .requires("compare-vantage")
+        )
+        .arg(
+            Arg::new("agent-token")
+                .long("agent-token")
+                .help("Bearer token for agents that have none of their own")
+                .value_name("TOKEN")
+        )
+        .arg(
+            Arg::new("protocol")
+                .long("protocol")
+                .help("Check ALPN (HTTP/2), Alt-Svc (HTTP/3) and QUIC support (extra TLS handshakes and a UDP packet)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["passive", "passive-only"])
+        )
+        .arg(
+            Arg::new("header-order")
+                .long("header-order")
+                .help("Fingerprint the order and casing of response headers (one extra raw HTTP/1.1 request)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["passive", "passive-only"])
+        )
+        .arg(
+            Arg::new("favicon")
+                .long("favicon")
+                .help("Hash /favicon.ico and linked icons (Shodan mmh3) and check edge-only static assets")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["passive", "passive-only"])
+        )
+        .arg(
+            Arg::new("cache-probe")
+                .long("cache-probe")
+                .help("Fingerprint the caching layers from repeated, conditional, range and cache debug requests under fresh cache keys (six extra requests)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["passive", "passive-only"])
+        )
+        .arg(
+            Arg::new("favicon-db")
+                .long("favicon-db")
+                .value_name("FILE")
+                .help("JSON list of {\"vendor\", \"hash\", \"description\"} favicon hashes to attribute")
+                .requires("favicon")
+        )
+        .arg(
+            Arg::new("ct")
+                .long("ct")
+                .help("Look the host up in certificate transparency logs: CDN issuers, shared SANs and certificate churn as supporting evidence")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("ct-url")
+                .long("ct-url")
+                .value_name("URL")
+                .help("crt.sh-compatible certificate transparency search service for --ct and enum (default: https://crt.sh)")
+                .global(true)
+        )
+        .arg(
+            Arg::new("pdns")
+                .long("pdns")
+                .value_name("SOURCE[:KEY]")
+                .help("Passive DNS history for former CNAMEs and origin candidates: securitytrails:KEY, virustotal:KEY or circl:USER:PASSWORD; without a key it is read from SECURITYTRAILS_API_KEY, VIRUSTOTAL_API_KEY or CIRCL_PDNS_CREDENTIALS; repeatable")
+                .action(clap::ArgAction::Append)
+        )
+        .arg(
+            Arg::new("intel")
+                .long("intel")
+                .value_name("SOURCE[:KEY]")
+                .help("Look resolved addresses up in Shodan or Censys (banners, WAF tags) and search them for origin candidates: shodan:KEY or censys:API_ID:SECRET; without a key it is read from SHODAN_API_KEY or CENSYS_CREDENTIALS; repeatable (needs the `intel` feature)")
+                .action(clap::ArgAction::Append)
+        )
+        .arg(
+            Arg::new("exporters")
+                .long("exporters")
+                .value_name("FILE")
+                .help("YAML/JSON file listing exporters (splunk_hec, syslog, findings) that receive scan results, smoke-test findings and scheduled-scan change events")
+                .global(true)
+        )
+        .arg(
+            Arg::new("payload")
+                .long("payload")
+                .help("Enable payload probing (sends XSS/SQLi test strings)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["skip-payload", "passive", "passive-only"])
+        )
+        .arg(
+            Arg::new("active-probes")
+                .long("active-probes")
+                .help("Enable provider-specific active probes")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["passive", "passive-only"])
+        )
+        .arg(
+            Arg::new("skip-timing")
+                .long("skip-timing")
+                .help("Deprecated: timing analysis only runs with --timing")
+                .action(clap::ArgAction::SetTrue)
+                .hide(true)
+        )
+        .arg(
+            Arg::new("skip-dns")
+                .long("skip-dns")
+                .help("Skip DNS CNAME analysis")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("skip-block-page")
+                .long("skip-block-page")
+                .help("Skip matching the response body against known WAF block pages")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("skip-payload")
+                .long("skip-payload")
+                .help("Deprecated: payload probing only runs with --payload")
+                .action(clap::ArgAction::SetTrue)
+                .hide(true)
+        )
+        .arg(
+            Arg::new("passive-only")
+                .long("passive-only")
+                .help("Deprecated: probing techniques only run when requested; see --passive for a one-request guarantee")
+                .action(clap::ArgAction::SetTrue)
+                .hide(true)
+        )
+        .arg(
+            Arg::new("passive")
+                .long("passive")
+                .help("Strict passive mode: send exactly one GET request per target, never retried or redirected and never attack-like probes")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("smoke-test")
+        )
+        .arg(
+            Arg::new("preflight")
+                .long("preflight")
+                .help("Fetch robots.txt and security.txt before scanning and include them in the results")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("respect-robots")
+                .long("respect-robots")
+                .help("Refuse timing/payload/active probing on paths disallowed by robots.txt (implies --preflight)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("fingerprint")
+                .long("fingerprint")
+                .help("Probe the detected WAF for its ruleset generation, CRS paranoia level and rule IDs")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("mode-analysis")
+                .long("mode-analysis")
+                .help("Probe the detected WAF with attack payloads and report per category whether it blocks or only monitors")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("ratelimit-probe")
+                .long("ratelimit-probe")
+                .help("Ramp up the request rate (at most 60 requests) to profile the target's rate limiting")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("passive")
+        )
+        .arg(
+            Arg::new("paths")
+                .long("paths")
+                .help("Scan several paths per target and compare their protection (comma-separated list or @file; default: common paths)")
+                .value_name("PATHS")
+                .num_args(0..=1)
+                .default_missing_value("")
+                .conflicts_with_all(["passive", "smoke-test"])
+        )
+        .arg(
+            Arg::new("path-payloads")
+                .long("path-payloads")
+                .help("Smoke payloads per category sent to each path with --paths (default: 1, 0 = detection only)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .requires("paths")
+        )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .help("Retries for transient network errors, with exponential backoff (default: 2)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u32))
+        )
+        .arg(
+            Arg::new("max-body-size")
+                .long("max-body-size")
+                .help("Body bytes read per response; longer bodies are cut off and flagged (metadata.body_truncation) (default: 2097152)")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(usize))
+        )
+        .arg(
+            Arg::new("body-tail")
+                .long("body-tail")
+                .help("Also keep the last BYTES of a cut-off body for body signatures (reads up to 8x --max-body-size)")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(usize))
+        )
+        .arg(
+            Arg::new("impersonate")
+                .long("impersonate")
+                .help("Send a real browser's headers with every request: chrome, chrome-android, firefox, firefox-android, safari, safari-ios, or rotate / rotate-mobile to switch per request")
+                .value_name("PROFILE")
+                .global(true)
+        )
+        .arg(
+            Arg::new("runs")
+                .long("runs")
+                .help("Run N independent detection passes per target and report their consensus, with variance")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+        )
+        .arg(
+            Arg::new("run-interval")
+                .long("run-interval")
+                .help("Seconds to wait between --runs passes, to catch different edges")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64))
+                .requires("runs")
+        )
+        .arg(
+            Arg::new("scoring")
+                .long("scoring")
+                .help("Override evidence weights, category multipliers and confidence thresholds from a YAML/JSON file")
+                .value_name("FILE")
+                .global(true)
+        )
+        .arg(
+            Arg::new("rps")
+                .long("rps")
+                .help("Maximum requests per second sent to each host (default: 10)")
+                .value_name("RPS")
+                .value_parser(parse_rps)
+        )
+        .arg(
+            Arg::new("max-requests-per-host")
+                .long("max-requests-per-host")
+                .help("Hard cap on the number of requests sent to each host")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+        )
+        .arg(
+            Arg::new("data-dir")
+                .long("data-dir")
+                .help("Directory holding installed signature bundles (default: $WAF_DETECTOR_DATA_DIR or ~/.local/share/waf-detector)")
+                .value_name("DIR")
+                .global(true)
+        )
+        .arg(
+            Arg::new("web")
+                .long("web")
+                .short('w')
+                .help("Start web server mode with beautiful dashboard")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("api-only")
+                .long("api-only")
+                .help("Serve only the tool-integration API on 127.0.0.1 (POST /api/passive with a raw response, POST /api/detect with a host), e.g. for a Burp or Caido extension")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("web")
+        )
+        .arg(
+            Arg::new("schedules")
+                .long("schedules")
+                .help("JSON file persisting the web server's scheduled scans (/api/schedules)")
+                .value_name("FILE")
+                .default_value("schedules.json")
+        )
+        .arg(
+            Arg::new("projects")
+                .long("projects")
+                .help("JSON file persisting the web server's projects (/api/projects)")
+                .value_name("FILE")
+                .default_value("projects.json")
+        )
+        .arg(
+            Arg::new("legacy-script")
+                .long("legacy-script")
+                .help("Deprecated: run scripts/waf-smoke-test.sh (needs bash and curl) for --combined and /api/combined-scan instead of the built-in smoke test")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("script-timeout")
+                .long("script-timeout")
+                .help("Kill the combined-scan test script after this many seconds (default: 120)")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .requires("legacy-script")
+        )
+        .arg(
+            Arg::new("script-max-output")
+                .long("script-max-output")
+                .help("Kill the combined-scan test script when it writes more than this many bytes (default: 1048576)")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(usize))
+                .requires("legacy-script")
+        )
+        .arg(
+            Arg::new("script-workdir")
+                .long("script-workdir")
+                .help("Run the combined-scan test script in a fresh directory under DIR, removed afterwards")
+                .value_name("DIR")
+                .requires("legacy-script")
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .help("SQLite database keeping the web server's scan history (/api/results); needs the `sqlite` feature, kept in memory otherwise")
+                .value_name("FILE")
+                .default_value("history.db")
+        )
+        .arg(
+            Arg::new("history-half-life")
+                .long("history-half-life")
+                .help("Hours after which the confidence of a result served from history has halved (0 disables decay, default: 168)")
+                .value_name("HOURS")
+                .value_parser(clap::value_parser!(u64))
+        )
+        .arg(
+            Arg::new("api-key")
+                .long("api-key")
+                .help("Require this API key (X-API-Key or Authorization: Bearer) for the web server; repeatable")
+                .value_name("KEY")
+                .action(clap::ArgAction::Append)
+        )
+        .arg(
+            Arg::new("auth-config")
+                .long("auth-config")
+                .help("YAML file with web server API keys, basic-auth users, rate limits and CORS origins")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("audit-log")
+                .long("audit-log")
+                .help("Append a JSON line per web server scan request and rejected attempt to FILE")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("port")
+                .long("port")
+                .short('p')
+                .help("Port for web server and --api-only (default: 8080)")
+                .value_name("PORT")
+                .value_parser(clap::value_parser!(u16))
+                .default_value("8080")
+        )
+        .arg(
+            Arg::new("list")
+                .long("list")
+                .help("List available detection providers")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("smoke-test")
+                .long("smoke-test")
+                .help("Run comprehensive WAF effectiveness smoke test")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("combined")
+                .long("combined")
+                .help("Detect the WAF, then smoke test it and print a combined effectiveness summary")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["smoke-test", "passive", "paths"])
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Export smoke test results to FILE (format from --format or the file extension, default JSON)")
+                .value_name("FILE")
+                .requires("smoke-test")
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Smoke test export format: json, csv, html")
+                .value_name("FORMAT")
+                .value_parser(|value: &str| value.parse::<ExportFormat>())
+                .requires("output")
+        )
+        .arg(
+            Arg::new("headers")
+                .long("header")
+                .short('H')
+                .help("Custom headers for smoke test (format: 'Key: Value')")
+                .value_name("HEADER")
+                .action(clap::ArgAction::Append)
+                .requires("smoke-test")
+        )
+        .arg(
+            Arg::new("payload-locations")
+                .long("payload-locations")
+                .help("Where smoke test payloads are placed: query, json, form, multipart, graphql, referer, x-forwarded-for, user-agent, cookie, path (default: query)")
+                .value_name("LOCATIONS")
+                .value_delimiter(',')
+                .value_parser(|value: &str| value.parse::<PayloadLocation>())
+                .requires("smoke-test")
+        )
+        .arg(
+            Arg::new("concurrency")
+                .long("concurrency")
+                .help("Smoke test payload requests in flight at once (default: 3)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .requires("smoke-test")
+        )
+        .arg(
+            Arg::new("payloads")
+                .long("payloads")
+                .help("Load smoke test payloads from a file or directory instead of the built-in lists (text: one per line, YAML: category -> list)")
+                .value_name("PATH")
+                .requires("smoke-test")
+        )
+        .arg(
+            Arg::new("aggressive")
+                .long("aggressive")
+                .help("Enable aggressive testing mode (more payloads, faster)")
+                .action(clap::ArgAction::SetTrue)
+                .requires("smoke-test")
+        )
+        .subcommand(build_enum_command())
+        .subcommand(build_analyze_command())
+        .subcommand(build_validate_command())
+        .subcommand(build_benchmark_command())
+        .subcommand(build_scoring_command())
+        .subcommand(build_agent_command())
+        .subcommand(build_completions_command())
+        .subcommand(build_man_command())
+        .subcommand(build_update_signatures_command())
+        .subcommand(build_signatures_command())
+}
+
+fn print_signature_inventory(inventory: &signatures::Inventory) {
+    println!("waf-detect {}", inventory.tool_version);
+    match &inventory.bundle {
+        Some(bundle) => {
+            println!("📦 Signature bundle {} (published {})", bundle.version, bundle.published.format("%Y-%m-%d %H:%M UTC"));
+            println!(
+                "   {} DNS pattern(s), {} block page(s), {} IP range(s)",
+                bundle.dns_patterns, bundle.block_pages, bundle.ip_ranges
+            );
+        }
+        None => println!("📦 No signature bundle installed (built-in signatures only)"),
+    }
+    println!();
+
+    for provider in &inventory.providers {
+        let source = match (provider.built_in, provider.rules.len()) {
+            (true, 0) => "built-in".to_string(),
+            (true, rules) => format!("built-in + {} bundle rule(s)", rules),
+            (false, rules) => format!("bundle, {} rule(s)", rules),
+        };
+        let status = if provider.enabled { "" } else { " (disabled)" };
+        println!("🔌 {} v{} [{}] {}{}", provider.name, provider.version, provider.provider_type, source, status);
+        for rule in &provider.rules {
+            println!("   {:<28} {:<24} {:.2}  {}", rule.id, rule.target.to_string(), rule.confidence, rule.pattern);
+        }
+    }
+    let rules: usize = inventory.providers.iter().map(|provider| provider.rules.len()).sum();
+    println!();
+    println!("Total: {} provider(s), {} bundle rule(s)", inventory.providers.len(), rules);
+}
+
+fn print_lint_report(report: &signatures::lint::LintReport) {
+    println!("🧪 Signatures {}: {} rule(s) against {} sample response(s)", report.version, report.rules.len(), signatures::lint::SAMPLES.len());
+    for rule in &report.rules {
+        let icon = if !rule.compiled { "❌" } else if rule.matched.is_empty() { "⚪" } else { "✅" };
+        let matched = if rule.matched.is_empty() { "no samples".to_string() } else { rule.matched.join(", ") };
+        println!("{} {}/{} [{}] {:.1}ms → {}", icon, rule.provider, rule.id, rule.target, rule.duration_us as f64 / 1000.0, matched);
+    }
+    if !report.issues.is_empty() {
+        println!();
+    }
+    for issue in &report.issues {
+        let icon = match issue.severity {
+            signatures::lint::Severity::Error => "❌",
+            signatures::lint::Severity::Warning => "⚠️ ",
+        };
+        println!("{} {}: {}", icon, issue.location, issue.message);
+    }
+    println!();
+    println!("{} error(s), {} warning(s)", report.errors(), report.warnings());
+}
+
+fn build_enum_command() -> Command {
+    Command::new("enum")
+        .about("Enumerate subdomains and show which of them are protected by a WAF")
+        .arg(
+            Arg::new("domain")
+                .help("Apex domain to enumerate")
+                .value_name("DOMAIN")
+                .required(true)
+        )
+        .arg(
+            Arg::new("wordlist")
+                .long("wordlist")
+                .help("File with one subdomain label per line (default: built-in list)")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("no-ct")
+                .long("no-ct")
+                .help("Skip certificate transparency (crt.sh) lookups")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .help("Maximum number of subdomains to scan, including the apex")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+        )
+        .arg(
+            Arg::new("workers")
+                .long("workers")
+                .help("Subdomains scanned concurrently (default: 3)")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Output the coverage matrix as JSON")
+                .action(clap::ArgAction::SetTrue)
+        )
+}
+
+fn build_analyze_command() -> Command {
+    Command::new("analyze")
+        .about("Run passive detection over captured traffic (HAR or pcap) without sending any requests")
+        .arg(
+            Arg::new("file")
+                .help("HAR file exported from browser devtools or an intercepting proxy, or a libpcap capture (needs the `pcap` feature)")
+                .value_name("FILE")
+                .required(true)
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Output the per-host report as JSON")
+                .action(clap::ArgAction::SetTrue)
+        )
+}
+
+fn build_validate_command() -> Command {
+    Command::new("validate")
+        .about("Scan labelled targets and report detection accuracy and confidence calibration")
+        .arg(
+            Arg::new("ground-truth")
+                .long("ground-truth")
+                .help("YAML list of targets with their known providers ({url, waf, cdn, notes})")
+                .value_name("FILE")
+                .required(true)
+        )
+        .arg(
+            Arg::new("weights-out")
+                .long("weights-out")
+                .help("Write suggested evidence weight adjustments (YAML, keyed by signature)")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Output the validation report as JSON")
+                .action(clap::ArgAction::SetTrue)
+        )
+}
+
+fn build_benchmark_command() -> Command {
+    Command::new("benchmark")
+        .about("Scan well-known sites with known providers and print a markdown scoreboard of per-provider accuracy and timing")
+        .arg(
+            Arg::new("targets")
+                .long("targets")
+                .help("Target list to use instead of the bundled or refreshed one ({version, targets} or a validate ground-truth list)")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("refresh")
+                .long("refresh")
+                .help("Download the latest target list into the data directory first; later runs use it instead of the bundled one")
+                .action(clap::ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .help("Target list URL for --refresh (default: $WAF_DETECTOR_BENCHMARK_URL)")
+                .value_name("URL")
+                .requires("refresh")
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Also write the markdown scoreboard to FILE")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::new("min-accuracy")
+                .long("min-accuracy")
+                .help("Exit with code 3 when fewer than this percentage of targets are detected correctly")
+                .value_name("PERCENT")
+                .value_parser(parse_percent)
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Output the benchmark report as JSON")
+                .action(clap::ArgAction::SetTrue)
+        )
+}
+
+fn build_scoring_command() -> Command {
+    Command::new("scoring")
+        .about("Inspect the confidence scoring weights")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("dump")
+                .about("Print the effective weights and thresholds in --scoring format")
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Output JSON instead of YAML")
+                        .action(clap::ArgAction::SetTrue)
+                )
+        )
+}
+
+fn build_agent_command() -> Command {
+    Command::new("agent")
+        .about("Run scans on behalf of a coordinator (`--agent`/`--agents`) from this machine's region")
+        .arg(
+            Arg::new("listen")
+                .long("listen")
+                .help("Address to listen on (default: 0.0.0.0:8700)")
+                .value_name("ADDR")
+        )
+        .arg(
+            Arg::new("region")
+                .long("region")
+                .help("Name reported for this vantage point, e.g. eu-west")
+                .value_name("NAME")
+                .required(true)
+        )
+        .arg(
+            Arg::new("token")
+                .long("token")
+                .help("Bearer token the coordinator must send")
+                .value_name("TOKEN")
+        )
+}
+
+fn build_completions_command() -> Command {
+    Command::new("completions")
+        .about("Print a shell completion script, e.g. `waf-detect completions bash > /etc/bash_completion.d/waf-detect`")
+        .arg(
+            Arg::new("shell")
+                .help("Shell to generate completions for")
+                .value_name("SHELL")
+                .value_parser(clap::value_parser!(clap_complete::Shell))
+                .required(true)
+        )
+}
+
+fn build_man_command() -> Command {
+    Command::new("man")
+        .about("Print the waf-detect(1) man page in roff format")
+        .arg(
+            Arg::new("out-dir")
+                .long("out-dir")
+                .help("Write waf-detect.1 and a page per subcommand (waf-detect-enum.1, ...) into DIR instead")
+                .value_name("DIR")
+        )
+}
+
+fn build_update_signatures_command() -> Command {
+    Command::new("update-signatures")
+        .about("Download a signed signature bundle and install it into the data directory, where every later run loads it")
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .help("Bundle URL (default: $WAF_DETECTOR_SIGNATURES_URL)")
+                .value_name("URL")
+        )
+        .arg(
+            Arg::new("signature-url")
+                .long("signature-url")
+                .help("URL of the detached base64 Ed25519 signature (default: <url>.sig)")
+                .value_name("URL")
+        )
+        .arg(
+            Arg::new("public-key")
+                .long("public-key")
+                .help("Base64 Ed25519 public key the bundle must be signed with (default: $WAF_DETECTOR_SIGNATURES_KEY)")
+                .value_name("KEY")
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Install the bundle even if it is older than the installed one")
+                .action(clap::ArgAction::SetTrue)
+        )
+}
+
+fn build_signatures_command() -> Command {
+    Command::new("signatures")
+        .about("Inspect the detection signatures in effect")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("list")
+                .about("Show the tool and bundle versions and the rules loaded for each provider")
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Output JSON")
+                        .action(clap::ArgAction::SetTrue)
+                )
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Check a signature file: compile every pattern, time it against pathological inputs and show which bundled sample responses each rule matches (exit 1 on errors)")
+                .arg(
+                    Arg::new("file")
+                        .help("Signature bundle JSON, in the format update-signatures installs")
+                        .value_name("FILE")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("budget-ms")
+                        .long("budget-ms")
+                        .help("Time a single rule may take over the stress inputs and samples")
+                        .value_name("MS")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("200")
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Output the report as JSON")
+                        .action(clap::ArgAction::SetTrue)
+                )
+        )
+}
+
+fn parse_rps(value: &str) -> std::result::Result<f64, String> {
+    let rps: f64 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if rps.is_finite() && rps > 0.0 {
+        Ok(rps)
+    } else {
+        Err("must be a positive number".to_string())
+    }
+}
+
+fn parse_percent(value: &str) -> std::result::Result<f64, String> {
+    let percent: f64 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if (0.0..=100.0).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err("must be between 0 and 100".to_string())
+    }
 }
 
 // Backward compatibility aliases
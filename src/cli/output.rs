@@ -1,2 +1,57 @@
-// Output formatting implementations will go here
-// For now, all output logic is in the main CLI module 
\ No newline at end of file
+//! Where CLI output goes
+//!
+//! Results (tables, compact lines, JSON, YAML) are written to stdout; banners, progress
+//! and hints go to stderr, so `waf-detect site.com --json | jq` only ever sees JSON.
+//! `--quiet` drops that stderr chrome, and `--no-color` or a non-empty `NO_COLOR`
+//! turns off ANSI colors.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Apply `--quiet` and `--no-color`
+pub fn configure(quiet: bool, no_color: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    NO_COLOR.store(no_color, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Whether colored output on stderr is wanted: not disabled and stderr is a terminal
+pub fn use_color() -> bool {
+    color_allowed(NO_COLOR.load(Ordering::Relaxed), std::env::var_os("NO_COLOR").as_deref())
+        && std::io::stderr().is_terminal()
+}
+
+/// `NO_COLOR` disables colors when set to anything but the empty string (no-color.org)
+fn color_allowed(no_color_flag: bool, no_color_env: Option<&std::ffi::OsStr>) -> bool {
+    !no_color_flag && no_color_env.is_none_or(|value| value.is_empty())
+}
+
+/// A banner or progress line on stderr, dropped with `--quiet`
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::cli::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use status;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_no_color_env_and_flag() {
+        assert!(color_allowed(false, None));
+        assert!(color_allowed(false, Some(OsStr::new(""))));
+        assert!(!color_allowed(false, Some(OsStr::new("1"))));
+        assert!(!color_allowed(true, None));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{Evidence, MethodType};
-use std::collections::HashMap;
+use crate::http::Headers;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use anyhow::{anyhow, Result};
 use serde::{Serialize, Deserialize};
 
 /// Advanced confidence scoring system for WAF/CDN detection
@@ -9,8 +12,121 @@ pub struct AdvancedScoring {
     evidence_weights: HashMap<String, EvidenceWeight>,
     /// Minimum evidence requirements for high confidence
     confidence_thresholds: ConfidenceThresholds,
-    /// Negative evidence that rules out providers
-    negative_evidence_patterns: HashMap<String, Vec<String>>,
+    /// Negative evidence that rules out providers, keyed by provider name
+    negative_evidence_patterns: HashMap<String, Vec<NegativeEvidenceRule>>,
+    /// Multiplier applied to every piece of evidence in a category (missing = 1.0)
+    #[serde(default)]
+    category_multipliers: HashMap<EvidenceCategory, f64>,
+}
+
+/// User overrides for evidence weights and thresholds (`--scoring weights.yaml`).
+///
+/// Every section is optional; whatever is given replaces the built-in value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoringConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<ConfidenceThresholds>,
+    /// Scale whole evidence categories, e.g. `Body: 0.0` ignores body evidence entirely
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub category_multipliers: BTreeMap<EvidenceCategory, f64>,
+    /// Weights keyed by evidence signature (`cf-ray-header`, ...)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub evidence_weights: BTreeMap<String, EvidenceWeight>,
+}
+
+impl ScoringConfig {
+    /// Load overrides from a YAML (or JSON) file and validate them
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read scoring config '{}': {}", path.display(), e))?;
+        let config: ScoringConfig = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid scoring config '{}': {}", path.display(), e))?;
+        config.validate().map_err(|e| anyhow!("Invalid scoring config '{}': {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// Weights, multipliers and thresholds must be in range; thresholds must be ordered
+    pub fn validate(&self) -> Result<()> {
+        fn unit(name: &str, value: f64) -> Result<()> {
+            if (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(anyhow!("{} must be between 0 and 1 (got {})", name, value))
+            }
+        }
+
+        if let Some(t) = &self.thresholds {
+            for (name, value) in [("minimum", t.minimum), ("high", t.high), ("very_high", t.very_high), ("absolute", t.absolute)] {
+                unit(&format!("thresholds.{}", name), value)?;
+            }
+            if !(t.minimum <= t.high && t.high <= t.very_high && t.very_high <= t.absolute) {
+                return Err(anyhow!("thresholds must satisfy minimum <= high <= very_high <= absolute"));
+            }
+        }
+        for (category, multiplier) in &self.category_multipliers {
+            if !multiplier.is_finite() || *multiplier < 0.0 {
+                return Err(anyhow!("category_multipliers.{:?} must be a non-negative number (got {})", category, multiplier));
+            }
+        }
+        for (signature, weight) in &self.evidence_weights {
+            unit(&format!("evidence_weights.{}.base_weight", signature), weight.base_weight)?;
+            unit(&format!("evidence_weights.{}.specificity", signature), weight.specificity)?;
+            unit(&format!("evidence_weights.{}.reliability", signature), weight.reliability)?;
+        }
+        Ok(())
+    }
+}
+
+/// A response header that contradicts a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegativeEvidenceRule {
+    /// Header name (lowercase)
+    pub header: String,
+    /// Case-insensitive substring the header value must contain; `None` matches any value
+    #[serde(default)]
+    pub value_contains: Option<String>,
+    /// Multiplier applied to the score (0.0 rules the provider out)
+    pub penalty: f64,
+    pub reason: String,
+}
+
+/// A negative-evidence rule that fired on a response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NegativeEvidence {
+    pub header: String,
+    pub value: String,
+    pub penalty: f64,
+    pub reason: String,
+}
+
+impl NegativeEvidenceRule {
+    /// Rule that fires whenever `header` is present
+    pub fn header(header: &str, penalty: f64, reason: &str) -> Self {
+        Self { header: header.to_string(), value_contains: None, penalty, reason: reason.to_string() }
+    }
+
+    /// Rule that fires when `header` contains `value`
+    pub fn value(header: &str, value: &str, penalty: f64, reason: &str) -> Self {
+        Self { value_contains: Some(value.to_string()), ..Self::header(header, penalty, reason) }
+    }
+
+    /// The contradicting header, if this rule fires on `headers`; every value of a
+    /// repeated header is checked
+    pub fn evaluate(&self, headers: &Headers) -> Option<NegativeEvidence> {
+        let value = headers.get_all(&self.header).find(|value| {
+            self.value_contains
+                .as_ref()
+                .is_none_or(|needle| value.to_lowercase().contains(&needle.to_lowercase()))
+        })?;
+        Some(NegativeEvidence {
+            header: self.header.clone(),
+            value: value.clone(),
+            penalty: self.penalty,
+            reason: self.reason.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,7 +141,7 @@ pub struct EvidenceWeight {
     pub category: EvidenceCategory,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub enum EvidenceCategory {
     /// HTTP headers (highest reliability)
     Headers,
@@ -67,6 +183,9 @@ pub struct ConfidenceResult {
     pub positive_evidence_count: usize,
     /// Negative evidence (contradictory) count
     pub negative_evidence_count: usize,
+    /// Contradicting headers and the penalty each applied
+    #[serde(default)]
+    pub negative_evidence: Vec<NegativeEvidence>,
     /// Required evidence still missing
     pub missing_evidence: Vec<String>,
     /// Explanation of scoring logic
@@ -250,6 +369,231 @@ impl AdvancedScoring {
             category: EvidenceCategory::Server,
         });
         
+        // Netlify, Render and Fly.io platform patterns
+        evidence_weights.insert("netlify-request-id-header".to_string(), EvidenceWeight {
+            base_weight: 0.95,
+            specificity: 0.98,
+            reliability: 0.96,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("netlify-server-header".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.93,
+            category: EvidenceCategory::Server,
+        });
+        
+        evidence_weights.insert("render-request-id-header".to_string(), EvidenceWeight {
+            base_weight: 0.95,
+            specificity: 0.98,
+            reliability: 0.95,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("fly-request-id-header".to_string(), EvidenceWeight {
+            base_weight: 0.95,
+            specificity: 0.98,
+            reliability: 0.96,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("fly-server-header".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.97,    // Build hash and date format is unique to the Fly proxy
+            reliability: 0.93,
+            category: EvidenceCategory::Server,
+        });
+        
+        // BunnyCDN, StackPath, CDN77 and Cachefly patterns
+        evidence_weights.insert("bunnycdn-server-header".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.97,
+            reliability: 0.94,
+            category: EvidenceCategory::Server,
+        });
+        
+        evidence_weights.insert("stackpath-sp-header".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.92,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("cdn77-server-header".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.97,
+            reliability: 0.94,
+            category: EvidenceCategory::Server,
+        });
+        
+        evidence_weights.insert("cachefly-cache-header".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.93,
+            reliability: 0.92,
+            category: EvidenceCategory::Headers,
+        });
+        
+        // Alibaba Cloud, Tencent Cloud and Huawei Cloud patterns
+        evidence_weights.insert("alibaba-eagleid-header".to_string(), EvidenceWeight {
+            base_weight: 0.93,
+            specificity: 0.96,
+            reliability: 0.94,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("alibaba-waf-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.92,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("tencent-edgeone-header".to_string(), EvidenceWeight {
+            base_weight: 0.95,
+            specificity: 0.98,
+            reliability: 0.95,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("huawei-waf-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.97,
+            reliability: 0.94,
+            category: EvidenceCategory::Headers,
+        });
+        
+        // Azure Front Door / Application Gateway patterns
+        evidence_weights.insert("azure-ref-header".to_string(), EvidenceWeight {
+            base_weight: 0.95,
+            specificity: 0.98,    // Only emitted by Azure Front Door
+            reliability: 0.96,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("azure-app-gateway-server-header".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.97,
+            reliability: 0.94,
+            category: EvidenceCategory::Server,
+        });
+        
+        evidence_weights.insert("azure-msedge-ref-header".to_string(), EvidenceWeight {
+            base_weight: 0.85,
+            specificity: 0.85,    // Shared by other Microsoft edge services
+            reliability: 0.90,
+            category: EvidenceCategory::Headers,
+        });
+        
+        // Google Cloud Armor / Cloud CDN patterns
+        evidence_weights.insert("gcp-via-google-header".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.95,    // Only the Google load balancer adds this via value
+            reliability: 0.95,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("gcp-goog-header".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.93,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("gcp-cloud-armor-403".to_string(), EvidenceWeight {
+            base_weight: 0.80,
+            specificity: 0.70,    // A bare 403 page is not unique without the via header
+            reliability: 0.80,
+            category: EvidenceCategory::ErrorPage,
+        });
+        
+        // Appliance WAF session cookies
+        evidence_weights.insert("barracuda-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.90,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("fortiweb-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.90,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("netscaler-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.90,
+            reliability: 0.90,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("netscaler-connection-anomaly".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.95,    // Scrambled Connection header is unique to NetScaler
+            reliability: 0.85,
+            category: EvidenceCategory::Headers,
+        });
+        
+        // CMS plugin WAFs
+        evidence_weights.insert("wordfence-block-page".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.95,
+            reliability: 0.85,
+            category: EvidenceCategory::ErrorPage,
+        });
+        
+        evidence_weights.insert("sitelock-header".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.92,
+            reliability: 0.88,
+            category: EvidenceCategory::Headers,
+        });
+        
+        // Bot-management products
+        evidence_weights.insert("datadome-header".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.98,
+            reliability: 0.92,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("datadome-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.95,
+            reliability: 0.90,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("datadome-captcha".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.88,
+            category: EvidenceCategory::ErrorPage,
+        });
+        
+        evidence_weights.insert("perimeterx-cookie".to_string(), EvidenceWeight {
+            base_weight: 0.88,
+            specificity: 0.92,
+            reliability: 0.90,
+            category: EvidenceCategory::Headers,
+        });
+        
+        evidence_weights.insert("perimeterx-app-id".to_string(), EvidenceWeight {
+            base_weight: 0.92,
+            specificity: 0.98,    // App IDs are PX-prefixed and unique to the sensor
+            reliability: 0.92,
+            category: EvidenceCategory::Body,
+        });
+        
+        evidence_weights.insert("perimeterx-captcha".to_string(), EvidenceWeight {
+            base_weight: 0.90,
+            specificity: 0.95,
+            reliability: 0.88,
+            category: EvidenceCategory::ErrorPage,
+        });
+        
         // === TIMING EVIDENCE WEIGHTS ===
         // Timing-based detections (high reliability for WAF delays)
         evidence_weights.insert("timing-waf-delay".to_string(), EvidenceWeight {
@@ -310,6 +654,29 @@ impl AdvancedScoring {
             category: EvidenceCategory::Network,
         });
         
+        for platform in ["netlify", "render", "fly.io", "bunnycdn", "stackpath", "cdn77", "cachefly", "alibabacloud", "tencentcloud", "huaweicloud"] {
+            evidence_weights.insert(format!("dns-cname-{}", platform), EvidenceWeight {
+                base_weight: 0.98,
+                specificity: 0.99,    // Platform and CDN CNAMEs are definitive
+                reliability: 0.98,    // DNS is highly reliable
+                category: EvidenceCategory::Network,
+            });
+        }
+        
+        evidence_weights.insert("dns-cname-azure".to_string(), EvidenceWeight {
+            base_weight: 0.98,
+            specificity: 0.98,    // azurefd.net / azureedge.net CNAMEs are definitive
+            reliability: 0.98,    // DNS is highly reliable
+            category: EvidenceCategory::Network,
+        });
+        
+        evidence_weights.insert("dns-cname-googlecloud".to_string(), EvidenceWeight {
+            base_weight: 0.97,
+            specificity: 0.97,    // googlehosted/googleusercontent CNAMEs are definitive
+            reliability: 0.98,    // DNS is highly reliable
+            category: EvidenceCategory::Network,
+        });
+        
         evidence_weights.insert("dns-cname-keycdn".to_string(), EvidenceWeight {
             base_weight: 0.98,
             specificity: 0.99,    // KeyCDN CNAMEs are definitive
@@ -382,26 +749,37 @@ impl AdvancedScoring {
             category: EvidenceCategory::Behavioral,
         });
         
-        // Define negative evidence patterns
-        let mut negative_evidence_patterns = HashMap::new();
+        // Define negative evidence rules: headers that contradict a provider
+        let mut negative_evidence_patterns: HashMap<String, Vec<NegativeEvidenceRule>> = HashMap::new();
+        
+        negative_evidence_patterns.insert("AWS".to_string(), vec![
+            NegativeEvidenceRule::value("server", "gws", 0.0, "Google Front End serves the response, not CloudFront"),
+            NegativeEvidenceRule::header("cf-ray", 0.3, "CloudFlare request ID present"),
+            NegativeEvidenceRule::header("cf-cache-status", 0.3, "CloudFlare cache header present"),
+        ]);
         
-        // If we see AWS headers, it's NOT CloudFlare
         negative_evidence_patterns.insert("CloudFlare".to_string(), vec![
-            "x-amz-cf-id".to_string(),
-            "x-amz-cf-pop".to_string(),
-            "cloudfront".to_string(),
+            NegativeEvidenceRule::header("x-amz-cf-id", 0.3, "CloudFront request ID present"),
+            NegativeEvidenceRule::header("x-amz-cf-pop", 0.3, "CloudFront POP header present"),
+            NegativeEvidenceRule::value("via", "cloudfront", 0.3, "Response came through CloudFront"),
+            NegativeEvidenceRule::header("akamai-grn", 0.3, "Akamai reference header present"),
+            NegativeEvidenceRule::header("x-akamai-transformed", 0.3, "Akamai transformation header present"),
         ]);
         
-        // If we see CloudFlare headers, it's NOT AWS
-        negative_evidence_patterns.insert("AWS".to_string(), vec![
-            "cf-ray".to_string(),
-            "cf-cache-status".to_string(),
+        negative_evidence_patterns.insert("Akamai".to_string(), vec![
+            NegativeEvidenceRule::header("x-vercel-id", 0.5, "Vercel edge serves the response"),
+            NegativeEvidenceRule::header("cf-ray", 0.3, "CloudFlare request ID present"),
+            NegativeEvidenceRule::header("x-amz-cf-id", 0.3, "CloudFront request ID present"),
         ]);
         
-        // If we see Akamai headers, it's NOT CloudFlare
-        negative_evidence_patterns.insert("CloudFlare".to_string(), vec![
-            "akamai-grn".to_string(),
-            "x-akamai-transformed".to_string(),
+        negative_evidence_patterns.insert("Fastly".to_string(), vec![
+            NegativeEvidenceRule::value("server", "gws", 0.0, "Google Front End serves the response"),
+            NegativeEvidenceRule::header("x-vercel-id", 0.5, "Vercel edge serves the response"),
+        ]);
+        
+        negative_evidence_patterns.insert("GoogleCloud".to_string(), vec![
+            NegativeEvidenceRule::header("x-amz-cf-id", 0.3, "CloudFront request ID present"),
+            NegativeEvidenceRule::header("cf-ray", 0.5, "CloudFlare request ID present"),
         ]);
         
         Self {
@@ -413,20 +791,49 @@ impl AdvancedScoring {
                 absolute: 0.98,
             },
             negative_evidence_patterns,
+            category_multipliers: HashMap::new(),
+        }
+    }
+
+    /// Apply user overrides on top of the current weights and thresholds
+    pub fn with_config(mut self, config: ScoringConfig) -> Result<Self> {
+        config.validate()?;
+        if let Some(thresholds) = config.thresholds {
+            self.confidence_thresholds = thresholds;
+        }
+        self.category_multipliers.extend(config.category_multipliers);
+        self.evidence_weights.extend(config.evidence_weights);
+        Ok(self)
+    }
+
+    /// Current weights and thresholds in the `--scoring` file format
+    pub fn to_config(&self) -> ScoringConfig {
+        ScoringConfig {
+            thresholds: Some(self.confidence_thresholds.clone()),
+            category_multipliers: self.category_multipliers.iter().map(|(k, v)| (*k, *v)).collect(),
+            evidence_weights: self.evidence_weights.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
         }
     }
     
+    pub fn thresholds(&self) -> &ConfidenceThresholds {
+        &self.confidence_thresholds
+    }
+
+    /// Configured weight for an evidence signature (`None` if it falls back to the method default)
+    pub fn evidence_weight(&self, signature: &str) -> Option<&EvidenceWeight> {
+        self.evidence_weights.get(signature)
+    }
+
     /// Calculate advanced confidence score with detailed breakdown
     pub fn calculate_confidence(
         &self,
         provider: &str,
         evidence: &[Evidence],
-        response_headers: &std::collections::HashMap<String, String>,
+        response_headers: &Headers,
     ) -> ConfidenceResult {
         let mut total_score = 0.0;
         let mut evidence_breakdown = HashMap::new();
         let mut positive_evidence_count = 0;
-        let mut negative_evidence_count = 0;
         let mut explanation_parts = Vec::new();
         
         // Initialize category scores
@@ -451,7 +858,8 @@ impl AdvancedScoring {
                 self.get_fallback_weight(&ev.method_type, &ev.signature_matched)
             };
             
-            let evidence_score = ev.confidence * weight.base_weight * weight.specificity * weight.reliability;
+            let multiplier = self.category_multipliers.get(&weight.category).copied().unwrap_or(1.0);
+            let evidence_score = ev.confidence * weight.base_weight * weight.specificity * weight.reliability * multiplier;
             total_score += evidence_score;
             
             // Add to category breakdown
@@ -464,26 +872,21 @@ impl AdvancedScoring {
                 "✅ {} ({:.1}% × {:.2} weight = {:.3})",
                 ev.description,
                 ev.confidence * 100.0,
-                weight.base_weight * weight.specificity * weight.reliability,
+                weight.base_weight * weight.specificity * weight.reliability * multiplier,
                 evidence_score
             ));
         }
         
-        // Check for negative evidence (contradictory patterns)
-        if let Some(negative_patterns) = self.negative_evidence_patterns.get(provider) {
-            for pattern in negative_patterns {
-                for header_name in response_headers.keys() {
-                    if header_name.to_lowercase().contains(&pattern.to_lowercase()) {
-                        negative_evidence_count += 1;
-                        total_score *= 0.3; // Heavily penalize contradictory evidence
-                        explanation_parts.push(format!(
-                            "❌ Contradictory evidence: {} header found",
-                            header_name
-                        ));
-                    }
-                }
-            }
+        // Check for negative evidence (contradictory headers)
+        let negative_evidence = self.negative_evidence(provider, response_headers);
+        for hit in &negative_evidence {
+            total_score *= hit.penalty;
+            explanation_parts.push(format!(
+                "❌ Contradictory evidence: {}: {} ({}, ×{:.1})",
+                hit.header, hit.value, hit.reason, hit.penalty
+            ));
         }
+        let negative_evidence_count = negative_evidence.len();
         
         // Apply evidence type bonuses/penalties
         let header_evidence_ratio = evidence_breakdown.get(&EvidenceCategory::Headers).unwrap_or(&0.0) / total_score.max(0.001);
@@ -547,10 +950,19 @@ impl AdvancedScoring {
             evidence_breakdown,
             positive_evidence_count,
             negative_evidence_count,
+            negative_evidence,
             missing_evidence,
             explanation,
         }
     }
+
+    /// Negative-evidence rules for `provider` that fire on `response_headers`
+    pub fn negative_evidence(&self, provider: &str, response_headers: &Headers) -> Vec<NegativeEvidence> {
+        self.negative_evidence_patterns
+            .get(provider)
+            .map(|rules| rules.iter().filter_map(|rule| rule.evaluate(response_headers)).collect())
+            .unwrap_or_default()
+    }
     
     fn suggest_missing_evidence(&self, provider: &str, current_evidence: &[Evidence]) -> Vec<String> {
         let mut suggestions = Vec::new();
@@ -654,6 +1066,42 @@ impl AdvancedScoring {
                     category: EvidenceCategory::Behavioral,
                 }
             }
+            MethodType::Protocol(_) => {
+                // Protocol support is shared by many networks
+                EvidenceWeight {
+                    base_weight: 0.55,
+                    specificity: 0.45,
+                    reliability: 0.70,
+                    category: EvidenceCategory::Network,
+                }
+            }
+            MethodType::HeaderOrder(_) => {
+                // Header layout is written by the proxy but shared by similar stacks
+                EvidenceWeight {
+                    base_weight: 0.60,
+                    specificity: 0.55,
+                    reliability: 0.70,
+                    category: EvidenceCategory::Headers,
+                }
+            }
+            MethodType::HostIntel(_) => {
+                // Third-party scan records can be stale, so they only corroborate
+                EvidenceWeight {
+                    base_weight: 0.50,
+                    specificity: 0.55,
+                    reliability: 0.60,
+                    category: EvidenceCategory::Network,
+                }
+            }
+            MethodType::Cache(_) => {
+                // Cache behavior follows the vendor's software, but origins can shape it
+                EvidenceWeight {
+                    base_weight: 0.60,
+                    specificity: 0.55,
+                    reliability: 0.65,
+                    category: EvidenceCategory::Behavioral,
+                }
+            }
         }
     }
 }
@@ -662,4 +1110,91 @@ impl Default for AdvancedScoring {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DetectionMethod;
+
+    fn header_evidence(signature: &str, header: &str) -> Evidence {
+        Evidence {
+            method_type: DetectionMethod::Header(header.to_string()),
+            confidence: 0.9,
+            description: format!("{} header", header),
+            raw_data: "1".to_string(),
+            signature_matched: signature.to_string(),
+        }
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> Headers {
+        pairs.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_value_rule_only_fires_on_matching_value() {
+        let rule = NegativeEvidenceRule::value("server", "gws", 0.0, "Google Front End");
+
+        assert!(rule.evaluate(&headers(&[("server", "nginx")])).is_none());
+        let hit = rule.evaluate(&headers(&[("Server", "GWS")])).unwrap();
+        assert_eq!(hit.value, "GWS");
+        assert_eq!(hit.penalty, 0.0);
+    }
+
+    #[test]
+    fn test_negative_evidence_lowers_score_and_is_reported() {
+        let scoring = AdvancedScoring::new();
+        let evidence = vec![header_evidence("akamai-grn-header", "akamai-grn")];
+
+        let clean = scoring.calculate_confidence("Akamai", &evidence, &headers(&[("akamai-grn", "0.1")]));
+        let contradicted = scoring.calculate_confidence(
+            "Akamai",
+            &evidence,
+            &headers(&[("akamai-grn", "0.1"), ("x-vercel-id", "fra1::abcde")]),
+        );
+
+        assert_eq!(clean.negative_evidence_count, 0);
+        assert_eq!(contradicted.negative_evidence_count, 1);
+        assert_eq!(contradicted.negative_evidence[0].header, "x-vercel-id");
+        assert!(contradicted.score < clean.score);
+        assert!(contradicted.explanation.contains("x-vercel-id"));
+    }
+
+    #[test]
+    fn test_both_cloudflare_rule_groups_apply() {
+        let scoring = AdvancedScoring::new();
+        let response = headers(&[("x-amz-cf-id", "abc"), ("x-akamai-transformed", "9 - 0 pmb=mRUM,1")]);
+
+        let hits = scoring.negative_evidence("CloudFlare", &response);
+        let mut fired: Vec<&str> = hits.iter().map(|hit| hit.header.as_str()).collect();
+        fired.sort();
+        assert_eq!(fired, vec!["x-akamai-transformed", "x-amz-cf-id"]);
+    }
+
+    #[test]
+    fn test_category_multiplier_scales_evidence() {
+        let evidence = vec![header_evidence("akamai-grn-header", "akamai-grn")];
+        let response = headers(&[("akamai-grn", "0.1")]);
+        let config: ScoringConfig = serde_yaml::from_str("category_multipliers:\n  Headers: 0.0\n").unwrap();
+
+        let default = AdvancedScoring::new().calculate_confidence("Akamai", &evidence, &response);
+        let muted = AdvancedScoring::new().with_config(config).unwrap().calculate_confidence("Akamai", &evidence, &response);
+
+        assert!(default.score > 0.0);
+        assert_eq!(muted.score, 0.0);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let out_of_range: ScoringConfig = serde_yaml::from_str(
+            "evidence_weights:\n  cf-ray-header: {base_weight: 1.2, specificity: 0.9, reliability: 0.9, category: Headers}\n",
+        ).unwrap();
+        let unordered: ScoringConfig = serde_yaml::from_str(
+            "thresholds: {minimum: 0.9, high: 0.8, very_high: 0.95, absolute: 0.98}\n",
+        ).unwrap();
+
+        assert!(out_of_range.validate().unwrap_err().to_string().contains("cf-ray-header.base_weight"));
+        assert!(unordered.validate().is_err());
+        assert!(serde_yaml::from_str::<ScoringConfig>("weights: {}\n").is_err());
+        AdvancedScoring::new().to_config().validate().unwrap();
+    }
+}
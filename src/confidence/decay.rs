@@ -0,0 +1,100 @@
+//! Confidence decay for results served from history
+//!
+//! A "CloudFlare 95%" verdict from last week says less about the site today than one
+//! from this scan. Served results lose confidence exponentially with age: after a
+//! grace period, every half-life halves it.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Default time for a served confidence to halve
+pub const DEFAULT_HALF_LIFE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Results younger than this are served as recorded
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceDecay {
+    /// `None` turns decay off
+    half_life: Option<Duration>,
+    grace: Duration,
+}
+
+impl Default for ConfidenceDecay {
+    fn default() -> Self {
+        Self::new(DEFAULT_HALF_LIFE)
+    }
+}
+
+impl ConfidenceDecay {
+    pub fn new(half_life: Duration) -> Self {
+        Self { half_life: (!half_life.is_zero()).then_some(half_life), grace: DEFAULT_GRACE }
+    }
+
+    /// Serve recorded confidences unchanged
+    pub fn disabled() -> Self {
+        Self { half_life: None, grace: DEFAULT_GRACE }
+    }
+
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Multiplier for a result `age` old, in (0, 1]
+    pub fn factor(&self, age: Duration) -> f64 {
+        match self.half_life {
+            Some(half_life) if age > self.grace => 0.5f64.powf((age - self.grace).as_secs_f64() / half_life.as_secs_f64()),
+            _ => 1.0,
+        }
+    }
+
+    /// How a served result was aged
+    pub fn annotate(&self, age: Duration, recorded_confidence: Option<f64>) -> CachedAge {
+        CachedAge { age_secs: age.as_secs(), decay_factor: self.factor(age), recorded_confidence }
+    }
+}
+
+/// Marks a result as served from history rather than scanned just now
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedAge {
+    /// Seconds since the scan ran
+    pub age_secs: u64,
+    /// Factor the recorded confidences were multiplied by
+    pub decay_factor: f64,
+    /// Confidence as recorded at scan time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_confidence: Option<f64>,
+}
+
+impl CachedAge {
+    /// Age in the largest whole unit: "45s", "12m", "5h", "3d"
+    pub fn describe(&self) -> String {
+        match self.age_secs {
+            secs if secs < 60 => format!("{}s", secs),
+            secs if secs < 3600 => format!("{}m", secs / 60),
+            secs if secs < 86_400 => format!("{}h", secs / 3600),
+            secs => format!("{}d", secs / 86_400),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decay_halves_per_half_life_after_grace() {
+        let decay = ConfidenceDecay::new(Duration::from_secs(100)).with_grace(Duration::from_secs(10));
+        assert_eq!(decay.factor(Duration::from_secs(5)), 1.0);
+        assert!((decay.factor(Duration::from_secs(110)) - 0.5).abs() < 1e-9);
+        assert!((decay.factor(Duration::from_secs(210)) - 0.25).abs() < 1e-9);
+
+        assert_eq!(ConfidenceDecay::disabled().factor(Duration::from_secs(10_000_000)), 1.0);
+        assert_eq!(ConfidenceDecay::new(Duration::ZERO), ConfidenceDecay::disabled());
+
+        let week = ConfidenceDecay::default().annotate(DEFAULT_HALF_LIFE + DEFAULT_GRACE, Some(0.95));
+        assert!((week.decay_factor - 0.5).abs() < 1e-9);
+        assert_eq!(week.describe(), "7d");
+    }
+}
@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 
 pub mod advanced_scoring;
+pub mod decay;
 
 pub use advanced_scoring::{
     AdvancedScoring, 
@@ -10,7 +11,10 @@ pub use advanced_scoring::{
     EvidenceCategory, 
     ConfidenceResult, 
     ConfidenceLevel,
-    ConfidenceThresholds
+    ConfidenceThresholds,
+    NegativeEvidence,
+    NegativeEvidenceRule,
+    ScoringConfig,
 };
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,213 @@
+//! Multi-run consensus (`--runs N`)
+//!
+//! Load-balanced targets answer from different edges with different headers, so a
+//! single pass can miss a provider or catch one that only fronts some edges. The
+//! engine can repeat detection; [`merge`] unions the runs' evidence, keeps the
+//! detections most runs agree on, and reports each provider's mean confidence with
+//! its variance. A run that did not see a provider counts as 0 for it.
+
+use crate::{evidence, DetectionResult, ProviderDetection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One provider's score across runs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProviderConsensus {
+    pub name: String,
+    /// Runs that scored the provider above zero
+    pub seen_in: usize,
+    pub mean: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ProviderConsensus {
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+/// How the runs behind a consensus result agreed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Consensus {
+    /// Runs that completed
+    pub runs: usize,
+    /// Runs that failed and were left out
+    #[serde(default)]
+    pub failed_runs: usize,
+    /// Highest mean first
+    pub providers: Vec<ProviderConsensus>,
+}
+
+impl Consensus {
+    /// Every completed run saw the same providers
+    pub fn unanimous(&self) -> bool {
+        self.providers.iter().all(|provider| provider.seen_in == self.runs)
+    }
+}
+
+/// Combine completed runs of the same target into one result, or `None` without runs
+pub fn merge(runs: Vec<DetectionResult>, failed_runs: usize) -> Option<DetectionResult> {
+    let count = runs.len();
+    let mut merged = runs.first()?.clone();
+
+    let mut names: Vec<&String> = runs.iter().flat_map(|run| run.provider_scores.keys()).collect();
+    names.sort();
+    names.dedup();
+    let mut providers: Vec<ProviderConsensus> = names
+        .into_iter()
+        .map(|name| {
+            let scores: Vec<f64> = runs.iter().map(|run| run.provider_scores.get(name).copied().unwrap_or(0.0)).collect();
+            let mean = scores.iter().sum::<f64>() / count as f64;
+            ProviderConsensus {
+                name: name.clone(),
+                seen_in: scores.iter().filter(|score| **score > 0.0).count(),
+                mean,
+                variance: scores.iter().map(|score| (score - mean).powi(2)).sum::<f64>() / count as f64,
+                min: scores.iter().copied().fold(f64::INFINITY, f64::min),
+                max: scores.iter().copied().fold(0.0, f64::max),
+            }
+        })
+        .collect();
+    providers.sort_by(|a, b| b.mean.partial_cmp(&a.mean).unwrap_or(std::cmp::Ordering::Equal).then(a.name.cmp(&b.name)));
+    let mean = |name: &str| providers.iter().find(|p| p.name == name).map_or(0.0, |p| p.mean);
+
+    merged.provider_scores = providers.iter().map(|p| (p.name.clone(), p.mean)).collect();
+    merged.detected_waf = majority(runs.iter().map(|run| run.detected_waf.as_ref()), count, &mean);
+    merged.detected_cdn = majority(runs.iter().map(|run| run.detected_cdn.as_ref()), count, &mean);
+    merged.detected_bot_manager = majority(runs.iter().map(|run| run.detected_bot_manager.as_ref()), count, &mean);
+
+    // The stack in the order of the first run that reported each layer
+    let mut stack: Vec<ProviderDetection> = Vec::new();
+    for detection in runs.iter().flat_map(|run| &run.detected_stack) {
+        if !stack.iter().any(|kept| kept.name == detection.name) {
+            stack.push(detection.clone());
+        }
+    }
+    stack.retain(|detection| runs.iter().filter(|run| run.detected_stack.iter().any(|d| d.name == detection.name)).count() * 2 > count);
+    for detection in &mut stack {
+        detection.confidence = mean(&detection.name);
+    }
+    merged.detected_stack = stack;
+
+    // Union of every run's full evidence, each finding once
+    let mut all_evidence: HashMap<String, Vec<crate::Evidence>> = HashMap::new();
+    for run in &runs {
+        for (provider, items) in &run.evidence_map {
+            let full: Vec<crate::Evidence> = match run.evidence_groups.get(provider) {
+                Some(groups) => groups.iter().flat_map(|g| std::iter::once(&g.primary).chain(&g.related)).cloned().collect(),
+                None => items.clone(),
+            };
+            let kept = all_evidence.entry(provider.clone()).or_default();
+            for item in full {
+                if !kept.iter().any(|k| k.description == item.description && k.raw_data == item.raw_data) {
+                    kept.push(item);
+                }
+            }
+        }
+    }
+    merged.evidence_groups = all_evidence.iter().map(|(provider, items)| (provider.clone(), evidence::group(items))).collect();
+    merged.evidence_map = merged.evidence_groups.iter().map(|(provider, groups)| (provider.clone(), evidence::condense(groups))).collect();
+
+    for run in &runs[1..] {
+        for (provider, hits) in &run.negative_evidence {
+            let kept = merged.negative_evidence.entry(provider.clone()).or_default();
+            for hit in hits {
+                if !kept.iter().any(|k| k.header == hit.header && k.value == hit.value) {
+                    kept.push(hit.clone());
+                }
+            }
+        }
+        merged.detection_time_ms += run.detection_time_ms;
+        merged.diagnostics.total_requests += run.diagnostics.total_requests;
+        merged.diagnostics.techniques.extend(run.diagnostics.techniques.iter().cloned());
+    }
+
+    merged.consensus = Some(Consensus { runs: count, failed_runs, providers });
+    Some(merged)
+}
+
+/// The provider more than half of the runs detected in a role, at its mean confidence
+fn majority<'a>(
+    detections: impl Iterator<Item = Option<&'a ProviderDetection>>,
+    runs: usize,
+    mean: &impl Fn(&str) -> f64,
+) -> Option<ProviderDetection> {
+    let mut votes: Vec<(&ProviderDetection, usize)> = Vec::new();
+    for detection in detections.flatten() {
+        match votes.iter_mut().find(|(first, _)| first.name == detection.name) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((detection, 1)),
+        }
+    }
+    let (detection, _) = votes.into_iter().find(|(_, count)| count * 2 > runs)?;
+    Some(ProviderDetection { confidence: mean(&detection.name), ..detection.clone() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DetectionMethod, Evidence};
+
+    fn run(waf: Option<(&str, f64)>, header: &str) -> DetectionResult {
+        let mut result: DetectionResult = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com/",
+            "detected_waf": null,
+            "detected_cdn": null,
+            "provider_scores": {},
+            "evidence_map": {},
+            "detection_time_ms": 10,
+            "metadata": {"timestamp": "2024-01-01T00:00:00Z", "version": "0.1.0", "user_agent": "test"}
+        }))
+        .unwrap();
+        if let Some((name, confidence)) = waf {
+            result.detected_waf = Some(ProviderDetection { name: name.to_string(), confidence, features: Vec::new(), layer: None });
+            result.provider_scores.insert(name.to_string(), confidence);
+            result.evidence_map.insert(
+                name.to_string(),
+                vec![Evidence {
+                    method_type: DetectionMethod::Header(header.to_string()),
+                    confidence,
+                    description: format!("{} header", header),
+                    raw_data: header.to_string(),
+                    signature_matched: header.to_string(),
+                }],
+            );
+        }
+        result
+    }
+
+    #[test]
+    fn test_majority_detection_with_mean_and_variance() {
+        let runs = vec![
+            run(Some(("CloudFlare", 0.9)), "cf-ray"),
+            run(Some(("CloudFlare", 0.9)), "cf-cache-status"),
+            run(None, ""),
+        ];
+        let merged = merge(runs, 1).unwrap();
+
+        let waf = merged.detected_waf.unwrap();
+        assert_eq!(waf.name, "CloudFlare");
+        assert!((waf.confidence - 0.6).abs() < 1e-9);
+        assert_eq!(merged.evidence_map["CloudFlare"].len(), 2);
+        assert_eq!(merged.detection_time_ms, 30);
+
+        let consensus = merged.consensus.unwrap();
+        assert_eq!((consensus.runs, consensus.failed_runs), (3, 1));
+        assert!(!consensus.unanimous());
+        let provider = &consensus.providers[0];
+        assert_eq!(provider.seen_in, 2);
+        assert!((provider.variance - 0.18).abs() < 1e-9);
+        assert_eq!((provider.min, provider.max), (0.0, 0.9));
+    }
+
+    #[test]
+    fn test_minority_detection_is_dropped() {
+        let runs = vec![run(Some(("Akamai", 0.8)), "x-akamai-transformed"), run(None, ""), run(None, "")];
+        let merged = merge(runs, 0).unwrap();
+        assert!(merged.detected_waf.is_none());
+        assert!(merged.provider_scores["Akamai"] > 0.0);
+        assert!(merge(Vec::new(), 2).is_none());
+    }
+}
@@ -0,0 +1,319 @@
+//! Certificate transparency enrichment (`--ct`)
+//!
+//! The certificates logged for a host say who terminates its TLS: CDNs issue their
+//! own (Cloudflare's issuing CAs, ACM's Amazon CAs) or put customer names on shared
+//! certificates (`sni.cloudflaressl.com`, `*.edgekey.net`). Edge-managed certificates
+//! also churn: short-lived, renewed every few weeks. These are supporting evidence
+//! only, since a certificate can outlive the CDN that asked for it.
+//!
+//! Lookups go through a [`CtSource`]; [`CrtSh`] queries crt.sh or a compatible
+//! service. The subdomain enumerator uses the same source.
+
+use crate::http::{BodyLimit, HttpClient};
+use crate::{Evidence, MethodType};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const CRT_SH_URL: &str = "https://crt.sh";
+
+/// Certificates issued within this many days count towards churn
+pub const CHURN_WINDOW_DAYS: i64 = 90;
+
+/// A logged certificate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CtCertificate {
+    pub issuer: String,
+    pub common_name: String,
+    /// Subject alternative names, lowercased
+    pub names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<DateTime<Utc>>,
+}
+
+impl CtCertificate {
+    pub fn validity_days(&self) -> Option<i64> {
+        Some((self.not_after? - self.not_before?).num_days())
+    }
+}
+
+/// A certificate transparency search backend
+#[async_trait::async_trait]
+pub trait CtSource: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &'static str;
+
+    /// Certificates logged for `query`: a host name, or `%.domain` for every subdomain
+    async fn certificates(&self, query: &str) -> Result<Vec<CtCertificate>>;
+}
+
+/// crt.sh, or a service answering its `?q=...&output=json` API
+#[derive(Debug, Clone)]
+pub struct CrtSh {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl CrtSh {
+    pub fn new() -> Result<Self> {
+        Ok(Self { client: HttpClient::new()?.with_body_limit(BodyLimit::unlimited()), base_url: CRT_SH_URL.to_string() })
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+impl Default for CrtSh {
+    fn default() -> Self {
+        Self { client: HttpClient::new().unwrap_or_default().with_body_limit(BodyLimit::unlimited()), base_url: CRT_SH_URL.to_string() }
+    }
+}
+
+#[async_trait::async_trait]
+impl CtSource for CrtSh {
+    fn name(&self) -> &'static str {
+        "crt.sh"
+    }
+
+    async fn certificates(&self, query: &str) -> Result<Vec<CtCertificate>> {
+        let url = format!("{}/?q={}&output=json", self.base_url, urlencoding::encode(query));
+        let response = self.client.get(&url).await?;
+        if response.status != 200 {
+            anyhow::bail!("certificate transparency search returned HTTP {}", response.status);
+        }
+        parse_crt_sh(&response.full_body()?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CrtShEntry {
+    #[serde(default)]
+    issuer_name: String,
+    #[serde(default)]
+    common_name: String,
+    #[serde(default)]
+    name_value: String,
+    #[serde(default)]
+    not_before: Option<String>,
+    #[serde(default)]
+    not_after: Option<String>,
+}
+
+/// Certificates from a crt.sh JSON response
+pub fn parse_crt_sh(body: &str) -> Result<Vec<CtCertificate>> {
+    let entries: Vec<CrtShEntry> = serde_json::from_str(body)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| CtCertificate {
+            issuer: entry.issuer_name,
+            names: entry.name_value.lines().map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()).collect(),
+            common_name: entry.common_name.trim().to_lowercase(),
+            not_before: entry.not_before.as_deref().and_then(parse_time),
+            not_after: entry.not_after.as_deref().and_then(parse_time),
+        })
+        .collect())
+}
+
+/// crt.sh timestamps carry no zone and are UTC
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok().map(|time| time.and_utc())
+}
+
+/// Subdomains of `domain` named by `certificates`, wildcards reduced to their base, sorted
+pub fn subdomains(certificates: &[CtCertificate], domain: &str) -> Vec<String> {
+    let suffix = format!(".{}", domain);
+    let mut names: Vec<String> = certificates
+        .iter()
+        .flat_map(|cert| cert.names.iter().chain(std::iter::once(&cert.common_name)))
+        .map(|name| name.trim_start_matches("*.").to_string())
+        .filter(|name| name.ends_with(&suffix) && !name.contains('*') && !name.contains(' '))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+struct IssuerPattern {
+    marker: &'static str,
+    provider: &'static str,
+    confidence: f64,
+}
+
+/// Issuing CAs run by or exclusive to a CDN
+const ISSUER_PATTERNS: &[IssuerPattern] = &[
+    IssuerPattern { marker: "cloudflare", provider: "CloudFlare", confidence: 0.7 },
+    IssuerPattern { marker: "o=amazon", provider: "AWS", confidence: 0.5 },
+    IssuerPattern { marker: "microsoft azure", provider: "Azure", confidence: 0.5 },
+];
+
+/// Names CDNs put on shared certificates
+const SAN_PATTERNS: &[IssuerPattern] = &[
+    IssuerPattern { marker: "cloudflaressl.com", provider: "CloudFlare", confidence: 0.7 },
+    IssuerPattern { marker: "edgekey.net", provider: "Akamai", confidence: 0.6 },
+    IssuerPattern { marker: "akamaized.net", provider: "Akamai", confidence: 0.6 },
+    IssuerPattern { marker: "fastly.net", provider: "Fastly", confidence: 0.6 },
+    IssuerPattern { marker: "fastlylb.net", provider: "Fastly", confidence: 0.6 },
+    IssuerPattern { marker: "cloudfront.net", provider: "AWS", confidence: 0.6 },
+    IssuerPattern { marker: "azureedge.net", provider: "Azure", confidence: 0.6 },
+    IssuerPattern { marker: "azurefd.net", provider: "Azure", confidence: 0.6 },
+    IssuerPattern { marker: "vercel.app", provider: "Vercel", confidence: 0.5 },
+    IssuerPattern { marker: "netlify.app", provider: "Netlify", confidence: 0.5 },
+    IssuerPattern { marker: "onrender.com", provider: "Render", confidence: 0.5 },
+    IssuerPattern { marker: "fly.dev", provider: "Fly.io", confidence: 0.5 },
+    IssuerPattern { marker: "b-cdn.net", provider: "BunnyCDN", confidence: 0.6 },
+    IssuerPattern { marker: "stackpathcdn.com", provider: "StackPath", confidence: 0.6 },
+    IssuerPattern { marker: "cdn77.org", provider: "CDN77", confidence: 0.6 },
+    IssuerPattern { marker: "cachefly.net", provider: "Cachefly", confidence: 0.6 },
+];
+
+/// How often the host's certificates are replaced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CertificateChurn {
+    /// Certificates issued in the last [`CHURN_WINDOW_DAYS`] days
+    pub recent: usize,
+    /// Share of all certificates valid for 90 days or less
+    pub short_lived_share: f64,
+    /// Distinct SAN lists among recent certificates
+    pub distinct_san_sets: usize,
+}
+
+impl CertificateChurn {
+    pub fn measure(certificates: &[CtCertificate], now: DateTime<Utc>) -> Self {
+        let recent: Vec<&CtCertificate> = certificates
+            .iter()
+            .filter(|cert| cert.not_before.is_some_and(|issued| (now - issued).num_days() <= CHURN_WINDOW_DAYS))
+            .collect();
+        let dated: Vec<i64> = certificates.iter().filter_map(CtCertificate::validity_days).collect();
+        let short_lived_share = if dated.is_empty() { 0.0 } else { dated.iter().filter(|days| **days <= 90).count() as f64 / dated.len() as f64 };
+        let mut san_sets: Vec<Vec<&String>> = recent
+            .iter()
+            .map(|cert| {
+                let mut names: Vec<&String> = cert.names.iter().collect();
+                names.sort();
+                names
+            })
+            .collect();
+        san_sets.sort();
+        san_sets.dedup();
+        Self { recent: recent.len(), short_lived_share, distinct_san_sets: san_sets.len() }
+    }
+
+    /// Renewed every few weeks with changing names, as edge-managed certificates are
+    pub fn is_edge_managed(&self) -> bool {
+        self.recent >= 3 && self.short_lived_share >= 0.8 && self.distinct_san_sets >= 2
+    }
+}
+
+/// Supporting evidence for CDN detection from `host`'s certificates
+pub fn evidence(host: &str, certificates: &[CtCertificate], now: DateTime<Utc>) -> Vec<Evidence> {
+    let mut evidence = Vec::new();
+    let mut attributed: Vec<&'static str> = Vec::new();
+    for (patterns, field) in [(ISSUER_PATTERNS, "issuer"), (SAN_PATTERNS, "SAN")] {
+        for pattern in patterns {
+            let matching: Vec<&CtCertificate> = certificates
+                .iter()
+                .filter(|cert| match field {
+                    "issuer" => cert.issuer.to_lowercase().contains(pattern.marker),
+                    _ => cert.names.iter().any(|name| name.contains(pattern.marker)),
+                })
+                .collect();
+            let Some(example) = matching.first() else { continue };
+            attributed.push(pattern.provider);
+            let sample = match field {
+                "issuer" => example.issuer.clone(),
+                _ => example.names.iter().find(|name| name.contains(pattern.marker)).cloned().unwrap_or_default(),
+            };
+            evidence.push(Evidence {
+                method_type: MethodType::Certificate,
+                confidence: pattern.confidence,
+                description: format!("{} of {} logged certificate(s) - {} detected via CT {}", matching.len(), certificates.len(), pattern.provider, field),
+                raw_data: format!("{}: {}", host, sample),
+                signature_matched: format!("ct-{}-{}", field.to_lowercase(), pattern.provider.to_lowercase()),
+            });
+        }
+    }
+
+    let churn = CertificateChurn::measure(certificates, now);
+    if churn.is_edge_managed() {
+        attributed.sort();
+        attributed.dedup();
+        let provider = if attributed.is_empty() { "an edge provider".to_string() } else { attributed.join("/") };
+        evidence.push(Evidence {
+            method_type: MethodType::Certificate,
+            confidence: 0.3,
+            description: format!(
+                "{} certificates in {} days, {:.0}% short-lived - renewal pattern of certificates managed by {}",
+                churn.recent,
+                CHURN_WINDOW_DAYS,
+                churn.short_lived_share * 100.0,
+                provider
+            ),
+            raw_data: format!("{}: {} distinct SAN sets", host, churn.distinct_san_sets),
+            signature_matched: "ct-churn".to_string(),
+        });
+    }
+    evidence
+}
+
+/// Runs CT lookups for detection
+#[derive(Debug, Clone)]
+pub struct CtAnalyzer {
+    source: Arc<dyn CtSource>,
+}
+
+impl CtAnalyzer {
+    pub fn new(source: Arc<dyn CtSource>) -> Self {
+        Self { source }
+    }
+
+    pub fn source(&self) -> Arc<dyn CtSource> {
+        Arc::clone(&self.source)
+    }
+
+    pub async fn analyze(&self, url: &str) -> Result<Vec<Evidence>> {
+        let host = url::Url::parse(url)?.host_str().map(str::to_lowercase).ok_or_else(|| anyhow::anyhow!("URL '{}' has no host", url))?;
+        let certificates = self.source.certificates(&host).await?;
+        Ok(evidence(&host, &certificates, Utc::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRT_SH: &str = r#"[
+        {"issuer_name": "C=US, O=Cloudflare, Inc., CN=Cloudflare Inc ECC CA-3", "common_name": "sni.cloudflaressl.com",
+         "name_value": "shop.example.com\nsni.cloudflaressl.com", "not_before": "2024-05-01T00:00:00", "not_after": "2024-07-30T23:59:59"},
+        {"issuer_name": "C=US, O=Google Trust Services, CN=WE1", "common_name": "shop.example.com",
+         "name_value": "shop.example.com\n*.example.com", "not_before": "2024-06-01T00:00:00", "not_after": "2024-08-30T00:00:00"},
+        {"issuer_name": "C=US, O=Let's Encrypt, CN=R3", "common_name": "shop.example.com",
+         "name_value": "shop.example.com", "not_before": "2024-06-20T12:00:00.123", "not_after": "2024-09-18T12:00:00"}
+    ]"#;
+
+    #[test]
+    fn test_issuer_san_and_churn_evidence() {
+        let certificates = parse_crt_sh(CRT_SH).unwrap();
+        assert_eq!(certificates.len(), 3);
+        assert_eq!(certificates[0].validity_days(), Some(90));
+
+        let now = "2024-07-01T00:00:00Z".parse().unwrap();
+        let churn = CertificateChurn::measure(&certificates, now);
+        assert_eq!((churn.recent, churn.distinct_san_sets), (3, 3));
+        assert!(churn.is_edge_managed());
+
+        let evidence = evidence("shop.example.com", &certificates, now);
+        let signatures: Vec<&str> = evidence.iter().map(|e| e.signature_matched.as_str()).collect();
+        assert_eq!(signatures, vec!["ct-issuer-cloudflare", "ct-san-cloudflare", "ct-churn"]);
+        assert!(evidence[2].description.ends_with("managed by CloudFlare"));
+    }
+
+    #[test]
+    fn test_subdomains_from_certificates() {
+        let certificates = parse_crt_sh(CRT_SH).unwrap();
+        assert_eq!(subdomains(&certificates, "example.com"), vec!["shop.example.com"]);
+    }
+}
@@ -0,0 +1,203 @@
+//! Library facade
+//!
+//! Wires the registry, providers, HTTP client and engine together so library users
+//! do not have to:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use std::time::Duration;
+//! use waf_detector::Detector;
+//!
+//! let detector = Detector::builder()
+//!     .with_default_providers()
+//!     .passive_only()
+//!     .timeout(Duration::from_secs(5))
+//!     .build()?;
+//! let result = detector.detect("https://example.com").await?;
+//! println!("{:?}", result.detected_waf);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::confidence::{AdvancedScoring, ScoringConfig};
+use crate::dns::AddressFamily;
+use crate::engine::DetectionEngine;
+use crate::http::{BodyLimit, HttpClient, HttpResponse, RetryPolicy};
+use crate::providers::{default_providers, Provider};
+use crate::registry::ProviderRegistry;
+use crate::signatures::SignatureBundle;
+use crate::{DetectionOptions, DetectionResult};
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+
+/// Per-request timeout when none is configured
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Targets scanned at once by `detect_batch` when none is configured
+pub const DEFAULT_CONCURRENCY: usize = 3;
+
+/// Ready-to-use WAF/CDN detector, created with [`Detector::builder`]
+#[derive(Debug, Clone)]
+pub struct Detector {
+    engine: DetectionEngine,
+    concurrency: usize,
+}
+
+/// Builder for [`Detector`]
+#[derive(Debug, Clone, Default)]
+pub struct DetectorBuilder {
+    providers: Vec<Provider>,
+    options: DetectionOptions,
+    passive: bool,
+    timeout: Option<Duration>,
+    proxy: Option<String>,
+    address_family: AddressFamily,
+    retries: Option<u32>,
+    concurrency: Option<usize>,
+    scoring: Option<ScoringConfig>,
+    signatures: Option<SignatureBundle>,
+    body_limit: Option<BodyLimit>,
+}
+
+impl Detector {
+    pub fn builder() -> DetectorBuilder {
+        DetectorBuilder::default()
+    }
+
+    pub async fn detect(&self, url: &str) -> Result<DetectionResult> {
+        self.engine.detect(url).await
+    }
+
+    /// Analyze an already-captured response without any network I/O
+    pub async fn detect_from_response(&self, url: &str, response: HttpResponse) -> Result<DetectionResult> {
+        self.engine.detect_from_response(url, response).await
+    }
+
+    /// Scan several targets concurrently. Results come back in input order; targets
+    /// that fail carry `DetectionResult::error` instead of aborting the batch.
+    pub async fn detect_batch(&self, urls: &[&str]) -> Result<Vec<DetectionResult>> {
+        let results = self.engine.detect_batch(urls, self.concurrency).await?;
+        urls.iter()
+            .map(|url| results.get(*url).cloned().ok_or_else(|| anyhow!("No result for {}", url)))
+            .collect()
+    }
+
+    /// The underlying engine, for anything the facade does not cover
+    pub fn engine(&self) -> &DetectionEngine {
+        &self.engine
+    }
+}
+
+impl DetectorBuilder {
+    /// Register every built-in provider
+    pub fn with_default_providers(mut self) -> Self {
+        self.providers.extend(default_providers());
+        self
+    }
+
+    pub fn with_provider(mut self, provider: Provider) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Techniques to run on every scan (probing techniques are off by default)
+    pub fn with_options(mut self, options: DetectionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Strict passive mode: exactly one GET request per target, no probes, no retries
+    /// and no redirects followed
+    pub fn passive_only(mut self) -> Self {
+        self.passive = true;
+        self
+    }
+
+    /// Per-request timeout (default 10s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send requests through an `http://`, `https://` or `socks5://` proxy
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Only connect over one IP version (default: both)
+    pub fn address_family(mut self, family: AddressFamily) -> Self {
+        self.address_family = family;
+        self
+    }
+
+    /// Retries for transient network errors (default 2; never in `passive_only` mode)
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Targets scanned at once by `detect_batch` (default 3)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Override evidence weights and thresholds (same format as `--scoring`)
+    pub fn scoring(mut self, scoring: ScoringConfig) -> Self {
+        self.scoring = Some(scoring);
+        self
+    }
+
+    /// Body bytes read per response (default 2 MiB from the start, no tail sample)
+    pub fn body_limit(mut self, body_limit: BodyLimit) -> Self {
+        self.body_limit = Some(body_limit);
+        self
+    }
+
+    /// Extend the providers with a signature bundle (e.g. from `signatures::load_installed`)
+    pub fn signatures(mut self, bundle: SignatureBundle) -> Self {
+        self.signatures = Some(bundle);
+        self
+    }
+
+    pub fn build(self) -> Result<Detector> {
+        if self.providers.is_empty() {
+            return Err(anyhow!("No providers registered (use with_default_providers or with_provider)"));
+        }
+
+        let mut client = HttpClient::with_address_family(
+            self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            self.proxy.as_deref(),
+            self.address_family,
+        )?;
+        if let Some(retries) = self.retries {
+            client = client.with_retry_policy(RetryPolicy::with_retries(retries));
+        }
+        if let Some(body_limit) = self.body_limit {
+            client = client.with_body_limit(body_limit);
+        }
+
+        let registry = ProviderRegistry::new();
+        for provider in self.providers {
+            registry.register_provider(provider)?;
+        }
+        if let Some(bundle) = &self.signatures {
+            registry.apply_signatures(bundle)?;
+        }
+        if let Some(scoring) = self.scoring {
+            registry.set_advanced_scoring(AdvancedScoring::new().with_config(scoring)?);
+        }
+
+        let mut engine = DetectionEngine::new(registry)
+            .with_options(self.options)
+            .with_http_client(client);
+        if self.passive {
+            engine = engine.with_passive_mode();
+        }
+
+        Ok(Detector {
+            engine,
+            concurrency: self.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1),
+        })
+    }
+}
@@ -0,0 +1,788 @@
+//! CNAME pattern matching against known provider domains (`dns` feature)
+
+use super::passive::{self, PassiveDnsRecord, PassiveDnsSource};
+use crate::signatures::{DnsSignature, IpRangeSignature};
+use crate::error::WafDetectorError;
+use crate::{Evidence, MethodType};
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// DNS analysis results
+#[derive(Debug, Clone)]
+pub struct DnsAnalysis {
+    pub domain: String,
+    pub cname_records: Vec<String>,
+    pub provider_matches: Vec<ProviderMatch>,
+    pub confidence: f64,
+}
+
+/// Provider match from DNS analysis
+#[derive(Debug, Clone)]
+pub struct ProviderMatch {
+    pub provider: String,
+    pub matched_pattern: String,
+    pub cname_record: String,
+    pub confidence: f64,
+}
+
+/// DNS resolver with provider pattern matching
+#[derive(Debug, Clone)]
+pub struct DnsAnalyzer {
+    provider_patterns: HashMap<String, Vec<DnsPattern>>,
+    passive_dns: Vec<Arc<dyn PassiveDnsSource>>,
+    ip_ranges: Vec<IpRangeSignature>,
+}
+
+/// DNS pattern for provider identification
+#[derive(Debug, Clone)]
+pub struct DnsPattern {
+    pub pattern: Regex,
+    pub confidence: f64,
+    pub description: String,
+}
+
+impl DnsAnalyzer {
+    pub fn new() -> Self {
+        let mut provider_patterns = HashMap::new();
+        
+        // CloudFlare CNAME patterns
+        provider_patterns.insert("CloudFlare".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.cloudflare\.net$").unwrap(),
+                confidence: 0.98,
+                description: "CloudFlare CDN CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.cloudflaressl\.com$").unwrap(),
+                confidence: 0.95,
+                description: "CloudFlare SSL CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.cf-dns\.com$").unwrap(),
+                confidence: 0.90,
+                description: "CloudFlare DNS CNAME record".to_string(),
+            },
+        ]);
+        
+        // AWS CloudFront patterns
+        provider_patterns.insert("AWS".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.cloudfront\.net$").unwrap(),
+                confidence: 0.98,
+                description: "AWS CloudFront CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r"d[0-9a-z]+\.cloudfront\.net$").unwrap(),
+                confidence: 0.99,
+                description: "AWS CloudFront distribution CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.amazonaws\.com$").unwrap(),
+                confidence: 0.95,
+                description: "AWS service CNAME record".to_string(),
+            },
+        ]);
+        
+        // Fastly patterns
+        provider_patterns.insert("Fastly".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.fastly\.com$").unwrap(),
+                confidence: 0.98,
+                description: "Fastly CDN CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.fastlylb\.net$").unwrap(),
+                confidence: 0.95,
+                description: "Fastly load balancer CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.global\.fastly\.net$").unwrap(),
+                confidence: 0.96,
+                description: "Fastly global network CNAME".to_string(),
+            },
+        ]);
+        
+        // Akamai patterns
+        provider_patterns.insert("Akamai".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.akamai\.net$").unwrap(),
+                confidence: 0.98,
+                description: "Akamai CDN CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.akamaized\.net$").unwrap(),
+                confidence: 0.95,
+                description: "Akamai edge network CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.akamaihd\.net$").unwrap(),
+                confidence: 0.96,
+                description: "Akamai HD network CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.edgesuite\.net$").unwrap(),
+                confidence: 0.94,
+                description: "Akamai EdgeSuite CNAME".to_string(),
+            },
+        ]);
+        
+        // Vercel patterns
+        provider_patterns.insert("Vercel".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.vercel\.app$").unwrap(),
+                confidence: 0.99,
+                description: "Vercel deployment CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.vercel-dns\.com$").unwrap(),
+                confidence: 0.96,
+                description: "Vercel DNS CNAME record".to_string(),
+            },
+        ]);
+        
+        // Netlify patterns
+        provider_patterns.insert("Netlify".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.netlify\.app$").unwrap(),
+                confidence: 0.99,
+                description: "Netlify site CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.netlify\.com$").unwrap(),
+                confidence: 0.96,
+                description: "Netlify load balancer CNAME".to_string(),
+            },
+        ]);
+        
+        // Render patterns
+        provider_patterns.insert("Render".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.onrender\.com$").unwrap(),
+                confidence: 0.99,
+                description: "Render service CNAME".to_string(),
+            },
+        ]);
+        
+        // Fly.io patterns
+        provider_patterns.insert("Fly.io".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.fly\.dev$").unwrap(),
+                confidence: 0.99,
+                description: "Fly.io app CNAME".to_string(),
+            },
+        ]);
+        
+        // BunnyCDN patterns
+        provider_patterns.insert("BunnyCDN".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.b-cdn\.net$").unwrap(),
+                confidence: 0.98,
+                description: "BunnyCDN pull zone CNAME".to_string(),
+            },
+        ]);
+        
+        // StackPath patterns
+        provider_patterns.insert("StackPath".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.stackpathcdn\.com$").unwrap(),
+                confidence: 0.98,
+                description: "StackPath CDN site CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.stackpathdns\.com$").unwrap(),
+                confidence: 0.96,
+                description: "StackPath edge DNS CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.hwcdn\.net$").unwrap(),
+                confidence: 0.94,
+                description: "StackPath (Highwinds) CDN CNAME".to_string(),
+            },
+        ]);
+        
+        // CDN77 patterns
+        provider_patterns.insert("CDN77".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.cdn77\.org$").unwrap(),
+                confidence: 0.98,
+                description: "CDN77 resource CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.cdn77\.net$").unwrap(),
+                confidence: 0.95,
+                description: "CDN77 network CNAME".to_string(),
+            },
+        ]);
+        
+        // Cachefly patterns
+        provider_patterns.insert("Cachefly".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.cachefly\.net$").unwrap(),
+                confidence: 0.98,
+                description: "Cachefly CDN CNAME".to_string(),
+            },
+        ]);
+        
+        // Alibaba Cloud patterns
+        provider_patterns.insert("AlibabaCloud".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.yundunwaf\d*\.com$").unwrap(),
+                confidence: 0.98,
+                description: "Alibaba Cloud WAF CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.(ali)?kunlun[a-z]*\.com$").unwrap(),
+                confidence: 0.97,
+                description: "Alibaba Cloud CDN (Kunlun) CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.aliyundunwaf\.com$").unwrap(),
+                confidence: 0.97,
+                description: "Alibaba Cloud WAF international CNAME".to_string(),
+            },
+        ]);
+        
+        // Tencent Cloud patterns
+        provider_patterns.insert("TencentCloud".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.eo\.dnse\d\.com$").unwrap(),
+                confidence: 0.98,
+                description: "Tencent EdgeOne CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.edgeone\.app$").unwrap(),
+                confidence: 0.97,
+                description: "Tencent EdgeOne Pages CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.cdn\.dnsv1\.com(\.cn)?$").unwrap(),
+                confidence: 0.96,
+                description: "Tencent Cloud CDN CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.tc\.cdntip\.com$").unwrap(),
+                confidence: 0.95,
+                description: "Tencent Cloud CDN CNAME".to_string(),
+            },
+        ]);
+        
+        // Huawei Cloud patterns
+        provider_patterns.insert("HuaweiCloud".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.huaweicloudwaf\.com$").unwrap(),
+                confidence: 0.98,
+                description: "Huawei Cloud WAF CNAME".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.cdnhwc\d+\.com$").unwrap(),
+                confidence: 0.96,
+                description: "Huawei Cloud CDN CNAME".to_string(),
+            },
+        ]);
+        
+        // Azure Front Door / Traffic Manager patterns
+        provider_patterns.insert("Azure".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.azurefd\.net$").unwrap(),
+                confidence: 0.98,
+                description: "Azure Front Door CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.trafficmanager\.net$").unwrap(),
+                confidence: 0.90,
+                description: "Azure Traffic Manager CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.azureedge\.net$").unwrap(),
+                confidence: 0.95,
+                description: "Azure CDN CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.t-msedge\.net$").unwrap(),
+                confidence: 0.90,
+                description: "Microsoft edge network CNAME record".to_string(),
+            },
+        ]);
+        
+        // Google Cloud patterns
+        provider_patterns.insert("GoogleCloud".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r"^ghs\.googlehosted\.com$").unwrap(),
+                confidence: 0.95,
+                description: "Google hosted services CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r".*\.googleusercontent\.com$").unwrap(),
+                confidence: 0.92,
+                description: "Google Cloud load balancer CNAME record".to_string(),
+            },
+            DnsPattern {
+                pattern: Regex::new(r"^c\.storage\.googleapis\.com$").unwrap(),
+                confidence: 0.95,
+                description: "Google Cloud Storage CNAME record".to_string(),
+            },
+        ]);
+        
+        // Additional common CDN patterns
+        provider_patterns.insert("KeyCDN".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.keycdn\.com$").unwrap(),
+                confidence: 0.98,
+                description: "KeyCDN CNAME record".to_string(),
+            },
+        ]);
+        
+        provider_patterns.insert("MaxCDN".to_string(), vec![
+            DnsPattern {
+                pattern: Regex::new(r".*\.maxcdn\.com$").unwrap(),
+                confidence: 0.98,
+                description: "MaxCDN CNAME record".to_string(),
+            },
+        ]);
+        
+        Self { provider_patterns, passive_dns: Vec::new(), ip_ranges: Vec::new() }
+    }
+
+    /// Add CNAME patterns from a signature bundle
+    pub fn with_patterns(mut self, signatures: &[DnsSignature]) -> Result<Self> {
+        for signature in signatures {
+            self.provider_patterns.entry(signature.provider.clone()).or_default().push(DnsPattern {
+                pattern: crate::signatures::compile(&signature.pattern, signature.confidence)?,
+                confidence: signature.confidence,
+                description: signature.description.clone().unwrap_or_else(|| format!("{} CNAME record", signature.provider)),
+            });
+        }
+        Ok(self)
+    }
+
+    /// Also resolve the host and attribute addresses inside these provider ranges
+    pub fn with_ip_ranges(mut self, ranges: Vec<IpRangeSignature>) -> Self {
+        self.ip_ranges = ranges;
+        self
+    }
+
+    /// Also match the host's former CNAMEs from passive DNS history
+    pub fn with_passive_dns(mut self, sources: Vec<Arc<dyn PassiveDnsSource>>) -> Self {
+        self.passive_dns = sources;
+        self
+    }
+
+    pub fn passive_dns_sources(&self) -> &[Arc<dyn PassiveDnsSource>] {
+        &self.passive_dns
+    }
+    
+    /// Perform DNS analysis on a domain
+    pub async fn analyze(&self, domain: &str) -> Result<Vec<Evidence>> {
+        let mut evidence = Vec::new();
+        
+        // Clean the domain (remove protocol, path, etc.)
+        let clean_domain = self.extract_domain(domain);
+        
+        // Resolve CNAME records
+        let cname_records = self.resolve_cname(&clean_domain).await?;
+        
+        // Check each CNAME record against provider patterns
+        for cname in &cname_records {
+            for (provider, patterns) in &self.provider_patterns {
+                for pattern in patterns {
+                    if pattern.pattern.is_match(cname) {
+                        evidence.push(Evidence {
+                            method_type: MethodType::DNS("cname".to_string()),
+                            confidence: pattern.confidence,
+                            description: format!(
+                                "{} - {} detected via CNAME record",
+                                pattern.description,
+                                provider
+                            ),
+                            raw_data: format!("{} -> {}", clean_domain, cname),
+                            signature_matched: format!("dns-cname-{}", provider.to_lowercase()),
+                        });
+                    }
+                }
+            }
+        }
+
+        if !self.ip_ranges.is_empty() {
+            if let Ok(resolved) = super::resolve_addresses(&clean_domain).await {
+                let addresses: Vec<IpAddr> = resolved.ipv4.iter().chain(&resolved.ipv6).copied().collect();
+                evidence.extend(self.ip_range_evidence(&clean_domain, &addresses));
+            }
+        }
+
+        if !self.passive_dns.is_empty() {
+            let history = passive::history(&self.passive_dns, &clean_domain).await;
+            evidence.extend(self.former_cname_evidence(&clean_domain, &cname_records, &history));
+        }
+        
+        Ok(evidence)
+    }
+
+    /// Addresses of `domain` inside a provider's published ranges, one per provider
+    pub fn ip_range_evidence(&self, domain: &str, addresses: &[IpAddr]) -> Vec<Evidence> {
+        let mut evidence: Vec<Evidence> = Vec::new();
+        for range in &self.ip_ranges {
+            let signature = format!("dns-ip-range-{}", range.provider.to_lowercase());
+            if evidence.iter().any(|e| e.signature_matched == signature) {
+                continue;
+            }
+            if let Some(addr) = addresses.iter().find(|addr| range.cidr.contains(**addr)) {
+                evidence.push(Evidence {
+                    method_type: MethodType::DNS("ip-range".to_string()),
+                    confidence: 0.85,
+                    description: format!("{} resolves into {} address range {}", domain, range.provider, range.cidr),
+                    raw_data: format!("{} -> {}", domain, addr),
+                    signature_matched: signature,
+                });
+            }
+        }
+        evidence
+    }
+
+    /// Providers the host was CNAMEd to before; weak, as the site may have moved since
+    pub fn former_cname_evidence(&self, domain: &str, current: &[String], history: &[PassiveDnsRecord]) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+        for record in passive::former_cnames(history, current) {
+            for (provider, patterns) in &self.provider_patterns {
+                if let Some(pattern) = patterns.iter().find(|pattern| pattern.pattern.is_match(&record.value)) {
+                    evidence.push(Evidence {
+                        method_type: MethodType::DNS("cname-history".to_string()),
+                        confidence: pattern.confidence * 0.4,
+                        description: format!(
+                            "{} - {} used before, former CNAME ({}, {})",
+                            pattern.description,
+                            provider,
+                            record.source,
+                            record.last_seen_label()
+                        ),
+                        raw_data: format!("{} -> {}", domain, record.value),
+                        signature_matched: format!("dns-history-cname-{}", provider.to_lowercase()),
+                    });
+                }
+            }
+        }
+        evidence
+    }
+    
+    /// Extract clean domain from URL, in ASCII (punycode) form for the resolver
+    fn extract_domain(&self, url: &str) -> String {
+        let domain = self.extract_host(url);
+        crate::idn::to_ascii(&domain).unwrap_or(domain)
+    }
+
+    fn extract_host(&self, url: &str) -> String {
+        let url = url.trim();
+        
+        // Remove protocol
+        let without_protocol = if url.contains("://") {
+            url.split("://").nth(1).unwrap_or(url)
+        } else {
+            url
+        };
+        
+        // Remove path, query, and fragment
+        let domain_part = without_protocol
+            .split('/')
+            .next()
+            .unwrap_or(without_protocol)
+            .split('?')
+            .next()
+            .unwrap_or(without_protocol)
+            .split('#')
+            .next()
+            .unwrap_or(without_protocol);
+        
+        // Remove port
+        if let Some(colon_pos) = domain_part.rfind(':') {
+            // Check if it's likely a port (numeric after colon)
+            let after_colon = &domain_part[colon_pos + 1..];
+            if after_colon.chars().all(|c| c.is_ascii_digit()) {
+                return domain_part[..colon_pos].to_string();
+            }
+        }
+        
+        domain_part.to_string()
+    }
+    
+    /// Resolve CNAME records for a domain (through the scan's record/replay tape, if any)
+    async fn resolve_cname(&self, domain: &str) -> Result<Vec<String>> {
+        crate::replay::dns(crate::replay::LookupKind::Cname, domain, self.lookup_cname(domain)).await
+    }
+
+    async fn lookup_cname(&self, domain: &str) -> Result<Vec<String>> {
+        use tokio::process::Command;
+        
+        // Use system's dig command for DNS resolution
+        let output = Command::new("dig")
+            .args(["+short", "CNAME", domain])
+            .output()
+            .await;
+        
+        match output {
+            Ok(output) => {
+                if output.status.success() {
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    let cnames: Vec<String> = stdout
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(|line| {
+                            // Remove trailing dot if present
+                            let clean = line.trim();
+                            if clean.ends_with('.') {
+                                clean[..clean.len() - 1].to_string()
+                            } else {
+                                clean.to_string()
+                            }
+                        })
+                        .collect();
+                    Ok(cnames)
+                } else {
+                    // If dig fails, try with nslookup as fallback
+                    self.resolve_cname_nslookup(domain).await
+                }
+            }
+            Err(_) => {
+                // If dig is not available, try nslookup
+                self.resolve_cname_nslookup(domain).await
+            }
+        }
+    }
+    
+    /// Fallback CNAME resolution using nslookup
+    async fn resolve_cname_nslookup(&self, domain: &str) -> Result<Vec<String>> {
+        use tokio::process::Command;
+        
+        let output = Command::new("nslookup")
+            .args(["-type=CNAME", domain])
+            .output()
+            .await
+            .map_err(|e| WafDetectorError::Dns {
+                host: domain.to_string(),
+                message: format!("neither dig nor nslookup could be run: {}", e),
+            })?;
+        
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+        
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut cnames = Vec::new();
+        
+        // Parse nslookup output for CNAME records
+        for line in stdout.lines() {
+            if line.contains("canonical name") {
+                if let Some(cname_part) = line.split("canonical name = ").nth(1) {
+                    let cname = cname_part.trim();
+                    let clean_cname = if cname.ends_with('.') {
+                        cname[..cname.len() - 1].to_string()
+                    } else {
+                        cname.to_string()
+                    };
+                    cnames.push(clean_cname);
+                }
+            }
+        }
+        
+        Ok(cnames)
+    }
+    
+    /// Get all supported providers and their patterns
+    pub fn get_supported_providers(&self) -> Vec<String> {
+        self.provider_patterns.keys().cloned().collect()
+    }
+    
+    /// Get pattern count for a provider
+    pub fn get_pattern_count(&self, provider: &str) -> usize {
+        self.provider_patterns
+            .get(provider)
+            .map(|patterns| patterns.len())
+            .unwrap_or(0)
+    }
+}
+
+impl Default for DnsAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_analyzer_creation() {
+        let analyzer = DnsAnalyzer::new();
+        assert!(analyzer.provider_patterns.len() > 0);
+        assert!(analyzer.provider_patterns.contains_key("CloudFlare"));
+        assert!(analyzer.provider_patterns.contains_key("AWS"));
+    }
+    
+    #[test]
+    fn test_extract_domain() {
+        let analyzer = DnsAnalyzer::new();
+        
+        assert_eq!(analyzer.extract_domain("https://example.com"), "example.com");
+        assert_eq!(analyzer.extract_domain("http://example.com/path"), "example.com");
+        assert_eq!(analyzer.extract_domain("example.com"), "example.com");
+        assert_eq!(analyzer.extract_domain("example.com:8080"), "example.com");
+        assert_eq!(analyzer.extract_domain("https://example.com:443/path?query=1"), "example.com");
+        assert_eq!(analyzer.extract_domain("subdomain.example.com"), "subdomain.example.com");
+        assert_eq!(analyzer.extract_domain("https://münchen.de/"), "xn--mnchen-3ya.de");
+    }
+    
+    #[test]
+    fn test_provider_patterns() {
+        let analyzer = DnsAnalyzer::new();
+        
+        // Test CloudFlare patterns
+        let cf_patterns = analyzer.provider_patterns.get("CloudFlare").unwrap();
+        assert!(cf_patterns.iter().any(|p| p.pattern.is_match("target.cloudflare.net")));
+        assert!(cf_patterns.iter().any(|p| p.pattern.is_match("ssl.cloudflaressl.com")));
+        
+        // Test AWS patterns
+        let aws_patterns = analyzer.provider_patterns.get("AWS").unwrap();
+        assert!(aws_patterns.iter().any(|p| p.pattern.is_match("d123abc.cloudfront.net")));
+        assert!(aws_patterns.iter().any(|p| p.pattern.is_match("example.amazonaws.com")));
+        
+        // Test Fastly patterns
+        let fastly_patterns = analyzer.provider_patterns.get("Fastly").unwrap();
+        assert!(fastly_patterns.iter().any(|p| p.pattern.is_match("target.fastly.com")));
+        
+        // Test Akamai patterns
+        let akamai_patterns = analyzer.provider_patterns.get("Akamai").unwrap();
+        assert!(akamai_patterns.iter().any(|p| p.pattern.is_match("target.akamai.net")));
+        assert!(akamai_patterns.iter().any(|p| p.pattern.is_match("target.edgesuite.net")));
+    }
+    
+    #[test]
+    fn test_platform_patterns() {
+        let analyzer = DnsAnalyzer::new();
+        let matches = |provider: &str, cname: &str| analyzer.provider_patterns[provider].iter().any(|p| p.pattern.is_match(cname));
+        assert!(matches("Netlify", "my-site.netlify.app"));
+        assert!(matches("Netlify", "apex-loadbalancer.netlify.com"));
+        assert!(matches("Render", "my-service.onrender.com"));
+        assert!(matches("Fly.io", "my-app.fly.dev"));
+        assert!(!matches("Fly.io", "butterfly.dev"));
+    }
+    
+    #[test]
+    fn test_mid_tier_cdn_patterns() {
+        let analyzer = DnsAnalyzer::new();
+        let matches = |provider: &str, cname: &str| analyzer.provider_patterns[provider].iter().any(|p| p.pattern.is_match(cname));
+        assert!(matches("BunnyCDN", "example.b-cdn.net"));
+        assert!(matches("StackPath", "a1b2c3d4.stackpathcdn.com"));
+        assert!(matches("StackPath", "cds.x7f9k2p4.hwcdn.net"));
+        assert!(matches("CDN77", "1234567890.rsc.cdn77.org"));
+        assert!(matches("Cachefly", "example.cachefly.net"));
+        assert!(!matches("BunnyCDN", "example.cdn.net"));
+    }
+    
+    #[test]
+    fn test_chinese_cloud_patterns() {
+        let analyzer = DnsAnalyzer::new();
+        let matches = |provider: &str, cname: &str| analyzer.provider_patterns[provider].iter().any(|p| p.pattern.is_match(cname));
+        assert!(matches("AlibabaCloud", "abc123.yundunwaf3.com"));
+        assert!(matches("AlibabaCloud", "www.example.com.w.kunlunsl.com"));
+        assert!(matches("AlibabaCloud", "www.example.com.w.alikunlun.com"));
+        assert!(matches("TencentCloud", "www.example.com.eo.dnse2.com"));
+        assert!(matches("TencentCloud", "www.example.com.cdn.dnsv1.com"));
+        assert!(matches("HuaweiCloud", "abcd1234.vip1.huaweicloudwaf.com"));
+        assert!(matches("HuaweiCloud", "www.example.com.c.cdnhwc1.com"));
+    }
+    
+    #[test]
+    fn test_confidence_levels() {
+        let analyzer = DnsAnalyzer::new();
+        // CloudFlare main pattern should have high confidence
+        let cf_patterns = analyzer.provider_patterns.get("CloudFlare").unwrap();
+        let main_pattern = cf_patterns.iter().find(|p| p.pattern.to_string().contains("cloudflare")).unwrap();
+        assert!(main_pattern.confidence >= 0.95, "CloudFlare confidence was {}", main_pattern.confidence);
+        // AWS CloudFront distribution pattern should have very high confidence
+        let aws_patterns = analyzer.provider_patterns.get("AWS").unwrap();
+        let dist_pattern = aws_patterns.iter().find(|p| p.pattern.to_string().contains("cloudfront")).unwrap();
+        assert!(dist_pattern.confidence >= 0.95, "AWS CloudFront confidence was {}", dist_pattern.confidence);
+    }
+    
+    #[test]
+    fn test_get_supported_providers() {
+        let analyzer = DnsAnalyzer::new();
+        let providers = analyzer.get_supported_providers();
+        
+        assert!(providers.contains(&"CloudFlare".to_string()));
+        assert!(providers.contains(&"AWS".to_string()));
+        assert!(providers.contains(&"Fastly".to_string()));
+        assert!(providers.contains(&"Akamai".to_string()));
+        assert!(providers.contains(&"Vercel".to_string()));
+        assert!(providers.contains(&"Netlify".to_string()));
+        assert!(providers.contains(&"Render".to_string()));
+        assert!(providers.contains(&"Fly.io".to_string()));
+        assert!(providers.contains(&"BunnyCDN".to_string()));
+        assert!(providers.contains(&"StackPath".to_string()));
+        assert!(providers.contains(&"CDN77".to_string()));
+        assert!(providers.contains(&"Cachefly".to_string()));
+        assert!(providers.contains(&"AlibabaCloud".to_string()));
+        assert!(providers.contains(&"TencentCloud".to_string()));
+        assert!(providers.contains(&"HuaweiCloud".to_string()));
+        assert!(providers.contains(&"Azure".to_string()));
+        assert!(providers.contains(&"GoogleCloud".to_string()));
+    }
+    
+    #[test]
+    fn test_get_pattern_count() {
+        let analyzer = DnsAnalyzer::new();
+        
+        assert!(analyzer.get_pattern_count("CloudFlare") > 0);
+        assert!(analyzer.get_pattern_count("AWS") > 0);
+        assert_eq!(analyzer.get_pattern_count("NonExistentProvider"), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_dns_analysis_mock() {
+        let analyzer = DnsAnalyzer::new();
+        
+        // Test domain extraction works
+        let domain = analyzer.extract_domain("https://example.com/test");
+        assert_eq!(domain, "example.com");
+        
+        // Note: We can't easily test actual DNS resolution in unit tests
+        // without mocking the DNS system or having known test domains
+        // This would require integration tests with controlled DNS records
+    }
+
+    #[test]
+    fn test_former_cname_evidence() {
+        let analyzer = DnsAnalyzer::new();
+        let record = |value: &str| PassiveDnsRecord {
+            record_type: passive::RecordType::Cname,
+            value: value.to_string(),
+            source: "circl".to_string(),
+            first_seen: None,
+            last_seen: Some("2019-02-03T00:00:00Z".parse().unwrap()),
+        };
+        let history = vec![record("shop.example.com.akamaized.net"), record("shop.example.com.cdn.cloudflare.net")];
+
+        let evidence = analyzer.former_cname_evidence("shop.example.com", &["shop.example.com.cdn.cloudflare.net".to_string()], &history);
+        assert_eq!(evidence.len(), 1);
+        assert_eq!(evidence[0].signature_matched, "dns-history-cname-akamai");
+        assert!(evidence[0].confidence < 0.5);
+        assert!(evidence[0].description.ends_with("(circl, last seen 2019-02-03)"));
+    }
+
+    #[test]
+    fn test_bundle_patterns_and_ip_ranges() {
+        let bundle = crate::signatures::SignatureBundle::from_json(br#"{"version": "1", "published": "2026-10-01T00:00:00Z",
+            "dns_patterns": [{"provider": "Imperva", "pattern": "\\.incapdns\\.net$", "confidence": 0.95}],
+            "ip_ranges": [{"provider": "Imperva", "cidr": "45.60.0.0/16"}, {"provider": "Imperva", "cidr": "45.64.64.0/22"}]}"#).unwrap();
+        let analyzer = DnsAnalyzer::new().with_patterns(&bundle.dns_patterns).unwrap().with_ip_ranges(bundle.ip_ranges);
+        assert!(analyzer.provider_patterns["Imperva"][0].pattern.is_match("shop.x7k2.incapdns.net"));
+
+        let addresses = ["45.60.1.1".parse().unwrap(), "45.64.64.9".parse().unwrap(), "192.0.2.1".parse().unwrap()];
+        let evidence = analyzer.ip_range_evidence("shop.example.com", &addresses);
+        assert_eq!(evidence.len(), 1);
+        assert_eq!(evidence[0].raw_data, "shop.example.com -> 45.60.1.1");
+        assert!(analyzer.ip_range_evidence("shop.example.com", &addresses[2..]).is_empty());
+    }
+}
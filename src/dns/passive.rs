@@ -0,0 +1,419 @@
+//! Passive DNS history (`--pdns`)
+//!
+//! Passive DNS services record what a name resolved to over time. Before a site moved
+//! behind a CDN its A records usually pointed straight at the origin, and an old CNAME
+//! shows which edge it used before. The DNS analyzer reports former CNAMEs of known
+//! providers; origin discovery tries former addresses that are no longer the edge's.
+//!
+//! Each service is a [`PassiveDnsSource`] with its own API key, chosen with
+//! `--pdns securitytrails:KEY`, `--pdns virustotal:KEY` or `--pdns circl:USER:PASSWORD`.
+
+use crate::http::{BodyLimit, HttpClient};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+pub const SECURITYTRAILS_URL: &str = "https://api.securitytrails.com";
+pub const VIRUSTOTAL_URL: &str = "https://www.virustotal.com";
+pub const CIRCL_URL: &str = "https://www.circl.lu";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RecordType {
+    A,
+    Aaaa,
+    Cname,
+}
+
+/// A value a name resolved to, and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PassiveDnsRecord {
+    pub record_type: RecordType,
+    /// Address, or CNAME target without the trailing dot
+    pub value: String,
+    /// Service that reported the record
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl PassiveDnsRecord {
+    pub fn address(&self) -> Option<IpAddr> {
+        match self.record_type {
+            RecordType::A | RecordType::Aaaa => self.value.parse().ok(),
+            RecordType::Cname => None,
+        }
+    }
+
+    /// `last seen 2021-03-04`, or `date unknown`
+    pub fn last_seen_label(&self) -> String {
+        self.last_seen.map_or_else(|| "date unknown".to_string(), |seen| format!("last seen {}", seen.format("%Y-%m-%d")))
+    }
+}
+
+/// A passive DNS history backend
+#[async_trait::async_trait]
+pub trait PassiveDnsSource: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &'static str;
+
+    /// A, AAAA and (where the service has them) CNAME records ever seen for `host`
+    async fn history(&self, host: &str) -> Result<Vec<PassiveDnsRecord>>;
+}
+
+/// SecurityTrails DNS history (A and AAAA)
+#[derive(Debug, Clone)]
+pub struct SecurityTrails {
+    client: HttpClient,
+    api_key: String,
+    base_url: String,
+}
+
+impl SecurityTrails {
+    pub fn new(api_key: &str) -> Result<Self> {
+        Ok(Self { client: HttpClient::new()?.with_body_limit(BodyLimit::unlimited()), api_key: api_key.to_string(), base_url: SECURITYTRAILS_URL.to_string() })
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl PassiveDnsSource for SecurityTrails {
+    fn name(&self) -> &'static str {
+        "securitytrails"
+    }
+
+    async fn history(&self, host: &str) -> Result<Vec<PassiveDnsRecord>> {
+        let mut records = Vec::new();
+        for (path, record_type) in [("a", RecordType::A), ("aaaa", RecordType::Aaaa)] {
+            let url = format!("{}/v1/history/{}/dns/{}", self.base_url, host, path);
+            let body = fetch(&self.client, &url, &[("APIKEY", self.api_key.as_str())]).await?;
+            records.extend(parse_securitytrails(&body, record_type)?);
+        }
+        Ok(records)
+    }
+}
+
+/// VirusTotal domain resolutions (A and AAAA, last resolution date only)
+#[derive(Debug, Clone)]
+pub struct VirusTotal {
+    client: HttpClient,
+    api_key: String,
+    base_url: String,
+}
+
+impl VirusTotal {
+    pub fn new(api_key: &str) -> Result<Self> {
+        Ok(Self { client: HttpClient::new()?.with_body_limit(BodyLimit::unlimited()), api_key: api_key.to_string(), base_url: VIRUSTOTAL_URL.to_string() })
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl PassiveDnsSource for VirusTotal {
+    fn name(&self) -> &'static str {
+        "virustotal"
+    }
+
+    async fn history(&self, host: &str) -> Result<Vec<PassiveDnsRecord>> {
+        let url = format!("{}/api/v3/domains/{}/resolutions?limit=40", self.base_url, host);
+        parse_virustotal(&fetch(&self.client, &url, &[("x-apikey", self.api_key.as_str())]).await?)
+    }
+}
+
+/// CIRCL Passive DNS, or another service answering the passive DNS common output format
+#[derive(Debug, Clone)]
+pub struct Circl {
+    client: HttpClient,
+    /// `user:password`
+    credentials: String,
+    base_url: String,
+}
+
+impl Circl {
+    pub fn new(credentials: &str) -> Result<Self> {
+        Ok(Self { client: HttpClient::new()?.with_body_limit(BodyLimit::unlimited()), credentials: credentials.to_string(), base_url: CIRCL_URL.to_string() })
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl PassiveDnsSource for Circl {
+    fn name(&self) -> &'static str {
+        "circl"
+    }
+
+    async fn history(&self, host: &str) -> Result<Vec<PassiveDnsRecord>> {
+        let url = format!("{}/pdns/query/{}", self.base_url, host);
+        let authorization = format!("Basic {}", crate::utils::base64_encode(self.credentials.as_bytes()));
+        parse_circl(&fetch(&self.client, &url, &[("Authorization", authorization.as_str())]).await?)
+    }
+}
+
+async fn fetch(client: &HttpClient, url: &str, headers: &[(&str, &str)]) -> Result<String> {
+    let response = client.get_with_headers(url, headers).await?;
+    match response.status {
+        200 => response.full_body(),
+        // No history for the name
+        404 => Ok(String::new()),
+        status => anyhow::bail!("passive DNS query returned HTTP {}", status),
+    }
+}
+
+/// A source from `--pdns NAME[:KEY]`; without a key it is read from the environment
+/// (`SECURITYTRAILS_API_KEY`, `VIRUSTOTAL_API_KEY`, `CIRCL_PDNS_CREDENTIALS`)
+pub fn source_from_spec(spec: &str) -> Result<Arc<dyn PassiveDnsSource>> {
+    let (name, key) = match spec.split_once(':') {
+        Some((name, key)) => (name.trim().to_ascii_lowercase(), Some(key.to_string())),
+        None => (spec.trim().to_ascii_lowercase(), None),
+    };
+    let variable = match name.as_str() {
+        "securitytrails" => "SECURITYTRAILS_API_KEY",
+        "virustotal" => "VIRUSTOTAL_API_KEY",
+        "circl" => "CIRCL_PDNS_CREDENTIALS",
+        _ => anyhow::bail!("unknown passive DNS source '{}' (expected securitytrails, virustotal or circl)", name),
+    };
+    let key = key
+        .or_else(|| std::env::var(variable).ok())
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("passive DNS source '{}' needs a key: --pdns {}:KEY or {}", name, name, variable))?;
+    Ok(match name.as_str() {
+        "securitytrails" => Arc::new(SecurityTrails::new(&key)?),
+        "virustotal" => Arc::new(VirusTotal::new(&key)?),
+        _ => Arc::new(Circl::new(&key)?),
+    })
+}
+
+/// History of `host` from every source, merged per record and most recently seen
+/// first; a failing source is logged and skipped
+pub async fn history(sources: &[Arc<dyn PassiveDnsSource>], host: &str) -> Vec<PassiveDnsRecord> {
+    let lookups = sources.iter().map(|source| async move { (source.name(), source.history(host).await) });
+    let mut merged: Vec<PassiveDnsRecord> = Vec::new();
+    for (name, outcome) in futures::future::join_all(lookups).await {
+        let records = match outcome {
+            Ok(records) => records,
+            Err(e) => {
+                tracing::warn!(host, source = name, error = %e, "passive DNS lookup failed");
+                continue;
+            }
+        };
+        for record in records {
+            match merged.iter_mut().find(|known| known.record_type == record.record_type && known.value == record.value) {
+                Some(known) => {
+                    known.first_seen = earliest(known.first_seen, record.first_seen);
+                    known.last_seen = known.last_seen.max(record.last_seen);
+                }
+                None => merged.push(record),
+            }
+        }
+    }
+    merged.sort_by_key(|record| std::cmp::Reverse(record.last_seen));
+    merged
+}
+
+fn earliest(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        _ => a.or(b),
+    }
+}
+
+/// Former addresses: A/AAAA records in `history` that `current` no longer includes
+pub fn former_addresses<'a>(history: &'a [PassiveDnsRecord], current: &[IpAddr]) -> Vec<(IpAddr, &'a PassiveDnsRecord)> {
+    history
+        .iter()
+        .filter_map(|record| Some((record.address()?, record)))
+        .filter(|(ip, _)| !current.contains(ip))
+        .collect()
+}
+
+/// Former CNAME targets: CNAME records in `history` other than `current`
+pub fn former_cnames<'a>(history: &'a [PassiveDnsRecord], current: &[String]) -> Vec<&'a PassiveDnsRecord> {
+    history
+        .iter()
+        .filter(|record| record.record_type == RecordType::Cname)
+        .filter(|record| !current.iter().any(|cname| cname.eq_ignore_ascii_case(&record.value)))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct SecurityTrailsHistory {
+    #[serde(default)]
+    records: Vec<SecurityTrailsRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SecurityTrailsRecord {
+    #[serde(default)]
+    values: Vec<SecurityTrailsValue>,
+    first_seen: Option<String>,
+    last_seen: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SecurityTrailsValue {
+    ip: Option<String>,
+    ipv6: Option<String>,
+}
+
+/// Records from a SecurityTrails `/v1/history/{host}/dns/{a,aaaa}` response
+pub fn parse_securitytrails(body: &str, record_type: RecordType) -> Result<Vec<PassiveDnsRecord>> {
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let history: SecurityTrailsHistory = serde_json::from_str(body)?;
+    Ok(history
+        .records
+        .into_iter()
+        .flat_map(|record| {
+            let first_seen = record.first_seen.as_deref().and_then(parse_date);
+            let last_seen = record.last_seen.as_deref().and_then(parse_date);
+            record.values.into_iter().filter_map(move |value| {
+                Some(PassiveDnsRecord {
+                    record_type,
+                    value: value.ip.or(value.ipv6)?,
+                    source: "securitytrails".to_string(),
+                    first_seen,
+                    last_seen,
+                })
+            })
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct VirusTotalResolutions {
+    #[serde(default)]
+    data: Vec<VirusTotalResolution>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VirusTotalResolution {
+    attributes: VirusTotalAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct VirusTotalAttributes {
+    ip_address: String,
+    date: Option<i64>,
+}
+
+/// Records from a VirusTotal `/api/v3/domains/{host}/resolutions` response
+pub fn parse_virustotal(body: &str) -> Result<Vec<PassiveDnsRecord>> {
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let resolutions: VirusTotalResolutions = serde_json::from_str(body)?;
+    Ok(resolutions
+        .data
+        .into_iter()
+        .filter_map(|resolution| {
+            let ip: IpAddr = resolution.attributes.ip_address.parse().ok()?;
+            Some(PassiveDnsRecord {
+                record_type: if ip.is_ipv4() { RecordType::A } else { RecordType::Aaaa },
+                value: ip.to_string(),
+                source: "virustotal".to_string(),
+                first_seen: None,
+                last_seen: resolution.attributes.date.and_then(|date| DateTime::from_timestamp(date, 0)),
+            })
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct CommonOutputRecord {
+    rrtype: String,
+    rdata: String,
+    time_first: Option<i64>,
+    time_last: Option<i64>,
+}
+
+/// Records from a passive DNS common output format response (one JSON object per line)
+pub fn parse_circl(body: &str) -> Result<Vec<PassiveDnsRecord>> {
+    let mut records = Vec::new();
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let record: CommonOutputRecord = serde_json::from_str(line)?;
+        let record_type = match record.rrtype.to_ascii_uppercase().as_str() {
+            "A" => RecordType::A,
+            "AAAA" => RecordType::Aaaa,
+            "CNAME" => RecordType::Cname,
+            _ => continue,
+        };
+        records.push(PassiveDnsRecord {
+            record_type,
+            value: record.rdata.trim().trim_end_matches('.').to_lowercase(),
+            source: "circl".to_string(),
+            first_seen: record.time_first.and_then(|time| DateTime::from_timestamp(time, 0)),
+            last_seen: record.time_last.and_then(|time| DateTime::from_timestamp(time, 0)),
+        });
+    }
+    Ok(records)
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_service_responses() {
+        let securitytrails = r#"{"records": [
+            {"values": [{"ip": "104.16.1.1", "ip_count": 1}, {"ip": "104.16.2.2"}], "type": "a", "first_seen": "2021-05-01", "last_seen": "2024-06-01"},
+            {"values": [{"ip": "203.0.113.7"}], "type": "a", "first_seen": "2016-01-10", "last_seen": "2021-04-30"}
+        ]}"#;
+        let records = parse_securitytrails(securitytrails, RecordType::A).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].last_seen_label(), "last seen 2021-04-30");
+
+        let virustotal = r#"{"data": [{"attributes": {"ip_address": "2001:db8::7", "date": 1600000000, "host_name": "shop.example.com"}}]}"#;
+        let records = parse_virustotal(virustotal).unwrap();
+        assert_eq!((records[0].record_type, records[0].address()), (RecordType::Aaaa, Some("2001:db8::7".parse().unwrap())));
+
+        let circl = "{\"rrname\": \"shop.example.com\", \"rrtype\": \"CNAME\", \"rdata\": \"Shop.Example.com.edgekey.net.\", \"time_first\": 1400000000, \"time_last\": 1500000000}\n\
+                     {\"rrname\": \"shop.example.com\", \"rrtype\": \"MX\", \"rdata\": \"mail.example.com\"}\n";
+        let records = parse_circl(circl).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].value, "shop.example.com.edgekey.net");
+    }
+
+    #[test]
+    fn test_former_records_leave_out_current_ones() {
+        let record = |record_type, value: &str| PassiveDnsRecord { record_type, value: value.to_string(), source: "test".to_string(), first_seen: None, last_seen: None };
+        let history = vec![
+            record(RecordType::A, "104.16.1.1"),
+            record(RecordType::A, "203.0.113.7"),
+            record(RecordType::Cname, "shop.example.com.cdn.cloudflare.net"),
+            record(RecordType::Cname, "shop.example.com.edgekey.net"),
+        ];
+
+        let addresses = former_addresses(&history, &["104.16.1.1".parse().unwrap()]);
+        assert_eq!(addresses.iter().map(|(ip, _)| ip.to_string()).collect::<Vec<_>>(), vec!["203.0.113.7"]);
+        let cnames = former_cnames(&history, &["SHOP.example.com.cdn.cloudflare.net".to_string()]);
+        assert_eq!(cnames.iter().map(|r| r.value.as_str()).collect::<Vec<_>>(), vec!["shop.example.com.edgekey.net"]);
+    }
+
+    #[test]
+    fn test_source_from_spec() {
+        assert_eq!(source_from_spec("SecurityTrails:abc").unwrap().name(), "securitytrails");
+        assert_eq!(source_from_spec("circl:user:secret").unwrap().name(), "circl");
+        assert!(source_from_spec("passivetotal:abc").is_err());
+        assert!(source_from_spec("virustotal:").is_err());
+    }
+}
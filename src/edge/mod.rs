@@ -0,0 +1,237 @@
+//! Edge location (PoP) of the response
+//!
+//! CDNs name their points of presence after the nearest airport and put the name in a
+//! response header: CloudFront in `X-Amz-Cf-Pop` (`FRA56-P1`), Cloudflare at the end
+//! of `CF-RAY` (`8a1b2c3d4e5f6789-FRA`), Fastly at the end of each `X-Served-By` entry
+//! (`cache-fra-eddf8230045-FRA`), Vercel at the start of `X-Vercel-Id` (`fra1::...`)
+//! and Fly.io at the end of `Fly-Request-Id` (`01HQ3Z...-fra`).
+
+use crate::http::HttpResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// IATA code, city and ISO country of the airports PoPs are named after
+const AIRPORTS: &[(&str, &str, &str)] = &[
+    ("AKL", "Auckland", "NZ"),
+    ("AMS", "Amsterdam", "NL"),
+    ("ARN", "Stockholm", "SE"),
+    ("ATH", "Athens", "GR"),
+    ("ATL", "Atlanta", "US"),
+    ("BAH", "Manama", "BH"),
+    ("BKK", "Bangkok", "TH"),
+    ("BLR", "Bangalore", "IN"),
+    ("BNE", "Brisbane", "AU"),
+    ("BOG", "Bogotá", "CO"),
+    ("BOM", "Mumbai", "IN"),
+    ("BOS", "Boston", "US"),
+    ("BRU", "Brussels", "BE"),
+    ("BUD", "Budapest", "HU"),
+    ("BWI", "Baltimore", "US"),
+    ("CAI", "Cairo", "EG"),
+    ("CDG", "Paris", "FR"),
+    ("CGK", "Jakarta", "ID"),
+    ("CLE", "Cleveland", "US"),
+    ("CMH", "Columbus", "US"),
+    ("CPH", "Copenhagen", "DK"),
+    ("CPT", "Cape Town", "ZA"),
+    ("DEL", "New Delhi", "IN"),
+    ("DEN", "Denver", "US"),
+    ("DFW", "Dallas", "US"),
+    ("DOH", "Doha", "QA"),
+    ("DTW", "Detroit", "US"),
+    ("DUB", "Dublin", "IE"),
+    ("DUS", "Düsseldorf", "DE"),
+    ("DXB", "Dubai", "AE"),
+    ("EWR", "Newark", "US"),
+    ("EZE", "Buenos Aires", "AR"),
+    ("FCO", "Rome", "IT"),
+    ("FRA", "Frankfurt", "DE"),
+    ("GIG", "Rio de Janeiro", "BR"),
+    ("GRU", "São Paulo", "BR"),
+    ("HAM", "Hamburg", "DE"),
+    ("HEL", "Helsinki", "FI"),
+    ("HKG", "Hong Kong", "HK"),
+    ("HND", "Tokyo", "JP"),
+    ("HYD", "Hyderabad", "IN"),
+    ("IAD", "Ashburn", "US"),
+    ("IAH", "Houston", "US"),
+    ("ICN", "Seoul", "KR"),
+    ("IST", "Istanbul", "TR"),
+    ("JED", "Jeddah", "SA"),
+    ("JFK", "New York", "US"),
+    ("JNB", "Johannesburg", "ZA"),
+    ("KIX", "Osaka", "JP"),
+    ("KUL", "Kuala Lumpur", "MY"),
+    ("LAS", "Las Vegas", "US"),
+    ("LAX", "Los Angeles", "US"),
+    ("LCY", "London", "GB"),
+    ("LHR", "London", "GB"),
+    ("LIM", "Lima", "PE"),
+    ("LIS", "Lisbon", "PT"),
+    ("LOS", "Lagos", "NG"),
+    ("MAA", "Chennai", "IN"),
+    ("MAD", "Madrid", "ES"),
+    ("MAN", "Manchester", "GB"),
+    ("MEL", "Melbourne", "AU"),
+    ("MEX", "Mexico City", "MX"),
+    ("MIA", "Miami", "US"),
+    ("MNL", "Manila", "PH"),
+    ("MRS", "Marseille", "FR"),
+    ("MSP", "Minneapolis", "US"),
+    ("MUC", "Munich", "DE"),
+    ("MXP", "Milan", "IT"),
+    ("NBO", "Nairobi", "KE"),
+    ("NRT", "Tokyo", "JP"),
+    ("ORD", "Chicago", "US"),
+    ("OSL", "Oslo", "NO"),
+    ("OTP", "Bucharest", "RO"),
+    ("PDX", "Portland", "US"),
+    ("PER", "Perth", "AU"),
+    ("PHL", "Philadelphia", "US"),
+    ("PHX", "Phoenix", "US"),
+    ("PRG", "Prague", "CZ"),
+    ("QRO", "Querétaro", "MX"),
+    ("RUH", "Riyadh", "SA"),
+    ("SCL", "Santiago", "CL"),
+    ("SEA", "Seattle", "US"),
+    ("SFO", "San Francisco", "US"),
+    ("SIN", "Singapore", "SG"),
+    ("SJC", "San Jose", "US"),
+    ("SLC", "Salt Lake City", "US"),
+    ("SOF", "Sofia", "BG"),
+    ("SYD", "Sydney", "AU"),
+    ("TLV", "Tel Aviv", "IL"),
+    ("TPE", "Taipei", "TW"),
+    ("VIE", "Vienna", "AT"),
+    ("WAW", "Warsaw", "PL"),
+    ("YUL", "Montreal", "CA"),
+    ("YVR", "Vancouver", "CA"),
+    ("YYZ", "Toronto", "CA"),
+    ("ZRH", "Zurich", "CH"),
+];
+
+/// City and country of an airport code, if it is in the table
+pub fn airport(code: &str) -> Option<(&'static str, &'static str)> {
+    AIRPORTS
+        .binary_search_by(|(known, _, _)| known.cmp(&code))
+        .ok()
+        .map(|index| (AIRPORTS[index].1, AIRPORTS[index].2))
+}
+
+/// The PoP that served a response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeLocation {
+    /// Provider whose header named the PoP
+    pub provider: String,
+    /// Airport code the PoP is named after
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Header and value the code was read from
+    pub source: String,
+}
+
+impl EdgeLocation {
+    fn new(provider: &str, code: &str, header: &str, value: &str) -> Option<Self> {
+        let code = code.to_ascii_uppercase();
+        if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return None;
+        }
+        let (city, country) = airport(&code).map_or((None, None), |(city, country)| (Some(city.to_string()), Some(country.to_string())));
+        Some(Self { provider: provider.to_string(), code, city, country, source: format!("{}: {}", header, value) })
+    }
+
+    /// `FRA (Frankfurt, DE)`, or just the code when the airport is not known
+    pub fn label(&self) -> String {
+        match (&self.city, &self.country) {
+            (Some(city), Some(country)) => format!("{} ({}, {})", self.code, city, country),
+            _ => self.code.clone(),
+        }
+    }
+}
+
+/// The PoP named in `response`'s headers, if any
+pub fn edge_location(response: &HttpResponse) -> Option<EdgeLocation> {
+    let headers = &response.headers;
+    if let Some(pop) = headers.get("x-amz-cf-pop") {
+        return EdgeLocation::new("AWS", pop.get(..3)?, "x-amz-cf-pop", pop);
+    }
+    if let Some(ray) = headers.get("cf-ray") {
+        return EdgeLocation::new("CloudFlare", ray.rsplit('-').next()?, "cf-ray", ray);
+    }
+    if let Some(served_by) = headers.get("x-served-by") {
+        // Each cache in the chain appends itself; the last one is nearest the client
+        let last = served_by.rsplit(',').next()?.trim();
+        if last.starts_with("cache-") {
+            return EdgeLocation::new("Fastly", last.rsplit('-').next()?, "x-served-by", served_by);
+        }
+    }
+    if let Some(id) = headers.get("x-vercel-id") {
+        let region = id.split("::").next()?;
+        return EdgeLocation::new("Vercel", region.trim_end_matches(|c: char| c.is_ascii_digit()), "x-vercel-id", id);
+    }
+    if let Some(id) = headers.get("fly-request-id") {
+        return EdgeLocation::new("Fly.io", id.rsplit('-').next()?, "fly-request-id", id);
+    }
+    None
+}
+
+/// Targets served from each PoP, most used first, e.g. for "served from FRA (12), AMS (3)"
+pub fn summarize<'a>(locations: impl IntoIterator<Item = &'a EdgeLocation>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for location in locations {
+        *counts.entry(location.code.as_str()).or_default() += 1;
+    }
+    let mut summary: Vec<(String, usize)> = counts.into_iter().map(|(code, count)| (code.to_string(), count)).collect();
+    summary.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(headers: &[(&str, &str)]) -> Option<EdgeLocation> {
+        edge_location(&HttpResponse::from_parts(200, headers.iter().copied(), "", "https://example.com/"))
+    }
+
+    #[test]
+    fn test_airports_are_sorted() {
+        assert!(AIRPORTS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(airport("FRA"), Some(("Frankfurt", "DE")));
+        assert_eq!(airport("XXX"), None);
+    }
+
+    #[test]
+    fn test_pop_headers() {
+        let cloudfront = location(&[("x-amz-cf-pop", "FRA56-P1"), ("x-cache", "Miss from cloudfront")]).unwrap();
+        assert_eq!((cloudfront.provider.as_str(), cloudfront.label()), ("AWS", "FRA (Frankfurt, DE)".to_string()));
+
+        let cloudflare = location(&[("cf-ray", "8a1b2c3d4e5f6789-AMS")]).unwrap();
+        assert_eq!((cloudflare.provider.as_str(), cloudflare.code.as_str()), ("CloudFlare", "AMS"));
+
+        let fastly = location(&[("x-served-by", "cache-iad-kiad7000025-IAD, cache-lcy-eglc8600043-LCY")]).unwrap();
+        assert_eq!((fastly.provider.as_str(), fastly.code.as_str(), fastly.city.as_deref()), ("Fastly", "LCY", Some("London")));
+
+        let vercel = location(&[("x-vercel-id", "cdg1::iad1::8x2kq-1700000000000-abcdef")]).unwrap();
+        assert_eq!(vercel.code, "CDG");
+
+        let fly = location(&[("fly-request-id", "01HQ3Z8J5K7M9N1P3R5T7V9X1Z-ams")]).unwrap();
+        assert_eq!((fly.provider.as_str(), fly.code.as_str()), ("Fly.io", "AMS"));
+
+        let unknown = location(&[("cf-ray", "8a1b2c3d4e5f6789-XYZ")]).unwrap();
+        assert_eq!((unknown.label(), unknown.city), ("XYZ".to_string(), None));
+        assert!(location(&[("cf-ray", "8a1b2c3d4e5f6789")]).is_none());
+        assert!(location(&[("x-served-by", "web-01")]).is_none());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let fra = location(&[("cf-ray", "1-FRA")]).unwrap();
+        let ams = location(&[("x-amz-cf-pop", "AMS1-C1")]).unwrap();
+        let summary = summarize([&fra, &ams, &fra]);
+        assert_eq!(summary, [("FRA".to_string(), 2), ("AMS".to_string(), 1)]);
+    }
+}
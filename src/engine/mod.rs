@@ -1,7 +1,17 @@
 //! Detection engine for coordinating WAF/CDN detection
 
-use crate::{DetectionContext, DetectionResult, registry::ProviderRegistry, http::HttpClient};
+use crate::{DetectionContext, DetectionOptions, DetectionResult, registry::ProviderRegistry, http::{BodyLimit, HttpClient, HttpResponse, RetryPolicy}};
+use crate::dns::{AddressFamily, DualStackReport, FamilyScan};
+use crate::http::session::{CookieSession, SessionComparison};
+use crate::origin::{BypassReport, EdgeView, OriginProbe};
+use crate::error::{ScanError, WafDetectorError};
+use crate::preflight::PreflightInfo;
+use crate::fingerprint::Fingerprinter;
+use crate::ratelimit_probe::RateLimitProbe;
+use crate::replay::Tape;
+use crate::{TechniqueDiagnostics, TechniqueKind};
 use anyhow::Result;
+use tracing::Instrument;
 use std::sync::Arc;
 use std::collections::HashMap;
 
@@ -15,67 +25,561 @@ pub struct DetectionEngine {
     registry: ProviderRegistry,
     http_client: Arc<HttpClient>,
     waf_mode_detector: Option<WafModeDetector>,
+    options: DetectionOptions,
+    /// Detection passes merged per scan (see the `consensus` module)
+    runs: usize,
+    run_interval: std::time::Duration,
+    /// Origin addresses to check besides the discovered ones
+    origin_candidates: Vec<std::net::IpAddr>,
+    /// Records or replays the network interactions of every scan
+    tape: Option<Tape>,
 }
 
 impl DetectionEngine {
     pub fn new(registry: ProviderRegistry) -> Self {
         Self {
             registry,
-            http_client: Arc::new(HttpClient::default()),
+            http_client: Arc::new(HttpClient::new().unwrap_or_default()),
             waf_mode_detector: None,
+            options: DetectionOptions::default(),
+            runs: 1,
+            run_interval: std::time::Duration::ZERO,
+            origin_candidates: Vec::new(),
+            tape: None,
         }
     }
 
+    /// Set the detection options used for every scan run by this engine
+    pub fn with_options(mut self, options: DetectionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &DetectionOptions {
+        &self.options
+    }
+
+    /// Enable strict passive mode: each scan sends exactly one GET request. It is never
+    /// retried and redirects are not followed, so a 3xx answer is what gets analyzed.
+    pub fn with_passive_mode(self) -> Self {
+        self.registry.set_strict_passive(true);
+        self
+    }
+
+    pub fn is_passive(&self) -> bool {
+        self.registry.is_strict_passive()
+    }
+
+    /// Number of HTTP requests the engine itself has sent so far
+    pub fn request_count(&self) -> usize {
+        self.http_client.request_count()
+    }
+
+    /// Retry policy for the engine's own requests (initial GET and pre-flight)
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.http_client = Arc::new((*self.http_client).clone().with_retry_policy(retry_policy));
+        self
+    }
+
+    /// Read at most `body_limit` of each response body
+    pub fn with_body_limit(self, body_limit: BodyLimit) -> Self {
+        let client = (*self.http_client).clone().with_body_limit(body_limit);
+        self.with_http_client(client)
+    }
+
+    /// Send every request the engine, its providers and its analyzers make through
+    /// `client`; the timing analyzer is rebuilt on it with its configuration kept
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.registry = self.registry.with_http_client(client.clone());
+        self.waf_mode_detector = self.waf_mode_detector.map(|detector| detector.with_http_client(client.clone()));
+        self.http_client = Arc::new(client);
+        self
+    }
+
+    /// Run `runs` independent detection passes per scan, `interval` apart, and report
+    /// their consensus
+    pub fn with_runs(mut self, runs: usize, interval: std::time::Duration) -> Self {
+        self.runs = runs.max(1);
+        self.run_interval = interval;
+        self
+    }
+
+    /// Known or suspected origin addresses for the origin check
+    pub fn with_origin_candidates(mut self, candidates: Vec<std::net::IpAddr>) -> Self {
+        self.origin_candidates = candidates;
+        self
+    }
+
+    /// Keep a cookie session per scan: probes return the cookies the target set, and
+    /// the result compares a first-time client with a returning one
+    pub fn with_cookie_session(self) -> Self {
+        let client = (*self.http_client).clone().with_cookie_session(Arc::new(CookieSession::new()));
+        self.with_http_client(client)
+    }
+
+    /// Record every scan's HTTP exchanges and DNS lookups on `tape`, or answer them
+    /// from it when it replays a session (see the `replay` module)
+    pub fn with_tape(mut self, tape: Tape) -> Self {
+        self.tape = Some(tape);
+        self
+    }
+
+    pub fn tape(&self) -> Option<&Tape> {
+        self.tape.as_ref()
+    }
+
     pub fn with_waf_mode_detection(mut self) -> Self {
         self.waf_mode_detector = Some(WafModeDetector::new());
         self
     }
 
     pub async fn detect(&self, url: &str) -> Result<DetectionResult> {
-        // Make HTTP request
-        let response = self.http_client.get(url).await?;
+        if self.runs <= 1 {
+            return self.detect_once(url).await;
+        }
+
+        let mut completed = Vec::new();
+        let mut last_failure = None;
+        for pass in 0..self.runs {
+            if pass > 0 && !self.run_interval.is_zero() {
+                tokio::time::sleep(self.run_interval).await;
+            }
+            match self.detect_once(url).await {
+                Ok(result) if !result.failed() => completed.push(result),
+                Ok(result) => last_failure = Some(Ok(result)),
+                Err(e) => {
+                    tracing::debug!(url = %url, pass, error = %e, "detection pass failed");
+                    last_failure = Some(Err(e));
+                }
+            }
+        }
+        let failed_runs = self.runs - completed.len();
+        match crate::consensus::merge(completed, failed_runs) {
+            Some(result) => Ok(result),
+            None => last_failure.unwrap_or_else(|| Err(anyhow::anyhow!("no detection pass ran for {}", url))),
+        }
+    }
+
+    /// A single detection pass
+    async fn detect_once(&self, url: &str) -> Result<DetectionResult> {
+        let url = &crate::idn::ascii_url(url);
+        // Techniques count their own requests in nested scopes; this scope sees the rest.
+        // The pass is boxed: inline, its future overflows the stack of test and worker threads
+        let span = tracing::info_span!("scan", url = %url);
+        let pass = crate::http::count_requests(Box::pin(self.detect_inner(url)));
+        let (result, engine_requests) = match &self.tape {
+            Some(tape) => {
+                tape.add_target(url);
+                tape.scope(pass).instrument(span).await
+            }
+            None => pass.instrument(span).await,
+        };
+        let mut result = result?;
+        result.diagnostics.total_requests = engine_requests + result.diagnostics.technique_requests();
+        result.idn = crate::idn::IdnHost::from_url(url);
+        Ok(result)
+    }
+
+    /// Analyze a response captured elsewhere (own crawler, proxy export, HAR file).
+    ///
+    /// No network I/O: DNS, probes and pre-flight are skipped whatever the engine's
+    /// options say, and only the providers' passive checks and scoring run.
+    pub async fn detect_from_response(&self, url: &str, response: HttpResponse) -> Result<DetectionResult> {
+        let url = &crate::idn::ascii_url(url);
+        url::Url::parse(url).map_err(|e| WafDetectorError::InvalidUrl {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?;
+
+        let context = DetectionContext {
+            url: url.to_string(),
+            response: Some(response),
+            dns_info: None,
+            user_agent: "WAF-Detector/1.0".to_string(),
+        };
+        let mut result = self.registry.detect_all_with_options(&context, &DetectionOptions::offline()).await?;
+        result.idn = crate::idn::IdnHost::from_url(url);
+        Ok(result)
+    }
+
+    async fn detect_inner(&self, url: &str) -> Result<DetectionResult> {
+        url::Url::parse(url).map_err(|e| WafDetectorError::InvalidUrl {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?;
+
+        // Make HTTP request; a session starts over with each scan
+        if let Some(session) = self.http_client.session() {
+            session.reset(url);
+        }
+        // Strict passive mode sends this GET once: never retried, redirects not followed
+        let fetch = self.http_client.get_with_retry_count(url);
+        let (mut response, retries) = if self.is_passive() { crate::http::single_request(fetch).await? } else { fetch.await? };
+        let stateless = (response.status, crate::challenge::detect_challenge(&response).is_some_and(|c| c.interstitial));
+
+        // Optional robots.txt / security.txt pre-flight (never in strict passive mode); it
+        // decides whether any probing, the browser fallback included, runs at all
+        let mut options = self.options.clone();
+        let mut preflight = None;
+        if options.runs_preflight() && !self.is_passive() {
+            let mut info = PreflightInfo::fetch(&self.http_client, url).await;
+            if options.respect_robots && !info.target_allowed && options.sends_probe_traffic() {
+                options.disable_probing();
+                info.probing_skipped = true;
+            }
+            preflight = Some(info);
+        }
+
+        // Optionally detect on the page behind a JS challenge instead of the challenge
+        let mut challenge = None;
+        let mut browser_fallback = None;
+        if options.browser_fallback && !self.is_passive() {
+            if let Some(served) = crate::challenge::detect_challenge(&response).filter(|c| c.interstitial) {
+                let start = std::time::Instant::now();
+                let fallback = match crate::browser::fetch_past_challenge(url, crate::browser::DEFAULT_TIMEOUT).await {
+                    Ok(fetch) => {
+                        let fallback = crate::browser::BrowserFallback::from_fetch(&fetch, start.elapsed());
+                        if fetch.solved {
+                            response = fetch.response;
+                            challenge = Some(served);
+                        }
+                        fallback
+                    }
+                    Err(e) => {
+                        tracing::warn!(url, error = %e, "browser fallback failed");
+                        crate::browser::BrowserFallback::failed(&e, start.elapsed())
+                    }
+                };
+                browser_fallback = Some(fallback);
+            }
+        }
         
         // Create detection context
         let context = DetectionContext {
             url: url.to_string(),
             response: Some(response),
             dns_info: None,
-            user_agent: "WAF-Detector/1.0".to_string(),
+            user_agent: crate::profiles::user_agent_label(),
         };
 
         // Run detection through registry
-        self.registry.detect_all(&context).await
+        let mut result = self.registry.detect_all_with_options(&context, &options).await?;
+        result.metadata.retries = retries;
+        result.metadata.user_agent = crate::profiles::user_agent_label();
+        result.metadata.preflight = preflight;
+        if let Some(fallback) = browser_fallback {
+            result.diagnostics.techniques.push(TechniqueDiagnostics {
+                name: "BrowserFallback".to_string(),
+                kind: TechniqueKind::Analyzer,
+                duration_ms: fallback.duration_ms,
+                requests: 0,
+                error: fallback.error.clone(),
+            });
+            // The challenge was still served, even though detection ran past it
+            result.bot_challenge = challenge.or(result.bot_challenge.take());
+            result.browser_fallback = Some(fallback);
+        }
+
+        // Optional ruleset fingerprinting of the detected WAF
+        if options.fingerprint && !self.is_passive() {
+            if let (Some(waf), Some(response)) = (result.detected_waf.clone(), context.response.as_ref()) {
+                self.fingerprint(url, &waf.name, response, &mut result).await;
+            }
+        }
+
+        // Optional per-category blocking/monitoring analysis of the detected WAF
+        if options.mode_analysis && !self.is_passive() && result.detected_waf.is_some() {
+            self.analyze_mode(url, &mut result).await;
+        }
+
+        // Optional rate-limit profiling (ramps request frequency, capped)
+        if options.ratelimit_probe && !self.is_passive() {
+            self.probe_rate_limit(url, &mut result).await;
+        }
+
+        // Optional IPv4/IPv6 comparison (one more GET per IP version)
+        if options.dual_stack && !self.is_passive() {
+            self.compare_address_families(url, &mut result).await;
+        }
+
+        // Optional direct-to-origin comparison (one GET per candidate origin)
+        if options.origin_check && !self.is_passive() {
+            if let Some(response) = context.response.as_ref() {
+                self.check_origin(url, response, &mut result).await;
+            }
+        }
+
+        // With a session, one more GET returning the cookies collected so far
+        if let (Some(session), false) = (self.http_client.session(), self.is_passive()) {
+            result.session = Some(self.compare_session(url, session, stateless).await);
+        }
+        Ok(result)
+    }
+
+    async fn check_origin(&self, url: &str, edge_response: &HttpResponse, result: &mut DetectionResult) {
+        let start = std::time::Instant::now();
+        let edge = EdgeView {
+            addresses: Vec::new(),
+            status: edge_response.status,
+            waf: result.waf_name().map(str::to_string),
+            cdn: result.cdn_name().map(str::to_string),
+        };
+        let (outcome, requests) = crate::http::count_requests(self.origin_report(url, edge, edge_response)).await;
+
+        let error = match outcome {
+            Ok(report) => {
+                result.origin_bypass = Some(report);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(url, error = %e, "origin check failed");
+                Some(e.to_string())
+            }
+        };
+        result.diagnostics.techniques.push(TechniqueDiagnostics {
+            name: "OriginBypass".to_string(),
+            kind: TechniqueKind::Analyzer,
+            duration_ms: start.elapsed().as_millis() as u64,
+            requests,
+            error,
+        });
+    }
+
+    async fn origin_report(&self, url: &str, mut edge: EdgeView, edge_response: &HttpResponse) -> Result<BypassReport> {
+        let parsed = url::Url::parse(url)?;
+        let host = parsed.host_str().ok_or_else(|| anyhow::anyhow!("URL '{}' has no host", url))?;
+        let port = parsed.port_or_known_default().unwrap_or(443);
+        let addresses = crate::dns::resolve_addresses(host).await.unwrap_or_default();
+        edge.addresses = addresses.ipv4.into_iter().chain(addresses.ipv6).collect();
+
+        let mut candidates = Vec::new();
+        for candidate in crate::origin::discover(host, &edge.addresses, &self.origin_candidates, &self.registry.passive_dns_sources(), &self.registry.intel_sources()).await {
+            let addr = std::net::SocketAddr::new(candidate.ip, port);
+            let client = match HttpClient::pinned_to(std::time::Duration::from_secs(10), host, addr) {
+                Ok(client) => client.with_retry_policy(RetryPolicy::none()),
+                Err(e) => {
+                    candidates.push(OriginProbe::failed(candidate, e.to_string()));
+                    continue;
+                }
+            };
+            let response = match client.get(url).await {
+                Ok(response) => response,
+                Err(e) => {
+                    candidates.push(OriginProbe::failed(candidate, e.to_string()));
+                    continue;
+                }
+            };
+            let context = DetectionContext {
+                url: url.to_string(),
+                response: Some(response.clone()),
+                dns_info: None,
+                user_agent: crate::profiles::user_agent_label(),
+            };
+            let detection = self.registry.detect_all_with_options(&context, &DetectionOptions::offline()).await?;
+            candidates.push(OriginProbe::compare(
+                candidate,
+                &edge,
+                edge_response,
+                &response,
+                detection.waf_name().map(str::to_string),
+                detection.cdn_name().map(str::to_string),
+            ));
+        }
+        Ok(BypassReport { edge, candidates })
+    }
+
+    async fn compare_session(&self, url: &str, session: &CookieSession, (stateless_status, stateless_challenge): (u16, bool)) -> SessionComparison {
+        let cookies = session.cookie_names(url);
+        let (stateful_status, stateful_challenge, error) = match self.http_client.get(url).await {
+            Ok(response) => {
+                let challenge = crate::challenge::detect_challenge(&response).is_some_and(|c| c.interstitial);
+                (Some(response.status), challenge, None)
+            }
+            Err(e) => (None, false, Some(e.to_string())),
+        };
+        SessionComparison { cookies, stateless_status, stateful_status, stateless_challenge, stateful_challenge, error }
+    }
+
+    async fn compare_address_families(&self, url: &str, result: &mut DetectionResult) {
+        let start = std::time::Instant::now();
+        let (outcome, requests) = crate::http::count_requests(self.dual_stack_report(url)).await;
+
+        let error = match outcome {
+            Ok(report) => {
+                result.dual_stack = Some(report);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(url, error = %e, "dual-stack scan failed");
+                Some(e.to_string())
+            }
+        };
+        result.diagnostics.techniques.push(TechniqueDiagnostics {
+            name: "DualStack".to_string(),
+            kind: TechniqueKind::Analyzer,
+            duration_ms: start.elapsed().as_millis() as u64,
+            requests,
+            error,
+        });
+    }
+
+    async fn dual_stack_report(&self, url: &str) -> Result<DualStackReport> {
+        let parsed = url::Url::parse(url)?;
+        let host = parsed.host_str().ok_or_else(|| anyhow::anyhow!("URL '{}' has no host", url))?;
+        let addresses = crate::dns::resolve_addresses(host).await?;
+        let ipv4 = if addresses.ipv4.is_empty() { None } else { Some(self.scan_over(url, AddressFamily::Ipv4).await) };
+        let ipv6 = if addresses.ipv6.is_empty() { None } else { Some(self.scan_over(url, AddressFamily::Ipv6).await) };
+        Ok(DualStackReport::new(addresses, ipv4, ipv6))
+    }
+
+    /// Fetch `url` over one IP version and run the providers' passive checks on it
+    async fn scan_over(&self, url: &str, family: AddressFamily) -> FamilyScan {
+        let client = match HttpClient::with_address_family(std::time::Duration::from_secs(10), None, family) {
+            Ok(client) => client.with_retry_policy(self.http_client.retry_policy().clone()),
+            Err(e) => return FamilyScan { error: Some(e.to_string()), ..FamilyScan::default() },
+        };
+        let response = match client.get(url).await {
+            Ok(response) => response,
+            Err(e) => return FamilyScan { error: Some(e.to_string()), ..FamilyScan::default() },
+        };
+        let status = Some(response.status);
+        let context = DetectionContext {
+            url: url.to_string(),
+            response: Some(response),
+            dns_info: None,
+            user_agent: "WAF-Detector/1.0".to_string(),
+        };
+        match self.registry.detect_all_with_options(&context, &DetectionOptions::offline()).await {
+            Ok(detection) => FamilyScan {
+                status,
+                waf: detection.waf_name().map(str::to_string),
+                cdn: detection.cdn_name().map(str::to_string),
+                error: None,
+            },
+            Err(e) => FamilyScan { status, error: Some(e.to_string()), ..FamilyScan::default() },
+        }
+    }
+
+    async fn probe_rate_limit(&self, url: &str, result: &mut DetectionResult) {
+        let start = std::time::Instant::now();
+        let probe = RateLimitProbe::new(&self.http_client);
+        let (outcome, requests) = crate::http::count_requests(probe.probe(url)).await;
+
+        let error = match outcome {
+            Ok(profile) => {
+                result.rate_limit = Some(profile);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(url, error = %e, "rate-limit probe failed");
+                Some(e.to_string())
+            }
+        };
+        result.diagnostics.techniques.push(TechniqueDiagnostics {
+            name: "RateLimitProbe".to_string(),
+            kind: TechniqueKind::Analyzer,
+            duration_ms: start.elapsed().as_millis() as u64,
+            requests,
+            error,
+        });
+    }
+
+    async fn analyze_mode(&self, url: &str, result: &mut DetectionResult) {
+        let start = std::time::Instant::now();
+        let detector = self.waf_mode_detector.clone()
+            .unwrap_or_else(|| WafModeDetector::new().with_http_client((*self.http_client).clone()));
+        let (outcome, requests) = crate::http::count_requests(detector.detect_mode(url, None)).await;
+
+        let error = match outcome {
+            Ok(mode) => {
+                result.waf_mode = Some(mode);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(url, error = %e, "WAF mode analysis failed");
+                Some(e.to_string())
+            }
+        };
+        result.diagnostics.techniques.push(TechniqueDiagnostics {
+            name: "ModeAnalysis".to_string(),
+            kind: TechniqueKind::Analyzer,
+            duration_ms: start.elapsed().as_millis() as u64,
+            requests,
+            error,
+        });
+    }
+
+    async fn fingerprint(&self, url: &str, vendor: &str, baseline: &crate::http::HttpResponse, result: &mut DetectionResult) {
+        let start = std::time::Instant::now();
+        let fingerprinter = Fingerprinter::new(&self.http_client);
+        let (outcome, requests) = crate::http::count_requests(fingerprinter.fingerprint(url, vendor, baseline)).await;
+
+        let error = match outcome {
+            Ok(info) => {
+                result.ruleset_info = Some(info);
+                None
+            }
+            Err(e) => {
+                tracing::warn!(url, vendor, error = %e, "fingerprinting failed");
+                Some(e.to_string())
+            }
+        };
+        result.diagnostics.techniques.push(TechniqueDiagnostics {
+            name: "Fingerprint".to_string(),
+            kind: TechniqueKind::Analyzer,
+            duration_ms: start.elapsed().as_millis() as u64,
+            requests,
+            error,
+        });
     }
 
     pub async fn detect_batch(&self, urls: &[&str], workers: usize) -> Result<HashMap<String, DetectionResult>> {
         use futures::stream::{self, StreamExt};
         use tokio::time::{sleep, Duration};
         
+        // Owned URLs keep the future `Send` for any caller lifetime, so it can be awaited
+        // in spawned tasks and web handlers
+        let urls: Vec<String> = urls.iter().map(|url| url.to_string()).collect();
         let results = stream::iter(urls)
-            .map(|&url| async move {
+            .map(|url| async move {
                 // Add small delay to prevent overwhelming servers
                 sleep(Duration::from_millis(100)).await;
                 
-                match self.detect(url).await {
-                    Ok(result) => Some((url.to_string(), result)),
+                match self.detect(&url).await {
+                    Ok(result) => Some((url, result)),
                     Err(e) => {
-                        eprintln!("⚠️  Failed to detect {}: {}", url, e);
+                        tracing::warn!(url = %url, error = %e, "detection failed");
                         
                         // Create a failed result instead of None so we maintain the URL in output
                         let failed_result = DetectionResult {
                             url: url.to_string(),
                             detected_waf: None,
                             detected_cdn: None,
+                            detected_bot_manager: None,
+                            detected_stack: Vec::new(),
+                            bot_challenge: None,
+                            edge_location: None,
                             provider_scores: std::collections::HashMap::new(),
                             evidence_map: std::collections::HashMap::new(),
+                            negative_evidence: std::collections::HashMap::new(),
+                            confidence_details: std::collections::HashMap::new(),
                             detection_time_ms: 0,
-                            metadata: crate::DetectionMetadata {
-                                timestamp: chrono::Utc::now(),
-                                version: "1.0.0".to_string(),
-                                user_agent: "WAF-Detector/1.0".to_string(),
-                            },
+                            metadata: self.registry.detection_metadata(),
+                            diagnostics: Default::default(),
+                            error: Some(ScanError::from_error(&e)),
+                            ruleset_info: None,
+                            waf_mode: None,
+                            rate_limit: None,
+                            dual_stack: None,
+                            idn: crate::idn::IdnHost::from_url(&url),
+                            evidence_groups: HashMap::new(),
+                            explanation: None,
+                            consensus: None,
+                            browser_fallback: None,
+                            session: None,
+                            origin_bypass: None,
                         };
-                        Some((url.to_string(), failed_result))
+                        Some((url, failed_result))
                     }
                 }
             })
@@ -90,7 +594,7 @@ impl DetectionEngine {
         let detection_result = self.detect(url).await?;
         
         let mode_result = if let Some(detector) = &self.waf_mode_detector {
-            if detection_result.detected() {
+            if detection_result.detected() && !self.is_passive() && !self.robots_forbid_probing(&detection_result) {
                 Some(detector.detect_mode(url, None).await?)
             } else {
                 None
@@ -102,6 +606,16 @@ impl DetectionEngine {
         Ok((detection_result, mode_result))
     }
 
+    fn robots_forbid_probing(&self, result: &DetectionResult) -> bool {
+        self.options.respect_robots
+            && result.metadata.preflight.as_ref().is_some_and(|p| !p.target_allowed)
+    }
+
+    /// Access the underlying provider registry (e.g. to enable/disable techniques)
+    pub fn registry(&self) -> &ProviderRegistry {
+        &self.registry
+    }
+
     pub fn list_providers(&self) -> Vec<crate::providers::ProviderMetadata> {
         self.registry.list_providers()
     }
@@ -110,3 +624,26 @@ impl DetectionEngine {
         self.registry.get_provider_count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorClass;
+
+    #[tokio::test]
+    async fn test_failed_batch_entries_carry_error_class() {
+        let engine = DetectionEngine::new(ProviderRegistry::new());
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let unreachable = format!("http://127.0.0.1:{}/", port);
+
+        let results = engine.detect_batch(&["not a url", &unreachable], 2).await.unwrap();
+
+        let invalid = &results["not a url"];
+        assert!(invalid.failed());
+        assert_eq!(invalid.error.as_ref().unwrap().class, ErrorClass::InvalidUrl);
+
+        let refused = &results[&unreachable];
+        assert_eq!(refused.error.as_ref().unwrap().class, ErrorClass::Network);
+        assert!(!refused.detected());
+    }
+}
@@ -47,6 +47,8 @@ pub enum PayloadType {
     FileUpload,
     ScannerDetection,
     Enumeration,
+    /// Harmless input that merely looks like an attack; used to measure false positives
+    Benign,
 }
 
 impl std::fmt::Display for PayloadType {
@@ -61,6 +63,51 @@ impl std::fmt::Display for PayloadType {
             PayloadType::FileUpload => write!(f, "File Upload"),
             PayloadType::ScannerDetection => write!(f, "Scanner Detection"),
             PayloadType::Enumeration => write!(f, "Enumeration"),
+            PayloadType::Benign => write!(f, "Benign"),
+        }
+    }
+}
+
+impl PayloadType {
+    /// OWASP Top 10 (2021) category the payload type exercises
+    pub fn owasp_category(&self) -> &'static str {
+        match self {
+            PayloadType::XssBasic
+            | PayloadType::XssAdvanced
+            | PayloadType::SqlInjectionBasic
+            | PayloadType::SqlInjectionAdvanced
+            | PayloadType::CommandInjection => "A03:2021 Injection",
+            PayloadType::PathTraversal => "A01:2021 Broken Access Control",
+            PayloadType::FileUpload => "A04:2021 Insecure Design",
+            PayloadType::ScannerDetection | PayloadType::Enumeration => "A05:2021 Security Misconfiguration",
+            PayloadType::Benign => "N/A",
+        }
+    }
+}
+
+impl std::str::FromStr for PayloadType {
+    type Err = String;
+
+    /// Accepts variant names in any case and separator style (`SqlInjectionBasic`,
+    /// `sql-injection-basic`) plus the short aliases used for payload corpus files
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .collect::<String>()
+            .to_lowercase();
+        match normalized.as_str() {
+            "xss" | "xssbasic" => Ok(PayloadType::XssBasic),
+            "xssadvanced" => Ok(PayloadType::XssAdvanced),
+            "sqli" | "sql" | "sqlinjection" | "sqlinjectionbasic" => Ok(PayloadType::SqlInjectionBasic),
+            "sqliadvanced" | "sqlinjectionadvanced" => Ok(PayloadType::SqlInjectionAdvanced),
+            "lfi" | "traversal" | "pathtraversal" => Ok(PayloadType::PathTraversal),
+            "rce" | "cmdi" | "commandinjection" => Ok(PayloadType::CommandInjection),
+            "upload" | "fileupload" => Ok(PayloadType::FileUpload),
+            "scanner" | "scannerdetection" => Ok(PayloadType::ScannerDetection),
+            "enum" | "enumeration" => Ok(PayloadType::Enumeration),
+            "benign" => Ok(PayloadType::Benign),
+            _ => Err(format!("unknown payload category '{}'", s)),
         }
     }
 }
@@ -83,6 +130,74 @@ pub struct WafModeResult {
     pub confidence: f64,
     pub test_results: Vec<ProbeResult>,
     pub detection_time_ms: u64,
+    /// Mode observed for each payload category
+    #[serde(default)]
+    pub category_modes: Vec<CategoryMode>,
+}
+
+/// Mode observed for one payload category, e.g. SQLi blocked while XSS is only monitored
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryMode {
+    pub payload_type: PayloadType,
+    pub mode: WafMode,
+    pub tested: usize,
+    pub blocked: usize,
+}
+
+/// Outcome of one probe as far as per-category mode analysis is concerned
+#[derive(Debug, Clone)]
+pub struct ProbeOutcome {
+    pub payload_type: PayloadType,
+    pub blocked: bool,
+    /// The payload came back in the response body unmodified
+    pub reflected: bool,
+}
+
+/// Classify each payload category on its own.
+///
+/// A category with every probe blocked is `Blocking` and one with some blocked is `Mixed`.
+/// A category with nothing blocked is `Monitoring` when its payloads were reflected or the
+/// WAF enforced other categories (it sees the traffic but lets this class through), and
+/// `Unknown` otherwise. Categories are sorted by name.
+pub fn category_modes(outcomes: &[ProbeOutcome]) -> Vec<CategoryMode> {
+    let mut categories: Vec<(CategoryMode, bool)> = Vec::new();
+    for outcome in outcomes {
+        let index = match categories.iter().position(|(c, _)| c.payload_type == outcome.payload_type) {
+            Some(index) => index,
+            None => {
+                categories.push((CategoryMode {
+                    payload_type: outcome.payload_type.clone(),
+                    mode: WafMode::Unknown,
+                    tested: 0,
+                    blocked: 0,
+                }, false));
+                categories.len() - 1
+            }
+        };
+        let (category, reflected) = &mut categories[index];
+        category.tested += 1;
+        category.blocked += usize::from(outcome.blocked);
+        *reflected |= outcome.reflected;
+    }
+
+    let enforcing = categories.iter().any(|(c, _)| c.blocked > 0);
+    let mut modes: Vec<CategoryMode> = categories
+        .into_iter()
+        .map(|(mut category, reflected)| {
+            category.mode = if category.blocked == category.tested {
+                WafMode::Blocking
+            } else if category.blocked > 0 {
+                WafMode::Mixed
+            } else if reflected || enforcing {
+                WafMode::Monitoring
+            } else {
+                WafMode::Unknown
+            };
+            category
+        })
+        .collect();
+    modes.sort_by_key(|c| c.payload_type.to_string());
+    modes
 }
 
 /// WAF Mode Detector
@@ -188,10 +303,19 @@ impl WafModeDetector {
 
         // Analyze results to determine mode
         let (mode, confidence) = self.analyze_results(&test_results);
+        let outcomes: Vec<ProbeOutcome> = test_results
+            .iter()
+            .map(|r| ProbeOutcome {
+                payload_type: r.payload_type.clone(),
+                blocked: r.blocked,
+                reflected: r.evidence.iter().any(|e| e.contains("reflected")),
+            })
+            .collect();
 
         Ok(WafModeResult {
             mode,
             confidence,
+            category_modes: category_modes(&outcomes),
             test_results,
             detection_time_ms: detection_time.as_millis() as u64,
         })
@@ -313,6 +437,14 @@ impl WafModeDetector {
     }
 }
 
+impl WafModeDetector {
+    /// Send mode-analysis probes through `client` (timeout, proxy, retries)
+    pub fn with_http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
+}
+
 impl Default for WafModeDetector {
     fn default() -> Self {
         Self::new()
@@ -359,4 +491,29 @@ mod tests {
         assert_eq!(mode, WafMode::Blocking);
         assert!(confidence > 0.8);
     }
+
+    #[test]
+    fn test_category_modes() {
+        let outcome = |payload_type: PayloadType, blocked: bool, reflected: bool| ProbeOutcome { payload_type, blocked, reflected };
+        let outcomes = vec![
+            outcome(PayloadType::SqlInjectionBasic, true, false),
+            outcome(PayloadType::SqlInjectionBasic, true, false),
+            outcome(PayloadType::XssBasic, false, false),
+            outcome(PayloadType::XssBasic, false, true),
+            outcome(PayloadType::PathTraversal, true, false),
+            outcome(PayloadType::PathTraversal, false, false),
+        ];
+
+        let modes = category_modes(&outcomes);
+        let summary: Vec<(PayloadType, WafMode, usize)> = modes.iter().map(|c| (c.payload_type.clone(), c.mode.clone(), c.blocked)).collect();
+        assert_eq!(summary, vec![
+            (PayloadType::PathTraversal, WafMode::Mixed, 1),
+            (PayloadType::SqlInjectionBasic, WafMode::Blocking, 2),
+            (PayloadType::XssBasic, WafMode::Monitoring, 0),
+        ]);
+
+        // Nothing blocked and nothing reflected anywhere: no basis for a verdict
+        let modes = category_modes(&[outcome(PayloadType::XssBasic, false, false)]);
+        assert_eq!(modes[0].mode, WafMode::Unknown);
+    }
 }
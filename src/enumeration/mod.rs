@@ -0,0 +1,255 @@
+//! Subdomain enumeration and per-subdomain WAF coverage
+//!
+//! Candidates come from a wordlist and from certificate transparency logs (a
+//! [`CtSource`], crt.sh by default).
+//! Each subdomain that resolves is scanned, and the results are folded into a
+//! coverage matrix that highlights hosts reachable without going through the WAF.
+
+use crate::ct::{CrtSh, CtSource};
+use crate::engine::DetectionEngine;
+use crate::error::ScanError;
+use crate::DetectionResult;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Common subdomain labels tried when no wordlist is given
+pub const DEFAULT_WORDLIST: &[&str] = &[
+    "www", "api", "app", "admin", "portal", "dev", "staging", "stage", "test", "qa",
+    "beta", "demo", "m", "mobile", "static", "assets", "cdn", "img", "media", "files",
+    "blog", "shop", "store", "docs", "help", "support", "status", "auth", "login", "sso",
+    "account", "dashboard", "mail", "webmail", "vpn", "remote", "git", "jenkins", "ci", "grafana",
+    "origin", "direct", "old", "legacy", "internal", "intranet", "backend", "gateway", "ws", "graphql",
+];
+
+/// Where a subdomain was discovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubdomainSource {
+    Apex,
+    Wordlist,
+    CertificateTransparency,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subdomain {
+    pub name: String,
+    pub source: SubdomainSource,
+}
+
+/// Subdomain enumerator (wordlist + certificate transparency)
+#[derive(Debug, Clone)]
+pub struct SubdomainEnumerator {
+    wordlist: Vec<String>,
+    use_ct: bool,
+    ct_source: Arc<dyn CtSource>,
+    resolve: bool,
+    limit: Option<usize>,
+}
+
+impl SubdomainEnumerator {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            wordlist: DEFAULT_WORDLIST.iter().map(|w| w.to_string()).collect(),
+            use_ct: true,
+            ct_source: Arc::new(CrtSh::new()?),
+            resolve: true,
+            limit: None,
+        })
+    }
+
+    pub fn with_wordlist(mut self, wordlist: Vec<String>) -> Self {
+        self.wordlist = wordlist;
+        self
+    }
+
+    /// Enable or disable certificate transparency lookups
+    pub fn with_certificate_transparency(mut self, enabled: bool) -> Self {
+        self.use_ct = enabled;
+        self
+    }
+
+    /// Base URL of the crt.sh-compatible CT search service
+    pub fn with_ct_url(mut self, url: &str) -> Self {
+        self.ct_source = Arc::new(CrtSh::default().with_base_url(url));
+        self
+    }
+
+    /// Certificate transparency backend (e.g. the one the registry uses for `--ct`)
+    pub fn with_ct_source(mut self, source: Arc<dyn CtSource>) -> Self {
+        self.ct_source = source;
+        self
+    }
+
+    /// Only keep candidates that resolve in DNS (default: true)
+    pub fn with_resolution(mut self, resolve: bool) -> Self {
+        self.resolve = resolve;
+        self
+    }
+
+    /// Maximum number of subdomains returned (the apex always counts as one)
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Enumerate the subdomains of `domain`, apex first, then sorted by name
+    pub async fn enumerate(&self, domain: &str) -> Result<Vec<Subdomain>> {
+        let domain = domain.trim().trim_end_matches('.').to_lowercase();
+        let mut found: BTreeMap<String, SubdomainSource> = BTreeMap::new();
+
+        for label in &self.wordlist {
+            let label = label.trim().trim_end_matches('.');
+            if !label.is_empty() && !label.starts_with('#') {
+                found.insert(format!("{}.{}", label.to_lowercase(), domain), SubdomainSource::Wordlist);
+            }
+        }
+
+        if self.use_ct {
+            match self.ct_subdomains(&domain).await {
+                // CT hits are more trustworthy than wordlist guesses, so they win on overlap
+                Ok(names) => {
+                    for name in names {
+                        found.insert(name, SubdomainSource::CertificateTransparency);
+                    }
+                }
+                Err(e) => tracing::warn!(domain = %domain, error = %e, "certificate transparency lookup failed"),
+            }
+        }
+        found.remove(&domain);
+
+        let mut subdomains = vec![Subdomain { name: domain.clone(), source: SubdomainSource::Apex }];
+        for (name, source) in found {
+            if self.limit.is_some_and(|limit| subdomains.len() >= limit) {
+                break;
+            }
+            if !self.resolve || resolves(&name).await {
+                subdomains.push(Subdomain { name, source });
+            }
+        }
+
+        Ok(subdomains)
+    }
+
+    async fn ct_subdomains(&self, domain: &str) -> Result<Vec<String>> {
+        let certificates = self.ct_source.certificates(&format!("%.{}", domain)).await?;
+        Ok(crate::ct::subdomains(&certificates, domain))
+    }
+}
+
+/// Extract the subdomains of `domain` from a crt.sh JSON response
+pub fn parse_ct_names(body: &str, domain: &str) -> Result<Vec<String>> {
+    Ok(crate::ct::subdomains(&crate::ct::parse_crt_sh(body)?, domain))
+}
+
+async fn resolves(name: &str) -> bool {
+    tokio::net::lookup_host((name, 443)).await.is_ok_and(|mut addrs| addrs.next().is_some())
+}
+
+/// Protection status of one subdomain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageRow {
+    pub subdomain: String,
+    pub source: SubdomainSource,
+    pub waf: Option<String>,
+    pub cdn: Option<String>,
+    pub protected: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanError>,
+}
+
+/// Which subdomains of a domain sit behind a WAF and which bypass it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageMatrix {
+    pub domain: String,
+    pub rows: Vec<CoverageRow>,
+}
+
+impl CoverageMatrix {
+    /// Build the matrix from scan results keyed by the URL scanned for each subdomain
+    pub fn from_results(domain: &str, subdomains: &[Subdomain], results: &HashMap<String, DetectionResult>) -> Self {
+        let rows = subdomains
+            .iter()
+            .map(|subdomain| {
+                let result = results.get(&subdomain_url(&subdomain.name));
+                let waf = result.and_then(|r| r.detected_waf.as_ref()).map(|d| d.name.clone());
+                CoverageRow {
+                    subdomain: subdomain.name.clone(),
+                    source: subdomain.source,
+                    protected: waf.is_some(),
+                    waf,
+                    cdn: result.and_then(|r| r.detected_cdn.as_ref()).map(|d| d.name.clone()),
+                    error: result.and_then(|r| r.error.clone()),
+                }
+            })
+            .collect();
+
+        Self { domain: domain.to_string(), rows }
+    }
+
+    /// Subdomains that were scanned successfully but have no WAF in front of them
+    pub fn unprotected(&self) -> Vec<&CoverageRow> {
+        self.rows.iter().filter(|row| !row.protected && row.error.is_none()).collect()
+    }
+
+    /// WAF vendors seen across the domain, with how many subdomains each covers
+    pub fn vendors(&self) -> BTreeMap<String, usize> {
+        let mut vendors = BTreeMap::new();
+        for waf in self.rows.iter().filter_map(|row| row.waf.as_ref()) {
+            *vendors.entry(waf.clone()).or_insert(0) += 1;
+        }
+        vendors
+    }
+}
+
+/// URL scanned for a subdomain
+pub fn subdomain_url(name: &str) -> String {
+    format!("https://{}/", name)
+}
+
+/// Enumerate `domain` and scan every subdomain found
+pub async fn scan_domain(
+    engine: &DetectionEngine,
+    enumerator: &SubdomainEnumerator,
+    domain: &str,
+    workers: usize,
+) -> Result<CoverageMatrix> {
+    let subdomains = enumerator.enumerate(domain).await?;
+    let urls: Vec<String> = subdomains.iter().map(|s| subdomain_url(&s.name)).collect();
+    let url_refs: Vec<&str> = urls.iter().map(|u| u.as_str()).collect();
+    let results = engine.detect_batch(&url_refs, workers).await?;
+
+    Ok(CoverageMatrix::from_results(&domain.to_lowercase(), &subdomains, &results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ct_names() {
+        let body = r#"[
+            {"common_name": "example.com", "name_value": "example.com\nwww.example.com"},
+            {"common_name": "*.api.example.com", "name_value": "*.api.example.com\nAPI.example.com"},
+            {"common_name": "evil-example.com", "name_value": "notexample.com"}
+        ]"#;
+
+        let names = parse_ct_names(body, "example.com").unwrap();
+        assert_eq!(names, vec!["api.example.com", "www.example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_enumerate_without_resolution() {
+        let enumerator = SubdomainEnumerator::new()
+            .unwrap()
+            .with_wordlist(vec!["www".to_string(), "# comment".to_string(), "API".to_string()])
+            .with_certificate_transparency(false)
+            .with_resolution(false);
+
+        let subdomains = enumerator.enumerate("Example.com.").await.unwrap();
+        let names: Vec<&str> = subdomains.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["example.com", "api.example.com", "www.example.com"]);
+        assert_eq!(subdomains[0].source, SubdomainSource::Apex);
+    }
+}
@@ -0,0 +1,168 @@
+//! Structured error taxonomy
+//!
+//! Most of the crate still propagates `anyhow::Error`, but the errors raised by the
+//! engine, HTTP client, DNS and payload analyzers are `WafDetectorError`s underneath,
+//! so callers can downcast them (or use [`ErrorClass::of`]) to tell a failed scan
+//! apart from a target without a WAF.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WafDetectorError {
+    #[error("DNS resolution failed for '{host}': {message}")]
+    Dns { host: String, message: String },
+
+    #[error("Request to '{url}' timed out")]
+    Timeout { url: String },
+
+    #[error("TLS error for '{url}': {message}")]
+    Tls { url: String, message: String },
+
+    #[error("Request to '{url}' was blocked by the target (HTTP {status})")]
+    BlockedByTarget { url: String, status: u16 },
+
+    #[error("Invalid URL '{url}': {message}")]
+    InvalidUrl { url: String, message: String },
+
+    #[error("Provider '{provider}' failed: {message}")]
+    Provider { provider: String, message: String },
+
+    #[error("Connection to '{url}' failed: {message}")]
+    Network { url: String, message: String },
+
+    #[error("Request budget exhausted for host '{host}' ({limit} requests max)")]
+    RequestBudgetExhausted { host: String, limit: usize },
+
+    /// A failure read back from a recorded session (see the `replay` module)
+    #[error("{message}")]
+    Replayed { class: ErrorClass, message: String },
+}
+
+/// Coarse error category, stable enough to be serialized in results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    Dns,
+    Timeout,
+    Tls,
+    BlockedByTarget,
+    InvalidUrl,
+    Provider,
+    Network,
+    RateLimited,
+    Other,
+}
+
+impl WafDetectorError {
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Self::Dns { .. } => ErrorClass::Dns,
+            Self::Timeout { .. } => ErrorClass::Timeout,
+            Self::Tls { .. } => ErrorClass::Tls,
+            Self::BlockedByTarget { .. } => ErrorClass::BlockedByTarget,
+            Self::InvalidUrl { .. } => ErrorClass::InvalidUrl,
+            Self::Provider { .. } => ErrorClass::Provider,
+            Self::Network { .. } => ErrorClass::Network,
+            Self::RequestBudgetExhausted { .. } => ErrorClass::RateLimited,
+            Self::Replayed { class, .. } => *class,
+        }
+    }
+
+    /// Classify a reqwest error; DNS and TLS failures are only visible in the source chain
+    pub fn from_reqwest(url: &str, error: &reqwest::Error) -> Self {
+        let message = error_chain(error);
+        let lowered = message.to_lowercase();
+
+        if error.is_timeout() {
+            Self::Timeout { url: url.to_string() }
+        } else if error.is_builder() {
+            Self::InvalidUrl { url: url.to_string(), message }
+        } else if lowered.contains("dns error") || lowered.contains("failed to lookup address") {
+            let host = url::Url::parse(url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_else(|| url.to_string());
+            Self::Dns { host, message }
+        } else if ["certificate", "tls", "ssl", "handshake"].iter().any(|k| lowered.contains(k)) {
+            Self::Tls { url: url.to_string(), message }
+        } else {
+            Self::Network { url: url.to_string(), message }
+        }
+    }
+}
+
+impl ErrorClass {
+    /// Classify any error produced by the crate
+    pub fn of(error: &anyhow::Error) -> Self {
+        if let Some(e) = error.downcast_ref::<WafDetectorError>() {
+            e.class()
+        } else if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+            WafDetectorError::from_reqwest("", e).class()
+        } else {
+            ErrorClass::Other
+        }
+    }
+}
+
+/// Why a scan failed, as recorded in `DetectionResult::error`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanError {
+    pub class: ErrorClass,
+    pub message: String,
+}
+
+impl ScanError {
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        Self {
+            class: ErrorClass::of(error),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Join an error and its sources into one message
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_class_downcast() {
+        let error: anyhow::Error = WafDetectorError::Timeout { url: "https://example.com".to_string() }.into();
+        assert_eq!(ErrorClass::of(&error), ErrorClass::Timeout);
+
+        let wrapped = error.context("scan failed");
+        assert_eq!(ErrorClass::of(&wrapped), ErrorClass::Timeout);
+
+        assert_eq!(ErrorClass::of(&anyhow::anyhow!("something else")), ErrorClass::Other);
+    }
+
+    #[test]
+    fn test_scan_error_serialization() {
+        let error: anyhow::Error = WafDetectorError::InvalidUrl {
+            url: "nope".to_string(),
+            message: "relative URL without a base".to_string(),
+        }.into();
+        let json = serde_json::to_value(ScanError::from_error(&error)).unwrap();
+        assert_eq!(json["class"], "invalid_url");
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_is_network_error() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let url = format!("http://127.0.0.1:{}/", port);
+        let error = reqwest::get(&url).await.unwrap_err();
+        assert_eq!(WafDetectorError::from_reqwest(&url, &error).class(), ErrorClass::Network);
+    }
+}
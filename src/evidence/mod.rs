@@ -0,0 +1,138 @@
+//! Evidence grouping
+//!
+//! Providers often report the same fact several times: a handful of CloudFront
+//! headers, or one header matched by two signatures. After detection the registry
+//! groups each provider's evidence by detection method, marks the strongest item of a
+//! group as its primary signal, drops exact repeats and keeps at most
+//! [`MAX_PER_GROUP`] items per group in `evidence_map`. The complete groups are kept
+//! in `evidence_groups` (shown by `--debug`). Scoring always sees the full list.
+
+use crate::Evidence;
+use serde::{Deserialize, Serialize};
+
+/// Items per group kept in `evidence_map`: the primary signal and the next strongest
+pub const MAX_PER_GROUP: usize = 3;
+
+/// Related evidence from one detection method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceGroup {
+    /// Detection method shared by the group (`header`, `dns`, `body`, ...)
+    pub method: String,
+    /// Strongest item of the group
+    pub primary: Evidence,
+    /// The other items, strongest first, including repeats of earlier ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<Evidence>,
+}
+
+impl EvidenceGroup {
+    /// Items in the group, primary included
+    pub fn count(&self) -> usize {
+        1 + self.related.len()
+    }
+}
+
+/// Same description and data: the same finding reported twice
+fn is_repeat(a: &Evidence, b: &Evidence) -> bool {
+    a.description == b.description && a.raw_data == b.raw_data
+}
+
+/// Group `evidence` by method, strongest group first
+pub fn group(evidence: &[Evidence]) -> Vec<EvidenceGroup> {
+    let mut buckets: Vec<(&'static str, Vec<Evidence>)> = Vec::new();
+    for item in evidence {
+        let (method, _) = item.method_type.name_and_source();
+        match buckets.iter_mut().find(|(name, _)| *name == method) {
+            Some((_, items)) => items.push(item.clone()),
+            None => buckets.push((method, vec![item.clone()])),
+        }
+    }
+
+    let mut groups: Vec<EvidenceGroup> = buckets
+        .into_iter()
+        .map(|(method, mut items)| {
+            // Stable, so equally confident items keep the provider's order
+            items.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+            let primary = items.remove(0);
+            EvidenceGroup { method: method.to_string(), primary, related: items }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.primary.confidence.partial_cmp(&a.primary.confidence).unwrap_or(std::cmp::Ordering::Equal));
+    groups
+}
+
+/// The evidence worth showing: per group the primary signal, then up to
+/// [`MAX_PER_GROUP`] - 1 related items that are not repeats
+pub fn condense(groups: &[EvidenceGroup]) -> Vec<Evidence> {
+    let mut condensed = Vec::new();
+    for group in groups {
+        let mut kept = vec![group.primary.clone()];
+        for item in &group.related {
+            if kept.len() >= MAX_PER_GROUP {
+                break;
+            }
+            if !kept.iter().any(|existing| is_repeat(existing, item)) {
+                kept.push(item.clone());
+            }
+        }
+        condensed.extend(kept);
+    }
+    condensed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DetectionMethod;
+
+    fn header(name: &str, confidence: f64, description: &str) -> Evidence {
+        Evidence {
+            method_type: DetectionMethod::Header(name.to_string()),
+            confidence,
+            description: description.to_string(),
+            raw_data: format!("{}: value", name),
+            signature_matched: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_groups_by_method_with_strongest_primary() {
+        let dns = Evidence {
+            method_type: DetectionMethod::DNS("CNAME".to_string()),
+            confidence: 0.9,
+            description: "CNAME points to cloudfront.net".to_string(),
+            raw_data: "d111.cloudfront.net".to_string(),
+            signature_matched: "cloudfront.net".to_string(),
+        };
+        let evidence = vec![
+            header("via", 0.6, "Via header names CloudFront"),
+            header("x-amz-cf-id", 0.8, "CloudFront request id"),
+            dns,
+        ];
+
+        let groups = group(&evidence);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].method, "dns");
+        assert_eq!(groups[1].method, "header");
+        assert_eq!(groups[1].primary.signature_matched, "x-amz-cf-id");
+        assert_eq!(groups[1].count(), 2);
+    }
+
+    #[test]
+    fn test_condense_drops_repeats_and_caps_groups() {
+        let evidence = vec![
+            header("x-amz-cf-id", 0.8, "CloudFront request id"),
+            header("x-amz-cf-id", 0.8, "CloudFront request id"),
+            header("x-amz-cf-pop", 0.7, "CloudFront edge location"),
+            header("via", 0.6, "Via header names CloudFront"),
+            header("x-cache", 0.5, "CloudFront cache status"),
+        ];
+
+        let groups = group(&evidence);
+        let condensed = condense(&groups);
+        let kept: Vec<&str> = condensed.iter().map(|e| e.signature_matched.as_str()).collect();
+        assert_eq!(kept, vec!["x-amz-cf-id", "x-amz-cf-pop", "via"]);
+        // The full list is still in the group
+        assert_eq!(groups[0].count(), 5);
+    }
+}
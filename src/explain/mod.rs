@@ -0,0 +1,194 @@
+//! "Why was this detected" narratives
+//!
+//! Turns a result's evidence and scoring breakdown into sentences for reports:
+//! the strongest signal, what corroborates it and what contradicts it.
+
+use crate::evidence::{self, EvidenceGroup};
+use crate::utils::truncate_display;
+use crate::{DetectionMethod, DetectionResult, Evidence, ProviderDetection};
+
+/// Most corroborating signals named per detection
+const MAX_CORROBORATING: usize = 3;
+
+/// One paragraph per detected provider, or why nothing was detected
+pub fn narrative(result: &DetectionResult) -> String {
+    if let Some(error) = &result.error {
+        return format!("Nothing was detected because the scan failed ({:?}): {}.", error.class, error.message);
+    }
+
+    // A provider detected as both WAF and CDN gets one paragraph
+    let mut roles: Vec<(&ProviderDetection, Vec<&str>)> = Vec::new();
+    for (detection, role) in [
+        (&result.detected_waf, "WAF"),
+        (&result.detected_cdn, "CDN"),
+        (&result.detected_bot_manager, "bot manager"),
+    ] {
+        let Some(detection) = detection else { continue };
+        match roles.iter_mut().find(|(existing, _)| existing.name == detection.name) {
+            Some((_, names)) => names.push(role),
+            None => roles.push((detection, vec![role])),
+        }
+    }
+
+    if roles.is_empty() {
+        let mut weak: Vec<(&String, &f64)> = result.provider_scores.iter().filter(|(_, score)| **score > 0.0).collect();
+        if weak.is_empty() {
+            return "No WAF, CDN or bot manager was detected: no provider matched any evidence.".to_string();
+        }
+        weak.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(b.0)));
+        let names: Vec<String> = weak.iter().map(|(name, score)| format!("{} ({:.0}%)", name, *score * 100.0)).collect();
+        return format!("No WAF, CDN or bot manager was detected; the strongest signals were too weak: {}.", join(&names));
+    }
+
+    roles
+        .into_iter()
+        .map(|(detection, names)| paragraph(result, detection, &names))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn paragraph(result: &DetectionResult, detection: &ProviderDetection, roles: &[&str]) -> String {
+    let mut text = format!("Detected {} as {} with {:.0}% confidence", detection.name, join(roles), detection.confidence * 100.0);
+    if let Some(details) = result.confidence_details.get(&detection.name) {
+        text.push_str(&format!(" ({:?})", details.level));
+    }
+
+    let groups = match result.evidence_groups.get(&detection.name) {
+        Some(groups) => groups.clone(),
+        None => evidence::group(result.evidence_map.get(&detection.name).map(Vec::as_slice).unwrap_or_default()),
+    };
+    match groups.first() {
+        Some(strongest) => {
+            text.push_str(&format!(" because of {}", signal(&strongest.primary)));
+            let corroborating = corroborating(&groups);
+            if corroborating.is_empty() {
+                text.push_str(", with nothing else corroborating it");
+            } else {
+                text.push_str(&format!(", corroborated by {}", join(&corroborating)));
+            }
+        }
+        None => text.push_str(" without recorded evidence"),
+    }
+
+    let contradictions: Vec<String> = result
+        .negative_evidence
+        .get(&detection.name)
+        .into_iter()
+        .flatten()
+        .map(|hit| format!("the {} header \"{}\" ({})", hit.header, truncate_display(&hit.value, 40), hit.reason))
+        .collect();
+    if contradictions.is_empty() {
+        text.push_str(", and contradicted by nothing.");
+    } else {
+        text.push_str(&format!(", and contradicted by {}.", join(&contradictions)));
+    }
+    text
+}
+
+/// Other groups' primary signals first, then the strongest group's other items
+fn corroborating(groups: &[EvidenceGroup]) -> Vec<String> {
+    let mut signals: Vec<String> = Vec::new();
+    let candidates = groups[1..]
+        .iter()
+        .map(|group| &group.primary)
+        .chain(groups[0].related.iter());
+    for item in candidates {
+        let text = signal(item);
+        if signal(&groups[0].primary) != text && !signals.contains(&text) {
+            signals.push(text);
+        }
+        if signals.len() == MAX_CORROBORATING {
+            break;
+        }
+    }
+    signals
+}
+
+/// One piece of evidence as a noun phrase
+fn signal(item: &Evidence) -> String {
+    let data = truncate_display(item.raw_data.trim(), 60);
+    match &item.method_type {
+        DetectionMethod::Header(name) if !data.is_empty() => format!("the {} header \"{}\"", name.to_lowercase(), data),
+        DetectionMethod::Header(name) => format!("the {} header", name.to_lowercase()),
+        DetectionMethod::DNS(record) if !data.is_empty() => format!("the {} record {}", record, data),
+        DetectionMethod::StatusCode(code) => format!("HTTP status {} ({})", code, item.description),
+        _ => item.description.clone(),
+    }
+}
+
+/// "a", "a and b", "a, b and c"
+fn join<S: AsRef<str>>(items: &[S]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.as_ref().to_string(),
+        [rest @ .., last] => format!(
+            "{} and {}",
+            rest.iter().map(|item| item.as_ref()).collect::<Vec<_>>().join(", "),
+            last.as_ref()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confidence::advanced_scoring::NegativeEvidence;
+
+    fn result_with(evidence: Vec<Evidence>) -> DetectionResult {
+        let mut result: DetectionResult = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com/",
+            "detected_waf": null,
+            "detected_cdn": null,
+            "provider_scores": {},
+            "evidence_map": {},
+            "detection_time_ms": 0,
+            "metadata": {"timestamp": "2024-01-01T00:00:00Z", "version": "0.1.0", "user_agent": "test"}
+        }))
+        .unwrap();
+        let detection = ProviderDetection { name: "CloudFlare".to_string(), confidence: 0.92, features: Vec::new(), layer: None };
+        result.detected_waf = Some(detection.clone());
+        result.detected_cdn = Some(detection);
+        result.evidence_map.insert("CloudFlare".to_string(), evidence);
+        result
+    }
+
+    fn evidence(method_type: DetectionMethod, confidence: f64, raw_data: &str, description: &str) -> Evidence {
+        Evidence {
+            method_type,
+            confidence,
+            description: description.to_string(),
+            raw_data: raw_data.to_string(),
+            signature_matched: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_narrative_names_signal_corroboration_and_contradictions() {
+        let mut result = result_with(vec![
+            evidence(DetectionMethod::Header("CF-Ray".to_string()), 0.9, "8f1c2a3b4c5d-FRA", "CF-Ray header"),
+            evidence(DetectionMethod::DNS("CNAME".to_string()), 0.7, "example.com.cdn.cloudflare.net", "CNAME to Cloudflare"),
+        ]);
+        assert_eq!(
+            narrative(&result),
+            "Detected CloudFlare as WAF and CDN with 92% confidence because of the cf-ray header \"8f1c2a3b4c5d-FRA\", \
+             corroborated by the CNAME record example.com.cdn.cloudflare.net, and contradicted by nothing."
+        );
+
+        result.negative_evidence.insert(
+            "CloudFlare".to_string(),
+            vec![NegativeEvidence { header: "x-amz-cf-id".to_string(), value: "abc".to_string(), penalty: 0.5, reason: "CloudFront request id".to_string() }],
+        );
+        assert!(narrative(&result).ends_with("contradicted by the x-amz-cf-id header \"abc\" (CloudFront request id)."));
+    }
+
+    #[test]
+    fn test_narrative_without_detection() {
+        let mut result = result_with(Vec::new());
+        result.detected_waf = None;
+        result.detected_cdn = None;
+        assert!(narrative(&result).starts_with("No WAF, CDN or bot manager was detected: no provider"));
+
+        result.provider_scores.insert("Akamai".to_string(), 0.25);
+        assert!(narrative(&result).ends_with("too weak: Akamai (25%)."));
+    }
+}
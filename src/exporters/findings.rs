@@ -0,0 +1,370 @@
+//! Vulnerability findings for DefectDojo or a generic vuln JSON consumer
+//!
+//! Two kinds of results become findings: attack payloads a smoke test got through
+//! (one finding per payload type and injection point) and targets with no WAF in front
+//! of them. Each finding carries a dedup key that stays the same across runs, so
+//! re-importing a report updates the existing finding instead of duplicating it.
+//!
+//! The DefectDojo format is its "Generic Findings Import" JSON. Each export rewrites
+//! the file at `path`.
+
+use super::{ChangeEvent, Exporter, ScanEvent};
+use crate::engine::waf_mode_detector::PayloadType;
+use crate::payload::waf_smoke_test::{PayloadClassification, SmokeTestResult};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// CWE-693: Protection Mechanism Failure
+const CWE_PROTECTION_FAILURE: u32 = 693;
+
+/// Bypassing payloads quoted per finding
+const MAX_EXAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingsFormat {
+    #[default]
+    DefectDojo,
+    Generic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FindingsConfig {
+    /// Report file, rewritten on each export
+    pub path: PathBuf,
+    #[serde(default)]
+    pub format: FindingsFormat,
+}
+
+/// One tool-neutral finding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub title: String,
+    pub severity: Severity,
+    pub url: String,
+    /// `waf-bypass` or `no-waf`
+    pub kind: String,
+    /// Payload type of a bypass finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub description: String,
+    pub mitigation: String,
+    pub evidence: Vec<String>,
+    /// Stable across runs: the same weakness on the same host always has the same key
+    pub dedup_key: String,
+    pub date: DateTime<Utc>,
+}
+
+/// Severity of letting a payload type through
+pub fn bypass_severity(payload_type: &PayloadType) -> Severity {
+    match payload_type {
+        PayloadType::SqlInjectionBasic | PayloadType::SqlInjectionAdvanced | PayloadType::CommandInjection => Severity::High,
+        PayloadType::XssBasic | PayloadType::XssAdvanced | PayloadType::PathTraversal | PayloadType::FileUpload => Severity::Medium,
+        PayloadType::ScannerDetection | PayloadType::Enumeration => Severity::Low,
+        PayloadType::Benign => Severity::Info,
+    }
+}
+
+/// A completed scan that found no WAF; failed scans say nothing either way
+pub fn from_scan(event: &ScanEvent) -> Option<Finding> {
+    if event.waf.is_some() || event.error.is_some() {
+        return None;
+    }
+    Some(no_waf(&event.url, event.cdn.as_deref(), event.timestamp))
+}
+
+/// Bypass findings of a smoke test, plus a no-WAF finding when none was detected
+pub fn from_smoke_test(result: &SmokeTestResult) -> Vec<Finding> {
+    let mut bypasses: BTreeMap<(String, &'static str), (&PayloadType, Vec<String>, usize)> = BTreeMap::new();
+    for test in &result.test_results {
+        if test.classification != PayloadClassification::Allowed || test.payload_type == PayloadType::Benign {
+            continue;
+        }
+        let entry = bypasses
+            .entry((slug(&test.payload_type), test.location.as_str()))
+            .or_insert_with(|| (&test.payload_type, Vec::new(), 0));
+        entry.2 += 1;
+        if entry.1.len() < MAX_EXAMPLES {
+            entry.1.push(format!("{} -> HTTP {}", test.payload, test.response_status));
+        }
+    }
+
+    let host = host_of(&result.url);
+    let waf = result.detected_waf.as_deref().unwrap_or("no WAF");
+    let mut findings: Vec<Finding> = bypasses
+        .into_iter()
+        .map(|((category, location), (payload_type, examples, count))| Finding {
+            title: format!("WAF bypass: {} payloads via {} on {}", payload_type, location, host),
+            severity: bypass_severity(payload_type),
+            url: result.url.clone(),
+            kind: "waf-bypass".to_string(),
+            description: format!(
+                "{} of the {} payloads sent in the {} were not blocked by {} (effectiveness {:.1}%).",
+                count, payload_type, location, waf, result.summary.effectiveness_percentage
+            ),
+            mitigation: format!("Enable or tune {} rules for the {} and retest.", payload_type, location),
+            evidence: examples,
+            dedup_key: format!("waf-detector:waf-bypass:{}:{}:{}", host, category, location),
+            category: Some(category),
+            location: Some(location.to_string()),
+            date: result.timestamp,
+        })
+        .collect();
+    if result.detected_waf.is_none() {
+        findings.push(no_waf(&result.url, result.detected_cdn.as_deref(), result.timestamp));
+    }
+    findings
+}
+
+fn no_waf(url: &str, cdn: Option<&str>, date: DateTime<Utc>) -> Finding {
+    let host = host_of(url);
+    let mut evidence = vec!["No WAF signature in headers, cookies, block pages or DNS".to_string()];
+    if let Some(cdn) = cdn {
+        evidence.push(format!("Served through {} without a detected WAF", cdn));
+    }
+    Finding {
+        title: format!("No WAF detected on {}", host),
+        severity: Severity::Medium,
+        url: url.to_string(),
+        kind: "no-waf".to_string(),
+        category: None,
+        location: None,
+        description: format!("No web application firewall was detected in front of {}.", url),
+        mitigation: "Put the application behind a WAF, or record why it does not need one.".to_string(),
+        evidence,
+        dedup_key: format!("waf-detector:no-waf:{}", host),
+        date,
+    }
+}
+
+fn slug(payload_type: &PayloadType) -> String {
+    payload_type.to_string().to_ascii_lowercase().replace(' ', "-")
+}
+
+fn host_of(url: &str) -> String {
+    url::Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_else(|| url.to_string())
+}
+
+/// DefectDojo "Generic Findings Import" document
+pub fn defectdojo(findings: &[Finding]) -> serde_json::Value {
+    let findings: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|finding| {
+            let mut description = finding.description.clone();
+            if !finding.evidence.is_empty() {
+                description.push_str("\n\nEvidence:\n");
+                for line in &finding.evidence {
+                    description.push_str(&format!("- `{}`\n", line));
+                }
+            }
+            serde_json::json!({
+                "title": finding.title,
+                "description": description,
+                "severity": finding.severity,
+                "mitigation": finding.mitigation,
+                "date": finding.date.format("%Y-%m-%d").to_string(),
+                "cwe": CWE_PROTECTION_FAILURE,
+                "unique_id_from_tool": finding.dedup_key,
+                "vuln_id_from_tool": finding.kind,
+                "active": true,
+                "verified": false,
+                "static_finding": false,
+                "dynamic_finding": true,
+                "endpoints": endpoint(&finding.url).into_iter().collect::<Vec<_>>(),
+                "tags": ["waf-detector", finding.kind.as_str()],
+            })
+        })
+        .collect();
+    serde_json::json!({ "findings": findings })
+}
+
+fn endpoint(url: &str) -> Option<serde_json::Value> {
+    let url = url::Url::parse(url).ok()?;
+    Some(serde_json::json!({
+        "protocol": url.scheme(),
+        "host": url.host_str()?,
+        "port": url.port_or_known_default(),
+        "path": url.path(),
+    }))
+}
+
+/// Generic vuln JSON: the findings as they are, with the tool that produced them
+pub fn generic(findings: &[Finding]) -> serde_json::Value {
+    serde_json::json!({
+        "tool": "waf-detector",
+        "version": env!("CARGO_PKG_VERSION"),
+        "generated_at": Utc::now(),
+        "findings": findings,
+    })
+}
+
+#[derive(Debug)]
+pub struct FindingsExporter {
+    config: FindingsConfig,
+}
+
+impl FindingsExporter {
+    pub fn new(config: FindingsConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn render(&self, findings: &[Finding]) -> Result<String> {
+        let document = match self.config.format {
+            FindingsFormat::DefectDojo => defectdojo(findings),
+            FindingsFormat::Generic => generic(findings),
+        };
+        Ok(serde_json::to_string_pretty(&document)?)
+    }
+
+    fn write(&self, findings: &[Finding]) -> Result<()> {
+        crate::payload::export::write_atomic(&self.config.path, &self.render(findings)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl Exporter for FindingsExporter {
+    fn name(&self) -> &'static str {
+        "findings"
+    }
+
+    async fn export_scans(&self, events: &[ScanEvent]) -> Result<()> {
+        let findings: Vec<Finding> = events.iter().filter_map(from_scan).collect();
+        self.write(&findings)
+    }
+
+    /// Change events are not findings
+    async fn export_changes(&self, _events: &[ChangeEvent]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn export_smoke_tests(&self, results: &[SmokeTestResult]) -> Result<()> {
+        let findings: Vec<Finding> = results.iter().flat_map(from_smoke_test).collect();
+        self.write(&findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::waf_smoke_test::{PayloadLocation, PayloadTestResult};
+
+    fn test(payload_type: PayloadType, location: PayloadLocation, payload: &str, classification: PayloadClassification) -> PayloadTestResult {
+        PayloadTestResult {
+            category: payload_type.to_string(),
+            payload: payload.to_string(),
+            payload_type,
+            location,
+            response_status: if classification == PayloadClassification::Allowed { 200 } else { 403 },
+            response_time_ms: 10,
+            classification,
+            evidence: Vec::new(),
+            waf_indicators: Vec::new(),
+        }
+    }
+
+    fn smoke_test(detected_waf: Option<&str>) -> SmokeTestResult {
+        let mut result: SmokeTestResult = serde_json::from_value(serde_json::json!({
+            "url": "https://shop.example.com/",
+            "test_results": [],
+            "summary": {
+                "total_tests": 4, "blocked_count": 1, "allowed_count": 3, "error_count": 0,
+                "rate_limited_count": 0, "challenge_count": 0, "effectiveness_percentage": 25.0,
+                "average_response_time_ms": 10.0
+            },
+            "waf_mode": null,
+            "detected_waf": detected_waf,
+            "detected_cdn": null,
+            "recommendations": [],
+            "total_time_ms": 100,
+            "timestamp": "2026-03-04T05:06:07Z",
+            "is_smoke_test": true
+        }))
+        .unwrap();
+        result.test_results = vec![
+            test(PayloadType::SqlInjectionBasic, PayloadLocation::Query, "' OR 1=1--", PayloadClassification::Allowed),
+            test(PayloadType::SqlInjectionBasic, PayloadLocation::Query, "1 UNION SELECT 1", PayloadClassification::Allowed),
+            test(PayloadType::XssBasic, PayloadLocation::JsonBody, "<script>alert(1)</script>", PayloadClassification::Allowed),
+            test(PayloadType::XssBasic, PayloadLocation::Query, "<svg onload=alert(1)>", PayloadClassification::Blocked),
+        ];
+        result
+    }
+
+    #[test]
+    fn test_bypasses_grouped_by_type_and_location() {
+        let findings = from_smoke_test(&smoke_test(Some("ModSecurity")));
+        assert_eq!(findings.len(), 2);
+
+        let sqli = findings.iter().find(|f| f.category.as_deref() == Some("sql-injection-basic")).unwrap();
+        assert_eq!(sqli.severity, Severity::High);
+        assert_eq!(sqli.dedup_key, "waf-detector:waf-bypass:shop.example.com:sql-injection-basic:query");
+        assert_eq!(sqli.evidence, vec!["' OR 1=1-- -> HTTP 200", "1 UNION SELECT 1 -> HTTP 200"]);
+        assert!(sqli.description.starts_with("2 of the SQL Injection Basic payloads sent in the query were not blocked by ModSecurity"));
+
+        let xss = findings.iter().find(|f| f.location.as_deref() == Some("json")).unwrap();
+        assert_eq!(xss.severity, Severity::Medium);
+    }
+
+    #[test]
+    fn test_no_waf_findings_share_a_dedup_key() {
+        let from_smoke = from_smoke_test(&smoke_test(None));
+        let no_waf = from_smoke.iter().find(|f| f.kind == "no-waf").unwrap();
+
+        let scan = ScanEvent {
+            url: "https://shop.example.com/checkout".to_string(),
+            timestamp: Utc::now(),
+            waf: None,
+            waf_confidence: None,
+            cdn: Some("Fastly".to_string()),
+            bot_manager: None,
+            detection_time_ms: 5,
+            error: None,
+        };
+        let from_scan = from_scan(&scan).unwrap();
+        assert_eq!(from_scan.dedup_key, no_waf.dedup_key);
+        assert!(from_scan.evidence.iter().any(|e| e.contains("Fastly")));
+
+        assert!(super::from_scan(&ScanEvent { waf: Some("CloudFlare".to_string()), ..scan.clone() }).is_none());
+        assert!(super::from_scan(&ScanEvent { error: Some("timed out".to_string()), ..scan }).is_none());
+    }
+
+    #[test]
+    fn test_defectdojo_document() {
+        let document = defectdojo(&from_smoke_test(&smoke_test(None)));
+        let findings = document["findings"].as_array().unwrap();
+        assert_eq!(findings.len(), 3);
+        let first = &findings[0];
+        assert_eq!(first["severity"], "High");
+        assert_eq!(first["date"], "2026-03-04");
+        assert_eq!(first["cwe"], 693);
+        assert_eq!(first["endpoints"][0]["host"], "shop.example.com");
+        assert_eq!(first["endpoints"][0]["port"], 443);
+        assert!(first["description"].as_str().unwrap().contains("Evidence:\n- `' OR 1=1-- -> HTTP 200`"));
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("findings.json");
+        let exporter = FindingsExporter::new(FindingsConfig { path: path.clone(), format: FindingsFormat::Generic });
+        exporter.export_smoke_tests(&[smoke_test(Some("ModSecurity"))]).await.unwrap();
+
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(report["tool"], "waf-detector");
+        assert_eq!(report["findings"].as_array().unwrap().len(), 2);
+    }
+}
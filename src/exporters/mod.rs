@@ -0,0 +1,257 @@
+//! Scan result exporters (`--exporters FILE`)
+//!
+//! Exporters forward scan results, and the change events scheduled scans raise when a
+//! target's WAF or CDN differs from the previous run, to a log pipeline. They are
+//! listed in a YAML or JSON file:
+//!
+//! ```yaml
+//! exporters:
+//!   - type: splunk_hec
+//!     url: https://splunk.example.com:8088
+//!     token: 00000000-0000-0000-0000-000000000000
+//!     index: security
+//!   - type: syslog
+//!     address: 10.0.0.5:514
+//!     protocol: tcp
+//!     facility: local3
+//!   - type: findings
+//!     path: findings.json
+//!     format: defectdojo
+//! ```
+//!
+//! Smoke test results go to the exporters that accept them: the `findings` exporter
+//! turns bypasses and unprotected targets into DefectDojo or generic vuln JSON.
+//!
+//! A failing exporter is logged and does not fail the scan.
+
+#[cfg(feature = "payload")]
+pub mod findings;
+pub mod splunk;
+pub mod syslog;
+
+#[cfg(feature = "payload")]
+use crate::payload::waf_smoke_test::SmokeTestResult;
+use crate::DetectionResult;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Scan summary sent for each result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanEvent {
+    pub url: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf_confidence: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_manager: Option<String>,
+    pub detection_time_ms: u64,
+    /// Why the scan failed; absent when it completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ScanEvent {
+    pub fn from_result(result: &DetectionResult) -> Self {
+        Self {
+            url: result.url.clone(),
+            timestamp: result.metadata.timestamp,
+            waf: result.waf_name().map(str::to_string),
+            waf_confidence: result.waf_confidence(),
+            cdn: result.cdn_name().map(str::to_string),
+            bot_manager: result.bot_manager_name().map(str::to_string),
+            detection_time_ms: result.detection_time_ms,
+            error: result.error.as_ref().map(|error| error.message.clone()),
+        }
+    }
+}
+
+/// A monitored target whose WAF or CDN changed between two scheduled runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub url: String,
+    pub timestamp: DateTime<Utc>,
+    /// Schedule that scanned the target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    pub previous_waf: Option<String>,
+    pub previous_cdn: Option<String>,
+    pub waf: Option<String>,
+    pub cdn: Option<String>,
+}
+
+impl ChangeEvent {
+    /// A WAF was there on the previous run and is gone now
+    pub fn waf_lost(&self) -> bool {
+        self.previous_waf.is_some() && self.waf.is_none()
+    }
+
+    pub fn summary(&self) -> String {
+        let name = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+        format!(
+            "{}: WAF {} -> {}, CDN {} -> {}",
+            self.url,
+            name(&self.previous_waf),
+            name(&self.waf),
+            name(&self.previous_cdn),
+            name(&self.cdn)
+        )
+    }
+}
+
+/// A destination for scan results and change events
+#[async_trait::async_trait]
+pub trait Exporter: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &'static str;
+
+    async fn export_scans(&self, events: &[ScanEvent]) -> Result<()>;
+
+    async fn export_changes(&self, events: &[ChangeEvent]) -> Result<()>;
+
+    /// Smoke test results; ignored unless the exporter has a use for them
+    #[cfg(feature = "payload")]
+    async fn export_smoke_tests(&self, _results: &[SmokeTestResult]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// One entry of the exporters file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExporterConfig {
+    SplunkHec(splunk::SplunkHecConfig),
+    Syslog(syslog::SyslogConfig),
+    #[cfg(feature = "payload")]
+    Findings(findings::FindingsConfig),
+}
+
+impl ExporterConfig {
+    pub fn build(&self) -> Result<Arc<dyn Exporter>> {
+        Ok(match self {
+            ExporterConfig::SplunkHec(config) => Arc::new(splunk::SplunkHec::new(config.clone())?),
+            ExporterConfig::Syslog(config) => Arc::new(syslog::Syslog::new(config.clone())?),
+            #[cfg(feature = "payload")]
+            ExporterConfig::Findings(config) => Arc::new(findings::FindingsExporter::new(config.clone())),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportersFile {
+    #[serde(default)]
+    pub exporters: Vec<ExporterConfig>,
+}
+
+/// The configured exporters
+#[derive(Debug, Clone, Default)]
+pub struct Exporters {
+    exporters: Vec<Arc<dyn Exporter>>,
+}
+
+impl Exporters {
+    pub fn new(exporters: Vec<Arc<dyn Exporter>>) -> Self {
+        Self { exporters }
+    }
+
+    /// Exporters listed in a YAML or JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read exporters config '{}': {}", path.display(), e))?;
+        let file: ExportersFile = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid exporters config '{}': {}", path.display(), e))?;
+        let exporters = file
+            .exporters
+            .iter()
+            .map(ExporterConfig::build)
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| anyhow!("Invalid exporters config '{}': {}", path.display(), e))?;
+        Ok(Self::new(exporters))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exporters.is_empty()
+    }
+
+    /// Send `results` to every exporter; returns how many exporters failed
+    pub async fn scans(&self, results: &[DetectionResult]) -> usize {
+        if self.exporters.is_empty() || results.is_empty() {
+            return 0;
+        }
+        let events: Vec<ScanEvent> = results.iter().map(ScanEvent::from_result).collect();
+        let sends = self.exporters.iter().map(|exporter| async { (exporter.name(), exporter.export_scans(&events).await) });
+        failures(futures::future::join_all(sends).await)
+    }
+
+    /// Send `changes` to every exporter; returns how many exporters failed
+    pub async fn changes(&self, changes: &[ChangeEvent]) -> usize {
+        if self.exporters.is_empty() || changes.is_empty() {
+            return 0;
+        }
+        let sends = self.exporters.iter().map(|exporter| async { (exporter.name(), exporter.export_changes(changes).await) });
+        failures(futures::future::join_all(sends).await)
+    }
+
+    /// Send smoke test `results` to every exporter; returns how many exporters failed
+    #[cfg(feature = "payload")]
+    pub async fn smoke_tests(&self, results: &[SmokeTestResult]) -> usize {
+        if self.exporters.is_empty() || results.is_empty() {
+            return 0;
+        }
+        let sends = self.exporters.iter().map(|exporter| async { (exporter.name(), exporter.export_smoke_tests(results).await) });
+        failures(futures::future::join_all(sends).await)
+    }
+}
+
+fn failures(outcomes: Vec<(&'static str, Result<()>)>) -> usize {
+    let mut failed = 0;
+    for (name, outcome) in outcomes {
+        if let Err(e) = outcome {
+            tracing::warn!(exporter = name, error = %e, "export failed");
+            failed += 1;
+        }
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "payload")]
+    fn test_exporters_file_parses() {
+        let file: ExportersFile = serde_yaml::from_str(
+            "exporters:\n  - type: splunk_hec\n    url: https://splunk:8088\n    token: t0ken\n  - type: syslog\n    address: 127.0.0.1:514\n    facility: local3\n  - type: findings\n    path: findings.json\n",
+        )
+        .unwrap();
+        assert_eq!(file.exporters.len(), 3);
+        assert!(matches!(&file.exporters[0], ExporterConfig::SplunkHec(config) if config.sourcetype == splunk::DEFAULT_SOURCETYPE));
+        assert!(matches!(&file.exporters[1], ExporterConfig::Syslog(config) if config.protocol == syslog::Protocol::Udp));
+        assert!(matches!(&file.exporters[2], ExporterConfig::Findings(config) if config.format == findings::FindingsFormat::DefectDojo));
+        assert!(file.exporters.iter().all(|config| config.build().is_ok()));
+
+        assert!(serde_yaml::from_str::<ExportersFile>("exporters:\n  - type: kafka\n").is_err());
+    }
+
+    #[test]
+    fn test_change_summary() {
+        let change = ChangeEvent {
+            url: "https://example.com/".to_string(),
+            timestamp: Utc::now(),
+            schedule: None,
+            previous_waf: Some("CloudFlare".to_string()),
+            previous_cdn: Some("CloudFlare".to_string()),
+            waf: None,
+            cdn: Some("CloudFlare".to_string()),
+        };
+        assert!(change.waf_lost());
+        assert_eq!(change.summary(), "https://example.com/: WAF CloudFlare -> none, CDN CloudFlare -> CloudFlare");
+    }
+}
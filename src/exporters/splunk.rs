@@ -0,0 +1,172 @@
+//! Splunk HTTP Event Collector exporter
+//!
+//! Events are batched into one request to `/services/collector/event`, one JSON object
+//! per event, authenticated with the collector token.
+
+use super::{ChangeEvent, Exporter, ScanEvent};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SOURCETYPE: &str = "waf-detector";
+
+const EVENT_PATH: &str = "/services/collector/event";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplunkHecConfig {
+    /// Collector base URL, e.g. `https://splunk.example.com:8088`
+    pub url: String,
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    #[serde(default = "default_sourcetype")]
+    pub sourcetype: String,
+}
+
+fn default_sourcetype() -> String {
+    DEFAULT_SOURCETYPE.to_string()
+}
+
+#[derive(Debug)]
+pub struct SplunkHec {
+    client: crate::http::HttpClient,
+    config: SplunkHecConfig,
+}
+
+impl SplunkHec {
+    pub fn new(config: SplunkHecConfig) -> Result<Self> {
+        if config.token.is_empty() {
+            return Err(anyhow!("splunk_hec exporter needs a token"));
+        }
+        url::Url::parse(&config.url).map_err(|e| anyhow!("invalid splunk_hec url '{}': {}", config.url, e))?;
+        Ok(Self { client: crate::http::HttpClient::new()?, config })
+    }
+
+    /// One HEC event envelope per event, newline separated
+    pub fn payload<T: Serialize>(&self, events: &[(DateTime<Utc>, &str, &str, T)]) -> Result<String> {
+        let mut body = String::new();
+        for (time, host, kind, event) in events {
+            let mut envelope = serde_json::json!({
+                "time": time.timestamp_millis() as f64 / 1000.0,
+                "host": host,
+                "source": "waf-detector",
+                "sourcetype": self.config.sourcetype,
+                "event": { "type": kind, "data": event },
+            });
+            if let Some(index) = &self.config.index {
+                envelope["index"] = serde_json::Value::String(index.clone());
+            }
+            body.push_str(&serde_json::to_string(&envelope)?);
+            body.push('\n');
+        }
+        Ok(body)
+    }
+
+    async fn send(&self, body: String) -> Result<()> {
+        let url = format!("{}{}", self.config.url.trim_end_matches('/'), EVENT_PATH);
+        let authorization = format!("Splunk {}", self.config.token);
+        let response = self
+            .client
+            .post_with_headers(&url, &body, &[("Authorization", authorization.as_str()), ("Content-Type", "application/json")])
+            .await?;
+        if !(200..300).contains(&response.status) {
+            return Err(anyhow!("Splunk HEC returned HTTP {}: {}", response.status, response.body.trim()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Exporter for SplunkHec {
+    fn name(&self) -> &'static str {
+        "splunk_hec"
+    }
+
+    async fn export_scans(&self, events: &[ScanEvent]) -> Result<()> {
+        let events: Vec<_> = events.iter().map(|event| (event.timestamp, host_of(&event.url), "scan", event)).collect();
+        self.send(self.payload(&events)?).await
+    }
+
+    async fn export_changes(&self, events: &[ChangeEvent]) -> Result<()> {
+        let events: Vec<_> = events.iter().map(|event| (event.timestamp, host_of(&event.url), "change", event)).collect();
+        self.send(self.payload(&events)?).await
+    }
+}
+
+fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    rest.split(['/', '?', '#']).next().unwrap_or(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan() -> ScanEvent {
+        ScanEvent {
+            url: "https://example.com/login".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2026-01-02T03:04:05.250Z").unwrap().with_timezone(&Utc),
+            waf: Some("CloudFlare".to_string()),
+            waf_confidence: Some(0.9),
+            cdn: None,
+            bot_manager: None,
+            detection_time_ms: 120,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_splunk_hec_posts_events() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/collector/event")
+            .match_header("authorization", "Splunk t0ken")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r#""index":"security""#.to_string()),
+                mockito::Matcher::Regex(r#""url":"https://example.com/login""#.to_string()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"text":"Success","code":0}"#)
+            .create_async()
+            .await;
+
+        let exporter = SplunkHec::new(SplunkHecConfig {
+            url: server.url(),
+            token: "t0ken".to_string(),
+            index: Some("security".to_string()),
+            sourcetype: DEFAULT_SOURCETYPE.to_string(),
+        })
+        .unwrap();
+        exporter.export_scans(&[scan()]).await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_splunk_hec_rejected_token_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/services/collector/event").with_status(403).with_body(r#"{"text":"Invalid token","code":4}"#).create_async().await;
+
+        let exporter =
+            SplunkHec::new(SplunkHecConfig { url: server.url(), token: "bad".to_string(), index: None, sourcetype: DEFAULT_SOURCETYPE.to_string() }).unwrap();
+        let error = exporter.export_scans(&[scan()]).await.unwrap_err();
+        assert!(error.to_string().contains("403"));
+    }
+
+    #[test]
+    fn test_payload_envelope() {
+        let exporter = SplunkHec::new(SplunkHecConfig {
+            url: "https://splunk:8088".to_string(),
+            token: "t".to_string(),
+            index: None,
+            sourcetype: DEFAULT_SOURCETYPE.to_string(),
+        })
+        .unwrap();
+        let event = scan();
+        let body = exporter.payload(&[(event.timestamp, host_of(&event.url), "scan", &event)]).unwrap();
+        let envelope: serde_json::Value = serde_json::from_str(body.trim()).unwrap();
+        assert_eq!(envelope["time"], 1767323045.25);
+        assert_eq!(envelope["host"], "example.com");
+        assert_eq!(envelope["event"]["type"], "scan");
+        assert!(envelope.get("index").is_none());
+    }
+}
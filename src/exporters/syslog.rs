@@ -0,0 +1,320 @@
+//! RFC 5424 syslog exporter
+//!
+//! Each event is one message: the summary fields as structured data, the full event as
+//! JSON in the message body. UDP sends one datagram per message; TCP uses octet-counting
+//! framing (RFC 6587).
+
+use super::{ChangeEvent, Exporter, ScanEvent};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Private enterprise number used for the structured-data ID
+const SD_ID: &str = "waf@32473";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Facility {
+    #[default]
+    User,
+    Daemon,
+    Auth,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl Facility {
+    pub fn code(self) -> u8 {
+        match self {
+            Facility::User => 1,
+            Facility::Daemon => 3,
+            Facility::Auth => 4,
+            Facility::Local0 => 16,
+            Facility::Local1 => 17,
+            Facility::Local2 => 18,
+            Facility::Local3 => 19,
+            Facility::Local4 => 20,
+            Facility::Local5 => 21,
+            Facility::Local6 => 22,
+            Facility::Local7 => 23,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning = 4,
+    Notice = 5,
+    Informational = 6,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyslogConfig {
+    /// Collector `host:port`
+    pub address: String,
+    #[serde(default)]
+    pub protocol: Protocol,
+    #[serde(default)]
+    pub facility: Facility,
+    #[serde(default = "default_app_name")]
+    pub app_name: String,
+    /// HOSTNAME field; the nil value `-` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+fn default_app_name() -> String {
+    "waf-detector".to_string()
+}
+
+/// One structured message before framing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub severity: Severity,
+    pub timestamp: DateTime<Utc>,
+    pub msg_id: &'static str,
+    pub params: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct Syslog {
+    config: SyslogConfig,
+}
+
+impl Syslog {
+    pub fn new(config: SyslogConfig) -> Result<Self> {
+        let valid = config.address.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if !valid {
+            return Err(anyhow!("syslog exporter address '{}' must be host:port", config.address));
+        }
+        Ok(Self { config })
+    }
+
+    /// The RFC 5424 line for `message`, without transport framing
+    pub fn format(&self, message: &Message) -> String {
+        let pri = self.config.facility.code() as u32 * 8 + message.severity as u32;
+        let params: Vec<String> = message.params.iter().map(|(name, value)| format!("{}=\"{}\"", name, escape_param(value))).collect();
+        format!(
+            "<{}>1 {} {} {} - {} [{} {}] {}",
+            pri,
+            message.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            header_field(self.config.hostname.as_deref().unwrap_or("-"), 255),
+            header_field(&self.config.app_name, 48),
+            message.msg_id,
+            SD_ID,
+            params.join(" "),
+            message.body
+        )
+    }
+
+    async fn send(&self, messages: Vec<Message>) -> Result<()> {
+        let lines: Vec<String> = messages.iter().map(|message| self.format(message)).collect();
+        match self.config.protocol {
+            Protocol::Udp => {
+                let socket = tokio::net::UdpSocket::bind(if self.config.address.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" }).await?;
+                socket.connect(&self.config.address).await?;
+                for line in lines {
+                    socket.send(line.as_bytes()).await?;
+                }
+            }
+            Protocol::Tcp => {
+                let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect(&self.config.address))
+                    .await
+                    .map_err(|_| anyhow!("timed out connecting to syslog collector {}", self.config.address))??;
+                let mut framed = String::new();
+                for line in lines {
+                    framed.push_str(&format!("{} {}", line.len(), line));
+                }
+                stream.write_all(framed.as_bytes()).await?;
+                stream.shutdown().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Exporter for Syslog {
+    fn name(&self) -> &'static str {
+        "syslog"
+    }
+
+    async fn export_scans(&self, events: &[ScanEvent]) -> Result<()> {
+        let messages = events.iter().map(scan_message).collect::<Result<Vec<_>>>()?;
+        self.send(messages).await
+    }
+
+    async fn export_changes(&self, events: &[ChangeEvent]) -> Result<()> {
+        let messages = events.iter().map(change_message).collect::<Result<Vec<_>>>()?;
+        self.send(messages).await
+    }
+}
+
+pub fn scan_message(event: &ScanEvent) -> Result<Message> {
+    let mut params = vec![("url", event.url.clone()), ("waf", or_none(&event.waf)), ("cdn", or_none(&event.cdn))];
+    if let Some(error) = &event.error {
+        params.push(("error", error.clone()));
+    }
+    Ok(Message {
+        severity: if event.error.is_some() { Severity::Warning } else { Severity::Informational },
+        timestamp: event.timestamp,
+        msg_id: "scan",
+        params,
+        body: serde_json::to_string(event)?,
+    })
+}
+
+pub fn change_message(event: &ChangeEvent) -> Result<Message> {
+    Ok(Message {
+        severity: if event.waf_lost() { Severity::Warning } else { Severity::Notice },
+        timestamp: event.timestamp,
+        msg_id: "change",
+        params: vec![
+            ("url", event.url.clone()),
+            ("previous_waf", or_none(&event.previous_waf)),
+            ("waf", or_none(&event.waf)),
+            ("previous_cdn", or_none(&event.previous_cdn)),
+            ("cdn", or_none(&event.cdn)),
+        ],
+        body: serde_json::to_string(event)?,
+    })
+}
+
+fn or_none(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "none".to_string())
+}
+
+/// PARAM-VALUE escaping: `"`, `\` and `]` are backslash-escaped
+fn escape_param(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Header fields are printable US-ASCII without spaces, bounded in length
+fn header_field(value: &str, max: usize) -> String {
+    let field: String = value.chars().filter(|c| c.is_ascii_graphic()).take(max).collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syslog(protocol: Protocol, address: &str) -> Syslog {
+        Syslog::new(SyslogConfig {
+            address: address.to_string(),
+            protocol,
+            facility: Facility::Local3,
+            app_name: default_app_name(),
+            hostname: Some("scanner-1".to_string()),
+        })
+        .unwrap()
+    }
+
+    fn change() -> ChangeEvent {
+        ChangeEvent {
+            url: "https://example.com/".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z").unwrap().with_timezone(&Utc),
+            schedule: Some("nightly".to_string()),
+            previous_waf: Some("CloudFlare".to_string()),
+            previous_cdn: Some("CloudFlare".to_string()),
+            waf: None,
+            cdn: Some("CloudFlare".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_rfc5424_line() {
+        let line = syslog(Protocol::Udp, "127.0.0.1:514").format(&change_message(&change()).unwrap());
+        // local3 (19) * 8 + warning (4)
+        assert!(line.starts_with(
+            "<156>1 2026-01-02T03:04:05.000Z scanner-1 waf-detector - change [waf@32473 url=\"https://example.com/\" previous_waf=\"CloudFlare\" waf=\"none\""
+        ));
+        assert!(line.ends_with("] {\"url\":\"https://example.com/\",\"timestamp\":\"2026-01-02T03:04:05Z\",\"schedule\":\"nightly\",\"previous_waf\":\"CloudFlare\",\"previous_cdn\":\"CloudFlare\",\"waf\":null,\"cdn\":\"CloudFlare\"}"));
+    }
+
+    #[test]
+    fn test_param_escaping_and_address_validation() {
+        assert_eq!(escape_param(r#"a"b\c]d"#), r#"a\"b\\c\]d"#);
+        assert_eq!(header_field("my host", 48), "myhost");
+        assert!(Syslog::new(SyslogConfig {
+            address: "collector".to_string(),
+            protocol: Protocol::Udp,
+            facility: Facility::User,
+            app_name: default_app_name(),
+            hostname: None,
+        })
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_octet_counting() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let received = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = String::new();
+            tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut buffer).await.unwrap();
+            buffer
+        });
+
+        syslog(Protocol::Tcp, &address).export_changes(&[change(), change()]).await.unwrap();
+        let buffer = received.await.unwrap();
+        let (length, rest) = buffer.split_once(' ').unwrap();
+        let length: usize = length.parse().unwrap();
+        assert!(rest[..length].starts_with("<156>1 "));
+        assert!(rest[length..].starts_with(&format!("{} <156>1 ", length)));
+    }
+
+    #[tokio::test]
+    async fn test_udp_datagram() {
+        let collector = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = collector.local_addr().unwrap().to_string();
+        let scan = ScanEvent {
+            url: "https://example.com/".to_string(),
+            timestamp: Utc::now(),
+            waf: None,
+            waf_confidence: None,
+            cdn: None,
+            bot_manager: None,
+            detection_time_ms: 5,
+            error: Some("connection refused".to_string()),
+        };
+
+        syslog(Protocol::Udp, &address).export_scans(&[scan]).await.unwrap();
+        let mut buffer = [0u8; 2048];
+        let received = collector.recv(&mut buffer).await.unwrap();
+        let datagram = String::from_utf8_lossy(&buffer[..received]);
+        assert!(datagram.starts_with("<156>1 "));
+        assert!(datagram.contains("error=\"connection refused\""));
+    }
+}
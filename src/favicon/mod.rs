@@ -0,0 +1,341 @@
+//! Favicon hashes and static-asset fingerprints
+//!
+//! Icons are hashed the way Shodan indexes them (`http.favicon.hash`): MurmurHash3
+//! x86_32 of the base64 encoding with a newline every 76 characters. A known hash
+//! names the vendor, an unknown one is still reported so scans can be correlated
+//! with search-engine results when hunting for origins. A challenge or block page
+//! served in place of the icon is a signal of its own, as are static assets that
+//! only an edge answers (Cloudflare's `/cdn-cgi/trace`).
+
+use crate::blockpage::BlockPageClassifier;
+use crate::challenge::detect_challenge;
+use crate::http::{HttpClient, HttpResponse};
+use crate::utils::base64_encode;
+use crate::{Evidence, MethodType};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use url::Url;
+
+/// Confidence of a favicon hash found in the hash list
+const KNOWN_HASH_CONFIDENCE: f64 = 0.7;
+
+/// Confidence of an unmatched hash, reported for correlation only
+const UNKNOWN_HASH_CONFIDENCE: f64 = 0.1;
+
+/// A favicon hash attributed to a vendor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownFavicon {
+    pub vendor: String,
+    /// Shodan-style signed mmh3 hash
+    pub hash: i32,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Load a JSON list of `{"vendor", "hash", "description"}` entries
+pub fn load_known_favicons(path: impl AsRef<Path>) -> Result<Vec<KnownFavicon>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read favicon hashes '{}': {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| anyhow!("Invalid favicon hashes '{}': {}", path.display(), e))
+}
+
+/// A path answered by the edge itself rather than the origin
+#[derive(Debug, Clone, Copy)]
+pub struct StaticAsset {
+    pub vendor: &'static str,
+    pub path: &'static str,
+    /// Substrings that must all appear in a 200 response
+    pub markers: &'static [&'static str],
+    pub confidence: f64,
+    pub description: &'static str,
+}
+
+pub const STATIC_ASSETS: &[StaticAsset] = &[StaticAsset {
+    vendor: "CloudFlare",
+    path: "/cdn-cgi/trace",
+    markers: &["fl=", "visit_scheme=", "colo="],
+    confidence: 0.9,
+    description: "/cdn-cgi/trace answered with Cloudflare's edge trace",
+}];
+
+/// MurmurHash3 x86_32
+pub fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let scramble = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+
+    let mut hash = seed;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        hash ^= scramble(u32::from_le_bytes([block[0], block[1], block[2], block[3]]));
+        hash = hash.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        hash ^= scramble(tail.iter().rev().fold(0u32, |k, byte| k << 8 | *byte as u32));
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ hash >> 16
+}
+
+/// Shodan's favicon hash: signed mmh3 of the MIME-wrapped base64 encoding
+pub fn favicon_hash(icon: &[u8]) -> i32 {
+    let encoded = base64_encode(icon);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+    for line in encoded.as_bytes().chunks(76) {
+        wrapped.push_str(std::str::from_utf8(line).unwrap_or_default());
+        wrapped.push('\n');
+    }
+    murmur3_32(wrapped.as_bytes(), 0) as i32
+}
+
+/// `href` of every `<link rel="icon">` (or `shortcut icon`, `apple-touch-icon`) in a page
+pub fn icon_links(body: &str) -> Vec<String> {
+    let lower = body.to_ascii_lowercase();
+    let mut links = Vec::new();
+    let mut rest = lower.as_str();
+    while let Some(start) = rest.find("<link") {
+        let tag_start = lower.len() - rest.len() + start;
+        let tag_end = lower[tag_start..].find('>').map_or(lower.len(), |end| tag_start + end);
+        let tag = &lower[tag_start..tag_end];
+        let is_icon = attribute(tag, "rel").is_some_and(|rel| rel.split_whitespace().any(|rel| rel.ends_with("icon")));
+        if let (true, Some(href)) = (is_icon, attribute(tag, "href")) {
+            // Take the href from the original body so its case is kept
+            let offset = tag_start + href.as_ptr() as usize - tag.as_ptr() as usize;
+            links.push(body[offset..offset + href.len()].to_string());
+        }
+        rest = &lower[tag_end..];
+    }
+    links
+}
+
+/// Value of a quoted or bare attribute in a lowercased tag
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=", name);
+    let start = tag.match_indices(&pattern)
+        .find(|(i, _)| tag[..*i].ends_with(char::is_whitespace))?
+        .0 + pattern.len();
+    let value = &tag[start..];
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next(),
+        _ => value.split_whitespace().next(),
+    }
+}
+
+/// Fetches favicons and edge-only assets and matches them against known vendors
+#[derive(Debug, Clone)]
+pub struct FaviconAnalyzer {
+    http_client: Arc<HttpClient>,
+    known: Vec<KnownFavicon>,
+    block_pages: BlockPageClassifier,
+}
+
+impl FaviconAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            http_client: Arc::new(HttpClient::default()),
+            known: Vec::new(),
+            block_pages: BlockPageClassifier::new(),
+        }
+    }
+
+    /// Hashes to attribute; no list is bundled, unknown hashes are still reported
+    pub fn with_known_favicons(mut self, known: Vec<KnownFavicon>) -> Self {
+        self.known = known;
+        self
+    }
+
+    pub fn with_http_client(mut self, http_client: Arc<HttpClient>) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Fetch `/favicon.ico`, icons linked from the page and the static assets
+    pub async fn analyze(&self, url: &str, response: Option<&HttpResponse>) -> Result<Vec<Evidence>> {
+        let base = Url::parse(url)?;
+        let mut icons = vec![base.join("/favicon.ico")?];
+        for href in response.map(|response| icon_links(&response.body)).unwrap_or_default() {
+            if let Ok(icon) = base.join(&href) {
+                if matches!(icon.scheme(), "http" | "https") && !icons.contains(&icon) {
+                    icons.push(icon);
+                }
+            }
+        }
+
+        let mut evidence = Vec::new();
+        let mut fetched = false;
+        let mut last_error = None;
+        for icon in &icons {
+            match self.http_client.get_bytes(icon.as_str()).await {
+                Ok((response, bytes)) => {
+                    fetched = true;
+                    evidence.extend(self.icon_evidence(&response, &bytes));
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        for asset in STATIC_ASSETS {
+            if let Ok(response) = self.http_client.get(base.join(asset.path)?.as_str()).await {
+                fetched = true;
+                evidence.extend(asset_evidence(asset, &response));
+            }
+        }
+
+        match (fetched, last_error) {
+            (false, Some(e)) => Err(e),
+            _ => Ok(evidence),
+        }
+    }
+
+    /// Evidence from one icon response: its hash, or the page served in its place
+    pub fn icon_evidence(&self, response: &HttpResponse, bytes: &[u8]) -> Vec<Evidence> {
+        let is_html = response.headers.get("content-type").is_some_and(|value| value.contains("text/html"))
+            || response.body.trim_start().starts_with('<');
+        if is_html {
+            if let Some(challenge) = detect_challenge(response) {
+                return vec![Evidence {
+                    method_type: MethodType::Body("favicon-challenge".to_string()),
+                    confidence: challenge.confidence,
+                    description: format!("{} {} served instead of {}", challenge.vendor, challenge.kind, response.url),
+                    raw_data: challenge.evidence.join("; "),
+                    signature_matched: challenge.vendor,
+                }];
+            }
+            if let Some(found) = self.block_pages.classify(&response.body) {
+                return vec![Evidence {
+                    method_type: MethodType::Body("favicon-block-page".to_string()),
+                    confidence: found.confidence,
+                    description: format!("{} {} block page served instead of {}", found.vendor, found.page, response.url),
+                    raw_data: format!("{:.3}", found.similarity),
+                    signature_matched: found.vendor,
+                }];
+            }
+            return Vec::new();
+        }
+        if response.status != 200 || bytes.is_empty() {
+            return Vec::new();
+        }
+
+        let hash = favicon_hash(bytes);
+        let evidence = match self.known.iter().find(|known| known.hash == hash) {
+            Some(known) => Evidence {
+                method_type: MethodType::Body("favicon-hash".to_string()),
+                confidence: KNOWN_HASH_CONFIDENCE,
+                description: format!("Favicon {} hashes to {} ({})", response.url, hash, known.description),
+                raw_data: hash.to_string(),
+                signature_matched: known.vendor.clone(),
+            },
+            None => Evidence {
+                method_type: MethodType::Body("favicon-hash".to_string()),
+                confidence: UNKNOWN_HASH_CONFIDENCE,
+                description: format!("Favicon {} hashes to {} (no known vendor)", response.url, hash),
+                raw_data: hash.to_string(),
+                signature_matched: String::new(),
+            },
+        };
+        vec![evidence]
+    }
+}
+
+impl Default for FaviconAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn asset_evidence(asset: &StaticAsset, response: &HttpResponse) -> Option<Evidence> {
+    let matched = response.status == 200 && asset.markers.iter().all(|marker| response.body.contains(marker));
+    matched.then(|| Evidence {
+        method_type: MethodType::Body("static-asset".to_string()),
+        confidence: asset.confidence,
+        description: asset.description.to_string(),
+        raw_data: asset.path.to_string(),
+        signature_matched: asset.vendor.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn icon_response(content_type: &str, body: &[u8]) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            headers: [("content-type", content_type)].into_iter().collect(),
+            body: String::from_utf8_lossy(body).into_owned(),
+            url: "https://example.com/favicon.ico".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_murmur3_matches_reference_values() {
+        assert_eq!(murmur3_32(b"", 0), 0);
+        assert_eq!(murmur3_32(b"foo", 0) as i32, -156908512);
+        assert_eq!(murmur3_32(b"hello", 0), 0x248b_fa47);
+        assert_eq!(murmur3_32(b"", 1), 0x514e_28b7);
+        assert_eq!(murmur3_32(b"The quick brown fox jumps over the lazy dog", 0x9747_b28c), 0x2fa8_26cd);
+    }
+
+    #[test]
+    fn test_favicon_hash_wraps_base64_lines() {
+        // 57 bytes encode to exactly one 76-character line
+        let icon = [0u8; 58];
+        let encoded = base64_encode(&icon);
+        let wrapped = format!("{}\n{}\n", &encoded[..76], &encoded[76..]);
+        assert_eq!(favicon_hash(&icon), murmur3_32(wrapped.as_bytes(), 0) as i32);
+    }
+
+    #[test]
+    fn test_known_and_unknown_hashes() {
+        let icon = b"\x00\x00\x01\x00\x01\x00\x10\x10";
+        let response = icon_response("image/x-icon", icon);
+        let known = KnownFavicon { vendor: "Sucuri".to_string(), hash: favicon_hash(icon), description: "test".to_string() };
+
+        let unknown = FaviconAnalyzer::new().icon_evidence(&response, icon);
+        assert_eq!(unknown[0].signature_matched, "");
+        assert_eq!(unknown[0].raw_data, favicon_hash(icon).to_string());
+
+        let matched = FaviconAnalyzer::new().with_known_favicons(vec![known]).icon_evidence(&response, icon);
+        assert_eq!(matched[0].signature_matched, "Sucuri");
+        assert!(matched[0].confidence > unknown[0].confidence);
+    }
+
+    #[test]
+    fn test_challenge_served_instead_of_icon() {
+        let body = b"<html><script>window._cf_chl_opt={cType: 'managed'}</script></html>";
+        let mut response = icon_response("text/html; charset=UTF-8", body);
+        response.status = 403;
+        response.headers.append("cf-mitigated", "challenge");
+
+        let evidence = FaviconAnalyzer::new().icon_evidence(&response, body);
+        assert_eq!(evidence[0].method_type, MethodType::Body("favicon-challenge".to_string()));
+        assert_eq!(evidence[0].signature_matched, "CloudFlare");
+        assert!(FaviconAnalyzer::new().icon_evidence(&icon_response("text/html", b"<html>Not found</html>"), b"").is_empty());
+    }
+
+    #[test]
+    fn test_icon_links() {
+        let page = r#"<head><link rel="stylesheet" href="/a.css"><LINK REL="Shortcut Icon" HREF="/Static/Icon.png">
+            <link href='https://cdn.example.com/touch.png' rel='apple-touch-icon' /><link rel=icon href=/fav.svg></head>"#;
+        assert_eq!(icon_links(page), vec!["/Static/Icon.png", "https://cdn.example.com/touch.png", "/fav.svg"]);
+    }
+
+    #[test]
+    fn test_cloudflare_trace_asset() {
+        let trace = "fl=123f45\nh=example.com\nip=192.0.2.1\nts=1700000000.1\nvisit_scheme=https\ncolo=AMS\nhttp=http/2\n";
+        let mut response = icon_response("text/plain", trace.as_bytes());
+        assert_eq!(asset_evidence(&STATIC_ASSETS[0], &response).unwrap().signature_matched, "CloudFlare");
+        response.body = "<html>Not found</html>".to_string();
+        assert!(asset_evidence(&STATIC_ASSETS[0], &response).is_none());
+    }
+}
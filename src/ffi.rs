@@ -0,0 +1,137 @@
+//! C ABI for embedding (`ffi` feature)
+//!
+//! Build the shared library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`; the declarations
+//! are in `include/waf_detector.h`. Every call returns a JSON string owned by the
+//! library, `{"success": true, "result": {...}}` or `{"success": false, "error": "..."}`,
+//! which the caller releases with `waf_string_free`.
+//!
+//! Calls block until detection finishes. They run on a runtime and detector (default
+//! providers, default techniques) created on first use and shared by all threads.
+
+use crate::{DetectionResult, Detector};
+use anyhow::{anyhow, Result};
+use std::ffi::{c_char, CStr, CString};
+use std::sync::OnceLock;
+
+struct Embedded {
+    runtime: tokio::runtime::Runtime,
+    detector: Detector,
+}
+
+static EMBEDDED: OnceLock<std::result::Result<Embedded, String>> = OnceLock::new();
+
+fn embedded() -> Result<&'static Embedded> {
+    EMBEDDED
+        .get_or_init(|| {
+            let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().map_err(|e| e.to_string())?;
+            let detector = Detector::builder().with_default_providers().build().map_err(|e| e.to_string())?;
+            Ok(Embedded { runtime, detector })
+        })
+        .as_ref()
+        .map_err(|e| anyhow!("failed to initialize: {}", e))
+}
+
+/// `ptr` as UTF-8; `name` is used in the error
+///
+/// # Safety
+/// `ptr` is null or points to a NUL-terminated string.
+unsafe fn string_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(anyhow!("{} is null", name));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| anyhow!("{} is not valid UTF-8", name))
+}
+
+/// The JSON envelope handed back to the caller
+fn respond(outcome: Result<DetectionResult>) -> *mut c_char {
+    let envelope = match outcome {
+        Ok(result) => serde_json::json!({ "success": true, "result": result }),
+        Err(e) => serde_json::json!({ "success": false, "error": e.to_string() }),
+    };
+    // serde_json escapes control characters, so the JSON never contains a NUL byte
+    CString::new(envelope.to_string()).expect("JSON has no interior NUL").into_raw()
+}
+
+/// Full detection of `url` (a URL or bare host name, scanned over https).
+///
+/// # Safety
+/// `url` is null or points to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn waf_detect_url(url: *const c_char) -> *mut c_char {
+    respond(detect_url(url))
+}
+
+unsafe fn detect_url(url: *const c_char) -> Result<DetectionResult> {
+    let url = crate::idn::normalize_url(string_arg(url, "url")?)?;
+    let embedded = embedded()?;
+    embedded.runtime.block_on(embedded.detector.detect(&url))
+}
+
+/// Passive detection of a raw HTTP response (status line, headers, blank line, body)
+/// received from `url`; nothing is sent over the network.
+///
+/// # Safety
+/// `url` is null or points to a NUL-terminated string; `response` is null or points to
+/// `response_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn waf_detect_response(url: *const c_char, response: *const u8, response_len: usize) -> *mut c_char {
+    respond(detect_response(url, response, response_len))
+}
+
+unsafe fn detect_response(url: *const c_char, response: *const u8, response_len: usize) -> Result<DetectionResult> {
+    let url = string_arg(url, "url")?;
+    if response.is_null() {
+        return Err(anyhow!("response is null"));
+    }
+    let raw = std::slice::from_raw_parts(response, response_len);
+    let response = crate::capture::parse_raw_response(raw, url)?;
+    let embedded = embedded()?;
+    embedded.runtime.block_on(embedded.detector.detect_from_response(url, response))
+}
+
+/// Release a string returned by this library; null is ignored.
+///
+/// # Safety
+/// `ptr` is null or a pointer returned by `waf_detect_url`/`waf_detect_response` that
+/// has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn waf_string_free(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(ptr: *mut c_char) -> serde_json::Value {
+        let json = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { waf_string_free(ptr) };
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_detect_response() {
+        let url = CString::new("https://shop.example.com/").unwrap();
+        let raw = b"HTTP/1.1 403 Forbidden\r\nServer: cloudflare\r\nCF-RAY: 8a1b2c3d4e5f6a7b-AMS\r\n\r\nAttention Required!";
+        let value = take(unsafe { waf_detect_response(url.as_ptr(), raw.as_ptr(), raw.len()) });
+        assert_eq!(value["success"], true);
+        assert_eq!(value["result"]["detected_waf"]["name"], "CloudFlare");
+
+        let value = take(unsafe { waf_detect_response(url.as_ptr(), b"nope".as_ptr(), 4) });
+        assert_eq!(value["success"], false);
+        assert!(value["error"].as_str().unwrap().contains("not an HTTP response"));
+    }
+
+    #[test]
+    fn test_null_arguments_are_errors() {
+        let value = take(unsafe { waf_detect_url(std::ptr::null()) });
+        assert_eq!(value["error"], "url is null");
+        let url = CString::new("https://example.com/").unwrap();
+        let value = take(unsafe { waf_detect_response(url.as_ptr(), std::ptr::null(), 0) });
+        assert_eq!(value["error"], "response is null");
+        unsafe { waf_string_free(std::ptr::null_mut()) };
+    }
+}
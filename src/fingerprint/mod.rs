@@ -0,0 +1,225 @@
+//! WAF version / ruleset fingerprinting
+//!
+//! Once a vendor has been identified, a handful of targeted probes estimate which
+//! ruleset generation is deployed and at what OWASP CRS paranoia level, and rule or
+//! incident IDs are pulled out of the block pages. All results are best-effort
+//! heuristics: a custom rule or a tuned anomaly threshold can shift any of them.
+
+use crate::http::{HttpClient, HttpResponse};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Vendors whose managed rules are derived from the OWASP Core Rule Set
+const CRS_BASED_VENDORS: &[&str] = &["Azure", "GoogleCloud", "CloudFlare"];
+
+/// What could be inferred about the deployed ruleset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RulesetInfo {
+    pub vendor: String,
+    /// e.g. "OWASP CRS"
+    pub ruleset: Option<String>,
+    /// Ruleset generation, e.g. "3.x" or "4.x"
+    pub generation: Option<String>,
+    /// Highest CRS paranoia level whose probe was blocked (1-4)
+    pub paranoia_level: Option<u8>,
+    /// Rule, incident and reference IDs found in block pages, as `kind:id`
+    pub rule_ids: Vec<String>,
+    pub probes: Vec<ProbeOutcome>,
+}
+
+/// Result of one fingerprinting probe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeOutcome {
+    pub name: String,
+    pub status: u16,
+    pub blocked: bool,
+}
+
+/// A single fingerprinting probe, sent as the `q` query parameter
+#[derive(Debug, Clone, Copy)]
+pub struct Probe {
+    pub name: &'static str,
+    pub value: &'static str,
+    /// CRS paranoia level this probe needs to be blocked, if it is a ladder probe
+    pub paranoia_level: Option<u8>,
+}
+
+/// Paranoia-level ladder. Each step is built around a SQL special-character anomaly
+/// rule that only runs at that level (942100 at PL1, 942430/942431/942432 at PL2-4).
+pub const PARANOIA_PROBES: &[Probe] = &[
+    Probe { name: "crs-pl1", value: "' OR 1=1--", paranoia_level: Some(1) },
+    Probe { name: "crs-pl2", value: "a'b'c'd'e'f'g'h'i'j'k'l'm'n", paranoia_level: Some(2) },
+    Probe { name: "crs-pl3", value: "a'b'c'd'e'f'g", paranoia_level: Some(3) },
+    Probe { name: "crs-pl4", value: "a'b'c", paranoia_level: Some(4) },
+];
+
+/// `${IFS}`-style shell evasion is only caught by the reworked CRS 4 unix RCE rules
+pub const CRS4_PROBE: Probe = Probe {
+    name: "crs4-unix-rce",
+    value: ";curl${IFS}example.com",
+    paranoia_level: None,
+};
+
+/// Generic probe used to provoke a block page on non-CRS vendors
+pub const BLOCK_PAGE_PROBE: Probe = Probe {
+    name: "block-page",
+    value: "<script>alert(1)</script>",
+    paranoia_level: None,
+};
+
+/// Ruleset fingerprinter for an already identified vendor
+#[derive(Debug, Clone)]
+pub struct Fingerprinter<'a> {
+    client: &'a HttpClient,
+}
+
+impl<'a> Fingerprinter<'a> {
+    pub fn new(client: &'a HttpClient) -> Self {
+        Self { client }
+    }
+
+    /// Probe `url` and report what can be inferred about `vendor`'s ruleset
+    pub async fn fingerprint(&self, url: &str, vendor: &str, baseline: &HttpResponse) -> Result<RulesetInfo> {
+        let mut info = RulesetInfo {
+            vendor: vendor.to_string(),
+            rule_ids: extract_rule_ids(&baseline.body),
+            ..Default::default()
+        };
+
+        if !is_crs_based(vendor, baseline) {
+            let response = self.send(url, &BLOCK_PAGE_PROBE).await?;
+            info.record(&BLOCK_PAGE_PROBE, &response, baseline);
+            return Ok(info);
+        }
+
+        for probe in PARANOIA_PROBES {
+            let response = self.send(url, probe).await?;
+            let blocked = info.record(probe, &response, baseline);
+            // The ladder stops at the first level that lets the probe through
+            if !blocked {
+                break;
+            }
+            info.paranoia_level = probe.paranoia_level;
+        }
+
+        if info.paranoia_level.is_some() {
+            info.ruleset = Some("OWASP CRS".to_string());
+            let response = self.send(url, &CRS4_PROBE).await?;
+            let crs4 = info.record(&CRS4_PROBE, &response, baseline);
+            info.generation = Some(if crs4 { "4.x" } else { "3.x" }.to_string());
+        }
+
+        Ok(info)
+    }
+
+    async fn send(&self, url: &str, probe: &Probe) -> Result<HttpResponse> {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let probe_url = format!("{}{}q={}", url, separator, urlencoding::encode(probe.value));
+        self.client.get(&probe_url).await
+    }
+}
+
+impl RulesetInfo {
+    /// Record a probe outcome and any IDs on its block page; returns whether it was blocked
+    fn record(&mut self, probe: &Probe, response: &HttpResponse, baseline: &HttpResponse) -> bool {
+        let blocked = is_blocked(response, baseline);
+        if blocked {
+            for id in extract_rule_ids(&response.body) {
+                if !self.rule_ids.contains(&id) {
+                    self.rule_ids.push(id);
+                }
+            }
+        }
+        self.probes.push(ProbeOutcome {
+            name: probe.name.to_string(),
+            status: response.status,
+            blocked,
+        });
+        blocked
+    }
+}
+
+fn is_crs_based(vendor: &str, baseline: &HttpResponse) -> bool {
+    CRS_BASED_VENDORS.contains(&vendor) || baseline.body.to_lowercase().contains("mod_security")
+}
+
+/// A probe counts as blocked when it gets a typical WAF block status the baseline did not
+pub fn is_blocked(response: &HttpResponse, baseline: &HttpResponse) -> bool {
+    matches!(response.status, 403 | 406 | 418 | 429 | 501) && response.status != baseline.status
+}
+
+fn rule_id_patterns() -> &'static [(&'static str, Regex)] {
+    static PATTERNS: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| vec![
+        // ModSecurity audit/debug pages: [id "942100"]
+        ("crs", Regex::new(r#"\[id "(\d{6})"\]"#).unwrap()),
+        // Cloudflare custom block pages and error codes
+        ("cloudflare-rule", Regex::new(r"(?i)rule[ _-]?id[:=\s]+([0-9a-f]{32})").unwrap()),
+        ("cloudflare-error", Regex::new(r"(?i)error\s*(?:code:?\s*)?(10\d{2})\b").unwrap()),
+        ("cloudflare-ray", Regex::new(r"(?i)ray id:\s*(?:<[^>]+>)?\s*([0-9a-f]{16})").unwrap()),
+        ("fortiweb-attack", Regex::new(r"(?i)attack id:\s*(\d+)").unwrap()),
+        ("akamai-reference", Regex::new(r"Reference\s*#([0-9a-f]+(?:\.[0-9a-f]+)+)").unwrap()),
+        ("imperva-incident", Regex::new(r"(?i)incident id:?\s*(\d+-\d+)").unwrap()),
+        ("sucuri-block", Regex::new(r"(?i)block id:?\s*([A-Z0-9]+)").unwrap()),
+    ])
+}
+
+/// Pull rule/incident/reference IDs out of a block page, labelled `kind:id`
+pub fn extract_rule_ids(body: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for (kind, pattern) in rule_id_patterns() {
+        for captures in pattern.captures_iter(body) {
+            let id = format!("{}:{}", kind, &captures[1]);
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: Default::default(),
+            body: body.to_string(),
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_rule_ids() {
+        let body = r#"<p>Error 1020</p><span>Cloudflare Ray ID: <strong>8a1b2c3d4e5f6a7b</strong></span>
+            Message: [id "942100"] [msg "SQL Injection Attack Detected via libinjection"] [id "949110"]"#;
+
+        assert_eq!(extract_rule_ids(body), vec![
+            "crs:942100",
+            "crs:949110",
+            "cloudflare-error:1020",
+            "cloudflare-ray:8a1b2c3d4e5f6a7b",
+        ]);
+        assert!(extract_rule_ids("<html>Welcome</html>").is_empty());
+    }
+
+    #[test]
+    fn test_is_blocked() {
+        let baseline = response(200, "ok");
+
+        assert!(is_blocked(&response(403, ""), &baseline));
+        assert!(!is_blocked(&response(404, ""), &baseline));
+        assert!(!is_blocked(&response(403, ""), &response(403, "")));
+    }
+
+    #[test]
+    fn test_paranoia_ladder_is_ordered() {
+        let levels: Vec<u8> = PARANOIA_PROBES.iter().filter_map(|p| p.paranoia_level).collect();
+        assert_eq!(levels, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,340 @@
+//! Response header order and casing fingerprints
+//!
+//! A proxy rebuilds the header block of every response it forwards, so where it puts
+//! the headers it adds and how it spells their names stays the same across every site
+//! behind it: CloudFront appends `X-Cache`, `Via`, `X-Amz-Cf-Pop`, `X-Amz-Cf-Id`,
+//! Fastly appends `X-Served-By`, `X-Cache`, `X-Cache-Hits`, `X-Timer`, and proxies that
+//! speak HTTP/2 internally write every name in lowercase even on HTTP/1.1. The HTTP
+//! client lowercases names, so casing is read from one raw HTTP/1.1 request.
+
+use crate::http::HttpResponse;
+use crate::{Evidence, MethodType};
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::Url;
+
+/// Stop reading a response head after this many bytes
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// Headers a provider writes in a fixed relative order
+#[derive(Debug, Clone, Copy)]
+pub struct OrderSignature {
+    pub provider: &'static str,
+    /// Lowercase names that must appear in this order; others may sit in between
+    pub sequence: &'static [&'static str],
+    pub confidence: f64,
+    pub description: &'static str,
+}
+
+pub const ORDER_SIGNATURES: &[OrderSignature] = &[
+    OrderSignature {
+        provider: "CloudFront",
+        sequence: &["x-cache", "via", "x-amz-cf-pop", "x-amz-cf-id"],
+        confidence: 0.6,
+        description: "X-Cache, Via, X-Amz-Cf-Pop and X-Amz-Cf-Id appended in CloudFront's order",
+    },
+    OrderSignature {
+        provider: "Fastly",
+        sequence: &["x-served-by", "x-cache", "x-cache-hits", "x-timer"],
+        confidence: 0.6,
+        description: "X-Served-By, X-Cache, X-Cache-Hits and X-Timer appended in Fastly's order",
+    },
+    OrderSignature {
+        provider: "Cloudflare",
+        sequence: &["server", "cf-ray"],
+        confidence: 0.4,
+        description: "Server followed by CF-RAY at the end of the header block, as Cloudflare writes it",
+    },
+];
+
+/// How the names in a header block are spelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderCasing {
+    /// Every name in lowercase
+    Lowercase,
+    /// Every name capitalized (`Content-Type`, `CF-RAY`)
+    Capitalized,
+    /// Some names lowercase, the rest capitalized
+    Mixed,
+}
+
+/// Header names of one response, in the order they were received
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderLayout {
+    pub names: Vec<String>,
+    /// Whether `names` are spelled as sent; false when the HTTP client lowercased them
+    pub casing_preserved: bool,
+}
+
+impl HeaderLayout {
+    /// Layout recorded in a response; casing counts as preserved if any name has capitals
+    pub fn from_response(response: &HttpResponse) -> Self {
+        let names = response.header_order.clone();
+        let casing_preserved = names.iter().any(|name| name.chars().any(|c| c.is_ascii_uppercase()));
+        Self { names, casing_preserved }
+    }
+
+    /// Layout of a raw HTTP/1.x response head (status line and header lines)
+    pub fn from_raw(head: &str) -> Self {
+        let names = head
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.trim().is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, _)| name.trim().to_string())
+            .collect();
+        Self { names, casing_preserved: true }
+    }
+
+    /// Lowercased names joined in order, comparable across responses
+    pub fn fingerprint(&self) -> String {
+        self.names.iter().map(|name| name.to_ascii_lowercase()).collect::<Vec<_>>().join(",")
+    }
+
+    /// Casing of the names, if it was preserved and there is anything to judge
+    pub fn casing(&self) -> Option<HeaderCasing> {
+        if !self.casing_preserved {
+            return None;
+        }
+        let lettered: Vec<&String> = self.names.iter().filter(|name| name.chars().any(|c| c.is_ascii_alphabetic())).collect();
+        if lettered.is_empty() {
+            return None;
+        }
+        let lowercase = lettered.iter().filter(|name| !name.chars().any(|c| c.is_ascii_uppercase())).count();
+        Some(match lowercase {
+            0 => HeaderCasing::Capitalized,
+            n if n == lettered.len() => HeaderCasing::Lowercase,
+            _ => HeaderCasing::Mixed,
+        })
+    }
+
+    /// Whether the signature's headers all appear, in its order
+    pub fn matches(&self, signature: &OrderSignature) -> bool {
+        let mut names = self.names.iter();
+        signature.sequence.iter().all(|wanted| names.any(|name| name.eq_ignore_ascii_case(wanted)))
+    }
+
+    pub fn to_evidence(&self) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+        if self.names.is_empty() {
+            return evidence;
+        }
+        let mut push = |signal: &str, confidence: f64, description: String, raw_data: String, signature: &str| {
+            evidence.push(Evidence {
+                method_type: MethodType::HeaderOrder(signal.to_string()),
+                confidence,
+                description,
+                raw_data,
+                signature_matched: signature.to_string(),
+            });
+        };
+
+        push("order", 0.2, format!("Response header order ({} headers)", self.names.len()), self.fingerprint(), "header-order");
+        for signature in ORDER_SIGNATURES.iter().filter(|signature| self.matches(signature)) {
+            push("order", signature.confidence, signature.description.to_string(), signature.sequence.join(","), signature.provider);
+        }
+
+        let raw_names = self.names.join(",");
+        match self.casing() {
+            Some(HeaderCasing::Lowercase) => push(
+                "casing",
+                0.3,
+                "Header names are all lowercase, as written by proxies that speak HTTP/2 internally".to_string(),
+                raw_names,
+                "lowercase-header-names",
+            ),
+            // Cloudflare capitalizes its own headers but sends alt-svc in lowercase
+            Some(HeaderCasing::Mixed) if self.names.iter().any(|n| n == "alt-svc") && self.names.iter().any(|n| n == "CF-RAY") => push(
+                "casing",
+                0.5,
+                "Lowercase alt-svc next to capitalized CF-RAY matches Cloudflare".to_string(),
+                raw_names,
+                "Cloudflare",
+            ),
+            Some(HeaderCasing::Mixed) => push(
+                "casing",
+                0.2,
+                "Header names mix lowercase and capitalized spellings, typical of headers added by a proxy".to_string(),
+                raw_names,
+                "mixed-header-casing",
+            ),
+            Some(HeaderCasing::Capitalized) | None => {}
+        }
+        evidence
+    }
+}
+
+/// Fingerprints header order and casing of a target
+#[derive(Debug, Clone)]
+pub struct HeaderOrderAnalyzer {
+    timeout: Duration,
+}
+
+impl HeaderOrderAnalyzer {
+    pub fn new() -> Self {
+        Self { timeout: Duration::from_secs(5) }
+    }
+
+    /// Time allowed for the raw request, connect to end of headers
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub async fn analyze(&self, url: &str, response: Option<&HttpResponse>) -> Result<Vec<Evidence>> {
+        Ok(self.layout(url, response).await?.to_evidence())
+    }
+
+    /// Layout from a raw request, falling back to the order recorded in `response`
+    pub async fn layout(&self, url: &str, response: Option<&HttpResponse>) -> Result<HeaderLayout> {
+        match self.fetch_head(url).await {
+            Ok(head) => Ok(HeaderLayout::from_raw(&head)),
+            Err(e) => response
+                .filter(|response| !response.header_order.is_empty())
+                .map(HeaderLayout::from_response)
+                .ok_or(e),
+        }
+    }
+
+    /// Response head of a plain HTTP/1.1 GET, byte for byte
+    pub async fn fetch_head(&self, url: &str) -> Result<String> {
+        let parsed = Url::parse(url)?;
+        let host = parsed.host_str().ok_or_else(|| anyhow!("URL '{}' has no host", url))?.to_string();
+        let port = parsed.port_or_known_default().unwrap_or(80);
+        let host_header = match parsed.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.clone(),
+        };
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: WAF-Detector/1.0\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+            path, host_header
+        );
+
+        let exchange = async {
+            let stream = TcpStream::connect((host.as_str(), port)).await?;
+            if parsed.scheme() == "https" {
+                read_head_tls(stream, &host, &request).await
+            } else {
+                read_head(stream, &request).await
+            }
+        };
+        tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| anyhow!("raw request to {} timed out", url))?
+    }
+}
+
+impl Default for HeaderOrderAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "tls")]
+async fn read_head_tls(stream: TcpStream, host: &str, request: &str) -> Result<String> {
+    let connector = native_tls::TlsConnector::builder()
+        .request_alpns(&["http/1.1"])
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()?;
+    let tls = tokio_native_tls::TlsConnector::from(connector).connect(host, stream).await?;
+    read_head(tls, request).await
+}
+
+#[cfg(not(feature = "tls"))]
+async fn read_head_tls(_stream: TcpStream, _host: &str, _request: &str) -> Result<String> {
+    Err(anyhow!("built without the `tls` feature"))
+}
+
+/// Send `request` and read until the blank line that ends the response head
+async fn read_head<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, request: &str) -> Result<String> {
+    stream.write_all(request.as_bytes()).await?;
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_HEAD_SIZE {
+            return Err(anyhow!("response head exceeds {} bytes", MAX_HEAD_SIZE));
+        }
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    if !head.starts_with("HTTP/") {
+        return Err(anyhow!("not an HTTP/1.x response"));
+    }
+    Ok(head.split("\r\n\r\n").next().unwrap_or_default().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLOUDFRONT_HEAD: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 12\r\nConnection: keep-alive\r\nDate: Tue, 13 Oct 2026 10:00:00 GMT\r\nX-Cache: Miss from cloudfront\r\nVia: 1.1 abc.cloudfront.net (CloudFront)\r\nX-Amz-Cf-Pop: FRA56-P1\r\nX-Amz-Cf-Id: 3xAmPlE==\r\n";
+
+    fn signatures(layout: &HeaderLayout) -> Vec<String> {
+        layout.to_evidence().into_iter().map(|e| e.signature_matched).collect()
+    }
+
+    #[test]
+    fn test_cloudfront_order() {
+        let layout = HeaderLayout::from_raw(CLOUDFRONT_HEAD);
+        assert_eq!(layout.names[0], "Content-Type");
+        assert_eq!(layout.casing(), Some(HeaderCasing::Capitalized));
+        assert_eq!(signatures(&layout), ["header-order", "CloudFront"]);
+
+        // Same headers, CloudFront's block reordered
+        let mut shuffled = layout.clone();
+        shuffled.names.swap(4, 7);
+        assert!(!signatures(&shuffled).contains(&"CloudFront".to_string()));
+    }
+
+    #[test]
+    fn test_casing_signals() {
+        let cloudflare = HeaderLayout::from_raw("HTTP/1.1 200 OK\r\nDate: x\r\nContent-Type: text/html\r\nServer: cloudflare\r\nCF-RAY: 8a1b-FRA\r\nalt-svc: h3=\":443\"; ma=86400\r\n");
+        assert_eq!(cloudflare.casing(), Some(HeaderCasing::Mixed));
+        assert_eq!(signatures(&cloudflare), ["header-order", "Cloudflare", "Cloudflare"]);
+
+        let envoy = HeaderLayout::from_raw("HTTP/1.1 200 OK\r\ncontent-type: text/html\r\nx-envoy-upstream-service-time: 3\r\nserver: envoy\r\n");
+        assert_eq!(envoy.casing(), Some(HeaderCasing::Lowercase));
+        assert!(signatures(&envoy).contains(&"lowercase-header-names".to_string()));
+    }
+
+    #[test]
+    fn test_live_response_has_no_casing() {
+        let response = HttpResponse {
+            header_order: vec!["x-served-by".into(), "x-cache".into(), "x-cache-hits".into(), "x-timer".into()],
+            ..HttpResponse::default()
+        };
+        let layout = HeaderLayout::from_response(&response);
+        assert_eq!(layout.casing(), None);
+        assert_eq!(signatures(&layout), ["header-order", "Fastly"]);
+
+        let captured = HttpResponse::from_parts(200, [("Server", "nginx"), ("x-cache", "HIT")], "", "https://example.com");
+        assert_eq!(HeaderLayout::from_response(&captured).casing(), Some(HeaderCasing::Mixed));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_head_keeps_casing() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            socket.write_all(b"HTTP/1.1 200 OK\r\nX-Served-By: cache-fra\r\nx-cache: HIT\r\nContent-Length: 2\r\n\r\nok").await.unwrap();
+        });
+
+        let layout = HeaderOrderAnalyzer::new().layout(&format!("http://127.0.0.1:{}/", port), None).await.unwrap();
+        assert_eq!(layout.names, ["X-Served-By", "x-cache", "Content-Length"]);
+        assert_eq!(layout.casing(), Some(HeaderCasing::Mixed));
+    }
+}
@@ -1,89 +1,745 @@
-use reqwest::{Client, Response};
+pub mod pool;
+pub mod session;
+
+use reqwest::{Client, RequestBuilder, Response};
 use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::dns::AddressFamily;
+use crate::error::WafDetectorError;
+use crate::ratelimit::{self, RateLimiter};
+use crate::replay::{self, RecordedResponse};
+use crate::profiles::{self, BrowserProfile};
+use session::CookieSession;
+
+/// Redirects followed per request (reqwest's default limit)
+const MAX_REDIRECTS: usize = 10;
+
+/// Body bytes kept per response when no limit is configured
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// With a tail sample, reading stops after this many times the limit
+const TAIL_READ_FACTOR: u64 = 8;
+
+/// Separates the kept start of a truncated body from its tail sample in `HttpResponse::body`
+pub const TAIL_SEPARATOR: &str = "\n<!-- waf-detector: body truncated -->\n";
+
+/// How much of a response body is read and kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit {
+    /// Bytes kept from the start of the body
+    pub max_bytes: usize,
+    /// Bytes kept from the end of a longer body (0: stop reading at `max_bytes`)
+    pub tail_bytes: usize,
+}
+
+impl Default for BodyLimit {
+    fn default() -> Self {
+        Self { max_bytes: DEFAULT_MAX_BODY_SIZE, tail_bytes: 0 }
+    }
+}
+
+impl BodyLimit {
+    /// Read bodies to the end, for API answers and bundles that are parsed or verified whole
+    pub fn unlimited() -> Self {
+        Self { max_bytes: usize::MAX, tail_bytes: 0 }
+    }
+}
+
+/// A body cut off at the client's [`BodyLimit`] or by a read error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyTruncation {
+    /// Bytes kept from the start of the body
+    pub kept_bytes: usize,
+    /// Bytes read off the wire (after decompression) before the client stopped
+    pub read_bytes: u64,
+    /// Declared Content-Length, if the server sent one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<u64>,
+    /// Bytes of the tail sample appended after [`TAIL_SEPARATOR`]
+    #[serde(default)]
+    pub tail_bytes: usize,
+}
+
+/// Retry policy for transient network failures
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts per request, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for every further retry
+    pub initial_backoff: Duration,
+    /// Upper bound for a single backoff delay
+    pub max_backoff: Duration,
+    /// Randomize each delay between 50% and 100% to avoid synchronized retries
+    pub jitter: bool,
+    /// Response status codes that are retried like network errors
+    pub retry_on_status: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+            jitter: true,
+            // 503 and 429 are deliberately absent: WAFs use them for challenges and blocks
+            retry_on_status: vec![502, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Policy allowing `retries` retries after the first attempt
+    pub fn with_retries(retries: u32) -> Self {
+        Self {
+            max_attempts: retries + 1,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let delay = self.initial_backoff
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            delay.mul_f64(0.5 + 0.5 * random_fraction())
+        } else {
+            delay
+        }
+    }
+
+    fn should_retry_status(&self, status: u16) -> bool {
+        self.retry_on_status.contains(&status)
+    }
+}
+
+tokio::task_local! {
+    /// Per-task request counter installed by [`count_requests`]
+    static SCOPED_REQUESTS: Arc<AtomicUsize>;
+}
+
+/// Run a future and count the HTTP requests it issues (through any client in the crate).
+///
+/// Scopes nest: requests are attributed to the innermost scope only.
+pub async fn count_requests<F: std::future::Future>(future: F) -> (F::Output, usize) {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let output = SCOPED_REQUESTS.scope(Arc::clone(&counter), future).await;
+    (output, counter.load(Ordering::Relaxed))
+}
+
+/// Attribute one request to the current [`count_requests`] scope, if any
+pub(crate) fn record_scoped_request() {
+    let _ = SCOPED_REQUESTS.try_with(|counter| counter.fetch_add(1, Ordering::Relaxed));
+}
+
+tokio::task_local! {
+    /// Set inside [`single_request`]
+    static SINGLE_REQUEST: ();
+}
+
+/// Run a future whose requests each go out exactly once, whatever the client's retry
+/// policy, and are not redirected: a 3xx comes back as the response. Strict passive
+/// scans use it for their one GET.
+pub async fn single_request<F: std::future::Future>(future: F) -> F::Output {
+    SINGLE_REQUEST.scope((), future).await
+}
+
+fn in_single_request() -> bool {
+    SINGLE_REQUEST.try_with(|_| ()).is_ok()
+}
+
+/// Only connection-level failures are worth retrying; e.g. invalid URLs are not
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+/// Random value in [0, 1) without pulling in a RNG dependency
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
 
 #[derive(Debug, Clone)]
 pub struct HttpClient {
     client: Client,
+    /// Number of requests sent through this client (shared between clones)
+    request_count: Arc<AtomicUsize>,
+    /// Dedicated rate limiter; the process-wide limiter is used when unset
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_policy: RetryPolicy,
+    /// Number of retries performed by this client (shared between clones)
+    retry_count: Arc<AtomicUsize>,
+    /// Cookies returned to the target on later requests; none kept when unset
+    session: Option<Arc<CookieSession>>,
+    body_limit: BodyLimit,
 }
 
 impl Default for HttpClient {
     fn default() -> Self {
         Self {
             client: Client::new(),
+            request_count: Arc::new(AtomicUsize::new(0)),
+            rate_limiter: None,
+            retry_policy: RetryPolicy::default(),
+            retry_count: Arc::new(AtomicUsize::new(0)),
+            session: None,
+            body_limit: BodyLimit::default(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Response headers in the order received. Names are lowercased; a repeated header
+/// (Set-Cookie, Via) keeps one entry per occurrence.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+type HeaderEntry<'a> = (&'a String, &'a String);
+
+impl Headers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a header after the existing ones, keeping earlier values of the same name
+    pub fn append(&mut self, name: impl AsRef<str>, value: impl Into<String>) {
+        self.entries.push((name.as_ref().to_lowercase(), value.into()));
+    }
+
+    /// Set a header, replacing every earlier value of the same name
+    pub fn insert(&mut self, name: impl AsRef<str>, value: impl Into<String>) {
+        let name = name.as_ref().to_lowercase();
+        self.entries.retain(|(existing, _)| *existing != name);
+        self.entries.push((name, value.into()));
+    }
+
+    /// First value of a header
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.entries
+            .iter()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// Every value of a header, in the order received
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = HeaderEntry<'_>> {
+        self.into_iter()
+    }
+
+    /// Header names in order; a repeated header is listed once per occurrence
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(name, _)| name)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// One value per name: repeated headers are joined with ", ", except Set-Cookie
+    /// whose values may contain commas and are kept one per line
+    pub fn to_map(&self) -> HashMap<String, String> {
+        let mut merged: HashMap<String, String> = HashMap::new();
+        for (name, value) in &self.entries {
+            let separator = if name == "set-cookie" { "\n" } else { ", " };
+            merged.entry(name.clone())
+                .and_modify(|existing| {
+                    existing.push_str(separator);
+                    existing.push_str(value);
+                })
+                .or_insert_with(|| value.clone());
+        }
+        merged
+    }
+}
+
+impl<'a> IntoIterator for &'a Headers {
+    type Item = HeaderEntry<'a>;
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (String, String)>, fn(&'a (String, String)) -> HeaderEntry<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(name, value)| (name, value))
+    }
+}
+
+impl<N: AsRef<str>, V: Into<String>> FromIterator<(N, V)> for Headers {
+    fn from_iter<I: IntoIterator<Item = (N, V)>>(iter: I) -> Self {
+        let mut headers = Headers::new();
+        for (name, value) in iter {
+            headers.append(name, value);
+        }
+        headers
+    }
+}
+
+impl From<HashMap<String, String>> for Headers {
+    fn from(map: HashMap<String, String>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl std::ops::Index<&str> for Headers {
+    type Output = String;
+
+    fn index(&self, name: &str) -> &String {
+        self.get(name).unwrap_or_else(|| panic!("no '{}' header", name))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct HttpResponse {
     pub status: u16,
-    pub headers: HashMap<String, String>,
+    pub headers: Headers,
     pub body: String,
     pub url: String,
+    /// Header names in the order they were received. Live responses are lowercased
+    /// by the HTTP stack; captured responses keep the names exactly as recorded.
+    pub header_order: Vec<String>,
+    /// Protocol version of the response, e.g. `HTTP/1.1` or `HTTP/2.0`
+    pub version: Option<String>,
+    /// Address of the server that answered, when the response came over a live connection
+    pub remote_ip: Option<IpAddr>,
+    /// Set when `body` holds only the start (and possibly a tail sample) of a longer body
+    pub truncation: Option<BodyTruncation>,
+}
+
+impl HttpResponse {
+    /// Build a response captured elsewhere (crawler, proxy export, HAR), keeping header
+    /// order and repeated headers
+    pub fn from_parts<N, V>(status: u16, headers: impl IntoIterator<Item = (N, V)>, body: impl Into<String>, url: impl Into<String>) -> Self
+    where
+        N: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut collected = Headers::new();
+        let mut header_order = Vec::new();
+        for (name, value) in headers {
+            collected.append(name.as_ref(), value.as_ref());
+            header_order.push(name.as_ref().to_string());
+        }
+        Self {
+            status,
+            headers: collected,
+            body: body.into(),
+            url: url.into(),
+            header_order,
+            ..Self::default()
+        }
+    }
+
+    /// The body, or an error when it was cut off by the body limit or a read error
+    pub fn full_body(self) -> Result<String> {
+        match self.truncation {
+            Some(truncation) => Err(anyhow::anyhow!(
+                "{} response body is incomplete ({} bytes read)",
+                self.url,
+                truncation.read_bytes
+            )),
+            None => Ok(self.body),
+        }
+    }
 }
 
 impl HttpClient {
     pub fn new() -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .pool_max_idle_per_host(10)
+        Self::with_settings(Duration::from_secs(10), None)
+    }
+
+    /// Client with a per-request timeout, optionally sending everything through `proxy`
+    /// (`http://`, `https://` or `socks5://` URL)
+    pub fn with_settings(timeout: Duration, proxy: Option<&str>) -> Result<Self> {
+        Self::with_address_family(timeout, proxy, AddressFamily::Any)
+    }
+
+    fn builder(timeout: Duration) -> reqwest::ClientBuilder {
+        let pool = pool::config();
+        Client::builder()
+            .timeout(timeout)
+            .pool_max_idle_per_host(pool.max_idle_per_host)
+            .pool_idle_timeout(pool.idle_timeout)
+            .dns_resolver(pool::resolver())
             .tcp_keepalive(Duration::from_secs(60))
-            .user_agent("WAF-Detector/1.0")
+            .user_agent(profiles::DEFAULT_USER_AGENT)
+            .redirect(reqwest::redirect::Policy::custom(|attempt| {
+                if in_single_request() {
+                    attempt.stop()
+                } else if attempt.previous().len() > MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else {
+                    attempt.follow()
+                }
+            }))
             .danger_accept_invalid_certs(true) // For testing purposes
-            .build()?;
-            
-        Ok(Self { client })
     }
-    
-    pub async fn get(&self, url: &str) -> Result<HttpResponse> {
-        let response = self.client.get(url).send().await?;
-        self.response_to_http_response(response, url).await
+
+    fn from_client(client: Client) -> Self {
+        Self { client, ..Self::default() }
     }
-    
-    pub async fn get_with_headers(&self, url: &str, headers: &[(& str, & str)]) -> Result<HttpResponse> {
-        let mut request = self.client.get(url);
+
+    /// Client that connects to `addr` for every request to `host`, whatever DNS says;
+    /// the Host header and TLS SNI still name `host`. Does not follow redirects, so a
+    /// redirect back to the edge is seen as such.
+    pub fn pinned_to(timeout: Duration, host: &str, addr: std::net::SocketAddr) -> Result<Self> {
+        let client = Self::builder(timeout).resolve(host, addr).redirect(reqwest::redirect::Policy::none()).build()?;
+        Ok(Self::from_client(client))
+    }
+
+    /// Like [`HttpClient::with_settings`], connecting only over the given IP version
+    pub fn with_address_family(timeout: Duration, proxy: Option<&str>, family: AddressFamily) -> Result<Self> {
+        let mut builder = Self::builder(timeout);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)
+                .map_err(|e| anyhow::anyhow!("Invalid proxy '{}': {}", proxy, e))?);
+        }
+        // Binding to an unspecified local address of one version makes the connector
+        // skip resolved addresses of the other
+        match family {
+            AddressFamily::Any => {}
+            AddressFamily::Ipv4 => builder = builder.local_address(IpAddr::from(Ipv4Addr::UNSPECIFIED)),
+            AddressFamily::Ipv6 => builder = builder.local_address(IpAddr::from(Ipv6Addr::UNSPECIFIED)),
+        }
+        Ok(Self::from_client(builder.build()?))
+    }
+
+    /// Total number of requests sent by this client and its clones
+    pub fn request_count(&self) -> usize {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    /// Total number of retries performed by this client and its clones
+    pub fn retry_count(&self) -> usize {
+        self.retry_count.load(Ordering::Relaxed)
+    }
+
+    /// Use a dedicated rate limiter instead of the process-wide one
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Keep the cookies targets set and send them back on later requests
+    pub fn with_cookie_session(mut self, session: Arc<CookieSession>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn session(&self) -> Option<&Arc<CookieSession>> {
+        self.session.as_ref()
+    }
+
+    /// Cap the body bytes read per response (default 2 MiB, no tail sample)
+    pub fn with_body_limit(mut self, body_limit: BodyLimit) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+
+    pub fn body_limit(&self) -> BodyLimit {
+        self.body_limit
+    }
+
+    fn rate_limiter(&self) -> &RateLimiter {
+        self.rate_limiter.as_deref().unwrap_or_else(|| ratelimit::global())
+    }
+
+    /// Wait for the target's rate limit and count the request
+    async fn before_request(&self, url: &str) -> Result<()> {
+        self.rate_limiter().acquire(url).await?;
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+        record_scoped_request();
+        pool::record_request(url);
+        Ok(())
+    }
+
+    /// Count a request answered from a replayed session; no rate limit applies
+    fn count_replayed(&self) {
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+        record_scoped_request();
+    }
+
+    /// Send a request, retrying transient failures according to the retry policy.
+    ///
+    /// Returns the response together with the number of retries that were needed.
+    async fn send_with_retry<F>(&self, url: &str, build: F) -> Result<(HttpResponse, u32)>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let tape = replay::current().zip(build().build().ok());
+        if let Some((tape, request)) = &tape {
+            if let Some(replayed) = tape.replay_http(request) {
+                self.count_replayed();
+                return replayed.map(|recorded| (recorded.response(), 0));
+            }
+        }
+
+        let result = match self.send_raw_with_retry(url, build).await {
+            Ok((response, retries)) => self.response_to_http_response(response, url).await.map(|response| (response, retries)),
+            Err(e) => Err(e),
+        };
+        if let Some((tape, request)) = &tape {
+            tape.record_http(request, result.as_ref().map(|(response, _)| RecordedResponse::from(response)));
+        }
+        result
+    }
+
+    async fn send_raw_with_retry<F>(&self, url: &str, build: F) -> Result<(Response, u32)>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let max_attempts = if in_single_request() { 1 } else { self.retry_policy.max_attempts.max(1) };
+        let mut retries = 0;
+
+        loop {
+            self.before_request(url).await?;
+            let last_attempt = retries + 1 >= max_attempts;
+
+            match build().send().await.inspect(|response| {
+                if let Some(session) = &self.session {
+                    session.store(url, response.headers());
+                }
+            }) {
+                Ok(response) if !last_attempt && self.retry_policy.should_retry_status(response.status().as_u16()) => {}
+                Ok(response) => return Ok((response, retries)),
+                Err(e) if !last_attempt && is_transient(&e) => {}
+                Err(e) => return Err(WafDetectorError::from_reqwest(url, &e).into()),
+            }
+
+            retries += 1;
+            self.retry_count.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(self.retry_policy.backoff(retries)).await;
+        }
+    }
+
+    /// A request carrying the next browser profile's headers (when impersonating),
+    /// then `headers`, which replace profile headers of the same name
+    fn request(&self, method: reqwest::Method, url: &str, profile: Option<&'static BrowserProfile>, headers: &[(&str, &str)]) -> RequestBuilder {
+        let mut request = self.client.request(method, url);
+        if let Some(profile) = profile {
+            for (name, value) in profile.headers_except(headers) {
+                request = request.header(name, value);
+            }
+        }
+        if let Some(session) = &self.session {
+            let cookie = session.cookie_header(url).filter(|_| !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("cookie")));
+            if let Some(cookie) = cookie {
+                request = request.header(reqwest::header::COOKIE, cookie);
+            }
+        }
         for (name, value) in headers {
             request = request.header(*name, *value);
         }
-        let response = request.send().await?;
-        self.response_to_http_response(response, url).await
+        request
+    }
+
+    pub async fn get(&self, url: &str) -> Result<HttpResponse> {
+        self.get_with_retry_count(url).await.map(|(response, _)| response)
+    }
+
+    /// GET request that also reports how many retries were needed
+    pub async fn get_with_retry_count(&self, url: &str) -> Result<(HttpResponse, u32)> {
+        let profile = profiles::select();
+        self.send_with_retry(url, || self.request(reqwest::Method::GET, url, profile, &[])).await
+    }
+    
+    /// GET returning the body as raw bytes (images and other binary assets); the
+    /// response's `body` holds the same bytes decoded lossily
+    pub async fn get_bytes(&self, url: &str) -> Result<(HttpResponse, Vec<u8>)> {
+        let profile = profiles::select();
+        let build = || self.request(reqwest::Method::GET, url, profile, &[]);
+        let tape = replay::current().zip(build().build().ok());
+        if let Some((tape, request)) = &tape {
+            if let Some(replayed) = tape.replay_http(request) {
+                self.count_replayed();
+                return replayed.map(|recorded| (recorded.response(), recorded.bytes()));
+            }
+        }
+
+        let result = match self.send_raw_with_retry(url, build).await {
+            Ok((response, _)) => {
+                let mut http_response = response_head(&response, url);
+                let body = read_body(response, self.body_limit).await;
+                http_response.body = String::from_utf8_lossy(&body.head).into_owned();
+                http_response.truncation = body.truncation;
+                Ok((http_response, body.head))
+            }
+            Err(e) => Err(e),
+        };
+        if let Some((tape, request)) = &tape {
+            tape.record_http(request, result.as_ref().map(|(response, bytes)| RecordedResponse::from(response).with_bytes(bytes)));
+        }
+        result
+    }
+
+    pub async fn get_with_headers(&self, url: &str, headers: &[(& str, & str)]) -> Result<HttpResponse> {
+        let profile = profiles::select();
+        let (response, _) = self.send_with_retry(url, || self.request(reqwest::Method::GET, url, profile, headers)).await?;
+        Ok(response)
     }
     
     pub async fn post(&self, url: &str, body: &str) -> Result<HttpResponse> {
-        let response = self.client
-            .post(url)
-            .body(body.to_string())
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .send()
-            .await?;
-        self.response_to_http_response(response, url).await
+        self.post_with_headers(url, body, &[("Content-Type", "application/x-www-form-urlencoded")]).await
+    }
+
+    pub async fn post_with_headers(&self, url: &str, body: &str, headers: &[(&str, &str)]) -> Result<HttpResponse> {
+        let profile = profiles::select();
+        let (response, _) = self.send_with_retry(url, || self.request(reqwest::Method::POST, url, profile, headers).body(body.to_string())).await?;
+        Ok(response)
     }
     
     pub async fn head(&self, url: &str) -> Result<HttpResponse> {
-        let response = self.client.head(url).send().await?;
-        self.response_to_http_response(response, url).await
+        let profile = profiles::select();
+        let (response, _) = self.send_with_retry(url, || self.request(reqwest::Method::HEAD, url, profile, &[])).await?;
+        Ok(response)
     }
     
     async fn response_to_http_response(&self, response: Response, url: &str) -> Result<HttpResponse> {
-        let status = response.status().as_u16();
-        
-        let mut headers = HashMap::new();
-        for (name, value) in response.headers() {
-            if let Ok(value_str) = value.to_str() {
-                headers.insert(name.to_string().to_lowercase(), value_str.to_string());
+        let mut http_response = response_head(&response, url);
+        let encoding = body_encoding(&http_response);
+        let body = read_body(response, self.body_limit).await;
+        http_response.body = encoding.decode(&body.head).0.into_owned();
+        if !body.tail.is_empty() {
+            http_response.body.push_str(TAIL_SEPARATOR);
+            http_response.body.push_str(&encoding.decode_without_bom_handling(&body.tail).0);
+        }
+        http_response.truncation = body.truncation;
+        Ok(http_response)
+    }
+}
+
+/// A body read within a [`BodyLimit`]
+struct LimitedBody {
+    head: Vec<u8>,
+    tail: Vec<u8>,
+    truncation: Option<BodyTruncation>,
+}
+
+/// Stream the body, keeping at most `limit.max_bytes` from its start and, when a tail is
+/// wanted, the last `limit.tail_bytes` read. A read error ends the body early and marks
+/// it truncated, keeping what was read.
+async fn read_body(mut response: Response, limit: BodyLimit) -> LimitedBody {
+    let content_length = response.content_length();
+    let max_read = (limit.max_bytes as u64).saturating_mul(TAIL_READ_FACTOR);
+    let mut head = Vec::new();
+    let mut tail = std::collections::VecDeque::new();
+    let mut read: u64 = 0;
+    let mut cut = false;
+
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(_) => {
+                cut = true;
+                break;
+            }
+        };
+        read += chunk.len() as u64;
+        let room = limit.max_bytes.saturating_sub(head.len());
+        head.extend_from_slice(&chunk[..room.min(chunk.len())]);
+        if chunk.len() > room {
+            cut = true;
+            if limit.tail_bytes == 0 {
+                break;
+            }
+            tail.extend(&chunk[room..]);
+            let excess = tail.len().saturating_sub(limit.tail_bytes);
+            tail.drain(..excess);
+            if read >= max_read {
+                break;
             }
         }
-        
-        let body = response.text().await.unwrap_or_default();
-        
-        Ok(HttpResponse {
-            status,
-            headers,
-            body,
-            url: url.to_string(),
+    }
+
+    let tail: Vec<u8> = tail.into();
+    let truncation = cut.then_some(BodyTruncation {
+        kept_bytes: head.len(),
+        read_bytes: read,
+        content_length,
+        tail_bytes: tail.len(),
+    });
+    LimitedBody { head, tail, truncation }
+}
+
+/// The Content-Type charset, UTF-8 when missing or unknown. A character split by the
+/// limit decodes to U+FFFD.
+fn body_encoding(response: &HttpResponse) -> &'static encoding_rs::Encoding {
+    response
+        .headers
+        .get("content-type")
+        .and_then(|content_type| {
+            content_type.split(';').find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim().eq_ignore_ascii_case("charset").then(|| value.trim().trim_matches('"'))
+            })
         })
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8)
+}
+
+/// Status, headers and connection details of a response whose body is not read yet
+fn response_head(response: &Response, url: &str) -> HttpResponse {
+    // HeaderMap keeps names in insertion order, which is the order on the wire;
+    // repeated headers follow their first occurrence
+    let header_order = response.headers().keys().map(|name| name.as_str().to_string()).collect();
+    let headers: Headers = response.headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+        .collect();
+
+    HttpResponse {
+        status: response.status().as_u16(),
+        headers,
+        body: String::new(),
+        url: url.to_string(),
+        header_order,
+        version: Some(format!("{:?}", response.version())),
+        remote_ip: response.remote_addr().map(|addr| addr.ip()),
+        truncation: None,
     }
 }
 
@@ -99,18 +755,203 @@ mod tests {
     
     #[test]
     fn test_http_response_structure() {
-        let mut headers = HashMap::new();
-        headers.insert("server".to_string(), "nginx".to_string());
+        let mut headers = Headers::new();
+        headers.insert("server", "nginx".to_string());
         
         let response = HttpResponse {
             status: 200,
             headers,
             body: "test body".to_string(),
             url: "https://example.com".to_string(),
+            ..Default::default()
         };
         
         assert_eq!(response.status, 200);
         assert_eq!(response.body, "test body");
         assert_eq!(response.headers.get("server"), Some(&"nginx".to_string()));
     }
-} 
\ No newline at end of file
+
+    fn fast_retry_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_retry_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+
+        let jittered = RetryPolicy { jitter: true, ..policy };
+        let delay = jittered.backoff(2);
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_retries_on_retryable_status() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("GET", "/")
+            .with_status(502)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = HttpClient::new().unwrap().with_retry_policy(fast_retry_policy(3));
+        let (response, retries) = client.get_with_retry_count(&server.url()).await.unwrap();
+
+        assert_eq!(response.status, 502);
+        assert_eq!(retries, 2);
+        assert_eq!(client.retry_count(), 2);
+        assert_eq!(client.request_count(), 3);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_block_responses_are_not_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("GET", "/")
+            .with_status(403)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = HttpClient::new().unwrap().with_retry_policy(fast_retry_policy(3));
+        let response = client.get(&server.url()).await.unwrap();
+
+        assert_eq!(response.status, 403);
+        assert_eq!(client.retry_count(), 0);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_retries_on_connection_error() {
+        // Bind and drop a listener to get a port nothing is listening on
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let client = HttpClient::new().unwrap().with_retry_policy(fast_retry_policy(2));
+
+        assert!(client.get(&format!("http://127.0.0.1:{}/", port)).await.is_err());
+        assert_eq!(client.retry_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_body_limit_truncates_and_samples_tail() {
+        let mut server = mockito::Server::new_async().await;
+        let body = format!("<html>{}<p>Incident ID: 42</p></html>", "x".repeat(10_000));
+        let _mock = server.mock("GET", "/").with_body(&body).create_async().await;
+        let _small = server.mock("GET", "/small").with_body("ok").create_async().await;
+
+        let client = HttpClient::new().unwrap().with_body_limit(BodyLimit { max_bytes: 1_000, tail_bytes: 0 });
+        let response = client.get(&server.url()).await.unwrap();
+        assert_eq!(response.body.len(), 1_000);
+        let truncation = response.truncation.unwrap();
+        assert_eq!((truncation.kept_bytes, truncation.content_length, truncation.tail_bytes), (1_000, Some(body.len() as u64), 0));
+        assert!(client.get(&format!("{}/small", server.url())).await.unwrap().truncation.is_none());
+
+        let client = client.with_body_limit(BodyLimit { max_bytes: 2_000, tail_bytes: 100 });
+        let response = client.get(&server.url()).await.unwrap();
+        let (head, tail) = response.body.split_once(TAIL_SEPARATOR).unwrap();
+        assert_eq!((head.len(), tail.len()), (2_000, 100));
+        assert!(tail.ends_with("<p>Incident ID: 42</p></html>"));
+        assert_eq!(response.truncation.unwrap().read_bytes, body.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_single_request_scope_stops_at_redirects() {
+        let mut server = mockito::Server::new_async().await;
+        let _redirect = server.mock("GET", "/").with_status(302).with_header("location", "/next").create_async().await;
+        let _next = server.mock("GET", "/next").with_status(200).create_async().await;
+
+        let client = HttpClient::new().unwrap();
+        assert_eq!(client.get(&server.url()).await.unwrap().status, 200);
+        assert_eq!(single_request(client.get(&server.url())).await.unwrap().status, 302);
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_body_is_read_whole() {
+        let mut server = mockito::Server::new_async().await;
+        let body = "x".repeat(DEFAULT_MAX_BODY_SIZE + 1_000);
+        let _mock = server.mock("GET", "/").with_body(&body).create_async().await;
+
+        let response = HttpClient::new().unwrap().get(&server.url()).await.unwrap();
+        assert!(response.full_body().is_err());
+        let client = HttpClient::new().unwrap().with_body_limit(BodyLimit::unlimited());
+        assert_eq!(client.get(&server.url()).await.unwrap().full_body().unwrap().len(), body.len());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_body_is_marked_truncated() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\npartial").await.unwrap();
+        });
+
+        let client = HttpClient::new().unwrap().with_retry_policy(RetryPolicy::none());
+        let response = client.get(&url).await.unwrap();
+        assert_eq!(response.body, "partial");
+        let truncation = response.truncation.clone().unwrap();
+        assert_eq!((truncation.read_bytes, truncation.content_length), (7, Some(1_000)));
+        assert!(response.full_body().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_body_charset_is_decoded() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server.mock("GET", "/")
+            .with_header("content-type", "text/html; charset=ISO-8859-1")
+            .with_body(b"Acc\xe8s refus\xe9")
+            .create_async()
+            .await;
+
+        let response = HttpClient::new().unwrap().get(&server.url()).await.unwrap();
+        assert_eq!(response.body, "Accès refusé");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_set_cookie_headers_are_kept() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server.mock("GET", "/")
+            .with_status(200)
+            .with_header("set-cookie", "a=1; Path=/")
+            .with_header("set-cookie", "b=2")
+            .create_async()
+            .await;
+
+        let client = HttpClient::new().unwrap();
+        let response = client.get(&server.url()).await.unwrap();
+
+        assert_eq!(response.headers.get_all("set-cookie").collect::<Vec<_>>(), ["a=1; Path=/", "b=2"]);
+        assert_eq!(crate::providers::set_cookie_names(&response), vec!["a", "b"]);
+        assert_eq!(response.version.as_deref(), Some("HTTP/1.1"));
+        assert!(response.remote_ip.is_some_and(|ip| ip.is_loopback()));
+    }
+
+    #[test]
+    fn test_headers_keep_order_and_repeats() {
+        let mut headers: Headers = [("Via", "1.1 varnish"), ("Server", "nginx"), ("via", "1.1 google")].into_iter().collect();
+        assert_eq!(headers.keys().collect::<Vec<_>>(), ["via", "server", "via"]);
+        assert_eq!(headers.get("VIA").unwrap(), "1.1 varnish");
+        assert_eq!(headers.get_all("via").collect::<Vec<_>>(), ["1.1 varnish", "1.1 google"]);
+        assert_eq!(headers.to_map()["via"], "1.1 varnish, 1.1 google");
+
+        headers.insert("via", "1.1 proxy");
+        assert_eq!(headers.get_all("via").count(), 1);
+        assert_eq!(headers.len(), 2);
+    }
+}
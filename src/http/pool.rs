@@ -0,0 +1,208 @@
+//! Connection pooling and DNS caching shared by every client in the process
+//!
+//! A batch scan sends many requests to each host (the initial GET, provider probes,
+//! payloads). Clients keep idle connections per host, and host names are resolved once
+//! and cached for [`PoolConfig::dns_ttl`]. Lookups only happen when a new connection is
+//! opened, so the resolver also counts connections, and [`stats`] reports how many
+//! requests were served over an already open one.
+
+use hyper014::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Connection pool and resolver cache settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Idle connections kept open per host
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept; scans move on from a host quickly
+    pub idle_timeout: Duration,
+    /// How long a resolved host is reused (zero: resolve on every new connection)
+    pub dns_ttl: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 10,
+            idle_timeout: Duration::from_secs(30),
+            dns_ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+fn config_lock() -> &'static RwLock<PoolConfig> {
+    static CONFIG: OnceLock<RwLock<PoolConfig>> = OnceLock::new();
+    CONFIG.get_or_init(|| RwLock::new(PoolConfig::default()))
+}
+
+/// Settings for clients built from now on (the DNS TTL applies at once)
+pub fn configure(config: PoolConfig) {
+    *config_lock().write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+pub fn config() -> PoolConfig {
+    *config_lock().read().unwrap_or_else(|e| e.into_inner())
+}
+
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+static CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+static DNS_LOOKUPS: AtomicU64 = AtomicU64::new(0);
+static DNS_CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+
+/// Requests and connections to named hosts since the process started. Targets given
+/// as IP addresses need no lookup and are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ConnectionStats {
+    pub requests: u64,
+    /// Connections opened; every other request reused a pooled one
+    pub connections: u64,
+    /// Lookups sent to the system resolver
+    pub dns_lookups: u64,
+    /// New connections whose address came from the cache
+    pub dns_cache_hits: u64,
+}
+
+impl ConnectionStats {
+    /// Share of requests sent over an already open connection
+    pub fn reuse_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.requests.saturating_sub(self.connections) as f64 / self.requests as f64
+    }
+
+    pub fn dns_hit_rate(&self) -> f64 {
+        if self.connections == 0 {
+            return 0.0;
+        }
+        self.dns_cache_hits as f64 / self.connections as f64
+    }
+
+    /// What happened between `earlier` and `self`
+    pub fn since(&self, earlier: &ConnectionStats) -> ConnectionStats {
+        ConnectionStats {
+            requests: self.requests.saturating_sub(earlier.requests),
+            connections: self.connections.saturating_sub(earlier.connections),
+            dns_lookups: self.dns_lookups.saturating_sub(earlier.dns_lookups),
+            dns_cache_hits: self.dns_cache_hits.saturating_sub(earlier.dns_cache_hits),
+        }
+    }
+}
+
+pub fn stats() -> ConnectionStats {
+    ConnectionStats {
+        requests: REQUESTS.load(Ordering::Relaxed),
+        connections: CONNECTIONS.load(Ordering::Relaxed),
+        dns_lookups: DNS_LOOKUPS.load(Ordering::Relaxed),
+        dns_cache_hits: DNS_CACHE_HITS.load(Ordering::Relaxed),
+    }
+}
+
+/// Count a request to `url` if it names its host
+pub(crate) fn record_request(url: &str) {
+    let named = url::Url::parse(url).is_ok_and(|url| matches!(url.host(), Some(url::Host::Domain(_))));
+    if named {
+        REQUESTS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Host name to when it was resolved and its addresses
+type HostCache = HashMap<String, (Instant, Vec<SocketAddr>)>;
+
+/// Resolver with a TTL cache, shared by every [`HttpClient`](super::HttpClient)
+#[derive(Debug, Clone, Default)]
+pub struct CachingResolver {
+    cache: Arc<Mutex<HostCache>>,
+}
+
+impl CachingResolver {
+    fn cached(&self, host: &str, ttl: Duration) -> Option<Vec<SocketAddr>> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(host).filter(|(resolved, _)| resolved.elapsed() < ttl).map(|(_, addrs)| addrs.clone())
+    }
+
+    /// Addresses of `host`, from the cache when they are younger than the TTL
+    pub async fn lookup(&self, host: &str) -> std::io::Result<Vec<SocketAddr>> {
+        let ttl = config().dns_ttl;
+        if let Some(addrs) = self.cached(host, ttl) {
+            DNS_CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+            return Ok(addrs);
+        }
+        DNS_LOOKUPS.fetch_add(1, Ordering::Relaxed);
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+        if !ttl.is_zero() && !addrs.is_empty() {
+            self.cache.lock().unwrap_or_else(|e| e.into_inner()).insert(host.to_string(), (Instant::now(), addrs.clone()));
+        }
+        Ok(addrs)
+    }
+
+    /// Forget every cached host
+    pub fn clear(&self) {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The process-wide resolver
+pub fn resolver() -> Arc<CachingResolver> {
+    static RESOLVER: OnceLock<Arc<CachingResolver>> = OnceLock::new();
+    RESOLVER.get_or_init(|| Arc::new(CachingResolver::default())).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates() {
+        let stats = ConnectionStats { requests: 40, connections: 10, dns_lookups: 2, dns_cache_hits: 8 };
+        assert_eq!(stats.reuse_rate(), 0.75);
+        assert_eq!(stats.dns_hit_rate(), 0.8);
+        assert_eq!(ConnectionStats::default().reuse_rate(), 0.0);
+
+        let later = ConnectionStats { requests: 50, connections: 11, dns_lookups: 2, dns_cache_hits: 9 };
+        assert_eq!(later.since(&stats), ConnectionStats { requests: 10, connections: 1, dns_lookups: 0, dns_cache_hits: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_lookups_are_cached() {
+        let resolver = CachingResolver::default();
+        let before = stats();
+        let first = resolver.lookup("localhost").await.unwrap();
+        assert_eq!(resolver.lookup("localhost").await.unwrap(), first);
+        let delta = stats().since(&before);
+        assert!(delta.dns_cache_hits >= 1);
+        assert!(resolver.cached("localhost", Duration::ZERO).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_clients_resolve_through_the_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server.mock("GET", "/").expect(2).create_async().await;
+        let url = format!("http://localhost:{}/", server.socket_address().port());
+
+        let before = stats();
+        let client = super::super::HttpClient::new().unwrap();
+        client.get(&url).await.unwrap();
+        client.get(&url).await.unwrap();
+        let delta = stats().since(&before);
+        assert!(delta.requests >= 2 && delta.connections >= 1, "{:?}", delta);
+        assert!(resolver().cached("localhost", config().dns_ttl).is_some());
+    }
+}
@@ -0,0 +1,108 @@
+//! Cookie sessions shared by the requests of a scan (`--session`)
+//!
+//! Without a session every probe looks like a new client. Some WAFs set a state cookie
+//! on the first visit and treat clients that never return it differently, so a
+//! session keeps one cookie jar per host: whatever the target sets is sent back on
+//! later requests to it.
+
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Cookie jars per host
+#[derive(Debug, Default)]
+pub struct CookieSession {
+    jars: RwLock<HashMap<String, Arc<Jar>>>,
+}
+
+impl CookieSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn jar(&self, url: &url::Url) -> Option<Arc<Jar>> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        if let Some(jar) = self.jars.read().unwrap_or_else(|e| e.into_inner()).get(&host) {
+            return Some(jar.clone());
+        }
+        Some(self.jars.write().unwrap_or_else(|e| e.into_inner()).entry(host).or_default().clone())
+    }
+
+    /// Start over with no cookies for the host of `url`
+    pub fn reset(&self, url: &str) {
+        if let Some(host) = url::Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_ascii_lowercase)) {
+            self.jars.write().unwrap_or_else(|e| e.into_inner()).remove(&host);
+        }
+    }
+
+    /// `Cookie` header value for a request to `url`
+    pub fn cookie_header(&self, url: &str) -> Option<String> {
+        let url = url::Url::parse(url).ok()?;
+        let value = self.jar(&url)?.cookies(&url)?;
+        value.to_str().ok().map(str::to_string)
+    }
+
+    /// Keep the cookies a response from `url` set
+    pub fn store(&self, url: &str, headers: &HeaderMap) {
+        let Ok(url) = url::Url::parse(url) else { return };
+        if let Some(jar) = self.jar(&url) {
+            jar.set_cookies(&mut headers.get_all(reqwest::header::SET_COOKIE).iter(), &url);
+        }
+    }
+
+    /// Names of the cookies held for `url`, sorted
+    pub fn cookie_names(&self, url: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .cookie_header(url)
+            .map(|header| header.split(';').filter_map(|pair| pair.split('=').next()).map(|name| name.trim().to_string()).collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+}
+
+/// The target answering a first-time client vs. one returning its cookies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionComparison {
+    /// Cookies the target set during the scan
+    pub cookies: Vec<String>,
+    /// Status of the initial request, sent without cookies
+    pub stateless_status: u16,
+    /// Status of a repeat request carrying the session's cookies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stateful_status: Option<u16>,
+    pub stateless_challenge: bool,
+    pub stateful_challenge: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SessionComparison {
+    /// The target treats returning clients differently
+    pub fn differs(&self) -> bool {
+        self.stateful_status.is_some_and(|status| status != self.stateless_status) || self.stateless_challenge != self.stateful_challenge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_session_returns_cookies_per_host() {
+        let session = CookieSession::new();
+        let mut headers = HeaderMap::new();
+        headers.append(reqwest::header::SET_COOKIE, HeaderValue::from_static("__cf_bm=abc; Path=/; HttpOnly"));
+        headers.append(reqwest::header::SET_COOKIE, HeaderValue::from_static("visitor=1"));
+        session.store("https://example.com/", &headers);
+
+        assert_eq!(session.cookie_names("https://example.com/login"), vec!["__cf_bm", "visitor"]);
+        assert_eq!(session.cookie_header("https://other.example/"), None);
+
+        session.reset("https://EXAMPLE.com/");
+        assert!(session.cookie_names("https://example.com/").is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! Internationalized domain names
+//!
+//! Targets can be given as `münchen.de` or as `xn--mnchen-3ya.de`. Both are turned
+//! into the same ASCII (punycode) form before anything is sent or resolved, and the
+//! result records both spellings.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Both spellings of an internationalized host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdnHost {
+    /// Human-readable form, e.g. `münchen.de`
+    pub unicode: String,
+    /// Punycode form used on the wire and in DNS, e.g. `xn--mnchen-3ya.de`
+    pub ascii: String,
+}
+
+impl IdnHost {
+    /// The host of `url` if it is internationalized, `None` for plain ASCII hosts and IPs
+    pub fn from_url(url: &str) -> Option<Self> {
+        let parsed = Url::parse(url).ok()?;
+        match parsed.host()? {
+            url::Host::Domain(host) => Self::from_host(host),
+            _ => None,
+        }
+    }
+
+    fn from_host(host: &str) -> Option<Self> {
+        let ascii = to_ascii(host).ok()?;
+        let unicode = to_unicode(&ascii);
+        (unicode != ascii).then_some(Self { unicode, ascii })
+    }
+}
+
+/// UTS #46 mapping to ASCII: lowercases, maps full-width dots and encodes non-ASCII
+/// labels as punycode. A trailing root dot is dropped.
+pub fn to_ascii(host: &str) -> Result<String> {
+    let host = host.trim().trim_end_matches('.');
+    idna::domain_to_ascii(host).map_err(|_| anyhow!("Invalid internationalized domain name: {}", host))
+}
+
+/// Unicode form of `host`; labels that fail to decode are kept as given
+pub fn to_unicode(host: &str) -> String {
+    idna::domain_to_unicode(host).0
+}
+
+/// Turn a domain or URL into an http(s) URL whose host is in ASCII form.
+///
+/// Bare domains get `https://`. `example.com:8443` is a domain with a port, not a
+/// URL with scheme `example.com`.
+pub fn normalize_url(input: &str) -> Result<String> {
+    let input = input.trim();
+    if let Ok(url) = Url::parse(input) {
+        if matches!(url.scheme(), "http" | "https") && url.has_host() {
+            return Ok(url.to_string());
+        }
+    }
+
+    if input.contains("://") {
+        return Err(anyhow!("Invalid URL: {}", input));
+    }
+    let with_https = format!("https://{}", input);
+    match Url::parse(&with_https) {
+        Ok(url) if url.has_host() => Ok(url.to_string()),
+        Ok(_) => Err(anyhow!("Invalid URL or domain: {}", input)),
+        Err(e) => Err(anyhow!("Invalid URL or domain '{}': {}", input, e)),
+    }
+}
+
+/// `url` with its host in ASCII form; unchanged when it already is (or does not parse)
+pub fn ascii_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) if parsed.host_str().is_some_and(|host| !url.contains(host)) => parsed.to_string(),
+        _ => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_and_punycode_normalize_alike() {
+        let unicode = normalize_url("münchen.de").unwrap();
+        let punycode = normalize_url("xn--mnchen-3ya.de").unwrap();
+        assert_eq!(unicode, "https://xn--mnchen-3ya.de/");
+        assert_eq!(unicode, punycode);
+        assert_eq!(normalize_url("https://MÜNCHEN.de/straße").unwrap(), "https://xn--mnchen-3ya.de/stra%C3%9Fe");
+        // Ideographic full stop used as a label separator
+        assert_eq!(normalize_url("例え。テスト").unwrap(), "https://xn--r8jz45g.xn--zckzah/");
+    }
+
+    #[test]
+    fn test_host_with_port_is_not_a_scheme() {
+        assert_eq!(normalize_url("example.com:8443").unwrap(), "https://example.com:8443/");
+        assert_eq!(normalize_url("http://example.com").unwrap(), "http://example.com/");
+        assert!(normalize_url("https://exa mple.com").is_err());
+    }
+
+    #[test]
+    fn test_idn_host_records_both_forms() {
+        let host = IdnHost::from_url("https://xn--mnchen-3ya.de/").unwrap();
+        assert_eq!(host.unicode, "münchen.de");
+        assert_eq!(host.ascii, "xn--mnchen-3ya.de");
+        assert_eq!(IdnHost::from_url("https://example.com/"), None);
+        assert_eq!(IdnHost::from_url("https://192.0.2.1/"), None);
+        assert_eq!(to_ascii("Bücher.example.").unwrap(), "xn--bcher-kva.example");
+    }
+
+    #[test]
+    fn test_ascii_url_only_rewrites_unicode_hosts() {
+        assert_eq!(ascii_url("https://example.com"), "https://example.com");
+        assert_eq!(ascii_url("https://münchen.de/a"), "https://xn--mnchen-3ya.de/a");
+    }
+}
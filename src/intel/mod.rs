@@ -0,0 +1,537 @@
+//! Internet-scan enrichment from Shodan and Censys (`--intel`, `intel` feature)
+//!
+//! Search engines that scan the whole address space have usually seen the target's
+//! addresses already: open ports, service banners, the network owner and (Shodan) the
+//! WAF in front of each HTTP service. For the addresses the host resolves to, these
+//! records corroborate the detected edge. Addresses the engines saw serving a
+//! certificate for the domain, outside the edge, become origin candidates.
+//!
+//! Lookups need API keys and go to the services, never to the target.
+
+use crate::{Evidence, MethodType};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+pub const SHODAN_URL: &str = "https://api.shodan.io";
+pub const CENSYS_URL: &str = "https://search.censys.io";
+
+/// Most resolved addresses looked up per scan
+pub const MAX_ADDRESSES: usize = 4;
+
+/// Longest banner kept per service
+const BANNER_LIMIT: usize = 200;
+
+/// A service seen on an address
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceBanner {
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    /// Start of the banner the scanner recorded
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub banner: String,
+    /// WAF the scanner attributed the service to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf: Option<String>,
+}
+
+/// What one service knows about an address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostIntel {
+    pub ip: IpAddr,
+    pub source: String,
+    pub ports: Vec<u16>,
+    pub services: Vec<ServiceBanner>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Network owner (organization or autonomous system name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+}
+
+/// An internet-scan search backend
+#[async_trait::async_trait]
+pub trait IntelSource: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &'static str;
+
+    /// The service's record of `ip`, if it has one
+    async fn host(&self, ip: IpAddr) -> Result<Option<HostIntel>>;
+
+    /// Addresses seen serving a TLS certificate for `domain`
+    async fn certificate_hosts(&self, domain: &str) -> Result<Vec<IpAddr>>;
+}
+
+/// Shodan host lookups and certificate searches
+#[cfg(feature = "intel")]
+#[derive(Debug, Clone)]
+pub struct Shodan {
+    client: crate::http::HttpClient,
+    api_key: String,
+    base_url: String,
+}
+
+#[cfg(feature = "intel")]
+impl Shodan {
+    pub fn new(api_key: &str) -> Result<Self> {
+        Ok(Self { client: crate::http::HttpClient::new()?.with_body_limit(crate::http::BodyLimit::unlimited()), api_key: api_key.to_string(), base_url: SHODAN_URL.to_string() })
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[cfg(feature = "intel")]
+#[async_trait::async_trait]
+impl IntelSource for Shodan {
+    fn name(&self) -> &'static str {
+        "shodan"
+    }
+
+    async fn host(&self, ip: IpAddr) -> Result<Option<HostIntel>> {
+        let url = format!("{}/shodan/host/{}?key={}", self.base_url, ip, urlencoding::encode(&self.api_key));
+        match fetch(&self.client, &url, &[]).await? {
+            Some(body) => parse_shodan_host(&body).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn certificate_hosts(&self, domain: &str) -> Result<Vec<IpAddr>> {
+        let query = format!("ssl.cert.subject.cn:\"{}\"", domain);
+        let url = format!("{}/shodan/host/search?key={}&query={}", self.base_url, urlencoding::encode(&self.api_key), urlencoding::encode(&query));
+        match fetch(&self.client, &url, &[]).await? {
+            Some(body) => parse_shodan_search(&body),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Censys Search (v2) host lookups and certificate searches
+#[cfg(feature = "intel")]
+#[derive(Debug, Clone)]
+pub struct Censys {
+    client: crate::http::HttpClient,
+    /// `api_id:secret`
+    credentials: String,
+    base_url: String,
+}
+
+#[cfg(feature = "intel")]
+impl Censys {
+    pub fn new(credentials: &str) -> Result<Self> {
+        Ok(Self { client: crate::http::HttpClient::new()?.with_body_limit(crate::http::BodyLimit::unlimited()), credentials: credentials.to_string(), base_url: CENSYS_URL.to_string() })
+    }
+
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn authorization(&self) -> String {
+        format!("Basic {}", crate::utils::base64_encode(self.credentials.as_bytes()))
+    }
+}
+
+#[cfg(feature = "intel")]
+#[async_trait::async_trait]
+impl IntelSource for Censys {
+    fn name(&self) -> &'static str {
+        "censys"
+    }
+
+    async fn host(&self, ip: IpAddr) -> Result<Option<HostIntel>> {
+        let url = format!("{}/api/v2/hosts/{}", self.base_url, ip);
+        match fetch(&self.client, &url, &[("Authorization", self.authorization().as_str())]).await? {
+            Some(body) => parse_censys_host(&body).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn certificate_hosts(&self, domain: &str) -> Result<Vec<IpAddr>> {
+        let query = format!("services.tls.certificates.leaf_data.names: {}", domain);
+        let url = format!("{}/api/v2/hosts/search?q={}&per_page=25", self.base_url, urlencoding::encode(&query));
+        match fetch(&self.client, &url, &[("Authorization", self.authorization().as_str())]).await? {
+            Some(body) => parse_censys_search(&body),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Body of a 200 answer; `None` when the service has no record
+#[cfg(feature = "intel")]
+async fn fetch(client: &crate::http::HttpClient, url: &str, headers: &[(&str, &str)]) -> Result<Option<String>> {
+    let response = client.get_with_headers(url, headers).await?;
+    match response.status {
+        200 => response.full_body().map(Some),
+        404 => Ok(None),
+        status => anyhow::bail!("internet-scan lookup returned HTTP {}", status),
+    }
+}
+
+/// A source from `--intel NAME[:KEY]`; without a key it is read from the environment
+/// (`SHODAN_API_KEY`, `CENSYS_CREDENTIALS` as `API_ID:SECRET`)
+#[cfg(feature = "intel")]
+pub fn source_from_spec(spec: &str) -> Result<Arc<dyn IntelSource>> {
+    let (name, key) = match spec.split_once(':') {
+        Some((name, key)) => (name.trim().to_ascii_lowercase(), Some(key.to_string())),
+        None => (spec.trim().to_ascii_lowercase(), None),
+    };
+    let variable = match name.as_str() {
+        "shodan" => "SHODAN_API_KEY",
+        "censys" => "CENSYS_CREDENTIALS",
+        _ => anyhow::bail!("unknown internet-scan source '{}' (expected shodan or censys)", name),
+    };
+    let key = key
+        .or_else(|| std::env::var(variable).ok())
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("internet-scan source '{}' needs a key: --intel {}:KEY or {}", name, name, variable))?;
+    Ok(match name.as_str() {
+        "shodan" => Arc::new(Shodan::new(&key)?),
+        _ => Arc::new(Censys::new(&key)?),
+    })
+}
+
+#[cfg(not(feature = "intel"))]
+pub fn source_from_spec(_spec: &str) -> Result<Arc<dyn IntelSource>> {
+    Err(anyhow::anyhow!("built without the `intel` feature"))
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanHost {
+    ip_str: String,
+    #[serde(default)]
+    ports: Vec<u16>,
+    #[serde(default)]
+    tags: Vec<String>,
+    org: Option<String>,
+    #[serde(default)]
+    data: Vec<ShodanService>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanService {
+    port: u16,
+    product: Option<String>,
+    #[serde(default)]
+    data: String,
+    http: Option<ShodanHttp>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanHttp {
+    waf: Option<String>,
+}
+
+/// A Shodan `/shodan/host/{ip}` response
+pub fn parse_shodan_host(body: &str) -> Result<HostIntel> {
+    let host: ShodanHost = serde_json::from_str(body)?;
+    Ok(HostIntel {
+        ip: host.ip_str.parse()?,
+        source: "shodan".to_string(),
+        ports: host.ports,
+        services: host
+            .data
+            .into_iter()
+            .map(|service| ServiceBanner {
+                port: service.port,
+                product: service.product,
+                banner: truncate(&service.data),
+                waf: service.http.and_then(|http| http.waf),
+            })
+            .collect(),
+        tags: host.tags,
+        org: host.org,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanSearch {
+    #[serde(default)]
+    matches: Vec<ShodanMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShodanMatch {
+    ip_str: String,
+}
+
+/// Addresses in a Shodan `/shodan/host/search` response
+pub fn parse_shodan_search(body: &str) -> Result<Vec<IpAddr>> {
+    let search: ShodanSearch = serde_json::from_str(body)?;
+    Ok(unique(search.matches.into_iter().filter_map(|hit| hit.ip_str.parse().ok())))
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysEnvelope<T> {
+    result: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysHost {
+    ip: String,
+    #[serde(default)]
+    services: Vec<CensysService>,
+    #[serde(default)]
+    labels: Vec<String>,
+    autonomous_system: Option<CensysAutonomousSystem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysService {
+    port: u16,
+    #[serde(default)]
+    software: Vec<CensysSoftware>,
+    #[serde(default)]
+    banner: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysSoftware {
+    product: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysAutonomousSystem {
+    name: Option<String>,
+}
+
+/// A Censys `/api/v2/hosts/{ip}` response
+pub fn parse_censys_host(body: &str) -> Result<HostIntel> {
+    let host: CensysEnvelope<CensysHost> = serde_json::from_str(body)?;
+    let host = host.result;
+    let mut ports: Vec<u16> = host.services.iter().map(|service| service.port).collect();
+    ports.sort_unstable();
+    ports.dedup();
+    Ok(HostIntel {
+        ip: host.ip.parse()?,
+        source: "censys".to_string(),
+        ports,
+        services: host
+            .services
+            .into_iter()
+            .map(|service| ServiceBanner {
+                port: service.port,
+                product: service.software.into_iter().find_map(|software| software.product),
+                banner: truncate(&service.banner),
+                waf: None,
+            })
+            .collect(),
+        tags: host.labels,
+        org: host.autonomous_system.and_then(|system| system.name),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysSearch {
+    #[serde(default)]
+    hits: Vec<CensysHit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CensysHit {
+    ip: String,
+}
+
+/// Addresses in a Censys `/api/v2/hosts/search` response
+pub fn parse_censys_search(body: &str) -> Result<Vec<IpAddr>> {
+    let search: CensysEnvelope<CensysSearch> = serde_json::from_str(body)?;
+    Ok(unique(search.result.hits.into_iter().filter_map(|hit| hit.ip.parse().ok())))
+}
+
+fn truncate(banner: &str) -> String {
+    banner.trim().chars().take(BANNER_LIMIT).collect()
+}
+
+fn unique(addresses: impl Iterator<Item = IpAddr>) -> Vec<IpAddr> {
+    let mut unique = Vec::new();
+    for ip in addresses {
+        if !unique.contains(&ip) {
+            unique.push(ip);
+        }
+    }
+    unique
+}
+
+/// Lowercase markers of edge vendors in WAF names, products and network owners
+const VENDOR_MARKERS: &[(&str, &str)] = &[
+    ("cloudflare", "CloudFlare"),
+    ("akamai", "Akamai"),
+    ("cloudfront", "AWS"),
+    ("amazon", "AWS"),
+    ("fastly", "Fastly"),
+    ("incapsula", "Imperva"),
+    ("imperva", "Imperva"),
+    ("sucuri", "Sucuri"),
+    ("big-ip", "F5 BIG-IP"),
+    ("microsoft", "Azure"),
+    ("azure", "Azure"),
+    ("vercel", "Vercel"),
+    ("netlify", "Netlify"),
+    ("fly.io", "Fly.io"),
+    ("bunnyway", "BunnyCDN"),
+    ("stackpath", "StackPath"),
+    ("highwinds", "StackPath"),
+    ("cdn77", "CDN77"),
+    ("cachefly", "Cachefly"),
+    ("alibaba", "AlibabaCloud"),
+    ("aliyun", "AlibabaCloud"),
+    ("tencent", "TencentCloud"),
+    ("huawei", "HuaweiCloud"),
+    ("netscaler", "NetScaler"),
+    ("fortiweb", "FortiWeb"),
+    ("barracuda", "Barracuda"),
+];
+
+fn vendor(text: &str) -> Option<&'static str> {
+    let text = text.to_lowercase();
+    VENDOR_MARKERS.iter().find(|(marker, _)| text.contains(marker)).map(|(_, vendor)| *vendor)
+}
+
+/// Corroborating evidence from the records of the host's addresses: WAFs the scanners
+/// saw in front of its services, and edge vendors owning the network or the software
+pub fn evidence(records: &[HostIntel]) -> Vec<Evidence> {
+    let mut evidence: Vec<Evidence> = Vec::new();
+    let mut push = |item: Evidence| {
+        if !evidence.iter().any(|known| known.signature_matched == item.signature_matched) {
+            evidence.push(item);
+        }
+    };
+    for record in records {
+        for service in &record.services {
+            if let Some(waf) = &service.waf {
+                let name = vendor(waf).unwrap_or(waf.as_str());
+                push(Evidence {
+                    method_type: MethodType::HostIntel(record.source.clone()),
+                    confidence: 0.6,
+                    description: format!("{} saw {} in front of {}:{}", record.source, waf, record.ip, service.port),
+                    raw_data: service.banner.clone(),
+                    signature_matched: format!("intel-waf-{}", name.to_lowercase().replace(' ', "-")),
+                });
+            }
+            if let Some((product, name)) = service.product.as_deref().and_then(|product| Some((product, vendor(product)?))) {
+                push(Evidence {
+                    method_type: MethodType::HostIntel(record.source.clone()),
+                    confidence: 0.5,
+                    description: format!("{} identified {}:{} as {} - {} edge", record.source, record.ip, service.port, product, name),
+                    raw_data: service.banner.clone(),
+                    signature_matched: format!("intel-product-{}", name.to_lowercase().replace(' ', "-")),
+                });
+            }
+        }
+        if let Some((org, name)) = record.org.as_deref().and_then(|org| Some((org, vendor(org)?))) {
+            push(Evidence {
+                method_type: MethodType::HostIntel(record.source.clone()),
+                confidence: 0.4,
+                description: format!("{} belongs to {} per {} - {} network", record.ip, org, record.source, name),
+                raw_data: format!("{}: ports {:?}, tags {:?}", record.ip, record.ports, record.tags),
+                signature_matched: format!("intel-network-{}", name.to_lowercase().replace(' ', "-")),
+            });
+        }
+    }
+    evidence
+}
+
+/// Addresses serving certificates for `domain` in any source, leaving out `edge`, with
+/// the source that reported them
+pub async fn certificate_hosts(sources: &[Arc<dyn IntelSource>], domain: &str, edge: &[IpAddr]) -> Vec<(IpAddr, &'static str)> {
+    let searches = sources.iter().map(|source| async move { (source.name(), source.certificate_hosts(domain).await) });
+    let mut hosts: Vec<(IpAddr, &'static str)> = Vec::new();
+    for (name, outcome) in futures::future::join_all(searches).await {
+        match outcome {
+            Ok(addresses) => {
+                for ip in addresses {
+                    if !edge.contains(&ip) && !hosts.iter().any(|(known, _)| *known == ip) {
+                        hosts.push((ip, name));
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(domain, source = name, error = %e, "internet-scan certificate search failed"),
+        }
+    }
+    hosts
+}
+
+/// Looks the host's addresses up for detection
+#[derive(Debug, Clone, Default)]
+pub struct IntelAnalyzer {
+    sources: Vec<Arc<dyn IntelSource>>,
+}
+
+impl IntelAnalyzer {
+    pub fn new(sources: Vec<Arc<dyn IntelSource>>) -> Self {
+        Self { sources }
+    }
+
+    pub fn sources(&self) -> &[Arc<dyn IntelSource>] {
+        &self.sources
+    }
+
+    pub async fn analyze(&self, url: &str) -> Result<Vec<Evidence>> {
+        if self.sources.is_empty() {
+            anyhow::bail!("no Shodan or Censys key configured (--intel)");
+        }
+        let host = url::Url::parse(url)?.host_str().map(str::to_lowercase).ok_or_else(|| anyhow::anyhow!("URL '{}' has no host", url))?;
+        let addresses = crate::dns::resolve_addresses(&host).await?;
+        let addresses: Vec<IpAddr> = addresses.ipv4.into_iter().chain(addresses.ipv6).take(MAX_ADDRESSES).collect();
+
+        let lookups = self.sources.iter().flat_map(|source| addresses.iter().map(move |ip| source.host(*ip)));
+        let mut records = Vec::new();
+        let mut first_error = None;
+        for outcome in futures::future::join_all(lookups).await {
+            match outcome {
+                Ok(record) => records.extend(record),
+                Err(e) => {
+                    tracing::warn!(host = %host, error = %e, "internet-scan lookup failed");
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if records.is_empty() => Err(e),
+            _ => Ok(evidence(&records)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHODAN_HOST: &str = r#"{
+        "ip_str": "104.16.1.1", "ports": [80, 443], "tags": ["cdn"], "org": "Cloudflare, Inc.",
+        "data": [
+            {"port": 443, "product": "CloudFlare", "data": "HTTP/1.1 403 Forbidden\r\nServer: cloudflare\r\n", "http": {"waf": "Cloudflare"}},
+            {"port": 80, "data": "HTTP/1.1 301 Moved Permanently\r\n", "http": {"waf": null}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_shodan_host_evidence() {
+        let record = parse_shodan_host(SHODAN_HOST).unwrap();
+        assert_eq!(record.ports, vec![80, 443]);
+        assert_eq!(record.services[0].waf.as_deref(), Some("Cloudflare"));
+
+        let evidence = evidence(&[record.clone(), record]);
+        let signatures: Vec<&str> = evidence.iter().map(|e| e.signature_matched.as_str()).collect();
+        assert_eq!(signatures, vec!["intel-waf-cloudflare", "intel-product-cloudflare", "intel-network-cloudflare"]);
+        assert_eq!(evidence[0].description, "shodan saw Cloudflare in front of 104.16.1.1:443");
+    }
+
+    #[test]
+    fn test_censys_responses() {
+        let host = r#"{"code": 200, "result": {"ip": "2001:db8::10", "labels": ["waf"],
+            "autonomous_system": {"name": "AKAMAI-AS"},
+            "services": [{"port": 443, "service_name": "HTTP", "software": [{"vendor": "Akamai", "product": "AkamaiGHost"}], "banner": "HTTP/1.1 400"}]}}"#;
+        let record = parse_censys_host(host).unwrap();
+        assert_eq!((record.ports.clone(), record.org.as_deref()), (vec![443], Some("AKAMAI-AS")));
+        let signatures: Vec<String> = evidence(&[record]).into_iter().map(|e| e.signature_matched).collect();
+        assert_eq!(signatures, vec!["intel-product-akamai", "intel-network-akamai"]);
+
+        let search = r#"{"result": {"hits": [{"ip": "203.0.113.7"}, {"ip": "203.0.113.7"}, {"ip": "198.51.100.20"}]}}"#;
+        assert_eq!(parse_censys_search(search).unwrap().len(), 2);
+        assert_eq!(parse_shodan_search(r#"{"matches": [{"ip_str": "203.0.113.7"}], "total": 1}"#).unwrap().len(), 1);
+    }
+}
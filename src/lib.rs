@@ -1,22 +1,68 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use chrono::{DateTime, Utc};
 
 pub mod engine;
+pub mod error;
 pub mod providers;
 pub mod confidence;
 pub mod http;
 pub mod registry;
+#[cfg(feature = "cli")]
 pub mod cli;
 pub mod utils;
+#[cfg(feature = "web")]
 pub mod web;
+#[cfg(feature = "payload")]
 pub mod script_executor;
 
 // NEW: Advanced confidence and validation modules
 pub mod testing;
+#[cfg(feature = "timing")]
 pub mod timing;
 pub mod dns;
+#[cfg(feature = "tls")]
+pub mod protocol;
+pub mod header_order;
+pub mod blockpage;
+pub mod favicon;
+pub mod cache;
+pub mod edge;
+#[cfg(feature = "web")]
+pub mod agents;
+pub mod logging;
+#[cfg(feature = "payload")]
 pub mod payload;
+pub mod ratelimit;
+pub mod preflight;
+pub mod fingerprint;
+pub mod challenge;
+pub mod enumeration;
+#[cfg(feature = "payload")]
+pub mod paths;
+pub mod ratelimit_probe;
+pub mod detector;
+pub mod capture;
+pub mod recon;
+pub mod policy;
+pub mod render;
+pub mod idn;
+pub mod targets;
+pub mod evidence;
+pub mod explain;
+pub mod consensus;
+pub mod profiles;
+pub mod browser;
+pub mod origin;
+pub mod ct;
+pub mod intel;
+pub mod exporters;
+pub mod signatures;
+pub mod replay;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+pub use detector::{Detector, DetectorBuilder};
 
 #[derive(Debug, Clone)]
 pub struct DetectionContext {
@@ -26,6 +72,196 @@ pub struct DetectionContext {
     pub user_agent: String,
 }
 
+/// Per-scan selection of the detection techniques that are run.
+///
+/// The defaults are deliberately safe: header analysis of the initial response plus
+/// DNS lookups. Techniques that send additional, attack-like traffic to the target
+/// (timing probes, payload probing, provider active probes) are opt-in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionOptions {
+    /// Resolve CNAME records and match them against provider patterns
+    pub dns: bool,
+    /// Measure response-time differences between normal and suspicious requests
+    pub timing: bool,
+    /// Send XSS/SQLi/etc. payloads and analyze how they are blocked
+    pub payload: bool,
+    /// Run each provider's `active_detect` probes
+    pub active_probes: bool,
+    /// Fetch robots.txt and security.txt before scanning and record them in the metadata
+    #[serde(default)]
+    pub preflight: bool,
+    /// Refuse timing/payload/active probing when robots.txt disallows the scanned path
+    /// (implies `preflight`)
+    #[serde(default)]
+    pub respect_robots: bool,
+    /// Probe the detected WAF for its ruleset generation, paranoia level and rule IDs
+    #[serde(default)]
+    pub fingerprint: bool,
+    /// Send attack probes to the detected WAF and report whether it blocks or only
+    /// monitors each payload category
+    #[serde(default)]
+    pub mode_analysis: bool,
+    /// Ramp up the request rate to profile the target's rate limiting (threshold,
+    /// ban duration, per-IP vs per-session)
+    #[serde(default)]
+    pub ratelimit_probe: bool,
+    /// Check which ALPN protocols the TLS endpoint accepts and whether it answers QUIC
+    #[serde(default)]
+    pub protocol: bool,
+    /// Fingerprint the order and casing of the response headers (one raw HTTP/1.1 request)
+    #[serde(default)]
+    pub header_order: bool,
+    /// Compare the response body with the bundled corpus of vendor block pages
+    #[serde(default)]
+    pub block_page: bool,
+    /// Hash `/favicon.ico` and linked icons and fetch edge-only static assets
+    #[serde(default)]
+    pub favicon: bool,
+    /// Request the page under fresh cache keys and with conditional, range and cache
+    /// debug headers to fingerprint the caching layers (six requests)
+    #[serde(default)]
+    pub cache: bool,
+    /// Repeat the initial request over IPv4 and over IPv6 and flag an IPv6 path that
+    /// bypasses the WAF
+    #[serde(default)]
+    pub dual_stack: bool,
+    /// When the response is a JS challenge interstitial, load the page in a headless
+    /// browser and detect on the page behind it (needs the `browser` feature)
+    #[serde(default)]
+    pub browser_fallback: bool,
+    /// Send the same benign request to candidate origin addresses and report origins
+    /// that serve the site without the WAF
+    #[serde(default)]
+    pub origin_check: bool,
+    /// Look the host up in certificate transparency logs and use issuers and SAN
+    /// churn as supporting evidence (queries crt.sh, not the target)
+    #[serde(default)]
+    pub ct: bool,
+    /// Look the resolved addresses up in Shodan/Censys for banners and WAF tags (needs
+    /// the `intel` feature and API keys; queries the services, not the target)
+    #[serde(default)]
+    pub host_intel: bool,
+}
+
+impl Default for DetectionOptions {
+    fn default() -> Self {
+        Self {
+            dns: true,
+            timing: false,
+            payload: false,
+            active_probes: false,
+            preflight: false,
+            respect_robots: false,
+            fingerprint: false,
+            mode_analysis: false,
+            ratelimit_probe: false,
+            protocol: false,
+            header_order: false,
+            block_page: true,
+            favicon: false,
+            cache: false,
+            dual_stack: false,
+            browser_fallback: false,
+            origin_check: false,
+            ct: false,
+            host_intel: false,
+        }
+    }
+}
+
+impl DetectionOptions {
+    /// Every technique enabled, including the lookups sent to third parties (CT logs,
+    /// Shodan/Censys) and the headless browser. The robots.txt pre-flight is a policy,
+    /// not a technique, and stays off.
+    pub fn full() -> Self {
+        Self {
+            dns: true,
+            timing: true,
+            payload: true,
+            active_probes: true,
+            preflight: false,
+            respect_robots: false,
+            fingerprint: true,
+            mode_analysis: true,
+            ratelimit_probe: true,
+            protocol: true,
+            header_order: true,
+            block_page: true,
+            favicon: true,
+            cache: true,
+            dual_stack: true,
+            browser_fallback: true,
+            origin_check: true,
+            ct: true,
+            host_intel: true,
+        }
+    }
+
+    /// Nothing that touches the network: only the providers' passive checks of a
+    /// response that was already captured
+    pub fn offline() -> Self {
+        Self {
+            dns: false,
+            timing: false,
+            payload: false,
+            active_probes: false,
+            preflight: false,
+            respect_robots: false,
+            fingerprint: false,
+            mode_analysis: false,
+            ratelimit_probe: false,
+            protocol: false,
+            header_order: false,
+            block_page: true,
+            favicon: false,
+            cache: false,
+            dual_stack: false,
+            browser_fallback: false,
+            origin_check: false,
+            ct: false,
+            host_intel: false,
+        }
+    }
+
+    /// Whether any enabled technique sends requests beyond the initial GET
+    pub fn sends_probe_traffic(&self) -> bool {
+        self.timing || self.payload || self.active_probes || self.fingerprint || self.mode_analysis || self.ratelimit_probe || self.protocol || self.header_order
+            || self.favicon || self.cache || self.dual_stack || self.browser_fallback || self.origin_check
+    }
+
+    /// Whether robots.txt/security.txt should be fetched before scanning
+    pub fn runs_preflight(&self) -> bool {
+        self.preflight || self.respect_robots
+    }
+
+    /// Turn off every technique that sends probe traffic
+    pub fn disable_probing(&mut self) {
+        self.timing = false;
+        self.payload = false;
+        self.active_probes = false;
+        self.fingerprint = false;
+        self.mode_analysis = false;
+        self.ratelimit_probe = false;
+        self.protocol = false;
+        self.header_order = false;
+        self.favicon = false;
+        self.cache = false;
+        self.dual_stack = false;
+        self.browser_fallback = false;
+        self.origin_check = false;
+    }
+
+    /// Turn off the techniques a replayed session cannot answer: timing measurements,
+    /// raw-socket probes and the headless browser bypass the HTTP client
+    pub fn disable_unreplayable(&mut self) {
+        self.timing = false;
+        self.protocol = false;
+        self.header_order = false;
+        self.browser_fallback = false;
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DnsInfo {
     pub ip_addresses: Vec<String>,
@@ -55,6 +291,16 @@ pub trait DetectionProvider: Send + Sync {
     async fn dns_detect(&self, _dns_info: &DnsInfo) -> anyhow::Result<Vec<Evidence>> {
         Ok(vec![])
     }
+
+    /// Vendor sub-features (challenge type, bot product, plan hints) seen in a response
+    fn features(&self, _response: &http::HttpResponse) -> Vec<ProviderFeature> {
+        Vec::new()
+    }
+
+    /// Where in a multi-layer deployment this provider usually sits
+    fn stack_layer(&self) -> StackLayer {
+        StackLayer::from_provider_type(&self.provider_type())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -62,6 +308,44 @@ pub enum ProviderType {
     WAF,
     CDN,
     Both,
+    /// Bot-management products (DataDome, PerimeterX) that are neither WAF nor CDN
+    BotManagement,
+}
+
+/// Position of a provider in the request path, outermost first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum StackLayer {
+    /// Edge CDN / reverse proxy network (Cloudflare, Akamai, Fastly)
+    Edge,
+    /// Bot-management layer, usually deployed at the edge or as an edge worker
+    BotManagement,
+    /// Cloud provider WAF in front of the application (AWS WAF, Azure, Cloud Armor)
+    CloudWaf,
+    /// Appliance or plugin WAF next to the origin (F5, FortiWeb, NetScaler, Wordfence)
+    Origin,
+}
+
+impl StackLayer {
+    /// Default layer for a provider type: CDNs at the edge, plain WAFs at the origin
+    pub fn from_provider_type(provider_type: &ProviderType) -> Self {
+        match provider_type {
+            ProviderType::CDN | ProviderType::Both => StackLayer::Edge,
+            ProviderType::WAF => StackLayer::Origin,
+            ProviderType::BotManagement => StackLayer::BotManagement,
+        }
+    }
+}
+
+impl std::fmt::Display for StackLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StackLayer::Edge => "edge",
+            StackLayer::BotManagement => "bot management",
+            StackLayer::CloudWaf => "cloud WAF",
+            StackLayer::Origin => "origin",
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +366,33 @@ pub enum DetectionMethod {
     Timing,
     Certificate,
     Payload,
+    /// ALPN, Alt-Svc or QUIC signal
+    Protocol(String),
+    /// Position or casing of the response headers
+    HeaderOrder(String),
+    /// Internet-scan record (Shodan, Censys) of the host's addresses
+    HostIntel(String),
+    /// How the caching layer answered repeated, conditional or debug requests
+    Cache(String),
+}
+
+impl DetectionMethod {
+    /// Short method name and what it looked at (header name, status code, ...)
+    pub fn name_and_source(&self) -> (&'static str, String) {
+        match self {
+            DetectionMethod::Header(name) => ("header", name.clone()),
+            DetectionMethod::Body(marker) => ("body", marker.clone()),
+            DetectionMethod::StatusCode(code) => ("status_code", code.to_string()),
+            DetectionMethod::DNS(record) => ("dns", record.clone()),
+            DetectionMethod::Timing => ("timing", String::new()),
+            DetectionMethod::Certificate => ("certificate", String::new()),
+            DetectionMethod::Payload => ("payload", String::new()),
+            DetectionMethod::Protocol(signal) => ("protocol", signal.clone()),
+            DetectionMethod::HeaderOrder(signal) => ("header_order", signal.clone()),
+            DetectionMethod::HostIntel(source) => ("host_intel", source.clone()),
+            DetectionMethod::Cache(signal) => ("cache", signal.clone()),
+        }
+    }
 }
 
 // Alias for backward compatibility
@@ -92,23 +403,151 @@ pub struct DetectionResult {
     pub url: String,
     pub detected_waf: Option<ProviderDetection>,
     pub detected_cdn: Option<ProviderDetection>,
+    /// Bot-management product (DataDome, PerimeterX), reported apart from WAF and CDN
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_bot_manager: Option<ProviderDetection>,
+    /// Every provider with evidence, ordered along the request path (edge CDN first,
+    /// origin appliance last) and by confidence within a layer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub detected_stack: Vec<ProviderDetection>,
+    /// JS challenge, captcha or bot sensor served by a bot-management product
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_challenge: Option<challenge::BotChallenge>,
+    /// CDN point of presence that served the response, from its PoP header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_location: Option<edge::EdgeLocation>,
     pub provider_scores: HashMap<String, f64>,
     pub evidence_map: HashMap<String, Vec<Evidence>>,
+    /// Contradicting headers that lowered a provider's score, keyed by provider
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub negative_evidence: HashMap<String, Vec<confidence::NegativeEvidence>>,
+    /// Confidence level, per-category breakdown and explanation behind each provider score
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub confidence_details: HashMap<String, confidence::ConfidenceResult>,
     pub detection_time_ms: u64,
     pub metadata: DetectionMetadata,
+    /// Per-technique timing, request counts and errors
+    #[serde(default)]
+    pub diagnostics: Diagnostics,
+    /// Set when the scan itself failed, so "no WAF" and "scan failed" can be told apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<error::ScanError>,
+    /// Ruleset generation / paranoia level of the detected WAF (see `DetectionOptions::fingerprint`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ruleset_info: Option<fingerprint::RulesetInfo>,
+    /// Blocking/monitoring mode of the detected WAF (see `DetectionOptions::mode_analysis`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf_mode: Option<engine::waf_mode_detector::WafModeResult>,
+    /// Rate-limiting behavior of the target (see `DetectionOptions::ratelimit_probe`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ratelimit_probe::RateLimitProfile>,
+    /// Detection over IPv4 and IPv6 separately (see `DetectionOptions::dual_stack`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dual_stack: Option<dns::DualStackReport>,
+    /// Unicode and punycode forms of the host when the target is an internationalized domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idn: Option<idn::IdnHost>,
+    /// Every provider's full evidence grouped by method; `evidence_map` holds the
+    /// condensed list (see the `evidence` module)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub evidence_groups: HashMap<String, Vec<evidence::EvidenceGroup>>,
+    /// Narrative from [`DetectionResult::explain`], filled in on request (`--explain`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    /// Agreement between the passes merged into this result (`--runs N`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consensus: Option<consensus::Consensus>,
+    /// Headless browser re-fetch past a JS challenge (see `DetectionOptions::browser_fallback`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser_fallback: Option<browser::BrowserFallback>,
+    /// Stateless vs. stateful answers when the scan kept a cookie session (`--session`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<http::session::SessionComparison>,
+    /// Candidate origins compared with the edge (see `DetectionOptions::origin_check`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_bypass: Option<origin::BypassReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderDetection {
     pub name: String,
     pub confidence: f64,
+    /// Structured sub-features of the detected vendor
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<ProviderFeature>,
+    /// Inferred position in the request path (only set for `detected_stack` entries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<StackLayer>,
+}
+
+/// A vendor sub-feature, e.g. `challenge = managed` or `plan = enterprise`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProviderFeature {
+    pub category: String,
+    pub value: String,
+    pub confidence: f64,
+    /// The header, cookie or body marker the feature was inferred from
+    pub evidence: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectionMetadata {
     pub timestamp: DateTime<Utc>,
+    /// waf-detector version that produced the result
     pub version: String,
     pub user_agent: String,
+    /// Retries needed to fetch the target (transient network errors, 502/504)
+    #[serde(default)]
+    pub retries: u32,
+    /// robots.txt / security.txt pre-flight results, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preflight: Option<preflight::PreflightInfo>,
+    /// Version of the signature bundle in effect, if one was loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_bundle: Option<String>,
+    /// Bundle rules per provider, on top of the built-in checks
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pattern_counts: BTreeMap<String, usize>,
+    /// Set when the target's body exceeded the client's limit and only part of it was analyzed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_truncation: Option<http::BodyTruncation>,
+}
+
+/// Where the time and requests of a scan went
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Diagnostics {
+    /// Requests sent for this target, including the initial GET and pre-flight
+    pub total_requests: usize,
+    pub techniques: Vec<TechniqueDiagnostics>,
+}
+
+/// Diagnostics for a single provider or analyzer run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TechniqueDiagnostics {
+    pub name: String,
+    pub kind: TechniqueKind,
+    pub duration_ms: u64,
+    pub requests: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TechniqueKind {
+    Provider,
+    Analyzer,
+}
+
+impl Diagnostics {
+    /// Requests issued by providers and analyzers (excludes the initial GET)
+    pub fn technique_requests(&self) -> usize {
+        self.techniques.iter().map(|t| t.requests).sum()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &TechniqueDiagnostics> {
+        self.techniques.iter().filter(|t| t.error.is_some())
+    }
 }
 
 impl DetectionResult {
@@ -119,10 +558,18 @@ impl DetectionResult {
     pub fn has_cdn(&self) -> bool {
         self.detected_cdn.is_some()
     }
+
+    pub fn has_bot_manager(&self) -> bool {
+        self.detected_bot_manager.is_some()
+    }
     
     pub fn detected(&self) -> bool {
         self.has_waf() || self.has_cdn()
     }
+
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
     
     pub fn waf_name(&self) -> Option<&str> {
         self.detected_waf.as_ref().map(|w| w.name.as_str())
@@ -131,6 +578,10 @@ impl DetectionResult {
     pub fn cdn_name(&self) -> Option<&str> {
         self.detected_cdn.as_ref().map(|c| c.name.as_str())
     }
+
+    pub fn bot_manager_name(&self) -> Option<&str> {
+        self.detected_bot_manager.as_ref().map(|b| b.name.as_str())
+    }
     
     pub fn waf_confidence(&self) -> Option<f64> {
         self.detected_waf.as_ref().map(|w| w.confidence)
@@ -149,76 +600,54 @@ impl DetectionResult {
         self.evidence_map.values().flatten().cloned().collect()
     }
     
+    /// Why each provider was detected: its strongest signal, what corroborates it and
+    /// what contradicts it, one paragraph per provider
+    pub fn explain(&self) -> String {
+        explain::narrative(self)
+    }
+
     pub fn format_as_table(&self) -> String {
-        let mut table = String::new();
-        
-        // Table header
-        table.push_str("┌─────────────────────────────────────────────────────────────────────────┐\n");
-        table.push_str("│                            WAF/CDN Detection Results                    │\n");
-        table.push_str("├─────────────────────────────────────────────────────────────────────────┤\n");
-        
-        // URL
-        let url_display = if self.url.len() > 67 {
-            format!("{}...", &self.url[..64])
-        } else {
-            self.url.clone()
-        };
-        table.push_str(&format!("│ URL: {:<67} │\n", url_display));
-        table.push_str("├─────────────────────────────────────────────────────────────────────────┤\n");
-        
-        // WAF Detection
-        if let Some(waf) = &self.detected_waf {
-            table.push_str(&format!("│ WAF: {:<20} Confidence: {:<6.1}%                    │\n", 
-                waf.name, waf.confidence * 100.0));
-        } else {
-            table.push_str("│ WAF: Not Detected                                                      │\n");
-        }
-        
-        // CDN Detection
-        if let Some(cdn) = &self.detected_cdn {
-            table.push_str(&format!("│ CDN: {:<20} Confidence: {:<6.1}%                    │\n", 
-                cdn.name, cdn.confidence * 100.0));
-        } else {
-            table.push_str("│ CDN: Not Detected                                                      │\n");
+        let mut table = utils::table::BoxTable::new("WAF/CDN Detection Results");
+        table.row(format!("URL: {}", self.url));
+        if let Some(host) = &self.idn {
+            table.row(format!("Host: {} ({})", host.unicode, host.ascii));
         }
-        
-        table.push_str("├─────────────────────────────────────────────────────────────────────────┤\n");
-        table.push_str(&format!("│ Detection Time: {:<8} ms                                          │\n", 
-            self.detection_time_ms));
-        table.push_str("├─────────────────────────────────────────────────────────────────────────┤\n");
-        
+        table.separator();
+
+        match &self.detected_waf {
+            Some(waf) => table.row(format!("WAF: {:<20} Confidence: {:.1}%", waf.name, waf.confidence * 100.0)),
+            None => table.row("WAF: Not Detected"),
+        };
+        match &self.detected_cdn {
+            Some(cdn) => table.row(format!("CDN: {:<20} Confidence: {:.1}%", cdn.name, cdn.confidence * 100.0)),
+            None => table.row("CDN: Not Detected"),
+        };
+
+        table.separator();
+        table.row(format!("Detection Time: {} ms", self.detection_time_ms));
+        table.separator();
+
         // Evidence Summary
-        table.push_str("│ Evidence Summary:                                                       │\n");
+        table.row("Evidence Summary:");
         for (provider, evidence_list) in &self.evidence_map {
             if !evidence_list.is_empty() {
-                table.push_str(&format!("│ • {:<20} Evidence Count: {:<3}                          │\n", 
-                    provider, evidence_list.len()));
+                table.row(format!("• {:<20} Evidence Count: {}", provider, evidence_list.len()));
                 
-                for (i, evidence) in evidence_list.iter().enumerate() {
-                    if i < 3 { // Show first 3 evidence items
-                        let desc = if evidence.description.len() > 45 {
-                            format!("{}...", &evidence.description[..42])
-                        } else {
-                            evidence.description.clone()
-                        };
-                        table.push_str(&format!("│   - {:<65} │\n", desc));
-                        
-                        // Show the raw data if it's short enough
-                        if evidence.raw_data.len() <= 50 {
-                            table.push_str(&format!("│     Data: {:<59} │\n", evidence.raw_data));
-                        }
+                for evidence in evidence_list.iter().take(3) { // Show first 3 evidence items
+                    table.row(format!("  - {}", utils::truncate_display(&evidence.description, 45)));
+                    
+                    // Show the raw data if it's short enough
+                    if utils::display_width(&evidence.raw_data) <= 50 {
+                        table.row(format!("    Data: {}", evidence.raw_data));
                     }
                 }
                 if evidence_list.len() > 3 {
-                    table.push_str(&format!("│   ... and {} more evidence items                                     │\n", 
-                        evidence_list.len() - 3));
+                    table.row(format!("  ... and {} more evidence items", evidence_list.len() - 3));
                 }
             }
         }
         
-        table.push_str("└─────────────────────────────────────────────────────────────────────────┘\n");
-        
-        table
+        table.render()
     }
     
     pub fn format_pretty(&self) -> String {
@@ -0,0 +1,199 @@
+//! Diagnostic logging
+//!
+//! Diagnostics go through `tracing`: each scan runs in a `scan` span and each provider
+//! or analyzer in a `technique` span inside it, so an event says which target and
+//! technique it came from. The CLI writes them to stderr, as text or, with
+//! `--log-json`, as one JSON object per line for log shippers.
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::span::Record;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::{FormatEvent, FormatFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Filter used when neither `--log-level` nor `RUST_LOG` is set
+pub const DEFAULT_LEVEL: &str = "warn";
+
+/// Install the global subscriber, writing to stderr.
+///
+/// `level` is a filter directive (`debug`, `waf_detector::registry=trace`); without
+/// one, `RUST_LOG` applies, then [`DEFAULT_LEVEL`].
+pub fn init(level: Option<&str>, json: bool) -> Result<()> {
+    let filter = match level {
+        Some(level) => EnvFilter::try_new(level).map_err(|e| anyhow!("Invalid log level '{}': {}", level, e))?,
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LEVEL)),
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    let installed = if json {
+        builder.fmt_fields(JsonFields).event_format(JsonFormat).try_init()
+    } else {
+        builder.try_init()
+    };
+    installed.map_err(|e| anyhow!("Failed to install logger: {}", e))
+}
+
+/// Collects event or span fields into a JSON object
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::String(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
+
+/// Stores span fields as a JSON object so [`JsonFormat`] can embed them
+pub struct JsonFields;
+
+impl<'writer> FormatFields<'writer> for JsonFields {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        write!(writer, "{}", Value::Object(visitor.0))
+    }
+
+    fn add_fields(&self, current: &'writer mut FormattedFields<Self>, fields: &Record<'_>) -> fmt::Result {
+        let mut visitor = JsonVisitor(match serde_json::from_str(&current.fields) {
+            Ok(Value::Object(existing)) => existing,
+            _ => Map::new(),
+        });
+        fields.record(&mut visitor);
+        current.fields = Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// One JSON object per event: timestamp, level, target, message, fields and the
+/// enclosing spans from the outermost in
+pub struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), chrono::Utc::now().to_rfc3339().into());
+        line.insert("level".to_string(), metadata.level().to_string().into());
+        // Records bridged from the `log` crate carry their real target as a field
+        let target = match visitor.0.remove("log.target") {
+            Some(target) => target,
+            None => metadata.target().into(),
+        };
+        visitor.0.retain(|name, _| !name.starts_with("log."));
+        line.insert("target".to_string(), target);
+        if let Some(message) = visitor.0.remove("message") {
+            line.insert("message".to_string(), message);
+        }
+        if !visitor.0.is_empty() {
+            line.insert("fields".to_string(), Value::Object(visitor.0));
+        }
+
+        let spans: Vec<Value> = ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| {
+                let mut entry = match span.extensions().get::<FormattedFields<N>>().map(|f| serde_json::from_str(&f.fields)) {
+                    Some(Ok(Value::Object(fields))) => fields,
+                    _ => Map::new(),
+                };
+                entry.insert("name".to_string(), span.name().into());
+                Value::Object(entry)
+            })
+            .collect();
+        if !spans.is_empty() {
+            line.insert("spans".to_string(), Value::Array(spans));
+        }
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_lines_carry_span_fields() {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(buffer.clone())
+            .fmt_fields(JsonFields)
+            .event_format(JsonFormat)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let scan = tracing::info_span!("scan", url = "https://example.com");
+            let _scan = scan.enter();
+            let technique = tracing::info_span!("technique", name = "CloudFlare", requests = tracing::field::Empty);
+            let _technique = technique.enter();
+            technique.record("requests", 2u64);
+            tracing::warn!(status = 403, "blocked");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "blocked");
+        assert_eq!(line["fields"]["status"], 403);
+        assert_eq!(line["spans"][0]["url"], "https://example.com");
+        assert_eq!(line["spans"][1]["name"], "technique");
+        assert_eq!(line["spans"][1]["requests"], 2);
+    }
+}
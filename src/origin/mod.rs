@@ -0,0 +1,208 @@
+//! Direct-to-origin bypass check (`--origin-check`)
+//!
+//! A WAF in front of a CDN only protects traffic that goes through the edge. When the
+//! origin server also answers requests sent straight to its address, attackers can
+//! skip the WAF entirely. Candidate origin addresses come from the user
+//! (`--origin-ip`), from the host's former A/AAAA records in passive DNS history
+//! (`--pdns`), from hosts Shodan/Censys saw serving the domain's certificate
+//! (`--intel`) and from subdomains that commonly point past the CDN
+//! (`origin.`, `direct.`, `mail.`, ...). Each candidate gets the same benign GET as
+//! the edge, with the target's Host header and SNI, and is reported as a bypass when
+//! it serves the same page without the WAF.
+
+use crate::dns::passive::PassiveDnsSource;
+use crate::intel::IntelSource;
+use crate::http::HttpResponse;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// Subdomains that often resolve straight to the origin
+pub const SUBDOMAIN_HINTS: &[&str] = &["origin", "direct", "direct-connect", "backend", "origin-www", "mail", "ftp", "cpanel", "webmail", "staging", "dev"];
+
+/// Most candidates probed per scan
+pub const MAX_CANDIDATES: usize = 10;
+
+/// An address that might be the origin, and where it came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OriginCandidate {
+    pub ip: IpAddr,
+    /// `user`, the host name it was resolved from, or the passive DNS or internet-scan
+    /// service that reported it
+    pub source: String,
+}
+
+impl OriginCandidate {
+    pub fn new(ip: IpAddr, source: impl Into<String>) -> Self {
+        Self { ip, source: source.into() }
+    }
+}
+
+/// Candidate origins for `host`: `supplied` first, then the host's former addresses in
+/// `passive_dns` history, then hosts `intel` saw with its certificate, then addresses
+/// of [`SUBDOMAIN_HINTS`], leaving out the edge's own addresses and anything private
+/// or loopback
+pub async fn discover(
+    host: &str,
+    edge: &[IpAddr],
+    supplied: &[IpAddr],
+    passive_dns: &[Arc<dyn PassiveDnsSource>],
+    intel: &[Arc<dyn IntelSource>],
+) -> Vec<OriginCandidate> {
+    let mut candidates: Vec<OriginCandidate> = supplied.iter().map(|ip| OriginCandidate::new(*ip, "user")).collect();
+    if !passive_dns.is_empty() {
+        let history = crate::dns::passive::history(passive_dns, host).await;
+        for (ip, record) in crate::dns::passive::former_addresses(&history, edge) {
+            if is_routable(&ip) && !candidates.iter().any(|c| c.ip == ip) {
+                candidates.push(OriginCandidate::new(ip, format!("{} history, {}", record.source, record.last_seen_label())));
+            }
+        }
+    }
+    for (ip, source) in crate::intel::certificate_hosts(intel, host, edge).await {
+        if is_routable(&ip) && !candidates.iter().any(|c| c.ip == ip) {
+            candidates.push(OriginCandidate::new(ip, format!("{} certificate search", source)));
+        }
+    }
+    let apex = host.strip_prefix("www.").unwrap_or(host);
+    let lookups = SUBDOMAIN_HINTS.iter().map(|label| {
+        let name = format!("{}.{}", label, apex);
+        async move {
+            let addresses = crate::dns::resolve_addresses(&name).await.unwrap_or_default();
+            (name, addresses)
+        }
+    });
+    for (name, addresses) in futures::future::join_all(lookups).await {
+        for ip in addresses.ipv4.into_iter().chain(addresses.ipv6) {
+            if is_routable(&ip) && !edge.contains(&ip) && !candidates.iter().any(|c| c.ip == ip) {
+                candidates.push(OriginCandidate::new(ip, name.clone()));
+            }
+        }
+    }
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
+
+fn is_routable(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified()),
+        IpAddr::V6(v6) => !(v6.is_loopback() || v6.is_unspecified() || (v6.segments()[0] & 0xfe00) == 0xfc00),
+    }
+}
+
+/// The edge's answer to the benign request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EdgeView {
+    pub addresses: Vec<IpAddr>,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn: Option<String>,
+}
+
+/// One candidate's answer compared with the edge's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OriginProbe {
+    pub candidate: OriginCandidate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waf: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn: Option<String>,
+    /// Serves the page the edge serves
+    pub same_content: bool,
+    /// Serves the page without the edge's WAF
+    pub bypasses_waf: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl OriginProbe {
+    pub fn failed(candidate: OriginCandidate, error: impl Into<String>) -> Self {
+        Self { candidate, status: None, waf: None, cdn: None, same_content: false, bypasses_waf: false, error: Some(error.into()) }
+    }
+
+    /// Compare a candidate's response, and what the providers made of it, with the edge
+    pub fn compare(candidate: OriginCandidate, edge: &EdgeView, edge_response: &HttpResponse, response: &HttpResponse, waf: Option<String>, cdn: Option<String>) -> Self {
+        let same_content = same_content(edge_response, response);
+        let bypasses_waf = same_content && edge.waf.is_some() && waf.is_none();
+        Self { candidate, status: Some(response.status), waf, cdn, same_content, bypasses_waf, error: None }
+    }
+}
+
+/// Result of the direct-to-origin comparison
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BypassReport {
+    pub edge: EdgeView,
+    pub candidates: Vec<OriginProbe>,
+}
+
+impl BypassReport {
+    /// Some origin serves the site without the WAF
+    pub fn bypassable(&self) -> bool {
+        self.candidates.iter().any(|probe| probe.bypasses_waf)
+    }
+
+    pub fn bypasses(&self) -> impl Iterator<Item = &OriginProbe> {
+        self.candidates.iter().filter(|probe| probe.bypasses_waf)
+    }
+}
+
+/// Same page: equal status, and the same `<title>` or bodies within 10% of each other's length
+pub fn same_content(edge: &HttpResponse, origin: &HttpResponse) -> bool {
+    if edge.status != origin.status {
+        return false;
+    }
+    match (title(&edge.body), title(&origin.body)) {
+        (Some(a), Some(b)) => a == b,
+        _ => {
+            let (a, b) = (edge.body.len() as f64, origin.body.len() as f64);
+            a.max(b) == 0.0 || (a - b).abs() / a.max(b) <= 0.1
+        }
+    }
+}
+
+fn title(body: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title>")?;
+    Some(body[start..end].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(status: u16, body: &str) -> HttpResponse {
+        HttpResponse { status, body: body.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_origin_serving_same_page_without_waf_is_a_bypass() {
+        let edge = EdgeView { status: 200, waf: Some("CloudFlare".to_string()), ..EdgeView::default() };
+        let edge_page = page(200, "<html><title>Shop</title>…cf</html>");
+        let candidate = OriginCandidate::new("203.0.113.7".parse().unwrap(), "origin.example.com");
+
+        let probe = OriginProbe::compare(candidate.clone(), &edge, &edge_page, &page(200, "<TITLE> Shop </TITLE>"), None, None);
+        assert!(probe.same_content && probe.bypasses_waf);
+
+        let guarded = OriginProbe::compare(candidate.clone(), &edge, &edge_page, &page(200, "<title>Shop</title>"), Some("CloudFlare".to_string()), None);
+        assert!(!guarded.bypasses_waf);
+
+        let other_site = OriginProbe::compare(candidate, &edge, &edge_page, &page(200, "<title>Plesk</title>"), None, None);
+        assert!(!other_site.same_content);
+
+        let report = BypassReport { edge, candidates: vec![probe, guarded, other_site] };
+        assert!(report.bypassable());
+        assert_eq!(report.bypasses().count(), 1);
+    }
+
+    #[test]
+    fn test_private_addresses_are_not_candidates() {
+        assert!(!is_routable(&"10.0.0.5".parse().unwrap()));
+        assert!(!is_routable(&"fd00::1".parse().unwrap()));
+        assert!(is_routable(&"198.51.100.20".parse().unwrap()));
+    }
+}
@@ -0,0 +1,218 @@
+//! Per-path WAF coverage scanning
+//!
+//! WAF rules are often scoped to part of a site: `/` is protected while `/api` or
+//! `/admin` is routed around the WAF. This runs detection plus a reduced smoke test
+//! against several paths of one host and reports where their protection differs.
+
+use crate::engine::DetectionEngine;
+use crate::error::ScanError;
+use crate::payload::waf_smoke_test::{PayloadClassification, SmokeTestConfig, SmokeTestResult, WafSmokeTest};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Paths scanned when `--paths` is given without a list
+pub const DEFAULT_PATHS: &[&str] = &["/", "/api", "/admin", "/login", "/graphql", "/wp-admin"];
+
+/// Protection observed on one path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathCoverage {
+    pub path: String,
+    pub url: String,
+    pub detected_waf: Option<String>,
+    pub detected_cdn: Option<String>,
+    /// Percentage of smoke payloads blocked, if the smoke test ran
+    pub effectiveness_percentage: Option<f64>,
+    /// Smoke payload categories with at least one payload allowed through
+    pub allowed_categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanError>,
+}
+
+/// Consolidated per-path result for one host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathCoverageReport {
+    pub base_url: String,
+    pub paths: Vec<PathCoverage>,
+    /// Human-readable protection differences relative to the first path
+    pub differences: Vec<String>,
+}
+
+/// Runs detection and smoke payloads against several paths of one host
+pub struct PathScanner<'a> {
+    engine: &'a DetectionEngine,
+    smoke_test: Option<WafSmokeTest>,
+}
+
+impl<'a> PathScanner<'a> {
+    pub fn new(engine: &'a DetectionEngine) -> Self {
+        Self { engine, smoke_test: None }
+    }
+
+    /// Also send `payloads_per_category` smoke payloads of each category to every path
+    pub fn with_smoke_test(mut self, payloads_per_category: usize) -> Result<Self> {
+        let config = SmokeTestConfig {
+            payloads_per_category: Some(payloads_per_category),
+            quiet: true,
+            ..SmokeTestConfig::default()
+        };
+        self.smoke_test = Some(WafSmokeTest::new(config)?);
+        Ok(self)
+    }
+
+    pub async fn scan(&self, base_url: &str, paths: &[String]) -> Result<PathCoverageReport> {
+        let mut coverage = Vec::new();
+        for path in paths {
+            let url = path_url(base_url, path)?;
+            coverage.push(self.scan_path(path, &url).await);
+        }
+
+        Ok(PathCoverageReport {
+            base_url: base_url.to_string(),
+            differences: differences(&coverage),
+            paths: coverage,
+        })
+    }
+
+    async fn scan_path(&self, path: &str, url: &str) -> PathCoverage {
+        let mut coverage = PathCoverage {
+            path: path.to_string(),
+            url: url.to_string(),
+            detected_waf: None,
+            detected_cdn: None,
+            effectiveness_percentage: None,
+            allowed_categories: Vec::new(),
+            error: None,
+        };
+
+        match self.engine.detect(url).await {
+            Ok(result) => {
+                coverage.detected_waf = result.detected_waf.map(|d| d.name);
+                coverage.detected_cdn = result.detected_cdn.map(|d| d.name);
+            }
+            Err(e) => {
+                coverage.error = Some(ScanError::from_error(&e));
+                return coverage;
+            }
+        }
+
+        if let Some(smoke_test) = &self.smoke_test {
+            match smoke_test.run_test(url).await {
+                Ok(result) => {
+                    coverage.effectiveness_percentage = Some(result.summary.effectiveness_percentage);
+                    coverage.allowed_categories = allowed_categories(&result);
+                }
+                Err(e) => coverage.error = Some(ScanError::from_error(&e)),
+            }
+        }
+
+        coverage
+    }
+}
+
+/// Resolve `path` against the scheme and host of `base_url`
+pub fn path_url(base_url: &str, path: &str) -> Result<String> {
+    let base = url::Url::parse(base_url).map_err(|e| anyhow!("Invalid URL {}: {}", base_url, e))?;
+    let path = if path.starts_with('/') { path.to_string() } else { format!("/{}", path) };
+    Ok(base.join(&path)?.to_string())
+}
+
+/// Parse a path list: one path per line, blank lines and `#` comments ignored
+pub fn parse_path_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+fn allowed_categories(result: &SmokeTestResult) -> Vec<String> {
+    let categories: BTreeSet<String> = result
+        .test_results
+        .iter()
+        .filter(|test| test.classification == PayloadClassification::Allowed)
+        .map(|test| test.category.clone())
+        .collect();
+    categories.into_iter().collect()
+}
+
+/// Compare every path against the first one (usually `/`)
+pub fn differences(paths: &[PathCoverage]) -> Vec<String> {
+    let Some(reference) = paths.iter().find(|p| p.error.is_none()) else {
+        return Vec::new();
+    };
+
+    let mut differences = Vec::new();
+    for path in paths.iter().filter(|p| p.error.is_none() && p.path != reference.path) {
+        match (&reference.detected_waf, &path.detected_waf) {
+            (Some(waf), None) => differences.push(format!(
+                "{} is not behind a WAF, but {} is behind {}", path.path, reference.path, waf
+            )),
+            (None, Some(waf)) => differences.push(format!(
+                "{} is behind {}, but {} has no WAF", path.path, waf, reference.path
+            )),
+            (Some(a), Some(b)) if a != b => differences.push(format!(
+                "{} is behind {}, but {} is behind {}", path.path, b, reference.path, a
+            )),
+            _ => {}
+        }
+
+        let newly_allowed: Vec<&String> = path
+            .allowed_categories
+            .iter()
+            .filter(|c| !reference.allowed_categories.contains(c))
+            .collect();
+        if !newly_allowed.is_empty() {
+            differences.push(format!(
+                "{} allows {} payloads that {} blocks",
+                path.path,
+                newly_allowed.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", "),
+                reference.path
+            ));
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage(path: &str, waf: Option<&str>, allowed: &[&str]) -> PathCoverage {
+        PathCoverage {
+            path: path.to_string(),
+            url: format!("https://example.com{}", path),
+            detected_waf: waf.map(|w| w.to_string()),
+            detected_cdn: None,
+            effectiveness_percentage: None,
+            allowed_categories: allowed.iter().map(|c| c.to_string()).collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_path_url() {
+        assert_eq!(path_url("https://example.com/app?x=1", "/api").unwrap(), "https://example.com/api");
+        assert_eq!(path_url("https://example.com", "admin").unwrap(), "https://example.com/admin");
+    }
+
+    #[test]
+    fn test_parse_path_list() {
+        assert_eq!(parse_path_list("/\n# comment\n\n /api \n"), vec!["/", "/api"]);
+    }
+
+    #[test]
+    fn test_differences() {
+        let paths = vec![
+            coverage("/", Some("CloudFlare"), &[]),
+            coverage("/api", None, &["SqlInjectionBasic"]),
+            coverage("/admin", Some("CloudFlare"), &[]),
+        ];
+
+        assert_eq!(differences(&paths), vec![
+            "/api is not behind a WAF, but / is behind CloudFlare",
+            "/api allows SqlInjectionBasic payloads that / blocks",
+        ]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Detection plus effectiveness testing (`--combined`, `/api/combined-scan`)
+//!
+//! Effectiveness is measured with a quiet [`WafSmokeTest`] and reported in the shape the
+//! external `scripts/waf-smoke-test.sh` used to produce, which the dashboard still reads.
+//! The script itself is only run with `--legacy-script`, see [`crate::script_executor`].
+
+use super::waf_smoke_test::{PayloadClassification, SmokeTestConfig, SmokeTestResult, WafSmokeTest};
+use crate::error::ErrorClass;
+use crate::DetectionResult;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Effectiveness half of a combined scan, in the shape the test script reported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivenessResult {
+    pub waf_detected: bool,
+    pub waf_name: String,
+    pub cdn_detected: bool,
+    pub cdn_name: String,
+    pub cloud_provider: String,
+    pub effectiveness_score: f64,
+    pub total_tests: u32,
+    pub blocked_tests: u32,
+    pub allowed_tests: u32,
+    pub error_tests: u32,
+    pub test_results: Vec<PayloadResult>,
+    pub recommendations: Vec<String>,
+    pub execution_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadResult {
+    pub category: String,
+    pub payload: String,
+    pub status: String, // "BLOCKED", "ALLOWED", "ERROR", "CHECK"
+    pub response_code: u16,
+    pub response_time_ms: u64,
+    pub detection_method: String,
+}
+
+/// Why effectiveness testing produced no result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivenessError {
+    /// `ErrorClass` of a failed smoke test, or the `ScriptError` kind of the legacy script
+    pub kind: String,
+    pub message: String,
+}
+
+impl EffectivenessError {
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        let kind = serde_json::to_value(ErrorClass::of(error)).ok().and_then(|v| v.as_str().map(str::to_string));
+        Self { kind: kind.unwrap_or_else(|| "other".to_string()), message: error.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinedResult {
+    pub url: String,
+    pub detection_result: crate::DetectionResult,
+    pub effectiveness_result: Option<EffectivenessResult>,
+    /// Why effectiveness testing produced no result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effectiveness_error: Option<EffectivenessError>,
+    pub analysis_summary: String,
+    pub recommendations: Vec<String>,
+    pub total_time_ms: u64,
+}
+
+impl EffectivenessResult {
+    /// Summary of a smoke test; rate limits and challenges count as blocked, like
+    /// `TestSummary::effectiveness_percentage`
+    pub fn from_smoke_test(result: &SmokeTestResult) -> Self {
+        let summary = &result.summary;
+        let test_results = result
+            .test_results
+            .iter()
+            .map(|test| {
+                let (status, method) = match test.classification {
+                    PayloadClassification::Blocked => ("BLOCKED", "HTTP Status Code"),
+                    PayloadClassification::RateLimited => ("BLOCKED", "Rate Limit"),
+                    PayloadClassification::Challenge => ("BLOCKED", "Challenge Page"),
+                    PayloadClassification::Allowed => ("ALLOWED", "HTTP Status Code"),
+                    PayloadClassification::Error => ("ERROR", "Request Error"),
+                };
+                PayloadResult {
+                    category: test.category.clone(),
+                    payload: test.payload.clone(),
+                    status: status.to_string(),
+                    response_code: test.response_status,
+                    response_time_ms: test.response_time_ms,
+                    detection_method: method.to_string(),
+                }
+            })
+            .collect();
+
+        Self {
+            waf_detected: result.detected_waf.is_some(),
+            waf_name: result.detected_waf.clone().unwrap_or_else(|| "Unknown".to_string()),
+            cdn_detected: result.detected_cdn.is_some(),
+            cdn_name: result.detected_cdn.clone().unwrap_or_else(|| "N/A".to_string()),
+            cloud_provider: "Not Detected".to_string(),
+            effectiveness_score: summary.effectiveness_percentage,
+            total_tests: summary.total_tests as u32,
+            blocked_tests: (summary.blocked_count + summary.rate_limited_count + summary.challenge_count) as u32,
+            allowed_tests: summary.allowed_count as u32,
+            error_tests: summary.error_count as u32,
+            test_results,
+            recommendations: result.recommendations.clone(),
+            execution_time_ms: result.total_time_ms,
+        }
+    }
+}
+
+/// Smoke test settings for combined scans: the defaults, without live output
+pub fn smoke_test_config() -> SmokeTestConfig {
+    SmokeTestConfig { quiet: true, ..SmokeTestConfig::default() }
+}
+
+/// Run the payload smoke test against `url`
+pub async fn test_effectiveness(url: &str, config: SmokeTestConfig) -> Result<EffectivenessResult> {
+    let result = WafSmokeTest::new(config)?.run_test(url).await?;
+    Ok(EffectivenessResult::from_smoke_test(&result))
+}
+
+/// Merge detection and effectiveness results into one summary with recommendations
+pub fn combine(
+    detection_result: DetectionResult,
+    effectiveness: Result<EffectivenessResult, EffectivenessError>,
+    total_time_ms: u64,
+) -> CombinedResult {
+    let (effectiveness_result, effectiveness_error) = match effectiveness {
+        Ok(result) => (Some(result), None),
+        Err(e) => (None, Some(e)),
+    };
+    let mut analysis_summary = String::new();
+    let mut recommendations = Vec::new();
+
+    // Generate analysis summary
+    if let Some(waf) = &detection_result.detected_waf {
+        analysis_summary.push_str(&format!("WAF Detected: {} ({:.1}% confidence)\n",
+            waf.name, waf.confidence * 100.0));
+    } else {
+        analysis_summary.push_str("No WAF detected\n");
+    }
+
+    if let Some(cdn) = &detection_result.detected_cdn {
+        analysis_summary.push_str(&format!("CDN Detected: {} ({:.1}% confidence)\n",
+            cdn.name, cdn.confidence * 100.0));
+    }
+
+    if let Some(error) = &effectiveness_error {
+        analysis_summary.push_str(&format!("Effectiveness Testing: not run ({})\n", error.message));
+    }
+
+    if let Some(effectiveness) = &effectiveness_result {
+        analysis_summary.push_str(&format!(
+            "Effectiveness Testing: {:.1}% blocked ({}/{} tests)\n",
+            effectiveness.effectiveness_score,
+            effectiveness.blocked_tests,
+            effectiveness.total_tests
+        ));
+
+        // Add effectiveness-based recommendations
+        if effectiveness.effectiveness_score < 50.0 {
+            recommendations.push("⚠️ Low WAF effectiveness - many payloads bypassed".to_string());
+            recommendations.push("Consider reviewing and tuning WAF rules".to_string());
+        } else if effectiveness.effectiveness_score > 90.0 {
+            recommendations.push("✅ High WAF effectiveness - good security posture".to_string());
+        }
+
+        recommendations.extend(effectiveness.recommendations.clone());
+    }
+
+    // Add provider-specific recommendations
+    if let Some(waf) = &detection_result.detected_waf {
+        match waf.name.as_str() {
+            "CloudFlare" => {
+                recommendations.push("🔒 CloudFlare detected - consider enabling additional security features".to_string());
+            }
+            "AWS" => {
+                recommendations.push("☁️ AWS WAF detected - review CloudWatch metrics and rules".to_string());
+            }
+            "Akamai" => {
+                recommendations.push("🛡️ Akamai detected - consider Bot Manager for advanced protection".to_string());
+            }
+            _ => {}
+        }
+    }
+
+    CombinedResult {
+        url: detection_result.url.clone(),
+        detection_result,
+        effectiveness_result,
+        effectiveness_error,
+        analysis_summary,
+        recommendations,
+        total_time_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_effectiveness_is_measured_natively() {
+        let mut server = mockito::Server::new_async().await;
+        let _blocked = server.mock("GET", mockito::Matcher::Any).with_status(403).expect_at_least(0).create_async().await;
+
+        let config = SmokeTestConfig { delay_between_requests_ms: 0, payloads_per_category: Some(1), ..smoke_test_config() };
+        let result = test_effectiveness(&server.url(), config).await.unwrap();
+
+        assert!(result.total_tests > 0);
+        assert_eq!(result.blocked_tests, result.total_tests);
+        assert_eq!(result.effectiveness_score, 100.0);
+        assert!(result.test_results.iter().all(|test| test.status == "BLOCKED" && test.response_code == 403));
+    }
+}
@@ -0,0 +1,159 @@
+//! External payload corpora
+//!
+//! Payloads can be loaded from a file or a directory of files instead of the built-in
+//! lists. Two formats are understood:
+//!
+//! * Text files: one payload per line. The file name (`sqli.txt`, `xss.txt`) sets the
+//!   category, and a `# category: <name>` line switches it for the lines that follow.
+//!   Blank lines and other `#` lines are ignored.
+//! * YAML files (`.yaml`/`.yml`): a map from category to a list of payloads.
+//!
+//! Categories are [`PayloadType`] names or their short aliases (`xss`, `sqli`, `lfi`, `rce`, ...).
+//! `benign` entries replace the smoke test's false-positive control group.
+
+use crate::engine::waf_mode_detector::PayloadType;
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// A single payload loaded from a corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusPayload {
+    pub category: PayloadType,
+    pub payload: String,
+}
+
+/// User-supplied payloads, in the order they were loaded
+#[derive(Debug, Clone, Default)]
+pub struct PayloadCorpus {
+    payloads: Vec<CorpusPayload>,
+}
+
+impl PayloadCorpus {
+    /// Load a corpus file, or every non-hidden file of a directory in name order
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut corpus = Self::default();
+
+        if path.is_dir() {
+            let mut files: Vec<_> = std::fs::read_dir(path)
+                .with_context(|| format!("Failed to read payload directory {}", path.display()))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+                .collect();
+            files.sort();
+            for file in files {
+                corpus.load_file(&file)?;
+            }
+        } else {
+            corpus.load_file(path)?;
+        }
+
+        if corpus.is_empty() {
+            bail!("No payloads found in {}", path.display());
+        }
+        Ok(corpus)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read payload file {}", path.display()))?;
+        let is_yaml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+
+        let parsed = if is_yaml {
+            Self::parse_yaml(&content)
+        } else {
+            let default_category = path.file_stem().and_then(|stem| stem.to_string_lossy().parse().ok());
+            Self::parse_text(&content, default_category)
+        };
+        self.payloads.extend(parsed.with_context(|| format!("Invalid payload file {}", path.display()))?.payloads);
+        Ok(())
+    }
+
+    /// Parse the line-based format; `default_category` applies until a `# category:` line
+    pub fn parse_text(content: &str, default_category: Option<PayloadType>) -> Result<Self> {
+        let mut category = default_category;
+        let mut payloads = Vec::new();
+
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.trim_start().strip_prefix('#') {
+                if let Some(name) = comment.trim().strip_prefix("category:") {
+                    category = Some(name.trim().parse().map_err(|e| anyhow!("line {}: {}", number + 1, e))?);
+                }
+                continue;
+            }
+            let category = category
+                .clone()
+                .ok_or_else(|| anyhow!("line {}: payload has no category (add a '# category: <name>' line)", number + 1))?;
+            payloads.push(CorpusPayload { category, payload: line.to_string() });
+        }
+
+        Ok(Self { payloads })
+    }
+
+    /// Parse the YAML format: `{ <category>: [<payload>, ...] }`
+    pub fn parse_yaml(content: &str) -> Result<Self> {
+        let groups: BTreeMap<String, Vec<String>> = serde_yaml::from_str(content)?;
+        let mut payloads = Vec::new();
+        for (name, values) in groups {
+            let category: PayloadType = name.parse().map_err(|e: String| anyhow!(e))?;
+            payloads.extend(values.into_iter().map(|payload| CorpusPayload { category: category.clone(), payload }));
+        }
+        Ok(Self { payloads })
+    }
+
+    pub fn payloads(&self) -> &[CorpusPayload] {
+        &self.payloads
+    }
+
+    pub fn len(&self) -> usize {
+        self.payloads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payloads.is_empty()
+    }
+
+    /// Payloads grouped by category, keeping their corpus order
+    pub fn by_type(&self) -> HashMap<PayloadType, Vec<String>> {
+        let mut grouped: HashMap<PayloadType, Vec<String>> = HashMap::new();
+        for entry in &self.payloads {
+            grouped.entry(entry.category.clone()).or_default().push(entry.payload.clone());
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_with_category_directives() {
+        let content = "' OR 1=1--\n\n# a comment\n# category: xss\n<svg onload=alert(1)>\n";
+        let corpus = PayloadCorpus::parse_text(content, Some(PayloadType::SqlInjectionBasic)).unwrap();
+
+        assert_eq!(corpus.payloads(), &[
+            CorpusPayload { category: PayloadType::SqlInjectionBasic, payload: "' OR 1=1--".to_string() },
+            CorpusPayload { category: PayloadType::XssBasic, payload: "<svg onload=alert(1)>".to_string() },
+        ]);
+        assert!(PayloadCorpus::parse_text("payload\n", None).is_err());
+        assert!(PayloadCorpus::parse_text("# category: nope\n", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_yaml() {
+        let corpus = PayloadCorpus::parse_yaml("sqli:\n  - \"' OR 1=1--\"\nCommandInjection:\n  - \";id\"\n").unwrap();
+
+        let grouped = corpus.by_type();
+        assert_eq!(grouped[&PayloadType::SqlInjectionBasic], vec!["' OR 1=1--"]);
+        assert_eq!(grouped[&PayloadType::CommandInjection], vec![";id"]);
+        assert!(PayloadCorpus::parse_yaml("bogus: [x]").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Baseline-aware response diffing
+//!
+//! Soft-blocking WAFs answer attacks with `200 OK` and a block page, which status codes
+//! alone cannot tell apart from a normal response. A clean baseline request is captured
+//! once, and every payload response is compared to it structurally: body similarity,
+//! title change, length delta and headers that only appear on the payload response.
+
+use crate::http::HttpResponse;
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Body similarity below which a response counts as a different page
+const DIFFERENT_PAGE_SIMILARITY: f64 = 0.5;
+/// Relative body length change that counts as a different page
+const DIFFERENT_PAGE_LENGTH_DELTA: f64 = 0.5;
+
+/// Phrases typical of block pages; only counted when the baseline does not contain them
+pub const BLOCK_PAGE_KEYWORDS: &[&str] = &[
+    "access denied", "blocked", "forbidden", "not allowed",
+    "security violation", "malicious request", "attack detected",
+    "waf", "firewall", "protection", "security policy", "threat detected",
+];
+
+/// The parts of a clean response that payload responses are compared against
+#[derive(Debug, Clone)]
+pub struct ResponseBaseline {
+    pub status: u16,
+    pub body_length: usize,
+    pub title: Option<String>,
+    body_lower: String,
+    tokens: HashSet<String>,
+    header_names: HashSet<String>,
+}
+
+/// How a payload response differs from the baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseDiff {
+    pub status_changed: bool,
+    /// Jaccard similarity of the word sets of both bodies (1.0 = same words)
+    pub body_similarity: f64,
+    /// Length change relative to the baseline body (0.0 = same length)
+    pub length_delta: f64,
+    pub title_changed: bool,
+    /// Header names present on the response but not on the baseline
+    pub new_headers: Vec<String>,
+    /// Block page phrases present in the response but not in the baseline
+    pub new_block_keywords: Vec<String>,
+}
+
+impl ResponseBaseline {
+    pub fn from_response(response: &HttpResponse) -> Self {
+        Self {
+            status: response.status,
+            body_length: response.body.len(),
+            title: extract_title(&response.body),
+            body_lower: response.body.to_lowercase(),
+            tokens: tokens(&response.body),
+            header_names: response.headers.keys().map(|k| k.to_lowercase()).collect(),
+        }
+    }
+
+    /// Compare `response` against this baseline
+    pub fn diff(&self, response: &HttpResponse) -> ResponseDiff {
+        let response_tokens = tokens(&response.body);
+        let body_similarity = jaccard(&self.tokens, &response_tokens);
+
+        let length_delta = if self.body_length == 0 {
+            if response.body.is_empty() { 0.0 } else { 1.0 }
+        } else {
+            (response.body.len() as f64 - self.body_length as f64).abs() / self.body_length as f64
+        };
+
+        let mut new_headers: Vec<String> = response
+            .headers
+            .keys()
+            .map(|k| k.to_lowercase())
+            .filter(|k| !self.header_names.contains(k))
+            .collect();
+        new_headers.sort();
+        new_headers.dedup();
+
+        let body_lower = response.body.to_lowercase();
+        let new_block_keywords = BLOCK_PAGE_KEYWORDS
+            .iter()
+            .filter(|keyword| body_lower.contains(*keyword) && !self.body_lower.contains(*keyword))
+            .map(|keyword| keyword.to_string())
+            .collect();
+
+        ResponseDiff {
+            status_changed: response.status != self.status,
+            body_similarity,
+            length_delta,
+            title_changed: extract_title(&response.body) != self.title,
+            new_headers,
+            new_block_keywords,
+        }
+    }
+}
+
+impl ResponseDiff {
+    /// The response is structurally a different page from the baseline
+    pub fn is_different_page(&self) -> bool {
+        self.body_similarity < DIFFERENT_PAGE_SIMILARITY
+            || self.title_changed
+            || self.length_delta > DIFFERENT_PAGE_LENGTH_DELTA
+    }
+
+    /// A different page that also introduces block page wording: a block served with an
+    /// unremarkable status code
+    pub fn is_soft_block(&self) -> bool {
+        self.is_different_page() && !self.new_block_keywords.is_empty()
+    }
+
+    /// One-line summary for evidence lists
+    pub fn describe(&self) -> String {
+        let mut parts = vec![
+            format!("similarity {:.0}%", self.body_similarity * 100.0),
+            format!("length delta {:.0}%", self.length_delta * 100.0),
+        ];
+        if self.title_changed {
+            parts.push("title changed".to_string());
+        }
+        if !self.new_headers.is_empty() {
+            parts.push(format!("new headers: {}", self.new_headers.join(", ")));
+        }
+        format!("Baseline diff: {}", parts.join(", "))
+    }
+}
+
+fn title_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap())
+}
+
+fn extract_title(body: &str) -> Option<String> {
+    title_pattern()
+        .captures(body)
+        .map(|captures| captures[1].split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+}
+
+fn tokens(body: &str) -> HashSet<String> {
+    body.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.len() > 1)
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let intersection = a.intersection(b).count();
+    let union = a.len() + b.len() - intersection;
+    intersection as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: headers.iter().copied().collect(),
+            body: body.to_string(),
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    const PAGE: &str = "<html><head><title>Example Shop</title></head><body><h1>Welcome to the shop</h1>\
+        <p>Browse our products and offers for the season</p></body></html>";
+
+    #[test]
+    fn test_same_page_is_not_a_soft_block() {
+        let baseline = ResponseBaseline::from_response(&response(200, &[("server", "nginx")], PAGE));
+        let diff = baseline.diff(&response(200, &[("server", "nginx")], PAGE));
+
+        assert_eq!(diff.body_similarity, 1.0);
+        assert!(!diff.is_different_page());
+        assert!(!diff.is_soft_block());
+    }
+
+    #[test]
+    fn test_block_page_with_200_is_a_soft_block() {
+        let baseline = ResponseBaseline::from_response(&response(200, &[("server", "nginx")], PAGE));
+        let block_page = "<html><head><title>Request Rejected</title></head><body>\
+            The requested URL was rejected. Your request was blocked by the security policy.</body></html>";
+        let diff = baseline.diff(&response(200, &[("server", "nginx"), ("x-block-id", "1")], block_page));
+
+        assert!(!diff.status_changed);
+        assert!(diff.title_changed);
+        assert_eq!(diff.new_headers, vec!["x-block-id"]);
+        assert_eq!(diff.new_block_keywords, vec!["blocked", "security policy"]);
+        assert!(diff.is_soft_block());
+    }
+
+    #[test]
+    fn test_keywords_already_on_the_baseline_do_not_count() {
+        let page = "<title>Firewall products</title><p>Buy our firewall appliance</p>";
+        let baseline = ResponseBaseline::from_response(&response(200, &[], page));
+        let diff = baseline.diff(&response(200, &[], "<title>Other</title><p>Our firewall range</p>"));
+
+        assert!(diff.is_different_page());
+        assert!(!diff.is_soft_block());
+    }
+}
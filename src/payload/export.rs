@@ -0,0 +1,144 @@
+//! Smoke test result export (JSON, CSV, HTML)
+//!
+//! Reports are rendered in memory and written through a temporary file in the
+//! destination directory that is then renamed over the target, so an interrupted
+//! export never leaves a truncated report behind.
+
+use super::waf_smoke_test::SmokeTestResult;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Output format for exported smoke test results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    Html,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Html];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// Format implied by the file extension, if it is a known one
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "htm" => Some(ExportFormat::Html),
+            other => other.parse().ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!(
+                "unknown export format '{}' (expected one of: {})",
+                s,
+                ExportFormat::ALL.map(|f| f.as_str()).join(", ")
+            ))
+    }
+}
+
+/// Render `result` in `format`
+pub fn render(result: &SmokeTestResult, format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(result)?),
+        ExportFormat::Csv => Ok(render_csv(result)),
+        ExportFormat::Html => crate::render::render("smoke_report", &crate::render::views::smoke_test(result)),
+    }
+}
+
+/// Render `result` and atomically write it to `path`
+pub fn export(result: &SmokeTestResult, path: impl AsRef<Path>, format: ExportFormat) -> Result<()> {
+    write_atomic(path.as_ref(), &render(result, format)?)
+}
+
+/// Write `contents` to a temporary file next to `path`, then rename it into place
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut file = NamedTempFile::new_in(directory)
+        .with_context(|| format!("Failed to create temporary file in {}", directory.display()))?;
+    file.write_all(contents.as_bytes())?;
+    file.as_file().sync_all()?;
+    file.persist(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+const CSV_HEADER: &str = "kind,category,location,payload,classification,response_status,response_time_ms";
+
+fn render_csv(result: &SmokeTestResult) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+
+    let rows = result.test_results.iter().map(|test| ("attack", test))
+        .chain(result.control_results.iter().map(|test| ("control", test)));
+    for (kind, test) in rows {
+        let fields = [
+            kind.to_string(),
+            test.category.clone(),
+            test.location.to_string(),
+            test.payload.clone(),
+            format!("{:?}", test.classification),
+            test.response_status.to_string(),
+            test.response_time_ms.to_string(),
+        ];
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("' OR \"1\"=\"1"), "\"' OR \"\"1\"\"=\"\"1\"");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ExportFormat::from_path("report.HTML"), Some(ExportFormat::Html));
+        assert_eq!(ExportFormat::from_path("out/results.csv"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_path("results.txt"), None);
+        assert_eq!("Json".parse::<ExportFormat>(), Ok(ExportFormat::Json));
+    }
+}
@@ -3,9 +3,18 @@
 //! This module implements wafw00f-style detection using malicious payloads
 //! to trigger WAF responses and analyze the differences.
 
+pub mod combined;
+pub mod corpus;
+pub mod diff;
+pub mod export;
 pub mod waf_smoke_test;
 
+pub use corpus::PayloadCorpus;
+pub use waf_smoke_test::PayloadLocation;
+
 use crate::{Evidence, MethodType};
+use crate::engine::waf_mode_detector::PayloadType;
+use crate::error::WafDetectorError;
 use crate::http::HttpClient;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -17,6 +26,8 @@ use std::time::{Duration, Instant};
 pub struct PayloadAnalyzer {
     http_client: Arc<HttpClient>,
     config: PayloadConfig,
+    /// Corpus payloads used instead of the built-in lists
+    custom_payloads: Option<Vec<Payload>>,
 }
 
 /// Configuration for payload analysis
@@ -30,6 +41,8 @@ pub struct PayloadConfig {
     pub request_delay: Duration,
     /// Enable aggressive testing (more payloads)
     pub aggressive_mode: bool,
+    /// Injection points each payload is sent to
+    pub locations: Vec<PayloadLocation>,
 }
 
 /// Categories of payloads for different attack types
@@ -44,6 +57,20 @@ pub enum PayloadCategory {
     NoSQLInjection,
 }
 
+impl PayloadCategory {
+    /// Analyzer category for a smoke test payload type, if it has one
+    pub fn from_payload_type(payload_type: &PayloadType) -> Option<Self> {
+        match payload_type {
+            PayloadType::XssBasic | PayloadType::XssAdvanced => Some(PayloadCategory::XSS),
+            PayloadType::SqlInjectionBasic | PayloadType::SqlInjectionAdvanced => Some(PayloadCategory::SQLInjection),
+            PayloadType::PathTraversal => Some(PayloadCategory::PathTraversal),
+            PayloadType::CommandInjection => Some(PayloadCategory::CommandInjection),
+            PayloadType::FileUpload => Some(PayloadCategory::RemoteFileInclusion),
+            PayloadType::ScannerDetection | PayloadType::Enumeration | PayloadType::Benign => None,
+        }
+    }
+}
+
 /// Individual payload definition
 #[derive(Debug, Clone)]
 pub struct Payload {
@@ -59,6 +86,9 @@ pub struct PayloadAnalysisResult {
     pub detected_waf: Option<String>,
     pub confidence: f64,
     pub blocked_payloads: Vec<BlockedPayload>,
+    /// Payloads sent and blocked per injection point
+    #[serde(default)]
+    pub location_results: Vec<LocationResult>,
     pub baseline_response: BaselineInfo,
     pub analysis_time_ms: u64,
 }
@@ -68,12 +98,22 @@ pub struct PayloadAnalysisResult {
 pub struct BlockedPayload {
     pub category: PayloadCategory,
     pub payload: String,
+    #[serde(default)]
+    pub location: PayloadLocation,
     pub response_status: u16,
     pub response_headers: HashMap<String, String>,
     pub response_body_sample: String,
     pub block_reason: String,
 }
 
+/// How many payloads were blocked at one injection point
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocationResult {
+    pub location: PayloadLocation,
+    pub tested: usize,
+    pub blocked: usize,
+}
+
 /// Baseline response information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BaselineInfo {
@@ -90,6 +130,7 @@ impl Default for PayloadConfig {
             request_timeout: Duration::from_secs(10),
             request_delay: Duration::from_millis(500),
             aggressive_mode: false,
+            locations: vec![PayloadLocation::Query],
         }
     }
 }
@@ -99,6 +140,7 @@ impl PayloadAnalyzer {
         Self {
             http_client: Arc::new(HttpClient::default()),
             config: PayloadConfig::default(),
+            custom_payloads: None,
         }
     }
 
@@ -107,6 +149,33 @@ impl PayloadAnalyzer {
         self
     }
 
+    /// Send payload probes through `client` (timeout, proxy, retries)
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.http_client = Arc::new(client);
+        self
+    }
+
+    /// Replace the built-in payloads with a user-supplied corpus. Scanner, enumeration
+    /// and benign entries have no payload category here and are skipped.
+    pub fn with_corpus(mut self, corpus: &PayloadCorpus) -> Self {
+        self.custom_payloads = Some(
+            corpus
+                .payloads()
+                .iter()
+                .filter_map(|entry| {
+                    let category = PayloadCategory::from_payload_type(&entry.category)?;
+                    Some(Payload {
+                        category,
+                        payload: entry.payload.clone(),
+                        description: format!("Corpus {} payload", entry.category),
+                        expected_blocks: Vec::new(),
+                    })
+                })
+                .collect(),
+        );
+        self
+    }
+
     /// Analyze URL using payload-based probing
     pub async fn analyze(&self, url: &str) -> Result<PayloadAnalysisResult, anyhow::Error> {
         let start_time = Instant::now();
@@ -116,6 +185,7 @@ impl PayloadAnalyzer {
 
         // Step 2: Test payloads
         let blocked_payloads = self.test_payloads(url, &baseline).await?;
+        let location_results = self.location_results(&blocked_payloads);
 
         // Step 3: Analyze results and determine WAF
         let (detected_waf, confidence) = self.analyze_blocked_payloads(&blocked_payloads);
@@ -126,6 +196,7 @@ impl PayloadAnalyzer {
             detected_waf,
             confidence,
             blocked_payloads,
+            location_results,
             baseline_response: baseline,
             analysis_time_ms: analysis_time,
         })
@@ -138,9 +209,17 @@ impl PayloadAnalyzer {
         let response = self.http_client.get(url).await?;
         let response_time = start_time.elapsed().as_millis() as u64;
 
+        // Payload comparisons are meaningless if even the clean request is blocked
+        if response.status == 403 || response.status == 429 {
+            return Err(WafDetectorError::BlockedByTarget {
+                url: url.to_string(),
+                status: response.status,
+            }.into());
+        }
+
         Ok(BaselineInfo {
             status: response.status,
-            headers: response.headers.clone(),
+            headers: response.headers.to_map(),
             body_length: response.body.len(),
             response_time_ms: response_time,
         })
@@ -151,13 +230,21 @@ impl PayloadAnalyzer {
         let mut blocked_payloads = Vec::new();
         let payloads = self.get_test_payloads();
 
-        for payload in payloads {
-            // Add delay to avoid overwhelming the server
-            tokio::time::sleep(self.config.request_delay).await;
-
-            if let Ok(blocked_payload) = self.test_single_payload(base_url, &payload, baseline).await {
-                if let Some(blocked) = blocked_payload {
-                    blocked_payloads.push(blocked);
+        for &location in &self.config.locations {
+            for payload in &payloads {
+                // Add delay to avoid overwhelming the server
+                tokio::time::sleep(self.config.request_delay).await;
+
+                match self.test_single_payload(base_url, payload, location, baseline).await {
+                    Ok(Some(blocked)) => {
+                        tracing::debug!(
+                            payload = %payload.payload, location = ?location, status = blocked.response_status,
+                            reason = %blocked.block_reason, "payload blocked"
+                        );
+                        blocked_payloads.push(blocked);
+                    }
+                    Ok(None) => tracing::debug!(payload = %payload.payload, location = ?location, "payload allowed"),
+                    Err(e) => tracing::debug!(payload = %payload.payload, location = ?location, error = %e, "payload failed"),
                 }
             }
         }
@@ -165,18 +252,30 @@ impl PayloadAnalyzer {
         Ok(blocked_payloads)
     }
 
+    /// Count sent and blocked payloads for every configured location
+    fn location_results(&self, blocked_payloads: &[BlockedPayload]) -> Vec<LocationResult> {
+        let tested = self.get_test_payloads().len();
+        self.config
+            .locations
+            .iter()
+            .map(|&location| LocationResult {
+                location,
+                tested,
+                blocked: blocked_payloads.iter().filter(|b| b.location == location).count(),
+            })
+            .collect()
+    }
+
     /// Test a single payload
     async fn test_single_payload(
         &self,
         base_url: &str,
         payload: &Payload,
+        location: PayloadLocation,
         baseline: &BaselineInfo,
     ) -> Result<Option<BlockedPayload>, anyhow::Error> {
         
-        // Construct URL with payload as query parameter
-        let test_url = format!("{}?test={}", base_url, urlencoding::encode(&payload.payload));
-
-        match self.http_client.get(&test_url).await {
+        match location.send(&self.http_client, base_url, &payload.payload).await {
             Ok(response) => {
                 // Check if response indicates blocking
                 if self.is_blocked_response(&response, baseline, payload) {
@@ -185,8 +284,9 @@ impl PayloadAnalyzer {
                     let blocked = BlockedPayload {
                         category: payload.category.clone(),
                         payload: payload.payload.clone(),
+                        location,
                         response_status: response.status,
-                        response_headers: response.headers.clone(),
+                        response_headers: response.headers.to_map(),
                         response_body_sample: response.body.chars().take(200).collect(),
                         block_reason,
                     };
@@ -200,6 +300,7 @@ impl PayloadAnalyzer {
                     let blocked = BlockedPayload {
                         category: payload.category.clone(),
                         payload: payload.payload.clone(),
+                        location,
                         response_status: 0,
                         response_headers: HashMap::new(),
                         response_body_sample: "Connection refused".to_string(),
@@ -397,6 +498,28 @@ impl PayloadAnalyzer {
 
     /// Get test payloads for different attack categories
     fn get_test_payloads(&self) -> Vec<Payload> {
+        let payloads = match &self.custom_payloads {
+            Some(custom) => custom.clone(),
+            None => self.builtin_payloads(),
+        };
+
+        // Limit payloads per category
+        let mut limited_payloads = Vec::new();
+        let mut category_counts: HashMap<PayloadCategory, usize> = HashMap::new();
+
+        for payload in payloads {
+            let count = category_counts.entry(payload.category.clone()).or_insert(0);
+            if *count < self.config.max_payloads_per_category {
+                limited_payloads.push(payload);
+                *count += 1;
+            }
+        }
+
+        limited_payloads
+    }
+
+    /// Built-in payloads for the different attack categories
+    fn builtin_payloads(&self) -> Vec<Payload> {
         let mut payloads = Vec::new();
 
         // XSS payloads
@@ -480,19 +603,7 @@ impl PayloadAnalyzer {
             payloads.extend(self.get_aggressive_payloads());
         }
 
-        // Limit payloads per category
-        let mut limited_payloads = Vec::new();
-        let mut category_counts: HashMap<PayloadCategory, usize> = HashMap::new();
-
-        for payload in payloads {
-            let count = category_counts.entry(payload.category.clone()).or_insert(0);
-            if *count < self.config.max_payloads_per_category {
-                limited_payloads.push(payload);
-                *count += 1;
-            }
-        }
-
-        limited_payloads
+        payloads
     }
 
     /// Get aggressive payloads for more thorough testing
@@ -539,8 +650,8 @@ impl PayloadAnalyzer {
                         confidence: 0.7,
                         description: format!("Blocked {:?} payload: {}", 
                                            blocked.category, blocked.block_reason),
-                        raw_data: format!("Status: {}, Payload: {}", 
-                                        blocked.response_status, 
+                        raw_data: format!("Status: {}, Location: {}, Payload: {}", 
+                                        blocked.response_status, blocked.location,
                                         blocked.payload.chars().take(50).collect::<String>()),
                         signature_matched: format!("blocked_{:?}_payload", blocked.category)
                             .to_lowercase(),
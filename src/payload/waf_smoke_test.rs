@@ -8,19 +8,34 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use crate::http::HttpClient;
-use crate::engine::waf_mode_detector::{PayloadType, WafMode};
-use tempfile::NamedTempFile;
-use std::io::Write;
+use crate::http::{session::CookieSession, HttpClient, HttpResponse};
+use super::corpus::PayloadCorpus;
+use super::diff::{ResponseBaseline, BLOCK_PAGE_KEYWORDS};
+use super::export::ExportFormat;
+use crate::engine::waf_mode_detector::{self, CategoryMode, PayloadType, WafMode};
 
 /// WAF Smoke Test Configuration
 #[derive(Debug, Clone)]
 pub struct SmokeTestConfig {
     pub timeout_seconds: u64,
     pub delay_between_requests_ms: u64,
+    /// Payload requests in flight at once
     pub max_concurrent_requests: usize,
     pub include_advanced_payloads: bool,
     pub custom_headers: HashMap<String, String>,
+    /// Only send the first N payloads of each category
+    pub payloads_per_category: Option<usize>,
+    /// Suppress the live per-payload output (used when embedding smoke tests in other scans)
+    pub quiet: bool,
+    /// Color the live per-payload results with ANSI escapes
+    pub color: bool,
+    /// Where payloads are placed in the request; every payload is sent once per location
+    pub payload_locations: Vec<PayloadLocation>,
+    /// Also send benign look-alike requests and report the false-positive rate
+    pub benign_controls: bool,
+    /// Send payloads as one client returning the cookies the target sets, instead of
+    /// a fresh client per payload
+    pub session: bool,
 }
 
 impl Default for SmokeTestConfig {
@@ -31,16 +46,196 @@ impl Default for SmokeTestConfig {
             max_concurrent_requests: 3,
             include_advanced_payloads: true,
             custom_headers: HashMap::new(),
+            payloads_per_category: None,
+            quiet: false,
+            color: true,
+            payload_locations: vec![PayloadLocation::Query],
+            benign_controls: true,
+            session: false,
         }
     }
 }
 
+/// Where in the request a payload is placed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadLocation {
+    /// `?test=<payload>` query parameter
+    #[default]
+    Query,
+    /// POST `{"test": "<payload>"}`
+    JsonBody,
+    /// POST `test=<payload>` (application/x-www-form-urlencoded)
+    FormBody,
+    /// POST multipart/form-data with a `test` field
+    Multipart,
+    /// POST GraphQL query with the payload as a string argument
+    GraphQl,
+    /// `Referer` request header
+    Referer,
+    /// `X-Forwarded-For` request header
+    XForwardedFor,
+    /// `User-Agent` request header
+    UserAgent,
+    /// `test=<payload>` cookie
+    Cookie,
+    /// Extra URL path segment (`/base/<payload>`)
+    Path,
+}
+
+const MULTIPART_BOUNDARY: &str = "----WafDetectorBoundary7MA4YWxkTrZu0gW";
+
+impl PayloadLocation {
+    pub const ALL: [PayloadLocation; 10] = [
+        PayloadLocation::Query,
+        PayloadLocation::JsonBody,
+        PayloadLocation::FormBody,
+        PayloadLocation::Multipart,
+        PayloadLocation::GraphQl,
+        PayloadLocation::Referer,
+        PayloadLocation::XForwardedFor,
+        PayloadLocation::UserAgent,
+        PayloadLocation::Cookie,
+        PayloadLocation::Path,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayloadLocation::Query => "query",
+            PayloadLocation::JsonBody => "json",
+            PayloadLocation::FormBody => "form",
+            PayloadLocation::Multipart => "multipart",
+            PayloadLocation::GraphQl => "graphql",
+            PayloadLocation::Referer => "referer",
+            PayloadLocation::XForwardedFor => "x-forwarded-for",
+            PayloadLocation::UserAgent => "user-agent",
+            PayloadLocation::Cookie => "cookie",
+            PayloadLocation::Path => "path",
+        }
+    }
+
+    /// Content type and body for body locations, `None` for locations outside the body
+    pub fn build_body(&self, payload: &str) -> Option<(String, String)> {
+        match self {
+            PayloadLocation::Query
+            | PayloadLocation::Referer
+            | PayloadLocation::XForwardedFor
+            | PayloadLocation::UserAgent
+            | PayloadLocation::Cookie
+            | PayloadLocation::Path => None,
+            PayloadLocation::JsonBody => Some((
+                "application/json".to_string(),
+                serde_json::json!({ "test": payload }).to_string(),
+            )),
+            PayloadLocation::FormBody => Some((
+                "application/x-www-form-urlencoded".to_string(),
+                format!("test={}", urlencoding::encode(payload)),
+            )),
+            PayloadLocation::Multipart => Some((
+                format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+                format!(
+                    "--{b}\r\nContent-Disposition: form-data; name=\"test\"\r\n\r\n{p}\r\n--{b}--\r\n",
+                    b = MULTIPART_BOUNDARY,
+                    p = payload
+                ),
+            )),
+            PayloadLocation::GraphQl => {
+                // A JSON string literal is also a valid GraphQL string literal
+                let argument = serde_json::Value::String(payload.to_string()).to_string();
+                let query = format!("query {{ search(term: {}) {{ id }} }}", argument);
+                Some(("application/json".to_string(), serde_json::json!({ "query": query }).to_string()))
+            }
+        }
+    }
+
+    /// Header name and value for header and cookie locations
+    pub fn build_header(&self, payload: &str) -> Option<(&'static str, String)> {
+        match self {
+            PayloadLocation::Referer => Some(("Referer", payload.to_string())),
+            PayloadLocation::XForwardedFor => Some(("X-Forwarded-For", payload.to_string())),
+            PayloadLocation::UserAgent => Some(("User-Agent", payload.to_string())),
+            // Cookie values cannot carry `;` or spaces, and WAFs URL-decode them before matching
+            PayloadLocation::Cookie => Some(("Cookie", format!("test={}", urlencoding::encode(payload)))),
+            _ => None,
+        }
+    }
+
+    /// Request URL for `payload` at this location; the payload stays out of the URL for
+    /// body and header locations
+    pub fn build_url(&self, base_url: &str, payload: &str) -> String {
+        match self {
+            PayloadLocation::Query => query_url(base_url, payload),
+            PayloadLocation::Path => path_url(base_url, payload),
+            _ => base_url.to_string(),
+        }
+    }
+
+    /// Send `payload` to `url` at this location
+    pub async fn send(&self, client: &HttpClient, url: &str, payload: &str) -> Result<HttpResponse, anyhow::Error> {
+        let request_url = self.build_url(url, payload);
+        if let Some((content_type, body)) = self.build_body(payload) {
+            client.post_with_headers(&request_url, &body, &[("Content-Type", &content_type)]).await
+        } else if let Some((name, value)) = self.build_header(payload) {
+            client.get_with_headers(&request_url, &[(name, &value)]).await
+        } else {
+            client.get(&request_url).await
+        }
+    }
+}
+
+/// `base_url` with the payload substituted for `FUZZ`, or appended as the `test` parameter
+fn query_url(base_url: &str, payload: &str) -> String {
+    if base_url.contains("FUZZ") {
+        base_url.replace("FUZZ", payload)
+    } else if base_url.contains('?') {
+        format!("{}&test={}", base_url, urlencoding::encode(payload))
+    } else {
+        format!("{}?test={}", base_url, urlencoding::encode(payload))
+    }
+}
+
+/// `base_url` with the percent-encoded payload appended as a final path segment
+fn path_url(base_url: &str, payload: &str) -> String {
+    match url::Url::parse(base_url) {
+        Ok(mut url) if !url.cannot_be_a_base() => {
+            if let Ok(mut segments) = url.path_segments_mut() {
+                segments.pop_if_empty().push(payload);
+            }
+            url.to_string()
+        }
+        _ => format!("{}/{}", base_url.trim_end_matches('/'), urlencoding::encode(payload)),
+    }
+}
+
+impl std::fmt::Display for PayloadLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PayloadLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PayloadLocation::ALL
+            .into_iter()
+            .find(|location| location.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!(
+                "unknown payload location '{}' (expected one of: {})",
+                s,
+                PayloadLocation::ALL.map(|l| l.as_str()).join(", ")
+            ))
+    }
+}
+
 /// Test result for a single payload
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PayloadTestResult {
     pub category: String,
     pub payload: String,
     pub payload_type: PayloadType,
+    #[serde(default)]
+    pub location: PayloadLocation,
     pub response_status: u16,
     pub response_time_ms: u64,
     pub classification: PayloadClassification,
@@ -64,6 +259,11 @@ pub enum PayloadClassification {
 }
 
 impl PayloadClassification {
+    /// Whether the WAF interfered with the request (blocked, rate limited or challenged)
+    pub fn is_interference(&self) -> bool {
+        matches!(self, PayloadClassification::Blocked | PayloadClassification::RateLimited | PayloadClassification::Challenge)
+    }
+
     /// Get the color code for terminal output
     pub fn color_code(&self) -> &'static str {
         match self {
@@ -103,14 +303,26 @@ impl PayloadClassification {
 pub struct SmokeTestResult {
     pub url: String,
     pub test_results: Vec<PayloadTestResult>,
+    /// Benign control requests; kept out of `test_results` so they do not skew effectiveness
+    #[serde(default)]
+    pub control_results: Vec<PayloadTestResult>,
     pub summary: TestSummary,
+    /// Summary per injection point, in the order the locations were tested
+    #[serde(default)]
+    pub location_summaries: Vec<LocationSummary>,
     pub waf_mode: Option<WafMode>,
+    /// Mode observed per payload category (e.g. SQLi blocked, XSS only monitored)
+    #[serde(default)]
+    pub category_modes: Vec<CategoryMode>,
     pub detected_waf: Option<String>,
     pub detected_cdn: Option<String>,
     pub recommendations: Vec<String>,
     pub total_time_ms: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub is_smoke_test: bool,
+    /// The run was interrupted; results cover only the payloads that completed
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
 /// Summary statistics for the smoke test
@@ -124,6 +336,39 @@ pub struct TestSummary {
     pub challenge_count: usize,
     pub effectiveness_percentage: f64,
     pub average_response_time_ms: f64,
+    /// Benign control requests sent
+    #[serde(default)]
+    pub control_count: usize,
+    /// Benign control requests that were blocked, rate limited or challenged
+    #[serde(default)]
+    pub false_positive_count: usize,
+    #[serde(default)]
+    pub false_positive_percentage: f64,
+    /// Per attack category coverage, ordered by OWASP category then payload type
+    #[serde(default)]
+    pub category_coverage: Vec<CategoryCoverage>,
+}
+
+/// How well one attack category is covered by the WAF's rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCoverage {
+    pub category: String,
+    /// OWASP Top 10 (2021) category, e.g. "A03:2021 Injection"
+    pub owasp_category: String,
+    pub tested: usize,
+    pub blocked: usize,
+    pub blocked_percentage: f64,
+    /// Up to three payloads of this category that were allowed through
+    pub bypassed_examples: Vec<String>,
+}
+
+const MAX_BYPASS_EXAMPLES: usize = 3;
+
+/// Summary statistics for the payloads sent at one injection point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationSummary {
+    pub location: PayloadLocation,
+    pub summary: TestSummary,
 }
 
 /// WAF Smoke Test Engine
@@ -131,20 +376,47 @@ pub struct WafSmokeTest {
     http_client: HttpClient,
     config: SmokeTestConfig,
     payloads: HashMap<PayloadType, Vec<String>>,
+    progress: Option<tokio::sync::mpsc::UnboundedSender<PayloadTestResult>>,
 }
 
 impl WafSmokeTest {
     pub fn new(config: SmokeTestConfig) -> Result<Self, anyhow::Error> {
-        let http_client = HttpClient::new()?;
+        let mut http_client = HttpClient::new()?;
+        if config.session {
+            http_client = http_client.with_cookie_session(std::sync::Arc::new(CookieSession::new()));
+        }
         let payloads = Self::initialize_advanced_payloads();
 
         Ok(Self {
             http_client,
             config,
             payloads,
+            progress: None,
         })
     }
 
+    /// Replace the built-in payload lists with a user-supplied corpus. The built-in
+    /// benign controls are kept unless the corpus brings its own.
+    pub fn with_corpus(mut self, corpus: &PayloadCorpus) -> Self {
+        let builtin_controls = self.payloads.remove(&PayloadType::Benign);
+        self.payloads = corpus.by_type();
+        if let Some(controls) = builtin_controls {
+            self.payloads.entry(PayloadType::Benign).or_insert(controls);
+        }
+        self
+    }
+
+    /// Receive every payload result as soon as it is classified
+    pub fn with_progress(mut self, sender: tokio::sync::mpsc::UnboundedSender<PayloadTestResult>) -> Self {
+        self.progress = Some(sender);
+        self
+    }
+
+    /// Number of requests a run will send, controls included
+    pub fn payload_count(&self) -> usize {
+        self.payload_jobs().len()
+    }
+
     /// Initialize comprehensive attack payloads for testing
     fn initialize_advanced_payloads() -> HashMap<PayloadType, Vec<String>> {
         let mut payloads = HashMap::new();
@@ -227,61 +499,163 @@ impl WafSmokeTest {
             "wp-config.php".to_string(),
         ]);
 
+        // Benign controls - legitimate input that shares tokens with attack signatures
+        payloads.insert(PayloadType::Benign, vec![
+            "O'Brien".to_string(),
+            "select a product".to_string(),
+            "SGVsbG8gV29ybGQhIFRoaXMgaXMgYSBsZWdpdCBibG9i".to_string(),
+            "Tom & Jerry <3".to_string(),
+            "drop by the office".to_string(),
+            "1 or 2 items".to_string(),
+            "script writing workshop".to_string(),
+            "100% cotton -- size M".to_string(),
+        ]);
+
         payloads
     }
 
     /// Run comprehensive WAF smoke test
     pub async fn run_test(&self, url: &str) -> Result<SmokeTestResult, anyhow::Error> {
+        self.run_test_until(url, std::future::pending::<()>()).await
+    }
+
+    /// Run the smoke test until every payload has been sent or `cancel` completes.
+    /// On cancellation in-flight requests are dropped and the payloads finished so far
+    /// are returned as a partial result with `cancelled` set.
+    pub async fn run_test_until<F>(&self, url: &str, cancel: F) -> Result<SmokeTestResult, anyhow::Error>
+    where
+        F: std::future::Future<Output = ()>,
+    {
+        use futures::stream::{self, StreamExt};
+
         let start_time = Instant::now();
         let mut test_results = Vec::new();
+        let mut control_results = Vec::new();
 
-        println!("🔍 Starting Advanced WAF Effectiveness Test");
-        println!("🎯 Target: {}", url);
-        println!("═══════════════════════════════════════════════════════════════");
+        if !self.config.quiet {
+            eprintln!("🔍 Starting Advanced WAF Effectiveness Test");
+            eprintln!("🎯 Target: {}", url);
+            eprintln!("═══════════════════════════════════════════════════════════════");
+        }
 
-        // Test each payload type
-        for (payload_type, payloads) in &self.payloads {
-            for payload in payloads {
-                let result = self.test_single_payload(url, payload_type.clone(), payload).await?;
-                test_results.push(result);
+        // A clean request with the same parameter lets classification spot soft blocks
+        let baseline = match self.http_client.get(&PayloadLocation::Query.build_url(url, "")).await {
+            Ok(response) => Some(ResponseBaseline::from_response(&response)),
+            Err(_) => None,
+        };
+        let baseline = baseline.as_ref();
 
-                // Delay between requests to avoid overwhelming the target
-                sleep(Duration::from_millis(self.config.delay_between_requests_ms)).await;
+        let jobs = self.payload_jobs();
+        let mut progress: HashMap<PayloadType, (usize, usize, usize)> = HashMap::new();
+        for (_, payload_type, _) in &jobs {
+            progress.entry(payload_type.clone()).or_insert((0, 0, 0)).1 += 1;
+        }
+
+        // Payloads run through a bounded stream; each slot waits out the delay after its
+        // request, so the delay still throttles every concurrent lane
+        let delay = Duration::from_millis(self.config.delay_between_requests_ms);
+        let cancelled = std::sync::atomic::AtomicBool::new(false);
+        let cancel = async {
+            cancel.await;
+            cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+        };
+        let mut results = std::pin::pin!(stream::iter(jobs)
+            .map(|(location, payload_type, payload)| async move {
+                let result = self.test_single_payload(url, payload_type, &payload, location, baseline).await;
+                sleep(delay).await;
+                result
+            })
+            .buffered(self.config.max_concurrent_requests.max(1))
+            .take_until(cancel));
+
+        while let Some(result) = results.next().await {
+            let result = result?;
+            if let Some(progress) = &self.progress {
+                let _ = progress.send(result.clone());
+            }
+
+            let entry = progress.entry(result.payload_type.clone()).or_insert((0, 0, 0));
+            entry.0 += 1;
+            if result.classification.is_interference() {
+                entry.2 += 1;
+            }
+            let (done, total, blocked) = *entry;
+            if !self.config.quiet && done == total {
+                eprintln!("   ✓ {} complete: {}/{} payloads, {} blocked", result.payload_type, done, total, blocked);
             }
+
+            if result.payload_type == PayloadType::Benign {
+                control_results.push(result);
+            } else {
+                test_results.push(result);
+            }
+        }
+        let cancelled = cancelled.load(std::sync::atomic::Ordering::Relaxed);
+
+        if cancelled && !self.config.quiet {
+            eprintln!("⚠️  Smoke test cancelled - reporting {} completed payloads", test_results.len() + control_results.len());
         }
 
         let total_time = start_time.elapsed();
 
         // Analyze results
-        let summary = self.calculate_summary(&test_results);
+        let summary = self.calculate_summary(&test_results, &control_results);
+        let location_summaries = self.calculate_location_summaries(&test_results, &control_results);
         let waf_mode = self.determine_waf_mode(&test_results);
+        let category_modes = self.determine_category_modes(&test_results);
         let detected_waf = self.identify_waf_from_results(&test_results);
         let recommendations = self.generate_recommendations(&summary, &waf_mode, &detected_waf);
 
         let result = SmokeTestResult {
             url: url.to_string(),
             test_results,
+            control_results,
             summary,
+            location_summaries,
             waf_mode,
+            category_modes,
             detected_waf,
             detected_cdn: None,
             recommendations,
             total_time_ms: total_time.as_millis() as u64,
             timestamp: chrono::Utc::now(),
             is_smoke_test: true,
+            cancelled,
         };
 
         Ok(result)
     }
 
+    /// Every (location, type, payload) combination the configuration asks for
+    fn payload_jobs(&self) -> Vec<(PayloadLocation, PayloadType, String)> {
+        let per_category = self.config.payloads_per_category.unwrap_or(usize::MAX);
+        let mut jobs = Vec::new();
+        for &location in &self.config.payload_locations {
+            for (payload_type, payloads) in &self.payloads {
+                // Scanner signatures are sent as User-Agent headers, not as placeable payloads
+                if *payload_type == PayloadType::ScannerDetection && location != PayloadLocation::Query {
+                    continue;
+                }
+                if *payload_type == PayloadType::Benign && !self.config.benign_controls {
+                    continue;
+                }
+                for payload in payloads.iter().take(per_category) {
+                    jobs.push((location, payload_type.clone(), payload.clone()));
+                }
+            }
+        }
+        jobs
+    }
+
     /// Test a single payload against the target
     async fn test_single_payload(
         &self,
         url: &str,
         payload_type: PayloadType,
         payload: &str,
+        location: PayloadLocation,
+        baseline: Option<&ResponseBaseline>,
     ) -> Result<PayloadTestResult, anyhow::Error> {
-        let test_url = self.build_test_url(url, payload)?;
         let start_time = Instant::now();
 
         // For scanner detection, use realistic User-Agent headers instead of query params
@@ -293,16 +667,19 @@ impl WafSmokeTest {
                 "nessus" => "Mozilla/5.0 (compatible; Nessus; https://www.tenable.com/)",
                 "burpsuite" => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 BurpSuite",
                 "acunetix" => "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 Acunetix/1.0",
-                _ => "WAF-Detector/1.0 Scanner Test",
+                // Corpus-supplied scanner signatures are sent verbatim
+                _ => payload,
             };
             
             match self.http_client.get_with_headers(url, &[("User-Agent", scanner_user_agent)]).await {
                 Ok(resp) => resp,
                 Err(e) => {
+                    tracing::debug!(payload_type = ?payload_type, location = ?location, payload, error = %e, "payload request failed");
                     return Ok(PayloadTestResult {
                         category: format!("{:?}", payload_type),
                         payload: payload.to_string(),
                         payload_type,
+                        location,
                         response_status: 0,
                         response_time_ms: start_time.elapsed().as_millis() as u64,
                         classification: PayloadClassification::Error,
@@ -312,14 +689,16 @@ impl WafSmokeTest {
                 }
             }
         } else {
-            // Regular payload testing via query parameters
-            match self.http_client.get(&test_url).await {
+            // Query, body, header, cookie and path placements
+            match location.send(&self.http_client, url, payload).await {
                 Ok(resp) => resp,
                 Err(e) => {
+                    tracing::debug!(payload_type = ?payload_type, location = ?location, payload, error = %e, "payload request failed");
                     return Ok(PayloadTestResult {
                         category: format!("{:?}", payload_type),
                         payload: payload.to_string(),
                         payload_type,
+                        location,
                         response_status: 0,
                         response_time_ms: start_time.elapsed().as_millis() as u64,
                         classification: PayloadClassification::Error,
@@ -333,7 +712,11 @@ impl WafSmokeTest {
         let response_time = start_time.elapsed();
 
         // Classify the response
-        let (classification, evidence, waf_indicators) = self.classify_response(&response, payload);
+        let (classification, evidence, waf_indicators) = self.classify_response(&response, payload, baseline);
+        tracing::debug!(
+            payload_type = ?payload_type, location = ?location, payload, status = response.status,
+            classification = ?classification, elapsed_ms = response_time.as_millis() as u64, "payload tested"
+        );
 
         // For scanner detection, add a special note about what's being tested
         let mut final_evidence = evidence;
@@ -342,12 +725,15 @@ impl WafSmokeTest {
         }
         
         // Print real-time result
-        self.print_test_result(&payload_type, payload, &classification, response.status, response_time.as_millis() as u64);
+        if !self.config.quiet {
+            self.print_test_result(&payload_type, location, payload, &classification, response.status, response_time.as_millis() as u64);
+        }
 
         Ok(PayloadTestResult {
             category: format!("{:?}", payload_type),
             payload: payload.to_string(),
             payload_type,
+            location,
             response_status: response.status,
             response_time_ms: response_time.as_millis() as u64,
             classification,
@@ -356,29 +742,18 @@ impl WafSmokeTest {
         })
     }
 
-    /// Build test URL with payload
-    fn build_test_url(&self, base_url: &str, payload: &str) -> Result<String, anyhow::Error> {
-        let url = if base_url.contains("FUZZ") {
-            base_url.replace("FUZZ", payload)
-        } else if base_url.contains('?') {
-            format!("{}&test={}", base_url, urlencoding::encode(payload))
-        } else {
-            format!("{}?test={}", base_url, urlencoding::encode(payload))
-        };
-        Ok(url)
-    }
-
     /// Classify the response based on status code, headers, and body
     fn classify_response(
         &self,
         response: &crate::http::HttpResponse,
         payload: &str,
+        baseline: Option<&ResponseBaseline>,
     ) -> (PayloadClassification, Vec<String>, Vec<String>) {
         let mut evidence = Vec::new();
         let mut waf_indicators = Vec::new();
 
         // Check status codes
-        let classification = match response.status {
+        let mut classification = match response.status {
             403 => {
                 evidence.push("HTTP 403 Forbidden - Request blocked".to_string());
                 PayloadClassification::Blocked
@@ -447,19 +822,29 @@ impl WafSmokeTest {
         }
 
         // Blocking page indicators
-        let blocking_keywords = [
-            "access denied", "blocked", "forbidden", "not allowed",
-            "security violation", "malicious request", "attack detected",
-            "waf", "firewall", "protection", "security policy", "threat detected"
-        ];
-
-        for keyword in &blocking_keywords {
+        for keyword in BLOCK_PAGE_KEYWORDS {
             if body_lower.contains(keyword) {
                 evidence.push(format!("Blocking keyword detected: {}", keyword));
                 break;
             }
         }
 
+        // Soft blocks: an unremarkable status with a page that differs from the baseline
+        if classification == PayloadClassification::Allowed {
+            if let Some(baseline) = baseline {
+                let diff = baseline.diff(response);
+                evidence.push(diff.describe());
+                if diff.is_soft_block() {
+                    evidence.push(format!(
+                        "Soft block: block page served with HTTP {} ({})",
+                        response.status,
+                        diff.new_block_keywords.join(", ")
+                    ));
+                    classification = PayloadClassification::Blocked;
+                }
+            }
+        }
+
         // Check if payload is reflected (monitoring mode indicator)
         if classification == PayloadClassification::Allowed && response.body.contains(payload) {
             evidence.push("Payload reflected in response (possible monitoring mode)".to_string());
@@ -472,25 +857,25 @@ impl WafSmokeTest {
     fn print_test_result(
         &self,
         payload_type: &PayloadType,
+        location: PayloadLocation,
         payload: &str,
         classification: &PayloadClassification,
         status_code: u16,
         response_time_ms: u64,
     ) {
-        let color = classification.color_code();
-        let reset = "\x1b[0m";
+        let (color, reset) = if self.config.color { (classification.color_code(), "\x1b[0m") } else { ("", "") };
         let emoji = classification.emoji();
         
-        let payload_display = if payload.len() > 30 {
-            format!("{}...", &payload[..27])
-        } else {
-            payload.to_string()
-        };
+        let payload_display = crate::utils::pad_display(&crate::utils::truncate_display(payload, 30), 30);
 
-        println!(
-            "{} {:<20} │ {:<30} │ {}{:<12}{} │ {:>3} │ {:>4}ms",
+        eprintln!(
+            "{} {:<20} │ {} │ {}{:<12}{} │ {:>3} │ {:>4}ms",
             emoji,
-            format!("{:?}", payload_type),
+            if location == PayloadLocation::Query {
+                format!("{:?}", payload_type)
+            } else {
+                format!("{:?}@{}", payload_type, location)
+            },
             payload_display,
             color,
             classification.display_text(),
@@ -501,7 +886,7 @@ impl WafSmokeTest {
     }
 
     /// Calculate summary statistics
-    fn calculate_summary(&self, results: &[PayloadTestResult]) -> TestSummary {
+    fn calculate_summary(&self, results: &[PayloadTestResult], controls: &[PayloadTestResult]) -> TestSummary {
         let total_tests = results.len();
         let blocked_count = results.iter().filter(|r| r.classification == PayloadClassification::Blocked).count();
         let allowed_count = results.iter().filter(|r| r.classification == PayloadClassification::Allowed).count();
@@ -521,6 +906,17 @@ impl WafSmokeTest {
             0.0
         };
 
+        let category_coverage = self.calculate_category_coverage(results);
+
+        // Any interference with a benign request counts as over-blocking
+        let control_count = controls.len();
+        let false_positive_count = controls.iter().filter(|r| r.classification.is_interference()).count();
+        let false_positive_percentage = if control_count > 0 {
+            (false_positive_count as f64 / control_count as f64) * 100.0
+        } else {
+            0.0
+        };
+
         TestSummary {
             total_tests,
             blocked_count,
@@ -530,9 +926,65 @@ impl WafSmokeTest {
             challenge_count,
             effectiveness_percentage,
             average_response_time_ms,
+            control_count,
+            false_positive_count,
+            false_positive_percentage,
+            category_coverage,
         }
     }
 
+    /// Coverage matrix of attack categories, blocked counting any WAF interference
+    fn calculate_category_coverage(&self, results: &[PayloadTestResult]) -> Vec<CategoryCoverage> {
+        let mut coverage: Vec<CategoryCoverage> = Vec::new();
+        for result in results {
+            let index = match coverage.iter().position(|c| c.category == result.category) {
+                Some(index) => index,
+                None => {
+                    coverage.push(CategoryCoverage {
+                        category: result.category.clone(),
+                        owasp_category: result.payload_type.owasp_category().to_string(),
+                        tested: 0,
+                        blocked: 0,
+                        blocked_percentage: 0.0,
+                        bypassed_examples: Vec::new(),
+                    });
+                    coverage.len() - 1
+                }
+            };
+            let entry = &mut coverage[index];
+            entry.tested += 1;
+            if result.classification.is_interference() {
+                entry.blocked += 1;
+            } else if result.classification == PayloadClassification::Allowed
+                && entry.bypassed_examples.len() < MAX_BYPASS_EXAMPLES
+                && !entry.bypassed_examples.contains(&result.payload)
+            {
+                entry.bypassed_examples.push(result.payload.clone());
+            }
+        }
+
+        for entry in &mut coverage {
+            entry.blocked_percentage = (entry.blocked as f64 / entry.tested as f64) * 100.0;
+        }
+        coverage.sort_by(|a, b| a.owasp_category.cmp(&b.owasp_category).then_with(|| a.category.cmp(&b.category)));
+        coverage
+    }
+
+    /// Summary statistics split by injection point
+    fn calculate_location_summaries(&self, results: &[PayloadTestResult], controls: &[PayloadTestResult]) -> Vec<LocationSummary> {
+        let at = |results: &[PayloadTestResult], location| -> Vec<PayloadTestResult> {
+            results.iter().filter(|r| r.location == location).cloned().collect()
+        };
+        self.config
+            .payload_locations
+            .iter()
+            .map(|&location| LocationSummary {
+                location,
+                summary: self.calculate_summary(&at(results, location), &at(controls, location)),
+            })
+            .collect()
+    }
+
     /// Determine WAF mode based on test results
     fn determine_waf_mode(&self, results: &[PayloadTestResult]) -> Option<WafMode> {
         let total_tests = results.len();
@@ -568,6 +1020,19 @@ impl WafSmokeTest {
         }
     }
 
+    /// Determine the WAF mode of each payload category on its own
+    fn determine_category_modes(&self, results: &[PayloadTestResult]) -> Vec<CategoryMode> {
+        let outcomes: Vec<waf_mode_detector::ProbeOutcome> = results
+            .iter()
+            .map(|r| waf_mode_detector::ProbeOutcome {
+                payload_type: r.payload_type.clone(),
+                blocked: r.classification.is_interference(),
+                reflected: r.evidence.iter().any(|e| e.contains("reflected")),
+            })
+            .collect();
+        waf_mode_detector::category_modes(&outcomes)
+    }
+
     /// Identify WAF type from test results
     fn identify_waf_from_results(&self, results: &[PayloadTestResult]) -> Option<String> {
         let mut waf_votes: HashMap<String, usize> = HashMap::new();
@@ -608,6 +1073,13 @@ impl WafSmokeTest {
             }
         }
 
+        if summary.false_positive_count > 0 {
+            recommendations.push(format!(
+                "⚠️ {} of {} benign control requests were blocked ({:.1}% false positives) - review rules for over-blocking.",
+                summary.false_positive_count, summary.control_count, summary.false_positive_percentage
+            ));
+        }
+
         // Mode-specific recommendations
         if let Some(mode) = waf_mode {
             match mode {
@@ -660,6 +1132,9 @@ impl WafSmokeTest {
         println!("║                           WAF EFFECTIVENESS TEST RESULTS                     ║");
         println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
         println!("║ Target URL: {:<65} ║", self.truncate_string(&result.url, 65));
+        if result.cancelled {
+            println!("║ Status: {:<69} ║", "CANCELLED - partial results");
+        }
         
         if let Some(waf) = &result.detected_waf {
             println!("║ Detected WAF: {:<61} ║", waf);
@@ -678,6 +1153,44 @@ impl WafSmokeTest {
                 s.error_count, s.rate_limited_count, s.challenge_count);
         println!("║ Effectiveness: {:<6.1}% │ Avg Response: {:<6.0}ms │ Total Time: {:<6}ms ║", 
                 s.effectiveness_percentage, s.average_response_time_ms, result.total_time_ms);
+        if s.control_count > 0 {
+            println!("║ False Positives: {:<4} of {:<4} benign controls ({:>5.1}%){:<22} ║",
+                    s.false_positive_count, s.control_count, s.false_positive_percentage, "");
+        }
+
+        if !s.category_coverage.is_empty() {
+            println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
+            println!("║ COVERAGE BY CATEGORY:                                                        ║");
+            for coverage in &s.category_coverage {
+                let owasp_id = coverage.owasp_category.split_whitespace().next().unwrap_or("");
+                let bypass = coverage.bypassed_examples.first().map(|p| self.truncate_string(p, 23)).unwrap_or_default();
+                println!("║ {:<20} │ {:<8} │ {:>3}/{:<3} │ {:>5.1}% │ {:<23} ║",
+                        coverage.category, owasp_id, coverage.blocked, coverage.tested,
+                        coverage.blocked_percentage, bypass);
+            }
+        }
+
+        if !result.category_modes.is_empty() {
+            println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
+            println!("║ MODE BY CATEGORY:                                                            ║");
+            for category in &result.category_modes {
+                println!("║ {:<75} ║", format!(
+                    "{:<24} │ {:<11} │ {:>3}/{:<3} blocked",
+                    category.payload_type.to_string(), category.mode.to_string(), category.blocked, category.tested
+                ));
+            }
+        }
+
+        if result.location_summaries.len() > 1 {
+            println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
+            println!("║ BY INJECTION POINT:                                                          ║");
+            for location in &result.location_summaries {
+                let ls = &location.summary;
+                println!("║ {:<15} │ Tests: {:<5} │ Blocked: {:<6} │ Allowed: {:<6} │ {:>6.1}% ║",
+                        location.location.as_str(), ls.total_tests, ls.blocked_count, ls.allowed_count,
+                        ls.effectiveness_percentage);
+            }
+        }
         
         println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
         println!("║ RECOMMENDATIONS:                                                             ║");
@@ -691,20 +1204,26 @@ impl WafSmokeTest {
         println!("╚═══════════════════════════════════════════════════════════════════════════════╝");
     }
 
-    /// Export results to JSON file
+    /// Export results to a JSON file
     pub fn export_json(&self, result: &SmokeTestResult, output_file: &str) -> Result<(), anyhow::Error> {
-        let json = serde_json::to_string_pretty(result)?;
-        let mut temp_file = NamedTempFile::new()?;
-        temp_file.write_all(json.as_bytes())?;
-        println!("📄 Results exported to: {}", output_file);
+        self.export(result, output_file, ExportFormat::Json)
+    }
+
+    /// Export results to `output_file` in the given format, replacing it atomically
+    pub fn export(&self, result: &SmokeTestResult, output_file: &str, format: ExportFormat) -> Result<(), anyhow::Error> {
+        super::export::export(result, output_file, format)?;
+        if !self.config.quiet {
+            eprintln!("📄 Results exported to: {} ({})", output_file, format);
+        }
         Ok(())
     }
 
     fn truncate_string(&self, s: &str, max_len: usize) -> String {
-        if s.len() <= max_len {
+        // Count characters, not bytes, so multi-byte corpus payloads cannot split a char
+        if s.chars().count() <= max_len {
             s.to_string()
         } else {
-            format!("{}...", &s[..max_len-3])
+            format!("{}...", s.chars().take(max_len - 3).collect::<String>())
         }
     }
 }
@@ -726,14 +1245,32 @@ mod tests {
         // Test blocked response
         let response = crate::http::HttpResponse {
             status: 403,
-            headers: std::collections::HashMap::new(),
+            headers: Default::default(),
             body: "Access Denied".to_string(),
             url: "test".to_string(),
+            ..Default::default()
         };
         
-        let (classification, evidence, _) = smoke_test.classify_response(&response, "test");
+        let (classification, evidence, _) = smoke_test.classify_response(&response, "test", None);
         assert_eq!(classification, PayloadClassification::Blocked);
         assert!(!evidence.is_empty());
+
+        // A 200 block page is only recognised against a baseline
+        let page = |body: &str| crate::http::HttpResponse {
+            status: 200,
+            headers: Default::default(),
+            body: body.to_string(),
+            url: "test".to_string(),
+            ..Default::default()
+        };
+        let baseline = ResponseBaseline::from_response(&page("<title>Home</title><p>Latest news and articles</p>"));
+        let block_page = page("<title>Request rejected</title><p>Malicious request detected, access denied</p>");
+
+        let (classification, _, _) = smoke_test.classify_response(&block_page, "test", None);
+        assert_eq!(classification, PayloadClassification::Allowed);
+        let (classification, evidence, _) = smoke_test.classify_response(&block_page, "test", Some(&baseline));
+        assert_eq!(classification, PayloadClassification::Blocked);
+        assert!(evidence.iter().any(|e| e.starts_with("Soft block")));
     }
 
     #[test]
@@ -743,6 +1280,7 @@ mod tests {
                 category: "XSS".to_string(),
                 payload: "test".to_string(),
                 payload_type: PayloadType::XssBasic,
+                location: PayloadLocation::Query,
                 response_status: 403,
                 response_time_ms: 100,
                 classification: PayloadClassification::Blocked,
@@ -753,6 +1291,7 @@ mod tests {
                 category: "SQLi".to_string(),
                 payload: "test".to_string(),
                 payload_type: PayloadType::SqlInjectionBasic,
+                location: PayloadLocation::JsonBody,
                 response_status: 200,
                 response_time_ms: 150,
                 classification: PayloadClassification::Allowed,
@@ -762,11 +1301,99 @@ mod tests {
         ];
         
         let smoke_test = WafSmokeTest::default();
-        let summary = smoke_test.calculate_summary(&results);
+        let summary = smoke_test.calculate_summary(&results, &[]);
         
         assert_eq!(summary.total_tests, 2);
         assert_eq!(summary.blocked_count, 1);
         assert_eq!(summary.allowed_count, 1);
         assert_eq!(summary.effectiveness_percentage, 50.0);
+        assert_eq!(summary.control_count, 0);
+
+        // The same responses to benign controls mean one false positive out of two
+        let summary = smoke_test.calculate_summary(&[], &results);
+        assert_eq!(summary.control_count, 2);
+        assert_eq!(summary.false_positive_count, 1);
+        assert_eq!(summary.false_positive_percentage, 50.0);
+    }
+
+    #[test]
+    fn test_category_coverage() {
+        let result = |payload_type: PayloadType, payload: &str, classification| PayloadTestResult {
+            category: format!("{:?}", payload_type),
+            payload: payload.to_string(),
+            payload_type,
+            location: PayloadLocation::Query,
+            response_status: 0,
+            response_time_ms: 0,
+            classification,
+            evidence: vec![],
+            waf_indicators: vec![],
+        };
+        let results = vec![
+            result(PayloadType::SqlInjectionBasic, "' OR 1=1--", PayloadClassification::Blocked),
+            result(PayloadType::SqlInjectionBasic, "1 UNION SELECT 1", PayloadClassification::Allowed),
+            result(PayloadType::PathTraversal, "../../etc/passwd", PayloadClassification::Challenge),
+        ];
+
+        let coverage = WafSmokeTest::default().calculate_summary(&results, &[]).category_coverage;
+
+        assert_eq!(coverage.len(), 2);
+        assert_eq!(coverage[0].owasp_category, "A01:2021 Broken Access Control");
+        assert_eq!(coverage[0].blocked_percentage, 100.0);
+        assert_eq!(coverage[1].category, "SqlInjectionBasic");
+        assert_eq!((coverage[1].tested, coverage[1].blocked), (2, 1));
+        assert_eq!(coverage[1].bypassed_examples, vec!["1 UNION SELECT 1"]);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_body_payload_locations() {
+        let payload = "' OR \"1\"='1";
+
+        assert_eq!(PayloadLocation::Query.build_body(payload), None);
+
+        let (content_type, body) = PayloadLocation::JsonBody.build_body(payload).unwrap();
+        assert_eq!(content_type, "application/json");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap()["test"], payload);
+
+        let (_, body) = PayloadLocation::FormBody.build_body(payload).unwrap();
+        assert_eq!(body, format!("test={}", urlencoding::encode(payload)));
+
+        let (content_type, body) = PayloadLocation::Multipart.build_body(payload).unwrap();
+        assert!(content_type.starts_with("multipart/form-data; boundary="));
+        assert!(body.contains("name=\"test\"\r\n\r\n' OR \"1\"='1\r\n"));
+
+        let (_, body) = PayloadLocation::GraphQl.build_body(payload).unwrap();
+        let query = serde_json::from_str::<serde_json::Value>(&body).unwrap()["query"].as_str().unwrap().to_string();
+        assert_eq!(query, "query { search(term: \"' OR \\\"1\\\"='1\") { id } }");
+    }
+
+    #[test]
+    fn test_payload_location_parsing() {
+        assert_eq!("graphql".parse::<PayloadLocation>(), Ok(PayloadLocation::GraphQl));
+        assert_eq!(" JSON ".parse::<PayloadLocation>(), Ok(PayloadLocation::JsonBody));
+        assert!("xml".parse::<PayloadLocation>().is_err());
+        assert_eq!("X-Forwarded-For".parse::<PayloadLocation>(), Ok(PayloadLocation::XForwardedFor));
+    }
+
+    #[test]
+    fn test_header_and_path_payload_locations() {
+        let payload = "<script>alert(1); x</script>";
+
+        assert_eq!(PayloadLocation::Query.build_header(payload), None);
+        assert_eq!(PayloadLocation::Referer.build_header(payload), Some(("Referer", payload.to_string())));
+        assert_eq!(
+            PayloadLocation::Cookie.build_header(payload),
+            Some(("Cookie", "test=%3Cscript%3Ealert%281%29%3B%20x%3C%2Fscript%3E".to_string()))
+        );
+
+        assert_eq!(PayloadLocation::UserAgent.build_url("https://example.com/", payload), "https://example.com/");
+        assert_eq!(
+            PayloadLocation::Query.build_url("https://example.com/?a=1", "1 OR 1"),
+            "https://example.com/?a=1&test=1%20OR%201"
+        );
+        assert_eq!(
+            PayloadLocation::Path.build_url("https://example.com/api/?a=1", "../etc/passwd"),
+            "https://example.com/api/..%2Fetc%2Fpasswd?a=1"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! CI failure policy (`--fail-on`)
+//!
+//! Conditions are checked against finished scans; any match turns into a distinct
+//! process exit code so pipelines can tell "the WAF is gone" from "the scan broke".
+
+use crate::confidence::ConfidenceThresholds;
+#[cfg(feature = "payload")]
+use crate::payload::waf_smoke_test::SmokeTestResult;
+use crate::DetectionResult;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Every check passed
+pub const EXIT_OK: i32 = 0;
+/// Runtime error: invalid arguments, unreadable files, unreachable single target
+pub const EXIT_ERROR: i32 = 1;
+/// A `--fail-on` condition matched
+pub const EXIT_POLICY_VIOLATION: i32 = 3;
+/// A target in a policy-checked scan could not be scanned
+pub const EXIT_SCAN_FAILED: i32 = 4;
+/// Interrupted with Ctrl-C
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Effectiveness below which `--smoke-test` fails when no `--fail-on` is given
+pub const DEFAULT_SMOKE_TEST_EFFECTIVENESS: f64 = 50.0;
+
+/// One `--fail-on` condition
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case", tag = "condition", content = "threshold")]
+pub enum FailCondition {
+    /// No WAF detected
+    NoWaf,
+    /// WAF confidence below the given percentage; the scoring engine's `high`
+    /// threshold when none is given
+    LowConfidence(Option<f64>),
+    /// Smoke-test effectiveness below the given percentage
+    Effectiveness(f64),
+}
+
+impl FromStr for FailCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let percent = |value: &str| -> std::result::Result<f64, String> {
+            let value: f64 = value.trim().trim_end_matches('%').parse().map_err(|_| format!("'{}' is not a number", value))?;
+            if (0.0..=100.0).contains(&value) {
+                Ok(value)
+            } else {
+                Err(format!("threshold {} must be a percentage between 0 and 100", value))
+            }
+        };
+
+        match s.split_once('<') {
+            None if s == "no-waf" => Ok(Self::NoWaf),
+            None if s == "low-confidence" => Ok(Self::LowConfidence(None)),
+            Some(("low-confidence", value)) => Ok(Self::LowConfidence(Some(percent(value)?))),
+            Some(("effectiveness", value)) => Ok(Self::Effectiveness(percent(value)?)),
+            _ => Err(format!("unknown condition '{}' (expected no-waf, low-confidence[<N] or effectiveness<N)", s)),
+        }
+    }
+}
+
+impl fmt::Display for FailCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoWaf => write!(f, "no-waf"),
+            Self::LowConfidence(None) => write!(f, "low-confidence"),
+            Self::LowConfidence(Some(threshold)) => write!(f, "low-confidence<{}", threshold),
+            Self::Effectiveness(threshold) => write!(f, "effectiveness<{}", threshold),
+        }
+    }
+}
+
+/// A matched condition
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyViolation {
+    pub url: String,
+    pub condition: FailCondition,
+    pub message: String,
+}
+
+/// Outcome of checking a set of scans
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolicyReport {
+    pub violations: Vec<PolicyViolation>,
+    /// Targets whose scan failed, so their protection is unknown
+    pub scan_failures: Vec<String>,
+}
+
+impl PolicyReport {
+    /// Process exit code for this outcome; violations win over scan failures
+    pub fn exit_code(&self) -> i32 {
+        if !self.violations.is_empty() {
+            EXIT_POLICY_VIOLATION
+        } else if !self.scan_failures.is_empty() {
+            EXIT_SCAN_FAILED
+        } else {
+            EXIT_OK
+        }
+    }
+}
+
+/// The conditions given with `--fail-on`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FailPolicy {
+    pub conditions: Vec<FailCondition>,
+}
+
+impl FailPolicy {
+    pub fn new(conditions: Vec<FailCondition>) -> Self {
+        Self { conditions }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// `effectiveness` needs a smoke test
+    pub fn validate_for_detection(&self) -> Result<()> {
+        match self.conditions.iter().find(|c| matches!(c, FailCondition::Effectiveness(_))) {
+            Some(condition) => Err(anyhow!("--fail-on {} only applies to --smoke-test", condition)),
+            None => Ok(()),
+        }
+    }
+
+    /// `low-confidence` needs a detection scan
+    pub fn validate_for_smoke_test(&self) -> Result<()> {
+        match self.conditions.iter().find(|c| matches!(c, FailCondition::LowConfidence(_))) {
+            Some(condition) => Err(anyhow!("--fail-on {} does not apply to --smoke-test", condition)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn check_detections(&self, results: &[DetectionResult], thresholds: &ConfidenceThresholds) -> Result<PolicyReport> {
+        self.validate_for_detection()?;
+        let mut report = PolicyReport::default();
+        for result in results {
+            if result.error.is_some() {
+                report.scan_failures.push(result.url.clone());
+                continue;
+            }
+            for condition in &self.conditions {
+                let message = match (condition, &result.detected_waf) {
+                    (FailCondition::NoWaf, None) => Some("no WAF detected".to_string()),
+                    (FailCondition::LowConfidence(threshold), Some(waf)) => {
+                        let threshold = threshold.unwrap_or(thresholds.high * 100.0);
+                        (waf.confidence * 100.0 < threshold).then(|| {
+                            format!("{} detected with {:.1}% confidence (below {}%)", waf.name, waf.confidence * 100.0, threshold)
+                        })
+                    }
+                    _ => None,
+                };
+                if let Some(message) = message {
+                    report.violations.push(PolicyViolation { url: result.url.clone(), condition: *condition, message });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    #[cfg(feature = "payload")]
+    pub fn check_smoke_test(&self, result: &SmokeTestResult) -> Result<PolicyReport> {
+        self.validate_for_smoke_test()?;
+        let effectiveness = result.summary.effectiveness_percentage;
+        let mut report = PolicyReport::default();
+        for condition in &self.conditions {
+            let message = match condition {
+                FailCondition::NoWaf if result.detected_waf.is_none() => Some("no WAF detected".to_string()),
+                FailCondition::Effectiveness(threshold) if effectiveness < *threshold => {
+                    Some(format!("WAF effectiveness {:.1}% (below {}%)", effectiveness, threshold))
+                }
+                _ => None,
+            };
+            if let Some(message) = message {
+                report.violations.push(PolicyViolation { url: result.url.clone(), condition: *condition, message });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conditions() {
+        assert_eq!("no-waf".parse::<FailCondition>(), Ok(FailCondition::NoWaf));
+        assert_eq!("low-confidence".parse::<FailCondition>(), Ok(FailCondition::LowConfidence(None)));
+        assert_eq!("low-confidence<75".parse::<FailCondition>(), Ok(FailCondition::LowConfidence(Some(75.0))));
+        assert_eq!("Effectiveness<80%".parse::<FailCondition>(), Ok(FailCondition::Effectiveness(80.0)));
+        assert!("effectiveness".parse::<FailCondition>().is_err());
+        assert!("effectiveness<150".parse::<FailCondition>().is_err());
+        assert!("no-cdn".parse::<FailCondition>().is_err());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let mut report = PolicyReport::default();
+        assert_eq!(report.exit_code(), EXIT_OK);
+        report.scan_failures.push("https://down.example".to_string());
+        assert_eq!(report.exit_code(), EXIT_SCAN_FAILED);
+        report.violations.push(PolicyViolation {
+            url: "https://example.com".to_string(),
+            condition: FailCondition::NoWaf,
+            message: "no WAF detected".to_string(),
+        });
+        assert_eq!(report.exit_code(), EXIT_POLICY_VIOLATION);
+    }
+}
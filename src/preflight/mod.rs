@@ -0,0 +1,313 @@
+//! Pre-flight checks: robots.txt and security.txt awareness
+//!
+//! Before probing a target the engine can fetch `/robots.txt` and
+//! `/.well-known/security.txt`. Both are recorded in the result metadata, and with
+//! `respect_robots` enabled active/payload probing is refused on disallowed paths.
+
+use crate::http::HttpClient;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// User-agent token matched against robots.txt groups
+pub const ROBOTS_USER_AGENT: &str = "WAF-Detector";
+
+/// Outcome of the pre-flight step for a single target
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PreflightInfo {
+    pub robots_txt: Option<RobotsTxt>,
+    pub security_txt: Option<SecurityTxt>,
+    /// Whether robots.txt allows our user agent to access the scanned path
+    pub target_allowed: bool,
+    /// Set when probing techniques were skipped because robots.txt disallows the path
+    pub probing_skipped: bool,
+}
+
+impl PreflightInfo {
+    /// Fetch robots.txt and security.txt for the target's origin
+    pub async fn fetch(client: &HttpClient, url: &str) -> Self {
+        let robots_txt = match origin_url(url, "/robots.txt") {
+            Some(robots_url) => fetch_text(client, &robots_url).await.map(|body| RobotsTxt::parse(&body)),
+            None => None,
+        };
+        let security_txt = match origin_url(url, "/.well-known/security.txt") {
+            Some(security_url) => fetch_text(client, &security_url).await.map(|body| SecurityTxt::parse(&body)),
+            None => None,
+        };
+
+        let path = target_path(url);
+        let target_allowed = robots_txt
+            .as_ref()
+            .map(|robots| robots.is_allowed(ROBOTS_USER_AGENT, &path))
+            .unwrap_or(true);
+
+        Self {
+            robots_txt,
+            security_txt,
+            target_allowed,
+            probing_skipped: false,
+        }
+    }
+}
+
+/// A single `Allow`/`Disallow` line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RobotsRule {
+    pub allow: bool,
+    pub path: String,
+}
+
+/// Rules that apply to a set of user agents
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RobotsGroup {
+    pub user_agents: Vec<String>,
+    pub rules: Vec<RobotsRule>,
+}
+
+/// Parsed robots.txt
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RobotsTxt {
+    pub groups: Vec<RobotsGroup>,
+    pub sitemaps: Vec<String>,
+}
+
+impl RobotsTxt {
+    pub fn parse(content: &str) -> Self {
+        let mut robots = RobotsTxt::default();
+        let mut current: Option<RobotsGroup> = None;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    // Consecutive user-agent lines share one group
+                    match current.as_mut() {
+                        Some(group) if group.rules.is_empty() => {
+                            group.user_agents.push(value.to_lowercase());
+                        }
+                        _ => {
+                            if let Some(group) = current.take() {
+                                robots.groups.push(group);
+                            }
+                            current = Some(RobotsGroup {
+                                user_agents: vec![value.to_lowercase()],
+                                rules: Vec::new(),
+                            });
+                        }
+                    }
+                }
+                "allow" | "disallow" => {
+                    if let Some(group) = current.as_mut() {
+                        // An empty Disallow means "allow everything"
+                        if !value.is_empty() {
+                            group.rules.push(RobotsRule {
+                                allow: key.trim().eq_ignore_ascii_case("allow"),
+                                path: value.to_string(),
+                            });
+                        }
+                    }
+                }
+                "sitemap" => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        if let Some(group) = current {
+            robots.groups.push(group);
+        }
+
+        robots
+    }
+
+    /// Check whether `user_agent` may access `path` (longest matching rule wins, ties allow)
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        let agent = user_agent.to_lowercase();
+
+        let mut groups: Vec<&RobotsGroup> = self.groups
+            .iter()
+            .filter(|g| g.user_agents.iter().any(|ua| ua != "*" && agent.contains(ua.as_str())))
+            .collect();
+        if groups.is_empty() {
+            groups = self.groups
+                .iter()
+                .filter(|g| g.user_agents.iter().any(|ua| ua == "*"))
+                .collect();
+        }
+
+        let mut best: Option<&RobotsRule> = None;
+        for rule in groups.iter().flat_map(|g| g.rules.iter()) {
+            if !path_matches(&rule.path, path) {
+                continue;
+            }
+            best = match best {
+                Some(current) if current.path.len() > rule.path.len() => Some(current),
+                Some(current) if current.path.len() == rule.path.len() && current.allow => Some(current),
+                _ => Some(rule),
+            };
+        }
+
+        best.map(|rule| rule.allow).unwrap_or(true)
+    }
+}
+
+/// Parsed security.txt (RFC 9116)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecurityTxt {
+    pub contact: Vec<String>,
+    pub expires: Option<String>,
+    pub encryption: Vec<String>,
+    pub policy: Vec<String>,
+    pub preferred_languages: Option<String>,
+    pub canonical: Vec<String>,
+}
+
+impl SecurityTxt {
+    pub fn parse(content: &str) -> Self {
+        let mut security = SecurityTxt::default();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+
+            match key.trim().to_lowercase().as_str() {
+                "contact" => security.contact.push(value),
+                "expires" => security.expires = Some(value),
+                "encryption" => security.encryption.push(value),
+                "policy" => security.policy.push(value),
+                "preferred-languages" => security.preferred_languages = Some(value),
+                "canonical" => security.canonical.push(value),
+                _ => {}
+            }
+        }
+
+        security
+    }
+}
+
+/// Fetch a plain-text file, ignoring errors, non-200 responses and HTML soft-404 pages
+async fn fetch_text(client: &HttpClient, url: &str) -> Option<String> {
+    let response = client.get(url).await.ok()?;
+    if response.status != 200 || response.body.trim_start().starts_with('<') {
+        return None;
+    }
+    Some(response.body)
+}
+
+fn origin_url(url: &str, path: &str) -> Option<String> {
+    let mut parsed = Url::parse(url).ok()?;
+    parsed.set_path(path);
+    parsed.set_query(None);
+    parsed.set_fragment(None);
+    Some(parsed.to_string())
+}
+
+fn target_path(url: &str) -> String {
+    Url::parse(url)
+        .map(|parsed| match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        })
+        .unwrap_or_else(|_| "/".to_string())
+}
+
+/// robots.txt path matching with `*` wildcards and a `$` end anchor
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(stripped) => (stripped, true),
+        None => (pattern, false),
+    };
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut remaining = path;
+
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match remaining.strip_prefix(part) {
+                Some(rest) => remaining = rest,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 && anchored {
+            return remaining.ends_with(part);
+        } else {
+            match remaining.find(part) {
+                Some(idx) => remaining = &remaining[idx + part.len()..],
+                None => return false,
+            }
+        }
+    }
+
+    !anchored || remaining.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+User-agent: *
+Disallow: /admin
+Allow: /admin/public
+Disallow: /*.php$
+
+User-agent: waf-detector
+User-agent: other-bot
+Disallow: /
+
+Sitemap: https://example.com/sitemap.xml
+";
+
+    #[test]
+    fn test_robots_parsing() {
+        let robots = RobotsTxt::parse(ROBOTS);
+        assert_eq!(robots.groups.len(), 2);
+        assert_eq!(robots.groups[1].user_agents, vec!["waf-detector", "other-bot"]);
+        assert_eq!(robots.sitemaps, vec!["https://example.com/sitemap.xml"]);
+    }
+
+    #[test]
+    fn test_robots_rule_precedence() {
+        let robots = RobotsTxt::parse(ROBOTS);
+        assert!(robots.is_allowed("SomeBot", "/"));
+        assert!(!robots.is_allowed("SomeBot", "/admin/users"));
+        assert!(robots.is_allowed("SomeBot", "/admin/public/page"));
+        assert!(!robots.is_allowed("SomeBot", "/index.php"));
+        assert!(robots.is_allowed("SomeBot", "/index.php?x=1"));
+        // A specific group replaces the wildcard group
+        assert!(!robots.is_allowed(ROBOTS_USER_AGENT, "/"));
+    }
+
+    #[test]
+    fn test_empty_disallow_allows_everything() {
+        let robots = RobotsTxt::parse("User-agent: *\nDisallow:\n");
+        assert!(robots.is_allowed(ROBOTS_USER_AGENT, "/anything"));
+    }
+
+    #[test]
+    fn test_security_txt_parsing() {
+        let security = SecurityTxt::parse(
+            "# comment\nContact: mailto:security@example.com\nContact: https://example.com/report\nExpires: 2030-01-01T00:00:00Z\nPolicy: https://example.com/policy\n",
+        );
+        assert_eq!(security.contact.len(), 2);
+        assert_eq!(security.expires.as_deref(), Some("2030-01-01T00:00:00Z"));
+        assert_eq!(security.policy, vec!["https://example.com/policy"]);
+    }
+
+    #[test]
+    fn test_origin_url() {
+        assert_eq!(
+            origin_url("https://example.com/app/page?q=1", "/robots.txt").as_deref(),
+            Some("https://example.com/robots.txt")
+        );
+        assert_eq!(target_path("https://example.com/app?q=1"), "/app?q=1");
+    }
+}
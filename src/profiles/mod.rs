@@ -0,0 +1,231 @@
+//! Browser impersonation profiles (`--impersonate`)
+//!
+//! Some WAFs challenge or block non-browser user agents outright, which hides the
+//! product behind a generic error page. A profile is the header set a real browser
+//! sends on a top-level navigation. Once configured, every [`HttpClient`] request
+//! (detection, payload probing, smoke tests) and the timing analyzer's requests carry
+//! the profile's headers; headers a request sets itself, such as a test payload in
+//! `User-Agent`, take precedence.
+//!
+//! [`HttpClient`]: crate::http::HttpClient
+
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+/// User agent sent when no profile is configured
+pub const DEFAULT_USER_AGENT: &str = "WAF-Detector/1.0";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BrowserProfile {
+    pub name: &'static str,
+    pub mobile: bool,
+    /// Headers in the order the browser sends them, `User-Agent` included
+    pub headers: &'static [(&'static str, &'static str)],
+}
+
+impl BrowserProfile {
+    pub fn user_agent(&self) -> &'static str {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("user-agent"))
+            .map_or(DEFAULT_USER_AGENT, |(_, value)| value)
+    }
+
+    /// The profile's headers minus those named in `overrides`
+    pub fn headers_except<'a>(&'static self, overrides: &'a [(&'a str, &'a str)]) -> impl Iterator<Item = (&'static str, &'static str)> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(name, _)| !overrides.iter().any(|(other, _)| other.eq_ignore_ascii_case(name)))
+            .copied()
+    }
+}
+
+pub static PROFILES: &[BrowserProfile] = &[
+    BrowserProfile {
+        name: "chrome",
+        mobile: false,
+        headers: &[
+            ("sec-ch-ua", "\"Chromium\";v=\"128\", \"Not;A=Brand\";v=\"24\", \"Google Chrome\";v=\"128\""),
+            ("sec-ch-ua-mobile", "?0"),
+            ("sec-ch-ua-platform", "\"Windows\""),
+            ("Upgrade-Insecure-Requests", "1"),
+            ("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/128.0.0.0 Safari/537.36"),
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("Sec-Fetch-User", "?1"),
+            ("Sec-Fetch-Dest", "document"),
+            ("Accept-Language", "en-US,en;q=0.9"),
+        ],
+    },
+    BrowserProfile {
+        name: "chrome-android",
+        mobile: true,
+        headers: &[
+            ("sec-ch-ua", "\"Chromium\";v=\"128\", \"Not;A=Brand\";v=\"24\", \"Google Chrome\";v=\"128\""),
+            ("sec-ch-ua-mobile", "?1"),
+            ("sec-ch-ua-platform", "\"Android\""),
+            ("Upgrade-Insecure-Requests", "1"),
+            ("User-Agent", "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/128.0.0.0 Mobile Safari/537.36"),
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("Sec-Fetch-User", "?1"),
+            ("Sec-Fetch-Dest", "document"),
+            ("Accept-Language", "en-US,en;q=0.9"),
+        ],
+    },
+    BrowserProfile {
+        name: "firefox",
+        mobile: false,
+        headers: &[
+            ("User-Agent", "Mozilla/5.0 (X11; Linux x86_64; rv:130.0) Gecko/20100101 Firefox/130.0"),
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/png,image/svg+xml,*/*;q=0.8"),
+            ("Accept-Language", "en-US,en;q=0.5"),
+            ("Upgrade-Insecure-Requests", "1"),
+            ("Sec-Fetch-Dest", "document"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-User", "?1"),
+            ("Priority", "u=0, i"),
+        ],
+    },
+    BrowserProfile {
+        name: "firefox-android",
+        mobile: true,
+        headers: &[
+            ("User-Agent", "Mozilla/5.0 (Android 14; Mobile; rv:130.0) Gecko/130.0 Firefox/130.0"),
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/png,image/svg+xml,*/*;q=0.8"),
+            ("Accept-Language", "en-US,en;q=0.5"),
+            ("Upgrade-Insecure-Requests", "1"),
+            ("Sec-Fetch-Dest", "document"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-User", "?1"),
+        ],
+    },
+    BrowserProfile {
+        name: "safari",
+        mobile: false,
+        headers: &[
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.6 Safari/605.1.15"),
+            ("Accept-Language", "en-US,en;q=0.9"),
+            ("Sec-Fetch-Dest", "document"),
+        ],
+    },
+    BrowserProfile {
+        name: "safari-ios",
+        mobile: true,
+        headers: &[
+            ("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+            ("Sec-Fetch-Site", "none"),
+            ("Sec-Fetch-Mode", "navigate"),
+            ("User-Agent", "Mozilla/5.0 (iPhone; CPU iPhone OS 17_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.6 Mobile/15E148 Safari/604.1"),
+            ("Accept-Language", "en-US,en;q=0.9"),
+            ("Sec-Fetch-Dest", "document"),
+        ],
+    },
+];
+
+/// Which profile requests use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Impersonation {
+    /// Every request uses this profile
+    Pinned(&'static BrowserProfile),
+    /// Each request takes the next profile, desktop and mobile alike
+    Rotate,
+    /// Each request takes the next mobile profile
+    RotateMobile,
+}
+
+impl Impersonation {
+    /// A profile name (`chrome`, `safari-ios`, ...), or `rotate` / `rotate-mobile`
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rotate" => Ok(Self::Rotate),
+            "rotate-mobile" => Ok(Self::RotateMobile),
+            name => PROFILES
+                .iter()
+                .find(|profile| profile.name == name)
+                .map(Self::Pinned)
+                .ok_or_else(|| anyhow!("Unknown browser profile '{}' (expected {}, rotate or rotate-mobile)", name, names().join(", "))),
+        }
+    }
+
+    /// The profile for the next request
+    pub fn next(&self) -> &'static BrowserProfile {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        match self {
+            Self::Pinned(profile) => profile,
+            Self::Rotate => &PROFILES[NEXT.fetch_add(1, Ordering::Relaxed) % PROFILES.len()],
+            Self::RotateMobile => {
+                let mobile: Vec<&'static BrowserProfile> = PROFILES.iter().filter(|profile| profile.mobile).collect();
+                mobile[NEXT.fetch_add(1, Ordering::Relaxed) % mobile.len()]
+            }
+        }
+    }
+
+    /// Recorded as the scan's user agent
+    pub fn label(&self) -> String {
+        match self {
+            Self::Pinned(profile) => profile.user_agent().to_string(),
+            Self::Rotate => "rotating browser profiles".to_string(),
+            Self::RotateMobile => "rotating mobile browser profiles".to_string(),
+        }
+    }
+}
+
+pub fn names() -> Vec<&'static str> {
+    PROFILES.iter().map(|profile| profile.name).collect()
+}
+
+static CONFIGURED: RwLock<Option<Impersonation>> = RwLock::new(None);
+
+/// Impersonate browsers process-wide; `None` sends the tool's own user agent
+pub fn configure(impersonation: Option<Impersonation>) {
+    *CONFIGURED.write().unwrap_or_else(|e| e.into_inner()) = impersonation;
+}
+
+pub fn configured() -> Option<Impersonation> {
+    *CONFIGURED.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The profile for the next request, if impersonating
+pub fn select() -> Option<&'static BrowserProfile> {
+    configured().map(|impersonation| impersonation.next())
+}
+
+/// User agent recorded in scan metadata
+pub fn user_agent_label() -> String {
+    configured().map_or_else(|| DEFAULT_USER_AGENT.to_string(), |impersonation| impersonation.label())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_rotate() {
+        let Impersonation::Pinned(chrome) = Impersonation::parse("Chrome").unwrap() else { panic!("expected a pinned profile") };
+        assert!(chrome.user_agent().contains("Chrome/"));
+        assert!(Impersonation::parse("lynx").is_err());
+
+        let rotate = Impersonation::parse("rotate").unwrap();
+        let first = rotate.next();
+        assert!((0..PROFILES.len() - 1).map(|_| rotate.next()).all(|profile| profile != first));
+        assert!((0..4).all(|_| Impersonation::RotateMobile.next().mobile));
+    }
+
+    #[test]
+    fn test_request_headers_override_profile() {
+        let firefox = PROFILES.iter().find(|profile| profile.name == "firefox").unwrap();
+        let overrides = [("user-agent", "sqlmap/1.0")];
+        let headers: Vec<_> = firefox.headers_except(&overrides).collect();
+        assert!(headers.iter().all(|(name, _)| *name != "User-Agent"));
+        assert_eq!(headers.len(), firefox.headers.len() - 1);
+    }
+}
@@ -0,0 +1,341 @@
+//! HTTP/2 and HTTP/3 protocol signals
+//!
+//! Which ALPN protocols the TLS endpoint accepts, what `Alt-Svc` advertises for HTTP/3,
+//! and which QUIC versions the UDP endpoint lists when it is sent a version it cannot
+//! speak (a version negotiation probe, so no QUIC handshake is needed). Edge networks
+//! differ in all three: Akamai advertises `ma=93600`, Fastly writes `;ma=` without a
+//! space and keeps draft versions, Google front ends still list gQUIC versions.
+
+use crate::http::HttpResponse;
+use crate::{Evidence, MethodType};
+use anyhow::{anyhow, Result};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::{TcpStream, UdpSocket};
+use url::Url;
+
+/// ALPN protocols offered one at a time to see which the server accepts
+pub const ALPN_PROTOCOLS: [&str; 2] = ["h2", "http/1.1"];
+
+/// QUIC v1 and v2 (RFC 9000, RFC 9369)
+const QUIC_V1: u32 = 0x0000_0001;
+const QUIC_V2: u32 = 0x6b33_43cf;
+
+/// A reserved version no server supports, so every QUIC server answers with version negotiation
+const GREASE_VERSION: u32 = 0x1a2a_3a4a;
+
+/// Client Initial datagrams must be at least this large or servers drop them
+const MIN_INITIAL_SIZE: usize = 1200;
+
+/// One `Alt-Svc` alternative, e.g. `h3=":443"; ma=86400`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AltService {
+    pub protocol: String,
+    pub authority: String,
+    pub max_age: Option<u64>,
+}
+
+impl AltService {
+    pub fn is_http3(&self) -> bool {
+        self.protocol == "h3" || self.protocol.starts_with("h3-")
+    }
+
+    /// Port of the authority (`:443`), if it names one
+    pub fn port(&self) -> Option<u16> {
+        self.authority.rsplit(':').next()?.parse().ok()
+    }
+}
+
+/// Parse an `Alt-Svc` header value; `clear` yields no alternatives
+pub fn parse_alt_svc(value: &str) -> Vec<AltService> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let mut params = entry.split(';').map(str::trim);
+            let (protocol, authority) = params.next()?.split_once('=')?;
+            let max_age = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("ma"))
+                .and_then(|(_, value)| value.trim().parse().ok());
+            Some(AltService {
+                protocol: protocol.trim().to_string(),
+                authority: authority.trim().trim_matches('"').to_string(),
+                max_age,
+            })
+        })
+        .collect()
+}
+
+/// Readable name of a QUIC version number
+pub fn quic_version_name(version: u32) -> String {
+    let bytes = version.to_be_bytes();
+    match version {
+        QUIC_V1 => "v1".to_string(),
+        QUIC_V2 => "v2".to_string(),
+        v if v >> 8 == 0xff_0000 => format!("draft-{}", v & 0xff),
+        v if v & 0x0f0f_0f0f == 0x0a0a_0a0a => "reserved".to_string(),
+        // gQUIC versions are ASCII tags such as Q050 or T051
+        _ if (bytes[0] == b'Q' || bytes[0] == b'T') && bytes[1..].iter().all(u8::is_ascii_digit) => {
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        v => format!("{:#010x}", v),
+    }
+}
+
+/// Protocol support observed for one endpoint
+#[derive(Debug, Clone, Default)]
+pub struct ProtocolAnalysis {
+    /// ALPN protocols the TLS endpoint agreed to when offered alone
+    pub alpn: Vec<String>,
+    /// Alternatives from the response's `Alt-Svc` header, exactly as sent in `alt_svc_raw`
+    pub alt_svc: Vec<AltService>,
+    pub alt_svc_raw: Option<String>,
+    /// Versions from the QUIC version negotiation reply; `None` if nothing answered
+    pub quic_versions: Option<Vec<u32>>,
+}
+
+impl ProtocolAnalysis {
+    pub fn advertises_http3(&self) -> bool {
+        self.alt_svc.iter().any(AltService::is_http3)
+    }
+
+    /// Evidence for the observed signals, with provider hints where a behavior is
+    /// specific to one network
+    pub fn to_evidence(&self) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+        let mut push = |signal: &str, confidence: f64, description: String, raw_data: String, signature: &str| {
+            evidence.push(Evidence {
+                method_type: MethodType::Protocol(signal.to_string()),
+                confidence,
+                description,
+                raw_data,
+                signature_matched: signature.to_string(),
+            });
+        };
+
+        if !self.alpn.is_empty() {
+            let (description, signature) = if self.alpn.iter().any(|p| p == "h2") {
+                ("TLS endpoint negotiates HTTP/2", "alpn-h2")
+            } else {
+                ("TLS endpoint only negotiates HTTP/1.1, typical of an origin server rather than an edge network", "alpn-http1-only")
+            };
+            push("alpn", 0.3, description.to_string(), self.alpn.join(","), signature);
+        }
+
+        if let Some(raw) = &self.alt_svc_raw {
+            let http3 = self.alt_svc.iter().filter(|alt| alt.is_http3()).collect::<Vec<_>>();
+            if !http3.is_empty() {
+                push("alt-svc", 0.35, "HTTP/3 advertised in Alt-Svc".to_string(), raw.clone(), "alt-svc-h3");
+            }
+            if http3.iter().any(|alt| alt.max_age == Some(93_600)) {
+                push("alt-svc", 0.6, "Alt-Svc max-age 93600 is Akamai's default".to_string(), raw.clone(), "Akamai");
+            }
+            if http3.iter().any(|alt| alt.max_age == Some(2_592_000)) && http3.iter().any(|alt| alt.protocol == "h3-29") {
+                push("alt-svc", 0.5, "Alt-Svc h3-29 with a 30 day max-age is served by Google front ends".to_string(), raw.clone(), "Google");
+            }
+            if raw.contains(";ma=") && http3.iter().any(|alt| alt.protocol.starts_with("h3-")) {
+                push("alt-svc", 0.5, "Alt-Svc without spaces listing HTTP/3 drafts matches Fastly".to_string(), raw.clone(), "Fastly");
+            }
+        }
+
+        match &self.quic_versions {
+            Some(versions) => {
+                let names: Vec<String> = versions.iter().map(|v| quic_version_name(*v)).collect();
+                push("quic", 0.4, "QUIC endpoint answered version negotiation".to_string(), names.join(","), "quic-version-negotiation");
+                if names.iter().any(|name| name.starts_with('Q') || name.starts_with('T')) {
+                    push("quic", 0.6, "QUIC endpoint still lists gQUIC versions, as Google front ends do".to_string(), names.join(","), "Google");
+                }
+            }
+            None if self.advertises_http3() => {
+                push("quic", 0.2, "HTTP/3 is advertised but the QUIC endpoint did not answer".to_string(), String::new(), "quic-unreachable");
+            }
+            None => {}
+        }
+
+        evidence
+    }
+}
+
+/// Probes ALPN and QUIC support of a target
+#[derive(Debug, Clone)]
+pub struct ProtocolAnalyzer {
+    timeout: Duration,
+}
+
+impl ProtocolAnalyzer {
+    pub fn new() -> Self {
+        Self { timeout: Duration::from_secs(3) }
+    }
+
+    /// Time allowed for each TLS handshake and for the QUIC reply
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Analyze `url`; `Alt-Svc` is read from the already fetched `response`
+    pub async fn analyze(&self, url: &str, response: Option<&HttpResponse>) -> Result<Vec<Evidence>> {
+        Ok(self.probe(url, response).await?.to_evidence())
+    }
+
+    pub async fn probe(&self, url: &str, response: Option<&HttpResponse>) -> Result<ProtocolAnalysis> {
+        let parsed = Url::parse(url)?;
+        let host = parsed.host_str().ok_or_else(|| anyhow!("URL '{}' has no host", url))?.to_string();
+        let mut analysis = ProtocolAnalysis::default();
+
+        if let Some(raw) = response.and_then(|r| r.headers.get("alt-svc")) {
+            analysis.alt_svc = parse_alt_svc(raw);
+            analysis.alt_svc_raw = Some(raw.clone());
+        }
+        // Cleartext targets have neither ALPN nor QUIC
+        if parsed.scheme() != "https" {
+            return Ok(analysis);
+        }
+
+        let port = parsed.port_or_known_default().unwrap_or(443);
+        for protocol in ALPN_PROTOCOLS {
+            if let Ok(Some(negotiated)) = self.negotiate_alpn(&host, port, protocol).await {
+                analysis.alpn.push(negotiated);
+            }
+        }
+
+        let quic_port = analysis.alt_svc.iter().filter(|alt| alt.is_http3()).find_map(AltService::port).unwrap_or(port);
+        analysis.quic_versions = self.quic_versions(&host, quic_port).await.ok();
+        Ok(analysis)
+    }
+
+    /// Offer only `protocol` and return what the server selected
+    async fn negotiate_alpn(&self, host: &str, port: u16, protocol: &str) -> Result<Option<String>> {
+        let connector = native_tls::TlsConnector::builder()
+            .request_alpns(&[protocol])
+            // Only the handshake matters here, not who signed the certificate
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+        let connector = tokio_native_tls::TlsConnector::from(connector);
+        let handshake = async {
+            let stream = TcpStream::connect((host, port)).await?;
+            let tls = connector.connect(host, stream).await?;
+            let negotiated = tls.get_ref().negotiated_alpn()?;
+            Ok::<_, anyhow::Error>(negotiated.map(|p| String::from_utf8_lossy(&p).into_owned()))
+        };
+        tokio::time::timeout(self.timeout, handshake)
+            .await
+            .map_err(|_| anyhow!("TLS handshake with {}:{} timed out", host, port))?
+    }
+
+    /// Versions the QUIC server at `host:port` supports, from its version negotiation reply
+    async fn quic_versions(&self, host: &str, port: u16) -> Result<Vec<u32>> {
+        let addr = tokio::net::lookup_host((host, port)).await?.next().ok_or_else(|| anyhow!("{} did not resolve", host))?;
+        let socket = UdpSocket::bind(if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+        socket.connect(addr).await?;
+
+        let (packet, scid) = version_negotiation_probe();
+        socket.send(&packet).await?;
+        let mut buf = [0u8; 1500];
+        let len = tokio::time::timeout(self.timeout, socket.recv(&mut buf))
+            .await
+            .map_err(|_| anyhow!("no QUIC reply from {}", addr))??;
+        parse_version_negotiation(&buf[..len], &scid)
+    }
+}
+
+impl Default for ProtocolAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A padded long-header packet with a reserved version, and its source connection ID
+fn version_negotiation_probe() -> (Vec<u8>, [u8; 8]) {
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+    let dcid = seed.to_be_bytes();
+    let scid = seed.rotate_left(32).wrapping_mul(0x9e37_79b9_7f4a_7c15).to_be_bytes();
+
+    let mut packet = Vec::with_capacity(MIN_INITIAL_SIZE);
+    packet.push(0xc0);
+    packet.extend_from_slice(&GREASE_VERSION.to_be_bytes());
+    packet.push(dcid.len() as u8);
+    packet.extend_from_slice(&dcid);
+    packet.push(scid.len() as u8);
+    packet.extend_from_slice(&scid);
+    packet.resize(MIN_INITIAL_SIZE, 0);
+    (packet, scid)
+}
+
+/// Supported versions from a version negotiation packet addressed to `scid`
+fn parse_version_negotiation(packet: &[u8], scid: &[u8]) -> Result<Vec<u32>> {
+    let invalid = || anyhow!("not a QUIC version negotiation packet");
+    if packet.len() < 7 || packet[0] & 0x80 == 0 || packet[1..5] != [0, 0, 0, 0] {
+        return Err(invalid());
+    }
+    let dcid_len = packet[5] as usize;
+    let dcid = packet.get(6..6 + dcid_len).ok_or_else(invalid)?;
+    if dcid != scid {
+        return Err(anyhow!("version negotiation reply for another connection"));
+    }
+    let scid_len = *packet.get(6 + dcid_len).ok_or_else(invalid)? as usize;
+    let versions = packet.get(7 + dcid_len + scid_len..).ok_or_else(invalid)?;
+    if versions.is_empty() || versions.len() % 4 != 0 {
+        return Err(invalid());
+    }
+    Ok(versions.chunks(4).map(|v| u32::from_be_bytes([v[0], v[1], v[2], v[3]])).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alt_svc() {
+        let alts = parse_alt_svc(r#"h3=":443"; ma=86400, h3-29=":8443";ma=3600, h2="alt.example:443""#);
+        assert_eq!(alts.len(), 3);
+        assert_eq!(alts[0], AltService { protocol: "h3".into(), authority: ":443".into(), max_age: Some(86_400) });
+        assert_eq!((alts[1].port(), alts[1].max_age), (Some(8443), Some(3600)));
+        assert!(alts[1].is_http3() && !alts[2].is_http3());
+        assert!(parse_alt_svc("clear").is_empty());
+    }
+
+    #[test]
+    fn test_version_negotiation_round_trip() {
+        let (probe, scid) = version_negotiation_probe();
+        assert_eq!(probe.len(), MIN_INITIAL_SIZE);
+        assert_eq!(&probe[1..5], &GREASE_VERSION.to_be_bytes());
+
+        // Server reply: our SCID as its DCID, its own SCID, then the versions
+        let mut reply = vec![0x80, 0, 0, 0, 0, 8];
+        reply.extend_from_slice(&scid);
+        reply.extend_from_slice(&[4, 1, 2, 3, 4]);
+        for version in [QUIC_V1, 0xff00_001d, u32::from_be_bytes(*b"Q050")] {
+            reply.extend_from_slice(&version.to_be_bytes());
+        }
+        let versions = parse_version_negotiation(&reply, &scid).unwrap();
+        let names: Vec<String> = versions.into_iter().map(quic_version_name).collect();
+        assert_eq!(names, ["v1", "draft-29", "Q050"]);
+
+        assert!(parse_version_negotiation(&reply, &[0; 8]).is_err());
+        assert!(parse_version_negotiation(&reply[..20], &scid).is_err());
+    }
+
+    #[test]
+    fn test_provider_hints() {
+        let akamai = ProtocolAnalysis {
+            alt_svc: parse_alt_svc(r#"h3=":443"; ma=93600"#),
+            alt_svc_raw: Some(r#"h3=":443"; ma=93600"#.to_string()),
+            ..ProtocolAnalysis::default()
+        };
+        let signatures: Vec<String> = akamai.to_evidence().into_iter().map(|e| e.signature_matched).collect();
+        assert_eq!(signatures, ["alt-svc-h3", "Akamai", "quic-unreachable"]);
+
+        let fastly_raw = r#"h3=":443";ma=86400,h3-29=":443";ma=86400"#;
+        let fastly = ProtocolAnalysis {
+            alpn: vec!["h2".into(), "http/1.1".into()],
+            alt_svc: parse_alt_svc(fastly_raw),
+            alt_svc_raw: Some(fastly_raw.to_string()),
+            quic_versions: Some(vec![QUIC_V1, 0xff00_001d]),
+        };
+        let evidence = fastly.to_evidence();
+        assert!(evidence.iter().any(|e| e.signature_matched == "Fastly"));
+        assert!(evidence.iter().all(|e| e.signature_matched != "Google"));
+        assert_eq!(evidence[0].method_type, MethodType::Protocol("alpn".into()));
+    }
+}
@@ -68,7 +68,7 @@ impl AkamaiProvider {
 
         // Check X-Cache headers
         for cache_header in ["x-cache", "x-cache-remote"] {
-            if let Some(cache_value) = response.headers.get(cache_header) {
+            for cache_value in response.headers.get_all(cache_header) {
                 if Self::akamai_cache_pattern().is_match(cache_value) {
                     evidence.push(Evidence {
                         method_type: MethodType::Header(cache_header.to_string()),
@@ -0,0 +1,178 @@
+//! Alibaba Cloud WAF / CDN Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Alibaba Cloud WAF and CDN detection provider
+#[derive(Debug, Clone)]
+pub struct AlibabaCloudProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl AlibabaCloudProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "AlibabaCloud".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Alibaba Cloud WAF and CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    /// Trace ID of the CDN node, e.g. `7ce1d69d16430950389458286e`
+    fn eagleid_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)^[0-9a-f]{24,34}$").unwrap())
+    }
+
+    /// Cache hops in Via, e.g. `cache13.l2cn1809[0,0,200-0,H], cache7.cn2465[1,0]`
+    fn swift_via_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"\bcache\d+\.(l2)?[a-z]{2}\d+\[").unwrap())
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(eagleid) = response.headers.get("eagleid") {
+            if Self::eagleid_pattern().is_match(eagleid) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("eagleid".to_string()),
+                    confidence: 0.95,
+                    description: "Alibaba Cloud CDN EagleEye trace header detected".to_string(),
+                    raw_data: eagleid.clone(),
+                    signature_matched: "alibaba-eagleid-header".to_string(),
+                });
+            }
+        }
+
+        // Swift is the caching layer of Alibaba Cloud CDN
+        let swift_headers: Vec<&str> = ["x-swift-cachetime", "x-swift-savetime", "ali-swift-global-savetime"]
+            .into_iter()
+            .filter(|header| response.headers.get(header).is_some())
+            .collect();
+        if !swift_headers.is_empty() {
+            evidence.push(Evidence {
+                method_type: MethodType::Header(swift_headers[0].to_string()),
+                confidence: 0.92,
+                description: format!("Alibaba Cloud CDN Swift cache headers detected ({})", swift_headers.join(", ")),
+                raw_data: swift_headers.join(", "),
+                signature_matched: "alibaba-swift-header".to_string(),
+            });
+        }
+
+        for via in response.headers.get_all("via") {
+            if Self::swift_via_pattern().is_match(via) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("via".to_string()),
+                    confidence: 0.85,
+                    description: "Alibaba Cloud CDN cache nodes in Via header".to_string(),
+                    raw_data: via.clone(),
+                    signature_matched: "alibaba-via-header".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // acw_tc / aliyungf_tc track sessions through the WAF; acw_sc__v2 is set by its JS challenge
+        for cookie in set_cookie_names(response) {
+            if matches!(cookie.as_str(), "acw_tc" | "aliyungf_tc" | "acw_sc__v2") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence: 0.90,
+                    description: format!("Alibaba Cloud WAF {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "alibaba-waf-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // The block page loads its artwork from errors.aliyun.com; the slider challenge
+        // is served under /_____tmd_____/
+        for marker in ["errors.aliyun.com", "_____tmd_____"] {
+            if response.body.contains(marker) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Body(marker.to_string()),
+                    confidence: 0.92,
+                    description: "Alibaba Cloud WAF block page detected".to_string(),
+                    raw_data: marker.to_string(),
+                    signature_matched: "alibaba-waf-block-page".to_string(),
+                });
+                break;
+            }
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for AlibabaCloudProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Both
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        85
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for AlibabaCloudProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
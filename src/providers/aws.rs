@@ -1,6 +1,6 @@
 //! AWS WAF/CloudFront Detection Provider
 
-use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType, StackLayer};
 use regex::Regex;
 use std::sync::OnceLock;
 use anyhow::Result;
@@ -124,7 +124,7 @@ impl AwsProvider {
         }
 
         // Check Via header for CloudFront
-        if let Some(via) = response.headers.get("via") {
+        for via in response.headers.get_all("via") {
             if Self::cloudfront_via_pattern().is_match(via) {
                 evidence.push(Evidence {
                     method_type: MethodType::Header("via".to_string()),
@@ -137,7 +137,7 @@ impl AwsProvider {
         }
 
         // Check x-cache header for CloudFront
-        if let Some(cache) = response.headers.get("x-cache") {
+        for cache in response.headers.get_all("x-cache") {
             if Self::cloudfront_cache_pattern().is_match(cache) {
                 evidence.push(Evidence {
                     method_type: MethodType::Header("x-cache".to_string()),
@@ -168,8 +168,8 @@ impl AwsProvider {
                 // Only add moderate confidence if other CloudFront indicators are present
                 let has_other_cf_indicators = response.headers.get("x-amz-cf-id").is_some() ||
                     response.headers.get("x-amz-cf-pop").is_some() ||
-                    response.headers.get("via").map_or(false, |v| Self::cloudfront_via_pattern().is_match(v)) ||
-                    response.headers.get("x-cache").map_or(false, |c| Self::cloudfront_cache_pattern().is_match(c));
+                    response.headers.get_all("via").any(|v| Self::cloudfront_via_pattern().is_match(v)) ||
+                    response.headers.get_all("x-cache").any(|c| Self::cloudfront_cache_pattern().is_match(c));
                 
                 if has_other_cf_indicators {
                     evidence.push(Evidence {
@@ -203,7 +203,7 @@ impl AwsProvider {
                (timing.contains("cache") && (
                    response.headers.get("x-amz-cf-pop").is_some() ||
                    response.headers.get("x-amz-cf-id").is_some() ||
-                   response.headers.get("via").map_or(false, |v| v.contains("CloudFront"))
+                   response.headers.get_all("via").any(|v| v.contains("CloudFront"))
                )) {
                 evidence.push(Evidence {
                     method_type: MethodType::Header("x-served-by".to_string()),
@@ -221,7 +221,7 @@ impl AwsProvider {
             if rt.contains("S") && (
                 response.headers.get("x-amz-cf-pop").is_some() ||
                 response.headers.get("x-amz-cf-id").is_some() ||
-                response.headers.get("via").map_or(false, |v| v.contains("CloudFront")) ||
+                response.headers.get_all("via").any(|v| v.contains("CloudFront")) ||
                 response.headers.get("server").map_or(false, |s| s.contains("CloudFront"))
             ) {
                 evidence.push(Evidence {
@@ -311,7 +311,7 @@ impl AwsProvider {
                 // Only match if we have specific CloudFront indicators to avoid false positives
                 let has_cloudfront_evidence = response.headers.get("x-amz-cf-pop").is_some() ||
                     response.headers.get("x-amz-cf-id").is_some() ||
-                    response.headers.get("via").map_or(false, |v| v.contains("CloudFront")) ||
+                    response.headers.get_all("via").any(|v| v.contains("CloudFront")) ||
                     response.headers.get("server").map_or(false, |s| s.contains("CloudFront")) ||
                     response.headers.get("x-amzn-requestid").is_some();
                 
@@ -478,7 +478,7 @@ impl AwsProvider {
         }
         
         // Check Via header specifically
-        if let Some(via) = response.headers.get("via") {
+        for via in response.headers.get_all("via") {
             report.push_str(&format!("\n=== Via Header Analysis ===\n"));
             report.push_str(&format!("Via value: '{}'\n", via));
             if Self::cloudfront_via_pattern().is_match(via) {
@@ -535,6 +535,10 @@ impl DetectionProvider for AwsProvider {
         ProviderType::Both // AWS provides both WAF and CDN (CloudFront)
     }
 
+    fn stack_layer(&self) -> StackLayer {
+        StackLayer::CloudWaf // AWS WAF is attached to CloudFront/ALB in front of the application
+    }
+
     fn confidence_base(&self) -> f64 {
         0.5
     }
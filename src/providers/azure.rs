@@ -0,0 +1,220 @@
+//! Azure Front Door / Azure WAF Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType, StackLayer};
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Azure Front Door and Application Gateway WAF detection provider
+#[derive(Debug, Clone)]
+pub struct AzureProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl AzureProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "Azure".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Azure Front Door CDN and Azure WAF (Front Door / Application Gateway) detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn azure_ref_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        // Classic "0AbC..." refs and the newer "20240101T000000Z-..." format
+        PATTERN.get_or_init(|| Regex::new(r"^(0[A-Za-z0-9+/=]{20,}|\d{8}T\d{6}Z-[A-Za-z0-9]+)").unwrap())
+    }
+
+    fn app_gateway_server_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)microsoft-azure-application-gateway").unwrap())
+    }
+
+    fn front_door_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(the request is blocked\.|our services aren't available right now)").unwrap())
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // Front Door request reference (most definitive)
+        if let Some(azure_ref) = response.headers.get("x-azure-ref") {
+            let confidence = if Self::azure_ref_pattern().is_match(azure_ref) { 0.97 } else { 0.90 };
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-azure-ref".to_string()),
+                confidence,
+                description: "Azure Front Door x-azure-ref header detected".to_string(),
+                raw_data: azure_ref.clone(),
+                signature_matched: "azure-ref-header".to_string(),
+            });
+        }
+
+        // Front Door health probe marker
+        if let Some(probe) = response.headers.get("x-fd-healthprobe") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-fd-healthprobe".to_string()),
+                confidence: 0.92,
+                description: "Azure Front Door health probe header detected".to_string(),
+                raw_data: probe.clone(),
+                signature_matched: "azure-fd-healthprobe-header".to_string(),
+            });
+        }
+
+        // Microsoft edge reference (also used by Azure CDN from Microsoft)
+        if let Some(msedge_ref) = response.headers.get("x-msedge-ref") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-msedge-ref".to_string()),
+                confidence: 0.85,
+                description: "Microsoft edge x-msedge-ref header detected".to_string(),
+                raw_data: msedge_ref.clone(),
+                signature_matched: "azure-msedge-ref-header".to_string(),
+            });
+        }
+
+        // Application Gateway server header
+        if let Some(server) = response.headers.get("server") {
+            if Self::app_gateway_server_pattern().is_match(server) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.95,
+                    description: "Azure Application Gateway server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "azure-app-gateway-server-header".to_string(),
+                });
+            }
+        }
+
+        // Front Door cache status values
+        for cache in response.headers.get_all("x-cache") {
+            if cache.eq_ignore_ascii_case("CONFIG_NOCACHE") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("x-cache".to_string()),
+                    confidence: 0.80,
+                    description: "Azure Front Door CONFIG_NOCACHE cache status detected".to_string(),
+                    raw_data: cache.clone(),
+                    signature_matched: "azure-cache-header".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // Application Gateway WAF block page footer
+        if Self::app_gateway_server_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("app-gateway-page".to_string()),
+                confidence: 0.90,
+                description: "Azure Application Gateway error page detected".to_string(),
+                raw_data: "app-gateway-page".to_string(),
+                signature_matched: "azure-app-gateway-body".to_string(),
+            });
+        }
+
+        // Front Door WAF block / outage page
+        if Self::front_door_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("front-door-block-page".to_string()),
+                confidence: 0.75,
+                description: "Azure Front Door block page detected".to_string(),
+                raw_data: "front-door-block-page".to_string(),
+                signature_matched: "azure-block-page-body".to_string(),
+            });
+        }
+
+        evidence
+    }
+
+    pub async fn check_status_codes(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // A 403 carrying Azure markers is an Azure WAF block
+        if response.status == 403 && (response.headers.contains_key("x-azure-ref")
+            || Self::app_gateway_server_pattern().is_match(&response.body)
+            || response.headers.get("server").is_some_and(|s| Self::app_gateway_server_pattern().is_match(s)))
+        {
+            evidence.push(Evidence {
+                method_type: MethodType::StatusCode(403),
+                confidence: 0.85,
+                description: "Azure WAF 403 Forbidden response".to_string(),
+                raw_data: "403".to_string(),
+                signature_matched: "azure-403-pattern".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for AzureProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Both // Front Door is a CDN with an integrated WAF
+    }
+
+    fn stack_layer(&self) -> StackLayer {
+        StackLayer::CloudWaf // Front Door / Application Gateway WAF in front of the application
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.90
+    }
+
+    fn priority(&self) -> u32 {
+        88
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        if let Some(response) = &context.response {
+            all_evidence.extend(self.check_headers(response).await);
+            all_evidence.extend(self.check_body_patterns(response).await);
+            all_evidence.extend(self.check_status_codes(response).await);
+        }
+
+        Ok(all_evidence)
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+        all_evidence.extend(self.check_status_codes(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for AzureProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,126 @@
+//! Barracuda Web Application Firewall Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Barracuda WAF appliance detection provider
+#[derive(Debug, Clone)]
+pub struct BarracudaProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl BarracudaProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "Barracuda".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Barracuda Web Application Firewall detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn barracuda_cookie_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"^(barra_counter_session|BNI__BARRACUDA_LB_COOKIE|BNI_persistence|BN[IE]S_.+)$").unwrap())
+    }
+
+    fn barracuda_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(barracuda\.networks|barracuda networks, inc|you have been blocked by the barracuda)").unwrap())
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        for cookie in set_cookie_names(response) {
+            if Self::barracuda_cookie_pattern().is_match(&cookie) {
+                let confidence = if cookie == "barra_counter_session" { 0.95 } else { 0.88 };
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence,
+                    description: format!("Barracuda {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "barracuda-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if Self::barracuda_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("block-page-detected".to_string()),
+                confidence: if response.status >= 400 { 0.85 } else { 0.65 },
+                description: "Barracuda block page detected".to_string(),
+                raw_data: "block-page-detected".to_string(),
+                signature_matched: "barracuda-block-page".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for BarracudaProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::WAF
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for BarracudaProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,113 @@
+//! BunnyCDN Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// BunnyCDN detection provider
+#[derive(Debug, Clone)]
+pub struct BunnyCdnProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl BunnyCdnProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "BunnyCDN".to_string(),
+            version: "1.0.0".to_string(),
+            description: "BunnyCDN edge network detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    /// `BunnyCDN-DE1-1043`: edge country, zone and server number
+    fn server_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"^BunnyCDN-[A-Z]{2}\d*-\d+$").unwrap())
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(server) = response.headers.get("server") {
+            if Self::server_pattern().is_match(server) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.97,
+                    description: "BunnyCDN edge server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "bunnycdn-server-header".to_string(),
+                });
+            }
+        }
+
+        // Pull zone and request headers are added to every response served from a pull zone
+        let headers: Vec<String> = ["cdn-pullzone", "cdn-uid", "cdn-requestid", "cdn-cache"]
+            .into_iter()
+            .filter_map(|header| response.headers.get(header).map(|value| format!("{}: {}", header, value)))
+            .collect();
+        if !headers.is_empty() {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("cdn-pullzone".to_string()),
+                confidence: if headers.len() > 1 { 0.93 } else { 0.75 },
+                description: "BunnyCDN pull zone headers detected".to_string(),
+                raw_data: headers.join(", "),
+                signature_matched: "bunnycdn-pullzone-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for BunnyCdnProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for BunnyCdnProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,106 @@
+//! Cachefly Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use anyhow::Result;
+
+/// Cachefly CDN detection provider
+#[derive(Debug, Clone)]
+pub struct CacheflyProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl CacheflyProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "Cachefly".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Cachefly CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(server) = response.headers.get("server") {
+            if server == "CFS" || server.starts_with("CFS ") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.85,
+                    description: "Cachefly server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "cachefly-server-header".to_string(),
+                });
+            }
+        }
+
+        // `x-cf1: 28002:fP.jfk2:co:1700000000:cacheN.jfk2-01:M` names the cache that answered;
+        // x-cf2 and x-cff carry its hit/miss flags
+        let headers: Vec<String> = ["x-cf1", "x-cf2", "x-cff", "x-cf-reqid"]
+            .into_iter()
+            .filter_map(|header| response.headers.get(header).map(|value| format!("{}: {}", header, value)))
+            .collect();
+        if !headers.is_empty() {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-cf1".to_string()),
+                confidence: if headers.len() > 1 { 0.93 } else { 0.80 },
+                description: "Cachefly cache headers detected".to_string(),
+                raw_data: headers.join(", "),
+                signature_matched: "cachefly-cache-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for CacheflyProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for CacheflyProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
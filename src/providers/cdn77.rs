@@ -0,0 +1,106 @@
+//! CDN77 Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use anyhow::Result;
+
+/// CDN77 detection provider
+#[derive(Debug, Clone)]
+pub struct Cdn77Provider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl Cdn77Provider {
+    pub fn new() -> Self {
+        Self {
+            name: "CDN77".to_string(),
+            version: "1.0.0".to_string(),
+            description: "CDN77 content delivery network detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(server) = response.headers.get("server") {
+            if server.to_ascii_lowercase().starts_with("cdn77") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.97,
+                    description: "CDN77 server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "cdn77-server-header".to_string(),
+                });
+            }
+        }
+
+        // x-77-nzt (request trace), x-77-cache and x-77-pop
+        let headers: Vec<String> = response.headers
+            .iter()
+            .filter(|(name, _)| name.to_ascii_lowercase().starts_with("x-77-"))
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        if !headers.is_empty() {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-77-*".to_string()),
+                confidence: 0.93,
+                description: "CDN77 edge headers detected".to_string(),
+                raw_data: headers.join(", "),
+                signature_matched: "cdn77-edge-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for Cdn77Provider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for Cdn77Provider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
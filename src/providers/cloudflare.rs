@@ -1,6 +1,7 @@
 //! CloudFlare WAF/CDN Detection Provider
 
-use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType, ProviderFeature};
+use super::set_cookie_names;
 use regex::Regex;
 use std::sync::OnceLock;
 use anyhow::Result;
@@ -57,6 +58,110 @@ impl CloudFlareProvider {
         PATTERN.get_or_init(|| Regex::new(r"(?i)(cf_chl_jschl_tk|cf_clearance|cf_chl_captcha_tk)").unwrap())
     }
 
+    fn cf_challenge_type_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        // `_cf_chl_opt = { cType: 'managed', ... }` on current challenge pages
+        PATTERN.get_or_init(|| Regex::new(r#"cType:\s*['"]([a-z-]+)['"]"#).unwrap())
+    }
+
+    fn cf_turnstile_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r#"(challenges\.cloudflare\.com/turnstile|class=["'][^"']*cf-turnstile)"#).unwrap())
+    }
+
+    fn cf_legacy_js_challenge_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(cf_chl_jschl_tk|jschl[-_]answer|jschl_vc)").unwrap())
+    }
+
+    fn cf_jsd_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        // JavaScript detections injected by Bot Fight Mode / Bot Management
+        PATTERN.get_or_init(|| Regex::new(r"/cdn-cgi/challenge-platform/scripts/jsd/").unwrap())
+    }
+
+    fn feature(category: &str, value: &str, confidence: f64, evidence: &str) -> ProviderFeature {
+        ProviderFeature {
+            category: category.to_string(),
+            value: value.to_string(),
+            confidence,
+            evidence: evidence.to_string(),
+        }
+    }
+
+    /// Which kind of challenge (if any) the response serves
+    pub fn challenge_feature(&self, response: &crate::http::HttpResponse) -> Option<ProviderFeature> {
+        if let Some(captures) = Self::cf_challenge_type_pattern().captures(&response.body) {
+            let value = match &captures[1] {
+                "managed" => "managed",
+                "non-interactive" => "js",
+                "interactive" => "interactive",
+                _ => "unknown",
+            };
+            return Some(Self::feature("challenge", value, 0.90, &captures[0]));
+        }
+
+        if Self::cf_legacy_js_challenge_pattern().is_match(&response.body) {
+            return Some(Self::feature("challenge", "js", 0.85, "jschl challenge form"));
+        }
+
+        if response.body.contains("cf_chl_captcha_tk") {
+            return Some(Self::feature("challenge", "interactive", 0.85, "cf_chl_captcha_tk"));
+        }
+
+        // Turnstile is embedded by the site itself rather than served as an interstitial
+        if Self::cf_turnstile_pattern().is_match(&response.body) {
+            return Some(Self::feature("challenge", "turnstile", 0.90, "turnstile widget"));
+        }
+
+        if response.headers.get("cf-mitigated").is_some_and(|v| v.eq_ignore_ascii_case("challenge")) {
+            return Some(Self::feature("challenge", "unknown", 0.80, "cf-mitigated: challenge"));
+        }
+
+        None
+    }
+
+    /// Enterprise vs self-serve plan hints. Header ordering is not available
+    /// from `HttpResponse`, so only header presence and values are used.
+    pub fn plan_feature(&self, response: &crate::http::HttpResponse) -> Option<ProviderFeature> {
+        let proxied = response.headers.contains_key("cf-ray");
+        let server = response.headers.get("server");
+
+        // Only Enterprise zones can remove or rewrite the `server: cloudflare` header
+        if proxied && !server.is_some_and(|s| Self::cf_server_pattern().is_match(s)) {
+            let evidence = match server {
+                Some(server) => format!("server: {}", server),
+                None => "server header removed".to_string(),
+            };
+            return Some(Self::feature("plan", "enterprise", 0.60, &evidence));
+        }
+
+        // Polish image optimization is a Pro plan feature and above
+        for header in ["cf-polished", "cf-bgj"] {
+            if proxied && response.headers.contains_key(header) {
+                return Some(Self::feature("plan", "pro_or_higher", 0.70, header));
+            }
+        }
+
+        None
+    }
+
+    /// Bot Fight Mode vs (Enterprise) Bot Management
+    pub fn bot_feature(&self, response: &crate::http::HttpResponse, enterprise: bool) -> Option<ProviderFeature> {
+        let bm_cookie = set_cookie_names(response).iter().any(|c| c == "__cf_bm");
+        let jsd = Self::cf_jsd_pattern().is_match(&response.body);
+        if !bm_cookie && !jsd {
+            return None;
+        }
+
+        let evidence = if bm_cookie { "__cf_bm cookie" } else { "challenge-platform jsd script" };
+        if enterprise {
+            Some(Self::feature("bot", "bot_management", 0.70, evidence))
+        } else {
+            Some(Self::feature("bot", "bot_fight_mode", 0.65, evidence))
+        }
+    }
+
     async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
         let mut evidence = Vec::new();
 
@@ -256,6 +361,17 @@ impl DetectionProvider for CloudFlareProvider {
 
         Ok(all_evidence)
     }
+
+    fn features(&self, response: &crate::http::HttpResponse) -> Vec<ProviderFeature> {
+        let plan = self.plan_feature(response);
+        let enterprise = plan.as_ref().is_some_and(|p| p.value == "enterprise");
+
+        let mut features = Vec::new();
+        features.extend(self.challenge_feature(response));
+        features.extend(self.bot_feature(response, enterprise));
+        features.extend(plan);
+        features
+    }
 }
 
 impl Default for CloudFlareProvider {
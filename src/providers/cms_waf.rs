@@ -0,0 +1,195 @@
+//! CMS Plugin WAF Detection Provider (Wordfence, SiteLock, PHP security plugins)
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Plugin-level WAFs commonly found on WordPress and other PHP CMS sites
+#[derive(Debug, Clone)]
+pub struct CmsWafProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl CmsWafProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "CmsWaf".to_string(),
+            version: "1.0.0".to_string(),
+            description: "CMS plugin WAF (Wordfence, SiteLock, NinjaFirewall, BulletProof Security) detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn wordfence_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(generated by wordfence|wordfence-lockout|your access to this site has been limited by the site owner|a potentially unsafe operation has been detected in your request to this site)").unwrap())
+    }
+
+    fn sitelock_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(sitelock incident id|sitelock-site-verification|blocked by sitelock)").unwrap())
+    }
+
+    fn php_plugin_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(ninjafirewall: 403 forbidden|bulletproof security|bpsmessage|all in one wp security|aiowps)").unwrap())
+    }
+
+    fn wordfence_cookie_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"^(wfwaf-authcookie-.+|wfvt_\d+|wordfence_verifiedHuman)$").unwrap())
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // SiteLock TrueShield stamps its own x-sitelock-* headers
+        let mut sitelock_headers: Vec<&str> = response.headers
+            .keys()
+            .filter(|k| k.starts_with("x-sitelock"))
+            .map(|k| k.as_str())
+            .collect();
+        if !sitelock_headers.is_empty() {
+            sitelock_headers.sort_unstable();
+            evidence.push(Evidence {
+                method_type: MethodType::Header(sitelock_headers[0].to_string()),
+                confidence: 0.92,
+                description: format!("SiteLock headers detected ({})", sitelock_headers.join(", ")),
+                raw_data: sitelock_headers.join(", "),
+                signature_matched: "sitelock-header".to_string(),
+            });
+        }
+
+        // Security plugins that advertise themselves next to the PHP runtime
+        if let Some(powered_by) = response.headers.get("x-powered-by") {
+            let lower = powered_by.to_lowercase();
+            if lower.contains("wordfence") || lower.contains("ninjafirewall") || lower.contains("sitelock") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("x-powered-by".to_string()),
+                    confidence: 0.85,
+                    description: "CMS security plugin advertised in x-powered-by header".to_string(),
+                    raw_data: powered_by.clone(),
+                    signature_matched: "cms-waf-powered-by".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        for cookie in set_cookie_names(response) {
+            if Self::wordfence_cookie_pattern().is_match(&cookie) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence: 0.90,
+                    description: format!("Wordfence {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "wordfence-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+        let blocked = response.status >= 400;
+
+        if Self::wordfence_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("wordfence-block-page".to_string()),
+                confidence: if blocked { 0.92 } else { 0.75 },
+                description: "Wordfence block page detected".to_string(),
+                raw_data: "wordfence-block-page".to_string(),
+                signature_matched: "wordfence-block-page".to_string(),
+            });
+        }
+
+        if Self::sitelock_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("sitelock-block-page".to_string()),
+                confidence: if blocked { 0.88 } else { 0.70 },
+                description: "SiteLock block page detected".to_string(),
+                raw_data: "sitelock-block-page".to_string(),
+                signature_matched: "sitelock-block-page".to_string(),
+            });
+        }
+
+        // Generic PHP plugin WAFs only count when they actually blocked the request
+        if blocked && Self::php_plugin_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("php-plugin-block-page".to_string()),
+                confidence: 0.75,
+                description: "PHP security plugin block page detected".to_string(),
+                raw_data: "php-plugin-block-page".to_string(),
+                signature_matched: "cms-waf-plugin-block-page".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for CmsWafProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::WAF
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.80
+    }
+
+    fn priority(&self) -> u32 {
+        60
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for CmsWafProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,130 @@
+//! DataDome Bot Protection Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use anyhow::Result;
+
+/// DataDome bot management detection provider
+#[derive(Debug, Clone)]
+pub struct DataDomeProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl DataDomeProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "DataDome".to_string(),
+            version: "1.0.0".to_string(),
+            description: "DataDome bot protection detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // x-datadome is sent on every protected response, x-datadome-cid with challenges
+        for header in ["x-datadome", "x-datadome-cid"] {
+            if let Some(value) = response.headers.get(header) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header(header.to_string()),
+                    confidence: 0.95,
+                    description: format!("DataDome {} header detected", header),
+                    raw_data: value.clone(),
+                    signature_matched: "datadome-header".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if set_cookie_names(response).iter().any(|cookie| cookie == "datadome") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("set-cookie".to_string()),
+                confidence: 0.90,
+                description: "DataDome session cookie detected".to_string(),
+                raw_data: "datadome".to_string(),
+                signature_matched: "datadome-cookie".to_string(),
+            });
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if response.body.contains("captcha-delivery.com") {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("captcha-delivery.com".to_string()),
+                confidence: 0.90,
+                description: "DataDome captcha interstitial detected".to_string(),
+                raw_data: "captcha-delivery.com".to_string(),
+                signature_matched: "datadome-captcha".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for DataDomeProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::BotManagement
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.90
+    }
+
+    fn priority(&self) -> u32 {
+        70
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for DataDomeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -68,7 +68,7 @@ impl FastlyProvider {
         }
 
         // Check Via header for Varnish (Fastly's cache technology)
-        if let Some(via) = response.headers.get("via") {
+        for via in response.headers.get_all("via") {
             if Self::fastly_via_pattern().is_match(via) {
                 evidence.push(Evidence {
                     method_type: MethodType::Header("via".to_string()),
@@ -94,7 +94,7 @@ impl FastlyProvider {
         }
 
         // Check x-cache header for Fastly cache status
-        if let Some(cache) = response.headers.get("x-cache") {
+        for cache in response.headers.get_all("x-cache") {
             // Exclude CloudFront patterns explicitly
             if Self::fastly_cache_pattern().is_match(cache) && 
                !cache.to_lowercase().contains("cloudfront") &&
@@ -0,0 +1,121 @@
+//! Fly.io Edge Proxy Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Fly.io platform CDN detection provider
+#[derive(Debug, Clone)]
+pub struct FlyProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl FlyProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "Fly.io".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Fly.io edge proxy detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    /// `Fly/9e5f3e8f (2024-01-15)`: proxy build and date
+    fn server_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"^Fly/[0-9a-f]+ \(\d{4}-\d{2}-\d{2}\)$").unwrap())
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(server) = response.headers.get("server") {
+            if Self::server_pattern().is_match(server) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.95,
+                    description: "Fly.io proxy server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "fly-server-header".to_string(),
+                });
+            }
+        }
+
+        // ULID followed by the region that took the request, e.g. `01HQ...-fra`
+        if let Some(request_id) = response.headers.get("fly-request-id") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("fly-request-id".to_string()),
+                confidence: 0.95,
+                description: "Fly.io request ID header detected".to_string(),
+                raw_data: request_id.clone(),
+                signature_matched: "fly-request-id-header".to_string(),
+            });
+        }
+
+        if let Some(via) = response.headers.get("via") {
+            if via.split(',').any(|hop| hop.trim().ends_with(" fly.io")) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("via".to_string()),
+                    confidence: 0.90,
+                    description: "Fly.io proxy in Via header".to_string(),
+                    raw_data: via.clone(),
+                    signature_matched: "fly-via-header".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for FlyProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        85
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for FlyProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,136 @@
+//! Fortinet FortiWeb Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Fortinet FortiWeb WAF appliance detection provider
+#[derive(Debug, Clone)]
+pub struct FortiWebProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl FortiWebProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "FortiWeb".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Fortinet FortiWeb Web Application Firewall detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn fortiweb_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(\.fgd_icon|fortiweb|fortigate application control)").unwrap())
+    }
+
+    fn fortiweb_attack_id_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)attack id:\s*\d+").unwrap())
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        for cookie in set_cookie_names(response) {
+            if cookie.eq_ignore_ascii_case("FORTIWAFSID") || cookie.starts_with("cookiesession1") {
+                let confidence = if cookie.eq_ignore_ascii_case("FORTIWAFSID") { 0.96 } else { 0.70 };
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence,
+                    description: format!("FortiWeb {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "fortiweb-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if Self::fortiweb_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("block-page-detected".to_string()),
+                confidence: if response.status >= 400 { 0.85 } else { 0.65 },
+                description: "FortiWeb block page detected".to_string(),
+                raw_data: "block-page-detected".to_string(),
+                signature_matched: "fortiweb-block-page".to_string(),
+            });
+        }
+
+        if response.status >= 400 && Self::fortiweb_attack_id_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("attack-id-detected".to_string()),
+                confidence: 0.75,
+                description: "FortiWeb attack ID in block page detected".to_string(),
+                raw_data: "attack-id-detected".to_string(),
+                signature_matched: "fortiweb-attack-id".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for FortiWebProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::WAF
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for FortiWebProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
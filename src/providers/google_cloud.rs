@@ -0,0 +1,208 @@
+//! Google Cloud Armor / Cloud CDN Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType, StackLayer};
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Google Cloud Armor (WAF) and Cloud CDN detection provider
+#[derive(Debug, Clone)]
+pub struct GoogleCloudProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl GoogleCloudProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "GoogleCloud".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Google Cloud Armor WAF and Cloud CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn google_via_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)\b1\.1 google\b").unwrap())
+    }
+
+    fn google_server_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)^(google frontend|gfe/[0-9.]+)$").unwrap())
+    }
+
+    fn google_error_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(that[’']s an error\.|your client does not have permission to get url)").unwrap())
+    }
+
+    fn cloud_armor_block_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        // Cloud Armor's default deny page is a bare "403 Forbidden" document
+        PATTERN.get_or_init(|| Regex::new(r"(?is)^\s*(<!doctype html>.*<title>403</title>\s*)?403 Forbidden\s*$").unwrap())
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // Google load balancer / Cloud CDN via header
+        for via in response.headers.get_all("via") {
+            if Self::google_via_pattern().is_match(via) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("via".to_string()),
+                    confidence: 0.92,
+                    description: "Google Cloud load balancer via header detected".to_string(),
+                    raw_data: via.clone(),
+                    signature_matched: "gcp-via-google-header".to_string(),
+                });
+            }
+        }
+
+        // Google Cloud Storage / Cloud CDN object headers
+        let goog_headers: Vec<&String> = response.headers
+            .keys()
+            .filter(|k| k.starts_with("x-goog-"))
+            .collect();
+        if !goog_headers.is_empty() {
+            let mut names: Vec<&str> = goog_headers.iter().map(|k| k.as_str()).collect();
+            names.sort_unstable();
+            evidence.push(Evidence {
+                method_type: MethodType::Header(names[0].to_string()),
+                confidence: 0.90,
+                description: format!("Google Cloud x-goog-* headers detected ({})", names.join(", ")),
+                raw_data: names.join(", "),
+                signature_matched: "gcp-goog-header".to_string(),
+            });
+        }
+
+        // Google Front End server header
+        if let Some(server) = response.headers.get("server") {
+            if Self::google_server_pattern().is_match(server) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.85,
+                    description: "Google Front End server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "gcp-google-frontend-server".to_string(),
+                });
+            }
+        }
+
+        // Cloud CDN cache hits carry an age header alongside the Google via header
+        if response.headers.contains_key("age")
+            && response.headers.get_all("via").any(|v| Self::google_via_pattern().is_match(v))
+        {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("age".to_string()),
+                confidence: 0.75,
+                description: "Google Cloud CDN cached response detected".to_string(),
+                raw_data: response.headers.get("age").cloned().unwrap_or_default(),
+                signature_matched: "gcp-cloud-cdn-cache".to_string(),
+            });
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // Google Front End error page ("That's an error.")
+        if Self::google_error_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("google-error-page".to_string()),
+                confidence: 0.80,
+                description: "Google Front End error page detected".to_string(),
+                raw_data: "google-error-page".to_string(),
+                signature_matched: "gcp-error-body".to_string(),
+            });
+        }
+
+        evidence
+    }
+
+    pub async fn check_status_codes(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // Cloud Armor deny rule: bare 403 page served through the Google load balancer
+        let via_google = response.headers.get_all("via").any(|v| Self::google_via_pattern().is_match(v));
+        if response.status == 403 && Self::cloud_armor_block_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::StatusCode(403),
+                confidence: if via_google { 0.90 } else { 0.60 },
+                description: "Google Cloud Armor 403 deny page detected".to_string(),
+                raw_data: "403".to_string(),
+                signature_matched: "gcp-cloud-armor-403".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for GoogleCloudProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Both // Cloud Armor (WAF) sits in front of Cloud CDN
+    }
+
+    fn stack_layer(&self) -> StackLayer {
+        StackLayer::CloudWaf // Cloud Armor runs at the Google load balancer
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        87
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        if let Some(response) = &context.response {
+            all_evidence.extend(self.check_headers(response).await);
+            all_evidence.extend(self.check_body_patterns(response).await);
+            all_evidence.extend(self.check_status_codes(response).await);
+        }
+
+        Ok(all_evidence)
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+        all_evidence.extend(self.check_status_codes(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for GoogleCloudProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,148 @@
+//! Huawei Cloud WAF / CDN Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use anyhow::Result;
+
+/// Huawei Cloud WAF and CDN detection provider
+#[derive(Debug, Clone)]
+pub struct HuaweiCloudProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl HuaweiCloudProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "HuaweiCloud".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Huawei Cloud WAF and CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(server) = response.headers.get("server") {
+            if server.eq_ignore_ascii_case("HuaweiCloudWAF") || server.eq_ignore_ascii_case("CloudWAF") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.95,
+                    description: "Huawei Cloud WAF server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "huawei-waf-server-header".to_string(),
+                });
+            }
+        }
+
+        // Huawei Cloud CDN cache headers
+        let cdn_headers: Vec<&str> = ["x-ccdn-cachettl", "x-ccdn-req-id-", "x-hcs-proxy-type"]
+            .into_iter()
+            .filter(|prefix| response.headers.keys().any(|name| name.starts_with(prefix)))
+            .collect();
+        if !cdn_headers.is_empty() {
+            evidence.push(Evidence {
+                method_type: MethodType::Header(cdn_headers[0].trim_end_matches('-').to_string()),
+                confidence: 0.90,
+                description: "Huawei Cloud CDN headers detected".to_string(),
+                raw_data: cdn_headers.join(", "),
+                signature_matched: "huawei-cdn-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        for cookie in set_cookie_names(response) {
+            if cookie == "HWWAFSESID" || cookie == "HWWAFSESTIME" {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence: 0.93,
+                    description: format!("Huawei Cloud WAF {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "huawei-waf-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // The block page links to Huawei Cloud support and embeds the WAF's event ID
+        let markers = ["hwclouds.com", "hws_security", "huaweicloud.com/waf"];
+        if let Some(marker) = markers.iter().find(|marker| response.body.contains(**marker)) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body(marker.to_string()),
+                confidence: 0.88,
+                description: "Huawei Cloud WAF block page detected".to_string(),
+                raw_data: marker.to_string(),
+                signature_matched: "huawei-waf-block-page".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for HuaweiCloudProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Both
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        85
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for HuaweiCloudProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
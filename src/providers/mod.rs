@@ -3,8 +3,27 @@ pub mod akamai;
 pub mod aws;
 pub mod fastly;
 pub mod vercel;
+pub mod netlify;
+pub mod render;
+pub mod fly;
+pub mod bunnycdn;
+pub mod stackpath;
+pub mod cdn77;
+pub mod cachefly;
+pub mod azure;
+pub mod google_cloud;
+pub mod alibaba_cloud;
+pub mod tencent_cloud;
+pub mod huawei_cloud;
+pub mod barracuda;
+pub mod fortiweb;
+pub mod netscaler;
+pub mod cms_waf;
+pub mod datadome;
+pub mod perimeterx;
+pub mod signature_based;
 
-use crate::{DetectionContext, Evidence, http::HttpClient, ProviderType, DetectionProvider};
+use crate::{DetectionContext, Evidence, http::HttpClient, ProviderType, DetectionProvider, ProviderFeature, StackLayer};
 use anyhow::Result;
 
 /// Provider enum to solve async trait object issue
@@ -15,6 +34,55 @@ pub enum Provider {
     AWS(aws::AwsProvider),
     Fastly(fastly::FastlyProvider),
     Vercel(vercel::VercelProvider),
+    Netlify(netlify::NetlifyProvider),
+    Render(render::RenderProvider),
+    Fly(fly::FlyProvider),
+    BunnyCdn(bunnycdn::BunnyCdnProvider),
+    StackPath(stackpath::StackPathProvider),
+    Cdn77(cdn77::Cdn77Provider),
+    Cachefly(cachefly::CacheflyProvider),
+    Azure(azure::AzureProvider),
+    GoogleCloud(google_cloud::GoogleCloudProvider),
+    AlibabaCloud(alibaba_cloud::AlibabaCloudProvider),
+    TencentCloud(tencent_cloud::TencentCloudProvider),
+    HuaweiCloud(huawei_cloud::HuaweiCloudProvider),
+    Barracuda(barracuda::BarracudaProvider),
+    FortiWeb(fortiweb::FortiWebProvider),
+    NetScaler(netscaler::NetScalerProvider),
+    CmsWaf(cms_waf::CmsWafProvider),
+    DataDome(datadome::DataDomeProvider),
+    PerimeterX(perimeterx::PerimeterXProvider),
+    /// Rules from a signature bundle, optionally extending a built-in provider
+    Signature(signature_based::SignatureProvider),
+}
+
+/// Every built-in provider, in registration order
+pub fn default_providers() -> Vec<Provider> {
+    vec![
+        Provider::CloudFlare(cloudflare::CloudFlareProvider::new()),
+        Provider::Akamai(akamai::AkamaiProvider::new()),
+        Provider::AWS(aws::AwsProvider::new()),
+        Provider::Fastly(fastly::FastlyProvider::new()),
+        Provider::Vercel(vercel::VercelProvider::new()),
+        Provider::Netlify(netlify::NetlifyProvider::new()),
+        Provider::Render(render::RenderProvider::new()),
+        Provider::Fly(fly::FlyProvider::new()),
+        Provider::BunnyCdn(bunnycdn::BunnyCdnProvider::new()),
+        Provider::StackPath(stackpath::StackPathProvider::new()),
+        Provider::Cdn77(cdn77::Cdn77Provider::new()),
+        Provider::Cachefly(cachefly::CacheflyProvider::new()),
+        Provider::Azure(azure::AzureProvider::new()),
+        Provider::GoogleCloud(google_cloud::GoogleCloudProvider::new()),
+        Provider::AlibabaCloud(alibaba_cloud::AlibabaCloudProvider::new()),
+        Provider::TencentCloud(tencent_cloud::TencentCloudProvider::new()),
+        Provider::HuaweiCloud(huawei_cloud::HuaweiCloudProvider::new()),
+        Provider::Barracuda(barracuda::BarracudaProvider::new()),
+        Provider::FortiWeb(fortiweb::FortiWebProvider::new()),
+        Provider::NetScaler(netscaler::NetScalerProvider::new()),
+        Provider::CmsWaf(cms_waf::CmsWafProvider::new()),
+        Provider::DataDome(datadome::DataDomeProvider::new()),
+        Provider::PerimeterX(perimeterx::PerimeterXProvider::new()),
+    ]
 }
 
 impl Provider {
@@ -25,6 +93,25 @@ impl Provider {
             Provider::AWS(p) => p.name(),
             Provider::Fastly(p) => p.name(),
             Provider::Vercel(p) => p.name(),
+            Provider::Netlify(p) => p.name(),
+            Provider::Render(p) => p.name(),
+            Provider::Fly(p) => p.name(),
+            Provider::BunnyCdn(p) => p.name(),
+            Provider::StackPath(p) => p.name(),
+            Provider::Cdn77(p) => p.name(),
+            Provider::Cachefly(p) => p.name(),
+            Provider::Azure(p) => p.name(),
+            Provider::GoogleCloud(p) => p.name(),
+            Provider::AlibabaCloud(p) => p.name(),
+            Provider::TencentCloud(p) => p.name(),
+            Provider::HuaweiCloud(p) => p.name(),
+            Provider::Barracuda(p) => p.name(),
+            Provider::FortiWeb(p) => p.name(),
+            Provider::NetScaler(p) => p.name(),
+            Provider::CmsWaf(p) => p.name(),
+            Provider::DataDome(p) => p.name(),
+            Provider::PerimeterX(p) => p.name(),
+            Provider::Signature(p) => p.name(),
         }
     }
 
@@ -35,6 +122,25 @@ impl Provider {
             Provider::AWS(p) => p.version(),
             Provider::Fastly(p) => p.version(),
             Provider::Vercel(p) => p.version(),
+            Provider::Netlify(p) => p.version(),
+            Provider::Render(p) => p.version(),
+            Provider::Fly(p) => p.version(),
+            Provider::BunnyCdn(p) => p.version(),
+            Provider::StackPath(p) => p.version(),
+            Provider::Cdn77(p) => p.version(),
+            Provider::Cachefly(p) => p.version(),
+            Provider::Azure(p) => p.version(),
+            Provider::GoogleCloud(p) => p.version(),
+            Provider::AlibabaCloud(p) => p.version(),
+            Provider::TencentCloud(p) => p.version(),
+            Provider::HuaweiCloud(p) => p.version(),
+            Provider::Barracuda(p) => p.version(),
+            Provider::FortiWeb(p) => p.version(),
+            Provider::NetScaler(p) => p.version(),
+            Provider::CmsWaf(p) => p.version(),
+            Provider::DataDome(p) => p.version(),
+            Provider::PerimeterX(p) => p.version(),
+            Provider::Signature(p) => p.version(),
         }
     }
 
@@ -45,6 +151,25 @@ impl Provider {
             Provider::AWS(p) => p.description(),
             Provider::Fastly(p) => p.description(),
             Provider::Vercel(p) => p.description(),
+            Provider::Netlify(p) => p.description(),
+            Provider::Render(p) => p.description(),
+            Provider::Fly(p) => p.description(),
+            Provider::BunnyCdn(p) => p.description(),
+            Provider::StackPath(p) => p.description(),
+            Provider::Cdn77(p) => p.description(),
+            Provider::Cachefly(p) => p.description(),
+            Provider::Azure(p) => p.description(),
+            Provider::GoogleCloud(p) => p.description(),
+            Provider::AlibabaCloud(p) => p.description(),
+            Provider::TencentCloud(p) => p.description(),
+            Provider::HuaweiCloud(p) => p.description(),
+            Provider::Barracuda(p) => p.description(),
+            Provider::FortiWeb(p) => p.description(),
+            Provider::NetScaler(p) => p.description(),
+            Provider::CmsWaf(p) => p.description(),
+            Provider::DataDome(p) => p.description(),
+            Provider::PerimeterX(p) => p.description(),
+            Provider::Signature(p) => p.description(),
         }
     }
 
@@ -55,6 +180,25 @@ impl Provider {
             Provider::AWS(p) => p.provider_type(),
             Provider::Fastly(p) => p.provider_type(),
             Provider::Vercel(p) => p.provider_type(),
+            Provider::Netlify(p) => p.provider_type(),
+            Provider::Render(p) => p.provider_type(),
+            Provider::Fly(p) => p.provider_type(),
+            Provider::BunnyCdn(p) => p.provider_type(),
+            Provider::StackPath(p) => p.provider_type(),
+            Provider::Cdn77(p) => p.provider_type(),
+            Provider::Cachefly(p) => p.provider_type(),
+            Provider::Azure(p) => p.provider_type(),
+            Provider::GoogleCloud(p) => p.provider_type(),
+            Provider::AlibabaCloud(p) => p.provider_type(),
+            Provider::TencentCloud(p) => p.provider_type(),
+            Provider::HuaweiCloud(p) => p.provider_type(),
+            Provider::Barracuda(p) => p.provider_type(),
+            Provider::FortiWeb(p) => p.provider_type(),
+            Provider::NetScaler(p) => p.provider_type(),
+            Provider::CmsWaf(p) => p.provider_type(),
+            Provider::DataDome(p) => p.provider_type(),
+            Provider::PerimeterX(p) => p.provider_type(),
+            Provider::Signature(p) => p.provider_type(),
         }
     }
 
@@ -65,6 +209,25 @@ impl Provider {
             Provider::AWS(p) => p.confidence_base(),
             Provider::Fastly(p) => p.confidence_base(),
             Provider::Vercel(p) => p.confidence_base(),
+            Provider::Netlify(p) => p.confidence_base(),
+            Provider::Render(p) => p.confidence_base(),
+            Provider::Fly(p) => p.confidence_base(),
+            Provider::BunnyCdn(p) => p.confidence_base(),
+            Provider::StackPath(p) => p.confidence_base(),
+            Provider::Cdn77(p) => p.confidence_base(),
+            Provider::Cachefly(p) => p.confidence_base(),
+            Provider::Azure(p) => p.confidence_base(),
+            Provider::GoogleCloud(p) => p.confidence_base(),
+            Provider::AlibabaCloud(p) => p.confidence_base(),
+            Provider::TencentCloud(p) => p.confidence_base(),
+            Provider::HuaweiCloud(p) => p.confidence_base(),
+            Provider::Barracuda(p) => p.confidence_base(),
+            Provider::FortiWeb(p) => p.confidence_base(),
+            Provider::NetScaler(p) => p.confidence_base(),
+            Provider::CmsWaf(p) => p.confidence_base(),
+            Provider::DataDome(p) => p.confidence_base(),
+            Provider::PerimeterX(p) => p.confidence_base(),
+            Provider::Signature(p) => p.confidence_base(),
         }
     }
 
@@ -75,6 +238,25 @@ impl Provider {
             Provider::AWS(p) => p.priority(),
             Provider::Fastly(p) => p.priority(),
             Provider::Vercel(p) => p.priority(),
+            Provider::Netlify(p) => p.priority(),
+            Provider::Render(p) => p.priority(),
+            Provider::Fly(p) => p.priority(),
+            Provider::BunnyCdn(p) => p.priority(),
+            Provider::StackPath(p) => p.priority(),
+            Provider::Cdn77(p) => p.priority(),
+            Provider::Cachefly(p) => p.priority(),
+            Provider::Azure(p) => p.priority(),
+            Provider::GoogleCloud(p) => p.priority(),
+            Provider::AlibabaCloud(p) => p.priority(),
+            Provider::TencentCloud(p) => p.priority(),
+            Provider::HuaweiCloud(p) => p.priority(),
+            Provider::Barracuda(p) => p.priority(),
+            Provider::FortiWeb(p) => p.priority(),
+            Provider::NetScaler(p) => p.priority(),
+            Provider::CmsWaf(p) => p.priority(),
+            Provider::DataDome(p) => p.priority(),
+            Provider::PerimeterX(p) => p.priority(),
+            Provider::Signature(p) => p.priority(),
         }
     }
 
@@ -85,6 +267,25 @@ impl Provider {
             Provider::AWS(p) => p.enabled(),
             Provider::Fastly(p) => p.enabled(),
             Provider::Vercel(p) => p.enabled(),
+            Provider::Netlify(p) => p.enabled(),
+            Provider::Render(p) => p.enabled(),
+            Provider::Fly(p) => p.enabled(),
+            Provider::BunnyCdn(p) => p.enabled(),
+            Provider::StackPath(p) => p.enabled(),
+            Provider::Cdn77(p) => p.enabled(),
+            Provider::Cachefly(p) => p.enabled(),
+            Provider::Azure(p) => p.enabled(),
+            Provider::GoogleCloud(p) => p.enabled(),
+            Provider::AlibabaCloud(p) => p.enabled(),
+            Provider::TencentCloud(p) => p.enabled(),
+            Provider::HuaweiCloud(p) => p.enabled(),
+            Provider::Barracuda(p) => p.enabled(),
+            Provider::FortiWeb(p) => p.enabled(),
+            Provider::NetScaler(p) => p.enabled(),
+            Provider::CmsWaf(p) => p.enabled(),
+            Provider::DataDome(p) => p.enabled(),
+            Provider::PerimeterX(p) => p.enabled(),
+            Provider::Signature(p) => p.enabled(),
         }
     }
 
@@ -95,6 +296,25 @@ impl Provider {
             Provider::AWS(p) => p.detect(context).await,
             Provider::Fastly(p) => p.detect(context).await,
             Provider::Vercel(p) => p.detect(context).await,
+            Provider::Netlify(p) => p.detect(context).await,
+            Provider::Render(p) => p.detect(context).await,
+            Provider::Fly(p) => p.detect(context).await,
+            Provider::BunnyCdn(p) => p.detect(context).await,
+            Provider::StackPath(p) => p.detect(context).await,
+            Provider::Cdn77(p) => p.detect(context).await,
+            Provider::Cachefly(p) => p.detect(context).await,
+            Provider::Azure(p) => p.detect(context).await,
+            Provider::GoogleCloud(p) => p.detect(context).await,
+            Provider::AlibabaCloud(p) => p.detect(context).await,
+            Provider::TencentCloud(p) => p.detect(context).await,
+            Provider::HuaweiCloud(p) => p.detect(context).await,
+            Provider::Barracuda(p) => p.detect(context).await,
+            Provider::FortiWeb(p) => p.detect(context).await,
+            Provider::NetScaler(p) => p.detect(context).await,
+            Provider::CmsWaf(p) => p.detect(context).await,
+            Provider::DataDome(p) => p.detect(context).await,
+            Provider::PerimeterX(p) => p.detect(context).await,
+            Provider::Signature(p) => p.detect(context).await,
         }
     }
 
@@ -105,6 +325,83 @@ impl Provider {
             Provider::AWS(p) => p.passive_detect(response).await,
             Provider::Fastly(p) => p.passive_detect(response).await,
             Provider::Vercel(p) => p.passive_detect(response).await,
+            Provider::Netlify(p) => p.passive_detect(response).await,
+            Provider::Render(p) => p.passive_detect(response).await,
+            Provider::Fly(p) => p.passive_detect(response).await,
+            Provider::BunnyCdn(p) => p.passive_detect(response).await,
+            Provider::StackPath(p) => p.passive_detect(response).await,
+            Provider::Cdn77(p) => p.passive_detect(response).await,
+            Provider::Cachefly(p) => p.passive_detect(response).await,
+            Provider::Azure(p) => p.passive_detect(response).await,
+            Provider::GoogleCloud(p) => p.passive_detect(response).await,
+            Provider::AlibabaCloud(p) => p.passive_detect(response).await,
+            Provider::TencentCloud(p) => p.passive_detect(response).await,
+            Provider::HuaweiCloud(p) => p.passive_detect(response).await,
+            Provider::Barracuda(p) => p.passive_detect(response).await,
+            Provider::FortiWeb(p) => p.passive_detect(response).await,
+            Provider::NetScaler(p) => p.passive_detect(response).await,
+            Provider::CmsWaf(p) => p.passive_detect(response).await,
+            Provider::DataDome(p) => p.passive_detect(response).await,
+            Provider::PerimeterX(p) => p.passive_detect(response).await,
+            Provider::Signature(p) => p.passive_detect(response).await,
+        }
+    }
+
+    pub fn features(&self, response: &crate::http::HttpResponse) -> Vec<ProviderFeature> {
+        match self {
+            Provider::CloudFlare(p) => p.features(response),
+            Provider::Akamai(p) => p.features(response),
+            Provider::AWS(p) => p.features(response),
+            Provider::Fastly(p) => p.features(response),
+            Provider::Vercel(p) => p.features(response),
+            Provider::Netlify(p) => p.features(response),
+            Provider::Render(p) => p.features(response),
+            Provider::Fly(p) => p.features(response),
+            Provider::BunnyCdn(p) => p.features(response),
+            Provider::StackPath(p) => p.features(response),
+            Provider::Cdn77(p) => p.features(response),
+            Provider::Cachefly(p) => p.features(response),
+            Provider::Azure(p) => p.features(response),
+            Provider::GoogleCloud(p) => p.features(response),
+            Provider::AlibabaCloud(p) => p.features(response),
+            Provider::TencentCloud(p) => p.features(response),
+            Provider::HuaweiCloud(p) => p.features(response),
+            Provider::Barracuda(p) => p.features(response),
+            Provider::FortiWeb(p) => p.features(response),
+            Provider::NetScaler(p) => p.features(response),
+            Provider::CmsWaf(p) => p.features(response),
+            Provider::DataDome(p) => p.features(response),
+            Provider::PerimeterX(p) => p.features(response),
+            Provider::Signature(p) => p.features(response),
+        }
+    }
+
+    pub fn stack_layer(&self) -> StackLayer {
+        match self {
+            Provider::CloudFlare(p) => p.stack_layer(),
+            Provider::Akamai(p) => p.stack_layer(),
+            Provider::AWS(p) => p.stack_layer(),
+            Provider::Fastly(p) => p.stack_layer(),
+            Provider::Vercel(p) => p.stack_layer(),
+            Provider::Netlify(p) => p.stack_layer(),
+            Provider::Render(p) => p.stack_layer(),
+            Provider::Fly(p) => p.stack_layer(),
+            Provider::BunnyCdn(p) => p.stack_layer(),
+            Provider::StackPath(p) => p.stack_layer(),
+            Provider::Cdn77(p) => p.stack_layer(),
+            Provider::Cachefly(p) => p.stack_layer(),
+            Provider::Azure(p) => p.stack_layer(),
+            Provider::GoogleCloud(p) => p.stack_layer(),
+            Provider::AlibabaCloud(p) => p.stack_layer(),
+            Provider::TencentCloud(p) => p.stack_layer(),
+            Provider::HuaweiCloud(p) => p.stack_layer(),
+            Provider::Barracuda(p) => p.stack_layer(),
+            Provider::FortiWeb(p) => p.stack_layer(),
+            Provider::NetScaler(p) => p.stack_layer(),
+            Provider::CmsWaf(p) => p.stack_layer(),
+            Provider::DataDome(p) => p.stack_layer(),
+            Provider::PerimeterX(p) => p.stack_layer(),
+            Provider::Signature(p) => p.stack_layer(),
         }
     }
 
@@ -115,12 +412,43 @@ impl Provider {
             Provider::AWS(p) => p.active_detect(client, url).await,
             Provider::Fastly(p) => p.active_detect(client, url).await,
             Provider::Vercel(p) => p.active_detect(client, url).await,
+            Provider::Netlify(p) => p.active_detect(client, url).await,
+            Provider::Render(p) => p.active_detect(client, url).await,
+            Provider::Fly(p) => p.active_detect(client, url).await,
+            Provider::BunnyCdn(p) => p.active_detect(client, url).await,
+            Provider::StackPath(p) => p.active_detect(client, url).await,
+            Provider::Cdn77(p) => p.active_detect(client, url).await,
+            Provider::Cachefly(p) => p.active_detect(client, url).await,
+            Provider::Azure(p) => p.active_detect(client, url).await,
+            Provider::GoogleCloud(p) => p.active_detect(client, url).await,
+            Provider::AlibabaCloud(p) => p.active_detect(client, url).await,
+            Provider::TencentCloud(p) => p.active_detect(client, url).await,
+            Provider::HuaweiCloud(p) => p.active_detect(client, url).await,
+            Provider::Barracuda(p) => p.active_detect(client, url).await,
+            Provider::FortiWeb(p) => p.active_detect(client, url).await,
+            Provider::NetScaler(p) => p.active_detect(client, url).await,
+            Provider::CmsWaf(p) => p.active_detect(client, url).await,
+            Provider::DataDome(p) => p.active_detect(client, url).await,
+            Provider::PerimeterX(p) => p.active_detect(client, url).await,
+            Provider::Signature(p) => p.active_detect(client, url).await,
         }
     }
 }
 
+/// Names of the cookies set by a response, from every `Set-Cookie` header (captured
+/// fixtures may also list several cookies in one value, one per line)
+pub fn set_cookie_names(response: &crate::http::HttpResponse) -> Vec<String> {
+    response.headers
+        .get_all("set-cookie")
+        .flat_map(|cookies| cookies.lines())
+        .filter_map(|cookie| cookie.split('=').next())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
 /// Provider metadata for listing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ProviderMetadata {
     pub name: String,
     pub version: String,
@@ -140,6 +468,7 @@ impl From<&Provider> for ProviderMetadata {
                 ProviderType::WAF => "WAF Only".to_string(),
                 ProviderType::CDN => "CDN Only".to_string(),
                 ProviderType::Both => "Both".to_string(),
+                ProviderType::BotManagement => "Bot Management".to_string(),
             },
             enabled: provider.enabled(),
             priority: provider.priority(),
@@ -0,0 +1,124 @@
+//! Netlify Edge Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use anyhow::Result;
+
+/// Netlify platform CDN detection provider
+#[derive(Debug, Clone)]
+pub struct NetlifyProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl NetlifyProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "Netlify".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Netlify Edge platform CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(server) = response.headers.get("server") {
+            if server.eq_ignore_ascii_case("netlify") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.95,
+                    description: "Netlify server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "netlify-server-header".to_string(),
+                });
+            }
+        }
+
+        // Every response from the edge carries a request ID, including ones proxied to an origin
+        if let Some(request_id) = response.headers.get("x-nf-request-id") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-nf-request-id".to_string()),
+                confidence: 0.95,
+                description: "Netlify request ID header detected".to_string(),
+                raw_data: request_id.clone(),
+                signature_matched: "netlify-request-id-header".to_string(),
+            });
+        }
+
+        // RFC 9211 cache status, e.g. `"Netlify Edge"; fwd=miss`
+        if let Some(cache_status) = response.headers.get("cache-status") {
+            if cache_status.contains("Netlify") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("cache-status".to_string()),
+                    confidence: 0.90,
+                    description: "Netlify cache status header detected".to_string(),
+                    raw_data: cache_status.clone(),
+                    signature_matched: "netlify-cache-status".to_string(),
+                });
+            }
+        }
+
+        if let Some(vary) = response.headers.get("netlify-vary") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("netlify-vary".to_string()),
+                confidence: 0.85,
+                description: "Netlify cache key header detected".to_string(),
+                raw_data: vary.clone(),
+                signature_matched: "netlify-vary-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for NetlifyProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        90
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for NetlifyProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
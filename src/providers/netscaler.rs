@@ -0,0 +1,159 @@
+//! Citrix NetScaler AppFirewall Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// Citrix NetScaler (ADC) Application Firewall detection provider
+#[derive(Debug, Clone)]
+pub struct NetScalerProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl NetScalerProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "NetScaler".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Citrix NetScaler AppFirewall detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn netscaler_cookie_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"^(ns_af|citrix_ns_id|NSC_.+)$").unwrap())
+    }
+
+    fn netscaler_block_page_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"(?i)(ns transaction id|appfw session id|netscaler appfw)").unwrap())
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        for cookie in set_cookie_names(response) {
+            if Self::netscaler_cookie_pattern().is_match(&cookie) {
+                // ns_af is set by the AppFirewall itself, NSC_ by the load balancer
+                let confidence = if cookie == "ns_af" { 0.95 } else { 0.85 };
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence,
+                    description: format!("Citrix NetScaler {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "netscaler-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // NetScaler scrambles the Connection header name to disable it downstream
+        for anomaly in ["cneonction", "nncoection"] {
+            if let Some(value) = response.headers.get(anomaly) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header(anomaly.to_string()),
+                    confidence: 0.90,
+                    description: format!("Citrix NetScaler scrambled connection header ({}) detected", anomaly),
+                    raw_data: value.clone(),
+                    signature_matched: "netscaler-connection-anomaly".to_string(),
+                });
+            }
+        }
+
+        for via in response.headers.get_all("via") {
+            if via.contains("NS-CACHE") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("via".to_string()),
+                    confidence: 0.85,
+                    description: "Citrix NetScaler cache via header detected".to_string(),
+                    raw_data: via.clone(),
+                    signature_matched: "netscaler-via-header".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if Self::netscaler_block_page_pattern().is_match(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("block-page-detected".to_string()),
+                confidence: if response.status >= 400 { 0.85 } else { 0.65 },
+                description: "Citrix NetScaler AppFirewall block page detected".to_string(),
+                raw_data: "block-page-detected".to_string(),
+                signature_matched: "netscaler-block-page".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for NetScalerProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::WAF
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for NetScalerProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
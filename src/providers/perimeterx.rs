@@ -0,0 +1,135 @@
+//! PerimeterX (HUMAN) Bot Defender Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use super::set_cookie_names;
+use regex::Regex;
+use std::sync::OnceLock;
+use anyhow::Result;
+
+/// PerimeterX / HUMAN Bot Defender detection provider
+#[derive(Debug, Clone)]
+pub struct PerimeterXProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl PerimeterXProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "PerimeterX".to_string(),
+            version: "1.0.0".to_string(),
+            description: "PerimeterX (HUMAN) Bot Defender detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    // Pre-compiled regex patterns for performance
+    fn px_cookie_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r"^(_px[23]?|_pxhd|_pxvid|_pxde|pxcts)$").unwrap())
+    }
+
+    fn px_app_id_pattern() -> &'static Regex {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new(r#"_pxAppId\s*=\s*['"](PX[A-Za-z0-9]+)['"]"#).unwrap())
+    }
+
+    pub async fn check_cookies(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        for cookie in set_cookie_names(response) {
+            if Self::px_cookie_pattern().is_match(&cookie) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("set-cookie".to_string()),
+                    confidence: 0.90,
+                    description: format!("PerimeterX {} cookie detected", cookie),
+                    raw_data: cookie,
+                    signature_matched: "perimeterx-cookie".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(captures) = Self::px_app_id_pattern().captures(&response.body) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("_pxAppId".to_string()),
+                confidence: 0.95,
+                description: "PerimeterX sensor app ID detected".to_string(),
+                raw_data: captures[1].to_string(),
+                signature_matched: "perimeterx-app-id".to_string(),
+            });
+        }
+
+        if response.body.contains("px-captcha") || response.body.contains("captcha.px-cdn.net") {
+            evidence.push(Evidence {
+                method_type: MethodType::Body("px-captcha".to_string()),
+                confidence: 0.90,
+                description: "PerimeterX press-and-hold captcha detected".to_string(),
+                raw_data: "px-captcha".to_string(),
+                signature_matched: "perimeterx-captcha".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for PerimeterXProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::BotManagement
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.90
+    }
+
+    fn priority(&self) -> u32 {
+        70
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_cookies(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for PerimeterXProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
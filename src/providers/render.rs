@@ -0,0 +1,101 @@
+//! Render Platform Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use anyhow::Result;
+
+/// Render platform CDN detection provider
+///
+/// Render serves through Cloudflare, so its responses also carry `cf-ray`; these
+/// headers tell the platform apart from a site that put Cloudflare in front itself.
+#[derive(Debug, Clone)]
+pub struct RenderProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl RenderProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "Render".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Render platform CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        if let Some(origin) = response.headers.get("x-render-origin-server") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-render-origin-server".to_string()),
+                confidence: 0.95,
+                description: "Render origin server header detected".to_string(),
+                raw_data: origin.clone(),
+                signature_matched: "render-origin-header".to_string(),
+            });
+        }
+
+        if let Some(request_id) = response.headers.get("rndr-id") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("rndr-id".to_string()),
+                confidence: 0.95,
+                description: "Render request ID header detected".to_string(),
+                raw_data: request_id.clone(),
+                signature_matched: "render-request-id-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for RenderProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        85
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for RenderProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
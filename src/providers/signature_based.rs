@@ -1 +1,221 @@
-// Signature-based provider foundation - stub for now 
\ No newline at end of file
+//! Data-driven provider built from a signature bundle
+//!
+//! Rules for a built-in provider wrap it: its own checks run first, the bundle's rules
+//! add evidence under the same name. Rules for any other name form a provider of their
+//! own.
+
+use super::Provider;
+use crate::signatures::{ProviderSignatures, Rule, RuleTarget};
+use crate::{DetectionContext, DetectionProvider, Evidence, MethodType, ProviderFeature, ProviderType, StackLayer};
+use crate::http::{HttpClient, HttpResponse};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::sync::Arc;
+
+/// Priority of providers that exist only in a bundle
+const BUNDLE_PRIORITY: u32 = 50;
+
+#[derive(Debug, Clone)]
+pub struct SignatureProvider {
+    name: String,
+    provider_type: ProviderType,
+    description: Option<String>,
+    /// Bundle version the rules came from
+    version: String,
+    rules: Arc<Vec<(Rule, Regex)>>,
+    base: Option<Box<Provider>>,
+}
+
+impl SignatureProvider {
+    /// `base` is the registered provider of the same name, if there is one; its name
+    /// and type win over the bundle's
+    pub fn new(signatures: &ProviderSignatures, version: &str, base: Option<Provider>) -> Result<Self> {
+        let (name, provider_type) = match (&base, &signatures.provider_type) {
+            (Some(base), _) => (base.name().to_string(), base.provider_type()),
+            (None, Some(provider_type)) => (signatures.name.clone(), provider_type.clone()),
+            (None, None) => return Err(anyhow!("signature provider '{}' needs a type", signatures.name)),
+        };
+        let rules = signatures
+            .rules
+            .iter()
+            .map(|rule| Ok((rule.clone(), crate::signatures::compile(&rule.pattern, rule.confidence)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            name,
+            provider_type,
+            description: signatures.description.clone(),
+            version: version.to_string(),
+            rules: Arc::new(rules),
+            base: base.map(Box::new),
+        })
+    }
+
+    /// The built-in provider these rules extend
+    pub fn base(&self) -> Option<&Provider> {
+        self.base.as_deref()
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    /// Evidence from the bundle rules alone
+    pub fn match_rules(&self, response: &HttpResponse) -> Vec<Evidence> {
+        let cookies = super::set_cookie_names(response);
+        let status = response.status.to_string();
+        let mut evidence = Vec::new();
+        for (rule, pattern) in self.rules.iter() {
+            let (method_type, values): (_, Vec<&str>) = match &rule.target {
+                RuleTarget::Header(name) => (MethodType::Header(name.to_lowercase()), response.headers.get_all(name).map(String::as_str).collect()),
+                RuleTarget::Body => (MethodType::Body(rule.id.clone()), vec![&response.body]),
+                RuleTarget::Cookie => (MethodType::Header("set-cookie".to_string()), cookies.iter().map(String::as_str).collect()),
+                RuleTarget::Status => (MethodType::StatusCode(response.status), vec![&status]),
+            };
+            if let Some(found) = values.iter().find_map(|value| pattern.find(value)) {
+                evidence.push(Evidence {
+                    method_type,
+                    confidence: rule.confidence,
+                    description: rule.description.clone().unwrap_or_else(|| format!("{} signature {} matched", self.name, rule.id)),
+                    raw_data: crate::utils::truncate_display(found.as_str(), 120),
+                    signature_matched: rule.id.clone(),
+                });
+            }
+        }
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for SignatureProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        self.provider_type.clone()
+    }
+
+    fn version(&self) -> &str {
+        match &self.base {
+            Some(base) => base.version(),
+            None => &self.version,
+        }
+    }
+
+    fn description(&self) -> Option<String> {
+        match &self.base {
+            Some(base) => base.description(),
+            None => self.description.clone().or_else(|| Some(format!("{} detection from signature bundle {}", self.name, self.version))),
+        }
+    }
+
+    fn confidence_base(&self) -> f64 {
+        self.base.as_ref().map_or(0.9, |base| base.confidence_base())
+    }
+
+    fn priority(&self) -> u32 {
+        self.base.as_ref().map_or(BUNDLE_PRIORITY, |base| base.priority())
+    }
+
+    fn enabled(&self) -> bool {
+        self.base.as_ref().is_none_or(|base| base.enabled())
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        let mut evidence = match &self.base {
+            Some(base) => base.detect(context).await?,
+            None => Vec::new(),
+        };
+        if let Some(response) = &context.response {
+            evidence.extend(self.match_rules(response));
+        }
+        Ok(evidence)
+    }
+
+    async fn passive_detect(&self, response: &HttpResponse) -> Result<Vec<Evidence>> {
+        let mut evidence = match &self.base {
+            Some(base) => base.passive_detect(response).await?,
+            None => Vec::new(),
+        };
+        evidence.extend(self.match_rules(response));
+        Ok(evidence)
+    }
+
+    async fn active_detect(&self, client: &HttpClient, url: &str) -> Result<Vec<Evidence>> {
+        match &self.base {
+            Some(base) => base.active_detect(client, url).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn features(&self, response: &HttpResponse) -> Vec<ProviderFeature> {
+        self.base.as_ref().map(|base| base.features(response)).unwrap_or_default()
+    }
+
+    fn stack_layer(&self) -> StackLayer {
+        match &self.base {
+            Some(base) => base.stack_layer(),
+            None => StackLayer::from_provider_type(&self.provider_type),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::cloudflare::CloudFlareProvider;
+
+    fn signatures(name: &str, provider_type: Option<ProviderType>, rules: Vec<Rule>) -> ProviderSignatures {
+        ProviderSignatures { name: name.to_string(), provider_type, description: None, rules }
+    }
+
+    fn rule(id: &str, target: RuleTarget, pattern: &str) -> Rule {
+        Rule { id: id.to_string(), target, pattern: pattern.to_string(), confidence: 0.9, description: None }
+    }
+
+    #[tokio::test]
+    async fn test_bundle_only_provider() {
+        let provider = SignatureProvider::new(
+            &signatures("Imperva", Some(ProviderType::WAF), vec![
+                rule("imperva-iinfo", RuleTarget::Header("X-Iinfo".to_string()), r"^\d+-\d+"),
+                rule("imperva-cookie", RuleTarget::Cookie, "^incap_ses_"),
+                rule("imperva-body", RuleTarget::Body, "Incapsula incident ID"),
+            ]),
+            "2026.10.1",
+            None,
+        )
+        .unwrap();
+        assert_eq!((provider.version(), provider.priority(), provider.stack_layer()), ("2026.10.1", BUNDLE_PRIORITY, StackLayer::Origin));
+
+        let response = HttpResponse::from_parts(200, [("x-iinfo", "12-345678-0 0NNN"), ("set-cookie", "incap_ses_123=abc; path=/")], "<html>ok</html>", "https://example.com/");
+        let evidence = provider.passive_detect(&response).await.unwrap();
+        let ids: Vec<&str> = evidence.iter().map(|e| e.signature_matched.as_str()).collect();
+        assert_eq!(ids, ["imperva-iinfo", "imperva-cookie"]);
+        assert_eq!(evidence[0].method_type, MethodType::Header("x-iinfo".to_string()));
+
+        assert!(SignatureProvider::new(&signatures("Acme", None, Vec::new()), "1", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rules_extend_builtin_provider() {
+        let base = Provider::CloudFlare(CloudFlareProvider::new());
+        let provider = SignatureProvider::new(
+            &signatures("cloudflare", None, vec![rule("cloudflare-nel", RuleTarget::Header("nel".to_string()), "cf-nel")]),
+            "2026.10.1",
+            Some(base.clone()),
+        )
+        .unwrap();
+        assert_eq!((provider.name(), provider.provider_type()), (base.name(), base.provider_type()));
+        assert_eq!(provider.version(), base.version());
+
+        let response = HttpResponse::from_parts(200, [("server", "cloudflare"), ("nel", r#"{"report_to":"cf-nel"}"#)], "", "https://example.com/");
+        let builtin = base.passive_detect(&response).await.unwrap();
+        let evidence = provider.passive_detect(&response).await.unwrap();
+        assert_eq!(evidence.len(), builtin.len() + 1);
+        assert_eq!(evidence.last().unwrap().signature_matched, "cloudflare-nel");
+    }
+}
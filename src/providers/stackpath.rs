@@ -0,0 +1,105 @@
+//! StackPath Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use anyhow::Result;
+
+/// StackPath CDN detection provider
+#[derive(Debug, Clone)]
+pub struct StackPathProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl StackPathProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "StackPath".to_string(),
+            version: "1.0.0".to_string(),
+            description: "StackPath edge CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // x-sp-url, x-sp-edge-host, x-sp-waf-* and friends
+        let sp_headers: Vec<String> = response.headers
+            .iter()
+            .filter(|(name, _)| name.to_ascii_lowercase().starts_with("x-sp-"))
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        if !sp_headers.is_empty() {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-sp-*".to_string()),
+                confidence: 0.92,
+                description: "StackPath edge headers detected".to_string(),
+                raw_data: sp_headers.join(", "),
+                signature_matched: "stackpath-sp-header".to_string(),
+            });
+        }
+
+        // Inherited from Highwinds: `x-hw: 1700000000.cds051.fr2.hn,1700000000.cds207.fr2.c`
+        if let Some(hw) = response.headers.get("x-hw") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-hw".to_string()),
+                confidence: 0.90,
+                description: "StackPath (Highwinds) cache trace header detected".to_string(),
+                raw_data: hw.clone(),
+                signature_matched: "stackpath-hw-header".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for StackPathProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CDN
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.85
+    }
+
+    fn priority(&self) -> u32 {
+        80
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        Ok(self.check_headers(response).await)
+    }
+}
+
+impl Default for StackPathProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
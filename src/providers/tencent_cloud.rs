@@ -0,0 +1,138 @@
+//! Tencent Cloud EdgeOne / CDN Detection Provider
+
+use crate::{DetectionProvider, DetectionContext, Evidence, ProviderType, MethodType};
+use anyhow::Result;
+
+/// Tencent Cloud EdgeOne (edge WAF and CDN) and Tencent Cloud CDN detection provider
+#[derive(Debug, Clone)]
+pub struct TencentCloudProvider {
+    name: String,
+    version: String,
+    description: String,
+    enabled: bool,
+}
+
+impl TencentCloudProvider {
+    pub fn new() -> Self {
+        Self {
+            name: "TencentCloud".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Tencent Cloud EdgeOne and CDN detection provider".to_string(),
+            enabled: true,
+        }
+    }
+
+    pub async fn check_headers(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // EdgeOne tags every response with a log ID, and cached ones with a cache status
+        for header in ["eo-log-uuid", "eo-cache-status"] {
+            if let Some(value) = response.headers.get(header) {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header(header.to_string()),
+                    confidence: 0.95,
+                    description: format!("Tencent EdgeOne {} header detected", header),
+                    raw_data: value.clone(),
+                    signature_matched: "tencent-edgeone-header".to_string(),
+                });
+            }
+        }
+
+        // Tencent Cloud CDN (NWS) log ID and cache lookup
+        if let Some(uuid) = response.headers.get("x-nws-log-uuid") {
+            evidence.push(Evidence {
+                method_type: MethodType::Header("x-nws-log-uuid".to_string()),
+                confidence: 0.93,
+                description: "Tencent Cloud CDN log ID header detected".to_string(),
+                raw_data: uuid.clone(),
+                signature_matched: "tencent-nws-header".to_string(),
+            });
+        }
+
+        if let Some(server) = response.headers.get("server") {
+            let lower = server.to_ascii_lowercase();
+            if lower.starts_with("nws_") || lower.contains("edgeone") {
+                evidence.push(Evidence {
+                    method_type: MethodType::Header("server".to_string()),
+                    confidence: 0.90,
+                    description: "Tencent Cloud edge server header detected".to_string(),
+                    raw_data: server.clone(),
+                    signature_matched: "tencent-server-header".to_string(),
+                });
+            }
+        }
+
+        evidence
+    }
+
+    pub async fn check_body_patterns(&self, response: &crate::http::HttpResponse) -> Vec<Evidence> {
+        let mut evidence = Vec::new();
+
+        // Block pages of the EdgeOne and Tencent Cloud WAFs
+        let markers = ["waf.tencent-cloud.com", "edgeone.ai/document", "EdgeOne Security"];
+        if let Some(marker) = markers.iter().find(|marker| response.body.contains(**marker)) {
+            evidence.push(Evidence {
+                method_type: MethodType::Body(marker.to_string()),
+                confidence: 0.90,
+                description: "Tencent Cloud WAF block page detected".to_string(),
+                raw_data: marker.to_string(),
+                signature_matched: "tencent-waf-block-page".to_string(),
+            });
+        }
+
+        evidence
+    }
+}
+
+#[async_trait::async_trait]
+impl DetectionProvider for TencentCloudProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Both
+    }
+
+    fn confidence_base(&self) -> f64 {
+        0.88
+    }
+
+    fn priority(&self) -> u32 {
+        85
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    async fn detect(&self, context: &DetectionContext) -> Result<Vec<Evidence>> {
+        match &context.response {
+            Some(response) => self.passive_detect(response).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn passive_detect(&self, response: &crate::http::HttpResponse) -> Result<Vec<Evidence>> {
+        let mut all_evidence = Vec::new();
+
+        all_evidence.extend(self.check_headers(response).await);
+        all_evidence.extend(self.check_body_patterns(response).await);
+
+        Ok(all_evidence)
+    }
+}
+
+impl Default for TencentCloudProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,260 @@
+//! Per-target politeness and global rate limiting
+//!
+//! Every outgoing probe (provider requests, timing analysis, payload probing and
+//! smoke tests) goes through a shared token-bucket limiter keyed by host, so a scan
+//! can never accidentally flood a single target.
+//!
+//! The per-host request cap counts every request of the process, which suits a one-shot
+//! CLI run. Long-running servers run each scan in a [`request_budget`] scope instead, so
+//! the cap applies per scan rather than for the life of the process.
+
+use crate::error::WafDetectorError;
+use anyhow::Result;
+use dashmap::DashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Rate limiting configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second allowed per host (`None` = unlimited)
+    pub requests_per_second: Option<f64>,
+    /// Number of requests that may be sent back-to-back before throttling kicks in
+    pub burst: u32,
+    /// Hard cap on the number of requests sent to a single host (`None` = unlimited),
+    /// per [`request_budget`] scope when inside one
+    pub max_requests_per_host: Option<usize>,
+    /// Minimum delay between two consecutive requests to the same host
+    pub min_delay: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: Some(10.0),
+            burst: 5,
+            max_requests_per_host: None,
+            min_delay: Duration::ZERO,
+        }
+    }
+}
+
+/// Token bucket state for a single host
+#[derive(Debug, Clone)]
+struct HostBucket {
+    tokens: f64,
+    last_refill: Instant,
+    next_allowed: Instant,
+    requests_sent: usize,
+}
+
+/// Token-bucket rate limiter keyed by host
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    hosts: DashMap<String, HostBucket>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            hosts: DashMap::new(),
+        }
+    }
+
+    /// Replace the active configuration (existing per-host counters are kept)
+    pub fn configure(&self, config: RateLimitConfig) {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Wait until a request to the URL's host is allowed.
+    ///
+    /// Returns an error without waiting if the host's request budget is exhausted.
+    pub async fn acquire(&self, url: &str) -> Result<()> {
+        let wait = self.reserve(&host_key(url), Instant::now())?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Reserve a request slot for `host` at time `now`, returning how long the caller must wait
+    fn reserve(&self, host: &str, now: Instant) -> Result<Duration> {
+        let config = self.config();
+        let burst = config.burst.max(1) as f64;
+
+        let mut bucket = self.hosts.entry(host.to_string()).or_insert_with(|| HostBucket {
+            tokens: burst,
+            last_refill: now,
+            next_allowed: now,
+            requests_sent: 0,
+        });
+
+        if let Some(max) = config.max_requests_per_host {
+            let sent = BUDGET
+                .try_with(|budget| budget.get(host).map_or(0, |count| *count))
+                .unwrap_or(bucket.requests_sent);
+            if sent >= max {
+                return Err(WafDetectorError::RequestBudgetExhausted {
+                    host: host.to_string(),
+                    limit: max,
+                }.into());
+            }
+        }
+
+        let mut wait = Duration::ZERO;
+
+        if let Some(rps) = config.requests_per_second.filter(|rps| *rps > 0.0) {
+            let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rps).min(burst);
+            bucket.last_refill = now;
+
+            if bucket.tokens < 1.0 {
+                wait = Duration::from_secs_f64((1.0 - bucket.tokens) / rps);
+            }
+            // Reserve the token now; a negative balance is paid back by the wait
+            bucket.tokens -= 1.0;
+        }
+
+        let earliest = bucket.next_allowed.saturating_duration_since(now);
+        wait = wait.max(earliest);
+        bucket.next_allowed = now + wait + config.min_delay;
+        bucket.requests_sent += 1;
+        let _ = BUDGET.try_with(|budget| *budget.entry(host.to_string()).or_insert(0) += 1);
+
+        Ok(wait)
+    }
+
+    /// Number of requests already sent (or reserved) for a URL's host
+    pub fn requests_sent(&self, url: &str) -> usize {
+        self.hosts
+            .get(&host_key(url))
+            .map(|bucket| bucket.requests_sent)
+            .unwrap_or(0)
+    }
+
+    /// Forget all per-host state
+    pub fn reset(&self) {
+        self.hosts.clear();
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+/// Process-wide limiter shared by every HTTP client in the crate
+pub fn global() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(RateLimiter::default)
+}
+
+tokio::task_local! {
+    /// Per-host request counts installed by [`request_budget`]
+    static BUDGET: Arc<DashMap<String, usize>>;
+}
+
+/// Run a future with its own per-host request budget: `max_requests_per_host` counts
+/// only the requests sent inside it. Pacing is still shared with the rest of the process.
+///
+/// Scopes nest: requests count against the innermost scope only.
+pub async fn request_budget<F: std::future::Future>(future: F) -> F::Output {
+    BUDGET.scope(Arc::new(DashMap::new()), future).await
+}
+
+/// Normalize a URL (or bare host) into the key used for per-host accounting
+fn host_key(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(|h| h.to_lowercase()))
+        .unwrap_or_else(|| url.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_key() {
+        assert_eq!(host_key("https://Example.com/path?q=1"), "example.com");
+        assert_eq!(host_key("example.com"), "example.com");
+    }
+
+    #[test]
+    fn test_burst_then_throttle() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: Some(2.0),
+            burst: 2,
+            max_requests_per_host: None,
+            min_delay: Duration::ZERO,
+        });
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve("a.com", now).unwrap(), Duration::ZERO);
+        assert_eq!(limiter.reserve("a.com", now).unwrap(), Duration::ZERO);
+        let wait = limiter.reserve("a.com", now).unwrap();
+        assert!(wait >= Duration::from_millis(450) && wait <= Duration::from_millis(550));
+
+        // Other hosts have their own bucket
+        assert_eq!(limiter.reserve("b.com", now).unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_max_requests_per_host() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: None,
+            burst: 1,
+            max_requests_per_host: Some(2),
+            min_delay: Duration::ZERO,
+        });
+
+        assert!(limiter.reserve("a.com", Instant::now()).is_ok());
+        assert!(limiter.reserve("a.com", Instant::now()).is_ok());
+        assert!(limiter.reserve("a.com", Instant::now()).is_err());
+        assert_eq!(limiter.requests_sent("https://a.com/"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_request_budget_scopes_the_cap() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: None,
+            burst: 1,
+            max_requests_per_host: Some(2),
+            min_delay: Duration::ZERO,
+        });
+        let scan = || request_budget(async {
+            assert!(limiter.reserve("a.com", Instant::now()).is_ok());
+            assert!(limiter.reserve("a.com", Instant::now()).is_ok());
+            assert!(limiter.reserve("a.com", Instant::now()).is_err());
+        });
+
+        // Each scope starts from zero, however many requests the process already sent
+        scan().await;
+        scan().await;
+        assert_eq!(limiter.requests_sent("https://a.com/"), 4);
+        assert!(limiter.reserve("a.com", Instant::now()).is_err());
+    }
+
+    #[test]
+    fn test_min_delay_between_requests() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: None,
+            burst: 1,
+            max_requests_per_host: None,
+            min_delay: Duration::from_millis(200),
+        });
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve("a.com", now).unwrap(), Duration::ZERO);
+        assert_eq!(limiter.reserve("a.com", now).unwrap(), Duration::from_millis(200));
+    }
+}
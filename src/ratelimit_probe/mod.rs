@@ -0,0 +1,256 @@
+//! Rate-limit behavior profiling
+//!
+//! Sends plain GET requests at a slowly increasing rate until the target starts
+//! rate limiting, then measures how long the limit lasts and whether it is keyed on
+//! the client IP or on the session. How a product answers a flood (status code,
+//! `Retry-After`, block page) is strong vendor evidence, and operators want to know
+//! the threshold. Every request still goes through the global per-host limiter, and
+//! the probe stops at the first limited response or at its own request cap.
+
+use crate::http::{HttpClient, HttpResponse};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Highest rate the probe will ever attempt, whatever the configuration says
+pub const MAX_PROBE_RPS: f64 = 20.0;
+
+/// Ramp and safety settings for the probe
+#[derive(Debug, Clone)]
+pub struct RateLimitProbeConfig {
+    /// Request rates to step through, in requests per second
+    pub rates: Vec<f64>,
+    /// Requests sent at each rate before moving to the next one
+    pub requests_per_step: usize,
+    /// Hard cap on requests sent by one probe run (ramp, ban and scope checks)
+    pub max_requests: usize,
+    /// Delay between checks while waiting for a limit to be lifted
+    pub ban_check_interval: Duration,
+    /// Give up measuring the ban duration after this long
+    pub max_ban_wait: Duration,
+}
+
+impl Default for RateLimitProbeConfig {
+    fn default() -> Self {
+        Self {
+            rates: vec![1.0, 2.0, 5.0, 10.0],
+            requests_per_step: 10,
+            max_requests: 60,
+            ban_check_interval: Duration::from_secs(5),
+            max_ban_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+/// What the limit appears to be keyed on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitScope {
+    /// A fresh session is still limited
+    PerIp,
+    /// A fresh session (new cookie and user agent) gets through
+    PerSession,
+    #[default]
+    Unknown,
+}
+
+/// Observed rate-limiting behavior of a target
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitProfile {
+    /// The target started limiting during the ramp
+    pub limited: bool,
+    /// Rate (requests per second) at which limiting first kicked in
+    pub threshold_rps: Option<f64>,
+    /// Requests accepted before the first limited response
+    pub requests_before_limit: Option<usize>,
+    /// Status code of the first limited response
+    pub limit_status: Option<u16>,
+    /// `Retry-After` header of the first limited response
+    pub retry_after: Option<String>,
+    /// Seconds until requests were accepted again (`None` if not lifted within the wait cap)
+    pub ban_duration_secs: Option<u64>,
+    pub scope: RateLimitScope,
+    /// Rate-limit related response headers seen (`x-ratelimit-*`, `retry-after`, ...)
+    pub limit_headers: Vec<String>,
+    /// Vendor suggested by the limited response
+    pub vendor_hint: Option<String>,
+    pub requests_sent: usize,
+    /// The ramp ended because the request cap was reached, not because it completed
+    pub stopped_by_cap: bool,
+}
+
+/// Rate-limit profiler for one target
+#[derive(Debug, Clone)]
+pub struct RateLimitProbe<'a> {
+    client: &'a HttpClient,
+    config: RateLimitProbeConfig,
+}
+
+impl<'a> RateLimitProbe<'a> {
+    pub fn new(client: &'a HttpClient) -> Self {
+        Self { client, config: RateLimitProbeConfig::default() }
+    }
+
+    pub fn with_config(mut self, config: RateLimitProbeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Ramp up the request rate against `url` and profile the first limit hit
+    pub async fn probe(&self, url: &str) -> Result<RateLimitProfile> {
+        let mut profile = RateLimitProfile::default();
+        let baseline = self.send(url, &mut profile, &[]).await?;
+        collect_limit_headers(&baseline, &mut profile.limit_headers);
+
+        let mut limited_response = None;
+        'ramp: for rate in self.config.rates.iter().map(|r| r.clamp(0.1, MAX_PROBE_RPS)) {
+            let interval = Duration::from_secs_f64(1.0 / rate);
+            for _ in 0..self.config.requests_per_step {
+                if profile.requests_sent >= self.config.max_requests {
+                    profile.stopped_by_cap = true;
+                    break 'ramp;
+                }
+                let sent_at = Instant::now();
+                let response = self.send(url, &mut profile, &[]).await?;
+                collect_limit_headers(&response, &mut profile.limit_headers);
+                if is_limited(&response, baseline.status) {
+                    profile.threshold_rps = Some(rate);
+                    limited_response = Some(response);
+                    break 'ramp;
+                }
+                tokio::time::sleep(interval.saturating_sub(sent_at.elapsed())).await;
+            }
+        }
+
+        let Some(response) = limited_response else {
+            return Ok(profile);
+        };
+        profile.limited = true;
+        profile.requests_before_limit = Some(profile.requests_sent - 1);
+        profile.limit_status = Some(response.status);
+        profile.retry_after = response.headers.get("retry-after").cloned();
+        profile.vendor_hint = vendor_hint(&response).map(|v| v.to_string());
+
+        profile.scope = self.check_scope(url, baseline.status, &mut profile).await?;
+        if profile.scope != RateLimitScope::PerSession {
+            profile.ban_duration_secs = self.measure_ban(url, baseline.status, &mut profile).await?;
+        }
+        Ok(profile)
+    }
+
+    /// Retry once as a fresh session; getting through means the limit is session-keyed
+    async fn check_scope(&self, url: &str, baseline_status: u16, profile: &mut RateLimitProfile) -> Result<RateLimitScope> {
+        if profile.requests_sent >= self.config.max_requests {
+            return Ok(RateLimitScope::Unknown);
+        }
+        let nonce = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let session = format!("session={:x}", nonce);
+        let headers = [("Cookie", session.as_str()), ("User-Agent", "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0")];
+        let response = self.send(url, profile, &headers).await?;
+        Ok(scope_from_fresh_session(is_limited(&response, baseline_status)))
+    }
+
+    /// Poll until the target accepts requests again, within the wait and request caps
+    async fn measure_ban(&self, url: &str, baseline_status: u16, profile: &mut RateLimitProfile) -> Result<Option<u64>> {
+        let start = Instant::now();
+        while start.elapsed() + self.config.ban_check_interval <= self.config.max_ban_wait
+            && profile.requests_sent < self.config.max_requests
+        {
+            tokio::time::sleep(self.config.ban_check_interval).await;
+            let response = self.send(url, profile, &[]).await?;
+            if !is_limited(&response, baseline_status) {
+                return Ok(Some(start.elapsed().as_secs()));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn send(&self, url: &str, profile: &mut RateLimitProfile, headers: &[(&str, &str)]) -> Result<HttpResponse> {
+        profile.requests_sent += 1;
+        self.client.get_with_headers(url, headers).await
+    }
+}
+
+/// A response counts as rate limited on 429, or on 403/503 when the baseline was not
+pub fn is_limited(response: &HttpResponse, baseline_status: u16) -> bool {
+    response.status == 429 || (matches!(response.status, 403 | 503) && response.status != baseline_status)
+}
+
+fn scope_from_fresh_session(still_limited: bool) -> RateLimitScope {
+    if still_limited { RateLimitScope::PerIp } else { RateLimitScope::PerSession }
+}
+
+fn collect_limit_headers(response: &HttpResponse, seen: &mut Vec<String>) {
+    for name in response.headers.keys() {
+        let is_limit_header = name.starts_with("x-ratelimit")
+            || name.starts_with("ratelimit")
+            || name.starts_with("x-rate-limit")
+            || name == "retry-after";
+        if is_limit_header && !seen.contains(name) {
+            seen.push(name.clone());
+        }
+    }
+    seen.sort();
+}
+
+/// Vendor whose rate-limit response this looks like
+pub fn vendor_hint(response: &HttpResponse) -> Option<&'static str> {
+    let body = response.body.to_lowercase();
+    let has = |name: &str| response.headers.contains_key(name);
+
+    if has("cf-ray") || body.contains("error 1015") {
+        Some("CloudFlare")
+    } else if has("x-amzn-requestid") || has("x-amz-cf-id") {
+        Some("AWS")
+    } else if has("x-akamai-transformed") || body.contains("akamai") {
+        Some("Akamai")
+    } else if has("x-iinfo") || body.contains("incapsula") {
+        Some("Imperva")
+    } else if has("x-sucuri-id") {
+        Some("Sucuri")
+    } else if has("x-served-by") && has("x-cache") {
+        Some("Fastly")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: headers.iter().copied().collect(),
+            body: body.to_string(),
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_limited() {
+        assert!(is_limited(&response(429, &[], ""), 200));
+        assert!(is_limited(&response(503, &[], ""), 200));
+        assert!(!is_limited(&response(403, &[], ""), 403));
+        assert!(!is_limited(&response(200, &[], ""), 200));
+    }
+
+    #[test]
+    fn test_limit_headers_and_vendor_hint() {
+        let limited = response(429, &[("retry-after", "30"), ("x-ratelimit-limit", "100"), ("cf-ray", "8a1b2c3d4e5f6a7b-AMS")], "error 1015");
+        let mut seen = vec!["x-ratelimit-limit".to_string()];
+        collect_limit_headers(&limited, &mut seen);
+
+        assert_eq!(seen, vec!["retry-after", "x-ratelimit-limit"]);
+        assert_eq!(vendor_hint(&limited), Some("CloudFlare"));
+        assert_eq!(vendor_hint(&response(429, &[], "Too Many Requests")), None);
+    }
+
+    #[test]
+    fn test_scope_from_fresh_session() {
+        assert_eq!(scope_from_fresh_session(true), RateLimitScope::PerIp);
+        assert_eq!(scope_from_fresh_session(false), RateLimitScope::PerSession);
+    }
+}
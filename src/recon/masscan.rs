@@ -0,0 +1,86 @@
+//! masscan JSON output
+//!
+//! `-oJ` writes a JSON array that older versions leave with a trailing comma, and
+//! `-oD` writes one object per line, so records are read line by line.
+
+use super::OpenPort;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Record {
+    ip: String,
+    #[serde(default)]
+    ports: Vec<PortRecord>,
+}
+
+#[derive(Deserialize)]
+struct PortRecord {
+    port: u16,
+    #[serde(default)]
+    proto: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    /// Present when masscan grabbed banners (`--banners`)
+    #[serde(default)]
+    service: Option<ServiceRecord>,
+}
+
+#[derive(Deserialize)]
+struct ServiceRecord {
+    name: String,
+}
+
+pub fn parse_masscan_json(content: &str) -> Result<Vec<OpenPort>> {
+    let mut ports = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim().trim_start_matches(['[', ',']).trim_end_matches([']', ',']).trim();
+        // Empty lines, array brackets and the `{"finished": 1}` trailer
+        if line.is_empty() || line.contains("\"finished\"") {
+            continue;
+        }
+        let record: Record = serde_json::from_str(line)
+            .map_err(|e| anyhow!("Invalid masscan record on line {}: {}", number + 1, e))?;
+        for port in record.ports {
+            let open = port.status.as_deref().is_none_or(|status| status == "open");
+            let tcp = port.proto.as_deref().is_none_or(|proto| proto == "tcp");
+            if open && tcp {
+                ports.push(OpenPort {
+                    host: record.ip.clone(),
+                    port: port.port,
+                    service: port.service.map(|service| service.name),
+                    tls: false,
+                });
+            }
+        }
+    }
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_masscan_json() {
+        let content = r#"[
+{   "ip": "192.0.2.10",   "timestamp": "1700000000", "ports": [ {"port": 443, "proto": "tcp", "status": "open", "reason": "syn-ack", "ttl": 54} ] }
+,
+{   "ip": "192.0.2.11",   "timestamp": "1700000001", "ports": [ {"port": 53, "proto": "udp", "status": "open"} ] },
+{   "ip": "192.0.2.12",   "timestamp": "1700000002", "ports": [ {"port": 8080, "proto": "tcp", "service": {"name": "http", "banner": "nginx"}} ] },
+{"finished": 1}
+]"#;
+
+        let ports = parse_masscan_json(content).unwrap();
+
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0].host, "192.0.2.10");
+        assert_eq!(ports[0].port, 443);
+        assert_eq!(ports[1].service.as_deref(), Some("http"));
+    }
+
+    #[test]
+    fn test_invalid_record_is_rejected() {
+        assert!(parse_masscan_json("{\"ports\": []}").is_err());
+    }
+}
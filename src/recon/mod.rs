@@ -0,0 +1,106 @@
+//! Targets from port-scanner output
+//!
+//! Nmap XML (`-oX`) and masscan JSON (`-oJ`/`-oD`) results are turned into scan
+//! targets: every host with an open web port becomes an `http://` or `https://` URL,
+//! so the detector can sit directly behind a recon pipeline.
+
+pub mod masscan;
+pub mod nmap;
+
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// An open port reported by a scanner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenPort {
+    /// Hostname the scan was run against, or the IP address
+    pub host: String,
+    pub port: u16,
+    /// Service name guessed by the scanner ("http", "https", "ssl/http")
+    pub service: Option<String>,
+    /// Nmap saw TLS on the port (`tunnel="ssl"`)
+    pub tls: bool,
+}
+
+impl OpenPort {
+    /// URL to scan, or `None` for ports that do not look like HTTP(S)
+    pub fn target_url(&self) -> Option<String> {
+        let service = self.service.as_deref().unwrap_or("").to_ascii_lowercase();
+        let https = self.tls || service.contains("https") || service.starts_with("ssl/http") || matches!(self.port, 443 | 8443);
+        let http = https || service.contains("http") || matches!(self.port, 80 | 8000 | 8080);
+        if !http {
+            return None;
+        }
+
+        let scheme = if https { "https" } else { "http" };
+        let host = match self.host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{}]", ip),
+            _ => self.host.clone(),
+        };
+        let default_port = if https { 443 } else { 80 };
+        Some(if self.port == default_port {
+            format!("{}://{}/", scheme, host)
+        } else {
+            format!("{}://{}:{}/", scheme, host, self.port)
+        })
+    }
+}
+
+/// Target URLs for the open web ports, in scanner order without duplicates
+pub fn target_urls(ports: &[OpenPort]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    ports.iter()
+        .filter_map(OpenPort::target_url)
+        .filter(|url| seen.insert(url.clone()))
+        .collect()
+}
+
+/// Parse Nmap XML or masscan JSON, telling them apart by content
+pub fn parse_scan(content: &str) -> Result<Vec<OpenPort>> {
+    if content.trim_start().starts_with('<') {
+        nmap::parse_nmap_xml(content)
+    } else {
+        masscan::parse_masscan_json(content)
+    }
+}
+
+/// Load a scanner output file and build the URLs to scan
+pub fn load_scan_targets(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    let ports = parse_scan(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    Ok(target_urls(&ports))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(host: &str, port: u16, service: Option<&str>, tls: bool) -> OpenPort {
+        OpenPort { host: host.to_string(), port, service: service.map(|s| s.to_string()), tls }
+    }
+
+    #[test]
+    fn test_target_urls() {
+        let ports = vec![
+            port("example.com", 443, None, false),
+            port("example.com", 80, Some("http"), false),
+            port("10.0.0.5", 8443, None, false),
+            port("10.0.0.5", 9000, Some("http"), true),
+            port("10.0.0.5", 22, Some("ssh"), false),
+            port("2001:db8::1", 80, None, false),
+            port("example.com", 443, Some("https"), true),
+        ];
+
+        assert_eq!(target_urls(&ports), vec![
+            "https://example.com/",
+            "http://example.com/",
+            "https://10.0.0.5:8443/",
+            "https://10.0.0.5:9000/",
+            "http://[2001:db8::1]/",
+        ]);
+    }
+}
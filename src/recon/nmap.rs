@@ -0,0 +1,232 @@
+//! Nmap XML output (`-oX`)
+//!
+//! Only the handful of elements describing hosts and ports are read, with a small
+//! tag scanner rather than a full XML parser.
+
+use super::OpenPort;
+use anyhow::{anyhow, Result};
+
+#[derive(Default)]
+struct Host {
+    up: bool,
+    address: Option<String>,
+    /// Name given on the command line; PTR names often serve a different site
+    user_hostname: Option<String>,
+    ports: Vec<(u16, Option<String>, bool)>,
+}
+
+#[derive(Default)]
+struct Port {
+    port: u16,
+    tcp: bool,
+    open: bool,
+    service: Option<String>,
+    tls: bool,
+}
+
+pub fn parse_nmap_xml(content: &str) -> Result<Vec<OpenPort>> {
+    if !content.contains("<nmaprun") {
+        return Err(anyhow!("Not an Nmap XML report (missing <nmaprun>)"));
+    }
+
+    let mut ports = Vec::new();
+    let mut host: Option<Host> = None;
+    let mut port: Option<Port> = None;
+
+    for tag in Tags::new(content) {
+        match (tag.name, tag.closing) {
+            ("host", false) => host = Some(Host { up: true, ..Host::default() }),
+            ("host", true) => {
+                if let Some(done) = host.take() {
+                    let name = done.user_hostname.or(done.address);
+                    if let (true, Some(name)) = (done.up, name) {
+                        ports.extend(done.ports.into_iter().map(|(number, service, tls)| OpenPort {
+                            host: name.clone(),
+                            port: number,
+                            service,
+                            tls,
+                        }));
+                    }
+                }
+            }
+            ("status", false) => {
+                if let Some(host) = host.as_mut() {
+                    host.up = tag.attr("state") != Some("down");
+                }
+            }
+            ("address", false) => {
+                if let (Some(host), Some("ipv4" | "ipv6")) = (host.as_mut(), tag.attr("addrtype")) {
+                    host.address = tag.attr("addr").map(|a| a.to_string());
+                }
+            }
+            ("hostname", false) if tag.attr("type") == Some("user") => {
+                if let Some(host) = host.as_mut() {
+                    host.user_hostname = tag.attr("name").map(|n| n.to_lowercase());
+                }
+            }
+            ("port", false) if host.is_some() => {
+                port = tag.attr("portid").and_then(|id| id.parse().ok()).map(|number| Port {
+                    port: number,
+                    tcp: tag.attr("protocol") == Some("tcp"),
+                    ..Port::default()
+                });
+            }
+            ("state", false) => {
+                if let Some(port) = port.as_mut() {
+                    port.open = tag.attr("state") == Some("open");
+                }
+            }
+            ("service", false) => {
+                if let Some(port) = port.as_mut() {
+                    port.service = tag.attr("name").map(|n| n.to_string());
+                    port.tls = tag.attr("tunnel") == Some("ssl");
+                }
+            }
+            ("port", true) => {
+                if let (Some(done), Some(host)) = (port.take(), host.as_mut()) {
+                    if done.open && done.tcp {
+                        host.ports.push((done.port, done.service, done.tls));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ports)
+}
+
+struct Tag<'a> {
+    name: &'a str,
+    closing: bool,
+    attrs: Vec<(&'a str, String)>,
+}
+
+impl Tag<'_> {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Iterator over element tags, skipping comments, declarations and text
+struct Tags<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tags<'a> {
+    fn new(content: &'a str) -> Self {
+        Self { rest: content }
+    }
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = Tag<'a>;
+
+    fn next(&mut self) -> Option<Tag<'a>> {
+        loop {
+            let start = self.rest.find('<')?;
+            self.rest = &self.rest[start..];
+
+            let terminator = if self.rest.starts_with("<!--") { "-->" } else { ">" };
+            if terminator == ">" && !self.rest.starts_with("<?") && !self.rest.starts_with("<!") {
+                let end = tag_end(self.rest)?;
+                let inner = &self.rest[1..end];
+                self.rest = &self.rest[end + 1..];
+                return Some(parse_tag(inner));
+            }
+            let end = self.rest.find(terminator)?;
+            self.rest = &self.rest[end + terminator.len()..];
+        }
+    }
+}
+
+/// Index of the `>` closing a tag, ignoring any inside quoted attribute values
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_tag(inner: &str) -> Tag<'_> {
+    let closing = inner.starts_with('/');
+    let inner = inner.trim_start_matches('/').trim_end_matches('/');
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    let name = &inner[..name_end];
+
+    let mut attrs = Vec::new();
+    let mut rest = &inner[name_end..];
+    while let Some(eq) = rest.find('=') {
+        let attr = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else { break };
+        let Some(len) = value[1..].find(quote) else { break };
+        attrs.push((attr, unescape(&value[1..1 + len])));
+        rest = &value[len + 2..];
+    }
+
+    Tag { name, closing, attrs }
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE nmaprun>
+<nmaprun scanner="nmap" args="nmap -sV -oX scan.xml shop.example.com 192.0.2.20">
+<!-- Nmap scan report -->
+<host starttime="1700000000"><status state="up" reason="syn-ack"/>
+<address addr="192.0.2.10" addrtype="ipv4"/>
+<hostnames>
+<hostname name="shop.example.com" type="user"/>
+<hostname name="ec2-192-0-2-10.compute.amazonaws.com" type="PTR"/>
+</hostnames>
+<ports><extraports state="filtered" count="997"/>
+<port protocol="tcp" portid="22"><state state="open" reason="syn-ack"/><service name="ssh" product="OpenSSH"/></port>
+<port protocol="tcp" portid="443"><state state="open" reason="syn-ack"/><service name="http" tunnel="ssl" product="nginx &amp; friends"/></port>
+<port protocol="tcp" portid="8080"><state state="closed" reason="reset"/><service name="http-proxy"/></port>
+</ports>
+</host>
+<host><status state="up"/>
+<address addr="192.0.2.20" addrtype="ipv4"/>
+<address addr="00:11:22:33:44:55" addrtype="mac"/>
+<ports><port protocol="tcp" portid="80"><state state="open"/></port></ports>
+</host>
+<host><status state="down"/><address addr="192.0.2.30" addrtype="ipv4"/>
+<ports><port protocol="tcp" portid="80"><state state="open"/></port></ports>
+</host>
+</nmaprun>"#;
+
+    #[test]
+    fn test_parse_nmap_xml() {
+        let ports = parse_nmap_xml(REPORT).unwrap();
+
+        assert_eq!(ports, vec![
+            OpenPort { host: "shop.example.com".to_string(), port: 22, service: Some("ssh".to_string()), tls: false },
+            OpenPort { host: "shop.example.com".to_string(), port: 443, service: Some("http".to_string()), tls: true },
+            OpenPort { host: "192.0.2.20".to_string(), port: 80, service: None, tls: false },
+        ]);
+        assert_eq!(crate::recon::target_urls(&ports), vec!["https://shop.example.com/", "http://192.0.2.20/"]);
+    }
+
+    #[test]
+    fn test_non_nmap_xml_is_rejected() {
+        assert!(parse_nmap_xml("<html></html>").is_err());
+    }
+}
@@ -1,36 +1,145 @@
 //! Provider registry for managing detection providers
 
 use crate::providers::{Provider, ProviderMetadata};
-use crate::{DetectionContext, DetectionResult, ProviderDetection, DetectionMetadata};
+use crate::{DetectionContext, DetectionOptions, DetectionResult, ProviderDetection, DetectionMetadata};
+use crate::{Diagnostics, Evidence, TechniqueDiagnostics, TechniqueKind};
+use crate::error::WafDetectorError;
+use crate::http::HttpClient;
 use crate::confidence::AdvancedScoring; // NEW: Import advanced scoring
+#[cfg(feature = "timing")]
 use crate::timing::{TimingAnalyzer, TimingConfig}; // NEW: Import timing analysis
+#[cfg(feature = "dns")]
 use crate::dns::DnsAnalyzer; // NEW: Import DNS analysis
+use crate::dns::passive::PassiveDnsSource;
+#[cfg(feature = "payload")]
 use crate::payload::PayloadAnalyzer; // NEW: Import payload analysis
+#[cfg(feature = "tls")]
+use crate::protocol::ProtocolAnalyzer;
+use crate::header_order::HeaderOrderAnalyzer;
+use crate::blockpage::BlockPageClassifier;
+use crate::favicon::{FaviconAnalyzer, KnownFavicon};
+use crate::cache::CacheAnalyzer;
+use crate::ct::{CrtSh, CtAnalyzer, CtSource};
+use crate::intel::{IntelAnalyzer, IntelSource};
+use crate::providers::signature_based::SignatureProvider;
+use crate::signatures::{BundleSummary, Inventory, ProviderInventory, SignatureBundle};
 use dashmap::DashMap;
-use std::sync::Arc;
-use std::collections::HashMap;
+use futures::future::{BoxFuture, FutureExt};
+use tracing::Instrument;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashMap};
 use anyhow::Result;
 
+/// Auxiliary analysis techniques that run alongside the providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Analyzer {
+    Timing,
+    Dns,
+    Payload,
+    Protocol,
+    HeaderOrder,
+    BlockPage,
+    Favicon,
+    Cache,
+    Ct,
+    HostIntel,
+}
+
+impl Analyzer {
+    pub const ALL: [Analyzer; 10] = [
+        Analyzer::Timing,
+        Analyzer::Dns,
+        Analyzer::Payload,
+        Analyzer::Protocol,
+        Analyzer::HeaderOrder,
+        Analyzer::BlockPage,
+        Analyzer::Favicon,
+        Analyzer::Cache,
+        Analyzer::Ct,
+        Analyzer::HostIntel,
+    ];
+
+    /// Key used for this analyzer in `DetectionResult::evidence_map`
+    pub fn evidence_key(&self) -> &'static str {
+        match self {
+            Analyzer::Timing => "TimingAnalysis",
+            Analyzer::Dns => "DnsAnalysis",
+            Analyzer::Payload => "PayloadAnalysis",
+            Analyzer::Protocol => "ProtocolAnalysis",
+            Analyzer::HeaderOrder => "HeaderOrderAnalysis",
+            Analyzer::BlockPage => "BlockPageAnalysis",
+            Analyzer::Favicon => "FaviconAnalysis",
+            Analyzer::Cache => "CacheAnalysis",
+            Analyzer::Ct => "CtAnalysis",
+            Analyzer::HostIntel => "HostIntelAnalysis",
+        }
+    }
+
+    /// Whether this analyzer sends additional requests to the target
+    pub fn sends_probe_traffic(&self) -> bool {
+        matches!(self, Analyzer::Timing | Analyzer::Payload | Analyzer::Protocol | Analyzer::HeaderOrder | Analyzer::Favicon | Analyzer::Cache)
+    }
+}
+
 /// Registry for managing detection providers
 #[derive(Debug, Clone)]
 pub struct ProviderRegistry {
     providers: Arc<DashMap<String, Provider>>,
     provider_metadata: Arc<DashMap<String, ProviderMetadata>>,
-    advanced_scoring: Arc<AdvancedScoring>, // NEW: Advanced confidence scoring
+    analyzers_enabled: Arc<DashMap<Analyzer, bool>>,
+    strict_passive: Arc<AtomicBool>,
+    advanced_scoring: Arc<RwLock<Arc<AdvancedScoring>>>, // NEW: Advanced confidence scoring
+    #[cfg(feature = "timing")]
     timing_analyzer: Arc<TimingAnalyzer>, // NEW: Timing analysis
-    dns_analyzer: Arc<DnsAnalyzer>, // NEW: DNS analysis
+    #[cfg(feature = "dns")]
+    dns_analyzer: Arc<RwLock<Arc<DnsAnalyzer>>>, // NEW: DNS analysis
+    passive_dns: Arc<RwLock<Vec<Arc<dyn PassiveDnsSource>>>>,
+    #[cfg(feature = "payload")]
     payload_analyzer: Arc<PayloadAnalyzer>, // NEW: Payload analysis
+    #[cfg(feature = "tls")]
+    protocol_analyzer: Arc<ProtocolAnalyzer>,
+    header_order_analyzer: Arc<HeaderOrderAnalyzer>,
+    block_page_classifier: Arc<RwLock<Arc<BlockPageClassifier>>>,
+    favicon_analyzer: Arc<RwLock<Arc<FaviconAnalyzer>>>,
+    cache_analyzer: Arc<CacheAnalyzer>,
+    ct_analyzer: Arc<RwLock<Arc<CtAnalyzer>>>,
+    intel_analyzer: Arc<RwLock<Arc<IntelAnalyzer>>>,
+    signature_bundle: Arc<RwLock<Option<Arc<SignatureBundle>>>>,
+    probe_client: Arc<HttpClient>, // Client used for provider active probes
 }
 
 impl ProviderRegistry {
     pub fn new() -> Self {
+        let analyzers_enabled = DashMap::new();
+        for analyzer in Analyzer::ALL {
+            analyzers_enabled.insert(analyzer, true);
+        }
+        let probe_client = HttpClient::new().unwrap_or_default();
+
         Self {
             providers: Arc::new(DashMap::new()),
             provider_metadata: Arc::new(DashMap::new()),
-            advanced_scoring: Arc::new(AdvancedScoring::new()), // NEW: Initialize advanced scoring
-            timing_analyzer: Arc::new(TimingAnalyzer::new(TimingConfig::default())), // NEW: Initialize timing analysis
-            dns_analyzer: Arc::new(DnsAnalyzer::new()), // NEW: Initialize DNS analysis
+            analyzers_enabled: Arc::new(analyzers_enabled),
+            strict_passive: Arc::new(AtomicBool::new(false)),
+            advanced_scoring: Arc::new(RwLock::new(Arc::new(AdvancedScoring::new()))), // NEW: Initialize advanced scoring
+            #[cfg(feature = "timing")]
+            timing_analyzer: Arc::new(TimingAnalyzer::with_client(TimingConfig::default(), probe_client.clone())), // NEW: Initialize timing analysis
+            #[cfg(feature = "dns")]
+            dns_analyzer: Arc::new(RwLock::new(Arc::new(DnsAnalyzer::new()))), // NEW: Initialize DNS analysis
+            passive_dns: Arc::new(RwLock::new(Vec::new())),
+            #[cfg(feature = "payload")]
             payload_analyzer: Arc::new(PayloadAnalyzer::new()), // NEW: Initialize payload analysis
+            #[cfg(feature = "tls")]
+            protocol_analyzer: Arc::new(ProtocolAnalyzer::new()),
+            header_order_analyzer: Arc::new(HeaderOrderAnalyzer::new()),
+            block_page_classifier: Arc::new(RwLock::new(Arc::new(BlockPageClassifier::new()))),
+            favicon_analyzer: Arc::new(RwLock::new(Arc::new(FaviconAnalyzer::new()))),
+            cache_analyzer: Arc::new(CacheAnalyzer::new().with_http_client(probe_client.clone())),
+            ct_analyzer: Arc::new(RwLock::new(Arc::new(CtAnalyzer::new(Arc::new(CrtSh::default()))))),
+            intel_analyzer: Arc::new(RwLock::new(Arc::new(IntelAnalyzer::default()))),
+            signature_bundle: Arc::new(RwLock::new(None)),
+            probe_client: Arc::new(probe_client),
         }
     }
 
@@ -52,9 +161,117 @@ impl ProviderRegistry {
         self.providers.get(name).map(|entry| entry.value().clone())
     }
 
-    /// Detect using all registered providers - matches working binary structure
+    /// Resolve a user-supplied provider name (case-insensitive) to its registered name
+    fn resolve_provider_name(&self, name: &str) -> Option<String> {
+        self.providers
+            .iter()
+            .find(|entry| entry.key().eq_ignore_ascii_case(name.trim()))
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Enable or disable a registered provider
+    pub fn set_provider_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        let resolved = self.resolve_provider_name(name)
+            .ok_or_else(|| anyhow::anyhow!("Provider '{}' is not registered", name))?;
+
+        if let Some(mut metadata) = self.provider_metadata.get_mut(&resolved) {
+            metadata.enabled = enabled;
+        }
+
+        Ok(())
+    }
+
+    /// Enable only the named providers and disable every other one
+    pub fn enable_only_providers<S: AsRef<str>>(&self, names: &[S]) -> Result<()> {
+        let mut selected = Vec::new();
+        for name in names {
+            let resolved = self.resolve_provider_name(name.as_ref())
+                .ok_or_else(|| anyhow::anyhow!("Provider '{}' is not registered", name.as_ref()))?;
+            selected.push(resolved);
+        }
+
+        for mut entry in self.provider_metadata.iter_mut() {
+            let enabled = selected.contains(entry.key());
+            entry.value_mut().enabled = enabled;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_provider_enabled(&self, name: &str) -> bool {
+        self.provider_metadata
+            .get(name)
+            .map(|meta| meta.enabled)
+            .unwrap_or(false)
+    }
+
+    /// Enable or disable one of the auxiliary analyzers
+    pub fn set_analyzer_enabled(&self, analyzer: Analyzer, enabled: bool) {
+        self.analyzers_enabled.insert(analyzer, enabled);
+    }
+
+    pub fn is_analyzer_enabled(&self, analyzer: Analyzer) -> bool {
+        if self.is_strict_passive() && analyzer.sends_probe_traffic() {
+            return false;
+        }
+
+        self.analyzers_enabled
+            .get(&analyzer)
+            .map(|entry| *entry.value())
+            .unwrap_or(false)
+    }
+
+    /// Disable every technique that sends extra traffic (timing, payload, protocol,
+    /// header order, favicon and cache probing), leaving header analysis of the initial response and DNS lookups
+    pub fn set_passive_only(&self) {
+        self.set_analyzer_enabled(Analyzer::Timing, false);
+        self.set_analyzer_enabled(Analyzer::Payload, false);
+        self.set_analyzer_enabled(Analyzer::Protocol, false);
+        self.set_analyzer_enabled(Analyzer::HeaderOrder, false);
+        self.set_analyzer_enabled(Analyzer::Favicon, false);
+        self.set_analyzer_enabled(Analyzer::Cache, false);
+    }
+
+    /// Strict passive mode: only the single GET issued by the engine is sent to the
+    /// target. Timing, payload and provider active probes stay disabled even if they
+    /// are re-enabled individually afterwards.
+    pub fn set_strict_passive(&self, strict: bool) {
+        self.strict_passive.store(strict, Ordering::SeqCst);
+        if strict {
+            self.set_passive_only();
+        }
+    }
+
+    pub fn is_strict_passive(&self) -> bool {
+        self.strict_passive.load(Ordering::SeqCst)
+    }
+
+    /// Whether an analyzer runs for a scan with the given options
+    fn should_run(&self, analyzer: Analyzer, options: &DetectionOptions) -> bool {
+        let requested = match analyzer {
+            Analyzer::Timing => options.timing,
+            Analyzer::Dns => options.dns,
+            Analyzer::Payload => options.payload,
+            Analyzer::Protocol => options.protocol,
+            Analyzer::HeaderOrder => options.header_order,
+            Analyzer::BlockPage => options.block_page,
+            Analyzer::Favicon => options.favicon,
+            Analyzer::Cache => options.cache,
+            Analyzer::Ct => options.ct,
+            Analyzer::HostIntel => options.host_intel,
+        };
+        requested && self.is_analyzer_enabled(analyzer)
+    }
+
+    /// Detect using all registered providers with the default (safe) detection options
     pub async fn detect_all(&self, context: &DetectionContext) -> Result<DetectionResult> {
+        self.detect_all_with_options(context, &DetectionOptions::default()).await
+    }
+
+    /// Detect using all registered providers - matches working binary structure
+    pub async fn detect_all_with_options(&self, context: &DetectionContext, options: &DetectionOptions) -> Result<DetectionResult> {
         let start_time = std::time::Instant::now();
+        let run_active_probes = options.active_probes && !self.is_strict_passive();
         
         // Filter enabled providers and sort by priority
         let mut providers: Vec<_> = self.providers
@@ -82,126 +299,96 @@ impl ProviderRegistry {
             .into_iter()
             .map(|(name, provider, _)| {
                 let context = context.clone();
-                async move {
-                    match provider.detect(&context).await {
-                        Ok(evidence) => Some((name, evidence, provider.confidence_base())),
+                let probe_client = Arc::clone(&self.probe_client);
+                instrumented(name.clone(), TechniqueKind::Provider, async move {
+                    let mut evidence = match provider.detect(&context).await {
+                        Ok(evidence) => evidence,
                         Err(e) => {
-                            eprintln!("Provider '{}' failed: {}", name, e);
-                            None
+                            let error = WafDetectorError::Provider { provider: name, message: e.to_string() };
+                            return (None, Some(error.to_string()));
                         }
-                    }
-                }
-            })
-            .collect();
+                    };
 
-        // NEW: Run timing analysis in parallel with provider detection
-        let timing_future = {
-            let url = context.url.clone();
-            let timing_analyzer = Arc::clone(&self.timing_analyzer);
-            async move {
-                match timing_analyzer.analyze(&url).await {
-                    Ok(timing_evidence) => {
-                        if !timing_evidence.is_empty() {
-                            Some(("TimingAnalysis".to_string(), timing_evidence, 0.85))
-                        } else {
-                            None
+                    let mut error = None;
+                    if run_active_probes {
+                        match provider.active_detect(&probe_client, &context.url).await {
+                            Ok(active_evidence) => evidence.extend(active_evidence),
+                            Err(e) => error = Some(format!("Provider '{}' active probes failed: {}", name, e)),
                         }
                     }
-                    Err(e) => {
-                        eprintln!("Timing analysis failed: {}", e);
-                        None
-                    }
-                }
-            }
-        };
 
-        // NEW: Run DNS analysis in parallel with provider detection
-        let dns_future = {
-            let url = context.url.clone();
-            let dns_analyzer = Arc::clone(&self.dns_analyzer);
-            async move {
-                match dns_analyzer.analyze(&url).await {
-                    Ok(dns_evidence) => {
-                        if !dns_evidence.is_empty() {
-                            Some(("DnsAnalysis".to_string(), dns_evidence, 0.95))
-                        } else {
-                            None
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("DNS analysis failed: {}", e);
-                        None
-                    }
-                }
-            }
-        };
+                    (Some((name, evidence, provider.confidence_base())), error)
+                })
+            })
+            .collect();
 
-        // NEW: Run payload analysis in parallel with provider detection
-        let payload_future = {
-            let url = context.url.clone();
-            let payload_analyzer = Arc::clone(&self.payload_analyzer);
-            async move {
-                match payload_analyzer.analyze(&url).await {
-                    Ok(payload_result) => {
-                        let evidence = payload_analyzer.to_evidence(&payload_result);
-                        if !evidence.is_empty() {
-                            Some(("PayloadAnalysis".to_string(), evidence, payload_result.confidence))
-                        } else {
-                            None
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Payload analysis failed: {}", e);
-                        None
-                    }
-                }
+        // Fuzzy match of the fetched body against known block pages; no requests
+        let block_page_result = match &context.response {
+            Some(response) if self.should_run(Analyzer::BlockPage, options) => {
+                let classifier = self.block_page_classifier();
+                Some(instrumented(Analyzer::BlockPage.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                    let evidence = classifier.analyze(response);
+                    let result = (!evidence.is_empty()).then(|| (Analyzer::BlockPage.evidence_key().to_string(), evidence, 0.9));
+                    (result, None)
+                }).await)
             }
+            _ => None,
         };
 
-        // Run all detection techniques in parallel
-        let (provider_results, timing_result, dns_result, payload_result) = futures::future::join4(
+        // Run all detection techniques in parallel; analyzers that are disabled are not built
+        let (provider_results, analyzer_results) = futures::future::join(
             futures::future::join_all(futures),
-            timing_future,
-            dns_future,
-            payload_future
+            futures::future::join_all(self.network_analyzer_futures(context, options)),
         ).await;
 
-        let mut results = provider_results;
-        if let Some(timing_result) = timing_result {
-            results.push(Some(timing_result));
-        }
-        if let Some(dns_result) = dns_result {
-            results.push(Some(dns_result));
-        }
-        if let Some(payload_result) = payload_result {
-            results.push(Some(payload_result));
+        let mut diagnostics = Diagnostics::default();
+        let mut results = Vec::new();
+        for (result, technique) in provider_results.into_iter().chain(analyzer_results).chain(block_page_result) {
+            results.push(result);
+            diagnostics.techniques.push(technique);
         }
+        diagnostics.total_requests = diagnostics.technique_requests();
         
         let mut provider_scores = HashMap::new();
+        let mut negative_evidence = HashMap::new();
+        let mut confidence_details = HashMap::new();
+        let scoring = self.advanced_scoring();
         let mut evidence_map = HashMap::new();
+        let mut evidence_groups = HashMap::new();
         let mut best_waf = None;
         let mut best_cdn = None;
+        let mut best_bot_manager = None;
         let mut max_confidence = 0.0;
 
-        // Initialize evidence map for all providers (matches working binary)
+        // Initialize evidence map for all enabled providers (matches working binary)
         for provider_name in self.providers.iter().map(|entry| entry.key().clone()) {
-            evidence_map.insert(provider_name, Vec::new());
+            if self.is_provider_enabled(&provider_name) {
+                evidence_map.insert(provider_name, Vec::new());
+            }
         }
         
-        // Initialize evidence map for additional analysis types
-        evidence_map.insert("TimingAnalysis".to_string(), Vec::new());
-        evidence_map.insert("DnsAnalysis".to_string(), Vec::new());
-        evidence_map.insert("PayloadAnalysis".to_string(), Vec::new());
+        // Initialize evidence map for additional analysis types that were run
+        for analyzer in Analyzer::ALL {
+            if self.should_run(analyzer, options) {
+                evidence_map.insert(analyzer.evidence_key().to_string(), Vec::new());
+            }
+        }
 
         // Track best WAF and CDN separately to support multi-vendor scenarios
         let mut best_waf_confidence = 0.0;
         let mut best_cdn_confidence = 0.0;
+        let mut best_bot_manager_confidence = 0.0;
 
         for result in results.into_iter().flatten() {
             let (name, evidence, _base_confidence) = result;
             
-            // Always insert evidence (even if empty) to match working binary structure
-            evidence_map.insert(name.clone(), evidence.clone());
+            // Always insert evidence (even if empty) to match working binary structure;
+            // scoring below still uses the full list
+            let groups = crate::evidence::group(&evidence);
+            evidence_map.insert(name.clone(), crate::evidence::condense(&groups));
+            if !groups.is_empty() {
+                evidence_groups.insert(name.clone(), groups);
+            }
             
             if !evidence.is_empty() {
                 // NEW: Use advanced confidence scoring instead of simple average
@@ -209,8 +396,12 @@ impl ProviderRegistry {
                     .as_ref()
                     .map(|r| r.headers.clone())
                     .unwrap_or_default();
-                let confidence_result = self.advanced_scoring.calculate_confidence(&name, &evidence, &response_headers);
+                let confidence_result = scoring.calculate_confidence(&name, &evidence, &response_headers);
                 let final_confidence = confidence_result.score;
+                if !confidence_result.negative_evidence.is_empty() {
+                    negative_evidence.insert(name.clone(), confidence_result.negative_evidence.clone());
+                }
+                confidence_details.insert(name.clone(), confidence_result);
                 
                 provider_scores.insert(name.clone(), final_confidence);
                 
@@ -228,6 +419,8 @@ impl ProviderRegistry {
                                 best_waf = Some(ProviderDetection {
                                     name: name.clone(),
                                     confidence: final_confidence,
+                                    features: Vec::new(),
+                                    layer: None,
                                 });
                             }
                         }
@@ -237,6 +430,8 @@ impl ProviderRegistry {
                                 best_cdn = Some(ProviderDetection {
                                     name: name.clone(),
                                     confidence: final_confidence,
+                                    features: Vec::new(),
+                                    layer: None,
                                 });
                             }
                         }
@@ -247,6 +442,8 @@ impl ProviderRegistry {
                                 best_waf = Some(ProviderDetection {
                                     name: name.clone(),
                                     confidence: final_confidence,
+                                    features: Vec::new(),
+                                    layer: None,
                                 });
                             }
                             if final_confidence > best_cdn_confidence {
@@ -254,35 +451,251 @@ impl ProviderRegistry {
                                 best_cdn = Some(ProviderDetection {
                                     name: name.clone(),
                                     confidence: final_confidence,
+                                    features: Vec::new(),
+                                    layer: None,
                                 });
                             }
                         }
+                        "Bot Management" if final_confidence > best_bot_manager_confidence => {
+                            best_bot_manager_confidence = final_confidence;
+                            best_bot_manager = Some(ProviderDetection {
+                                name: name.clone(),
+                                confidence: final_confidence,
+                                features: Vec::new(),
+                                layer: None,
+                            });
+                        }
                         _ => {}
                     }
                 }
             }
         }
 
+        // Attach vendor sub-features to the winning detections
+        if let Some(response) = &context.response {
+            for detection in [best_waf.as_mut(), best_cdn.as_mut(), best_bot_manager.as_mut()].into_iter().flatten() {
+                if let Some(provider) = self.providers.get(&detection.name) {
+                    detection.features = provider.features(response);
+                }
+            }
+        }
+
+        let detected_stack = self.detected_stack(&provider_scores, context.response.as_ref());
+
         let detection_time = start_time.elapsed().as_millis() as u64;
 
         // Create metadata matching working binary
-        let metadata = DetectionMetadata {
-            timestamp: chrono::Utc::now(),
-            version: "0.1.0".to_string(),
-            user_agent: "WAF-Detector/1.0".to_string(),
-        };
+        let mut metadata = self.detection_metadata();
+        metadata.body_truncation = context.response.as_ref().and_then(|response| response.truncation.clone());
 
         Ok(DetectionResult {
             url: context.url.clone(),
             detected_waf: best_waf,
             detected_cdn: best_cdn,
+            detected_bot_manager: best_bot_manager,
+            detected_stack,
+            bot_challenge: context.response.as_ref().and_then(crate::challenge::detect_challenge),
+            edge_location: context.response.as_ref().and_then(crate::edge::edge_location),
             provider_scores,
             evidence_map,
+            negative_evidence,
+            confidence_details,
             detection_time_ms: detection_time,
             metadata,
+            diagnostics,
+            error: None,
+            ruleset_info: None,
+            waf_mode: None,
+            rate_limit: None,
+            dual_stack: None,
+            idn: None,
+            evidence_groups,
+            explanation: None,
+            consensus: None,
+            browser_fallback: None,
+            session: None,
+            origin_bypass: None,
         })
     }
 
+    /// One future per enabled network analyzer, in `Analyzer::ALL` order; disabled
+    /// analyzers get none, so a passive scan builds nothing here
+    fn network_analyzer_futures(&self, context: &DetectionContext, options: &DetectionOptions) -> Vec<BoxFuture<'static, (Option<TechniqueResult>, TechniqueDiagnostics)>> {
+        let mut analyzers = Vec::new();
+
+        // NEW: Run timing analysis in parallel with provider detection
+        #[cfg(feature = "timing")]
+        if self.should_run(Analyzer::Timing, options) {
+            let url = context.url.clone();
+            let timing_analyzer = Arc::clone(&self.timing_analyzer);
+            analyzers.push(instrumented(Analyzer::Timing.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match timing_analyzer.analyze(&url).await {
+                    Ok(timing_evidence) if !timing_evidence.is_empty() => {
+                        (Some((Analyzer::Timing.evidence_key().to_string(), timing_evidence, 0.85)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Timing analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // NEW: Run DNS analysis in parallel with provider detection
+        #[cfg(feature = "dns")]
+        if self.should_run(Analyzer::Dns, options) {
+            let url = context.url.clone();
+            let dns_analyzer = self.dns_analyzer();
+            analyzers.push(instrumented(Analyzer::Dns.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match dns_analyzer.analyze(&url).await {
+                    Ok(dns_evidence) if !dns_evidence.is_empty() => {
+                        (Some((Analyzer::Dns.evidence_key().to_string(), dns_evidence, 0.95)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("DNS analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // NEW: Run payload analysis in parallel with provider detection
+        #[cfg(feature = "payload")]
+        if self.should_run(Analyzer::Payload, options) {
+            let url = context.url.clone();
+            let payload_analyzer = Arc::clone(&self.payload_analyzer);
+            analyzers.push(instrumented(Analyzer::Payload.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match payload_analyzer.analyze(&url).await {
+                    Ok(payload_result) => {
+                        let evidence = payload_analyzer.to_evidence(&payload_result);
+                        if !evidence.is_empty() {
+                            (Some((Analyzer::Payload.evidence_key().to_string(), evidence, payload_result.confidence)), None)
+                        } else {
+                            (None, None)
+                        }
+                    }
+                    Err(e) => (None, Some(format!("Payload analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // ALPN and QUIC probes; Alt-Svc comes from the response already fetched
+        #[cfg(feature = "tls")]
+        if self.should_run(Analyzer::Protocol, options) {
+            let url = context.url.clone();
+            let response = context.response.clone();
+            let protocol_analyzer = Arc::clone(&self.protocol_analyzer);
+            analyzers.push(instrumented(Analyzer::Protocol.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match protocol_analyzer.analyze(&url, response.as_ref()).await {
+                    Ok(evidence) if !evidence.is_empty() => {
+                        (Some((Analyzer::Protocol.evidence_key().to_string(), evidence, 0.5)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Protocol analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // Raw HTTP/1.1 request for header casing; order alone comes from the fetched response
+        if self.should_run(Analyzer::HeaderOrder, options) {
+            let url = context.url.clone();
+            let response = context.response.clone();
+            let header_order_analyzer = Arc::clone(&self.header_order_analyzer);
+            analyzers.push(instrumented(Analyzer::HeaderOrder.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match header_order_analyzer.analyze(&url, response.as_ref()).await {
+                    Ok(evidence) if !evidence.is_empty() => {
+                        (Some((Analyzer::HeaderOrder.evidence_key().to_string(), evidence, 0.5)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Header order analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // Favicon and static-asset requests
+        if self.should_run(Analyzer::Favicon, options) {
+            let url = context.url.clone();
+            let response = context.response.clone();
+            let favicon_analyzer = self.favicon_analyzer();
+            analyzers.push(instrumented(Analyzer::Favicon.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match favicon_analyzer.analyze(&url, response.as_ref()).await {
+                    Ok(evidence) if !evidence.is_empty() => {
+                        (Some((Analyzer::Favicon.evidence_key().to_string(), evidence, 0.5)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Favicon analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // Repeated, conditional and debug requests under fresh cache keys
+        if self.should_run(Analyzer::Cache, options) {
+            let url = context.url.clone();
+            let cache_analyzer = Arc::clone(&self.cache_analyzer);
+            analyzers.push(instrumented(Analyzer::Cache.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match cache_analyzer.analyze(&url).await {
+                    Ok(evidence) if !evidence.is_empty() => {
+                        (Some((Analyzer::Cache.evidence_key().to_string(), evidence, 0.5)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Cache analysis failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // Certificate transparency search; goes to the CT service, not the target
+        if self.should_run(Analyzer::Ct, options) {
+            let url = context.url.clone();
+            let ct_analyzer = self.ct_analyzer();
+            analyzers.push(instrumented(Analyzer::Ct.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match ct_analyzer.analyze(&url).await {
+                    Ok(evidence) if !evidence.is_empty() => {
+                        (Some((Analyzer::Ct.evidence_key().to_string(), evidence, 0.5)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Certificate transparency lookup failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        // Shodan/Censys records of the resolved addresses; goes to those services, not the target
+        if self.should_run(Analyzer::HostIntel, options) {
+            let url = context.url.clone();
+            let intel_analyzer = self.intel_analyzer();
+            analyzers.push(instrumented(Analyzer::HostIntel.evidence_key().to_string(), TechniqueKind::Analyzer, async move {
+                match intel_analyzer.analyze(&url).await {
+                    Ok(evidence) if !evidence.is_empty() => {
+                        (Some((Analyzer::HostIntel.evidence_key().to_string(), evidence, 0.5)), None)
+                    }
+                    Ok(_) => (None, None),
+                    Err(e) => (None, Some(format!("Internet-scan lookup failed: {}", e))),
+                }
+            }).boxed());
+        }
+
+        analyzers
+    }
+
+    /// Every scored provider with its inferred layer, outermost layer first and by
+    /// confidence within a layer
+    fn detected_stack(&self, provider_scores: &HashMap<String, f64>, response: Option<&crate::http::HttpResponse>) -> Vec<ProviderDetection> {
+        let mut stack: Vec<ProviderDetection> = provider_scores
+            .iter()
+            .filter(|(_, confidence)| **confidence > 0.0)
+            .filter_map(|(name, confidence)| {
+                let provider = self.providers.get(name)?;
+                Some(ProviderDetection {
+                    name: name.clone(),
+                    confidence: *confidence,
+                    features: response.map(|r| provider.features(r)).unwrap_or_default(),
+                    layer: Some(provider.stack_layer()),
+                })
+            })
+            .collect();
+        stack.sort_by(|a, b| {
+            a.layer.cmp(&b.layer)
+                .then(b.confidence.total_cmp(&a.confidence))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        stack
+    }
+
     pub fn list_providers(&self) -> Vec<ProviderMetadata> {
         let mut providers: Vec<_> = self.provider_metadata
             .iter()
@@ -300,6 +713,187 @@ impl ProviderRegistry {
     pub fn is_provider_registered(&self, name: &str) -> bool {
         self.providers.contains_key(name)
     }
+
+    /// Send provider active probes, payload, timing and cache probes through `client`
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.cache_analyzer = Arc::new((*self.cache_analyzer).clone().with_http_client(client.clone()));
+        #[cfg(feature = "payload")]
+        {
+            self.payload_analyzer = Arc::new((*self.payload_analyzer).clone().with_http_client(client.clone()));
+        }
+        #[cfg(feature = "timing")]
+        {
+            self.timing_analyzer = Arc::new(TimingAnalyzer::with_client(self.timing_analyzer.config().clone(), client.clone()));
+        }
+        self.probe_client = Arc::new(client);
+        self
+    }
+
+    /// Evidence weights used to score detections
+    pub fn advanced_scoring(&self) -> Arc<AdvancedScoring> {
+        self.advanced_scoring.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the evidence weights and thresholds (e.g. with user overrides from `--scoring`)
+    pub fn set_advanced_scoring(&self, scoring: AdvancedScoring) {
+        *self.advanced_scoring.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(scoring);
+    }
+
+    fn block_page_classifier(&self) -> Arc<BlockPageClassifier> {
+        self.block_page_classifier.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Load a signature bundle: its rules extend the provider of the same name or
+    /// register a new one, and its CNAME patterns, IP ranges and block pages are added
+    /// to the DNS and block-page analyzers
+    pub fn apply_signatures(&self, bundle: &SignatureBundle) -> Result<()> {
+        let mut providers = Vec::new();
+        for signatures in &bundle.providers {
+            let name = self.resolve_provider_name(&signatures.name);
+            let base = name.as_ref().and_then(|name| self.get_provider(name));
+            providers.push((name, SignatureProvider::new(signatures, &bundle.version, base)?));
+        }
+        #[cfg(feature = "dns")]
+        let dns_analyzer = (*self.dns_analyzer()).clone().with_patterns(&bundle.dns_patterns)?.with_ip_ranges(bundle.ip_ranges.clone());
+
+        for (name, provider) in providers {
+            let enabled = name.as_ref().is_none_or(|name| self.is_provider_enabled(name));
+            if let Some(name) = name {
+                self.providers.remove(&name);
+                self.provider_metadata.remove(&name);
+            }
+            let provider = Provider::Signature(provider);
+            let registered = provider.name().to_string();
+            self.register_provider(provider)?;
+            if !enabled {
+                self.set_provider_enabled(&registered, false)?;
+            }
+        }
+        #[cfg(feature = "dns")]
+        {
+            *self.dns_analyzer.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(dns_analyzer);
+        }
+        let classifier = (*self.block_page_classifier()).clone().with_pages(bundle.block_pages.clone());
+        *self.block_page_classifier.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(classifier);
+        *self.signature_bundle.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(bundle.clone()));
+        Ok(())
+    }
+
+    /// The bundle loaded with `apply_signatures`, if any
+    pub fn signature_bundle(&self) -> Option<Arc<SignatureBundle>> {
+        self.signature_bundle.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Bundle rules per registered provider; providers without any are left out
+    pub fn pattern_counts(&self) -> BTreeMap<String, usize> {
+        self.providers
+            .iter()
+            .filter_map(|entry| match entry.value() {
+                Provider::Signature(provider) => Some((entry.key().clone(), provider.rule_count())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Providers sorted by name with the bundle rules each one carries
+    pub fn signature_inventory(&self) -> Inventory {
+        let mut providers: Vec<ProviderInventory> = self
+            .list_providers()
+            .into_iter()
+            .map(|metadata| {
+                let (built_in, rules) = match self.get_provider(&metadata.name) {
+                    Some(Provider::Signature(provider)) => (provider.base().is_some(), provider.rules().cloned().collect()),
+                    _ => (true, Vec::new()),
+                };
+                ProviderInventory {
+                    name: metadata.name,
+                    version: metadata.version,
+                    provider_type: metadata.provider_type,
+                    enabled: metadata.enabled,
+                    built_in,
+                    rules,
+                }
+            })
+            .collect();
+        providers.sort_by_cached_key(|provider| provider.name.to_lowercase());
+        Inventory {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            bundle: self.signature_bundle().as_deref().map(BundleSummary::from),
+            providers,
+        }
+    }
+
+    /// Metadata for a result produced now: tool version and the signatures in effect
+    pub fn detection_metadata(&self) -> DetectionMetadata {
+        DetectionMetadata {
+            timestamp: chrono::Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            user_agent: "WAF-Detector/1.0".to_string(),
+            retries: 0,
+            preflight: None,
+            signature_bundle: self.signature_bundle().map(|bundle| bundle.version.clone()),
+            pattern_counts: self.pattern_counts(),
+            body_truncation: None,
+        }
+    }
+
+    fn favicon_analyzer(&self) -> Arc<FaviconAnalyzer> {
+        self.favicon_analyzer.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Favicon hashes attributed to vendors (e.g. from `--favicon-db`)
+    pub fn set_known_favicons(&self, known: Vec<KnownFavicon>) {
+        let analyzer = (*self.favicon_analyzer()).clone().with_known_favicons(known);
+        *self.favicon_analyzer.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(analyzer);
+    }
+
+    #[cfg(feature = "dns")]
+    fn dns_analyzer(&self) -> Arc<DnsAnalyzer> {
+        self.dns_analyzer.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Passive DNS services used for former CNAMEs and origin candidates
+    pub fn passive_dns_sources(&self) -> Vec<Arc<dyn PassiveDnsSource>> {
+        self.passive_dns.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Query passive DNS history (e.g. from `--pdns`) in DNS analysis and origin discovery
+    pub fn set_passive_dns_sources(&self, sources: Vec<Arc<dyn PassiveDnsSource>>) {
+        #[cfg(feature = "dns")]
+        {
+            let analyzer = (*self.dns_analyzer()).clone().with_passive_dns(sources.clone());
+            *self.dns_analyzer.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(analyzer);
+        }
+        *self.passive_dns.write().unwrap_or_else(|e| e.into_inner()) = sources;
+    }
+
+    fn intel_analyzer(&self) -> Arc<IntelAnalyzer> {
+        self.intel_analyzer.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Shodan/Censys sources used for host records and origin candidates
+    pub fn intel_sources(&self) -> Vec<Arc<dyn IntelSource>> {
+        self.intel_analyzer().sources().to_vec()
+    }
+
+    /// Replace the internet-scan sources (e.g. from `--intel`)
+    pub fn set_intel_sources(&self, sources: Vec<Arc<dyn IntelSource>>) {
+        *self.intel_analyzer.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(IntelAnalyzer::new(sources));
+    }
+
+    fn ct_analyzer(&self) -> Arc<CtAnalyzer> {
+        self.ct_analyzer.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Certificate transparency backend used for CT enrichment and subdomain enumeration
+    pub fn ct_source(&self) -> Arc<dyn CtSource> {
+        self.ct_analyzer().source()
+    }
+
+    /// Replace the certificate transparency backend (e.g. with `--ct-url`)
+    pub fn set_ct_source(&self, source: Arc<dyn CtSource>) {
+        *self.ct_analyzer.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(CtAnalyzer::new(source));
+    }
 }
 
 impl Default for ProviderRegistry {
@@ -307,3 +901,208 @@ impl Default for ProviderRegistry {
         Self::new()
     }
 }
+
+/// Name, evidence and base confidence produced by one provider or analyzer
+type TechniqueResult = (String, Vec<Evidence>, f64);
+
+/// Run a provider/analyzer future, recording its duration, request count and error
+async fn instrumented<F>(name: String, kind: TechniqueKind, future: F) -> (Option<TechniqueResult>, TechniqueDiagnostics)
+where
+    F: std::future::Future<Output = (Option<TechniqueResult>, Option<String>)>,
+{
+    let span = tracing::info_span!("technique", name = %name, kind = ?kind);
+    let start = std::time::Instant::now();
+    let ((result, error), requests) = crate::http::count_requests(future).instrument(span.clone()).await;
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    span.in_scope(|| match &error {
+        Some(error) => tracing::warn!(requests, duration_ms, "{}", error),
+        None => tracing::debug!(requests, duration_ms, found = result.is_some(), "technique finished"),
+    });
+
+    let diagnostics = TechniqueDiagnostics {
+        name,
+        kind,
+        duration_ms,
+        requests,
+        error,
+    };
+    (result, diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{akamai::AkamaiProvider, cloudflare::CloudFlareProvider};
+
+    fn registry_with_providers() -> ProviderRegistry {
+        let registry = ProviderRegistry::new();
+        registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+        registry.register_provider(Provider::Akamai(AkamaiProvider::new())).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_enable_only_providers() {
+        let registry = registry_with_providers();
+
+        registry.enable_only_providers(&["cloudflare"]).unwrap();
+        assert!(registry.is_provider_enabled("CloudFlare"));
+        assert!(!registry.is_provider_enabled("Akamai"));
+
+        assert!(registry.enable_only_providers(&["imperva"]).is_err());
+    }
+
+    #[test]
+    fn test_set_provider_enabled() {
+        let registry = registry_with_providers();
+
+        registry.set_provider_enabled("AKAMAI", false).unwrap();
+        assert!(!registry.is_provider_enabled("Akamai"));
+        assert!(registry.set_provider_enabled("unknown", false).is_err());
+    }
+
+    #[test]
+    fn test_analyzer_toggles() {
+        let registry = ProviderRegistry::new();
+        assert!(Analyzer::ALL.iter().all(|a| registry.is_analyzer_enabled(*a)));
+
+        registry.set_analyzer_enabled(Analyzer::Dns, false);
+        assert!(!registry.is_analyzer_enabled(Analyzer::Dns));
+
+        registry.set_passive_only();
+        assert!(!registry.is_analyzer_enabled(Analyzer::Timing));
+        assert!(!registry.is_analyzer_enabled(Analyzer::Payload));
+    }
+
+    #[tokio::test]
+    async fn test_passive_scan_builds_no_analyzer_futures() {
+        let registry = registry_with_providers();
+        let options = DetectionOptions::offline();
+        let response = crate::http::HttpResponse::from_parts(403, [("server", "cloudflare"), ("cf-ray", "8a1b2c3d4e5f6789-FRA")], "", "https://example.com/");
+        let context = DetectionContext { url: "https://example.com/".to_string(), response: Some(response), dns_info: None, user_agent: String::new() };
+
+        assert!(registry.network_analyzer_futures(&context, &options).is_empty());
+        // Only the enabled analyzers are built, not all of them
+        assert_eq!(registry.network_analyzer_futures(&context, &DetectionOptions::default()).len(), 1);
+        assert_eq!(registry.network_analyzer_futures(&context, &DetectionOptions { favicon: true, ct: true, ..options.clone() }).len(), 2);
+
+        let result = registry.detect_all_with_options(&context, &options).await.unwrap();
+        assert_eq!(result.detected_waf.map(|waf| waf.name).as_deref(), Some("CloudFlare"));
+        // The two providers and the block-page match, nothing else
+        assert_eq!(result.diagnostics.techniques.len(), 3);
+        assert!(result.diagnostics.techniques.iter().all(|t| t.kind == TechniqueKind::Provider || t.name == "BlockPageAnalysis"));
+    }
+
+    #[test]
+    fn test_strict_passive_cannot_be_overridden() {
+        let registry = ProviderRegistry::new();
+        registry.set_strict_passive(true);
+
+        registry.set_analyzer_enabled(Analyzer::Payload, true);
+        registry.set_analyzer_enabled(Analyzer::Timing, true);
+        assert!(!registry.is_analyzer_enabled(Analyzer::Payload));
+        assert!(!registry.is_analyzer_enabled(Analyzer::Timing));
+        assert!(!registry.is_analyzer_enabled(Analyzer::Protocol));
+        assert!(!registry.is_analyzer_enabled(Analyzer::HeaderOrder));
+        assert!(registry.is_analyzer_enabled(Analyzer::Dns));
+    }
+
+    #[test]
+    fn test_apply_signatures() {
+        let registry = registry_with_providers();
+        registry.set_provider_enabled("Akamai", false).unwrap();
+        let bundle = SignatureBundle::from_json(br#"{"version": "2026.10.1", "published": "2026-10-01T00:00:00Z", "providers": [
+            {"name": "akamai", "rules": [{"id": "akamai-grn", "target": {"header": "akamai-grn"}, "pattern": ".+", "confidence": 0.9}]},
+            {"name": "Imperva", "type": "WAF", "rules": [{"id": "imperva-iinfo", "target": {"header": "x-iinfo"}, "pattern": ".+", "confidence": 0.9}]}
+        ]}"#).unwrap();
+
+        registry.apply_signatures(&bundle).unwrap();
+        let akamai = registry.get_provider("Akamai").unwrap();
+        assert!(matches!(&akamai, Provider::Signature(provider) if matches!(provider.base(), Some(Provider::Akamai(_)))));
+        assert!(!registry.is_provider_enabled("Akamai"));
+        assert!(matches!(registry.get_provider("Imperva"), Some(Provider::Signature(_))));
+        assert!(registry.is_provider_enabled("Imperva"));
+
+        let metadata = registry.detection_metadata();
+        assert_eq!(metadata.signature_bundle.as_deref(), Some("2026.10.1"));
+        assert_eq!(metadata.pattern_counts, BTreeMap::from([("Akamai".to_string(), 1), ("Imperva".to_string(), 1)]));
+        let inventory = registry.signature_inventory();
+        let names: Vec<(&str, bool, usize)> = inventory.providers.iter().map(|p| (p.name.as_str(), p.built_in, p.rules.len())).collect();
+        assert_eq!(names, [("Akamai", true, 1), ("CloudFlare", true, 0), ("Imperva", false, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_techniques_are_skipped() {
+        let mut server = mockito::Server::new_async().await;
+        let untouched = server.mock("GET", mockito::Matcher::Any).expect(0).create_async().await;
+
+        let registry = registry_with_providers();
+        registry.set_provider_enabled("Akamai", false).unwrap();
+        for analyzer in Analyzer::ALL {
+            registry.set_analyzer_enabled(analyzer, false);
+        }
+
+        let context = DetectionContext {
+            url: server.url(),
+            response: None,
+            dns_info: None,
+            user_agent: "test-agent".to_string(),
+        };
+
+        // Request the probing analyzers; disabling them must win
+        let options = DetectionOptions { timing: true, payload: true, protocol: true, dns: false, ..DetectionOptions::default() };
+        let result = registry.detect_all_with_options(&context, &options).await.unwrap();
+        untouched.assert_async().await;
+        assert!(result.evidence_map.contains_key("CloudFlare"));
+        assert!(!result.evidence_map.contains_key("Akamai"));
+        assert!(!result.evidence_map.contains_key("TimingAnalysis"));
+        assert!(!result.evidence_map.contains_key("PayloadAnalysis"));
+        assert!(!result.evidence_map.contains_key("ProtocolAnalysis"));
+    }
+
+    #[tokio::test]
+    async fn test_default_options_skip_probing_analyzers() {
+        let registry = registry_with_providers();
+        registry.set_analyzer_enabled(Analyzer::Dns, false);
+
+        let context = DetectionContext {
+            url: "https://example.com".to_string(),
+            response: None,
+            dns_info: None,
+            user_agent: "test-agent".to_string(),
+        };
+
+        let result = registry.detect_all(&context).await.unwrap();
+        assert!(!result.evidence_map.contains_key("TimingAnalysis"));
+        assert!(!result.evidence_map.contains_key("PayloadAnalysis"));
+        assert!(!result.evidence_map.contains_key("CtAnalysis"));
+        assert!(result.evidence_map.contains_key("CloudFlare"));
+    }
+
+    #[tokio::test]
+    async fn test_ct_evidence_from_configured_source() {
+        let mut server = mockito::Server::new_async().await;
+        let ct = server.mock("GET", "/")
+            .match_query(mockito::Matcher::UrlEncoded("q".to_string(), "shop.example.com".to_string()))
+            .with_status(200)
+            .with_body(r#"[{"issuer_name": "C=US, O=Cloudflare, Inc., CN=Cloudflare Inc ECC CA-3", "common_name": "sni.cloudflaressl.com", "name_value": "shop.example.com\nsni.cloudflaressl.com"}]"#)
+            .create_async()
+            .await;
+
+        let registry = registry_with_providers();
+        registry.set_ct_source(Arc::new(CrtSh::default().with_base_url(&server.url())));
+        let context = DetectionContext {
+            url: "https://shop.example.com/".to_string(),
+            response: None,
+            dns_info: None,
+            user_agent: "test-agent".to_string(),
+        };
+
+        let options = DetectionOptions { ct: true, ..DetectionOptions::offline() };
+        let result = registry.detect_all_with_options(&context, &options).await.unwrap();
+        ct.assert_async().await;
+        let signatures: Vec<&str> = result.evidence_map["CtAnalysis"].iter().map(|e| e.signature_matched.as_str()).collect();
+        assert_eq!(signatures, vec!["ct-issuer-cloudflare", "ct-san-cloudflare"]);
+    }
+}
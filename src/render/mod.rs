@@ -0,0 +1,99 @@
+//! HTML rendering shared by the web interface and the CLI's HTML reports
+//!
+//! Templates live in `web/templates` (pages) and `web/templates/partials` (fragments such
+//! as result cards and evidence tables) and are compiled into the binary.
+
+pub mod template;
+pub mod views;
+
+pub use template::{escape_html, Partials, Template};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+macro_rules! embedded {
+    ($($name:literal => $path:literal),* $(,)?) => {
+        &[$(($name, include_str!(concat!("../../web/templates/", $path)))),*]
+    };
+}
+
+/// Every template by name; partials are included with `{{> name}}`
+const SOURCES: &[(&str, &str)] = embedded! {
+    "dashboard" => "dashboard.html",
+    "api_docs" => "api_docs.html",
+    "history" => "history.html",
+    "history_entry" => "history_entry.html",
+    "smoke_report" => "smoke_report.html",
+    "report_style" => "partials/report_style.html",
+    "result_card" => "partials/result_card.html",
+    "evidence_table" => "partials/evidence_table.html",
+    "payload_table" => "partials/payload_table.html",
+    "smoke_summary" => "partials/smoke_summary.html",
+};
+
+/// A set of parsed templates that can include each other
+#[derive(Debug, Default)]
+pub struct Templates {
+    templates: HashMap<String, Template>,
+}
+
+impl Partials for Templates {
+    fn partial(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+}
+
+impl Templates {
+    /// Parse `sources` and check that every included partial exists
+    pub fn parse<'a>(sources: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self> {
+        let mut templates = HashMap::new();
+        for (name, source) in sources {
+            let template = Template::parse(source).map_err(|e| anyhow!("template '{}': {}", name, e))?;
+            templates.insert(name.to_string(), template);
+        }
+        for (name, template) in &templates {
+            if let Some(missing) = template.partial_names().into_iter().find(|partial| !templates.contains_key(*partial)) {
+                return Err(anyhow!("template '{}' includes unknown partial '{}'", name, missing));
+            }
+        }
+        Ok(Self { templates })
+    }
+
+    pub fn render(&self, name: &str, context: &impl Serialize) -> Result<String> {
+        let template = self.templates.get(name).ok_or_else(|| anyhow!("unknown template '{}'", name))?;
+        template.render(&serde_json::to_value(context)?, self)
+    }
+}
+
+/// The templates compiled into the binary
+pub fn templates() -> &'static Templates {
+    static TEMPLATES: OnceLock<Templates> = OnceLock::new();
+    // Checked by `test_embedded_templates_parse`, so this cannot fail at runtime
+    TEMPLATES.get_or_init(|| Templates::parse(SOURCES.iter().copied()).expect("embedded templates are valid"))
+}
+
+/// Render one of the embedded templates
+pub fn render(name: &str, context: &impl Serialize) -> Result<String> {
+    templates().render(name, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_templates_parse() {
+        let templates = Templates::parse(SOURCES.iter().copied()).unwrap();
+        assert_eq!(templates.templates.len(), SOURCES.len());
+        let page = render("dashboard", &serde_json::json!({ "version": "1.2.3" })).unwrap();
+        assert!(page.contains("v1.2.3"));
+    }
+
+    #[test]
+    fn test_unknown_partial_is_rejected() {
+        let err = Templates::parse([("page", "{{> missing}}")]).unwrap_err();
+        assert!(err.to_string().contains("unknown partial 'missing'"));
+    }
+}
@@ -0,0 +1,252 @@
+//! A Mustache subset: `{{name}}` (HTML-escaped), `{{{name}}}` / `{{&name}}` (raw),
+//! `{{#name}}…{{/name}}` sections, `{{^name}}…{{/name}}` inverted sections,
+//! `{{>partial}}` and `{{!comment}}`. Names may be dotted; `{{.}}` is the current value.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable { name: String, escape: bool },
+    Section { name: String, inverted: bool, children: Vec<Node> },
+    Partial(String),
+}
+
+/// A parsed template
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// Looks up partials by name while rendering
+pub trait Partials {
+    fn partial(&self, name: &str) -> Option<&Template>;
+}
+
+impl Partials for () {
+    fn partial(&self, _name: &str) -> Option<&Template> {
+        None
+    }
+}
+
+/// Partials may include each other, but not endlessly
+const MAX_PARTIAL_DEPTH: usize = 16;
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut stack: Vec<(String, bool, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                nodes.push(Node::Text(rest[..start].to_string()));
+            }
+            let offset = source.len() - rest.len() + start;
+            let tag = &rest[start + 2..];
+            let (raw_triple, close) = if tag.starts_with('{') { (true, "}}}") } else { (false, "}}") };
+            let end = tag.find(close).ok_or_else(|| anyhow!("unclosed tag at line {}", line_of(source, offset)))?;
+            let body = if raw_triple { &tag[1..end] } else { &tag[..end] };
+            rest = &tag[end + close.len()..];
+
+            let (sigil, name) = match body.trim_start().chars().next() {
+                Some(c @ ('#' | '^' | '/' | '>' | '&' | '!')) => (Some(c), body.trim_start()[1..].trim()),
+                _ => (None, body.trim()),
+            };
+            if raw_triple {
+                nodes.push(Node::Variable { name: body.trim().to_string(), escape: false });
+                continue;
+            }
+            match sigil {
+                Some('!') => {}
+                Some('#') | Some('^') => {
+                    stack.push((name.to_string(), sigil == Some('^'), std::mem::take(&mut nodes)));
+                }
+                Some('/') => {
+                    let (open, inverted, parent) = stack.pop().ok_or_else(|| anyhow!("unexpected {{{{/{}}}}} at line {}", name, line_of(source, offset)))?;
+                    if open != name {
+                        return Err(anyhow!("{{{{/{}}}}} at line {} closes section '{}'", name, line_of(source, offset), open));
+                    }
+                    let children = std::mem::replace(&mut nodes, parent);
+                    nodes.push(Node::Section { name: open, inverted, children });
+                }
+                Some('>') => nodes.push(Node::Partial(name.to_string())),
+                Some('&') => nodes.push(Node::Variable { name: name.to_string(), escape: false }),
+                _ => nodes.push(Node::Variable { name: name.to_string(), escape: true }),
+            }
+        }
+        if let Some((open, ..)) = stack.last() {
+            return Err(anyhow!("section '{}' is never closed", open));
+        }
+        if !rest.is_empty() {
+            nodes.push(Node::Text(rest.to_string()));
+        }
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, context: &Value, partials: &dyn Partials) -> Result<String> {
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut vec![context], partials, 0, &mut out)?;
+        Ok(out)
+    }
+
+    /// Names of the partials this template includes
+    pub fn partial_names(&self) -> Vec<&str> {
+        fn collect<'a>(nodes: &'a [Node], names: &mut Vec<&'a str>) {
+            for node in nodes {
+                match node {
+                    Node::Partial(name) => names.push(name),
+                    Node::Section { children, .. } => collect(children, names),
+                    _ => {}
+                }
+            }
+        }
+        let mut names = Vec::new();
+        collect(&self.nodes, &mut names);
+        names
+    }
+}
+
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+fn render_nodes(nodes: &[Node], stack: &mut Vec<&Value>, partials: &dyn Partials, depth: usize, out: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Variable { name, escape } => {
+                let text = lookup(stack, name).map(display).unwrap_or_default();
+                if *escape {
+                    out.push_str(&escape_html(&text));
+                } else {
+                    out.push_str(&text);
+                }
+            }
+            Node::Section { name, inverted, children } => {
+                let value = lookup(stack, name).filter(|value| is_truthy(value));
+                match (value, inverted) {
+                    (None, true) => render_nodes(children, stack, partials, depth, out)?,
+                    (Some(Value::Array(items)), false) => {
+                        for item in items {
+                            stack.push(item);
+                            let result = render_nodes(children, stack, partials, depth, out);
+                            stack.pop();
+                            result?;
+                        }
+                    }
+                    (Some(value), false) => {
+                        stack.push(value);
+                        let result = render_nodes(children, stack, partials, depth, out);
+                        stack.pop();
+                        result?;
+                    }
+                    _ => {}
+                }
+            }
+            Node::Partial(name) => {
+                if depth >= MAX_PARTIAL_DEPTH {
+                    return Err(anyhow!("partials nested deeper than {} levels at '{}'", MAX_PARTIAL_DEPTH, name));
+                }
+                let partial = partials.partial(name).ok_or_else(|| anyhow!("unknown partial '{}'", name))?;
+                render_nodes(&partial.nodes, stack, partials, depth + 1, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a (dotted) name against the context stack, innermost first
+fn lookup<'v>(stack: &[&'v Value], name: &str) -> Option<&'v Value> {
+    let current = *stack.last()?;
+    if name == "." {
+        return Some(current);
+    }
+    let mut parts = name.split('.');
+    let first = parts.next()?;
+    let mut value = stack.iter().rev().find_map(|context| context.get(first))?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+/// Mustache falsiness: null, false and empty lists (and, here, empty strings)
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Array(items) => !items.is_empty(),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, context: Value) -> String {
+        Template::parse(source).unwrap().render(&context, &()).unwrap()
+    }
+
+    #[test]
+    fn test_variables_and_escaping() {
+        let context = json!({ "name": "<b>Tom & 'Jerry'</b>", "n": 3, "waf": { "name": "CloudFlare" } });
+        assert_eq!(render("{{name}}|{{{name}}}|{{& name}}", context.clone()), "&lt;b&gt;Tom &amp; &#39;Jerry&#39;&lt;/b&gt;|<b>Tom & 'Jerry'</b>|<b>Tom & 'Jerry'</b>");
+        assert_eq!(render("{{ n }} {{waf.name}} [{{missing}}]{{! ignored }}", context), "3 CloudFlare []");
+    }
+
+    #[test]
+    fn test_sections() {
+        let context = json!({ "items": [{ "name": "a" }, { "name": "b" }], "empty": [], "title": "T", "on": true, "off": false, "tags": ["x", "y"] });
+        assert_eq!(render("{{#items}}{{name}}-{{title}};{{/items}}", context.clone()), "a-T;b-T;");
+        assert_eq!(render("{{#empty}}no{{/empty}}{{^empty}}none{{/empty}}", context.clone()), "none");
+        assert_eq!(render("{{#on}}yes{{/on}}{{#off}}no{{/off}}{{^off}}!{{/off}}", context.clone()), "yes!");
+        assert_eq!(render("{{#tags}}<{{.}}>{{/tags}}", context), "<x><y>");
+    }
+
+    #[test]
+    fn test_partials_and_errors() {
+        struct One(Template);
+        impl Partials for One {
+            fn partial(&self, name: &str) -> Option<&Template> {
+                (name == "row").then_some(&self.0)
+            }
+        }
+        let partials = One(Template::parse("<li>{{.}}</li>").unwrap());
+        let list = Template::parse("<ul>{{#tags}}{{> row}}{{/tags}}</ul>").unwrap();
+        assert_eq!(list.partial_names(), vec!["row"]);
+        assert_eq!(list.render(&json!({ "tags": ["a", "b"] }), &partials).unwrap(), "<ul><li>a</li><li>b</li></ul>");
+
+        assert!(Template::parse("{{#a}}x{{/b}}").is_err());
+        assert!(Template::parse("{{#a}}x").is_err());
+        assert!(Template::parse("line\n{{oops").unwrap_err().to_string().contains("line 2"));
+        assert!(Template::parse("{{> nope}}").unwrap().render(&json!({}), &()).is_err());
+    }
+}
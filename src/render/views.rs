@@ -0,0 +1,130 @@
+//! Template contexts for scan results
+
+#[cfg(feature = "payload")]
+use crate::payload::waf_smoke_test::{PayloadTestResult, SmokeTestResult};
+use crate::{DetectionResult, ProviderDetection};
+use serde_json::{json, Value};
+
+fn percent(confidence: f64) -> String {
+    format!("{:.1}%", confidence * 100.0)
+}
+
+fn provider(detection: &ProviderDetection) -> Value {
+    json!({
+        "name": detection.name,
+        "confidence": percent(detection.confidence),
+        "layer": detection.layer.map(|layer| layer.to_string()),
+    })
+}
+
+/// Context of the `result_card` and `evidence_table` partials
+pub fn detection(result: &DetectionResult) -> Value {
+    let mut scores: Vec<_> = result.provider_scores.iter().filter(|(_, score)| **score > 0.0).collect();
+    scores.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut providers: Vec<_> = result.evidence_map.iter().collect();
+    providers.sort_by_key(|(name, _)| name.as_str());
+    let evidence: Vec<Value> = providers
+        .into_iter()
+        .flat_map(|(name, evidence)| evidence.iter().map(move |e| (name, e)))
+        .map(|(name, e)| {
+            let (method, source) = e.method_type.name_and_source();
+            json!({
+                "provider": name,
+                "method": method,
+                "source": source,
+                "confidence": percent(e.confidence),
+                "description": e.description,
+                "signature": e.signature_matched,
+            })
+        })
+        .collect();
+
+    json!({
+        "url": result.url,
+        "error": result.error,
+        "waf": result.detected_waf.as_ref().map(provider),
+        "cdn": result.detected_cdn.as_ref().map(provider),
+        "bot_manager": result.detected_bot_manager.as_ref().map(provider),
+        "stack": (!result.detected_stack.is_empty()).then(|| json!({ "layers": result.detected_stack.iter().map(provider).collect::<Vec<_>>() })),
+        "edge": result.edge_location.as_ref().map(|edge| json!({ "label": edge.label(), "provider": edge.provider })),
+        "scores": (!scores.is_empty()).then(|| json!({
+            "providers": scores.iter().map(|(name, score)| json!({ "name": name, "score": percent(**score) })).collect::<Vec<_>>(),
+        })),
+        "evidence": (!evidence.is_empty()).then(|| json!({ "rows": evidence })),
+        "time_ms": result.detection_time_ms,
+        "timestamp": result.metadata.timestamp.to_rfc3339(),
+    })
+}
+
+#[cfg(feature = "payload")]
+fn payload_rows(results: &[PayloadTestResult]) -> Option<Value> {
+    let rows: Vec<Value> = results
+        .iter()
+        .map(|test| {
+            json!({
+                "category": test.category,
+                "location": test.location.to_string(),
+                "payload": test.payload,
+                "classification": format!("{:?}", test.classification),
+                "status": test.response_status,
+                "time_ms": test.response_time_ms,
+            })
+        })
+        .collect();
+    (!rows.is_empty()).then(|| json!({ "rows": rows }))
+}
+
+/// Context of the `smoke_report` page and the `smoke_summary` partial
+#[cfg(feature = "payload")]
+pub fn smoke_test(result: &SmokeTestResult) -> Value {
+    let s = &result.summary;
+    let mut summary = vec![
+        ("Timestamp", result.timestamp.to_rfc3339()),
+        ("Detected WAF", result.detected_waf.clone().unwrap_or_else(|| "None".to_string())),
+        ("WAF mode", result.waf_mode.as_ref().map(|m| m.to_string()).unwrap_or_else(|| "Unknown".to_string())),
+        ("Effectiveness", format!("{:.1}%", s.effectiveness_percentage)),
+        ("Tests", format!("{} ({} blocked, {} allowed, {} errors)", s.total_tests, s.blocked_count, s.allowed_count, s.error_count)),
+    ];
+    if s.control_count > 0 {
+        summary.push(("False positives", format!(
+            "{} of {} benign controls ({:.1}%)", s.false_positive_count, s.control_count, s.false_positive_percentage
+        )));
+    }
+
+    let coverage: Vec<Value> = s
+        .category_coverage
+        .iter()
+        .map(|coverage| {
+            json!({
+                "owasp_category": coverage.owasp_category,
+                "category": coverage.category,
+                "tested": coverage.tested,
+                "blocked": format!("{:.1}%", coverage.blocked_percentage),
+                "examples": coverage.bypassed_examples,
+            })
+        })
+        .collect();
+    let modes: Vec<Value> = result
+        .category_modes
+        .iter()
+        .map(|category| {
+            json!({
+                "payload_type": category.payload_type.to_string(),
+                "mode": category.mode.to_string(),
+                "blocked": category.blocked,
+                "tested": category.tested,
+            })
+        })
+        .collect();
+
+    json!({
+        "url": result.url,
+        "summary": summary.into_iter().map(|(label, value)| json!({ "label": label, "value": value })).collect::<Vec<_>>(),
+        "coverage": (!coverage.is_empty()).then(|| json!({ "rows": coverage })),
+        "modes": (!modes.is_empty()).then(|| json!({ "rows": modes })),
+        "results": payload_rows(&result.test_results),
+        "controls": payload_rows(&result.control_results),
+        "recommendations": (!result.recommendations.is_empty()).then(|| json!({ "items": result.recommendations })),
+    })
+}
@@ -0,0 +1,463 @@
+//! Record-and-replay of the network interactions of a scan
+//!
+//! A [`Tape`] attached to the engine (`DetectionEngine::with_tape`) either records every
+//! HTTP exchange and DNS lookup the scans make into a [`Session`], or answers them from
+//! a session recorded earlier without touching the network. A replayed scan sees the
+//! same responses as the recorded one, so a puzzling result can be debugged offline,
+//! kept as a regression test, or sent to a vendor as a reproducible report:
+//!
+//! ```no_run
+//! # async fn example(engine: waf_detector::engine::DetectionEngine) -> anyhow::Result<()> {
+//! use waf_detector::replay::{Session, Tape};
+//!
+//! let tape = Tape::record();
+//! engine.clone().with_tape(tape.clone()).detect("https://example.com").await?;
+//! tape.session().save("session.json")?;
+//!
+//! let replay = Tape::replay(Session::load("session.json")?);
+//! engine.with_tape(replay).detect("https://example.com").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Requests are matched by method, URL and body, in the order they were recorded; a
+//! request whose query changed (a cache buster) falls back to one for the same path.
+//! Raw-socket and timing techniques bypass the HTTP client and cannot be replayed.
+
+use crate::error::{ScanError, WafDetectorError};
+use crate::http::{BodyTruncation, HttpResponse};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Format version written to new sessions
+pub const SESSION_VERSION: u32 = 1;
+
+/// Everything a recorded scan sent and received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    /// waf-detector version that recorded the session
+    pub tool_version: String,
+    pub recorded_at: DateTime<Utc>,
+    /// URLs scanned, in the order the scans started
+    #[serde(default)]
+    pub targets: Vec<String>,
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            version: SESSION_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            recorded_at: Utc::now(),
+            targets: Vec::new(),
+            interactions: Vec::new(),
+        }
+    }
+}
+
+impl Session {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read session '{}': {}", path.display(), e))?;
+        let session: Session = serde_json::from_str(&json).map_err(|e| anyhow!("Invalid session '{}': {}", path.display(), e))?;
+        if session.version > SESSION_VERSION {
+            return Err(anyhow!("Session '{}' has format version {}; this build reads up to {}", path.display(), session.version, SESSION_VERSION));
+        }
+        Ok(session)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).map_err(|e| anyhow!("Failed to write session '{}': {}", path.display(), e))
+    }
+
+    pub fn http_exchanges(&self) -> impl Iterator<Item = &HttpExchange> {
+        self.interactions.iter().filter_map(|interaction| match interaction {
+            Interaction::Http(exchange) => Some(exchange),
+            Interaction::Dns(_) => None,
+        })
+    }
+
+    pub fn dns_lookups(&self) -> impl Iterator<Item = &DnsLookup> {
+        self.interactions.iter().filter_map(|interaction| match interaction {
+            Interaction::Dns(lookup) => Some(lookup),
+            Interaction::Http(_) => None,
+        })
+    }
+}
+
+/// One recorded network interaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Interaction {
+    Http(HttpExchange),
+    Dns(DnsLookup),
+}
+
+/// A request and the response or error it got, after retries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpExchange {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub request_body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<RecordedResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanError>,
+}
+
+/// A response as the detection code saw it: headers in order, body decoded and limited
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Raw body bytes in hex, kept when they are not valid UTF-8 (favicons and other
+    /// binary assets); `body` then holds their lossy decoding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_hex: Option<String>,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<IpAddr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<BodyTruncation>,
+}
+
+impl RecordedResponse {
+    /// Keep the raw bytes of a body read with `HttpClient::get_bytes`
+    pub fn with_bytes(mut self, bytes: &[u8]) -> Self {
+        if std::str::from_utf8(bytes).is_err() {
+            self.body_hex = Some(bytes.iter().map(|byte| format!("{:02x}", byte)).collect());
+        }
+        self
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        let Some(hex) = &self.body_hex else {
+            return self.body.clone().into_bytes();
+        };
+        (0..hex.len() / 2).filter_map(|i| u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok()).collect()
+    }
+
+    pub fn response(&self) -> HttpResponse {
+        let mut header_order: Vec<String> = Vec::new();
+        for (name, _) in &self.headers {
+            if !header_order.contains(name) {
+                header_order.push(name.clone());
+            }
+        }
+        HttpResponse {
+            version: self.version.clone(),
+            remote_ip: self.remote_ip,
+            truncation: self.truncation.clone(),
+            header_order,
+            ..HttpResponse::from_parts(self.status, self.headers.iter().map(|(name, value)| (name, value)), self.body.clone(), self.url.clone())
+        }
+    }
+}
+
+impl From<&HttpResponse> for RecordedResponse {
+    fn from(response: &HttpResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers.iter().map(|(name, value)| (name.clone(), value.clone())).collect(),
+            body: response.body.clone(),
+            body_hex: None,
+            url: response.url.clone(),
+            version: response.version.clone(),
+            remote_ip: response.remote_ip,
+            truncation: response.truncation.clone(),
+        }
+    }
+}
+
+/// Record type of a DNS lookup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupKind {
+    /// A and AAAA addresses from the system resolver
+    Addresses,
+    Cname,
+}
+
+/// A DNS lookup and its answers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DnsLookup {
+    pub kind: LookupKind,
+    pub name: String,
+    pub answers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanError>,
+}
+
+#[derive(Debug)]
+struct TapeState {
+    replaying: bool,
+    session: Session,
+    /// Interactions already answered, by index (replay only)
+    used: Vec<bool>,
+}
+
+impl TapeState {
+    /// Index of the recorded exchange that answers a request: an unused exact match,
+    /// else an unused one for the same path, else the last exact match again
+    fn find_http(&self, method: &str, url: &str, body: &str) -> Option<usize> {
+        let path = |url: &str| url.split(['?', '#']).next().unwrap_or_default().to_string();
+        let candidates = |same_path: bool| {
+            self.session.interactions.iter().enumerate().filter_map(move |(index, interaction)| match interaction {
+                Interaction::Http(exchange) if exchange.method == method => {
+                    let matches = if same_path { path(&exchange.url) == path(url) } else { exchange.url == url && exchange.request_body == body };
+                    matches.then_some(index)
+                }
+                _ => None,
+            })
+        };
+        candidates(false)
+            .find(|index| !self.used[*index])
+            .or_else(|| candidates(true).find(|index| !self.used[*index]))
+            .or_else(|| candidates(false).next_back())
+    }
+
+    fn find_dns(&self, kind: LookupKind, name: &str) -> Option<usize> {
+        let candidates = self.session.interactions.iter().enumerate().filter_map(|(index, interaction)| match interaction {
+            Interaction::Dns(lookup) if lookup.kind == kind && lookup.name.eq_ignore_ascii_case(name) => Some(index),
+            _ => None,
+        });
+        let candidates: Vec<usize> = candidates.collect();
+        candidates.iter().copied().find(|index| !self.used[*index]).or(candidates.last().copied())
+    }
+}
+
+/// Recording or replaying session shared by every scan it is attached to
+#[derive(Debug, Clone)]
+pub struct Tape {
+    state: Arc<Mutex<TapeState>>,
+}
+
+tokio::task_local! {
+    /// Tape of the scan running on this task, installed by [`Tape::scope`]
+    static CURRENT: Tape;
+}
+
+/// The tape of the scan running on this task, if any
+pub(crate) fn current() -> Option<Tape> {
+    CURRENT.try_with(Tape::clone).ok()
+}
+
+impl Tape {
+    /// A tape that records into a new session
+    pub fn record() -> Self {
+        Self::new(false, Session::default())
+    }
+
+    /// A tape that answers from `session` and never touches the network
+    pub fn replay(session: Session) -> Self {
+        Self::new(true, session)
+    }
+
+    fn new(replaying: bool, session: Session) -> Self {
+        let used = vec![false; session.interactions.len()];
+        Self { state: Arc::new(Mutex::new(TapeState { replaying, session, used })) }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.lock().replaying
+    }
+
+    /// The session recorded (or being replayed) so far
+    pub fn session(&self) -> Session {
+        self.lock().session.clone()
+    }
+
+    /// Recorded interactions answered so far (replay only)
+    pub fn replayed(&self) -> usize {
+        self.lock().used.iter().filter(|used| **used).count()
+    }
+
+    /// Run `future` with every request and lookup it makes going through this tape
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        CURRENT.scope(self.clone(), future).await
+    }
+
+    /// Note a scanned URL in the session (recording only)
+    pub(crate) fn add_target(&self, url: &str) {
+        let mut state = self.lock();
+        if !state.replaying && !state.session.targets.iter().any(|target| target == url) {
+            state.session.targets.push(url.to_string());
+        }
+    }
+
+    /// The recorded outcome of `request` when replaying, `None` when recording
+    pub(crate) fn replay_http(&self, request: &reqwest::Request) -> Option<Result<RecordedResponse>> {
+        let mut state = self.lock();
+        if !state.replaying {
+            return None;
+        }
+        let (method, url, body) = request_key(request);
+        let Some(index) = state.find_http(&method, &url, &body) else {
+            return Some(Err(anyhow!("{} {} is not in the replayed session", method, url)));
+        };
+        state.used[index] = true;
+        let Interaction::Http(exchange) = &state.session.interactions[index] else {
+            unreachable!("find_http only returns HTTP exchanges");
+        };
+        Some(match (&exchange.response, &exchange.error) {
+            (Some(response), _) => Ok(response.clone()),
+            (None, Some(error)) => Err(replayed_error(error)),
+            (None, None) => Err(anyhow!("{} {} has no recorded outcome", method, url)),
+        })
+    }
+
+    /// Add an exchange to the session (recording only)
+    pub(crate) fn record_http(&self, request: &reqwest::Request, outcome: Result<RecordedResponse, &anyhow::Error>) {
+        let (method, url, request_body) = request_key(request);
+        let (response, error) = match outcome {
+            Ok(response) => (Some(response), None),
+            Err(error) => (None, Some(ScanError::from_error(error))),
+        };
+        self.push(Interaction::Http(HttpExchange { method, url, request_body, response, error }));
+    }
+
+    /// Answer a DNS lookup from the session when replaying; otherwise run `lookup` and
+    /// record what it returned
+    pub(crate) async fn dns<F>(&self, kind: LookupKind, name: &str, lookup: F) -> Result<Vec<String>>
+    where
+        F: Future<Output = Result<Vec<String>>>,
+    {
+        if self.is_replaying() {
+            let mut state = self.lock();
+            let Some(index) = state.find_dns(kind, name) else {
+                return Err(WafDetectorError::Dns { host: name.to_string(), message: "not in the replayed session".to_string() }.into());
+            };
+            state.used[index] = true;
+            let Interaction::Dns(recorded) = &state.session.interactions[index] else {
+                unreachable!("find_dns only returns DNS lookups");
+            };
+            return match &recorded.error {
+                Some(error) => Err(replayed_error(error)),
+                None => Ok(recorded.answers.clone()),
+            };
+        }
+
+        let result = lookup.await;
+        let (answers, error) = match &result {
+            Ok(answers) => (answers.clone(), None),
+            Err(error) => (Vec::new(), Some(ScanError::from_error(error))),
+        };
+        self.push(Interaction::Dns(DnsLookup { kind, name: name.to_string(), answers, error }));
+        result
+    }
+
+    fn push(&self, interaction: Interaction) {
+        let mut state = self.lock();
+        if !state.replaying {
+            state.session.interactions.push(interaction);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TapeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run `lookup` through the current tape, or directly when no scan is being recorded
+/// or replayed
+pub(crate) async fn dns<F>(kind: LookupKind, name: &str, lookup: F) -> Result<Vec<String>>
+where
+    F: Future<Output = Result<Vec<String>>>,
+{
+    match current() {
+        Some(tape) => tape.dns(kind, name, lookup).await,
+        None => lookup.await,
+    }
+}
+
+fn request_key(request: &reqwest::Request) -> (String, String, String) {
+    let body = request.body().and_then(|body| body.as_bytes()).map(|bytes| String::from_utf8_lossy(bytes).into_owned()).unwrap_or_default();
+    (request.method().to_string(), request.url().to_string(), body)
+}
+
+/// A recorded failure as an error of the same class
+fn replayed_error(error: &ScanError) -> anyhow::Error {
+    WafDetectorError::Replayed { class: error.class, message: error.message.clone() }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorClass;
+
+    fn request(method: &str, url: &str) -> reqwest::Request {
+        reqwest::Client::new().request(method.parse().unwrap(), url).build().unwrap()
+    }
+
+    fn response(body: &str) -> RecordedResponse {
+        RecordedResponse::from(&HttpResponse::from_parts(200, [("server", "cloudflare"), ("set-cookie", "a=1"), ("set-cookie", "b=2")], body, "https://example.com/"))
+    }
+
+    #[test]
+    fn test_replay_answers_in_recorded_order() {
+        let tape = Tape::record();
+        tape.record_http(&request("GET", "https://example.com/"), Ok(response("first")));
+        tape.record_http(&request("GET", "https://example.com/"), Ok(response("second")));
+        tape.record_http(&request("GET", "https://example.com/probe?cb=1"), Ok(response("probe")));
+        let failure = anyhow::Error::from(WafDetectorError::Timeout { url: "https://example.com/slow".to_string() });
+        tape.record_http(&request("GET", "https://example.com/slow"), Err(&failure));
+        assert_eq!(tape.session().interactions.len(), 4);
+
+        let replay = Tape::replay(tape.session());
+        let body = |url: &str| replay.replay_http(&request("GET", url)).unwrap().map(|response| response.body);
+        assert_eq!(body("https://example.com/").unwrap(), "first");
+        assert_eq!(body("https://example.com/").unwrap(), "second");
+        // Exhausted exact matches answer with the last one again; a new query reuses the path
+        assert_eq!(body("https://example.com/").unwrap(), "second");
+        assert_eq!(body("https://example.com/probe?cb=2").unwrap(), "probe");
+        assert_eq!(ErrorClass::of(&body("https://example.com/slow").unwrap_err()), ErrorClass::Timeout);
+        assert!(body("https://example.com/missing").is_err());
+        assert!(replay.replay_http(&request("POST", "https://example.com/")).unwrap().is_err());
+        assert_eq!(replay.replayed(), 4);
+    }
+
+    #[test]
+    fn test_recorded_response_round_trip() {
+        let recorded = response("<html>");
+        let restored = recorded.response();
+        assert_eq!(restored.headers.get_all("set-cookie").count(), 2);
+        assert_eq!(restored.header_order, ["server", "set-cookie"]);
+        assert_eq!(restored.body, "<html>");
+
+        let binary = RecordedResponse::from(&HttpResponse::default()).with_bytes(&[0x00, 0xff, 0x10]);
+        assert_eq!(binary.body_hex.as_deref(), Some("00ff10"));
+        assert_eq!(binary.bytes(), [0x00, 0xff, 0x10]);
+        assert_eq!(response("text").with_bytes(b"text").body_hex, None);
+    }
+
+    #[tokio::test]
+    async fn test_dns_lookups_are_recorded_and_replayed() {
+        let tape = Tape::record();
+        let answers = tape.scope(dns(LookupKind::Cname, "www.example.com", async { Ok(vec!["example.edgekey.net".to_string()]) })).await;
+        assert_eq!(answers.unwrap(), ["example.edgekey.net"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        tape.session().save(&path).unwrap();
+        let replay = Tape::replay(Session::load(&path).unwrap());
+        let replayed = replay.scope(dns(LookupKind::Cname, "WWW.example.com", async { panic!("replay must not resolve") })).await;
+        assert_eq!(replayed.unwrap(), ["example.edgekey.net"]);
+        assert!(replay.scope(dns(LookupKind::Addresses, "www.example.com", async { Ok(Vec::new()) })).await.is_err());
+
+        // Without a tape, lookups run as usual
+        assert_eq!(dns(LookupKind::Cname, "x", async { Ok(vec!["y".to_string()]) }).await.unwrap(), ["y"]);
+    }
+}
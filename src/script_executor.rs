@@ -1,103 +1,219 @@
-use std::process::{Command, Stdio};
-use std::path::Path;
-use serde::{Deserialize, Serialize};
-use anyhow::{Result, anyhow};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ScriptResult {
-    pub waf_detected: bool,
-    pub waf_name: String,
-    pub cdn_detected: bool,
-    pub cdn_name: String,
-    pub cloud_provider: String,
-    pub effectiveness_score: f64,
-    pub total_tests: u32,
-    pub blocked_tests: u32,
-    pub allowed_tests: u32,
-    pub error_tests: u32,
-    pub test_results: Vec<PayloadResult>,
-    pub recommendations: Vec<String>,
-    pub execution_time_ms: u64,
+//! Runs the external `scripts/waf-smoke-test.sh` for combined scans
+//!
+//! Deprecated: combined scans now test effectiveness natively (see
+//! [`crate::payload::combined`]); the script is only used with `--legacy-script`.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use anyhow::Result;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use crate::payload::combined::{EffectivenessError, EffectivenessResult, PayloadResult};
+
+/// Script run by [`ScriptExecutor::new`]
+pub const DEFAULT_SCRIPT: &str = "scripts/waf-smoke-test.sh";
+
+/// Characters of stdout kept in [`ScriptError::UnparseableOutput`]
+const STDOUT_EXCERPT_CHARS: usize = 200;
+
+/// Resource limits and isolation applied to every script run
+#[derive(Debug, Clone)]
+pub struct ScriptSandbox {
+    /// The script is killed when it runs longer than this
+    pub timeout: Duration,
+    /// Cap on stdout and on stderr, each; the script is killed when it writes more
+    pub max_output_bytes: usize,
+    /// Environment variables passed through; everything else is removed
+    pub env_allowlist: Vec<String>,
+    /// Run each script in a fresh directory under this one (also its `HOME` and
+    /// `TMPDIR`), removed afterwards
+    pub working_dir: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PayloadResult {
-    pub category: String,
-    pub payload: String,
-    pub status: String, // "BLOCKED", "ALLOWED", "ERROR", "CHECK"
-    pub response_code: u16,
-    pub response_time_ms: u64,
-    pub detection_method: String,
+impl Default for ScriptSandbox {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(120),
+            max_output_bytes: 1024 * 1024,
+            env_allowlist: ["PATH", "HOME", "LANG", "LC_ALL", "TZ"].map(String::from).to_vec(),
+            working_dir: None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CombinedResult {
-    pub url: String,
-    pub detection_result: crate::DetectionResult,
-    pub effectiveness_result: Option<ScriptResult>,
-    pub analysis_summary: String,
-    pub recommendations: Vec<String>,
-    pub total_time_ms: u64,
+/// Why a script run failed
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("WAF testing script not found at: {0}")]
+    NotFound(String),
+
+    #[error("Script is not executable: {0}")]
+    NotExecutable(String),
+
+    #[error("Failed to prepare working directory '{path}': {message}")]
+    WorkingDir { path: String, message: String },
+
+    #[error("Failed to execute script: {0}")]
+    Spawn(String),
+
+    #[error("Script timed out after {}s", .0.as_secs_f64())]
+    Timeout(Duration),
+
+    #[error("Script wrote more than {limit} bytes to {stream}")]
+    OutputTooLarge { stream: &'static str, limit: usize },
+
+    #[error("Script exited with {}: {stderr}", .code.map_or("a signal".to_string(), |code| format!("status {}", code)))]
+    Failed { code: Option<i32>, stderr: String },
+
+    #[error("Script output has no test results: '{stdout_excerpt}'")]
+    UnparseableOutput { stdout_excerpt: String },
+}
+
+impl ScriptError {
+    /// Stable name of the failure, e.g. `timeout`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "not_found",
+            Self::NotExecutable(_) => "not_executable",
+            Self::WorkingDir { .. } => "working_dir",
+            Self::Spawn(_) => "spawn",
+            Self::Timeout(_) => "timeout",
+            Self::OutputTooLarge { .. } => "output_too_large",
+            Self::Failed { .. } => "failed",
+            Self::UnparseableOutput { .. } => "unparseable_output",
+        }
+    }
+}
+
+impl From<&ScriptError> for EffectivenessError {
+    fn from(error: &ScriptError) -> Self {
+        Self { kind: error.kind().to_string(), message: error.to_string() }
+    }
+}
+
+/// What the script reports; same shape as the native result
+pub type ScriptResult = EffectivenessResult;
+
+/// Output of a script that exited successfully
+#[derive(Debug)]
+struct ScriptOutput {
+    stdout: String,
+    execution_time_ms: u64,
 }
 
 pub struct ScriptExecutor {
-    script_path: String,
+    script_path: PathBuf,
+    sandbox: ScriptSandbox,
 }
 
 impl ScriptExecutor {
     pub fn new() -> Result<Self> {
-        let script_path = "scripts/waf-smoke-test.sh";
-        
-        // Check if script exists
-        if !Path::new(script_path).exists() {
-            return Err(anyhow!("WAF testing script not found at: {}", script_path));
-        }
-        
-        // Check if script is executable
+        Ok(Self::from_path(DEFAULT_SCRIPT)?)
+    }
+
+    /// Executor for the script at `path`, with the default [`ScriptSandbox`]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ScriptError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        // Absolute, so the script is still found from a sandbox working directory
+        let script_path = path.canonicalize().map_err(|_| ScriptError::NotFound(display.clone()))?;
+
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            let metadata = std::fs::metadata(script_path)?;
-            let permissions = metadata.permissions();
-            if permissions.mode() & 0o111 == 0 {
-                return Err(anyhow!("Script is not executable: {}", script_path));
+            let metadata = std::fs::metadata(&script_path).map_err(|_| ScriptError::NotFound(display.clone()))?;
+            if metadata.permissions().mode() & 0o111 == 0 {
+                return Err(ScriptError::NotExecutable(display));
             }
         }
-        
-        Ok(Self {
-            script_path: script_path.to_string(),
-        })
+
+        Ok(Self { script_path, sandbox: ScriptSandbox::default() })
+    }
+
+    pub fn with_sandbox(mut self, sandbox: ScriptSandbox) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    pub fn sandbox(&self) -> &ScriptSandbox {
+        &self.sandbox
     }
-    
-    pub async fn execute_test(&self, url: &str) -> Result<ScriptResult> {
+
+    pub async fn execute_test(&self, url: &str) -> Result<ScriptResult, ScriptError> {
+        let output = self.run(url).await?;
+        self.parse_script_output(&output.stdout, output.execution_time_ms)
+    }
+
+    /// Run the script against `url` within the sandbox limits
+    async fn run(&self, url: &str) -> Result<ScriptOutput, ScriptError> {
         let start_time = std::time::Instant::now();
-        
-        // Execute the bash script
-        let output = Command::new("bash")
+
+        // Per-run directory: the sandbox working directory, or just a home for the output file
+        let run_dir = match &self.sandbox.working_dir {
+            Some(dir) => std::fs::create_dir_all(dir).and_then(|_| tempfile::Builder::new().prefix("run-").tempdir_in(dir)),
+            None => tempfile::tempdir(),
+        }
+        .map_err(|e| ScriptError::WorkingDir {
+            path: self.sandbox.working_dir.as_deref().unwrap_or(&std::env::temp_dir()).display().to_string(),
+            message: e.to_string(),
+        })?;
+
+        let mut command = Command::new("bash");
+        command
             .arg(&self.script_path)
             .arg(url)
             .arg("-o")
-            .arg("/tmp/waf_test_output.json") // Use JSON output for parsing
+            .arg(run_dir.path().join("waf_test_output.json"))
+            .env_clear()
+            .envs(self.sandbox.env_allowlist.iter().filter_map(|name| Some((name, std::env::var_os(name)?))))
+            .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| anyhow!("Failed to execute script: {}", e))?;
-        
-        let execution_time = start_time.elapsed().as_millis() as u64;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Script execution failed: {}", stderr));
+            .kill_on_drop(true);
+        if self.sandbox.working_dir.is_some() {
+            command.current_dir(run_dir.path()).env("HOME", run_dir.path()).env("TMPDIR", run_dir.path());
         }
-        
-        // Parse the script output
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        self.parse_script_output(&stdout, execution_time)
+
+        let mut child = command.spawn().map_err(|e| ScriptError::Spawn(e.to_string()))?;
+        let (stdout, stderr) = (child.stdout.take(), child.stderr.take());
+        let limit = self.sandbox.max_output_bytes;
+        let finished = tokio::time::timeout(self.sandbox.timeout, async {
+            let (stdout, stderr) = tokio::try_join!(read_capped(stdout, "stdout", limit), read_capped(stderr, "stderr", limit))?;
+            let status = child.wait().await.map_err(|e| ScriptError::Spawn(e.to_string()))?;
+            Ok::<_, ScriptError>((status, stdout, stderr))
+        })
+        .await;
+
+        let (status, stdout, stderr) = match finished {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => {
+                let _ = child.kill().await;
+                return Err(e);
+            }
+            Err(_) => {
+                let _ = child.kill().await;
+                return Err(ScriptError::Timeout(self.sandbox.timeout));
+            }
+        };
+
+        if !status.success() {
+            return Err(ScriptError::Failed {
+                code: status.code(),
+                stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+            });
+        }
+
+        Ok(ScriptOutput {
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+        })
     }
-    
-    fn parse_script_output(&self, output: &str, execution_time_ms: u64) -> Result<ScriptResult> {
+
+    fn parse_script_output(&self, output: &str, execution_time_ms: u64) -> Result<ScriptResult, ScriptError> {
         // Parse the actual output from the script
-        println!("Parsing script output: {}", output);
+        tracing::debug!(output, "parsing script output");
         
         let mut test_results = Vec::new();
         let mut blocked_count = 0;
@@ -172,52 +288,20 @@ impl ScriptExecutor {
             }
         }
         
-        // If we couldn't parse any results, fall back to mock data
         if test_results.is_empty() {
-            println!("Warning: Could not parse test results from script output, using mock data");
-            
-            // Generate mock data
-            for (i, category) in categories.iter().enumerate() {
-                let status = if i % 3 == 0 { "BLOCKED" } else if i % 3 == 1 { "ALLOWED" } else { "ERROR" };
-                
-                match status {
-                    "BLOCKED" => blocked_count += 1,
-                    "ALLOWED" => allowed_count += 1,
-                    "ERROR" => error_count += 1,
-                    _ => {}
-                }
-                
-                test_results.push(PayloadResult {
-                    category: category.to_string(),
-                    payload: format!("test-payload-{}", i),
-                    status: status.to_string(),
-                    response_code: if status == "BLOCKED" { 403 } else { 200 },
-                    response_time_ms: 150 + (i * 50) as u64,
-                    detection_method: "HTTP Status Code".to_string(),
-                });
-            }
+            return Err(ScriptError::UnparseableOutput {
+                stdout_excerpt: output.trim().chars().take(STDOUT_EXCERPT_CHARS).collect(),
+            });
         }
         
         let total_tests = test_results.len() as u32;
-        let effectiveness_score = if total_tests > 0 {
-            (blocked_count as f64 / total_tests as f64) * 100.0
-        } else {
-            0.0
-        };
         
-        // Extract effectiveness score from output if available
-        for line in &lines {
-            if line.contains("Effectiveness:") {
-                if let Some(score_str) = line.split(':').nth(1) {
-                    if let Some(score_str) = score_str.trim().strip_suffix('%') {
-                        if let Ok(_score) = score_str.parse::<f64>() {
-                            // Use the score from the script output
-                            // effectiveness_score = score;
-                        }
-                    }
-                }
-            }
-        }
+        // The score the script reports wins over one computed from the parsed lines
+        let effectiveness_score = lines
+            .iter()
+            .filter_map(|line| line.split_once("Effectiveness:"))
+            .find_map(|(_, score)| score.trim().strip_suffix('%')?.trim().parse::<f64>().ok())
+            .unwrap_or((blocked_count as f64 / total_tests as f64) * 100.0);
         
         Ok(ScriptResult {
             waf_detected,
@@ -238,82 +322,124 @@ impl ScriptExecutor {
             execution_time_ms,
         })
     }
-    
-    pub fn combine_results(
-        &self,
-        detection_result: crate::DetectionResult,
-        effectiveness_result: Option<ScriptResult>,
-        total_time_ms: u64,
-    ) -> CombinedResult {
-        let mut analysis_summary = String::new();
-        let mut recommendations = Vec::new();
-        
-        // Generate analysis summary
-        if let Some(waf) = &detection_result.detected_waf {
-            analysis_summary.push_str(&format!("WAF Detected: {} ({:.1}% confidence)\n", 
-                waf.name, waf.confidence * 100.0));
-        } else {
-            analysis_summary.push_str("No WAF detected\n");
-        }
-        
-        if let Some(cdn) = &detection_result.detected_cdn {
-            analysis_summary.push_str(&format!("CDN Detected: {} ({:.1}% confidence)\n", 
-                cdn.name, cdn.confidence * 100.0));
-        }
-        
-        if let Some(effectiveness) = &effectiveness_result {
-            analysis_summary.push_str(&format!(
-                "Effectiveness Testing: {:.1}% blocked ({}/{} tests)\n",
-                effectiveness.effectiveness_score,
-                effectiveness.blocked_tests,
-                effectiveness.total_tests
-            ));
-            
-            // Add effectiveness-based recommendations
-            if effectiveness.effectiveness_score < 50.0 {
-                recommendations.push("⚠️ Low WAF effectiveness - many payloads bypassed".to_string());
-                recommendations.push("Consider reviewing and tuning WAF rules".to_string());
-            } else if effectiveness.effectiveness_score > 90.0 {
-                recommendations.push("✅ High WAF effectiveness - good security posture".to_string());
-            }
-            
-            recommendations.extend(effectiveness.recommendations.clone());
-        }
-        
-        // Add provider-specific recommendations
-        if let Some(waf) = &detection_result.detected_waf {
-            match waf.name.as_str() {
-                "CloudFlare" => {
-                    recommendations.push("🔒 CloudFlare detected - consider enabling additional security features".to_string());
-                }
-                "AWS" => {
-                    recommendations.push("☁️ AWS WAF detected - review CloudWatch metrics and rules".to_string());
-                }
-                "Akamai" => {
-                    recommendations.push("🛡️ Akamai detected - consider Bot Manager for advanced protection".to_string());
-                }
-                _ => {}
-            }
-        }
-        
-        CombinedResult {
-            url: detection_result.url.clone(),
-            detection_result,
-            effectiveness_result,
-            analysis_summary,
-            recommendations,
-            total_time_ms,
-        }
-    }
 }
 
 impl Default for ScriptExecutor {
     fn default() -> Self {
         Self::new().unwrap_or_else(|e| {
-            println!("Warning: Failed to initialize script executor: {}", e);
+            tracing::warn!(error = %e, "failed to initialize script executor");
             Self {
-                script_path: "scripts/waf-smoke-test.sh".to_string(),
+                script_path: PathBuf::from(DEFAULT_SCRIPT),
+                sandbox: ScriptSandbox::default(),
             }
         })
     }
-} 
\ No newline at end of file
+}
+
+/// Read a stream to the end, failing as soon as it exceeds `limit` bytes
+async fn read_capped(reader: Option<impl AsyncRead + Unpin>, stream: &'static str, limit: usize) -> Result<Vec<u8>, ScriptError> {
+    let mut buf = Vec::new();
+    if let Some(reader) = reader {
+        reader
+            .take(limit as u64 + 1)
+            .read_to_end(&mut buf)
+            .await
+            .map_err(|e| ScriptError::Spawn(e.to_string()))?;
+    }
+    if buf.len() > limit {
+        return Err(ScriptError::OutputTooLarge { stream, limit });
+    }
+    Ok(buf)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn script(dir: &Path, body: &str) -> ScriptExecutor {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("test.sh");
+        std::fs::write(&path, format!("#!/bin/bash\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ScriptExecutor::from_path(&path).unwrap()
+    }
+
+    #[test]
+    fn test_script_must_exist_and_be_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.sh");
+        assert!(matches!(ScriptExecutor::from_path(&path), Err(ScriptError::NotFound(_))));
+        std::fs::write(&path, "exit 0").unwrap();
+        assert!(matches!(ScriptExecutor::from_path(&path), Err(ScriptError::NotExecutable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_output_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let sleeper = script(dir.path(), "sleep 10").with_sandbox(ScriptSandbox {
+            timeout: Duration::from_millis(200),
+            ..ScriptSandbox::default()
+        });
+        let started = std::time::Instant::now();
+        let error = sleeper.run("https://example.com").await.unwrap_err();
+        assert_eq!(error.kind(), "timeout");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let chatty = script(dir.path(), "head -c 100000 /dev/zero").with_sandbox(ScriptSandbox {
+            max_output_bytes: 1000,
+            ..ScriptSandbox::default()
+        });
+        let error = chatty.run("https://example.com").await.unwrap_err();
+        assert!(matches!(error, ScriptError::OutputTooLarge { stream: "stdout", limit: 1000 }));
+    }
+
+    #[tokio::test]
+    async fn test_failure_reports_status_and_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = script(dir.path(), "echo boom >&2; exit 3").run("https://example.com").await.unwrap_err();
+        assert!(matches!(&error, ScriptError::Failed { code: Some(3), stderr } if stderr == "boom"));
+        assert_eq!(error.to_string(), "Script exited with status 3: boom");
+    }
+
+    #[tokio::test]
+    async fn test_output_without_results_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = script(dir.path(), "echo 'curl: (6) Could not resolve host'").execute_test("https://example.com").await.unwrap_err();
+        assert!(matches!(&error, ScriptError::UnparseableOutput { stdout_excerpt } if stdout_excerpt == "curl: (6) Could not resolve host"));
+        assert_eq!(error.kind(), "unparseable_output");
+    }
+
+    #[tokio::test]
+    async fn test_reported_effectiveness_is_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor = script(
+            dir.path(),
+            "echo 'Testing XSS... BLOCKED (403)'; echo 'Testing RCE... ALLOWED (200)'; echo 'Effectiveness: 75.5%'",
+        );
+        let result = executor.execute_test("https://example.com").await.unwrap();
+        assert_eq!((result.total_tests, result.blocked_tests, result.allowed_tests), (2, 1, 1));
+        assert_eq!(result.effectiveness_score, 75.5);
+
+        let executor = script(dir.path(), "echo 'Testing XSS... BLOCKED (403)'; echo 'Testing RCE... ALLOWED (200)'");
+        assert_eq!(executor.execute_test("https://example.com").await.unwrap().effectiveness_score, 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_environment_is_scrubbed_and_working_dir_isolated() {
+        std::env::set_var("WAF_DETECTOR_SCRIPT_TEST_SECRET", "hunter2");
+        let dir = tempfile::tempdir().unwrap();
+        let sandbox_dir = dir.path().join("sandbox");
+        let executor = script(dir.path(), "echo \"secret=$WAF_DETECTOR_SCRIPT_TEST_SECRET\"; echo \"cwd=$(pwd)\"; echo \"home=$HOME\"")
+            .with_sandbox(ScriptSandbox { working_dir: Some(sandbox_dir.clone()), ..ScriptSandbox::default() });
+
+        let stdout = executor.run("https://example.com").await.unwrap().stdout;
+        let sandbox_dir = sandbox_dir.canonicalize().unwrap();
+        let value = |key: &str| stdout.lines().find_map(|line| line.strip_prefix(key)).unwrap().to_string();
+        assert_eq!(value("secret="), "");
+        let cwd = PathBuf::from(value("cwd="));
+        assert_eq!(cwd.parent(), Some(sandbox_dir.as_path()));
+        assert_eq!(value("home="), cwd.display().to_string());
+        // The per-run directory is removed afterwards
+        assert!(!cwd.exists());
+    }
+}
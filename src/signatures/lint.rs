@@ -0,0 +1,267 @@
+//! Checks for hand-written signature files (`waf-detect signatures test`)
+//!
+//! Every pattern is compiled on its own so one mistake does not hide the others, timed
+//! against large adversarial inputs, and run against a bundled set of sample responses
+//! to show what it matches, and what it should not.
+
+use super::{compile, ProviderSignatures, Rule, SignatureBundle};
+use crate::http::HttpResponse;
+use crate::providers::signature_based::SignatureProvider;
+use crate::providers::default_providers;
+use crate::ProviderType;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Time one rule may take over all stress inputs and samples
+pub const DEFAULT_BUDGET: Duration = Duration::from_millis(200);
+
+macro_rules! samples {
+    ($(($name:expr, $provider:expr, $path:expr)),* $(,)?) => {
+        &[$(Sample { name: $name, provider: $provider, raw: include_str!(concat!("samples/", $path)) }),*]
+    };
+}
+
+/// A captured response with the provider that served it (`None` for an unprotected origin)
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub name: &'static str,
+    pub provider: Option<&'static str>,
+    pub raw: &'static str,
+}
+
+pub const SAMPLES: &[Sample] = samples![
+    ("cloudflare-block", Some("CloudFlare"), "cloudflare_block.http"),
+    ("akamai-denied", Some("Akamai"), "akamai_denied.http"),
+    ("imperva-incident", Some("Imperva"), "imperva_incident.http"),
+    ("cloudfront-blocked", Some("AWS"), "cloudfront_blocked.http"),
+    ("fastly-cached", Some("Fastly"), "fastly_cached.http"),
+    ("origin-nginx", None, "origin_nginx.http"),
+    ("origin-apache-error", None, "origin_apache_error.http"),
+];
+
+impl Sample {
+    pub fn response(&self) -> HttpResponse {
+        crate::capture::parse_raw_response(self.raw.as_bytes(), "https://www.example.com/").expect("bundled samples are valid responses")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One finding about a rule, pattern or provider
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    /// e.g. `Imperva/imperva-iinfo` or `dns_patterns[2]`
+    pub location: String,
+    pub message: String,
+}
+
+/// How one provider rule fared
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleReport {
+    pub provider: String,
+    pub id: String,
+    pub target: String,
+    pub compiled: bool,
+    /// Time taken over the stress inputs and samples
+    pub duration_us: u64,
+    /// Samples the rule matched
+    pub matched: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport {
+    pub version: String,
+    pub rules: Vec<RuleReport>,
+    pub issues: Vec<Issue>,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning).count()
+    }
+}
+
+/// Parse `data` as a signature bundle without stopping at the first bad pattern
+pub fn lint_file(data: &[u8], budget: Duration) -> Result<LintReport> {
+    let bundle: SignatureBundle = serde_json::from_slice(data).map_err(|e| anyhow!("Invalid signature bundle: {}", e))?;
+    Ok(lint(&bundle, budget))
+}
+
+pub fn lint(bundle: &SignatureBundle, budget: Duration) -> LintReport {
+    let mut issues = Vec::new();
+    let mut issue = |severity, location: String, message: String| issues.push(Issue { severity, location, message });
+    if bundle.version.trim().is_empty() {
+        issue(Severity::Error, "version".to_string(), "empty version".to_string());
+    }
+
+    let builtin: Vec<String> = default_providers().iter().map(|provider| provider.name().to_lowercase()).collect();
+    let samples: Vec<(Sample, HttpResponse)> = SAMPLES.iter().map(|sample| (*sample, sample.response())).collect();
+    let haystacks = haystacks(&samples);
+    let mut rules = Vec::new();
+    let mut ids = HashSet::new();
+
+    for provider in &bundle.providers {
+        let is_builtin = builtin.contains(&provider.name.to_lowercase());
+        if !is_builtin && provider.provider_type.is_none() {
+            issue(Severity::Error, provider.name.clone(), "not a built-in provider, so it needs a \"type\"".to_string());
+        }
+        for rule in &provider.rules {
+            let location = format!("{}/{}", provider.name, rule.id);
+            if !ids.insert(rule.id.as_str()) {
+                issue(Severity::Warning, location.clone(), "duplicate rule id".to_string());
+            }
+            let mut report = RuleReport {
+                provider: provider.name.clone(),
+                id: rule.id.clone(),
+                target: rule.target.to_string(),
+                compiled: false,
+                duration_us: 0,
+                matched: Vec::new(),
+            };
+            let pattern = match compile(&rule.pattern, rule.confidence) {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    issue(Severity::Error, location, e.to_string());
+                    rules.push(report);
+                    continue;
+                }
+            };
+            report.compiled = true;
+
+            match time_pattern(pattern, haystacks.clone(), budget) {
+                Some(elapsed) => report.duration_us = elapsed.as_micros() as u64,
+                None => {
+                    issue(Severity::Error, location, format!("took longer than {}ms on the stress inputs", budget.as_millis()));
+                    report.duration_us = budget.as_micros() as u64;
+                    rules.push(report);
+                    continue;
+                }
+            }
+
+            report.matched = matching_samples(provider, rule, &samples);
+            for name in &report.matched {
+                let (sample, _) = samples.iter().find(|(sample, _)| sample.name == name).expect("matched a bundled sample");
+                if !sample.provider.is_some_and(|owner| owner.eq_ignore_ascii_case(&provider.name)) {
+                    issue(Severity::Warning, location.clone(), format!("matches the {} sample, which {} did not serve", name, provider.name));
+                }
+            }
+            rules.push(report);
+        }
+    }
+
+    for (index, pattern) in bundle.dns_patterns.iter().enumerate() {
+        if let Err(e) = compile(&pattern.pattern, pattern.confidence) {
+            issue(Severity::Error, format!("dns_patterns[{}]", index), e.to_string());
+        }
+    }
+    for (index, page) in bundle.block_pages.iter().enumerate() {
+        if !(0.0..=1.0).contains(&page.confidence) {
+            issue(Severity::Error, format!("block_pages[{}]", index), format!("confidence {} is not between 0 and 1", page.confidence));
+        }
+    }
+
+    LintReport { version: bundle.version.clone(), rules, issues }
+}
+
+/// Inputs that blow up backtracking engines, plus every header value and body of the samples
+fn haystacks(samples: &[(Sample, HttpResponse)]) -> Vec<String> {
+    let mut haystacks = vec![
+        "a".repeat(65_536) + "!",
+        "<div>".repeat(16_384),
+        "0123456789-".repeat(8_192),
+        " ".repeat(65_536) + "x",
+    ];
+    for (_, response) in samples {
+        haystacks.extend(response.headers.iter().map(|(_, value)| value.clone()));
+        haystacks.push(response.body.clone());
+    }
+    haystacks
+}
+
+/// Time `pattern` over every haystack on a worker thread; `None` once `budget` runs out
+fn time_pattern(pattern: Regex, haystacks: Vec<String>, budget: Duration) -> Option<Duration> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let start = Instant::now();
+        for haystack in &haystacks {
+            let _ = pattern.find(haystack);
+        }
+        let _ = sender.send(start.elapsed());
+    });
+    receiver.recv_timeout(budget).ok()
+}
+
+/// Names of the samples `rule` matches, using the same matching as detection
+fn matching_samples(provider: &ProviderSignatures, rule: &Rule, samples: &[(Sample, HttpResponse)]) -> Vec<String> {
+    let single = ProviderSignatures {
+        name: provider.name.clone(),
+        provider_type: Some(provider.provider_type.clone().unwrap_or(ProviderType::WAF)),
+        description: None,
+        rules: vec![rule.clone()],
+    };
+    let Ok(provider) = SignatureProvider::new(&single, "lint", None) else {
+        return Vec::new();
+    };
+    samples
+        .iter()
+        .filter(|(_, response)| !provider.match_rules(response).is_empty())
+        .map(|(sample, _)| sample.name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_parse() {
+        for sample in SAMPLES {
+            assert!(sample.response().status >= 200, "{}", sample.name);
+        }
+    }
+
+    #[test]
+    fn test_lint_reports_every_problem() {
+        let report = lint_file(br#"{"version": "custom-1", "published": "2026-10-01T00:00:00Z", "providers": [
+            {"name": "Imperva", "type": "WAF", "rules": [
+                {"id": "imperva-iinfo", "target": {"header": "x-iinfo"}, "pattern": "^\\d+-\\d+", "confidence": 0.9},
+                {"id": "imperva-broken", "target": "body", "pattern": "(unclosed", "confidence": 0.9},
+                {"id": "imperva-blocked", "target": "body", "pattern": "(?i)blocked", "confidence": 0.6}
+            ]},
+            {"name": "Acme", "rules": [{"id": "imperva-iinfo", "target": "status", "pattern": "^403$", "confidence": 1.2}]}
+        ], "dns_patterns": [{"provider": "Acme", "pattern": "[", "confidence": 0.9}]}"#, DEFAULT_BUDGET)
+        .unwrap();
+
+        assert_eq!(report.rules[0].matched, ["imperva-incident"]);
+        assert!(!report.rules[1].compiled);
+        let locations: Vec<(&str, Severity)> = report.issues.iter().map(|issue| (issue.location.as_str(), issue.severity)).collect();
+        assert!(locations.contains(&("Imperva/imperva-broken", Severity::Error)));
+        assert!(locations.contains(&("Imperva/imperva-blocked", Severity::Warning)), "{:?}", report.issues);
+        assert!(locations.contains(&("Acme", Severity::Error)));
+        assert!(locations.contains(&("Acme/imperva-iinfo", Severity::Warning)));
+        assert!(locations.contains(&("Acme/imperva-iinfo", Severity::Error)));
+        assert!(locations.contains(&("dns_patterns[0]", Severity::Error)));
+        assert_eq!(report.errors(), 4);
+    }
+
+    #[test]
+    fn test_budget() {
+        let pattern = compile(r"(\w+\s?)+$", 0.5).unwrap();
+        assert!(time_pattern(pattern.clone(), vec!["a".repeat(1_000)], Duration::from_secs(5)).is_some());
+        assert!(time_pattern(pattern, vec!["a ".repeat(1 << 20)], Duration::ZERO).is_none());
+    }
+}
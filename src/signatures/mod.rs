@@ -0,0 +1,335 @@
+//! Signature bundles: detection data shipped apart from binary releases
+//!
+//! A bundle is one JSON document with provider header/body/cookie rules, CNAME patterns,
+//! block pages and provider IP ranges:
+//!
+//! ```json
+//! {
+//!   "version": "2026.10.1",
+//!   "published": "2026-10-01T00:00:00Z",
+//!   "providers": [{"name": "Imperva", "type": "WAF", "rules": [
+//!     {"id": "imperva-incident-header", "target": {"header": "x-iinfo"}, "pattern": ".+", "confidence": 0.9}
+//!   ]}],
+//!   "dns_patterns": [{"provider": "Imperva", "pattern": "\\.incapdns\\.net$", "confidence": 0.95}],
+//!   "block_pages": [{"vendor": "Imperva", "name": "incident", "confidence": 0.9, "body": "<html>..."}],
+//!   "ip_ranges": [{"provider": "Imperva", "cidr": "45.60.0.0/16"}]
+//! }
+//! ```
+//!
+//! `waf-detect update-signatures` downloads a bundle and its detached Ed25519 signature,
+//! verifies it and installs it into the data directory (see [`data_dir`]), where every
+//! later run picks it up. Rules for a built-in provider extend it; rules for an unknown
+//! name add a provider. `waf-detect signatures test` checks a bundle before it is
+//! published (see [`lint`]).
+
+pub mod lint;
+#[cfg(feature = "signatures")]
+pub mod update;
+
+use crate::blockpage::CustomPage;
+use crate::ProviderType;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name of the installed bundle inside the data directory
+pub const BUNDLE_FILE: &str = "signatures.json";
+/// Detached signature installed next to the bundle
+pub const SIGNATURE_FILE: &str = "signatures.json.sig";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureBundle {
+    pub version: String,
+    pub published: DateTime<Utc>,
+    #[serde(default)]
+    pub providers: Vec<ProviderSignatures>,
+    #[serde(default)]
+    pub dns_patterns: Vec<DnsSignature>,
+    #[serde(default)]
+    pub block_pages: Vec<CustomPage>,
+    #[serde(default)]
+    pub ip_ranges: Vec<IpRangeSignature>,
+}
+
+/// Rules of one provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderSignatures {
+    pub name: String,
+    /// Required for a provider that is not built in
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub provider_type: Option<ProviderType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    /// Reported as the evidence's `signature_matched`
+    pub id: String,
+    pub target: RuleTarget,
+    pub pattern: String,
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// What a rule's pattern is matched against
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleTarget {
+    /// Value of the named response header
+    Header(String),
+    /// Response body
+    Body,
+    /// Name of each cookie the response sets
+    Cookie,
+    /// Status code, as decimal text
+    Status,
+}
+
+impl fmt::Display for RuleTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleTarget::Header(name) => write!(f, "header {}", name),
+            RuleTarget::Body => f.write_str("body"),
+            RuleTarget::Cookie => f.write_str("cookie"),
+            RuleTarget::Status => f.write_str("status"),
+        }
+    }
+}
+
+/// A CNAME pattern attributed to a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DnsSignature {
+    pub provider: String,
+    pub pattern: String,
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// An address block announced by a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpRangeSignature {
+    pub provider: String,
+    pub cidr: IpRange,
+}
+
+/// An IPv4 or IPv6 network in CIDR notation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.network, addr) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => prefix_matches(u32::from(network) as u128, u32::from(addr) as u128, self.prefix, 32),
+            (IpAddr::V6(network), IpAddr::V6(addr)) => prefix_matches(u128::from(network), u128::from(addr), self.prefix, 128),
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, addr: u128, prefix: u8, bits: u8) -> bool {
+    prefix == 0 || (network ^ addr) >> (bits - prefix) == 0
+}
+
+impl FromStr for IpRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (network, prefix) = match s.split_once('/') {
+            Some((network, prefix)) => (network, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = network.trim().parse().map_err(|_| anyhow!("'{}' is not an IP network", s))?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().ok().filter(|prefix| *prefix <= bits).ok_or_else(|| anyhow!("'{}' has an invalid prefix length", s))?,
+            None => bits,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl Serialize for IpRange {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IpRange {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl SignatureBundle {
+    /// Parse and validate a bundle; every pattern must compile
+    pub fn from_json(data: &[u8]) -> Result<Self> {
+        let bundle: SignatureBundle = serde_json::from_slice(data).map_err(|e| anyhow!("Invalid signature bundle: {}", e))?;
+        bundle.validate()?;
+        Ok(bundle)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.version.trim().is_empty() {
+            return Err(anyhow!("Invalid signature bundle: empty version"));
+        }
+        for provider in &self.providers {
+            for rule in &provider.rules {
+                compile(&rule.pattern, rule.confidence).with_context(|| format!("rule '{}' of {}", rule.id, provider.name))?;
+            }
+        }
+        for pattern in &self.dns_patterns {
+            compile(&pattern.pattern, pattern.confidence).with_context(|| format!("DNS pattern of {}", pattern.provider))?;
+        }
+        for page in &self.block_pages {
+            if !(0.0..=1.0).contains(&page.confidence) {
+                return Err(anyhow!("block page {} {}: confidence {} is not between 0 and 1", page.vendor, page.name, page.confidence));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a registry detects with (`waf-detect signatures list`)
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    pub tool_version: String,
+    pub bundle: Option<BundleSummary>,
+    pub providers: Vec<ProviderInventory>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleSummary {
+    pub version: String,
+    pub published: DateTime<Utc>,
+    pub dns_patterns: usize,
+    pub block_pages: usize,
+    pub ip_ranges: usize,
+}
+
+impl From<&SignatureBundle> for BundleSummary {
+    fn from(bundle: &SignatureBundle) -> Self {
+        Self {
+            version: bundle.version.clone(),
+            published: bundle.published,
+            dns_patterns: bundle.dns_patterns.len(),
+            block_pages: bundle.block_pages.len(),
+            ip_ranges: bundle.ip_ranges.len(),
+        }
+    }
+}
+
+/// A registered provider and the bundle rules it carries
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderInventory {
+    pub name: String,
+    pub version: String,
+    pub provider_type: String,
+    pub enabled: bool,
+    /// Has checks compiled into the binary (false for bundle-only providers)
+    pub built_in: bool,
+    pub rules: Vec<Rule>,
+}
+
+/// A rule or DNS pattern's regex, checked together with its confidence
+pub fn compile(pattern: &str, confidence: f64) -> Result<Regex> {
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(anyhow!("confidence {} is not between 0 and 1", confidence));
+    }
+    Regex::new(pattern).map_err(|e| anyhow!("invalid pattern '{}': {}", pattern, e))
+}
+
+/// Where installed bundles live: `WAF_DETECTOR_DATA_DIR`, else `$XDG_DATA_HOME/waf-detector`,
+/// else `~/.local/share/waf-detector` (`%APPDATA%\waf-detector` on Windows)
+pub fn data_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    var("WAF_DETECTOR_DATA_DIR")
+        .or_else(|| var("XDG_DATA_HOME").map(|dir| dir.join("waf-detector")))
+        .or_else(|| var("APPDATA").map(|dir| dir.join("waf-detector")))
+        .or_else(|| var("HOME").map(|dir| dir.join(".local/share/waf-detector")))
+}
+
+/// The bundle installed in `dir`, if any. It was verified when it was installed.
+pub fn load_installed(dir: &Path) -> Result<Option<SignatureBundle>> {
+    let path = dir.join(BUNDLE_FILE);
+    match std::fs::read(&path) {
+        Ok(data) => SignatureBundle::from_json(&data).with_context(|| format!("Failed to load {}", path.display())).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUNDLE: &str = r#"{
+        "version": "2026.10.1",
+        "published": "2026-10-01T00:00:00Z",
+        "providers": [
+            {"name": "Imperva", "type": "WAF", "rules": [
+                {"id": "imperva-iinfo", "target": {"header": "x-iinfo"}, "pattern": "^\\d+-\\d+", "confidence": 0.9},
+                {"id": "imperva-incap-cookie", "target": "cookie", "pattern": "^incap_ses_", "confidence": 0.85}
+            ]},
+            {"name": "CloudFlare", "rules": [
+                {"id": "cloudflare-nel", "target": {"header": "nel"}, "pattern": "cf-nel", "confidence": 0.7}
+            ]}
+        ],
+        "dns_patterns": [{"provider": "Imperva", "pattern": "\\.incapdns\\.net$", "confidence": 0.95}],
+        "ip_ranges": [{"provider": "Imperva", "cidr": "45.60.0.0/16"}, {"provider": "Imperva", "cidr": "2a02:e980::/29"}]
+    }"#;
+
+    #[test]
+    fn test_bundle_parses_and_validates() {
+        let bundle = SignatureBundle::from_json(BUNDLE.as_bytes()).unwrap();
+        assert_eq!(bundle.providers.len(), 2);
+        assert_eq!(bundle.providers[0].rules[1].target, RuleTarget::Cookie);
+        assert_eq!(bundle.ip_ranges[1].cidr.to_string(), "2a02:e980::/29");
+
+        let broken = BUNDLE.replace("^incap_ses_", "(unclosed");
+        let error = format!("{:#}", SignatureBundle::from_json(broken.as_bytes()).unwrap_err());
+        assert!(error.contains("imperva-incap-cookie"), "{}", error);
+        assert!(SignatureBundle::from_json(BUNDLE.replace("0.85", "1.5").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_ip_ranges() {
+        let range: IpRange = "45.60.0.0/16".parse().unwrap();
+        assert!(range.contains("45.60.12.1".parse().unwrap()));
+        assert!(!range.contains("45.61.0.1".parse().unwrap()));
+        assert!(!range.contains("::1".parse().unwrap()));
+        let range: IpRange = "2a02:e980::/29".parse().unwrap();
+        assert!(range.contains("2a02:e987:ffff::1".parse().unwrap()));
+        assert!(!range.contains("2a02:e988::1".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<IpRange>().unwrap().contains("8.8.8.8".parse().unwrap()));
+        assert_eq!("10.0.0.1".parse::<IpRange>().unwrap().to_string(), "10.0.0.1/32");
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("example.com/8".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_load_installed() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_installed(dir.path()).unwrap().is_none());
+        std::fs::write(dir.path().join(BUNDLE_FILE), BUNDLE).unwrap();
+        assert_eq!(load_installed(dir.path()).unwrap().unwrap().version, "2026.10.1");
+    }
+}
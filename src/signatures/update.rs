@@ -0,0 +1,207 @@
+//! Download, verification and installation of signature bundles (`signatures` feature)
+//!
+//! The publisher signs the exact bundle bytes with Ed25519; the detached signature is
+//! served next to the bundle (`<url>.sig`, base64). Nothing is written to the data
+//! directory unless the signature verifies against the configured public key and the
+//! bundle parses.
+
+use super::{SignatureBundle, BUNDLE_FILE, SIGNATURE_FILE};
+use crate::http::{BodyLimit, HttpClient};
+use anyhow::{anyhow, Context, Result};
+use openssl::pkey::{Id, PKey};
+use openssl::sign::Verifier;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Bundle URL when `--url` is not given
+pub const URL_VARIABLE: &str = "WAF_DETECTOR_SIGNATURES_URL";
+/// Base64 Ed25519 public key when `--public-key` is not given
+pub const KEY_VARIABLE: &str = "WAF_DETECTOR_SIGNATURES_KEY";
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateConfig {
+    pub url: String,
+    /// Defaults to `<url>.sig`
+    pub signature_url: Option<String>,
+    /// Base64 of the raw 32-byte Ed25519 public key
+    pub public_key: String,
+    /// Install even if the bundle was published before the installed one
+    pub force: bool,
+}
+
+impl UpdateConfig {
+    /// `url` and `public_key` fall back to `WAF_DETECTOR_SIGNATURES_URL` and
+    /// `WAF_DETECTOR_SIGNATURES_KEY`
+    pub fn new(url: Option<&str>, public_key: Option<&str>) -> Result<Self> {
+        let setting = |value: Option<&str>, variable: &str, flag: &str| {
+            value
+                .map(str::to_string)
+                .or_else(|| std::env::var(variable).ok())
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| anyhow!("no signature bundle {}: pass --{} or set {}", flag.replace('-', " "), flag, variable))
+        };
+        Ok(Self {
+            url: setting(url, URL_VARIABLE, "url")?,
+            signature_url: None,
+            public_key: setting(public_key, KEY_VARIABLE, "public-key")?,
+            force: false,
+        })
+    }
+
+    fn signature_url(&self) -> String {
+        self.signature_url.clone().unwrap_or_else(|| format!("{}.sig", self.url))
+    }
+}
+
+/// What `update` installed
+#[derive(Debug, Clone)]
+pub struct UpdateOutcome {
+    pub bundle: SignatureBundle,
+    /// Version of the bundle that was replaced
+    pub previous: Option<String>,
+    pub path: PathBuf,
+}
+
+/// Check the detached base64 `signature` of `data` against the base64 Ed25519 `public_key`
+pub fn verify(data: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let key = crate::utils::base64_decode(public_key.trim()).context("public key is not base64")?;
+    let key = PKey::public_key_from_raw_bytes(&key, Id::ED25519).map_err(|_| anyhow!("public key is not a 32-byte Ed25519 key"))?;
+    let signature = crate::utils::base64_decode(signature.trim()).context("signature is not base64")?;
+    let valid = Verifier::new_without_digest(&key)
+        .and_then(|mut verifier| verifier.verify_oneshot(&signature, data))
+        .unwrap_or(false);
+    if !valid {
+        return Err(anyhow!("signature bundle signature does not verify against the public key"));
+    }
+    Ok(())
+}
+
+/// Download the bundle and its signature, verify both and install them into `dir`
+pub async fn update(dir: &Path, config: &UpdateConfig) -> Result<UpdateOutcome> {
+    let client = HttpClient::with_settings(DOWNLOAD_TIMEOUT, None)?.with_body_limit(BodyLimit::unlimited());
+    // Verified as served: bytes, not text decoded by the response's charset
+    let data = download(&client, &config.url).await?;
+    let signature = download(&client, &config.signature_url()).await?;
+    let signature = String::from_utf8(signature).map_err(|_| anyhow!("{} is not a base64 signature", config.signature_url()))?;
+    verify(&data, &signature, &config.public_key)?;
+    let bundle = SignatureBundle::from_json(&data)?;
+
+    let installed = super::load_installed(dir).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "replacing unreadable signature bundle");
+        None
+    });
+    if let Some(installed) = &installed {
+        if bundle.published < installed.published && !config.force {
+            return Err(anyhow!(
+                "bundle {} ({}) is older than the installed {} ({}); use --force to install it anyway",
+                bundle.version,
+                bundle.published.to_rfc3339(),
+                installed.version,
+                installed.published.to_rfc3339()
+            ));
+        }
+    }
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    write_replacing(&dir.join(SIGNATURE_FILE), signature.as_bytes())?;
+    let path = dir.join(BUNDLE_FILE);
+    write_replacing(&path, &data)?;
+    Ok(UpdateOutcome { bundle, previous: installed.map(|installed| installed.version), path })
+}
+
+async fn download(client: &HttpClient, url: &str) -> Result<Vec<u8>> {
+    let (response, bytes) = client.get_bytes(url).await.with_context(|| format!("Failed to download {}", url))?;
+    if response.status != 200 {
+        return Err(anyhow!("{} returned HTTP {}", url, response.status));
+    }
+    response.full_body()?;
+    Ok(bytes)
+}
+
+/// Write next to `path`, then rename over it, so readers never see a partial file
+fn write_replacing(path: &Path, contents: &[u8]) -> Result<()> {
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, contents).with_context(|| format!("Failed to write {}", partial.display()))?;
+    std::fs::rename(&partial, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::base64_encode;
+    use openssl::sign::Signer;
+
+    const BUNDLE: &str = r#"{"version": "2026.10.1", "published": "2026-10-01T00:00:00Z",
+        "dns_patterns": [{"provider": "Imperva", "pattern": "\\.incapdns\\.net$", "confidence": 0.95}]}"#;
+
+    /// A fresh key pair: (base64 public key, signer of base64 signatures)
+    fn keys() -> (String, impl Fn(&str) -> String) {
+        let key = PKey::generate_ed25519().unwrap();
+        let public = base64_encode(&key.raw_public_key().unwrap());
+        (public, move |data: &str| base64_encode(&Signer::new_without_digest(&key).unwrap().sign_oneshot_to_vec(data.as_bytes()).unwrap()))
+    }
+
+    #[test]
+    fn test_verify() {
+        let (public, sign) = keys();
+        let signature = sign(BUNDLE);
+        verify(BUNDLE.as_bytes(), &signature, &public).unwrap();
+        assert!(verify(BUNDLE.replace("0.95", "0.99").as_bytes(), &signature, &public).is_err());
+        let (other, _) = keys();
+        assert!(verify(BUNDLE.as_bytes(), &signature, &other).is_err());
+        assert!(verify(BUNDLE.as_bytes(), &signature, "c2hvcnQ=").unwrap_err().to_string().contains("32-byte"));
+    }
+
+    #[tokio::test]
+    async fn test_update_installs_verified_bundle() {
+        let (public, sign) = keys();
+        let mut server = mockito::Server::new_async().await;
+        let newer = BUNDLE.replace("2026.10.1", "2026.10.2").replace("2026-10-01", "2026-10-08");
+        let _bundle = server.mock("GET", "/signatures.json").with_body(&newer).create_async().await;
+        let _signature = server.mock("GET", "/signatures.json.sig").with_body(sign(&newer)).create_async().await;
+        let _older = server.mock("GET", "/old.json").with_body(BUNDLE).create_async().await;
+        let _older_signature = server.mock("GET", "/old.json.sig").with_body(sign(BUNDLE)).create_async().await;
+        let _tampered = server.mock("GET", "/tampered.json").with_body(newer.replace("0.95", "0.5")).create_async().await;
+        let _tampered_signature = server.mock("GET", "/tampered.json.sig").with_body(sign(&newer)).create_async().await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = |path: &str| UpdateConfig { url: format!("{}/{}", server.url(), path), signature_url: None, public_key: public.clone(), force: false };
+
+        let outcome = update(dir.path(), &config("signatures.json")).await.unwrap();
+        assert_eq!((outcome.bundle.version.as_str(), outcome.previous), ("2026.10.2", None));
+        assert_eq!(std::fs::read_to_string(dir.path().join(BUNDLE_FILE)).unwrap(), newer);
+        assert!(dir.path().join(SIGNATURE_FILE).exists());
+
+        let error = update(dir.path(), &config("tampered.json")).await.unwrap_err();
+        assert!(error.to_string().contains("does not verify"), "{}", error);
+        let error = update(dir.path(), &config("old.json")).await.unwrap_err();
+        assert!(error.to_string().contains("older than the installed 2026.10.2"), "{}", error);
+        assert_eq!(super::super::load_installed(dir.path()).unwrap().unwrap().version, "2026.10.2");
+
+        let outcome = update(dir.path(), &UpdateConfig { force: true, ..config("old.json") }).await.unwrap();
+        assert_eq!(outcome.previous.as_deref(), Some("2026.10.2"));
+    }
+
+    #[tokio::test]
+    async fn test_update_verifies_the_bytes_served() {
+        let (public, sign) = keys();
+        let mut server = mockito::Server::new_async().await;
+        // UTF-8 bytes under a Latin-1 label: decoding them first would change what is verified
+        let bundle = BUNDLE.replace("2026.10.1", "2026.10.1-café");
+        let _bundle = server
+            .mock("GET", "/signatures.json")
+            .with_header("content-type", "application/json; charset=ISO-8859-1")
+            .with_body(&bundle)
+            .create_async()
+            .await;
+        let _signature = server.mock("GET", "/signatures.json.sig").with_body(sign(&bundle)).create_async().await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = UpdateConfig { url: format!("{}/signatures.json", server.url()), signature_url: None, public_key: public, force: false };
+        let outcome = update(dir.path(), &config).await.unwrap();
+        assert_eq!(outcome.bundle.version, "2026.10.1-café");
+        assert_eq!(std::fs::read(dir.path().join(BUNDLE_FILE)).unwrap(), bundle.as_bytes());
+    }
+}
@@ -0,0 +1,99 @@
+//! Target canonicalization and deduplication for batch scans
+//!
+//! `example.com`, `https://Example.com/` and `https://example.com:443` are the same
+//! site. Batch scans collapse them to the first spelling before dispatch so a target
+//! file does not hit one site several times; `www.` can optionally count as the same
+//! host too.
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// A target dropped because an earlier one names the same site
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollapsedTarget {
+    pub target: String,
+    /// The earlier target that is scanned instead
+    pub kept: String,
+}
+
+/// Targets left after deduplication, in their original order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DedupReport {
+    pub targets: Vec<String>,
+    pub collapsed: Vec<CollapsedTarget>,
+}
+
+/// Key under which two targets count as the same site.
+///
+/// Scheme, host (lowercase, no trailing dot, no `www.` with `merge_www`), port and
+/// path are compared; the fragment is ignored and `/` equals no path. Targets that
+/// do not parse as URLs are their own key.
+pub fn canonical_key(target: &str, merge_www: bool) -> String {
+    let Ok(url) = Url::parse(target) else {
+        return target.trim().to_string();
+    };
+    let Some(host) = url.host_str() else {
+        return url.to_string();
+    };
+    let mut host = host.trim_end_matches('.').to_ascii_lowercase();
+    if merge_www {
+        if let Some(rest) = host.strip_prefix("www.") {
+            host = rest.to_string();
+        }
+    }
+    let port = url.port_or_known_default().map(|port| format!(":{}", port)).unwrap_or_default();
+    let path = match url.path() {
+        "/" => "",
+        path => path,
+    };
+    let query = url.query().map(|query| format!("?{}", query)).unwrap_or_default();
+    format!("{}://{}{}{}{}", url.scheme(), host, port, path, query)
+}
+
+/// Keep the first target of each [`canonical_key`], recording the ones dropped
+pub fn dedup(targets: Vec<String>, merge_www: bool) -> DedupReport {
+    let mut seen: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut report = DedupReport::default();
+    for target in targets {
+        let key = canonical_key(&target, merge_www);
+        match seen.get(&key) {
+            Some(&index) => report.collapsed.push(CollapsedTarget { kept: report.targets[index].clone(), target }),
+            None => {
+                seen.insert(key, report.targets.len());
+                report.targets.push(target);
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(targets: &[&str]) -> Vec<String> {
+        targets.iter().map(|target| target.to_string()).collect()
+    }
+
+    #[test]
+    fn test_same_site_spellings_collapse() {
+        let report = dedup(
+            strings(&["https://example.com/", "https://EXAMPLE.com", "https://example.com.:443/#top", "https://www.example.com/"]),
+            false,
+        );
+        assert_eq!(report.targets, strings(&["https://example.com/", "https://www.example.com/"]));
+        assert_eq!(report.collapsed.len(), 2);
+        assert!(report.collapsed.iter().all(|c| c.kept == "https://example.com/"));
+    }
+
+    #[test]
+    fn test_merge_www_and_distinct_targets() {
+        let report = dedup(strings(&["https://example.com/", "https://www.example.com/"]), true);
+        assert_eq!(report.targets, strings(&["https://example.com/"]));
+        assert_eq!(report.collapsed[0].target, "https://www.example.com/");
+
+        // Different scheme, port or path is a different target
+        let distinct = strings(&["https://example.com/", "http://example.com/", "https://example.com:8443/", "https://example.com/api"]);
+        assert_eq!(dedup(distinct.clone(), true).targets, distinct);
+    }
+}
@@ -0,0 +1,370 @@
+//! Provider accuracy benchmark against well-known sites (`waf-detect benchmark`)
+//!
+//! Scans a curated list of sites whose providers are public knowledge and scores every
+//! provider on it, so a signature change that stops detecting (or starts wrongly
+//! detecting) a vendor shows up before a release. The list is bundled with the binary;
+//! `refresh` installs a newer one into the data directory, which then takes precedence.
+
+use super::validation_framework::GroundTruth;
+use crate::error::ScanError;
+use crate::engine::DetectionEngine;
+use crate::http::{BodyLimit, HttpClient};
+use crate::DetectionResult;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Target list URL when `--url` is not given
+pub const URL_VARIABLE: &str = "WAF_DETECTOR_BENCHMARK_URL";
+
+/// File name of a refreshed list in the data directory
+pub const TARGETS_FILE: &str = "benchmark_targets.yaml";
+
+const BUNDLED_TARGETS: &str = include_str!("benchmark_targets.yaml");
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A versioned list of labelled targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkTargets {
+    pub version: String,
+    pub targets: Vec<GroundTruth>,
+}
+
+impl BenchmarkTargets {
+    /// The list compiled into the binary
+    pub fn bundled() -> Self {
+        Self::from_yaml(BUNDLED_TARGETS).expect("bundled benchmark targets are valid")
+    }
+
+    /// `{version, targets}`, or a plain ground-truth list as used by `waf-detect validate`
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let list = match serde_yaml::from_str::<Self>(yaml) {
+            Ok(list) => list,
+            Err(_) => Self { version: "custom".to_string(), targets: serde_yaml::from_str(yaml)? },
+        };
+        if list.targets.is_empty() {
+            return Err(anyhow!("benchmark target list {} contains no targets", list.version));
+        }
+        Ok(list)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read benchmark targets '{}': {}", path.display(), e))?;
+        Self::from_yaml(&yaml).map_err(|e| anyhow!("Invalid benchmark targets '{}': {}", path.display(), e))
+    }
+
+    /// The list refreshed into `dir`, else the bundled one
+    pub fn installed_or_bundled(dir: Option<&Path>) -> Result<Self> {
+        match dir.map(|dir| dir.join(TARGETS_FILE)).filter(|path| path.exists()) {
+            Some(path) => Self::load(path),
+            None => Ok(Self::bundled()),
+        }
+    }
+
+    /// Providers labelled on at least one target
+    pub fn providers(&self) -> BTreeSet<&str> {
+        self.targets.iter().flat_map(|target| [target.known_waf.as_deref(), target.known_cdn.as_deref()]).flatten().collect()
+    }
+}
+
+/// Download the target list at `url` and install it into `dir`; nothing is written
+/// unless it parses
+pub async fn refresh(dir: &Path, url: &str) -> Result<(BenchmarkTargets, PathBuf)> {
+    let client = HttpClient::with_settings(DOWNLOAD_TIMEOUT, None)?.with_body_limit(BodyLimit::unlimited());
+    let response = client.get(url).await?;
+    if response.status != 200 {
+        return Err(anyhow!("Downloading benchmark targets from {} failed: HTTP {}", url, response.status));
+    }
+    let body = response.full_body()?;
+    let list = BenchmarkTargets::from_yaml(&body).map_err(|e| anyhow!("Invalid benchmark targets from {}: {}", url, e))?;
+    std::fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create '{}': {}", dir.display(), e))?;
+    let path = dir.join(TARGETS_FILE);
+    std::fs::write(&path, &body).map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e))?;
+    Ok((list, path))
+}
+
+/// One target's expected and reported providers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkCase {
+    pub url: String,
+    /// Labelled WAF and CDN, deduplicated
+    pub expected: Vec<String>,
+    /// Reported WAF, CDN and bot manager, deduplicated
+    pub detected: Vec<String>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanError>,
+}
+
+impl BenchmarkCase {
+    pub fn new(truth: &GroundTruth, result: Result<&DetectionResult, &anyhow::Error>, duration: Duration) -> Self {
+        let mut expected: Vec<String> = Vec::new();
+        for name in [&truth.known_waf, &truth.known_cdn].into_iter().flatten() {
+            if !expected.contains(name) {
+                expected.push(name.clone());
+            }
+        }
+        let (detected, error) = match result {
+            Ok(result) => {
+                let mut detected: Vec<String> = Vec::new();
+                for detection in [&result.detected_waf, &result.detected_cdn, &result.detected_bot_manager].into_iter().flatten() {
+                    if !detected.contains(&detection.name) {
+                        detected.push(detection.name.clone());
+                    }
+                }
+                (detected, result.error.clone())
+            }
+            Err(e) => (Vec::new(), Some(ScanError::from_error(e))),
+        };
+        Self { url: truth.url.clone(), expected, detected, duration_ms: duration.as_millis() as u64, error }
+    }
+
+    /// Labelled providers that were not reported
+    pub fn missed(&self) -> impl Iterator<Item = &str> {
+        self.expected.iter().filter(|name| !self.detected.contains(name)).map(String::as_str)
+    }
+
+    /// Reported providers the target is not labelled with
+    pub fn unexpected(&self) -> impl Iterator<Item = &str> {
+        self.detected.iter().filter(|name| !self.expected.contains(name)).map(String::as_str)
+    }
+
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.missed().next().is_none() && self.unexpected().next().is_none()
+    }
+}
+
+/// A provider's row on the scoreboard
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderScore {
+    pub provider: String,
+    /// Targets labelled with the provider
+    pub targets: usize,
+    pub detected: usize,
+    pub missed: usize,
+    /// Targets it was reported on without being labelled there
+    pub false_positives: usize,
+    /// `detected / (targets + false_positives)`
+    pub accuracy: f64,
+    /// Median scan time of its labelled targets
+    pub median_ms: u64,
+}
+
+/// Scoreboard of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    /// Version of the target list
+    pub list_version: String,
+    pub tool_version: String,
+    pub started_at: DateTime<Utc>,
+    /// Share of targets whose reported providers match their labels exactly
+    pub accuracy: f64,
+    pub median_ms: u64,
+    pub p95_ms: u64,
+    pub total_ms: u64,
+    pub providers: Vec<ProviderScore>,
+    pub cases: Vec<BenchmarkCase>,
+}
+
+impl BenchmarkReport {
+    pub fn from_cases(list_version: &str, started_at: DateTime<Utc>, cases: Vec<BenchmarkCase>) -> Self {
+        let names: BTreeSet<&str> = cases.iter().flat_map(|case| case.expected.iter().chain(&case.detected)).map(String::as_str).collect();
+        let providers = names
+            .into_iter()
+            .map(|name| {
+                let labelled: Vec<&BenchmarkCase> = cases.iter().filter(|case| case.expected.iter().any(|n| n == name)).collect();
+                let detected = labelled.iter().filter(|case| case.detected.iter().any(|n| n == name)).count();
+                let false_positives = cases
+                    .iter()
+                    .filter(|case| case.detected.iter().any(|n| n == name) && !case.expected.iter().any(|n| n == name))
+                    .count();
+                let scored = labelled.len() + false_positives;
+                ProviderScore {
+                    provider: name.to_string(),
+                    targets: labelled.len(),
+                    detected,
+                    missed: labelled.len() - detected,
+                    false_positives,
+                    accuracy: if scored == 0 { 0.0 } else { detected as f64 / scored as f64 },
+                    median_ms: percentile(labelled.iter().map(|case| case.duration_ms).collect(), 0.5),
+                }
+            })
+            .collect();
+
+        let durations: Vec<u64> = cases.iter().map(|case| case.duration_ms).collect();
+        let passed = cases.iter().filter(|case| case.passed()).count();
+        Self {
+            list_version: list_version.to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at,
+            accuracy: if cases.is_empty() { 0.0 } else { passed as f64 / cases.len() as f64 },
+            median_ms: percentile(durations.clone(), 0.5),
+            p95_ms: percentile(durations.clone(), 0.95),
+            total_ms: durations.iter().sum(),
+            providers,
+            cases,
+        }
+    }
+
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|case| case.passed()).count()
+    }
+
+    /// Markdown scoreboard: per-provider table, then every target that did not pass
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# WAF Detector benchmark\n\n");
+        out.push_str(&format!(
+            "waf-detector {} · target list {} · {}\n\n",
+            self.tool_version,
+            self.list_version,
+            self.started_at.format("%Y-%m-%d %H:%M UTC")
+        ));
+        out.push_str(&format!(
+            "**{} of {} targets correct ({:.1}%)** · median scan {} ms · p95 {} ms · total {:.1} s\n\n",
+            self.passed(),
+            self.cases.len(),
+            self.accuracy * 100.0,
+            self.median_ms,
+            self.p95_ms,
+            self.total_ms as f64 / 1000.0
+        ));
+
+        out.push_str("| Provider | Targets | Detected | Missed | False positives | Accuracy | Median time |\n");
+        out.push_str("|---|---:|---:|---:|---:|---:|---:|\n");
+        for score in &self.providers {
+            let median = if score.targets == 0 { "-".to_string() } else { format!("{} ms", score.median_ms) };
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {:.1}% | {} |\n",
+                score.provider,
+                score.targets,
+                score.detected,
+                score.missed,
+                score.false_positives,
+                score.accuracy * 100.0,
+                median
+            ));
+        }
+
+        let failures: Vec<&BenchmarkCase> = self.cases.iter().filter(|case| !case.passed()).collect();
+        if !failures.is_empty() {
+            out.push_str("\n## Mismatches\n\n| Target | Expected | Detected | Note |\n|---|---|---|---|\n");
+            let list = |names: &[String]| if names.is_empty() { "none".to_string() } else { names.join(", ") };
+            for case in failures {
+                let note = match &case.error {
+                    Some(error) => format!("scan failed: {}", error.message.replace('|', "\\|")),
+                    None => String::new(),
+                };
+                out.push_str(&format!("| {} | {} | {} | {} |\n", case.url, list(&case.expected), list(&case.detected), note));
+            }
+        }
+        out
+    }
+}
+
+/// Nearest-rank percentile; 0 for no values
+fn percentile(mut values: Vec<u64>, quantile: f64) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let rank = (quantile * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+/// Scans a target list one target at a time, so each scan time stands on its own
+pub struct Benchmark {
+    engine: DetectionEngine,
+    targets: BenchmarkTargets,
+}
+
+impl Benchmark {
+    pub fn new(engine: DetectionEngine, targets: BenchmarkTargets) -> Self {
+        Self { engine, targets }
+    }
+
+    pub fn targets(&self) -> &BenchmarkTargets {
+        &self.targets
+    }
+
+    /// Scan every target; `progress` is called after each one
+    pub async fn run(&self, mut progress: impl FnMut(&BenchmarkCase)) -> BenchmarkReport {
+        let started_at = Utc::now();
+        let mut cases = Vec::with_capacity(self.targets.targets.len());
+        for truth in &self.targets.targets {
+            let started = Instant::now();
+            let result = self.engine.detect(&truth.url).await;
+            let case = BenchmarkCase::new(truth, result.as_ref(), started.elapsed());
+            progress(&case);
+            cases.push(case);
+        }
+        BenchmarkReport::from_cases(&self.targets.version, started_at, cases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(url: &str, expected: &[&str], detected: &[&str], duration_ms: u64) -> BenchmarkCase {
+        BenchmarkCase {
+            url: url.to_string(),
+            expected: expected.iter().map(|s| s.to_string()).collect(),
+            detected: detected.iter().map(|s| s.to_string()).collect(),
+            duration_ms,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_bundled_targets_name_registered_providers() {
+        let list = BenchmarkTargets::bundled();
+        let registered: Vec<String> = crate::providers::default_providers().iter().map(|p| p.name().to_string()).collect();
+        for provider in list.providers() {
+            assert!(registered.iter().any(|name| name == provider), "unknown provider {} in the bundled list", provider);
+        }
+        assert!(list.targets.iter().any(|target| target.known_waf.is_none() && target.known_cdn.is_none()));
+    }
+
+    #[test]
+    fn test_plain_ground_truth_lists_are_accepted() {
+        let list = BenchmarkTargets::from_yaml("- url: https://a.example\n  cdn: Fastly\n").unwrap();
+        assert_eq!((list.version.as_str(), list.targets.len()), ("custom", 1));
+        assert!(BenchmarkTargets::from_yaml("version: x\ntargets: []\n").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(BenchmarkTargets::installed_or_bundled(Some(dir.path())).unwrap().version, BenchmarkTargets::bundled().version);
+        std::fs::write(dir.path().join(TARGETS_FILE), "version: \"2099.1\"\ntargets:\n  - url: https://b.example\n").unwrap();
+        assert_eq!(BenchmarkTargets::installed_or_bundled(Some(dir.path())).unwrap().version, "2099.1");
+    }
+
+    #[test]
+    fn test_scoreboard() {
+        let cases = vec![
+            case("https://a.example", &["CloudFlare"], &["CloudFlare"], 100),
+            case("https://b.example", &["CloudFlare"], &[], 300),
+            case("https://c.example", &["Fastly"], &["Fastly", "CloudFlare"], 200),
+            case("https://d.example", &[], &[], 50),
+        ];
+        let report = BenchmarkReport::from_cases("test", Utc::now(), cases);
+        assert_eq!(report.passed(), 2);
+        assert_eq!(report.accuracy, 0.5);
+        assert_eq!((report.median_ms, report.p95_ms, report.total_ms), (100, 300, 650));
+
+        let cloudflare = report.providers.iter().find(|score| score.provider == "CloudFlare").unwrap();
+        assert_eq!((cloudflare.targets, cloudflare.detected, cloudflare.missed, cloudflare.false_positives), (2, 1, 1, 1));
+        assert!((cloudflare.accuracy - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(cloudflare.median_ms, 100);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("**2 of 4 targets correct (50.0%)**"), "{}", markdown);
+        assert!(markdown.contains("| CloudFlare | 2 | 1 | 1 | 1 | 33.3% | 100 ms |"), "{}", markdown);
+        assert!(markdown.contains("| https://b.example | CloudFlare | none |  |"), "{}", markdown);
+        assert!(!markdown.contains("d.example"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Recorded provider responses and a local server that replays them
+//!
+//! A fixture is a raw HTTP response captured from a real deployment (status line,
+//! headers, a blank line and the body) stored as `<dir>/<provider>/<name>.http`, where
+//! `<provider>` is the provider's registry name and `origin` holds responses from
+//! unprotected servers. [`MockServer`] listens on a loopback port and answers with
+//! fixtures, so `detect`, `passive_detect` and `active_detect` run end to end without
+//! network access:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use waf_detector::testing::{load_fixtures, MockServer};
+//!
+//! for fixture in load_fixtures("tests/fixtures")? {
+//!     let server = MockServer::serving(&fixture).await?;
+//!     // point a DetectionEngine at server.url()
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::http::HttpResponse;
+use anyhow::{anyhow, Result};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Directory name of fixtures that no provider served
+pub const ORIGIN: &str = "origin";
+
+/// Largest request head the server reads
+const MAX_HEAD: usize = 64 * 1024;
+
+/// A recorded response and the provider that served it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    /// Registry name of the provider; `None` for an unprotected origin
+    pub provider: Option<String>,
+    pub name: String,
+    pub raw: String,
+}
+
+impl Fixture {
+    pub fn new(provider: Option<&str>, name: impl Into<String>, raw: impl Into<String>) -> Self {
+        Self { provider: provider.map(str::to_string), name: name.into(), raw: raw.into() }
+    }
+
+    /// Read `<provider>/<name>.http`; the provider comes from the parent directory
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read fixture '{}': {}", path.display(), e))?;
+        let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let provider = path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|dir| dir.to_str())
+            .filter(|dir| *dir != ORIGIN);
+        Ok(Self::new(provider, name, raw))
+    }
+
+    /// The response as if fetched from `url`
+    pub fn response(&self, url: &str) -> Result<HttpResponse> {
+        crate::capture::parse_raw_response(self.raw.as_bytes(), url)
+            .map_err(|e| anyhow!("Invalid fixture '{}': {}", self.name, e))
+    }
+}
+
+/// Every `*.http` fixture one level below `dir`, ordered by provider and name
+pub fn load_fixtures(dir: impl AsRef<Path>) -> Result<Vec<Fixture>> {
+    let dir = dir.as_ref();
+    let mut paths = Vec::new();
+    let providers = std::fs::read_dir(dir).map_err(|e| anyhow!("Failed to read fixtures '{}': {}", dir.display(), e))?;
+    for provider in providers {
+        let provider = provider?.path();
+        if !provider.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&provider)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "http") {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    paths.iter().map(Fixture::load).collect()
+}
+
+/// A request the server received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query as sent
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct Route {
+    method: String,
+    path: String,
+    response: HttpResponse,
+}
+
+#[derive(Debug, Default)]
+struct ServerState {
+    routes: Vec<Route>,
+    fallback: Option<HttpResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl ServerState {
+    fn respond(&self, request: &RecordedRequest) -> Option<HttpResponse> {
+        self.routes
+            .iter()
+            .rev()
+            .find(|route| route.method.eq_ignore_ascii_case(&request.method) && route.path == request.path())
+            .map(|route| route.response.clone())
+            .or_else(|| self.fallback.clone())
+    }
+}
+
+/// HTTP/1.1 server on a loopback port that answers with canned responses; stops when dropped
+#[derive(Debug)]
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    /// A server that answers 404 until given routes or a fallback
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        let accepting = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = accepting.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, state).await;
+                });
+            }
+        });
+        Ok(Self { address, state, task })
+    }
+
+    /// A server that answers every request with `fixture`
+    pub async fn serving(fixture: &Fixture) -> Result<Self> {
+        let server = Self::start().await?;
+        server.fallback(fixture.response(&server.url())?);
+        Ok(server)
+    }
+
+    /// Answer requests that match no route with `response`
+    pub fn fallback(&self, response: HttpResponse) -> &Self {
+        self.lock().fallback = Some(response);
+        self
+    }
+
+    /// Answer `method` requests for `path` (query ignored) with `response`; later routes win
+    pub fn route(&self, method: &str, path: &str, response: HttpResponse) -> &Self {
+        self.lock().routes.push(Route { method: method.to_string(), path: path.to_string(), response });
+        self
+    }
+
+    /// `http://127.0.0.1:<port>`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ServerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Read one request, answer it and close the connection
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<ServerState>>) -> Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_HEAD {
+            return Err(anyhow!("request head too large"));
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut start = lines.next().unwrap_or_default().split_whitespace();
+    let method = start.next().unwrap_or_default().to_string();
+    let target = start.next().unwrap_or("/").to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = RecordedRequest { method, target, headers, body: String::from_utf8_lossy(&body).into_owned() };
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let response = state.respond(&request);
+        state.requests.push(request.clone());
+        response
+    };
+    let response = response.unwrap_or_else(|| HttpResponse::from_parts(404, [("content-type", "text/plain")], "no route", ""));
+    stream.write_all(&encode(&response, request.method == "HEAD")).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Status line, the recorded headers with framing replaced, and the body
+fn encode(response: &HttpResponse, head_only: bool) -> Vec<u8> {
+    let reason = http::StatusCode::from_u16(response.status).ok().and_then(|status| status.canonical_reason()).unwrap_or("");
+    let mut out = format!("HTTP/1.1 {} {}\r\n", response.status, reason);
+    for (name, value) in response.headers.iter() {
+        if ["content-length", "transfer-encoding", "connection", "content-encoding"].iter().any(|skip| name.eq_ignore_ascii_case(skip)) {
+            continue;
+        }
+        // Captures may fold several Set-Cookie values into one, a line each
+        for line in value.lines() {
+            out.push_str(&format!("{}: {}\r\n", name, line));
+        }
+    }
+    out.push_str(&format!("content-length: {}\r\nconnection: close\r\n\r\n", response.body.len()));
+    let mut bytes = out.into_bytes();
+    if !head_only {
+        bytes.extend_from_slice(response.body.as_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpClient;
+
+    #[tokio::test]
+    async fn test_routes_and_fallback() {
+        let fixture = Fixture::new(Some("CloudFlare"), "ray", "HTTP/1.1 403 Forbidden\ncf-ray: 8a1b2c3d4e5f6789-FRA\nset-cookie: a=1\nset-cookie: b=2\n\nblocked");
+        let server = MockServer::serving(&fixture).await.unwrap();
+        server.route("GET", "/ok", HttpResponse::from_parts(200, [("server", "nginx")], "fine", ""));
+        let client = HttpClient::new().unwrap();
+
+        let blocked = client.get(&format!("{}/anything?q=1", server.url())).await.unwrap();
+        assert_eq!((blocked.status, blocked.body.as_str()), (403, "blocked"));
+        assert_eq!(blocked.headers.get("cf-ray").map(String::as_str), Some("8a1b2c3d4e5f6789-FRA"));
+        assert_eq!(blocked.headers.get_all("set-cookie").count(), 2);
+
+        let ok = client.get(&format!("{}/ok?x=1", server.url())).await.unwrap();
+        assert_eq!((ok.status, ok.body.as_str()), (200, "fine"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!((requests[0].path(), requests[0].target.as_str()), ("/anything", "/anything?q=1"));
+        assert!(requests[1].header("user-agent").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unrouted_requests_get_404() {
+        let server = MockServer::start().await.unwrap();
+        let response = HttpClient::new().unwrap().get(&server.url()).await.unwrap();
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn test_fixture_provider_comes_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        for (provider, name) in [("Fastly", "cached"), (ORIGIN, "nginx")] {
+            std::fs::create_dir_all(dir.path().join(provider)).unwrap();
+            std::fs::write(dir.path().join(provider).join(format!("{}.http", name)), "HTTP/1.1 200 OK\n\n").unwrap();
+        }
+        let fixtures = load_fixtures(dir.path()).unwrap();
+        let found: Vec<(Option<&str>, &str)> = fixtures.iter().map(|f| (f.provider.as_deref(), f.name.as_str())).collect();
+        assert_eq!(found, [(Some("Fastly"), "cached"), (None, "nginx")]);
+    }
+}
@@ -1,5 +1,9 @@
+pub mod benchmark;
+pub mod mock_server;
 pub mod validation_framework;
 
+pub use benchmark::{Benchmark, BenchmarkCase, BenchmarkReport, BenchmarkTargets, ProviderScore};
+pub use mock_server::{load_fixtures, Fixture, MockServer, RecordedRequest};
 pub use validation_framework::{
     ValidationFramework,
     GroundTruth,
@@ -7,5 +11,8 @@ pub use validation_framework::{
     TestOutcome,
     ValidationReport,
     ConfidenceAnalysis,
-    ProviderMetrics
-}; 
\ No newline at end of file
+    ProviderMetrics,
+    ProviderCalibration,
+    WeightSuggestion,
+    load_ground_truth,
+}; 
@@ -1,22 +1,49 @@
 //! Validation framework for testing WAF/CDN detection accuracy
 
 use crate::DetectionResult;
+use crate::confidence::{AdvancedScoring, EvidenceWeight};
 use crate::engine::DetectionEngine;
-use std::collections::HashMap;
-use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Reliability changes smaller than this are not worth suggesting
+const MIN_RELIABILITY_CHANGE: f64 = 0.05;
 
 /// Ground truth data for validation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroundTruth {
     pub url: String,
+    #[serde(default, rename = "waf", alias = "known_waf")]
     pub known_waf: Option<String>,
+    #[serde(default, rename = "cdn", alias = "known_cdn")]
     pub known_cdn: Option<String>,
+    #[serde(default = "default_confidence_level")]
     pub confidence_level: f64,
+    #[serde(default)]
     pub notes: Option<String>,
 }
 
+fn default_confidence_level() -> f64 {
+    1.0
+}
+
+/// Load a labelled target list: a YAML (or JSON) list of `{url, waf, cdn, notes}`
+pub fn load_ground_truth(path: impl AsRef<Path>) -> Result<Vec<GroundTruth>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read ground truth '{}': {}", path.display(), e))?;
+    let targets: Vec<GroundTruth> = serde_yaml::from_str(&content)
+        .map_err(|e| anyhow!("Invalid ground truth '{}': {}", path.display(), e))?;
+    if targets.is_empty() {
+        return Err(anyhow!("Ground truth '{}' contains no targets", path.display()));
+    }
+    Ok(targets)
+}
+
 /// Test outcome classification
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum TestOutcome {
     TruePositive,
     FalsePositive,
@@ -25,7 +52,7 @@ pub enum TestOutcome {
 }
 
 /// Validation result for a single test
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationResult {
     pub ground_truth: GroundTruth,
     pub detection_result: DetectionResult,
@@ -35,7 +62,7 @@ pub struct ValidationResult {
 }
 
 /// Confidence analysis for different ranges
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ConfidenceAnalysis {
     pub range: String,
     pub accuracy: f64,
@@ -47,7 +74,7 @@ pub struct ConfidenceAnalysis {
 }
 
 /// Provider-specific metrics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ProviderMetrics {
     pub provider_name: String,
     pub accuracy: f64,
@@ -57,8 +84,36 @@ pub struct ProviderMetrics {
     pub total_tests: usize,
 }
 
+/// How well a provider's confidence scores match the labels
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderCalibration {
+    pub provider: String,
+    /// Targets labelled with this provider (as WAF or CDN)
+    pub labelled: usize,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub precision: f64,
+    pub recall: f64,
+    /// Mean squared error of the provider score against the label (0.0 = perfect)
+    pub brier_score: f64,
+}
+
+/// Suggested reliability for one evidence signature, from how often it fired on the right provider
+#[derive(Debug, Clone, Serialize)]
+pub struct WeightSuggestion {
+    pub signature: String,
+    pub provider: String,
+    /// Targets the signature fired on
+    pub fired: usize,
+    /// Targets it fired on that are labelled with its provider
+    pub correct: usize,
+    pub current_reliability: f64,
+    pub suggested_reliability: f64,
+}
+
 /// Complete validation report
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationReport {
     pub overall_accuracy: f64,
     pub overall_precision: f64,
@@ -68,6 +123,10 @@ pub struct ValidationReport {
     pub provider_metrics: Vec<ProviderMetrics>,
     pub confidence_analysis: Vec<ConfidenceAnalysis>,
     pub failed_tests: Vec<ValidationResult>,
+    /// Per-provider precision/recall/Brier score, sorted by provider
+    pub calibration: Vec<ProviderCalibration>,
+    /// Reliability adjustments for `AdvancedScoring` evidence weights
+    pub weight_suggestions: Vec<WeightSuggestion>,
 }
 
 /// Validation framework for testing detection accuracy
@@ -85,6 +144,14 @@ impl ValidationFramework {
             detection_engine,
         }
     }
+
+    /// Validate against a custom labelled dataset instead of the built-in one
+    pub fn with_ground_truth(detection_engine: DetectionEngine, ground_truth_data: Vec<GroundTruth>) -> Self {
+        Self {
+            ground_truth_data,
+            detection_engine,
+        }
+    }
     
     /// Build ground truth dataset with known WAF/CDN providers
     fn build_ground_truth_dataset() -> Vec<GroundTruth> {
@@ -160,7 +227,13 @@ impl ValidationFramework {
         let mut results = Vec::new();
         
         for ground_truth in &self.ground_truth_data {
-            let detection_result = self.detection_engine.detect(&ground_truth.url).await?;
+            let detection_result = match self.detection_engine.detect(&ground_truth.url).await {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::warn!(url = %ground_truth.url, error = %e, "skipping target");
+                    continue;
+                }
+            };
             let validation_result = self.evaluate_result(ground_truth, detection_result);
             results.push(validation_result);
         }
@@ -336,6 +409,7 @@ impl ValidationFramework {
             },
         ];
         
+        let scoring = self.detection_engine.registry().advanced_scoring();
         ValidationReport {
             overall_accuracy,
             overall_precision,
@@ -345,8 +419,97 @@ impl ValidationFramework {
             provider_metrics,
             confidence_analysis,
             failed_tests,
+            calibration: calibrate(&results),
+            weight_suggestions: suggest_weights(&results, &scoring),
+        }
+    }
+}
+
+fn is_labelled(ground_truth: &GroundTruth, provider: &str) -> bool {
+    ground_truth.known_waf.as_deref() == Some(provider) || ground_truth.known_cdn.as_deref() == Some(provider)
+}
+
+fn is_reported(result: &DetectionResult, provider: &str) -> bool {
+    [&result.detected_waf, &result.detected_cdn, &result.detected_bot_manager]
+        .iter()
+        .any(|detection| detection.as_ref().is_some_and(|d| d.name == provider))
+}
+
+/// Precision, recall and Brier score for every provider that was labelled or scored
+pub fn calibrate(results: &[ValidationResult]) -> Vec<ProviderCalibration> {
+    let providers: BTreeSet<&str> = results
+        .iter()
+        .flat_map(|r| {
+            let labels = [&r.ground_truth.known_waf, &r.ground_truth.known_cdn];
+            labels.into_iter().flatten().map(|s| s.as_str()).collect::<Vec<_>>().into_iter()
+                .chain(r.detection_result.provider_scores.iter().filter(|(_, s)| **s > 0.0).map(|(p, _)| p.as_str()))
+        })
+        .collect();
+
+    providers
+        .into_iter()
+        .map(|provider| {
+            let (mut tp, mut fp, mut fn_count, mut labelled, mut squared_error) = (0, 0, 0, 0, 0.0);
+            for result in results {
+                let label = is_labelled(&result.ground_truth, provider);
+                let reported = is_reported(&result.detection_result, provider);
+                let score = result.detection_result.provider_scores.get(provider).copied().unwrap_or(0.0).clamp(0.0, 1.0);
+                let target = if label { 1.0 } else { 0.0 };
+                squared_error += (score - target) * (score - target);
+                labelled += label as usize;
+                match (label, reported) {
+                    (true, true) => tp += 1,
+                    (false, true) => fp += 1,
+                    (true, false) => fn_count += 1,
+                    (false, false) => {}
+                }
+            }
+            ProviderCalibration {
+                provider: provider.to_string(),
+                labelled,
+                true_positives: tp,
+                false_positives: fp,
+                false_negatives: fn_count,
+                precision: if tp + fp > 0 { tp as f64 / (tp + fp) as f64 } else { 0.0 },
+                recall: if tp + fn_count > 0 { tp as f64 / (tp + fn_count) as f64 } else { 0.0 },
+                brier_score: if results.is_empty() { 0.0 } else { squared_error / results.len() as f64 },
+            }
+        })
+        .collect()
+}
+
+/// Compare each weighted signature's observed precision with its configured reliability.
+///
+/// The observed value is Laplace-smoothed so a signature seen once is not pushed to 0 or 1.
+pub fn suggest_weights(results: &[ValidationResult], scoring: &AdvancedScoring) -> Vec<WeightSuggestion> {
+    let mut counts: BTreeMap<(String, String), (usize, usize)> = BTreeMap::new();
+    for result in results {
+        for (provider, evidence) in &result.detection_result.evidence_map {
+            let signatures: BTreeSet<&str> = evidence.iter().map(|e| e.signature_matched.as_str()).collect();
+            let correct = is_labelled(&result.ground_truth, provider);
+            for signature in signatures {
+                let entry = counts.entry((signature.to_string(), provider.clone())).or_default();
+                entry.0 += 1;
+                entry.1 += correct as usize;
+            }
         }
     }
+
+    counts
+        .into_iter()
+        .filter_map(|((signature, provider), (fired, correct))| {
+            let current_reliability = scoring.evidence_weight(&signature)?.reliability;
+            let suggested_reliability = (correct as f64 + 1.0) / (fired as f64 + 2.0);
+            ((suggested_reliability - current_reliability).abs() >= MIN_RELIABILITY_CHANGE).then_some(WeightSuggestion {
+                signature,
+                provider,
+                fired,
+                correct,
+                current_reliability,
+                suggested_reliability,
+            })
+        })
+        .collect()
 }
 
 impl ValidationReport {
@@ -370,6 +533,32 @@ impl ValidationReport {
                 provider.f1_score * 100.0);
         }
         
+        if !self.calibration.is_empty() {
+            println!("\n🎯 Calibration:");
+            println!("  {:<16} {:>8} {:>10} {:>8} {:>8}", "PROVIDER", "LABELLED", "PRECISION", "RECALL", "BRIER");
+            for calibration in &self.calibration {
+                println!("  {:<16} {:>8} {:>9.1}% {:>7.1}% {:>8.3}",
+                    calibration.provider,
+                    calibration.labelled,
+                    calibration.precision * 100.0,
+                    calibration.recall * 100.0,
+                    calibration.brier_score);
+            }
+        }
+
+        if !self.weight_suggestions.is_empty() {
+            println!("\n⚖️  Suggested reliability adjustments:");
+            for suggestion in &self.weight_suggestions {
+                println!("  {} ({}): {:.2} → {:.2} (correct on {}/{} targets)",
+                    suggestion.signature,
+                    suggestion.provider,
+                    suggestion.current_reliability,
+                    suggestion.suggested_reliability,
+                    suggestion.correct,
+                    suggestion.fired);
+            }
+        }
+        
         if !self.failed_tests.is_empty() {
             println!("\n❌ Failed Tests ({}):", self.failed_tests.len());
             for failed in &self.failed_tests {
@@ -384,6 +573,18 @@ impl ValidationReport {
         }
     }
     
+    /// Configured evidence weights with the suggested reliabilities applied, keyed by signature
+    pub fn adjusted_weights(&self, scoring: &AdvancedScoring) -> BTreeMap<String, EvidenceWeight> {
+        self.weight_suggestions
+            .iter()
+            .filter_map(|suggestion| {
+                let mut weight = scoring.evidence_weight(&suggestion.signature)?.clone();
+                weight.reliability = (suggestion.suggested_reliability * 100.0).round() / 100.0;
+                Some((suggestion.signature.clone(), weight))
+            })
+            .collect()
+    }
+
     /// Check if validation meets target thresholds
     pub fn meets_targets(&self, min_accuracy: f64, min_precision: f64, min_recall: f64) -> bool {
         self.overall_accuracy >= min_accuracy &&
@@ -1,23 +1,49 @@
 //! Timing analysis for WAF detection
 //! 
 //! Detects WAF presence by measuring processing delays introduced by WAF inspection.
-//! Research shows WAFs typically add 50-200ms processing delays compared to direct responses.
+//! Network round-trip time is measured first with HEAD requests, then benign and
+//! suspicious requests are interleaved and compared with a Mann-Whitney U test, so a
+//! delay is reported with its p-value rather than by checking a fixed delay window
+//! that slow networks fall into and fast WAFs stay below.
 
+pub mod stats;
+
+use crate::http::{HttpClient, HttpResponse, RetryPolicy};
 use crate::{Evidence, MethodType};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
-/// Timing analysis results
-#[derive(Debug, Clone)]
+/// Timing analysis results (times in milliseconds)
+#[derive(Debug, Clone, PartialEq)]
 pub struct TimingAnalysis {
-    pub baseline_time_ms: u64,
-    pub test_time_ms: u64,
-    pub delay_detected: bool,
-    pub delay_amount_ms: u64,
+    /// Median round-trip time of the HEAD requests
+    pub rtt_ms: f64,
+    /// Median absolute deviation of the round-trip times
+    pub jitter_ms: f64,
+    /// Median time of the benign requests
+    pub baseline_time_ms: f64,
+    /// Median time of the suspicious requests
+    pub test_time_ms: f64,
+    /// Hodges-Lehmann estimate of how much slower the suspicious requests are
+    pub delay_amount_ms: f64,
+    /// Mann-Whitney U of the suspicious requests against the benign ones
+    pub u_statistic: f64,
+    /// One-sided p-value of the suspicious requests being no slower than the benign ones
+    pub p_value: f64,
+    pub samples: usize,
     pub confidence: f64,
     pub technique_used: TimingTechnique,
 }
 
+impl TimingAnalysis {
+    /// Whether the delay is both significant at `significance` and larger than the
+    /// network's own jitter (and `min_delay_ms`)
+    pub fn delay_detected(&self, significance: f64, min_delay_ms: f64) -> bool {
+        self.p_value < significance && self.delay_amount_ms >= min_delay_ms.max(self.jitter_ms)
+    }
+}
+
 /// Different timing analysis techniques
 #[derive(Debug, Clone, PartialEq)]
 pub enum TimingTechnique {
@@ -32,14 +58,18 @@ pub enum TimingTechnique {
 /// Configuration for timing analysis
 #[derive(Debug, Clone)]
 pub struct TimingConfig {
-    /// Minimum delay to consider WAF processing (default: 50ms)
-    pub min_waf_delay_ms: u64,
-    /// Maximum delay to consider WAF processing (default: 200ms)  
-    pub max_waf_delay_ms: u64,
+    /// HEAD requests used to measure round-trip time and jitter
+    pub rtt_samples: usize,
     /// Number of baseline requests for comparison
     pub baseline_requests: usize,
     /// Number of test requests with suspicious patterns
     pub test_requests: usize,
+    /// p-value below which the test requests count as slower (default: 0.01)
+    pub significance: f64,
+    /// Smallest delay reported however significant; the measured jitter raises it (default: 5ms)
+    pub min_delay_ms: f64,
+    /// Pause between requests
+    pub request_interval: Duration,
     /// Timeout for individual requests
     pub request_timeout: Duration,
 }
@@ -47,323 +77,274 @@ pub struct TimingConfig {
 impl Default for TimingConfig {
     fn default() -> Self {
         Self {
-            min_waf_delay_ms: 50,
-            max_waf_delay_ms: 200,
-            baseline_requests: 3,
-            test_requests: 3,
+            rtt_samples: 5,
+            baseline_requests: 10,
+            test_requests: 10,
+            significance: 0.01,
+            min_delay_ms: 5.0,
+            request_interval: Duration::from_millis(100),
             request_timeout: Duration::from_secs(5),
         }
     }
 }
 
 /// Timing analyzer for WAF detection
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TimingAnalyzer {
     config: TimingConfig,
-    http_client: reqwest::Client,
+    http_client: Arc<HttpClient>,
 }
 
 impl TimingAnalyzer {
-    pub fn new(config: TimingConfig) -> Self {
-        let http_client = reqwest::Client::builder()
-            .timeout(config.request_timeout)
-            .build()
-            .unwrap();
-            
+    /// Analyzer with a client of its own, timing out after `config.request_timeout`
+    pub fn new(config: TimingConfig) -> Result<Self> {
+        let client = HttpClient::with_settings(config.request_timeout, None)?;
+        Ok(Self::with_client(config, client))
+    }
+
+    /// Analyzer sending through the scan's `client`: its proxy, user agent, cookie
+    /// session and rate limiter apply to timing requests too. Retries are turned off,
+    /// since a retried request would be timed as one slow response.
+    pub fn with_client(config: TimingConfig, client: HttpClient) -> Self {
         Self {
             config,
-            http_client,
+            http_client: Arc::new(client.with_retry_policy(RetryPolicy::none())),
         }
     }
 
+    pub fn config(&self) -> &TimingConfig {
+        &self.config
+    }
+
+    /// Run `request`, failing once `request_timeout` passes whatever the client's own timeout
+    async fn timed<F>(&self, url: &str, request: F) -> Result<f64>
+    where
+        F: std::future::Future<Output = Result<HttpResponse>>,
+    {
+        let start = Instant::now();
+        tokio::time::timeout(self.config.request_timeout, request)
+            .await
+            .map_err(|_| anyhow!("Timing request to {} timed out after {}ms", url, self.config.request_timeout.as_millis()))??;
+        Ok(elapsed_ms(start))
+    }
+
     /// Perform timing analysis on a URL
     pub async fn analyze(&self, url: &str) -> Result<Vec<Evidence>> {
-        let mut evidence = Vec::new();
-        
-        // Perform baseline comparison
-        if let Ok(baseline_analysis) = self.baseline_comparison(url).await {
-            if baseline_analysis.delay_detected {
-                evidence.push(Evidence {
-                    method_type: MethodType::Timing,
-                    confidence: baseline_analysis.confidence,
-                    description: format!(
-                        "WAF processing delay detected: {}ms (technique: {:?})",
-                        baseline_analysis.delay_amount_ms,
-                        baseline_analysis.technique_used
-                    ),
-                    raw_data: format!(
-                        "baseline: {}ms, test: {}ms, delay: {}ms",
-                        baseline_analysis.baseline_time_ms,
-                        baseline_analysis.test_time_ms,
-                        baseline_analysis.delay_amount_ms
-                    ),
-                    signature_matched: "timing-waf-delay".to_string(),
-                });
-            }
+        let analysis = self.measure(url).await?;
+        if !analysis.delay_detected(self.config.significance, self.config.min_delay_ms) {
+            return Ok(Vec::new());
         }
-        
-        // Perform pattern analysis
-        if let Ok(pattern_analysis) = self.pattern_analysis(url).await {
-            if pattern_analysis.delay_detected {
-                evidence.push(Evidence {
-                    method_type: MethodType::Timing,
-                    confidence: pattern_analysis.confidence,
-                    description: format!(
-                        "WAF timing pattern detected: {}ms consistent delay",
-                        pattern_analysis.delay_amount_ms
-                    ),
-                    raw_data: format!(
-                        "pattern_detected: {}ms average delay",
-                        pattern_analysis.delay_amount_ms
-                    ),
-                    signature_matched: "timing-pattern-analysis".to_string(),
-                });
-            }
-        }
-        
-        Ok(evidence)
+        Ok(vec![Evidence {
+            method_type: MethodType::Timing,
+            confidence: analysis.confidence,
+            description: format!(
+                "WAF processing delay detected: {:.1}ms on suspicious requests (p = {:.4}, technique: {:?})",
+                analysis.delay_amount_ms,
+                analysis.p_value,
+                analysis.technique_used
+            ),
+            raw_data: format!(
+                "rtt: {:.1}ms ± {:.1}ms, baseline: {:.1}ms, test: {:.1}ms, delay: {:.1}ms, U = {:.1}, p = {:.4}, n = {}",
+                analysis.rtt_ms,
+                analysis.jitter_ms,
+                analysis.baseline_time_ms,
+                analysis.test_time_ms,
+                analysis.delay_amount_ms,
+                analysis.u_statistic,
+                analysis.p_value,
+                analysis.samples
+            ),
+            signature_matched: "timing-waf-delay".to_string(),
+        }])
     }
 
-    /// Compare baseline (normal) vs test (suspicious) request timing
-    async fn baseline_comparison(&self, url: &str) -> Result<TimingAnalysis> {
-        // Measure baseline response times with normal requests
-        let baseline_times = self.measure_baseline_requests(url).await?;
-        let baseline_avg = baseline_times.iter().sum::<u64>() / baseline_times.len() as u64;
-        
-        // Measure test response times with suspicious patterns
-        let test_times = self.measure_test_requests(url).await?;
-        let test_avg = test_times.iter().sum::<u64>() / test_times.len() as u64;
-        
-        let delay_amount = if test_avg > baseline_avg {
-            test_avg - baseline_avg
-        } else {
-            0
-        };
-        
-        let delay_detected = delay_amount >= self.config.min_waf_delay_ms && 
-                           delay_amount <= self.config.max_waf_delay_ms;
-        
-        // Calculate confidence based on delay amount and consistency
-        let confidence = if delay_detected {
-            let delay_score = (delay_amount as f64 - self.config.min_waf_delay_ms as f64) / 
-                            (self.config.max_waf_delay_ms as f64 - self.config.min_waf_delay_ms as f64);
-            
-            // Check consistency across multiple requests
-            let baseline_variance = self.calculate_variance(&baseline_times, baseline_avg);
-            let test_variance = self.calculate_variance(&test_times, test_avg);
-            let consistency_score = 1.0 - (baseline_variance + test_variance) / 2.0;
-            
-            (delay_score * 0.7 + consistency_score * 0.3).min(0.95)
-        } else {
-            0.0
-        };
-        
-        Ok(TimingAnalysis {
-            baseline_time_ms: baseline_avg,
-            test_time_ms: test_avg,
-            delay_detected,
-            delay_amount_ms: delay_amount,
-            confidence,
-            technique_used: TimingTechnique::BaselineComparison,
-        })
+    /// Baseline the round-trip time, then compare benign and suspicious request times
+    pub async fn measure(&self, url: &str) -> Result<TimingAnalysis> {
+        let rtt = self.measure_rtt(url).await?;
+        let (baseline, test) = self.measure_requests(url).await?;
+        Ok(self.compare(&rtt, &baseline, &test))
     }
 
-    /// Analyze timing patterns across multiple requests
-    async fn pattern_analysis(&self, url: &str) -> Result<TimingAnalysis> {
-        // Make multiple requests and look for consistent timing patterns
-        let mut all_times = Vec::new();
-        
-        for _ in 0..self.config.baseline_requests + self.config.test_requests {
-            let start = Instant::now();
-            let _ = self.http_client.get(url).send().await?;
-            let elapsed = start.elapsed().as_millis() as u64;
-            all_times.push(elapsed);
-            
-            // Small delay between requests
-            tokio::time::sleep(Duration::from_millis(100)).await;
-        }
-        
-        let avg_time = all_times.iter().sum::<u64>() / all_times.len() as u64;
-        let variance = self.calculate_variance(&all_times, avg_time);
-        
-        // Look for consistent delays that might indicate WAF processing
-        let delay_detected = avg_time >= self.config.min_waf_delay_ms && 
-                           avg_time <= 1000 && // Reasonable upper bound
-                           variance < 0.3; // Low variance indicates consistent processing
-        
-        let confidence = if delay_detected {
-            let time_score = if avg_time <= self.config.max_waf_delay_ms { 0.8 } else { 0.4 };
-            let consistency_score = 1.0 - variance;
-            (time_score * 0.6 + consistency_score * 0.4).min(0.90)
-        } else {
-            0.0
+    fn compare(&self, rtt: &[f64], baseline: &[f64], test: &[f64]) -> TimingAnalysis {
+        let result = stats::mann_whitney_greater(test, baseline);
+        let mut analysis = TimingAnalysis {
+            rtt_ms: stats::median(rtt),
+            jitter_ms: stats::median_absolute_deviation(rtt),
+            baseline_time_ms: stats::median(baseline),
+            test_time_ms: stats::median(test),
+            delay_amount_ms: stats::hodges_lehmann(test, baseline).max(0.0),
+            u_statistic: result.u,
+            p_value: result.p_value,
+            samples: baseline.len() + test.len(),
+            confidence: 0.0,
+            technique_used: TimingTechnique::BaselineComparison,
         };
-        
-        Ok(TimingAnalysis {
-            baseline_time_ms: avg_time,
-            test_time_ms: avg_time,
-            delay_detected,
-            delay_amount_ms: if delay_detected { avg_time } else { 0 },
-            confidence,
-            technique_used: TimingTechnique::PatternAnalysis,
-        })
+        if analysis.delay_detected(self.config.significance, self.config.min_delay_ms) {
+            // 0.6 at the significance threshold, approaching 0.9 as the p-value shrinks
+            analysis.confidence = (0.6 + 0.3 * (1.0 - analysis.p_value / self.config.significance)).min(0.9);
+        }
+        analysis
     }
 
-    /// Measure baseline request times with normal requests
-    async fn measure_baseline_requests(&self, url: &str) -> Result<Vec<u64>> {
-        let mut times = Vec::new();
-        
-        for _ in 0..self.config.baseline_requests {
-            let start = Instant::now();
-            let _response = self.http_client
-                .get(url)
-                .header("User-Agent", "Mozilla/5.0 (compatible; WAF-Detector/1.0)")
-                .send()
-                .await?;
-            let elapsed = start.elapsed().as_millis() as u64;
-            times.push(elapsed);
-            
-            // Small delay between requests to avoid rate limiting
-            tokio::time::sleep(Duration::from_millis(200)).await;
+    /// Round-trip times of HEAD requests, which the server answers without a body
+    async fn measure_rtt(&self, url: &str) -> Result<Vec<f64>> {
+        let mut times = Vec::with_capacity(self.config.rtt_samples);
+        for _ in 0..self.config.rtt_samples {
+            times.push(self.timed(url, self.http_client.head(url)).await?);
+            tokio::time::sleep(self.config.request_interval).await;
         }
-        
         Ok(times)
     }
 
-    /// Measure test request times with suspicious patterns that might trigger WAF
-    async fn measure_test_requests(&self, url: &str) -> Result<Vec<u64>> {
-        let mut times = Vec::new();
-        
-        // Test with suspicious user agents and headers that might trigger WAF analysis
-        let test_patterns = vec![
+    /// Times of benign and suspicious requests, alternated so that changing network
+    /// conditions affect both samples alike
+    async fn measure_requests(&self, url: &str) -> Result<(Vec<f64>, Vec<f64>)> {
+        // Suspicious user agents and headers that might trigger WAF analysis
+        let test_patterns = [
             ("User-Agent", "sqlmap/1.0"),
             ("User-Agent", "Nikto/2.0"),
             ("X-Forwarded-For", "1.1.1.1"),
             ("X-Real-IP", "127.0.0.1"),
         ];
-        
-        for i in 0..self.config.test_requests {
-            let pattern = &test_patterns[i % test_patterns.len()];
-            
-            let start = Instant::now();
-            let _response = self.http_client
-                .get(url)
-                .header(pattern.0, pattern.1)
-                .send()
-                .await?;
-            let elapsed = start.elapsed().as_millis() as u64;
-            times.push(elapsed);
-            
-            // Small delay between requests
-            tokio::time::sleep(Duration::from_millis(200)).await;
-        }
-        
-        Ok(times)
-    }
+        let user_agent = [("User-Agent", "Mozilla/5.0 (compatible; WAF-Detector/1.0)")];
+        let benign: &[(&str, &str)] = if crate::profiles::configured().is_some() { &[] } else { &user_agent };
 
-    /// Calculate variance normalized to 0-1 scale
-    fn calculate_variance(&self, times: &[u64], mean: u64) -> f64 {
-        if times.len() <= 1 {
-            return 0.0;
-        }
-        
-        let variance: f64 = times.iter()
-            .map(|&time| {
-                let diff = time as f64 - mean as f64;
-                diff * diff
-            })
-            .sum::<f64>() / times.len() as f64;
-        
-        let std_dev = variance.sqrt();
-        
-        // Normalize to 0-1 scale (coefficient of variation)
-        if mean > 0 {
-            (std_dev / mean as f64).min(1.0)
-        } else {
-            0.0
+        let mut baseline = Vec::with_capacity(self.config.baseline_requests);
+        let mut test = Vec::with_capacity(self.config.test_requests);
+        let mut next_pattern = test_patterns.iter().cycle();
+        while baseline.len() < self.config.baseline_requests || test.len() < self.config.test_requests {
+            let suspicious = test.len() < self.config.test_requests && (test.len() < baseline.len() || baseline.len() == self.config.baseline_requests);
+            let headers: &[(&str, &str)] = match suspicious {
+                true => std::slice::from_ref(next_pattern.next().expect("cycle never ends")),
+                false => benign,
+            };
+
+            let elapsed = self.timed(url, self.http_client.get_with_headers(url, headers)).await?;
+            if suspicious {
+                test.push(elapsed);
+            } else {
+                baseline.push(elapsed);
+            }
+
+            tokio::time::sleep(self.config.request_interval).await;
         }
+        Ok((baseline, test))
     }
 }
 
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    fn quick_config() -> TimingConfig {
+        TimingConfig { rtt_samples: 3, baseline_requests: 8, test_requests: 8, request_interval: Duration::ZERO, ..TimingConfig::default() }
+    }
+
     #[test]
     fn test_timing_config_default() {
         let config = TimingConfig::default();
-        assert_eq!(config.min_waf_delay_ms, 50);
-        assert_eq!(config.max_waf_delay_ms, 200);
-        assert_eq!(config.baseline_requests, 3);
-        assert_eq!(config.test_requests, 3);
+        assert_eq!(config.rtt_samples, 5);
+        assert_eq!(config.baseline_requests, 10);
+        assert_eq!(config.test_requests, 10);
+        assert_eq!(config.significance, 0.01);
         assert_eq!(config.request_timeout, Duration::from_secs(5));
     }
     
     #[test]
     fn test_timing_analyzer_creation() {
         let config = TimingConfig::default();
-        let analyzer = TimingAnalyzer::new(config);
-        assert_eq!(analyzer.config.min_waf_delay_ms, 50);
+        let analyzer = TimingAnalyzer::new(config).unwrap();
+        assert_eq!(analyzer.config.min_delay_ms, 5.0);
     }
-    
+
     #[test]
-    fn test_calculate_variance() {
-        let config = TimingConfig::default();
-        let analyzer = TimingAnalyzer::new(config);
-        
-        // Test with consistent times (low variance)
-        let consistent_times = vec![100, 102, 98, 101, 99];
-        let mean = 100;
-        let variance = analyzer.calculate_variance(&consistent_times, mean);
-        assert!(variance < 0.1, "Consistent times should have low variance");
-        
-        // Test with inconsistent times (high variance)
-        let inconsistent_times = vec![50, 150, 75, 200, 25];
-        let mean = 100;
-        let variance = analyzer.calculate_variance(&inconsistent_times, mean);
-        assert!(variance > 0.3, "Inconsistent times should have high variance");
+    fn test_slow_network_is_not_a_waf() {
+        // 400ms round trips with the old 50-200ms window in mind: both samples are just slow
+        let analyzer = TimingAnalyzer::new(quick_config()).unwrap();
+        let rtt = [400.0, 410.0, 395.0];
+        let baseline = [480.0, 530.0, 455.0, 610.0, 470.0, 520.0, 500.0, 495.0];
+        let test = [470.0, 540.0, 505.0, 460.0, 600.0, 515.0, 490.0, 525.0];
+        let analysis = analyzer.compare(&rtt, &baseline, &test);
+        assert!(analysis.p_value > 0.1, "{:?}", analysis);
+        assert!(!analysis.delay_detected(0.01, 5.0));
+        assert_eq!(analysis.confidence, 0.0);
     }
-    
+
     #[test]
-    fn test_timing_analysis_delay_detection() {
-        let analysis = TimingAnalysis {
-            baseline_time_ms: 100,
-            test_time_ms: 170,
-            delay_detected: true,
-            delay_amount_ms: 70,
-            confidence: 0.85,
-            technique_used: TimingTechnique::BaselineComparison,
-        };
-        
-        assert!(analysis.delay_detected);
-        assert_eq!(analysis.delay_amount_ms, 70);
-        assert!(analysis.confidence > 0.8);
+    fn test_fast_waf_delay_is_detected() {
+        // A 15ms inspection delay, below the old 50ms floor, on a low-jitter network
+        let analyzer = TimingAnalyzer::new(quick_config()).unwrap();
+        let rtt = [20.0, 21.0, 20.5];
+        let baseline = [30.0, 31.0, 29.5, 30.5, 32.0, 30.2, 29.8, 31.5];
+        let test = [45.0, 46.5, 44.0, 47.0, 45.5, 44.8, 46.0, 45.2];
+        let analysis = analyzer.compare(&rtt, &baseline, &test);
+        assert!(analysis.p_value < 0.001, "{:?}", analysis);
+        assert!((analysis.delay_amount_ms - 15.0).abs() < 1.0);
+        assert!(analysis.confidence > 0.85 && analysis.confidence <= 0.9);
+
+        // The same shift is not reported when the network jitters by more than that
+        let jittery = analyzer.compare(&[10.0, 40.0, 70.0], &baseline, &test);
+        assert!(!jittery.delay_detected(0.01, 5.0));
     }
-    
-    #[test]
-    fn test_timing_technique_equality() {
-        assert_eq!(TimingTechnique::BaselineComparison, TimingTechnique::BaselineComparison);
-        assert_ne!(TimingTechnique::BaselineComparison, TimingTechnique::PatternAnalysis);
+
+    #[tokio::test]
+    async fn test_delay_on_suspicious_requests_is_measured() {
+        let mut server = mockito::Server::new_async().await;
+        let _head = server.mock("HEAD", "/").create_async().await;
+        let _get = server
+            .mock("GET", "/")
+            .with_body_from_request(|request| {
+                let user_agent = request.header("user-agent").first().and_then(|value| value.to_str().ok()).unwrap_or_default().to_string();
+                if user_agent.contains("sqlmap") || user_agent.contains("Nikto") || request.has_header("x-forwarded-for") || request.has_header("x-real-ip") {
+                    std::thread::sleep(Duration::from_millis(40));
+                }
+                b"ok".to_vec()
+            })
+            .create_async()
+            .await;
+
+        let analyzer = TimingAnalyzer::new(quick_config()).unwrap();
+        let analysis = analyzer.measure(&server.url()).await.unwrap();
+        assert_eq!(analysis.samples, 16);
+        assert!(analysis.p_value < 0.01, "{:?}", analysis);
+        let evidence = analyzer.analyze(&server.url()).await.unwrap();
+        assert_eq!(evidence[0].signature_matched, "timing-waf-delay");
+        assert!(evidence[0].raw_data.contains("p = "));
     }
-    
+
     #[tokio::test]
-    async fn test_timing_analyzer_with_mock_data() {
-        // This would be a more comprehensive test with mocked HTTP responses
-        // Testing the core logic without network calls
-        let config = TimingConfig {
-            min_waf_delay_ms: 50,
-            max_waf_delay_ms: 200,
-            baseline_requests: 2,
-            test_requests: 2,
-            request_timeout: Duration::from_secs(1),
-        };
-        
-        let analyzer = TimingAnalyzer::new(config);
-        
-        // Test variance calculation with known data
-        let times = vec![100, 105, 95, 102];
-        let mean = 100;
-        let variance = analyzer.calculate_variance(&times, mean);
-        assert!(variance < 0.2, "Small variance should be detected correctly");
+    async fn test_requests_go_through_the_shared_client() {
+        let mut server = mockito::Server::new_async().await;
+        let _head = server.mock("HEAD", "/").create_async().await;
+        let _get = server.mock("GET", "/").with_status(503).create_async().await;
+
+        // Retries are off for timing, even when the scan's client retries 503s
+        let client = HttpClient::new().unwrap().with_retry_policy(RetryPolicy::with_retries(3));
+        let analyzer = TimingAnalyzer::with_client(quick_config(), client.clone());
+        analyzer.measure(&server.url()).await.unwrap();
+        assert_eq!(client.request_count(), 3 + 8 + 8);
+        assert_eq!(client.retry_count(), 0);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_request_timeout_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        let _head = server
+            .mock("HEAD", "/")
+            .with_body_from_request(|_| {
+                std::thread::sleep(Duration::from_millis(300));
+                Vec::new()
+            })
+            .create_async()
+            .await;
+
+        let config = TimingConfig { request_timeout: Duration::from_millis(50), ..quick_config() };
+        let analyzer = TimingAnalyzer::with_client(config, HttpClient::new().unwrap());
+        let error = analyzer.analyze(&server.url()).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{}", error);
+    }
+}
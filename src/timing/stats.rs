@@ -0,0 +1,162 @@
+//! Rank statistics for comparing response-time samples
+//!
+//! Response times are skewed and heavy-tailed (a single retransmit adds hundreds of
+//! milliseconds), so the timing analyzer compares samples by rank rather than by mean.
+
+/// Samples up to this size without ties get an exact p-value; larger ones use the
+/// normal approximation
+const EXACT_LIMIT: usize = 25;
+
+/// Result of a one-sided Mann-Whitney U test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// Pairs in which the first sample is larger (ties count half)
+    pub u: f64,
+    /// Chance of a `u` at least this large if both samples come from one distribution
+    pub p_value: f64,
+}
+
+pub fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Median distance from the median; unlike the standard deviation, one outlier barely moves it
+pub fn median_absolute_deviation(values: &[f64]) -> f64 {
+    let center = median(values);
+    let deviations: Vec<f64> = values.iter().map(|value| (value - center).abs()).collect();
+    median(&deviations)
+}
+
+/// Hodges-Lehmann estimate of how far `x` is shifted above `y`: the median of all
+/// pairwise differences
+pub fn hodges_lehmann(x: &[f64], y: &[f64]) -> f64 {
+    let differences: Vec<f64> = x.iter().flat_map(|a| y.iter().map(move |b| a - b)).collect();
+    median(&differences)
+}
+
+/// Test whether `x` tends to be larger than `y`
+pub fn mann_whitney_greater(x: &[f64], y: &[f64]) -> MannWhitney {
+    let (m, n) = (x.len(), y.len());
+    if m == 0 || n == 0 {
+        return MannWhitney { u: 0.0, p_value: 1.0 };
+    }
+
+    // Average ranks over x and y together; tied values share the mean of their ranks
+    let mut pooled: Vec<(f64, bool)> = x.iter().map(|&v| (v, true)).chain(y.iter().map(|&v| (v, false))).collect();
+    pooled.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut rank_sum = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < pooled.len() {
+        let end = start + pooled[start..].iter().take_while(|(value, _)| *value == pooled[start].0).count();
+        let rank = (start + end + 1) as f64 / 2.0;
+        rank_sum += rank * pooled[start..end].iter().filter(|(_, from_x)| *from_x).count() as f64;
+        let tied = (end - start) as f64;
+        tie_term += tied * tied * tied - tied;
+        start = end;
+    }
+    let u = rank_sum - (m * (m + 1)) as f64 / 2.0;
+
+    let p_value = if tie_term == 0.0 && m <= EXACT_LIMIT && n <= EXACT_LIMIT {
+        exact_upper_tail(m, n, u.round() as usize)
+    } else {
+        let total = (m + n) as f64;
+        let variance = (m * n) as f64 / 12.0 * ((total + 1.0) - tie_term / (total * (total - 1.0)));
+        if variance <= 0.0 {
+            1.0
+        } else {
+            let z = (u - (m * n) as f64 / 2.0 - 0.5) / variance.sqrt();
+            0.5 * erfc(z / std::f64::consts::SQRT_2)
+        }
+    };
+    MannWhitney { u, p_value: p_value.clamp(0.0, 1.0) }
+}
+
+/// P(U >= u) for samples of `m` and `n` distinct values, counting every ordering of the
+/// pooled sample that gives each U
+fn exact_upper_tail(m: usize, n: usize, u: usize) -> f64 {
+    // counts[j][k]: orderings of i x-values and j y-values with U = k, for the current i.
+    // Whichever value is largest decides the step: an x beats all j y-values.
+    let mut previous: Vec<Vec<f64>> = (0..=n).map(|_| vec![1.0]).collect();
+    for i in 1..=m {
+        let mut current: Vec<Vec<f64>> = Vec::with_capacity(n + 1);
+        current.push(vec![1.0]);
+        for j in 1..=n {
+            let mut counts = vec![0.0; i * j + 1];
+            for (k, count) in counts.iter_mut().enumerate() {
+                let x_largest = k.checked_sub(j).and_then(|rest| previous[j].get(rest)).copied().unwrap_or(0.0);
+                let y_largest = current[j - 1].get(k).copied().unwrap_or(0.0);
+                *count = x_largest + y_largest;
+            }
+            current.push(counts);
+        }
+        previous = current;
+    }
+    let counts = &previous[n];
+    let total: f64 = counts.iter().sum();
+    counts.iter().skip(u).sum::<f64>() / total
+}
+
+/// Complementary error function (Chebyshev fit, relative error below 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let result = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robust_summaries() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(median_absolute_deviation(&[10.0, 11.0, 9.0, 10.0, 500.0]), 1.0);
+        assert_eq!(hodges_lehmann(&[20.0, 21.0, 22.0], &[10.0, 11.0, 12.0]), 10.0);
+    }
+
+    #[test]
+    fn test_exact_p_values() {
+        // 1 of the C(6, 3) = 20 orderings puts every x above every y
+        let result = mann_whitney_greater(&[4.0, 5.0, 6.0], &[1.0, 2.0, 3.0]);
+        assert_eq!(result.u, 9.0);
+        assert!((result.p_value - 0.05).abs() < 1e-12);
+        assert_eq!(mann_whitney_greater(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).p_value, 1.0);
+        // U >= 8 happens in 2 of 20 orderings
+        assert!((mann_whitney_greater(&[3.0, 5.0, 6.0], &[1.0, 2.0, 4.0]).p_value - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_normal_approximation_agrees_with_exact() {
+        let x: Vec<f64> = (0..30).map(|i| 100.0 + i as f64 * 1.5).collect();
+        let y: Vec<f64> = (0..30).map(|i| 95.0 + i as f64 * 1.5 + 0.25).collect();
+        let approximate = mann_whitney_greater(&x, &y).p_value;
+        let exact = exact_upper_tail(30, 30, mann_whitney_greater(&x, &y).u as usize);
+        assert!((approximate - exact).abs() < 0.01, "{} vs {}", approximate, exact);
+
+        // Ties go through the approximation; all-equal samples carry no evidence
+        assert_eq!(mann_whitney_greater(&[5.0; 4], &[5.0; 4]).p_value, 1.0);
+        assert!(mann_whitney_greater(&[2.0, 2.0, 3.0, 3.0], &[1.0, 1.0, 2.0, 2.0]).p_value < 0.1);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod table;
+
 use url::Url;
 use std::time::Duration;
+use unicode_width::UnicodeWidthChar;
 
 /// Validate and normalize URL
 pub fn validate_url(url: &str) -> anyhow::Result<String> {
@@ -50,6 +53,85 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// Standard base64 with padding
+pub fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard base64; padding is optional and whitespace (line wrapping) is skipped
+pub fn base64_decode(encoded: &str) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes().filter(|c| !c.is_ascii_whitespace()).take_while(|&c| c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(anyhow::anyhow!("invalid base64 character '{}'", c as char)),
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Display width of `text` in terminal columns (wide CJK and emoji count as two)
+pub fn display_width(text: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(text)
+}
+
+/// Left-align `text` in a `width`-column field
+pub fn pad_display(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+/// Shorten `text` to at most `max_width` columns, ending in "..." when cut.
+///
+/// Never splits a character, keeps combining marks with their base character and
+/// drops a dangling zero-width joiner, so emoji sequences are not left half-drawn.
+pub fn truncate_display(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    let budget = max_width.saturating_sub(3);
+    let mut out = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if w > 0 && width + w > budget {
+            break;
+        }
+        width += w;
+        out.push(c);
+    }
+    while out.ends_with('\u{200d}') {
+        out.pop();
+    }
+    out.push_str(&".".repeat(max_width.min(3)));
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +169,35 @@ mod tests {
         assert_eq!(format_duration(Duration::from_millis(500)), "500ms");
         assert_eq!(format_duration(Duration::from_secs(2)), "2.0s");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_base64_padding() {
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"HTTP/1.1 200 OK\r\n\r\n\xff\x00"] {
+            assert_eq!(base64_decode(&base64_encode(data)).unwrap(), data);
+        }
+        assert_eq!(base64_decode("Zm9v\nYmFy").unwrap(), b"foobar");
+        assert_eq!(base64_decode("Zg").unwrap(), b"f");
+        assert!(base64_decode("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn test_truncate_display_is_char_safe() {
+        assert_eq!(truncate_display("https://example.com", 40), "https://example.com");
+        assert_eq!(truncate_display("https://münchen.de/straße", 12), "https://m...");
+        assert_eq!(truncate_display("https://münchen.de/straße", 13), "https://mü...");
+        // Wide characters take two columns and are never split
+        let cut = truncate_display("https://例え.テスト/パス", 14);
+        assert_eq!(cut, "https://例...");
+        assert!(display_width(&cut) <= 14);
+        // A combining accent stays with its letter, a trailing joiner is dropped
+        assert_eq!(truncate_display("cafe\u{301}-latte", 7), "cafe\u{301}...");
+        assert_eq!(truncate_display("👩\u{200d}👩vwxyz", 6), "👩...");
+    }
+}
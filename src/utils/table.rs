@@ -0,0 +1,108 @@
+//! Boxed text tables for the CLI and `DetectionResult::format_as_table`
+//!
+//! Widths are measured in terminal columns rather than bytes, so multi-byte and wide
+//! text lines up. The box grows with its longest line up to the terminal width; past
+//! that, lines are truncated with "...".
+
+use tabled::builder::Builder;
+use tabled::grid::config::HorizontalLine;
+use tabled::settings::object::Rows;
+use tabled::settings::themes::Theme;
+use tabled::settings::{Alignment, Style, Width};
+
+/// Width of the box when every line is short
+pub const MIN_WIDTH: usize = 75;
+
+/// Width cap when the terminal width is unknown
+pub const DEFAULT_MAX_WIDTH: usize = 120;
+
+/// Terminal width from `COLUMNS`, falling back to [`DEFAULT_MAX_WIDTH`]
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse::<usize>().ok())
+        .filter(|&columns| columns >= MIN_WIDTH)
+        .unwrap_or(DEFAULT_MAX_WIDTH)
+}
+
+/// A single-column box: a centered title, then lines split into sections
+pub struct BoxTable {
+    title: String,
+    rows: Vec<String>,
+    separators: Vec<usize>,
+    max_width: usize,
+}
+
+impl BoxTable {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), rows: Vec::new(), separators: Vec::new(), max_width: terminal_width() }
+    }
+
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width.max(MIN_WIDTH);
+        self
+    }
+
+    pub fn row(&mut self, line: impl Into<String>) -> &mut Self {
+        self.rows.push(line.into());
+        self
+    }
+
+    /// Start a new section: a rule is drawn before the next row
+    pub fn separator(&mut self) -> &mut Self {
+        if !self.rows.is_empty() && self.separators.last() != Some(&self.rows.len()) {
+            self.separators.push(self.rows.len());
+        }
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut builder = Builder::default();
+        builder.push_record([self.title.clone()]);
+        for row in &self.rows {
+            builder.push_record([row.clone()]);
+        }
+        let mut table = builder.build();
+
+        let rule = HorizontalLine::full('─', '┼', '├', '┤');
+        let mut theme = Theme::from_style(Style::modern().remove_horizontal());
+        if !self.rows.is_empty() {
+            theme.insert_horizontal_line(1, rule);
+        }
+        // Line `i` sits above table row `i`; the title is row 0
+        for &before in self.separators.iter().filter(|&&before| before < self.rows.len()) {
+            theme.insert_horizontal_line(before + 1, rule);
+        }
+
+        table
+            .with(theme)
+            .modify(Rows::first(), Alignment::center())
+            .with(Width::increase(MIN_WIDTH))
+            .with(Width::truncate(self.max_width).suffix("..."));
+        let mut out = table.to_string();
+        out.push('\n');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::display_width;
+
+    #[test]
+    fn test_box_lines_share_one_width() {
+        let mut table = BoxTable::new("Results").with_max_width(80);
+        table.row("URL: https://münchen.de/日本語");
+        table.separator();
+        table.row("WAF: Cloudflare 🛡️");
+        table.row(format!("URL: https://example.com/{}", "ü".repeat(200)));
+        let rendered = table.render();
+
+        let widths: Vec<usize> = rendered.lines().map(display_width).collect();
+        assert!(widths.iter().all(|&w| w == widths[0]), "ragged box: {:?}\n{}", widths, rendered);
+        assert!(widths[0] >= MIN_WIDTH && widths[0] <= 80);
+        assert!(rendered.contains("..."));
+        assert_eq!(rendered.lines().filter(|line| line.starts_with('├')).count(), 2);
+    }
+}
@@ -0,0 +1,122 @@
+//! Localhost API for tool integrations (`--api-only`)
+//!
+//! Backs Burp Suite or Caido extensions: no dashboard, history or schedules, just
+//!
+//! - `POST /api/passive`: `{"url", "response" | "response_base64"}` with a raw HTTP
+//!   response the proxy already has; passive detection only, nothing is sent
+//! - `POST /api/detect`: `{"host"}` (a host name or URL); full detection
+//! - `GET /api/status`
+//!
+//! The server listens on 127.0.0.1 only and rejects requests whose `Host` is not a
+//! loopback name, so web pages cannot reach it through DNS rebinding. Bodies must be
+//! JSON, which browsers cannot send cross-origin without a preflight this API never
+//! answers.
+
+use super::{server_status, ScanResponse, WebServer};
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+
+/// Largest raw response accepted by `/api/passive`
+pub const MAX_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct PassiveRequest {
+    /// URL the response was received from
+    pub url: String,
+    /// Raw response as text
+    #[serde(default)]
+    pub response: Option<String>,
+    /// Raw response bytes, base64 encoded (binary bodies)
+    #[serde(default)]
+    pub response_base64: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetectRequest {
+    /// Host name or URL; a bare host is scanned over https
+    pub host: String,
+}
+
+pub fn routes() -> Router<WebServer> {
+    Router::new()
+        .route("/api/passive", post(passive))
+        .route("/api/detect", post(detect))
+        .route("/api/status", get(server_status))
+        .layer(axum::extract::DefaultBodyLimit::max(MAX_RESPONSE_BYTES * 2))
+}
+
+/// Whether `host` (a `Host` header value) names the loopback interface
+pub fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, port)| if port.chars().all(|c| c.is_ascii_digit()) { name } else { host }),
+    };
+    name.eq_ignore_ascii_case("localhost") || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+pub async fn require_loopback_host(request: Request, next: Next) -> Response {
+    let host = request.headers().get(header::HOST).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    if !is_loopback_host(host) {
+        return (StatusCode::FORBIDDEN, "Host must be localhost").into_response();
+    }
+    next.run(request).await
+}
+
+fn failure(status: StatusCode, error: String) -> (StatusCode, Json<ScanResponse>) {
+    (status, Json(ScanResponse { success: false, result: None, error: Some(error) }))
+}
+
+async fn passive(State(server): State<WebServer>, Json(payload): Json<PassiveRequest>) -> impl IntoResponse {
+    let raw = match (payload.response, payload.response_base64) {
+        (Some(text), None) => text.into_bytes(),
+        (None, Some(encoded)) => match crate::utils::base64_decode(&encoded) {
+            Ok(raw) => raw,
+            Err(e) => return failure(StatusCode::BAD_REQUEST, format!("response_base64: {}", e)),
+        },
+        _ => return failure(StatusCode::BAD_REQUEST, "exactly one of response or response_base64 is required".to_string()),
+    };
+    if raw.len() > MAX_RESPONSE_BYTES {
+        return failure(StatusCode::PAYLOAD_TOO_LARGE, format!("response exceeds {} bytes", MAX_RESPONSE_BYTES));
+    }
+    let response = match crate::capture::parse_raw_response(&raw, &payload.url) {
+        Ok(response) => response,
+        Err(e) => return failure(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+    match server.engine.detect_from_response(&payload.url, response).await {
+        Ok(result) => (StatusCode::OK, Json(ScanResponse { success: true, result: Some(result), error: None })),
+        Err(e) => failure(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+async fn detect(State(server): State<WebServer>, Json(payload): Json<DetectRequest>) -> impl IntoResponse {
+    let url = match crate::idn::normalize_url(&payload.host) {
+        Ok(url) => url,
+        Err(e) => return failure(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+    match server.engine.detect(&url).await {
+        Ok(result) => (StatusCode::OK, Json(ScanResponse { success: true, result: Some(result), error: None })),
+        Err(e) => failure(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_hosts() {
+        for host in ["localhost", "localhost:8080", "127.0.0.1:9000", "[::1]:9000", "LOCALHOST"] {
+            assert!(is_loopback_host(host), "{}", host);
+        }
+        for host in ["", "evil.example", "evil.example:8080", "localhost.evil.example", "10.0.0.1:8080", "[::ffff:10.0.0.1]:80"] {
+            assert!(!is_loopback_host(host), "{}", host);
+        }
+    }
+}
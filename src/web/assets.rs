@@ -0,0 +1,65 @@
+//! `web/static`, compiled into the binary so `--web` works wherever it is run from
+
+use axum::{
+    extract::Path,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+/// An embedded file under `/static`
+#[derive(Debug, Clone, Copy)]
+pub struct Asset {
+    pub path: &'static str,
+    pub content_type: &'static str,
+    pub body: &'static [u8],
+}
+
+macro_rules! asset {
+    ($path:literal, $content_type:literal) => {
+        Asset { path: $path, content_type: $content_type, body: include_bytes!(concat!("../../web/static/", $path)) }
+    };
+}
+
+pub static ASSETS: &[Asset] = &[
+    asset!("dashboard.css", "text/css; charset=utf-8"),
+    asset!("dashboard.js", "text/javascript; charset=utf-8"),
+    asset!("api-docs.css", "text/css; charset=utf-8"),
+    asset!("styles.css", "text/css; charset=utf-8"),
+    asset!("app.js", "text/javascript; charset=utf-8"),
+    asset!("demo.html", "text/html; charset=utf-8"),
+];
+
+pub fn get(path: &str) -> Option<&'static Asset> {
+    ASSETS.iter().find(|asset| asset.path == path)
+}
+
+/// `GET /static/*path`
+pub async fn serve(Path(path): Path<String>) -> Response {
+    match get(&path) {
+        Some(asset) => (
+            [
+                (header::CONTENT_TYPE, HeaderValue::from_static(asset.content_type)),
+                // Assets change with the binary, so revalidate rather than cache for long
+                (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+            ],
+            asset.body,
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_static_file_is_embedded() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/web/static");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            assert!(get(&name).is_some(), "web/static/{} is not in ASSETS", name);
+        }
+        assert!(get("../Cargo.toml").is_none());
+    }
+}
@@ -0,0 +1,408 @@
+//! Access control for the web server
+//!
+//! Off unless API keys or users are configured (`--api-key` or an `--auth-config` YAML
+//! file). Clients then authenticate with `X-API-Key: <key>`, `Authorization: Bearer <key>`
+//! or HTTP basic auth; each credential gets its own token-bucket rate limit. State-changing
+//! requests and rejected attempts are appended to a JSON-lines audit log when one is set.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Largest request body read for the audit log, same as axum's `Json` default
+const MAX_AUDITED_BODY: usize = 2 * 1024 * 1024;
+/// Paths reachable without credentials, for load-balancer health checks
+const PUBLIC_PATHS: &[&str] = &["/api/status"];
+
+/// Contents of `--auth-config`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    /// Basic-auth accounts; browsers prompt for these, so they also work for the dashboard
+    #[serde(default)]
+    pub users: Vec<BasicUser>,
+    /// Limit for credentials without their own `requests_per_minute`; unlimited when unset
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+    /// Origins allowed to call the API from a browser once authentication is on; other
+    /// sites are refused instead of getting the permissive development CORS policy
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKey {
+    /// Shown in the audit log instead of the key
+    pub name: String,
+    pub key: String,
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BasicUser {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+}
+
+impl AuthConfig {
+    /// Load a YAML (or JSON) file and validate it
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read auth config '{}': {}", path.display(), e))?;
+        let config: AuthConfig = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid auth config '{}': {}", path.display(), e))?;
+        config.validate().map_err(|e| anyhow!("Invalid auth config '{}': {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// Add a key given on the command line, named after its position
+    pub fn add_key(&mut self, key: impl Into<String>) {
+        let name = format!("cli-key-{}", self.api_keys.len() + 1);
+        self.api_keys.push(ApiKey { name, key: key.into(), requests_per_minute: None });
+    }
+
+    /// Whether requests must carry credentials
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || !self.users.is_empty()
+    }
+
+    /// Names must be unique (they key the rate limits); keys and passwords non-empty
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for key in &self.api_keys {
+            if key.key.is_empty() {
+                return Err(anyhow!("API key '{}' is empty", key.name));
+            }
+            if !names.insert(format!("key:{}", key.name)) {
+                return Err(anyhow!("duplicate API key name '{}'", key.name));
+            }
+        }
+        for user in &self.users {
+            if user.username.is_empty() || user.username.contains(':') || user.password.is_empty() {
+                return Err(anyhow!("user '{}' needs a username without ':' and a password", user.username));
+            }
+            if !names.insert(format!("user:{}", user.username)) {
+                return Err(anyhow!("duplicate user '{}'", user.username));
+            }
+        }
+        for origin in &self.cors_origins {
+            if !(origin.starts_with("http://") || origin.starts_with("https://")) || HeaderValue::from_str(origin).is_err() {
+                return Err(anyhow!("cors_origins entry '{}' must be an origin like https://dashboard.example.com", origin));
+            }
+        }
+        let limits = self.api_keys.iter().map(|k| k.requests_per_minute).chain(self.users.iter().map(|u| u.requests_per_minute));
+        if limits.chain([self.requests_per_minute]).any(|limit| limit == Some(0)) {
+            return Err(anyhow!("requests_per_minute must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Who made a request, as recorded in the audit log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// `key:<name>`, `user:<username>` or `anonymous`
+    pub name: String,
+    requests_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub principal: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_addr: Option<String>,
+    pub method: String,
+    pub path: String,
+    /// `url`, `urls` and `targets` of the request body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    pub status: u16,
+}
+
+/// Shared authentication, rate-limit and audit state behind the middleware
+pub struct WebAuth {
+    config: AuthConfig,
+    buckets: DashMap<String, Bucket>,
+    audit_log: Option<Mutex<std::fs::File>>,
+}
+
+impl WebAuth {
+    pub fn new(config: AuthConfig) -> Result<Self> {
+        config.validate()?;
+        let audit_log = match &config.audit_log {
+            Some(path) => Some(Mutex::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| anyhow!("Failed to open audit log '{}': {}", path.display(), e))?,
+            )),
+            None => None,
+        };
+        Ok(Self { config, buckets: DashMap::new(), audit_log })
+    }
+
+    pub fn config(&self) -> &AuthConfig {
+        &self.config
+    }
+
+    /// The credential in `headers`; `None` when authentication is on and it is missing
+    /// or wrong
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<Principal> {
+        if !self.config.is_enabled() {
+            return Some(Principal { name: "anonymous".to_string(), requests_per_minute: None });
+        }
+        let default_limit = self.config.requests_per_minute;
+
+        let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()).unwrap_or("");
+        let (scheme, credentials) = authorization.split_once(' ').unwrap_or(("", ""));
+        let api_key = headers
+            .get("x-api-key")
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| scheme.eq_ignore_ascii_case("bearer").then_some(credentials.trim()));
+        if let Some(candidate) = api_key {
+            return self.config.api_keys.iter().find(|k| constant_time_eq(k.key.as_bytes(), candidate.as_bytes())).map(|k| Principal {
+                name: format!("key:{}", k.name),
+                requests_per_minute: k.requests_per_minute.or(default_limit),
+            });
+        }
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = base64_decode(credentials.trim())?;
+            let (username, password) = std::str::from_utf8(&decoded).ok()?.split_once(':')?;
+            return self
+                .config
+                .users
+                .iter()
+                .find(|u| u.username == username && constant_time_eq(u.password.as_bytes(), password.as_bytes()))
+                .map(|u| Principal {
+                    name: format!("user:{}", u.username),
+                    requests_per_minute: u.requests_per_minute.or(default_limit),
+                });
+        }
+        None
+    }
+
+    /// Take a token from the principal's bucket; `Err` holds the seconds until the next one
+    pub fn check_rate(&self, principal: &Principal, now: Instant) -> std::result::Result<(), u64> {
+        let Some(per_minute) = principal.requests_per_minute else { return Ok(()) };
+        let capacity = per_minute as f64;
+        let per_second = capacity / 60.0;
+
+        let mut bucket = self.buckets.entry(principal.name.clone()).or_insert(Bucket { tokens: capacity, updated: now });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / per_second).ceil() as u64)
+        }
+    }
+
+    fn audit(&self, entry: &AuditEntry) {
+        let Some(log) = &self.audit_log else { return };
+        let Ok(line) = serde_json::to_string(entry) else { return };
+        let mut file = log.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            tracing::warn!(error = %e, "failed to write audit log");
+        }
+    }
+}
+
+/// Middleware enforcing credentials and rate limits and writing the audit log
+pub async fn require_auth(State(auth): State<Arc<WebAuth>>, request: Request, next: Next) -> Response {
+    let remote_addr = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.to_string());
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let audited = method != Method::GET || path.starts_with("/ws/");
+    let entry = |principal: &str, targets: Vec<String>, status: StatusCode| AuditEntry {
+        timestamp: Utc::now(),
+        principal: principal.to_string(),
+        remote_addr: remote_addr.clone(),
+        method: method.to_string(),
+        path: path.clone(),
+        targets,
+        status: status.as_u16(),
+    };
+
+    if PUBLIC_PATHS.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+    let Some(principal) = auth.authenticate(request.headers()) else {
+        auth.audit(&entry("unauthenticated", Vec::new(), StatusCode::UNAUTHORIZED));
+        return unauthorized(&auth.config);
+    };
+    if let Err(retry_after) = auth.check_rate(&principal, Instant::now()) {
+        auth.audit(&entry(&principal.name, Vec::new(), StatusCode::TOO_MANY_REQUESTS));
+        let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded");
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+    if !audited || auth.audit_log.is_none() {
+        return next.run(request).await;
+    }
+
+    // Read the body to log the scanned targets, then hand it on unchanged
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_AUDITED_BODY).await else {
+        auth.audit(&entry(&principal.name, Vec::new(), StatusCode::PAYLOAD_TOO_LARGE));
+        return error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+    };
+    let targets = request_targets(&bytes);
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+    auth.audit(&entry(&principal.name, targets, response.status()));
+    response
+}
+
+fn unauthorized(config: &AuthConfig) -> Response {
+    let mut response = error_response(StatusCode::UNAUTHORIZED, "Missing or invalid credentials");
+    if !config.users.is_empty() {
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Basic realm=\"waf-detector\""));
+    }
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "success": false, "error": message }))).into_response()
+}
+
+/// Target URLs named in a JSON request body
+fn request_targets(body: &[u8]) -> Vec<String> {
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(body) else { return Vec::new() };
+    let mut targets: Vec<String> = value.get("url").and_then(|u| u.as_str()).map(str::to_string).into_iter().collect();
+    for field in ["urls", "targets"] {
+        if let Some(list) = value.get(field).and_then(|l| l.as_array()) {
+            targets.extend(list.iter().filter_map(|u| u.as_str()).map(str::to_string));
+        }
+    }
+    targets
+}
+
+/// Compare secrets without returning early on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim_end_matches('=');
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn auth(requests_per_minute: Option<u32>) -> WebAuth {
+        let mut config = AuthConfig { requests_per_minute, ..AuthConfig::default() };
+        config.add_key("s3cret");
+        config.users.push(BasicUser { username: "ops".to_string(), password: "hunter2".to_string(), requests_per_minute: None });
+        WebAuth::new(config).unwrap()
+    }
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_credentials() {
+        let auth = auth(None);
+        let name = |h: &HeaderMap| auth.authenticate(h).map(|p| p.name);
+
+        assert_eq!(name(&headers(header::HeaderName::from_static("x-api-key"), "s3cret")), Some("key:cli-key-1".to_string()));
+        assert_eq!(name(&headers(header::AUTHORIZATION, "Bearer s3cret")), Some("key:cli-key-1".to_string()));
+        // ops:hunter2
+        assert_eq!(name(&headers(header::AUTHORIZATION, "Basic b3BzOmh1bnRlcjI=")), Some("user:ops".to_string()));
+        assert_eq!(name(&headers(header::AUTHORIZATION, "Bearer wrong")), None);
+        assert_eq!(name(&headers(header::AUTHORIZATION, "Basic b3BzOndyb25n")), None);
+        assert_eq!(name(&HeaderMap::new()), None);
+
+        let open = WebAuth::new(AuthConfig::default()).unwrap();
+        assert_eq!(open.authenticate(&HeaderMap::new()).unwrap().name, "anonymous");
+    }
+
+    #[test]
+    fn test_rate_limit_refills_per_key() {
+        let auth = auth(Some(2));
+        let principal = auth.authenticate(&headers(header::AUTHORIZATION, "Bearer s3cret")).unwrap();
+        let start = Instant::now();
+
+        assert!(auth.check_rate(&principal, start).is_ok());
+        assert!(auth.check_rate(&principal, start).is_ok());
+        assert_eq!(auth.check_rate(&principal, start), Err(30));
+        assert!(auth.check_rate(&principal, start + Duration::from_secs(30)).is_ok());
+
+        // Other credentials have their own bucket
+        let user = auth.authenticate(&headers(header::AUTHORIZATION, "Basic b3BzOmh1bnRlcjI=")).unwrap();
+        assert!(auth.check_rate(&user, start).is_ok());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = AuthConfig::default();
+        config.add_key("a");
+        config.api_keys.push(ApiKey { name: "cli-key-1".to_string(), key: "b".to_string(), requests_per_minute: None });
+        assert!(config.validate().is_err());
+
+        let config = AuthConfig { requests_per_minute: Some(0), ..AuthConfig::default() };
+        assert!(config.validate().is_err());
+
+        assert_eq!(request_targets(br#"{"kind": "batch-scan", "urls": ["https://a.example", "https://b.example"]}"#), vec!["https://a.example", "https://b.example"]);
+    }
+}
@@ -0,0 +1,169 @@
+//! gRPC service from `proto/waf_detector.proto`, served next to the JSON API
+//!
+//! Requests go through the same router, so authentication, rate limits and the scan
+//! history apply to gRPC calls as well. Only uncompressed unary calls are supported.
+
+pub mod proto;
+
+use super::history::{HistoryEntry, ScanKind};
+use super::WebServer;
+use crate::payload::waf_smoke_test::{SmokeTestConfig, WafSmokeTest};
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue},
+    response::Response,
+    routing::post,
+    Router,
+};
+use http_body_util::StreamBody;
+use hyper::body::Frame;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use proto::ToProto;
+use std::convert::Infallible;
+
+/// Path prefix of the service's methods
+pub const SERVICE: &str = "/waf_detector.v1.WafDetector";
+
+/// `grpc-message` is percent-encoded, except for printable ASCII
+const GRPC_MESSAGE: &AsciiSet = &CONTROLS.add(b'%');
+
+/// gRPC status codes used by the service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    Ok = 0,
+    InvalidArgument = 3,
+    Unimplemented = 12,
+    Internal = 13,
+}
+
+/// Routes of the `WafDetector` service
+pub fn routes() -> Router<WebServer> {
+    Router::new()
+        .route(&format!("{}/Detect", SERVICE), post(detect))
+        .route(&format!("{}/SmokeTest", SERVICE), post(smoke_test))
+}
+
+/// A message with the 5-byte gRPC prefix (compression flag, big-endian length)
+pub fn frame(message: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(message.len() + 5);
+    framed.push(0);
+    framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    framed.extend_from_slice(message);
+    framed
+}
+
+/// The single message of a unary request
+fn unframe(body: &[u8]) -> Result<&[u8], (Code, String)> {
+    if body.len() < 5 {
+        return Err((Code::InvalidArgument, "request is missing the message frame".to_string()));
+    }
+    if body[0] != 0 {
+        return Err((Code::Unimplemented, "compressed messages are not supported".to_string()));
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    if body.len() != len + 5 {
+        return Err((Code::InvalidArgument, "expected exactly one request message".to_string()));
+    }
+    Ok(&body[5..])
+}
+
+/// Validated `url` of the request
+fn request_url(headers: &HeaderMap, body: &[u8]) -> Result<String, (Code, String)> {
+    let grpc = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/grpc"));
+    if !grpc {
+        return Err((Code::InvalidArgument, "content-type must be application/grpc".to_string()));
+    }
+    let url = proto::decode_url_request(unframe(body)?).map_err(|e| (Code::InvalidArgument, e.to_string()))?;
+    crate::utils::validate_url(&url).map_err(|e| (Code::InvalidArgument, e.to_string()))?;
+    Ok(url)
+}
+
+fn status_headers(code: Code, message: Option<&str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("grpc-status", HeaderValue::from(code as u16));
+    if let Some(value) = message.and_then(|m| HeaderValue::from_str(&utf8_percent_encode(m, GRPC_MESSAGE).to_string()).ok()) {
+        headers.insert("grpc-message", value);
+    }
+    headers
+}
+
+fn grpc_response(body: Body) -> Response {
+    let mut response = Response::new(body);
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+    response
+}
+
+/// One message followed by an OK status in the trailers
+fn reply(message: proto::Message) -> Response {
+    let frames = [Frame::data(Bytes::from(frame(&message.into_bytes()))), Frame::trailers(status_headers(Code::Ok, None))];
+    let stream = futures::stream::iter(frames.map(Ok::<_, Infallible>));
+    grpc_response(Body::new(StreamBody::new(stream)))
+}
+
+/// A trailers-only response: the status travels in the headers and there is no body
+fn error((code, message): (Code, String)) -> Response {
+    let mut response = grpc_response(Body::empty());
+    response.headers_mut().extend(status_headers(code, Some(&message)));
+    response
+}
+
+async fn detect(State(server): State<WebServer>, headers: HeaderMap, body: Bytes) -> Response {
+    let url = match request_url(&headers, &body) {
+        Ok(url) => url,
+        Err(e) => return error(e),
+    };
+    let outcome = server.engine.detect(&url).await;
+    server.record_detection(&url, &outcome, None);
+    match outcome {
+        Ok(result) => reply(result.to_proto()),
+        Err(e) => error((Code::Internal, e.to_string())),
+    }
+}
+
+async fn smoke_test(State(server): State<WebServer>, headers: HeaderMap, body: Bytes) -> Response {
+    let url = match request_url(&headers, &body) {
+        Ok(url) => url,
+        Err(e) => return error(e),
+    };
+    let smoke_test = match WafSmokeTest::new(SmokeTestConfig::default()) {
+        Ok(test) => test,
+        Err(e) => return error((Code::Internal, format!("Failed to create smoke test: {}", e))),
+    };
+    match smoke_test.run_test(&url).await {
+        Ok(mut result) => {
+            result.is_smoke_test = true;
+            server.history.record_or_warn(HistoryEntry::from_smoke_test(&result));
+            reply(result.to_proto())
+        }
+        Err(e) => {
+            server.history.record_or_warn(HistoryEntry::failed(&url, ScanKind::SmokeTest, &e));
+            error((Code::Internal, format!("Smoke test failed: {}", e)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing() {
+        let framed = frame(b"\x0a\x03abc");
+        assert_eq!(framed, b"\x00\x00\x00\x00\x05\x0a\x03abc");
+        assert_eq!(unframe(&framed).unwrap(), b"\x0a\x03abc");
+
+        assert_eq!(unframe(b"\x01\x00\x00\x00\x00").unwrap_err().0, Code::Unimplemented);
+        assert_eq!(unframe(b"\x00\x00\x00\x00\x09abc").unwrap_err().0, Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_status_message_is_percent_encoded() {
+        let headers = status_headers(Code::Internal, Some("100% failed: ü"));
+        assert_eq!(headers["grpc-status"], "13");
+        assert_eq!(headers["grpc-message"], "100%25 failed: %C3%BC");
+    }
+}
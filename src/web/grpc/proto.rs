@@ -0,0 +1,320 @@
+//! Protobuf wire encoding of the messages in `proto/waf_detector.proto`
+//!
+//! Written by hand: only the request messages are decoded, and only the fields the
+//! service reads. Field numbers here must match the `.proto` file.
+
+use crate::error::ScanError;
+use crate::payload::waf_smoke_test::{PayloadTestResult, SmokeTestResult, TestSummary};
+use crate::{DetectionResult, Evidence, ProviderDetection, ProviderFeature};
+use anyhow::{anyhow, Result};
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+/// An encoded message; proto3 default values (empty, zero) are left out
+#[derive(Debug, Default, Clone)]
+pub struct Message {
+    buf: Vec<u8>,
+}
+
+impl Message {
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        put_varint(&mut self.buf, ((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn bytes(&mut self, field: u32, value: &[u8]) -> &mut Self {
+        self.key(field, WIRE_LEN);
+        put_varint(&mut self.buf, value.len() as u64);
+        self.buf.extend_from_slice(value);
+        self
+    }
+
+    pub fn string(&mut self, field: u32, value: &str) -> &mut Self {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+        self
+    }
+
+    pub fn uint64(&mut self, field: u32, value: u64) -> &mut Self {
+        if value != 0 {
+            self.key(field, WIRE_VARINT);
+            put_varint(&mut self.buf, value);
+        }
+        self
+    }
+
+    pub fn double(&mut self, field: u32, value: f64) -> &mut Self {
+        if value != 0.0 {
+            self.key(field, WIRE_FIXED64);
+            self.buf.extend_from_slice(&value.to_le_bytes());
+        }
+        self
+    }
+
+    /// Embedded message; written even when empty so presence is kept
+    pub fn message(&mut self, field: u32, value: &Message) -> &mut Self {
+        self.bytes(field, &value.buf)
+    }
+
+    fn optional<T: ToProto>(&mut self, field: u32, value: Option<&T>) -> &mut Self {
+        if let Some(value) = value {
+            self.message(field, &value.to_proto());
+        }
+        self
+    }
+
+    fn repeated<'a, T: ToProto + 'a>(&mut self, field: u32, values: impl IntoIterator<Item = &'a T>) -> &mut Self {
+        for value in values {
+            self.message(field, &value.to_proto());
+        }
+        self
+    }
+
+    /// `map<string, V>` entries, sorted by key for stable output
+    fn map<'a, V: 'a>(&mut self, field: u32, entries: impl IntoIterator<Item = (&'a String, V)>, mut value: impl FnMut(&mut Message, V)) -> &mut Self {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, v) in entries {
+            let mut entry = Message::default();
+            entry.bytes(1, key.as_bytes());
+            value(&mut entry, v);
+            self.message(field, &entry);
+        }
+        self
+    }
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Conversion into a protobuf message
+pub trait ToProto {
+    fn to_proto(&self) -> Message;
+}
+
+/// Serde name of a unit enum variant (`snake_case` for the types used here)
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+impl ToProto for ProviderFeature {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.string(1, &self.category).string(2, &self.value).double(3, self.confidence).string(4, &self.evidence);
+        m
+    }
+}
+
+impl ToProto for ProviderDetection {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.string(1, &self.name)
+            .double(2, self.confidence)
+            .repeated(3, &self.features)
+            .string(4, &self.layer.map(|layer| serde_name(&layer)).unwrap_or_default());
+        m
+    }
+}
+
+impl ToProto for Evidence {
+    fn to_proto(&self) -> Message {
+        let (method, source) = self.method_type.name_and_source();
+        let mut m = Message::default();
+        m.string(1, method)
+            .string(2, &source)
+            .double(3, self.confidence)
+            .string(4, &self.description)
+            .string(5, &self.raw_data)
+            .string(6, &self.signature_matched);
+        m
+    }
+}
+
+impl ToProto for ScanError {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.string(1, &serde_name(&self.class)).string(2, &self.message);
+        m
+    }
+}
+
+impl ToProto for DetectionResult {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.string(1, &self.url)
+            .optional(2, self.detected_waf.as_ref())
+            .optional(3, self.detected_cdn.as_ref())
+            .optional(4, self.detected_bot_manager.as_ref())
+            .repeated(5, &self.detected_stack)
+            .map(6, &self.provider_scores, |entry, score| {
+                entry.double(2, *score);
+            })
+            .map(7, &self.evidence_map, |entry, evidence| {
+                let mut list = Message::default();
+                list.repeated(1, evidence);
+                entry.message(2, &list);
+            })
+            .uint64(8, self.detection_time_ms)
+            .string(9, &self.metadata.timestamp.to_rfc3339())
+            .optional(10, self.error.as_ref())
+            .string(15, &serde_json::to_string(self).unwrap_or_default());
+        m
+    }
+}
+
+impl ToProto for TestSummary {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.uint64(1, self.total_tests as u64)
+            .uint64(2, self.blocked_count as u64)
+            .uint64(3, self.allowed_count as u64)
+            .uint64(4, self.error_count as u64)
+            .uint64(5, self.rate_limited_count as u64)
+            .uint64(6, self.challenge_count as u64)
+            .double(7, self.effectiveness_percentage);
+        m
+    }
+}
+
+impl ToProto for PayloadTestResult {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.string(1, &self.category)
+            .string(2, &self.payload)
+            .string(3, &serde_name(&self.location))
+            .uint64(4, self.response_status as u64)
+            .uint64(5, self.response_time_ms)
+            .string(6, &serde_name(&self.classification));
+        m
+    }
+}
+
+impl ToProto for SmokeTestResult {
+    fn to_proto(&self) -> Message {
+        let mut m = Message::default();
+        m.string(1, &self.url)
+            .message(2, &self.summary.to_proto())
+            .repeated(3, &self.test_results)
+            .string(4, self.detected_waf.as_deref().unwrap_or_default())
+            .string(5, self.detected_cdn.as_deref().unwrap_or_default());
+        for recommendation in &self.recommendations {
+            m.bytes(6, recommendation.as_bytes());
+        }
+        m.uint64(7, self.total_time_ms)
+            .string(8, &self.timestamp.to_rfc3339())
+            .string(15, &serde_json::to_string(self).unwrap_or_default());
+        m
+    }
+}
+
+/// A decoded field value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Split a message into `(field number, value)` pairs
+pub fn fields(mut data: &[u8]) -> Result<Vec<(u32, Value<'_>)>> {
+    fn varint(data: &mut &[u8]) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = data.split_first().ok_or_else(|| anyhow!("truncated varint"))?;
+            *data = rest;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("varint too long"))
+    }
+    fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+        if data.len() < len {
+            return Err(anyhow!("truncated field"));
+        }
+        let (value, rest) = data.split_at(len);
+        *data = rest;
+        Ok(value)
+    }
+
+    let mut fields = Vec::new();
+    while !data.is_empty() {
+        let key = varint(&mut data)?;
+        let field = (key >> 3) as u32;
+        let value = match (key & 0x7) as u8 {
+            WIRE_VARINT => Value::Varint(varint(&mut data)?),
+            WIRE_FIXED64 => Value::Fixed64(u64::from_le_bytes(take(&mut data, 8)?.try_into()?)),
+            WIRE_LEN => {
+                let len = varint(&mut data)? as usize;
+                Value::Bytes(take(&mut data, len)?)
+            }
+            WIRE_FIXED32 => Value::Fixed32(u32::from_le_bytes(take(&mut data, 4)?.try_into()?)),
+            wire_type => return Err(anyhow!("unsupported wire type {}", wire_type)),
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+/// `url` (field 1) of a `DetectRequest` or `SmokeTestRequest`
+pub fn decode_url_request(data: &[u8]) -> Result<String> {
+    let mut url = String::new();
+    for (field, value) in fields(data)? {
+        if let (1, Value::Bytes(bytes)) = (field, value) {
+            url = String::from_utf8(bytes.to_vec()).map_err(|_| anyhow!("url is not valid UTF-8"))?;
+        }
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_field<'a>(fields: &[(u32, Value<'a>)], number: u32) -> Option<&'a str> {
+        fields.iter().find_map(|(field, value)| match value {
+            Value::Bytes(bytes) if *field == number => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let mut request = Message::default();
+        request.string(1, "https://example.com/ünïcode").uint64(2, 300);
+        assert_eq!(decode_url_request(&request.into_bytes()).unwrap(), "https://example.com/ünïcode");
+        assert!(decode_url_request(&[0x0a, 0x05, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_provider_detection_encoding() {
+        let detection = ProviderDetection {
+            name: "CloudFlare".to_string(),
+            confidence: 0.95,
+            features: Vec::new(),
+            layer: Some(crate::StackLayer::Edge),
+        };
+        let bytes = detection.to_proto().into_bytes();
+        let fields = fields(&bytes).unwrap();
+
+        assert_eq!(string_field(&fields, 1), Some("CloudFlare"));
+        assert!(fields.contains(&(2, Value::Fixed64(0.95f64.to_bits()))));
+        assert_eq!(string_field(&fields, 4), Some("edge"));
+        // Known encoding of field 1: tag 0x0a, length 10
+        assert_eq!(&bytes[..2], &[0x0a, 10]);
+    }
+}
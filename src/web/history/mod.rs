@@ -0,0 +1,735 @@
+//! Scan history for the web server (`/api/results`)
+//!
+//! Every scan and smoke test run through the web API is recorded per host. With the
+//! opt-in `sqlite` feature the history is kept in a SQLite database and survives
+//! restarts; otherwise the latest `MAX_MEMORY_ENTRIES` scans are kept in memory.
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use crate::confidence::decay::{CachedAge, ConfidenceDecay};
+use crate::payload::waf_smoke_test::SmokeTestResult;
+use crate::utils::extract_domain;
+use crate::DetectionResult;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
+#[cfg(feature = "sqlite")]
+use std::sync::Mutex;
+
+/// Scans kept by the in-memory history; the oldest are dropped first
+pub const MAX_MEMORY_ENTRIES: usize = 1000;
+pub const DEFAULT_PER_PAGE: usize = 20;
+pub const MAX_PER_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScanKind {
+    Detection,
+    SmokeTest,
+}
+
+#[cfg(feature = "sqlite")]
+impl ScanKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Detection => "detection",
+            Self::SmokeTest => "smoke-test",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        if value == "smoke-test" { Self::SmokeTest } else { Self::Detection }
+    }
+}
+
+/// One recorded scan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub host: String,
+    pub url: String,
+    pub kind: ScanKind,
+    /// Project the scan was run for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub scanned_at: DateTime<Utc>,
+    pub waf: Option<String>,
+    pub cdn: Option<String>,
+    /// Highest confidence of the detected WAF and CDN (detections only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Smoke tests only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effectiveness: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Differences from the previous scan of the same kind for this host
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+    /// The full result, only returned for a single entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// Set on entries read back from the store: their age, and how much `confidence`
+    /// and the result's confidences were decayed for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached: Option<CachedAge>,
+}
+
+impl HistoryEntry {
+    pub fn from_detection(result: &DetectionResult) -> Self {
+        let confidence = [&result.detected_waf, &result.detected_cdn]
+            .into_iter()
+            .flatten()
+            .map(|d| d.confidence)
+            .reduce(f64::max);
+        Self {
+            confidence,
+            error: result.error.as_ref().map(|e| e.message.clone()),
+            result: serde_json::to_value(result).ok(),
+            ..Self::new(&result.url, ScanKind::Detection, result.detected_waf.as_ref().map(|d| d.name.clone()), result.detected_cdn.as_ref().map(|d| d.name.clone()))
+        }
+    }
+
+    pub fn from_smoke_test(result: &SmokeTestResult) -> Self {
+        Self {
+            effectiveness: Some(result.summary.effectiveness_percentage),
+            result: serde_json::to_value(result).ok(),
+            ..Self::new(&result.url, ScanKind::SmokeTest, result.detected_waf.clone(), result.detected_cdn.clone())
+        }
+    }
+
+    /// File the scan under `project`
+    pub fn in_project(self, project: Option<&str>) -> Self {
+        Self { project: project.map(str::to_string), ..self }
+    }
+
+    /// A scan that failed before producing a result
+    pub fn failed(url: &str, kind: ScanKind, error: &anyhow::Error) -> Self {
+        Self { error: Some(error.to_string()), ..Self::new(url, kind, None, None) }
+    }
+
+    fn new(url: &str, kind: ScanKind, waf: Option<String>, cdn: Option<String>) -> Self {
+        Self {
+            id: 0,
+            host: normalize_host(url),
+            url: url.to_string(),
+            kind,
+            project: None,
+            scanned_at: Utc::now(),
+            waf,
+            cdn,
+            confidence: None,
+            effectiveness: None,
+            error: None,
+            changes: Vec::new(),
+            result: None,
+            cached: None,
+        }
+    }
+
+    /// What changed since `previous`
+    fn diff(&self, previous: &HistoryEntry) -> Vec<String> {
+        let name = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+        let mut changes = Vec::new();
+        match (&previous.error, &self.error) {
+            (None, Some(_)) => changes.push("scan failed".to_string()),
+            (Some(_), None) => changes.push("scan recovered".to_string()),
+            _ => {}
+        }
+        // A failed scan says nothing about the WAF, so only compare successful ones
+        if previous.error.is_none() && self.error.is_none() {
+            if previous.waf != self.waf {
+                changes.push(format!("WAF {} → {}", name(&previous.waf), name(&self.waf)));
+            }
+            if previous.cdn != self.cdn {
+                changes.push(format!("CDN {} → {}", name(&previous.cdn), name(&self.cdn)));
+            }
+            if let (Some(before), Some(after)) = (previous.effectiveness, self.effectiveness) {
+                if (after - before).abs() >= 1.0 {
+                    changes.push(format!("effectiveness {:.1}% → {:.1}%", before, after));
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Host a URL or bare host name is filed under
+pub fn normalize_host(target: &str) -> String {
+    let target = target.trim();
+    let url = if target.contains("://") { target.to_string() } else { format!("https://{}", target) };
+    extract_domain(&url).unwrap_or_else(|_| target.to_lowercase())
+}
+
+/// Field `/api/results` sorts by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySort {
+    #[default]
+    ScannedAt,
+    Host,
+    Confidence,
+    Waf,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// `GET /api/results` query string
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Project id; only scans recorded for it
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Substring of the host name
+    #[serde(default)]
+    pub search: Option<String>,
+    /// WAF or CDN name, case-insensitive
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Inclusive confidence range (0.0 - 1.0); scans without a confidence never match
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    #[serde(default)]
+    pub max_confidence: Option<f64>,
+    /// `true`: a WAF was found; `false`: the scan succeeded without finding one
+    #[serde(default)]
+    pub detected: Option<bool>,
+    #[serde(default)]
+    pub sort: Option<HistorySort>,
+    /// Defaults to descending
+    #[serde(default)]
+    pub order: Option<SortOrder>,
+    /// 1-based
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [("min_confidence", self.min_confidence), ("max_confidence", self.max_confidence)] {
+            if value.is_some_and(|v| !(0.0..=1.0).contains(&v)) {
+                return Err(anyhow!("{} must be between 0.0 and 1.0", name));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_confidence, self.max_confidence) {
+            if min > max {
+                return Err(anyhow!("min_confidence must not exceed max_confidence"));
+            }
+        }
+        Ok(())
+    }
+
+    fn host(&self) -> Option<String> {
+        self.host.as_deref().filter(|h| !h.trim().is_empty()).map(normalize_host)
+    }
+
+    fn search(&self) -> Option<String> {
+        self.search.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(str::to_lowercase)
+    }
+
+    fn provider(&self) -> Option<String> {
+        self.provider.as_deref().map(str::trim).filter(|p| !p.is_empty()).map(str::to_lowercase)
+    }
+
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        let named = |name: &Option<String>, wanted: &str| name.as_deref().is_some_and(|n| n.to_lowercase() == wanted);
+        self.host().is_none_or(|host| entry.host == host)
+            && self.project.as_ref().is_none_or(|project| entry.project.as_ref() == Some(project))
+            && self.search().is_none_or(|search| entry.host.contains(&search))
+            && self.provider().is_none_or(|provider| named(&entry.waf, &provider) || named(&entry.cdn, &provider))
+            && self.min_confidence.is_none_or(|min| entry.confidence.is_some_and(|c| c >= min))
+            && self.max_confidence.is_none_or(|max| entry.confidence.is_some_and(|c| c <= max))
+            && self.detected.is_none_or(|detected| match detected {
+                true => entry.waf.is_some(),
+                false => entry.waf.is_none() && entry.error.is_none(),
+            })
+    }
+
+    /// Order of two entries on the page; ties go to the newest
+    fn compare(&self, a: &HistoryEntry, b: &HistoryEntry) -> std::cmp::Ordering {
+        let ordering = match self.sort.unwrap_or_default() {
+            HistorySort::ScannedAt => a.id.cmp(&b.id),
+            HistorySort::Host => a.host.cmp(&b.host),
+            HistorySort::Confidence => a.confidence.partial_cmp(&b.confidence).unwrap_or(std::cmp::Ordering::Equal),
+            HistorySort::Waf => a.waf.cmp(&b.waf),
+        };
+        let ordering = match self.order.unwrap_or_default() {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        ordering.then_with(|| b.id.cmp(&a.id))
+    }
+
+    /// `WHERE` clause and its parameters for the SQLite backend
+    #[cfg(feature = "sqlite")]
+    fn sql_filter(&self) -> (String, Vec<sqlite::Owned>) {
+        use sqlite::Owned;
+        let mut clauses = vec!["1 = 1".to_string()];
+        let mut params = Vec::new();
+        if let Some(host) = self.host() {
+            clauses.push("host = ?".to_string());
+            params.push(Owned::Text(host));
+        }
+        if let Some(project) = &self.project {
+            clauses.push("project = ?".to_string());
+            params.push(Owned::Text(project.clone()));
+        }
+        if let Some(search) = self.search() {
+            clauses.push("INSTR(host, ?) > 0".to_string());
+            params.push(Owned::Text(search));
+        }
+        if let Some(provider) = self.provider() {
+            clauses.push("(LOWER(waf) = ? OR LOWER(cdn) = ?)".to_string());
+            params.extend([Owned::Text(provider.clone()), Owned::Text(provider)]);
+        }
+        if let Some(min) = self.min_confidence {
+            clauses.push("confidence >= ?".to_string());
+            params.push(Owned::Real(min));
+        }
+        if let Some(max) = self.max_confidence {
+            clauses.push("confidence <= ?".to_string());
+            params.push(Owned::Real(max));
+        }
+        match self.detected {
+            Some(true) => clauses.push("waf IS NOT NULL".to_string()),
+            Some(false) => clauses.push("waf IS NULL AND error IS NULL".to_string()),
+            None => {}
+        }
+        (clauses.join(" AND "), params)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn sql_order(&self) -> String {
+        let column = match self.sort.unwrap_or_default() {
+            HistorySort::ScannedAt => "id",
+            HistorySort::Host => "host",
+            HistorySort::Confidence => "confidence",
+            HistorySort::Waf => "waf",
+        };
+        let direction = match self.order.unwrap_or_default() {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        format!("{} {}, id DESC", column, direction)
+    }
+
+    fn page(&self) -> usize {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    fn per_page(&self) -> usize {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE)
+    }
+}
+
+/// A page of history, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+enum Backend {
+    Memory(RwLock<Vec<HistoryEntry>>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Mutex<sqlite::Connection>),
+}
+
+/// Recorded scans, in memory or in a SQLite database
+pub struct HistoryStore {
+    backend: Backend,
+    decay: ConfidenceDecay,
+}
+
+impl HistoryStore {
+    /// A history that is lost on restart
+    pub fn in_memory() -> Self {
+        Self { backend: Backend::Memory(RwLock::new(Vec::new())), decay: ConfidenceDecay::default() }
+    }
+
+    /// How confidences of entries read back are decayed by age. Filters and sorting
+    /// by confidence use the recorded values.
+    pub fn with_confidence_decay(mut self, decay: ConfidenceDecay) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// Open (or create) a SQLite history database
+    #[cfg(feature = "sqlite")]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let connection = sqlite::Connection::open(path.as_ref())?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS scans (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                host TEXT NOT NULL,
+                url TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                scanned_at TEXT NOT NULL,
+                waf TEXT,
+                cdn TEXT,
+                effectiveness REAL,
+                error TEXT,
+                result TEXT,
+                confidence REAL,
+                project TEXT
+            );
+            CREATE INDEX IF NOT EXISTS scans_host ON scans (host, kind, id);",
+        )?;
+        // Databases created before these columns existed
+        let columns = connection.query("SELECT name FROM pragma_table_info('scans')", &[], |row| Ok(row.text(0).unwrap_or_default()))?;
+        for (column, definition) in [("confidence", "REAL"), ("project", "TEXT")] {
+            if !columns.iter().any(|c| c == column) {
+                connection.execute_batch(&format!("ALTER TABLE scans ADD COLUMN {} {}", column, definition))?;
+            }
+        }
+        connection.execute_batch("CREATE INDEX IF NOT EXISTS scans_project ON scans (project, id)")?;
+        Ok(Self { backend: Backend::Sqlite(Mutex::new(connection)), decay: ConfidenceDecay::default() })
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Err(anyhow!("{}: persistent scan history needs the `sqlite` feature", path.as_ref().display()))
+    }
+
+    /// Store a scan and return it with its id
+    pub fn record(&self, mut entry: HistoryEntry) -> Result<HistoryEntry> {
+        entry.changes.clear();
+        match &self.backend {
+            Backend::Memory(entries) => {
+                let mut entries = entries.write().unwrap();
+                entry.id = entries.last().map_or(1, |last| last.id + 1);
+                entries.push(entry.clone());
+                if entries.len() > MAX_MEMORY_ENTRIES {
+                    let excess = entries.len() - MAX_MEMORY_ENTRIES;
+                    entries.drain(..excess);
+                }
+            }
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(connection) => {
+                use sqlite::Value;
+                let connection = connection.lock().unwrap();
+                let result = entry.result.as_ref().map(|r| r.to_string());
+                let scanned_at = entry.scanned_at.to_rfc3339();
+                connection.execute(
+                    "INSERT INTO scans (host, url, kind, scanned_at, waf, cdn, effectiveness, error, result, confidence, project)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    &[
+                        Value::Text(&entry.host),
+                        Value::Text(&entry.url),
+                        Value::Text(entry.kind.as_str()),
+                        Value::Text(&scanned_at),
+                        entry.waf.as_deref().into(),
+                        entry.cdn.as_deref().into(),
+                        entry.effectiveness.map_or(Value::Null, Value::Real),
+                        entry.error.as_deref().into(),
+                        result.as_deref().into(),
+                        entry.confidence.map_or(Value::Null, Value::Real),
+                        entry.project.as_deref().into(),
+                    ],
+                )?;
+                entry.id = connection.last_insert_rowid();
+            }
+        }
+        Ok(entry)
+    }
+
+    /// Record a scan, logging instead of failing the request that produced it
+    pub fn record_or_warn(&self, entry: HistoryEntry) {
+        if let Err(e) = self.record(entry) {
+            tracing::warn!(error = %e, "failed to record scan history");
+        }
+    }
+
+    /// One page of matching scans (newest first unless sorted otherwise), each with its
+    /// changes from the scan before it
+    pub fn page(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        query.validate()?;
+        let (page, per_page) = (query.page(), query.per_page());
+        let offset = (page - 1) * per_page;
+        let (mut entries, total) = match &self.backend {
+            Backend::Memory(entries) => {
+                let entries = entries.read().unwrap();
+                let mut matching: Vec<&HistoryEntry> = entries.iter().filter(|e| query.matches(e)).collect();
+                matching.sort_by(|a, b| query.compare(a, b));
+                let page: Vec<HistoryEntry> = matching.iter().skip(offset).take(per_page).map(|e| HistoryEntry { result: None, ..(*e).clone() }).collect();
+                (page, matching.len())
+            }
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(connection) => {
+                use sqlite::Value;
+                let connection = connection.lock().unwrap();
+                let (filter, owned) = query.sql_filter();
+                let mut params: Vec<Value> = owned.iter().map(Value::from).collect();
+                let total = connection.query(&format!("SELECT COUNT(*) FROM scans WHERE {}", filter), &params, |row| Ok(row.integer(0)))?;
+                params.extend([Value::Integer(per_page as i64), Value::Integer(offset as i64)]);
+                let entries = connection.query(
+                    &format!("SELECT {} FROM scans WHERE {} ORDER BY {} LIMIT ? OFFSET ?", SUMMARY_COLUMNS, filter, query.sql_order()),
+                    &params,
+                    row_to_entry,
+                )?;
+                (entries, total.first().copied().unwrap_or(0) as usize)
+            }
+        };
+        for entry in &mut entries {
+            if let Some(previous) = self.previous(entry)? {
+                entry.changes = entry.diff(&previous);
+            }
+            self.age(entry);
+        }
+        Ok(HistoryPage { entries, total, page, per_page })
+    }
+
+    /// A single scan with its full result
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let entry = match &self.backend {
+            Backend::Memory(entries) => entries.read().unwrap().iter().find(|e| e.id == id).cloned(),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(connection) => {
+                let connection = connection.lock().unwrap();
+                let mut rows = connection.query(
+                    &format!("SELECT {}, result FROM scans WHERE id = ?1", SUMMARY_COLUMNS),
+                    &[sqlite::Value::Integer(id)],
+                    |row| {
+                        let mut entry = row_to_entry(row)?;
+                        entry.result = row.text(11).and_then(|r| serde_json::from_str(&r).ok());
+                        Ok(entry)
+                    },
+                )?;
+                rows.pop()
+            }
+        };
+        let Some(mut entry) = entry else { return Ok(None) };
+        if let Some(previous) = self.previous(&entry)? {
+            entry.changes = entry.diff(&previous);
+        }
+        self.age(&mut entry);
+        Ok(Some(entry))
+    }
+
+    /// Mark `entry` as served from history and decay its confidences by its age
+    fn age(&self, entry: &mut HistoryEntry) {
+        let age = (Utc::now() - entry.scanned_at).to_std().unwrap_or_default();
+        let cached = self.decay.annotate(age, entry.confidence);
+        let factor = cached.decay_factor;
+        entry.confidence = entry.confidence.map(|confidence| confidence * factor);
+        if let Some(serde_json::Value::Object(result)) = &mut entry.result {
+            if entry.kind == ScanKind::Detection {
+                for key in ["detected_waf", "detected_cdn", "detected_bot_manager"] {
+                    if let Some(detection) = result.get_mut(key) {
+                        decay_field(detection.get_mut("confidence"), factor);
+                    }
+                }
+                if let Some(serde_json::Value::Array(stack)) = result.get_mut("detected_stack") {
+                    for detection in stack {
+                        decay_field(detection.get_mut("confidence"), factor);
+                    }
+                }
+                if let Some(serde_json::Value::Object(scores)) = result.get_mut("provider_scores") {
+                    for score in scores.values_mut() {
+                        decay_field(Some(score), factor);
+                    }
+                }
+            }
+            if let Ok(annotation) = serde_json::to_value(&cached) {
+                result.insert("cached".to_string(), annotation);
+            }
+        }
+        entry.cached = Some(cached);
+    }
+
+    /// The scan of the same host and kind recorded just before `entry`
+    fn previous(&self, entry: &HistoryEntry) -> Result<Option<HistoryEntry>> {
+        match &self.backend {
+            Backend::Memory(entries) => Ok(entries
+                .read()
+                .unwrap()
+                .iter()
+                .rev()
+                .find(|e| e.id < entry.id && e.host == entry.host && e.kind == entry.kind)
+                .cloned()),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(connection) => {
+                use sqlite::Value;
+                let mut rows = connection.lock().unwrap().query(
+                    &format!("SELECT {} FROM scans WHERE host = ?1 AND kind = ?2 AND id < ?3 ORDER BY id DESC LIMIT 1", SUMMARY_COLUMNS),
+                    &[Value::Text(&entry.host), Value::Text(entry.kind.as_str()), Value::Integer(entry.id)],
+                    row_to_entry,
+                )?;
+                Ok(rows.pop())
+            }
+        }
+    }
+}
+
+fn decay_field(value: Option<&mut serde_json::Value>, factor: f64) {
+    if let Some(value) = value {
+        if let Some(number) = value.as_f64() {
+            *value = (number * factor).into();
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+const SUMMARY_COLUMNS: &str = "id, host, url, kind, scanned_at, waf, cdn, effectiveness, error, confidence, project";
+
+#[cfg(feature = "sqlite")]
+fn row_to_entry(row: &sqlite::Row) -> Result<HistoryEntry> {
+    let scanned_at = row.text(4).unwrap_or_default();
+    Ok(HistoryEntry {
+        id: row.integer(0),
+        host: row.text(1).unwrap_or_default(),
+        url: row.text(2).unwrap_or_default(),
+        kind: ScanKind::parse(&row.text(3).unwrap_or_default()),
+        scanned_at: DateTime::parse_from_rfc3339(&scanned_at)
+            .map_err(|e| anyhow!("Invalid scan time '{}' in history: {}", scanned_at, e))?
+            .with_timezone(&Utc),
+        waf: row.text(5),
+        cdn: row.text(6),
+        effectiveness: row.real(7),
+        error: row.text(8),
+        confidence: row.real(9),
+        project: row.text(10),
+        changes: Vec::new(),
+        result: None,
+        cached: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn entry(url: &str, waf: Option<&str>) -> HistoryEntry {
+        HistoryEntry::new(url, ScanKind::Detection, waf.map(str::to_string), Some("CloudFront".to_string()))
+    }
+
+    fn exercise(store: &HistoryStore) {
+        store.record(entry("https://shop.example.com/", Some("AWS WAF"))).unwrap();
+        store.record(entry("https://other.example.com/", None)).unwrap();
+        let mut last = entry("https://Shop.example.com/login", None);
+        last.result = Some(serde_json::json!({"url": "https://shop.example.com/login"}));
+        let last = store.record(last).unwrap();
+
+        let query = HistoryQuery { host: Some("shop.example.com".to_string()), ..HistoryQuery::default() };
+        let page = store.page(&query).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.entries[0].id, last.id);
+        assert_eq!(page.entries[0].changes, vec!["WAF AWS WAF → none"]);
+        assert!(page.entries[0].result.is_none());
+        assert!(page.entries[1].changes.is_empty());
+
+        let second_page = store.page(&HistoryQuery { page: Some(2), per_page: Some(2), ..HistoryQuery::default() }).unwrap();
+        assert_eq!((second_page.total, second_page.entries.len()), (3, 1));
+
+        let full = store.get(last.id).unwrap().unwrap();
+        assert_eq!(full.result.unwrap()["url"], "https://shop.example.com/login");
+        assert!(store.get(999).unwrap().is_none());
+    }
+
+    fn filter(store: &HistoryStore) {
+        let scan = |url: &str, waf: Option<&str>, confidence: Option<f64>| HistoryEntry { confidence, ..entry(url, waf) };
+        store.record(scan("https://a.example.com/", Some("AWS WAF"), Some(0.9)).in_project(Some("acme"))).unwrap();
+        store.record(scan("https://b.example.org/", Some("CloudFlare"), Some(0.6))).unwrap();
+        store.record(scan("https://c.example.com/", None, Some(0.4))).unwrap();
+        store.record(HistoryEntry::failed("https://d.example.com/", ScanKind::Detection, &anyhow!("timed out"))).unwrap();
+
+        let hosts = |query: HistoryQuery| store.page(&query).unwrap().entries.into_iter().map(|e| e.host).collect::<Vec<_>>();
+        assert_eq!(hosts(HistoryQuery { provider: Some("cloudflare".to_string()), ..HistoryQuery::default() }), vec!["b.example.org"]);
+        assert_eq!(hosts(HistoryQuery { project: Some("acme".to_string()), ..HistoryQuery::default() }), vec!["a.example.com"]);
+        assert_eq!(hosts(HistoryQuery { provider: Some("CLOUDFRONT".to_string()), ..HistoryQuery::default() }).len(), 3);
+        assert_eq!(hosts(HistoryQuery { search: Some("EXAMPLE.com".to_string()), ..HistoryQuery::default() }), vec!["d.example.com", "c.example.com", "a.example.com"]);
+        assert_eq!(hosts(HistoryQuery { min_confidence: Some(0.5), max_confidence: Some(0.9), ..HistoryQuery::default() }), vec!["b.example.org", "a.example.com"]);
+        assert_eq!(hosts(HistoryQuery { detected: Some(false), ..HistoryQuery::default() }), vec!["c.example.com"]);
+        assert_eq!(hosts(HistoryQuery { detected: Some(true), sort: Some(HistorySort::Confidence), order: Some(SortOrder::Asc), ..HistoryQuery::default() }), vec!["b.example.org", "a.example.com"]);
+        assert_eq!(hosts(HistoryQuery { sort: Some(HistorySort::Host), order: Some(SortOrder::Asc), per_page: Some(2), ..HistoryQuery::default() }), vec!["a.example.com", "b.example.org"]);
+        assert!(store.page(&HistoryQuery { min_confidence: Some(0.8), max_confidence: Some(0.2), ..HistoryQuery::default() }).is_err());
+    }
+
+    #[test]
+    fn test_memory_history() {
+        exercise(&HistoryStore::in_memory());
+        filter(&HistoryStore::in_memory());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_history_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        filter(&HistoryStore::open(dir.path().join("history.db")).unwrap());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_history_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        exercise(&HistoryStore::open(&path).unwrap());
+
+        let reopened = HistoryStore::open(&path).unwrap();
+        assert_eq!(reopened.page(&HistoryQuery::default()).unwrap().total, 3);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_history_adds_confidence_to_old_databases() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        sqlite::Connection::open(&path).unwrap().execute_batch(
+            "CREATE TABLE scans (id INTEGER PRIMARY KEY AUTOINCREMENT, host TEXT NOT NULL, url TEXT NOT NULL, kind TEXT NOT NULL,
+             scanned_at TEXT NOT NULL, waf TEXT, cdn TEXT, effectiveness REAL, error TEXT, result TEXT)",
+        ).unwrap();
+
+        let store = HistoryStore::open(&path).unwrap();
+        store.record(HistoryEntry { confidence: Some(0.7), ..entry("https://example.com/", Some("AWS WAF")) }).unwrap();
+        assert_eq!(store.page(&HistoryQuery::default()).unwrap().entries[0].confidence, Some(0.7));
+    }
+
+    #[test]
+    fn test_served_entries_decay_with_age() {
+        let store = HistoryStore::in_memory().with_confidence_decay(ConfidenceDecay::new(Duration::from_secs(24 * 3600)).with_grace(Duration::ZERO));
+        let mut old = HistoryEntry { confidence: Some(0.9), ..entry("https://example.com/", Some("CloudFlare")) };
+        old.scanned_at = Utc::now() - chrono::Duration::days(2);
+        old.result = Some(serde_json::json!({
+            "detected_waf": {"name": "CloudFlare", "confidence": 0.9},
+            "provider_scores": {"CloudFlare": 0.9},
+        }));
+        let old = store.record(old).unwrap();
+
+        let served = store.get(old.id).unwrap().unwrap();
+        let cached = served.cached.clone().unwrap();
+        assert!((cached.decay_factor - 0.25).abs() < 0.001);
+        assert_eq!(cached.recorded_confidence, Some(0.9));
+        assert_eq!(cached.describe(), "2d");
+        assert!((served.confidence.unwrap() - 0.225).abs() < 0.001);
+        let result = served.result.unwrap();
+        assert!((result["detected_waf"]["confidence"].as_f64().unwrap() - 0.225).abs() < 0.001);
+        assert!((result["provider_scores"]["CloudFlare"].as_f64().unwrap() - 0.225).abs() < 0.001);
+        assert_eq!(result["cached"]["age_secs"], cached.age_secs);
+
+        // Filters still see what was recorded
+        let query = HistoryQuery { min_confidence: Some(0.8), ..HistoryQuery::default() };
+        assert_eq!(store.page(&query).unwrap().total, 1);
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("https://WWW.Example.com:8443/path"), "www.example.com");
+        assert_eq!(normalize_host("example.com"), "example.com");
+    }
+}
@@ -0,0 +1,290 @@
+//! Just enough of the SQLite C API for the scan history, linked against the system
+//! `libsqlite3`
+
+use anyhow::{anyhow, Result};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::marker::PhantomData;
+use std::path::Path;
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::ffi::{c_char, c_int, c_void};
+
+    pub enum sqlite3 {}
+    pub enum sqlite3_stmt {}
+
+    pub const SQLITE_OK: c_int = 0;
+    pub const SQLITE_ROW: c_int = 100;
+    pub const SQLITE_DONE: c_int = 101;
+    pub const SQLITE_NULL: c_int = 5;
+    pub const SQLITE_OPEN_READWRITE: c_int = 0x2;
+    pub const SQLITE_OPEN_CREATE: c_int = 0x4;
+    pub const SQLITE_OPEN_FULLMUTEX: c_int = 0x10000;
+    /// `SQLITE_TRANSIENT`: SQLite copies bound text before the call returns
+    pub const SQLITE_TRANSIENT: isize = -1;
+
+    #[link(name = "sqlite3")]
+    extern "C" {
+        pub fn sqlite3_open_v2(filename: *const c_char, db: *mut *mut sqlite3, flags: c_int, vfs: *const c_char) -> c_int;
+        pub fn sqlite3_close(db: *mut sqlite3) -> c_int;
+        pub fn sqlite3_errmsg(db: *mut sqlite3) -> *const c_char;
+        pub fn sqlite3_busy_timeout(db: *mut sqlite3, ms: c_int) -> c_int;
+        pub fn sqlite3_exec(
+            db: *mut sqlite3,
+            sql: *const c_char,
+            callback: *mut c_void,
+            arg: *mut c_void,
+            errmsg: *mut *mut c_char,
+        ) -> c_int;
+        pub fn sqlite3_prepare_v2(
+            db: *mut sqlite3,
+            sql: *const c_char,
+            len: c_int,
+            stmt: *mut *mut sqlite3_stmt,
+            tail: *mut *const c_char,
+        ) -> c_int;
+        pub fn sqlite3_bind_null(stmt: *mut sqlite3_stmt, index: c_int) -> c_int;
+        pub fn sqlite3_bind_int64(stmt: *mut sqlite3_stmt, index: c_int, value: i64) -> c_int;
+        pub fn sqlite3_bind_double(stmt: *mut sqlite3_stmt, index: c_int, value: f64) -> c_int;
+        pub fn sqlite3_bind_text(stmt: *mut sqlite3_stmt, index: c_int, text: *const c_char, len: c_int, destructor: isize) -> c_int;
+        pub fn sqlite3_step(stmt: *mut sqlite3_stmt) -> c_int;
+        pub fn sqlite3_column_type(stmt: *mut sqlite3_stmt, column: c_int) -> c_int;
+        pub fn sqlite3_column_int64(stmt: *mut sqlite3_stmt, column: c_int) -> i64;
+        pub fn sqlite3_column_double(stmt: *mut sqlite3_stmt, column: c_int) -> f64;
+        pub fn sqlite3_column_text(stmt: *mut sqlite3_stmt, column: c_int) -> *const u8;
+        pub fn sqlite3_column_bytes(stmt: *mut sqlite3_stmt, column: c_int) -> c_int;
+        pub fn sqlite3_finalize(stmt: *mut sqlite3_stmt) -> c_int;
+        pub fn sqlite3_last_insert_rowid(db: *mut sqlite3) -> i64;
+        pub fn sqlite3_free(ptr: *mut c_void);
+    }
+}
+
+/// A statement parameter
+#[derive(Debug, Clone, Copy)]
+pub enum Value<'a> {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(&'a str),
+}
+
+impl<'a> From<Option<&'a str>> for Value<'a> {
+    fn from(value: Option<&'a str>) -> Self {
+        value.map_or(Value::Null, Value::Text)
+    }
+}
+
+/// An owned parameter, for statements assembled at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub enum Owned {
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl<'a> From<&'a Owned> for Value<'a> {
+    fn from(value: &'a Owned) -> Self {
+        match value {
+            Owned::Integer(value) => Value::Integer(*value),
+            Owned::Real(value) => Value::Real(*value),
+            Owned::Text(text) => Value::Text(text),
+        }
+    }
+}
+
+/// An open database; SQLite serializes access itself (`SQLITE_OPEN_FULLMUTEX`)
+pub struct Connection {
+    db: *mut ffi::sqlite3,
+}
+
+// The handle is opened in serialized mode, so it may move between threads
+unsafe impl Send for Connection {}
+
+impl Connection {
+    pub fn open(path: &Path) -> Result<Self> {
+        let name = path.to_str().ok_or_else(|| anyhow!("Database path '{}' is not valid UTF-8", path.display()))?;
+        let name = CString::new(name)?;
+        let mut db = std::ptr::null_mut();
+        let flags = ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_FULLMUTEX;
+        // SAFETY: `name` is NUL-terminated and `db` receives the handle, which SQLite
+        // allocates even on failure so the error message can be read
+        let code = unsafe { ffi::sqlite3_open_v2(name.as_ptr(), &mut db, flags, std::ptr::null()) };
+        let connection = Self { db };
+        if code != ffi::SQLITE_OK {
+            return Err(anyhow!("Failed to open database '{}': {}", path.display(), connection.error_message()));
+        }
+        // SAFETY: `db` is a valid open handle
+        unsafe { ffi::sqlite3_busy_timeout(connection.db, 5000) };
+        Ok(connection)
+    }
+
+    /// Run one or more statements without parameters
+    pub fn execute_batch(&self, sql: &str) -> Result<()> {
+        let sql = CString::new(sql)?;
+        let mut error: *mut c_char = std::ptr::null_mut();
+        // SAFETY: valid handle and NUL-terminated SQL; `error` is freed with sqlite3_free
+        let code = unsafe { ffi::sqlite3_exec(self.db, sql.as_ptr(), std::ptr::null_mut(), std::ptr::null_mut(), &mut error) };
+        if code != ffi::SQLITE_OK {
+            let message = if error.is_null() {
+                self.error_message()
+            } else {
+                // SAFETY: SQLite returned a NUL-terminated message it allocated
+                let message = unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned();
+                unsafe { ffi::sqlite3_free(error as *mut c_void) };
+                message
+            };
+            return Err(anyhow!("SQLite error: {}", message));
+        }
+        Ok(())
+    }
+
+    /// Run a statement that returns no rows
+    pub fn execute(&self, sql: &str, params: &[Value]) -> Result<()> {
+        let statement = self.prepare(sql, params)?;
+        match statement.step()? {
+            true => Err(anyhow!("Statement returned rows: {}", sql)),
+            false => Ok(()),
+        }
+    }
+
+    /// Run a query and map every row
+    pub fn query<T>(&self, sql: &str, params: &[Value], mut map: impl FnMut(&Row) -> Result<T>) -> Result<Vec<T>> {
+        let statement = self.prepare(sql, params)?;
+        let mut rows = Vec::new();
+        while statement.step()? {
+            rows.push(map(&Row { stmt: statement.stmt, _statement: PhantomData })?);
+        }
+        Ok(rows)
+    }
+
+    pub fn last_insert_rowid(&self) -> i64 {
+        // SAFETY: valid handle
+        unsafe { ffi::sqlite3_last_insert_rowid(self.db) }
+    }
+
+    fn prepare(&self, sql: &str, params: &[Value]) -> Result<Statement<'_>> {
+        let sql = CString::new(sql)?;
+        let mut stmt = std::ptr::null_mut();
+        // SAFETY: valid handle and NUL-terminated SQL
+        let code = unsafe { ffi::sqlite3_prepare_v2(self.db, sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut()) };
+        if code != ffi::SQLITE_OK {
+            return Err(anyhow!("SQLite error: {}", self.error_message()));
+        }
+        let statement = Statement { connection: self, stmt };
+
+        for (i, param) in params.iter().enumerate() {
+            let index = i as c_int + 1;
+            // SAFETY: `stmt` is a prepared statement; text is copied (SQLITE_TRANSIENT)
+            let code = unsafe {
+                match *param {
+                    Value::Null => ffi::sqlite3_bind_null(stmt, index),
+                    Value::Integer(value) => ffi::sqlite3_bind_int64(stmt, index, value),
+                    Value::Real(value) => ffi::sqlite3_bind_double(stmt, index, value),
+                    Value::Text(text) => ffi::sqlite3_bind_text(
+                        stmt,
+                        index,
+                        text.as_ptr() as *const c_char,
+                        text.len() as c_int,
+                        ffi::SQLITE_TRANSIENT,
+                    ),
+                }
+            };
+            if code != ffi::SQLITE_OK {
+                return Err(anyhow!("SQLite error binding parameter {}: {}", index, self.error_message()));
+            }
+        }
+        Ok(statement)
+    }
+
+    fn error_message(&self) -> String {
+        if self.db.is_null() {
+            return "out of memory".to_string();
+        }
+        // SAFETY: valid handle; the message is owned by SQLite and copied here
+        unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.db)) }.to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // SAFETY: every statement borrows the connection, so all are finalized by now
+        unsafe { ffi::sqlite3_close(self.db) };
+    }
+}
+
+struct Statement<'c> {
+    connection: &'c Connection,
+    stmt: *mut ffi::sqlite3_stmt,
+}
+
+impl Statement<'_> {
+    /// Advance; `true` while a row is available
+    fn step(&self) -> Result<bool> {
+        // SAFETY: `stmt` is a prepared statement
+        match unsafe { ffi::sqlite3_step(self.stmt) } {
+            ffi::SQLITE_ROW => Ok(true),
+            ffi::SQLITE_DONE => Ok(false),
+            _ => Err(anyhow!("SQLite error: {}", self.connection.error_message())),
+        }
+    }
+}
+
+impl Drop for Statement<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `stmt` is finalized exactly once
+        unsafe { ffi::sqlite3_finalize(self.stmt) };
+    }
+}
+
+/// The current row of a query
+pub struct Row<'s> {
+    stmt: *mut ffi::sqlite3_stmt,
+    _statement: PhantomData<&'s ()>,
+}
+
+impl Row<'_> {
+    pub fn integer(&self, column: usize) -> i64 {
+        // SAFETY: the statement is positioned on a row
+        unsafe { ffi::sqlite3_column_int64(self.stmt, column as c_int) }
+    }
+
+    pub fn real(&self, column: usize) -> Option<f64> {
+        // SAFETY: the statement is positioned on a row
+        unsafe {
+            (ffi::sqlite3_column_type(self.stmt, column as c_int) != ffi::SQLITE_NULL)
+                .then(|| ffi::sqlite3_column_double(self.stmt, column as c_int))
+        }
+    }
+
+    pub fn text(&self, column: usize) -> Option<String> {
+        // SAFETY: the statement is positioned on a row; the text pointer is valid for
+        // `sqlite3_column_bytes` bytes until the next step
+        unsafe {
+            let text = ffi::sqlite3_column_text(self.stmt, column as c_int);
+            if text.is_null() {
+                return None;
+            }
+            let len = ffi::sqlite3_column_bytes(self.stmt, column as c_int) as usize;
+            Some(String::from_utf8_lossy(std::slice::from_raw_parts(text, len)).into_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Connection::open(&dir.path().join("test.db")).unwrap();
+        db.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT, score REAL)").unwrap();
+        db.execute("INSERT INTO t (name, score) VALUES (?1, ?2)", &[Value::Text("it's ünïcode"), Value::Real(0.5)]).unwrap();
+        db.execute("INSERT INTO t (name, score) VALUES (?1, ?2)", &[Value::Null, Value::Null]).unwrap();
+        assert_eq!(db.last_insert_rowid(), 2);
+
+        let rows = db.query("SELECT id, name, score FROM t ORDER BY id", &[], |row| Ok((row.integer(0), row.text(1), row.real(2)))).unwrap();
+        assert_eq!(rows, vec![(1, Some("it's ünïcode".to_string()), Some(0.5)), (2, None, None)]);
+        assert!(db.execute("INSERT INTO missing VALUES (1)", &[]).is_err());
+    }
+}
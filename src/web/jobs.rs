@@ -0,0 +1,433 @@
+//! Background scan jobs (`/api/jobs`)
+//!
+//! A job is queued and answered with its id right away; scans run on a fixed number
+//! of workers and clients poll `/api/jobs/{id}` for progress and the result, so slow
+//! targets no longer hold an HTTP request open. `/ws/scan/{id}` streams the same
+//! job as `JobEvent`s while it runs.
+
+use super::history::{HistoryEntry, HistoryStore, ScanKind};
+use crate::engine::DetectionEngine;
+use crate::payload::waf_smoke_test::{PayloadTestResult, SmokeTestConfig, WafSmokeTest};
+use crate::DetectionResult;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, Semaphore};
+
+/// Jobs running at once when none is configured
+pub const DEFAULT_WORKERS: usize = 4;
+/// Jobs waiting for a worker before new ones are refused
+pub const MAX_QUEUED: usize = 100;
+/// Finished jobs kept for polling; the oldest are dropped first
+pub const MAX_FINISHED: usize = 500;
+/// Targets of a batch job scanned at once
+const BATCH_CONCURRENCY: usize = 3;
+/// Events buffered per job for slow WebSocket clients before they start lagging
+const EVENT_CAPACITY: usize = 256;
+
+/// Body of `POST /api/jobs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum JobRequest {
+    Scan { url: String },
+    BatchScan { urls: Vec<String> },
+    SmokeTest { url: String },
+}
+
+impl JobRequest {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::BatchScan { urls } if urls.is_empty() => Err(anyhow!("urls must not be empty")),
+            _ => Ok(()),
+        }
+    }
+
+    fn total(&self) -> usize {
+        match self {
+            Self::BatchScan { urls } => urls.len(),
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+/// Targets done out of the job's total
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub request: JobRequest,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Same shape as the synchronous endpoint's `result`
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// Live progress of a running job, sent to `/ws/scan/{id}` subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum JobEvent {
+    /// The job as it was when the subscription started
+    Snapshot { job: Job },
+    Status { status: JobStatus },
+    /// One provider's score for a scanned target
+    Provider { url: String, provider: String, confidence: f64, detected: bool },
+    /// A target of a scan or batch job finished
+    Target { url: String, completed: usize, total: usize, error: Option<String> },
+    /// A smoke-test payload was sent and classified
+    Payload { completed: usize, total: usize, result: Box<PayloadTestResult> },
+    /// Last event of a job, carrying its result or error
+    Finished { job: Job },
+}
+
+/// Queue of scan jobs executed by a bounded pool of workers
+pub struct JobQueue {
+    engine: Arc<DetectionEngine>,
+    history: Arc<HistoryStore>,
+    jobs: DashMap<u64, Job>,
+    /// Event channels of jobs that have not finished yet
+    events: DashMap<u64, broadcast::Sender<JobEvent>>,
+    next_id: AtomicU64,
+    workers: Arc<Semaphore>,
+}
+
+impl JobQueue {
+    /// Finished scans are also recorded in `history`
+    pub fn new(engine: Arc<DetectionEngine>, history: Arc<HistoryStore>, workers: usize) -> Self {
+        Self {
+            engine,
+            history,
+            jobs: DashMap::new(),
+            events: DashMap::new(),
+            next_id: AtomicU64::new(0),
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+        }
+    }
+
+    /// Queue a job and start it as soon as a worker is free
+    pub fn submit(self: &Arc<Self>, request: JobRequest) -> Result<Job> {
+        request.validate()?;
+        let queued = self.jobs.iter().filter(|job| job.status == JobStatus::Queued).count();
+        if queued >= MAX_QUEUED {
+            return Err(anyhow!("Job queue is full ({} jobs waiting)", queued));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Job {
+            id,
+            progress: JobProgress { completed: 0, total: request.total() },
+            request,
+            status: JobStatus::Queued,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            result: None,
+            error: None,
+        };
+        self.events.insert(id, broadcast::channel(EVENT_CAPACITY).0);
+        self.jobs.insert(id, job.clone());
+        self.prune_finished();
+
+        let queue = self.clone();
+        tokio::spawn(crate::ratelimit::request_budget(async move { queue.run(id).await }));
+        Ok(job)
+    }
+
+    pub fn get(&self, id: u64) -> Option<Job> {
+        self.jobs.get(&id).map(|job| job.clone())
+    }
+
+    /// Current state of a job plus a receiver for its remaining events; the receiver
+    /// is `None` once the job has finished
+    pub fn subscribe(&self, id: u64) -> Option<(Job, Option<broadcast::Receiver<JobEvent>>)> {
+        // Subscribe before taking the snapshot so no event falls between the two
+        let receiver = self.events.get(&id).map(|sender| sender.subscribe());
+        let job = self.get(id)?;
+        Some((job.clone(), receiver.filter(|_| !job.status.is_finished())))
+    }
+
+    /// All jobs, newest first
+    pub fn list(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self.jobs.iter().map(|job| job.clone()).collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.id));
+        jobs
+    }
+
+    async fn run(&self, id: u64) {
+        let Ok(_permit) = self.workers.clone().acquire_owned().await else { return };
+        let Some(request) = self.update(id, |job| {
+            job.status = JobStatus::Running;
+            job.started_at = Some(Utc::now());
+        }).map(|job| job.request) else { return };
+        self.emit(id, JobEvent::Status { status: JobStatus::Running });
+
+        let outcome = self.execute(id, &request).await;
+        let finished = self.update(id, |job| {
+            job.finished_at = Some(Utc::now());
+            match outcome {
+                Ok(result) => {
+                    job.status = JobStatus::Completed;
+                    job.progress.completed = job.progress.total;
+                    job.result = Some(result);
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+        });
+        if let Some(job) = finished {
+            self.emit(id, JobEvent::Finished { job });
+        }
+        self.events.remove(&id);
+    }
+
+    async fn execute(&self, id: u64, request: &JobRequest) -> Result<serde_json::Value> {
+        match request {
+            JobRequest::Scan { url } => {
+                let result = self.engine.detect(url).await;
+                self.emit_target(id, url, &result, 1, 1);
+                self.record_detection(url, &result);
+                Ok(serde_json::to_value(result?)?)
+            }
+            JobRequest::BatchScan { urls } => {
+                use futures::stream::{self, StreamExt};
+
+                let completed = AtomicUsize::new(0);
+                let completed = &completed;
+                let total = urls.len();
+                let results: Vec<_> = stream::iter(urls.clone())
+                    .map(|url| async move {
+                        let result = self.engine.detect(&url).await;
+                        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                        self.update(id, |job| job.progress.completed = done);
+                        self.emit_target(id, &url, &result, done, total);
+                        self.record_detection(&url, &result);
+                        result
+                    })
+                    .buffered(BATCH_CONCURRENCY)
+                    .collect()
+                    .await;
+                let results = results.into_iter().collect::<Result<Vec<_>>>()?;
+                Ok(serde_json::to_value(results)?)
+            }
+            JobRequest::SmokeTest { url } => {
+                let (sender, mut payloads) = mpsc::unbounded_channel();
+                let smoke_test = WafSmokeTest::new(SmokeTestConfig::default())?.with_progress(sender);
+                let total = smoke_test.payload_count();
+                self.update(id, |job| job.progress.total = total);
+
+                // The test owns the sender, so the forwarder ends when the test does
+                let test = async move { smoke_test.run_test(url).await };
+                let forward = async {
+                    let mut completed = 0;
+                    while let Some(result) = payloads.recv().await {
+                        completed += 1;
+                        self.update(id, |job| job.progress.completed = completed);
+                        self.emit(id, JobEvent::Payload { completed, total, result: Box::new(result) });
+                    }
+                };
+                let (result, ()) = tokio::join!(test, forward);
+                let mut result = match result {
+                    Ok(result) => result,
+                    Err(e) => {
+                        self.history.record_or_warn(HistoryEntry::failed(url, ScanKind::SmokeTest, &e));
+                        return Err(e);
+                    }
+                };
+                result.is_smoke_test = true;
+                self.history.record_or_warn(HistoryEntry::from_smoke_test(&result));
+                Ok(serde_json::to_value(result)?)
+            }
+        }
+    }
+
+    fn record_detection(&self, url: &str, result: &Result<DetectionResult>) {
+        let entry = match result {
+            Ok(result) => HistoryEntry::from_detection(result),
+            Err(e) => HistoryEntry::failed(url, ScanKind::Detection, e),
+        };
+        self.history.record_or_warn(entry);
+    }
+
+    /// Send an event to the job's subscribers, if any
+    fn emit(&self, id: u64, event: JobEvent) {
+        if let Some(sender) = self.events.get(&id) {
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Provider scores of a finished target, strongest first, then the target itself
+    fn emit_target(&self, id: u64, url: &str, result: &Result<DetectionResult>, completed: usize, total: usize) {
+        let error = match result {
+            Ok(result) => {
+                let detected: Vec<&str> = [&result.detected_waf, &result.detected_cdn]
+                    .into_iter()
+                    .flatten()
+                    .map(|d| d.name.as_str())
+                    .collect();
+                let mut scores: Vec<(&String, &f64)> = result.provider_scores.iter().collect();
+                scores.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                for (provider, confidence) in scores {
+                    self.emit(id, JobEvent::Provider {
+                        url: url.to_string(),
+                        provider: provider.clone(),
+                        confidence: *confidence,
+                        detected: detected.contains(&provider.as_str()),
+                    });
+                }
+                result.error.as_ref().map(|e| e.message.clone())
+            }
+            Err(e) => Some(e.to_string()),
+        };
+        self.emit(id, JobEvent::Target { url: url.to_string(), completed, total, error });
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut Job)) -> Option<Job> {
+        let mut job = self.jobs.get_mut(&id)?;
+        change(&mut job);
+        Some(job.clone())
+    }
+
+    /// Drop the oldest finished jobs beyond `MAX_FINISHED`
+    fn prune_finished(&self) {
+        let mut finished: Vec<u64> = self.jobs.iter().filter(|job| job.status.is_finished()).map(|job| job.id).collect();
+        if finished.len() > MAX_FINISHED {
+            finished.sort_unstable();
+            for id in &finished[..finished.len() - MAX_FINISHED] {
+                self.jobs.remove(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::default_providers;
+    use crate::registry::ProviderRegistry;
+    use crate::DetectionOptions;
+    use std::time::Duration;
+
+    fn queue(workers: usize) -> Arc<JobQueue> {
+        let registry = ProviderRegistry::new();
+        for provider in default_providers() {
+            registry.register_provider(provider).unwrap();
+        }
+        let engine = DetectionEngine::new(registry).with_options(DetectionOptions { dns: false, ..DetectionOptions::default() });
+        Arc::new(JobQueue::new(Arc::new(engine), Arc::new(HistoryStore::in_memory()), workers))
+    }
+
+    async fn wait_finished(queue: &JobQueue, id: u64) -> Job {
+        for _ in 0..200 {
+            let job = queue.get(id).unwrap();
+            if job.status.is_finished() {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn test_batch_job_reports_progress_and_result() {
+        let mut server = mockito::Server::new_async().await;
+        let _page = server.mock("GET", mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("server", "cloudflare")
+            .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+            .expect_at_least(0)
+            .create_async()
+            .await;
+        let queue = queue(2);
+
+        let urls = vec![format!("{}/a", server.url()), format!("{}/b", server.url())];
+        let job = queue.submit(JobRequest::BatchScan { urls }).unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.progress, JobProgress { completed: 0, total: 2 });
+
+        let job = wait_finished(&queue, job.id).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.progress.completed, 2);
+        let results = job.result.unwrap();
+        assert_eq!(results.as_array().unwrap().len(), 2);
+        assert_eq!(results[0]["detected_waf"]["name"], "CloudFlare");
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events_until_finished() {
+        let mut server = mockito::Server::new_async().await;
+        let _page = server.mock("GET", "/")
+            .with_status(200)
+            .with_header("server", "cloudflare")
+            .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+            .create_async()
+            .await;
+        let queue = queue(1);
+
+        let job = queue.submit(JobRequest::Scan { url: server.url() }).unwrap();
+        let (snapshot, events) = queue.subscribe(job.id).unwrap();
+        assert_eq!(snapshot.status, JobStatus::Queued);
+        let mut events = events.unwrap();
+
+        let mut received = Vec::new();
+        loop {
+            let event = events.recv().await.unwrap();
+            let last = matches!(event, JobEvent::Finished { .. });
+            received.push(event);
+            if last {
+                break;
+            }
+        }
+
+        assert!(matches!(received[0], JobEvent::Status { status: JobStatus::Running }));
+        assert!(received.iter().any(|e| matches!(e, JobEvent::Provider { provider, detected: true, .. } if provider == "CloudFlare")));
+        assert!(received.iter().any(|e| matches!(e, JobEvent::Target { completed: 1, total: 1, error: None, .. })));
+        let JobEvent::Finished { job } = received.last().unwrap() else { unreachable!() };
+        assert_eq!(job.status, JobStatus::Completed);
+
+        // Finished jobs only have a snapshot left
+        let (snapshot, events) = queue.subscribe(job.id).unwrap();
+        assert_eq!(snapshot.status, JobStatus::Completed);
+        assert!(events.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_scan_and_empty_batch() {
+        let queue = queue(1);
+
+        let job = queue.submit(JobRequest::Scan { url: "not a url".to_string() }).unwrap();
+        let job = wait_finished(&queue, job.id).await;
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.is_some());
+
+        assert!(queue.submit(JobRequest::BatchScan { urls: Vec::new() }).is_err());
+        assert_eq!(queue.list().len(), 1);
+    }
+}
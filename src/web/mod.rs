@@ -1,30 +1,66 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, Request, State},
     http::StatusCode,
-    response::{Html, IntoResponse},
+    response::{Html, IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use std::sync::Arc;
-use tower_http::{services::ServeDir, cors::CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use serde::{Deserialize, Serialize};
 use crate::engine::DetectionEngine;
+use crate::exporters::Exporters;
+use crate::providers::ProviderMetadata;
 use crate::DetectionResult;
-use crate::script_executor::{ScriptExecutor, CombinedResult};
+use crate::payload::combined::{self, CombinedResult, EffectivenessError};
+use crate::script_executor::ScriptExecutor;
 use crate::payload::waf_smoke_test::{WafSmokeTest, SmokeTestConfig, SmokeTestResult};
 use anyhow::Result;
 
-pub mod templates;
+pub mod api;
+pub mod assets;
+pub mod auth;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod history;
+pub mod jobs;
+pub mod pages;
+pub mod projects;
+pub mod schedules;
+pub mod ws;
+
+use auth::{AuthConfig, WebAuth};
+use history::{HistoryEntry, HistoryQuery, HistoryStore, ScanKind};
+use jobs::{Job, JobEvent, JobQueue, JobRequest};
+use projects::{Project, ProjectRequest, ProjectStore};
+use schedules::{Schedule, ScheduleRequest, ScheduleStore};
+
+/// URLs of a `/api/batch-scan` request scanned at once
+const BATCH_SCAN_WORKERS: usize = 5;
 
 #[derive(Clone)]
 pub struct WebServer {
     engine: Arc<DetectionEngine>,
-    script_executor: Arc<ScriptExecutor>,
+    /// Runs `/api/combined-scan` effectiveness tests instead of the native smoke test
+    script_executor: Option<Arc<ScriptExecutor>>,
+    schedules: Arc<ScheduleStore>,
+    projects: Arc<ProjectStore>,
+    jobs: Arc<JobQueue>,
+    job_workers: usize,
+    history: Arc<HistoryStore>,
+    auth: Arc<WebAuth>,
+    /// Receive scheduled scan results and change events
+    exporters: Arc<Exporters>,
+    /// Leave out the startup banner
+    quiet: bool,
 }
 
 #[derive(Deserialize)]
 pub struct ScanRequest {
     url: String,
+    /// Run with the project's settings and record the scan in its history
+    #[serde(default)]
+    project: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -37,12 +73,24 @@ pub struct ScanResponse {
 #[derive(Deserialize)]
 pub struct BatchScanRequest {
     urls: Vec<String>,
+    #[serde(default)]
+    project: Option<String>,
+}
+
+/// Outcome of one URL of a batch scan
+#[derive(Serialize)]
+pub struct BatchScanEntry {
+    url: String,
+    success: bool,
+    result: Option<DetectionResult>,
+    error: Option<String>,
 }
 
 #[derive(Serialize)]
 pub struct BatchScanResponse {
     success: bool,
-    results: Vec<DetectionResult>,
+    /// One entry per requested URL, in request order
+    results: Vec<BatchScanEntry>,
     error: Option<String>,
 }
 
@@ -60,53 +108,323 @@ pub struct SmokeTestResponse {
     error: Option<String>,
 }
 
+#[derive(Serialize)]
+pub struct ScheduleResponse {
+    success: bool,
+    schedule: Option<Schedule>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct JobResponse {
+    success: bool,
+    job: Option<Job>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct JobListResponse {
+    success: bool,
+    jobs: Vec<Job>,
+}
+
+#[derive(Serialize)]
+pub struct ProviderListResponse {
+    success: bool,
+    providers: Vec<ProviderMetadata>,
+}
+
+#[derive(Deserialize)]
+pub struct ProviderUpdateRequest {
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct ProviderResponse {
+    success: bool,
+    provider: Option<ProviderMetadata>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProjectResponse {
+    success: bool,
+    project: Option<Project>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProjectListResponse {
+    success: bool,
+    projects: Vec<Project>,
+}
+
+#[derive(Serialize)]
+pub struct ScheduleListResponse {
+    success: bool,
+    schedules: Vec<Schedule>,
+}
+
 impl WebServer {
     pub fn new(engine: DetectionEngine) -> Self {
+        let engine = Arc::new(engine);
+        let history = Arc::new(HistoryStore::in_memory());
         Self {
-            engine: Arc::new(engine),
-            script_executor: Arc::new(ScriptExecutor::default()),
+            jobs: Arc::new(JobQueue::new(engine.clone(), history.clone(), jobs::DEFAULT_WORKERS)),
+            job_workers: jobs::DEFAULT_WORKERS,
+            history,
+            engine,
+            script_executor: None,
+            schedules: Arc::new(ScheduleStore::in_memory()),
+            projects: Arc::new(ProjectStore::in_memory()),
+            auth: Arc::new(WebAuth::new(AuthConfig::default()).expect("default auth config is valid")),
+            exporters: Arc::new(Exporters::default()),
+            quiet: false,
         }
     }
 
-    pub async fn start(self, port: u16) -> Result<()> {
-        let app = Router::new()
+    /// Require API keys or basic auth, rate-limit each credential and/or write an audit log
+    pub fn with_auth(mut self, config: AuthConfig) -> Result<Self> {
+        self.auth = Arc::new(WebAuth::new(config)?);
+        Ok(self)
+    }
+
+    /// Number of background jobs (`/api/jobs`) running at once
+    pub fn with_job_workers(mut self, workers: usize) -> Self {
+        self.job_workers = workers;
+        self.jobs = Arc::new(JobQueue::new(self.engine.clone(), self.history.clone(), workers));
+        self
+    }
+
+    /// Record scans in `store` (in memory by default)
+    pub fn with_history_store(mut self, store: HistoryStore) -> Self {
+        self.history = Arc::new(store);
+        let workers = self.job_workers;
+        self.with_job_workers(workers)
+    }
+
+    /// Keep scheduled scans in `store` (in memory by default)
+    pub fn with_schedule_store(mut self, store: ScheduleStore) -> Self {
+        self.schedules = Arc::new(store);
+        self
+    }
+
+    /// Send scheduled scan results and change events to `exporters`
+    pub fn with_exporters(mut self, exporters: Exporters) -> Self {
+        self.exporters = Arc::new(exporters);
+        self
+    }
+
+    /// Run `/api/combined-scan` effectiveness tests with the legacy test script
+    pub fn with_script_executor(mut self, executor: ScriptExecutor) -> Self {
+        self.script_executor = Some(Arc::new(executor));
+        self
+    }
+
+    /// Keep projects in `store` (in memory by default)
+    pub fn with_project_store(mut self, store: ProjectStore) -> Self {
+        self.projects = Arc::new(store);
+        self
+    }
+
+    /// Start without printing the banner to stderr (`--quiet`)
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// A startup line on stderr, unless quiet
+    fn banner(&self, line: std::fmt::Arguments) {
+        if !self.quiet {
+            eprintln!("{}", line);
+        }
+    }
+
+    fn record_detection(&self, url: &str, outcome: &Result<DetectionResult>, project: Option<&str>) {
+        let entry = match outcome {
+            Ok(result) => HistoryEntry::from_detection(result),
+            Err(e) => HistoryEntry::failed(url, ScanKind::Detection, e),
+        };
+        self.history.record_or_warn(entry.in_project(project));
+    }
+
+    /// The project a request names; `Err` with a message if it does not exist
+    fn project(&self, id: Option<&str>) -> std::result::Result<Option<Project>, String> {
+        match id {
+            Some(id) => self.projects.get(id).map(Some).ok_or_else(|| format!("Project '{}' not found", id)),
+            None => Ok(None),
+        }
+    }
+
+    /// The engine, with the project's techniques if there is one
+    fn engine_for(&self, project: Option<&Project>) -> Arc<DetectionEngine> {
+        match project {
+            Some(project) => Arc::new(self.engine.as_ref().clone().with_options(project.options.clone())),
+            None => self.engine.clone(),
+        }
+    }
+
+    /// Scan `urls` concurrently and record them; failed URLs come back as entries with
+    /// `error` set, so one bad URL does not void the batch
+    async fn scan_batch(&self, urls: &[String], project: Option<&Project>) -> Result<Vec<BatchScanEntry>> {
+        let targets: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let scanned = self.engine_for(project).detect_batch(&targets, BATCH_SCAN_WORKERS).await?;
+        let project_id = project.map(|p| p.id.as_str());
+        for result in scanned.values() {
+            self.history.record_or_warn(HistoryEntry::from_detection(result).in_project(project_id));
+        }
+
+        Ok(urls
+            .iter()
+            .map(|url| {
+                // Repeated URLs share one scan
+                let result = scanned.get(url).cloned();
+                let error = match &result {
+                    Some(result) => result.error.as_ref().map(|e| e.message.clone()),
+                    None => Some("URL was not scanned".to_string()),
+                };
+                BatchScanEntry { url: url.clone(), success: error.is_none(), result: result.filter(|_| error.is_none()), error }
+            })
+            .collect())
+    }
+
+    /// All routes, with authentication and CORS applied
+    pub fn router(self) -> Router {
+        let auth = self.auth.clone();
+        let cors = cors_layer(auth.config());
+
+        let router = Router::new()
             // Static files
-            .nest_service("/static", ServeDir::new("web/static"))
+            .route("/static/*path", get(assets::serve))
             // API routes
             .route("/api/scan", post(scan_url))
             .route("/api/combined-scan", post(combined_scan))
             .route("/api/smoke-test", post(smoke_test))
             .route("/api/batch-scan", post(batch_scan))
             .route("/api/providers", get(list_providers))
+            .route("/api/providers/:name", post(update_provider))
             .route("/api/status", get(server_status))
+            .route("/api/jobs", get(list_jobs).post(submit_job))
+            .route("/api/jobs/:id", get(get_job))
+            .route("/ws/scan/:job_id", get(stream_job))
+            .route("/api/results", get(list_results))
+            .route("/api/results/:id", get(get_result))
+            .route("/api/projects", get(list_projects).post(create_project))
+            .route("/api/projects/:id", get(get_project).put(update_project).delete(delete_project))
+            .route("/api/projects/:id/scan", post(scan_project))
+            .route("/api/projects/:id/results", get(list_project_results))
+            .route("/api/schedules", get(list_schedules).post(create_schedule))
+            .route("/api/schedules/:id", get(get_schedule).put(update_schedule).delete(delete_schedule))
+            .route("/api/schedules/:id/run", post(run_schedule_now))
             // Web pages
             .route("/", get(dashboard))
             .route("/dashboard", get(dashboard))
             .route("/api-docs", get(api_docs))
-            // Add CORS for development
-            .layer(CorsLayer::permissive())
-            .with_state(self);
+            .route("/history", get(history_page))
+            .route("/history/:id", get(history_entry_page));
+        #[cfg(feature = "grpc")]
+        let router = router.merge(grpc::routes());
+
+        router
+            .layer(axum::middleware::from_fn(request_budget))
+            .layer(axum::middleware::from_fn_with_state(auth, auth::require_auth))
+            // CORS runs first so preflight requests, which carry no credentials, are answered
+            .layer(cors)
+            .with_state(self)
+    }
+
+    /// Only the tool-integration endpoints (`api::routes`), restricted to loopback `Host`s
+    pub fn api_router(self) -> Router {
+        let auth = self.auth.clone();
+        api::routes()
+            .layer(axum::middleware::from_fn(request_budget))
+            .layer(axum::middleware::from_fn_with_state(auth, auth::require_auth))
+            .layer(axum::middleware::from_fn(api::require_loopback_host))
+            .with_state(self)
+    }
+
+    /// Serve `api_router` on 127.0.0.1, without the dashboard or the scheduler
+    pub async fn start_api_only(self, port: u16) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+        self.banner(format_args!("🔌 WAF Detector API listening on http://127.0.0.1:{} (POST /api/passive, POST /api/detect)", port));
+        let app = self.api_router();
+        axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await?;
+        Ok(())
+    }
+
+    pub async fn start(self, port: u16) -> Result<()> {
+        tokio::spawn(schedules::run_scheduler(self.schedules.clone(), self.engine.clone(), self.exporters.clone()));
+        if self.auth.config().is_enabled() {
+            self.banner(format_args!("🔐 Authentication required ({} API keys, {} users)", self.auth.config().api_keys.len(), self.auth.config().users.len()));
+        }
+        if let Some(path) = &self.auth.config().audit_log {
+            self.banner(format_args!("📝 Audit log: {}", path.display()));
+        }
 
         let addr = format!("0.0.0.0:{}", port);
-        println!("🌐 WAF Detector Web Server starting on http://localhost:{}", port);
-        println!("📊 Dashboard: http://localhost:{}/dashboard", port);
-        println!("📖 API Docs: http://localhost:{}/api-docs", port);
+        self.banner(format_args!("🌐 WAF Detector Web Server starting on http://localhost:{}", port));
+        self.banner(format_args!("📊 Dashboard: http://localhost:{}/dashboard", port));
+        self.banner(format_args!("📖 API Docs: http://localhost:{}/api-docs", port));
+        let app = self.router();
         
         let listener = tokio::net::TcpListener::bind(&addr).await?;
-        axum::serve(listener, app).await?;
+        axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await?;
         
         Ok(())
     }
 }
 
+/// Give each API request its own per-host request budget (`--max-requests-per-host`)
+async fn request_budget(request: Request, next: axum::middleware::Next) -> Response {
+    crate::ratelimit::request_budget(next.run(request)).await
+}
+
+/// Permissive CORS for development; once authentication is on, only the configured origins
+fn cors_layer(config: &AuthConfig) -> CorsLayer {
+    if !config.is_enabled() {
+        return CorsLayer::permissive();
+    }
+    let origins = config.cors_origins.iter().filter_map(|origin| origin.parse().ok());
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(Any)
+        .allow_headers([axum::http::header::AUTHORIZATION, axum::http::header::CONTENT_TYPE, axum::http::HeaderName::from_static("x-api-key")])
+}
+
+/// A rendered page, or 500 if a template failed to render
+fn page(rendered: Result<String>) -> Response {
+    match rendered {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to render page: {}", e)).into_response(),
+    }
+}
+
 // Handler for the main dashboard
-async fn dashboard() -> impl IntoResponse {
-    Html(templates::DASHBOARD_HTML)
+async fn dashboard() -> Response {
+    page(pages::dashboard())
 }
 
 // Handler for API documentation
-async fn api_docs() -> impl IntoResponse {
-    Html(templates::API_DOCS_HTML)
+async fn api_docs() -> Response {
+    page(pages::api_docs())
+}
+
+// Handler for the server-rendered scan history
+async fn history_page(State(server): State<WebServer>, Query(query): Query<HistoryQuery>) -> Response {
+    if let Err(e) = query.validate() {
+        return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
+    let host = query.host.as_deref().map(str::trim).filter(|host| !host.is_empty());
+    page(server.history.page(&query).and_then(|history| pages::history(&history, host)))
+}
+
+// Handler for one past scan rendered with its result card and evidence
+async fn history_entry_page(State(server): State<WebServer>, Path(id): Path<i64>) -> Response {
+    match server.history.get(id) {
+        Ok(Some(entry)) => page(pages::history_entry(&entry)),
+        Ok(None) => (StatusCode::NOT_FOUND, format!("Scan {} not found", id)).into_response(),
+        Err(e) => page(Err(e)),
+    }
 }
 
 // Handler for single URL scan
@@ -114,7 +432,13 @@ async fn scan_url(
     State(server): State<WebServer>,
     Json(payload): Json<ScanRequest>,
 ) -> impl IntoResponse {
-    match server.engine.detect(&payload.url).await {
+    let project = match server.project(payload.project.as_deref()) {
+        Ok(project) => project,
+        Err(e) => return (StatusCode::NOT_FOUND, Json(ScanResponse { success: false, result: None, error: Some(e) })),
+    };
+    let outcome = server.engine_for(project.as_ref()).detect(&payload.url).await;
+    server.record_detection(&payload.url, &outcome, payload.project.as_deref());
+    match outcome {
         Ok(result) => {
             let response = ScanResponse {
                 success: true,
@@ -139,22 +463,23 @@ async fn batch_scan(
     State(server): State<WebServer>,
     Json(payload): Json<BatchScanRequest>,
 ) -> impl IntoResponse {
-    let mut results = Vec::new();
-    
-    for url in &payload.urls {
-        match server.engine.detect(url).await {
-            Ok(result) => results.push(result),
-            Err(e) => {
-                let response = BatchScanResponse {
-                    success: false,
-                    results: vec![],
-                    error: Some(format!("Error scanning {}: {}", url, e)),
-                };
-                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
-            }
-        }
+    if payload.urls.is_empty() {
+        let response = BatchScanResponse { success: false, results: Vec::new(), error: Some("urls must not be empty".to_string()) };
+        return (StatusCode::BAD_REQUEST, Json(response));
     }
-    
+
+    let project = match server.project(payload.project.as_deref()) {
+        Ok(project) => project,
+        Err(e) => return (StatusCode::NOT_FOUND, Json(BatchScanResponse { success: false, results: Vec::new(), error: Some(e) })),
+    };
+    let results = match server.scan_batch(&payload.urls, project.as_ref()).await {
+        Ok(results) => results,
+        Err(e) => {
+            let response = BatchScanResponse { success: false, results: Vec::new(), error: Some(e.to_string()) };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
+
     let response = BatchScanResponse {
         success: true,
         results,
@@ -163,33 +488,23 @@ async fn batch_scan(
     (StatusCode::OK, Json(response))
 }
 
-// Handler for provider list
-async fn list_providers() -> impl IntoResponse {
-    let providers = vec![
-        serde_json::json!({
-            "name": "CloudFlare",
-            "version": "1.0.0",
-            "type": "Both",
-            "description": "CloudFlare WAF and CDN detection"
-        }),
-        serde_json::json!({
-            "name": "AWS",
-            "version": "1.0.0", 
-            "type": "Both",
-            "description": "AWS WAF and CloudFront CDN detection"
-        }),
-        serde_json::json!({
-            "name": "Akamai",
-            "version": "1.0.0",
-            "type": "Both", 
-            "description": "Akamai WAF and CDN detection"
-        }),
-    ];
-    
-    Json(serde_json::json!({
-        "success": true,
-        "providers": providers
-    }))
+// Handler for provider list, straight from the engine's registry
+async fn list_providers(State(server): State<WebServer>) -> impl IntoResponse {
+    Json(ProviderListResponse { success: true, providers: server.engine.list_providers() })
+}
+
+// Handler for enabling or disabling a provider at runtime; applies to every later scan
+async fn update_provider(
+    State(server): State<WebServer>,
+    Path(name): Path<String>,
+    Json(payload): Json<ProviderUpdateRequest>,
+) -> impl IntoResponse {
+    let registry = server.engine.registry();
+    if let Err(e) = registry.set_provider_enabled(&name, payload.enabled) {
+        return (StatusCode::NOT_FOUND, Json(ProviderResponse { success: false, provider: None, error: Some(e.to_string()) }));
+    }
+    let provider = server.engine.list_providers().into_iter().find(|p| p.name.eq_ignore_ascii_case(name.trim()));
+    (StatusCode::OK, Json(ProviderResponse { success: true, provider, error: None }))
 }
 
 // Handler for server status
@@ -228,18 +543,20 @@ async fn combined_scan(
     };
     
     // Then, run effectiveness testing (optional, may fail)
-    let effectiveness_result = match server.script_executor.execute_test(&payload.url).await {
-        Ok(result) => Some(result),
-        Err(e) => {
-            println!("Warning: Effectiveness testing failed: {}", e);
-            None // Continue without effectiveness testing
-        }
+    let effectiveness_result = match &server.script_executor {
+        Some(executor) => executor.execute_test(&payload.url).await.map_err(|e| EffectivenessError::from(&e)),
+        None => combined::test_effectiveness(&payload.url, combined::smoke_test_config())
+            .await
+            .map_err(|e| EffectivenessError::from_error(&e)),
     };
+    if let Err(e) = &effectiveness_result {
+        tracing::warn!(url = %payload.url, error = %e.message, "effectiveness testing failed");
+    }
     
     let total_time = start_time.elapsed().as_millis() as u64;
     
     // Combine results
-    let combined_result = server.script_executor.combine_results(
+    let combined_result = combined::combine(
         detection_result,
         effectiveness_result,
         total_time,
@@ -256,17 +573,17 @@ async fn combined_scan(
 
 // Handler for WAF smoke test with detailed payload results
 async fn smoke_test(
-    State(_server): State<WebServer>,
+    State(server): State<WebServer>,
     Json(payload): Json<ScanRequest>,
 ) -> impl IntoResponse {
-    println!("[smoke_test] Handler entered for URL: {}", payload.url);
+    tracing::debug!(url = %payload.url, "smoke test requested");
     // Create smoke test configuration
     let config = SmokeTestConfig::default();
     // Create and run smoke test
     let smoke_test = match WafSmokeTest::new(config) {
         Ok(test) => test,
         Err(e) => {
-            eprintln!("[smoke_test] Failed to create smoke test for URL {}: {}", payload.url, e);
+            tracing::error!(url = %payload.url, error = %e, "failed to create smoke test");
             let response = SmokeTestResponse {
                 success: false,
                 result: None,
@@ -279,7 +596,8 @@ async fn smoke_test(
     match smoke_test.run_test(&payload.url).await {
         Ok(mut result) => {
             result.is_smoke_test = true;
-            println!("[smoke_test] Successfully ran smoke test for URL: {}", payload.url);
+            server.history.record_or_warn(HistoryEntry::from_smoke_test(&result).in_project(payload.project.as_deref()));
+            tracing::info!(url = %payload.url, "smoke test finished");
             let response = SmokeTestResponse {
                 success: true,
                 result: Some(result),
@@ -288,7 +606,8 @@ async fn smoke_test(
             (StatusCode::OK, Json(response))
         }
         Err(e) => {
-            eprintln!("[smoke_test] Smoke test failed for URL {}: {}", payload.url, e);
+            tracing::warn!(url = %payload.url, error = %e, "smoke test failed");
+            server.history.record_or_warn(HistoryEntry::failed(&payload.url, ScanKind::SmokeTest, &e).in_project(payload.project.as_deref()));
             let response = SmokeTestResponse {
                 success: false,
                 result: None,
@@ -299,4 +618,244 @@ async fn smoke_test(
     }
 }
 
- 
\ No newline at end of file
+ 
+fn project_response(status: StatusCode, project: Option<Project>, error: Option<String>) -> (StatusCode, Json<ProjectResponse>) {
+    (status, Json(ProjectResponse { success: error.is_none(), project, error }))
+}
+
+fn project_not_found(id: &str) -> (StatusCode, Json<ProjectResponse>) {
+    project_response(StatusCode::NOT_FOUND, None, Some(format!("Project '{}' not found", id)))
+}
+
+// Handler for listing projects
+async fn list_projects(State(server): State<WebServer>) -> impl IntoResponse {
+    Json(ProjectListResponse { success: true, projects: server.projects.list() })
+}
+
+// Handler for creating a project; 409 if its name maps to an existing project id
+async fn create_project(
+    State(server): State<WebServer>,
+    Json(payload): Json<ProjectRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = payload.validate() {
+        return project_response(StatusCode::BAD_REQUEST, None, Some(e.to_string()));
+    }
+    if server.projects.get(&payload.id()).is_some() {
+        return project_response(StatusCode::CONFLICT, None, Some(format!("Project '{}' already exists", payload.id())));
+    }
+    match server.projects.create(payload) {
+        Ok(project) => project_response(StatusCode::CREATED, Some(project), None),
+        Err(e) => project_response(StatusCode::INTERNAL_SERVER_ERROR, None, Some(e.to_string())),
+    }
+}
+
+async fn get_project(State(server): State<WebServer>, Path(id): Path<String>) -> impl IntoResponse {
+    match server.projects.get(&id) {
+        Some(project) => project_response(StatusCode::OK, Some(project), None),
+        None => project_not_found(&id),
+    }
+}
+
+async fn update_project(
+    State(server): State<WebServer>,
+    Path(id): Path<String>,
+    Json(payload): Json<ProjectRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = payload.validate() {
+        return project_response(StatusCode::BAD_REQUEST, None, Some(e.to_string()));
+    }
+    match server.projects.update(&id, payload) {
+        Ok(Some(project)) => project_response(StatusCode::OK, Some(project), None),
+        Ok(None) => project_not_found(&id),
+        Err(e) => project_response(StatusCode::INTERNAL_SERVER_ERROR, None, Some(e.to_string())),
+    }
+}
+
+// Handler for deleting a project; its scans stay in the history
+async fn delete_project(State(server): State<WebServer>, Path(id): Path<String>) -> impl IntoResponse {
+    match server.projects.delete(&id) {
+        Ok(true) => project_response(StatusCode::OK, None, None),
+        Ok(false) => project_not_found(&id),
+        Err(e) => project_response(StatusCode::INTERNAL_SERVER_ERROR, None, Some(e.to_string())),
+    }
+}
+
+// Handler for scanning every target of a project with its settings
+async fn scan_project(State(server): State<WebServer>, Path(id): Path<String>) -> impl IntoResponse {
+    let failed = |status, error: String| (status, Json(BatchScanResponse { success: false, results: Vec::new(), error: Some(error) }));
+    let Some(project) = server.projects.get(&id) else {
+        return failed(StatusCode::NOT_FOUND, format!("Project '{}' not found", id));
+    };
+    if project.targets.is_empty() {
+        return failed(StatusCode::BAD_REQUEST, format!("Project '{}' has no targets", id));
+    }
+    match server.scan_batch(&project.targets, Some(&project)).await {
+        Ok(results) => (StatusCode::OK, Json(BatchScanResponse { success: true, results, error: None })),
+        Err(e) => failed(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+// Handler for a project's scan history; takes the same filters as /api/results
+async fn list_project_results(
+    State(server): State<WebServer>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    if server.projects.get(&id).is_none() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "success": false, "error": format!("Project '{}' not found", id) })));
+    }
+    list_results(State(server), Query(HistoryQuery { project: Some(id), ..query })).await
+}
+
+fn schedule_response(status: StatusCode, schedule: Option<Schedule>, error: Option<String>) -> (StatusCode, Json<ScheduleResponse>) {
+    (status, Json(ScheduleResponse { success: error.is_none(), schedule, error }))
+}
+
+/// 200 with the schedule, 404 if it does not exist, 500 if it could not be saved
+fn schedule_result(id: u64, result: Result<Option<Schedule>>) -> (StatusCode, Json<ScheduleResponse>) {
+    match result {
+        Ok(Some(schedule)) => schedule_response(StatusCode::OK, Some(schedule), None),
+        Ok(None) => schedule_response(StatusCode::NOT_FOUND, None, Some(format!("Schedule {} not found", id))),
+        Err(e) => schedule_response(StatusCode::INTERNAL_SERVER_ERROR, None, Some(e.to_string())),
+    }
+}
+
+// Handler for listing scheduled scans
+async fn list_schedules(State(server): State<WebServer>) -> impl IntoResponse {
+    Json(ScheduleListResponse { success: true, schedules: server.schedules.list() })
+}
+
+// Handler for creating a scheduled scan
+async fn create_schedule(
+    State(server): State<WebServer>,
+    Json(payload): Json<ScheduleRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = payload.validate() {
+        return schedule_response(StatusCode::BAD_REQUEST, None, Some(e.to_string()));
+    }
+    match server.schedules.create(payload) {
+        Ok(schedule) => schedule_response(StatusCode::CREATED, Some(schedule), None),
+        Err(e) => schedule_response(StatusCode::INTERNAL_SERVER_ERROR, None, Some(e.to_string())),
+    }
+}
+
+async fn get_schedule(State(server): State<WebServer>, Path(id): Path<u64>) -> impl IntoResponse {
+    schedule_result(id, Ok(server.schedules.get(id)))
+}
+
+async fn update_schedule(
+    State(server): State<WebServer>,
+    Path(id): Path<u64>,
+    Json(payload): Json<ScheduleRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = payload.validate() {
+        return schedule_response(StatusCode::BAD_REQUEST, None, Some(e.to_string()));
+    }
+    schedule_result(id, server.schedules.update(id, payload))
+}
+
+async fn delete_schedule(State(server): State<WebServer>, Path(id): Path<u64>) -> impl IntoResponse {
+    match server.schedules.delete(id) {
+        Ok(true) => schedule_response(StatusCode::OK, None, None),
+        Ok(false) => schedule_response(StatusCode::NOT_FOUND, None, Some(format!("Schedule {} not found", id))),
+        Err(e) => schedule_response(StatusCode::INTERNAL_SERVER_ERROR, None, Some(e.to_string())),
+    }
+}
+
+// Handler for running a schedule at the next scheduler tick instead of waiting for its interval
+async fn run_schedule_now(State(server): State<WebServer>, Path(id): Path<u64>) -> impl IntoResponse {
+    schedule_result(id, server.schedules.trigger(id))
+}
+
+// Handler for browsing past scans, newest first, optionally for one host (`?host=&page=&per_page=`)
+async fn list_results(State(server): State<WebServer>, Query(query): Query<HistoryQuery>) -> (StatusCode, Json<serde_json::Value>) {
+    if let Err(e) = query.validate() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "success": false, "error": e.to_string() })));
+    }
+    match server.history.page(&query) {
+        Ok(page) => (StatusCode::OK, Json(serde_json::json!({ "success": true, "history": page }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "success": false, "error": e.to_string() }))),
+    }
+}
+
+// Handler for one past scan with its full result
+async fn get_result(State(server): State<WebServer>, Path(id): Path<i64>) -> impl IntoResponse {
+    match server.history.get(id) {
+        Ok(Some(entry)) => (StatusCode::OK, Json(serde_json::json!({ "success": true, "entry": entry }))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "success": false, "error": format!("Result {} not found", id) }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "success": false, "error": e.to_string() }))),
+    }
+}
+
+// Handler for queueing a background scan; answers 202 with the job to poll
+async fn submit_job(
+    State(server): State<WebServer>,
+    Json(payload): Json<JobRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, Json(JobResponse { success: false, job: None, error: Some(e.to_string()) }));
+    }
+    match server.jobs.submit(payload) {
+        Ok(job) => (StatusCode::ACCEPTED, Json(JobResponse { success: true, job: Some(job), error: None })),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, Json(JobResponse { success: false, job: None, error: Some(e.to_string()) })),
+    }
+}
+
+async fn list_jobs(State(server): State<WebServer>) -> impl IntoResponse {
+    Json(JobListResponse { success: true, jobs: server.jobs.list() })
+}
+
+async fn get_job(State(server): State<WebServer>, Path(id): Path<u64>) -> impl IntoResponse {
+    match server.jobs.get(id) {
+        Some(job) => (StatusCode::OK, Json(JobResponse { success: true, job: Some(job), error: None })),
+        None => (StatusCode::NOT_FOUND, Json(JobResponse { success: false, job: None, error: Some(format!("Job {} not found", id)) })),
+    }
+}
+
+// WebSocket handler streaming a job's events: a snapshot first, then progress until `finished`
+async fn stream_job(State(server): State<WebServer>, Path(id): Path<u64>, request: Request) -> Response {
+    let Some((job, events)) = server.jobs.subscribe(id) else {
+        return (StatusCode::NOT_FOUND, Json(JobResponse { success: false, job: None, error: Some(format!("Job {} not found", id)) })).into_response();
+    };
+    ws::upgrade(request, move |mut socket| async move {
+        let send = |event: &JobEvent| serde_json::to_string(event).unwrap_or_default();
+        let finished = job.status.is_finished().then(|| JobEvent::Finished { job: job.clone() });
+        if socket.send_text(&send(&JobEvent::Snapshot { job })).await.is_err() {
+            return;
+        }
+
+        if let Some(mut events) = events {
+            loop {
+                tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(event) => {
+                            let last = matches!(event, JobEvent::Finished { .. });
+                            if socket.send_text(&send(&event)).await.is_err() {
+                                return;
+                            }
+                            if last {
+                                break;
+                            }
+                        }
+                        // A slow client misses some progress events but still gets `finished`
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                            if let Some(job) = server.jobs.get(id) {
+                                let _ = socket.send_text(&send(&JobEvent::Finished { job })).await;
+                            }
+                            break;
+                        }
+                    },
+                    frame = socket.recv() => if frame.is_none() {
+                        return;
+                    },
+                }
+            }
+        } else if let Some(finished) = finished {
+            if socket.send_text(&send(&finished)).await.is_err() {
+                return;
+            }
+        }
+        let _ = socket.close().await;
+    })
+}
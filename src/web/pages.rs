@@ -0,0 +1,85 @@
+//! Server-rendered pages of the web interface
+
+use super::history::{HistoryEntry, HistoryPage, ScanKind};
+use crate::payload::waf_smoke_test::SmokeTestResult;
+use crate::render::{self, views};
+use crate::DetectionResult;
+use anyhow::Result;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::{json, Value};
+
+pub fn dashboard() -> Result<String> {
+    render::render("dashboard", &json!({ "version": env!("CARGO_PKG_VERSION") }))
+}
+
+pub fn api_docs() -> Result<String> {
+    render::render("api_docs", &json!({}))
+}
+
+fn history_link(host: Option<&str>, page: usize) -> String {
+    match host {
+        Some(host) => format!("/history?host={}&page={}", utf8_percent_encode(host, NON_ALPHANUMERIC), page),
+        None => format!("/history?page={}", page),
+    }
+}
+
+/// When the scan ran, and how old it is when served from history
+fn scanned_at(entry: &HistoryEntry) -> String {
+    let at = entry.scanned_at.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    match &entry.cached {
+        Some(cached) => format!("{} ({} old)", at, cached.describe()),
+        None => at,
+    }
+}
+
+/// `/history`: one page of past scans, optionally for one host
+pub fn history(page: &HistoryPage, host: Option<&str>) -> Result<String> {
+    let pages = page.total.div_ceil(page.per_page).max(1);
+    let rows: Vec<Value> = page
+        .entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "id": entry.id,
+                "host": entry.host,
+                "kind": entry.kind,
+                "scanned_at": scanned_at(entry),
+                "waf": entry.waf.as_deref().unwrap_or("None"),
+                "cdn": entry.cdn.as_deref().unwrap_or("None"),
+                "effectiveness": entry.effectiveness.map(|e| format!("{:.1}%", e)),
+                "error": entry.error,
+                "changes": entry.changes,
+            })
+        })
+        .collect();
+    render::render("history", &json!({
+        "host": host,
+        "total": page.total,
+        "page": page.page,
+        "pages": pages,
+        "entries": (!rows.is_empty()).then(|| json!({ "rows": rows })),
+        "prev": (page.page > 1).then(|| history_link(host, page.page - 1)),
+        "next": (page.page < pages).then(|| history_link(host, page.page + 1)),
+    }))
+}
+
+/// `/history/:id`: one past scan, with its result card and evidence
+pub fn history_entry(entry: &HistoryEntry) -> Result<String> {
+    let result = entry.result.clone().unwrap_or(Value::Null);
+    let (detection, smoke_test) = match entry.kind {
+        ScanKind::Detection => (serde_json::from_value::<DetectionResult>(result).ok().map(|r| views::detection(&r)), None),
+        ScanKind::SmokeTest => (None, serde_json::from_value::<SmokeTestResult>(result).ok().map(|r| views::smoke_test(&r))),
+    };
+    render::render("history_entry", &json!({
+        "id": entry.id,
+        "host": entry.host,
+        "url": entry.url,
+        "kind": entry.kind,
+        "scanned_at": scanned_at(entry),
+        "history_link": history_link(Some(&entry.host), 1),
+        "changes": entry.changes,
+        "error": entry.error,
+        "detection": detection,
+        "smoke_test": smoke_test,
+    }))
+}
@@ -0,0 +1,212 @@
+//! Projects for the web server (`/api/projects`)
+//!
+//! A project groups the scans of one engagement: its own target list, the techniques
+//! its scans run with, and the history entries recorded for it. Projects are kept in a
+//! JSON file that is rewritten after every change, like the schedules.
+
+use crate::utils::validate_url;
+use crate::DetectionOptions;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Body of `POST /api/projects` and `PUT /api/projects/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Techniques the project's scans run; omitted flags keep their defaults
+    #[serde(default)]
+    pub options: Option<DetectionOptions>,
+}
+
+impl ProjectRequest {
+    pub fn validate(&self) -> Result<()> {
+        if slug(&self.name).is_empty() {
+            return Err(anyhow!("name must contain at least one letter or digit"));
+        }
+        for target in &self.targets {
+            validate_url(target)?;
+        }
+        Ok(())
+    }
+
+    /// Id of the project this request creates
+    pub fn id(&self) -> String {
+        slug(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    /// URL-safe form of the name it was created with, e.g. `acme-corp`
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub targets: Vec<String>,
+    pub options: DetectionOptions,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Lowercase letters and digits, other runs of characters collapsed to `-`
+pub fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Projects, optionally persisted to a JSON file
+#[derive(Debug, Default)]
+pub struct ProjectStore {
+    path: Option<PathBuf>,
+    projects: RwLock<BTreeMap<String, Project>>,
+}
+
+impl ProjectStore {
+    /// A store that is lost on restart
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load projects from `path`; the file is created on the first change
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let projects = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read projects '{}': {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| anyhow!("Invalid projects file '{}': {}", path.display(), e))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { path: Some(path.to_path_buf()), projects: RwLock::new(projects) })
+    }
+
+    pub fn list(&self) -> Vec<Project> {
+        self.projects.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Project> {
+        self.projects.read().unwrap().get(id).cloned()
+    }
+
+    /// Add a project; fails if one with the same id exists
+    pub fn create(&self, request: ProjectRequest) -> Result<Project> {
+        request.validate()?;
+        let mut projects = self.projects.write().unwrap();
+        let id = request.id();
+        if projects.contains_key(&id) {
+            return Err(anyhow!("Project '{}' already exists", id));
+        }
+        let now = Utc::now();
+        let project = Project {
+            id,
+            name: request.name.trim().to_string(),
+            description: request.description,
+            targets: request.targets,
+            options: request.options.unwrap_or_default(),
+            created_at: now,
+            updated_at: now,
+        };
+        projects.insert(project.id.clone(), project.clone());
+        self.persist(&projects)?;
+        Ok(project)
+    }
+
+    /// Replace a project's settings; its id stays the same even if the name changes.
+    /// `None` if it does not exist
+    pub fn update(&self, id: &str, request: ProjectRequest) -> Result<Option<Project>> {
+        request.validate()?;
+        let mut projects = self.projects.write().unwrap();
+        let Some(project) = projects.get_mut(id) else { return Ok(None) };
+        project.name = request.name.trim().to_string();
+        project.description = request.description;
+        project.targets = request.targets;
+        project.options = request.options.unwrap_or_default();
+        project.updated_at = Utc::now();
+        let project = project.clone();
+        self.persist(&projects)?;
+        Ok(Some(project))
+    }
+
+    /// Remove a project; scans recorded for it stay in the history
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        let mut projects = self.projects.write().unwrap();
+        let removed = projects.remove(id).is_some();
+        if removed {
+            self.persist(&projects)?;
+        }
+        Ok(removed)
+    }
+
+    /// Write through a temporary file so a crash never leaves a truncated store
+    fn persist(&self, projects: &BTreeMap<String, Project>) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(projects)?)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| anyhow!("Failed to save projects '{}': {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str, targets: &[&str]) -> ProjectRequest {
+        ProjectRequest {
+            name: name.to_string(),
+            description: None,
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            options: None,
+        }
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("  ACME Corp. (EU) "), "acme-corp-eu");
+        assert_eq!(slug("Müller & Söhne"), "müller-söhne");
+        assert_eq!(slug("!!!"), "");
+    }
+
+    #[test]
+    fn test_projects_are_validated_and_unique() {
+        let store = ProjectStore::in_memory();
+        assert!(store.create(request("???", &[])).is_err());
+        assert!(store.create(request("Acme", &["example.com"])).is_err());
+        let acme = store.create(request("Acme", &["https://acme.example"])).unwrap();
+        assert_eq!(acme.id, "acme");
+        assert!(store.create(request("ACME", &[])).is_err());
+
+        let renamed = store.update("acme", request("Acme Corp", &[])).unwrap().unwrap();
+        assert_eq!((renamed.id.as_str(), renamed.name.as_str()), ("acme", "Acme Corp"));
+        assert!(store.update("nope", request("Nope", &[])).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_store_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+
+        let store = ProjectStore::open(&path).unwrap();
+        let first = store.create(request("First", &["https://a.example"])).unwrap();
+        let second = store.create(request("Second", &["https://b.example"])).unwrap();
+        assert!(store.delete(&first.id).unwrap());
+
+        assert_eq!(ProjectStore::open(&path).unwrap().list(), vec![second]);
+    }
+}
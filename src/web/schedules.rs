@@ -0,0 +1,396 @@
+//! Recurring scans for the web server (`/api/schedules`)
+//!
+//! Schedules live in a JSON file that is rewritten after every change, so they survive
+//! restarts. A background task started by `WebServer::start` checks every second for
+//! due schedules and runs their targets as a batch with the schedule's techniques.
+
+use crate::engine::DetectionEngine;
+use crate::exporters::{ChangeEvent, Exporters};
+use crate::utils::validate_url;
+use crate::{DetectionOptions, DetectionResult};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Shortest allowed interval, so a schedule cannot hammer its targets
+pub const MIN_INTERVAL_SECS: u64 = 60;
+/// Targets of one schedule scanned at once
+const SCHEDULE_CONCURRENCY: usize = 3;
+/// How often the scheduler looks for due schedules
+const SCHEDULER_TICK: Duration = Duration::from_secs(1);
+
+/// Body of `POST /api/schedules` and `PUT /api/schedules/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub targets: Vec<String>,
+    pub interval_secs: u64,
+    /// Techniques to run; omitted flags keep their defaults (header analysis and DNS only)
+    #[serde(default)]
+    pub options: Option<DetectionOptions>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ScheduleRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.targets.is_empty() {
+            return Err(anyhow!("targets must not be empty"));
+        }
+        for target in &self.targets {
+            validate_url(target)?;
+        }
+        if self.interval_secs < MIN_INTERVAL_SECS {
+            return Err(anyhow!("interval_secs must be at least {}", MIN_INTERVAL_SECS));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of one target in a scheduled run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetOutcome {
+    pub url: String,
+    pub waf: Option<String>,
+    pub cdn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub targets: Vec<TargetOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub targets: Vec<String>,
+    pub interval_secs: u64,
+    pub options: DetectionOptions,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub next_run: DateTime<Utc>,
+    #[serde(default)]
+    pub last_run: Option<ScheduleRun>,
+}
+
+impl Schedule {
+    fn apply(&mut self, request: ScheduleRequest, now: DateTime<Utc>) {
+        let interval_changed = request.interval_secs != self.interval_secs;
+        self.name = request.name;
+        self.targets = request.targets;
+        self.interval_secs = request.interval_secs;
+        self.options = request.options.unwrap_or_default();
+        self.enabled = request.enabled;
+        if interval_changed {
+            self.next_run = now + interval(self.interval_secs);
+        }
+    }
+}
+
+fn interval(secs: u64) -> ChronoDuration {
+    ChronoDuration::seconds(secs.min(i64::MAX as u64) as i64)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreState {
+    next_id: u64,
+    schedules: BTreeMap<u64, Schedule>,
+}
+
+/// Schedules, optionally persisted to a JSON file
+#[derive(Debug, Default)]
+pub struct ScheduleStore {
+    path: Option<PathBuf>,
+    state: RwLock<StoreState>,
+}
+
+impl ScheduleStore {
+    /// A store that is lost on restart
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load schedules from `path`; the file is created on the first change
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let state = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read schedules '{}': {}", path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| anyhow!("Invalid schedules file '{}': {}", path.display(), e))?
+        } else {
+            StoreState::default()
+        };
+        Ok(Self { path: Some(path.to_path_buf()), state: RwLock::new(state) })
+    }
+
+    pub fn list(&self) -> Vec<Schedule> {
+        self.state.read().unwrap().schedules.values().cloned().collect()
+    }
+
+    pub fn get(&self, id: u64) -> Option<Schedule> {
+        self.state.read().unwrap().schedules.get(&id).cloned()
+    }
+
+    /// Add a schedule; the first run is one interval from now
+    pub fn create(&self, request: ScheduleRequest) -> Result<Schedule> {
+        request.validate()?;
+        let now = Utc::now();
+        let mut state = self.state.write().unwrap();
+        state.next_id += 1;
+        let schedule = Schedule {
+            id: state.next_id,
+            name: request.name,
+            targets: request.targets,
+            interval_secs: request.interval_secs,
+            options: request.options.unwrap_or_default(),
+            enabled: request.enabled,
+            created_at: now,
+            next_run: now + interval(request.interval_secs),
+            last_run: None,
+        };
+        state.schedules.insert(schedule.id, schedule.clone());
+        self.persist(&state)?;
+        Ok(schedule)
+    }
+
+    /// Replace a schedule's settings, keeping its history; `None` if it does not exist
+    pub fn update(&self, id: u64, request: ScheduleRequest) -> Result<Option<Schedule>> {
+        request.validate()?;
+        self.modify(id, |schedule| schedule.apply(request, Utc::now()))
+    }
+
+    /// Run a schedule at the next scheduler tick
+    pub fn trigger(&self, id: u64) -> Result<Option<Schedule>> {
+        self.modify(id, |schedule| schedule.next_run = Utc::now())
+    }
+
+    pub fn delete(&self, id: u64) -> Result<bool> {
+        let mut state = self.state.write().unwrap();
+        let removed = state.schedules.remove(&id).is_some();
+        if removed {
+            self.persist(&state)?;
+        }
+        Ok(removed)
+    }
+
+    /// Enabled schedules due at `now`; their next run is moved one interval ahead so
+    /// a slow run is not started twice
+    pub fn take_due(&self, now: DateTime<Utc>) -> Vec<Schedule> {
+        let mut state = self.state.write().unwrap();
+        let mut due = Vec::new();
+        for schedule in state.schedules.values_mut() {
+            if schedule.enabled && schedule.next_run <= now {
+                schedule.next_run = now + interval(schedule.interval_secs);
+                due.push(schedule.clone());
+            }
+        }
+        if !due.is_empty() {
+            if let Err(e) = self.persist(&state) {
+                tracing::warn!(error = %e, "failed to save schedules");
+            }
+        }
+        due
+    }
+
+    /// Store the outcome of a run; ignored if the schedule was deleted meanwhile
+    pub fn record_run(&self, id: u64, run: ScheduleRun) -> Result<()> {
+        self.modify(id, |schedule| schedule.last_run = Some(run)).map(|_| ())
+    }
+
+    fn modify(&self, id: u64, change: impl FnOnce(&mut Schedule)) -> Result<Option<Schedule>> {
+        let mut state = self.state.write().unwrap();
+        let Some(schedule) = state.schedules.get_mut(&id) else { return Ok(None) };
+        change(schedule);
+        let schedule = schedule.clone();
+        self.persist(&state)?;
+        Ok(Some(schedule))
+    }
+
+    /// Write through a temporary file so a crash never leaves a truncated store
+    fn persist(&self, state: &StoreState) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(state)?)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| anyhow!("Failed to save schedules '{}': {}", path.display(), e))
+    }
+}
+
+/// Scan a schedule's targets once; also returns the results of the scans that completed
+pub async fn run_schedule(engine: &DetectionEngine, schedule: &Schedule) -> (ScheduleRun, Vec<DetectionResult>) {
+    use futures::stream::{self, StreamExt};
+
+    let started_at = Utc::now();
+    let engine = engine.clone().with_options(schedule.options.clone());
+    let engine = &engine;
+    let scans: Vec<(String, Result<DetectionResult>)> = stream::iter(schedule.targets.clone())
+        .map(|url| async move {
+            let result = engine.detect(&url).await;
+            (url, result)
+        })
+        .buffered(SCHEDULE_CONCURRENCY)
+        .collect()
+        .await;
+    let mut targets = Vec::with_capacity(scans.len());
+    let mut results = Vec::new();
+    for (url, scan) in scans {
+        match scan {
+            Ok(result) => {
+                targets.push(TargetOutcome {
+                    waf: result.waf_name().map(str::to_string),
+                    cdn: result.cdn_name().map(str::to_string),
+                    error: None,
+                    url,
+                });
+                results.push(result);
+            }
+            Err(e) => targets.push(TargetOutcome { url, waf: None, cdn: None, error: Some(e.to_string()) }),
+        }
+    }
+    (ScheduleRun { started_at, finished_at: Utc::now(), targets }, results)
+}
+
+/// Targets whose WAF or CDN differs from the schedule's previous run; targets that
+/// failed on either run are skipped, since a failed scan says nothing about the edge
+pub fn changes(schedule: &Schedule, run: &ScheduleRun) -> Vec<ChangeEvent> {
+    let Some(previous) = &schedule.last_run else { return Vec::new() };
+    run.targets
+        .iter()
+        .filter(|outcome| outcome.error.is_none())
+        .filter_map(|outcome| {
+            let before = previous.targets.iter().find(|before| before.url == outcome.url && before.error.is_none())?;
+            (before.waf != outcome.waf || before.cdn != outcome.cdn).then(|| ChangeEvent {
+                url: outcome.url.clone(),
+                timestamp: run.finished_at,
+                schedule: Some(schedule.name.clone().unwrap_or_else(|| schedule.id.to_string())),
+                previous_waf: before.waf.clone(),
+                previous_cdn: before.cdn.clone(),
+                waf: outcome.waf.clone(),
+                cdn: outcome.cdn.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Background loop running due schedules; each run is its own task. Results and
+/// change events go to `exporters`.
+pub async fn run_scheduler(store: Arc<ScheduleStore>, engine: Arc<DetectionEngine>, exporters: Arc<Exporters>) {
+    let mut tick = tokio::time::interval(SCHEDULER_TICK);
+    loop {
+        tick.tick().await;
+        for schedule in store.take_due(Utc::now()) {
+            let store = store.clone();
+            let engine = engine.clone();
+            let exporters = exporters.clone();
+            tokio::spawn(crate::ratelimit::request_budget(async move {
+                let (run, results) = run_schedule(&engine, &schedule).await;
+                let changes = changes(&schedule, &run);
+                for change in &changes {
+                    tracing::info!(schedule = schedule.id, "{}", change.summary());
+                }
+                exporters.scans(&results).await;
+                exporters.changes(&changes).await;
+                if let Err(e) = store.record_run(schedule.id, run) {
+                    tracing::warn!(schedule = schedule.id, error = %e, "failed to record schedule run");
+                }
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(targets: &[&str], interval_secs: u64) -> ScheduleRequest {
+        ScheduleRequest {
+            name: Some("prod".to_string()),
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            interval_secs,
+            options: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_requests_are_validated() {
+        let store = ScheduleStore::in_memory();
+        assert!(store.create(request(&[], 300)).is_err());
+        assert!(store.create(request(&["example.com"], 300)).is_err());
+        assert!(store.create(request(&["https://example.com"], 10)).is_err());
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn test_take_due_advances_next_run() {
+        let store = ScheduleStore::in_memory();
+        let schedule = store.create(request(&["https://example.com"], 300)).unwrap();
+
+        assert!(store.take_due(Utc::now()).is_empty());
+        let later = schedule.next_run + ChronoDuration::seconds(1);
+        assert_eq!(store.take_due(later).len(), 1);
+        assert!(store.take_due(later).is_empty());
+        assert_eq!(store.get(schedule.id).unwrap().next_run, later + ChronoDuration::seconds(300));
+    }
+
+    #[test]
+    fn test_store_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedules.json");
+
+        let store = ScheduleStore::open(&path).unwrap();
+        let first = store.create(request(&["https://a.example"], 300)).unwrap();
+        let second = store.create(request(&["https://b.example"], 600)).unwrap();
+        assert!(store.delete(first.id).unwrap());
+
+        let reopened = ScheduleStore::open(&path).unwrap();
+        assert_eq!(reopened.list(), vec![second]);
+        // Ids are never reused
+        assert_eq!(reopened.create(request(&["https://c.example"], 300)).unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_changes_against_previous_run() {
+        let outcome = |url: &str, waf: Option<&str>, error: Option<&str>| TargetOutcome {
+            url: url.to_string(),
+            waf: waf.map(str::to_string),
+            cdn: None,
+            error: error.map(str::to_string),
+        };
+        let run = |targets| ScheduleRun { started_at: Utc::now(), finished_at: Utc::now(), targets };
+        let mut schedule = ScheduleStore::in_memory().create(request(&["https://a.example", "https://b.example", "https://c.example"], 300)).unwrap();
+        let current = run(vec![
+            outcome("https://a.example", None, None),
+            outcome("https://b.example", Some("AWS WAF"), None),
+            outcome("https://c.example", None, Some("timed out")),
+        ]);
+        assert!(changes(&schedule, &current).is_empty());
+
+        schedule.last_run = Some(run(vec![
+            outcome("https://a.example", Some("CloudFlare"), None),
+            outcome("https://b.example", Some("AWS WAF"), None),
+            outcome("https://c.example", Some("Akamai"), None),
+        ]));
+        let changes = changes(&schedule, &current);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].url, "https://a.example");
+        assert_eq!(changes[0].schedule.as_deref(), Some("prod"));
+        assert!(changes[0].waf_lost());
+    }
+}
@@ -0,0 +1,226 @@
+//! Minimal server-side WebSocket (RFC 6455) on top of hyper's connection upgrades
+//!
+//! Enough for pushing JSON events to the dashboard: the opening handshake, text and
+//! close frames out, and reading client frames to answer pings and notice closes.
+//! Fragmented client messages are not reassembled.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use hyper_util::rt::TokioIo;
+use std::future::Future;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Largest client frame accepted; clients only send control frames here
+const MAX_CLIENT_FRAME: u64 = 64 * 1024;
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xa;
+
+/// A frame received from the client, already unmasked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub opcode: u8,
+    pub payload: Vec<u8>,
+}
+
+/// An upgraded connection
+pub struct WebSocket {
+    io: TokioIo<hyper::upgrade::Upgraded>,
+}
+
+impl WebSocket {
+    pub async fn send_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.send_frame(OPCODE_TEXT, text.as_bytes()).await
+    }
+
+    /// Send a normal-closure frame; the connection should be dropped afterwards
+    pub async fn close(&mut self) -> std::io::Result<()> {
+        self.send_frame(OPCODE_CLOSE, &1000u16.to_be_bytes()).await
+    }
+
+    /// Next data frame from the client; pings are answered, `None` on close or error
+    pub async fn recv(&mut self) -> Option<Frame> {
+        loop {
+            let frame = read_frame(&mut self.io).await.ok()?;
+            match frame.opcode {
+                OPCODE_CLOSE => return None,
+                OPCODE_PING => self.send_frame(OPCODE_PONG, &frame.payload).await.ok()?,
+                OPCODE_PONG => {}
+                _ => return Some(frame),
+            }
+        }
+    }
+
+    async fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        self.io.write_all(&encode_frame(opcode, payload)).await?;
+        self.io.flush().await
+    }
+}
+
+/// Answer a WebSocket handshake with 101 and run `handler` on the upgraded connection;
+/// anything that is not a valid handshake gets 400
+pub fn upgrade<F, Fut>(mut request: Request, handler: F) -> Response
+where
+    F: FnOnce(WebSocket) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let headers = request.headers();
+    let has_token = |name: header::HeaderName, token: &str| {
+        headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).any(|v| {
+            v.split(',').any(|part| part.trim().eq_ignore_ascii_case(token))
+        })
+    };
+    let valid = has_token(header::CONNECTION, "upgrade")
+        && has_token(header::UPGRADE, "websocket")
+        && headers.get(header::SEC_WEBSOCKET_VERSION).is_some_and(|v| v == "13");
+    let Some(key) = headers.get(header::SEC_WEBSOCKET_KEY).filter(|_| valid).map(|k| k.as_bytes().to_vec()) else {
+        return (StatusCode::BAD_REQUEST, "Expected a WebSocket upgrade").into_response();
+    };
+
+    let on_upgrade = hyper::upgrade::on(&mut request);
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => handler(WebSocket { io: TokioIo::new(upgraded) }).await,
+            Err(e) => tracing::warn!(error = %e, "WebSocket upgrade failed"),
+        }
+    });
+
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let headers = response.headers_mut();
+    headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+    headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    if let Ok(accept) = HeaderValue::from_str(&accept_key(&key)) {
+        headers.insert(header::SEC_WEBSOCKET_ACCEPT, accept);
+    }
+    response
+}
+
+/// `Sec-WebSocket-Accept` for a client key
+pub fn accept_key(key: &[u8]) -> String {
+    let mut input = key.to_vec();
+    input.extend_from_slice(HANDSHAKE_GUID.as_bytes());
+    crate::utils::base64_encode(&sha1(&input))
+}
+
+/// Unmasked, unfragmented server frame
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+async fn read_frame<R: AsyncReadExt + Unpin>(reader: &mut R) -> std::io::Result<Frame> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let opcode = head[0] & 0x0f;
+    let masked = head[1] & 0x80 != 0;
+    let len = match head[1] & 0x7f {
+        126 => reader.read_u16().await? as u64,
+        127 => reader.read_u64().await?,
+        len => len as u64,
+    };
+    if len > MAX_CLIENT_FRAME {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "WebSocket frame too large"));
+    }
+
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok(Frame { opcode, payload })
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_key_matches_rfc_example() {
+        assert_eq!(accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[tokio::test]
+    async fn test_masked_client_frame_round_trip() {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut frame = vec![0x89, 0x80 | 5];
+        frame.extend_from_slice(&mask);
+        frame.extend(b"Hello".iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+
+        let parsed = read_frame(&mut frame.as_slice()).await.unwrap();
+        assert_eq!(parsed, Frame { opcode: OPCODE_PING, payload: b"Hello".to_vec() });
+
+        let long = encode_frame(OPCODE_TEXT, &[b'x'; 300]);
+        assert_eq!(&long[..4], &[0x81, 126, 0x01, 0x2c]);
+    }
+}
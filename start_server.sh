@@ -14,9 +14,6 @@ fi
 echo "✅ Build successful!"
 echo "🚀 Starting web server on port 8080..."
 
-# Create web directory if it doesn't exist
-mkdir -p web/static
-
 # Start the web server
 cargo run --bin waf-detect -- --web --port 8080
 
@@ -35,7 +35,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Test timing analyzer
     writeln!(temp_file, "Testing timing analyzer...")?;
-    let timing_analyzer = TimingAnalyzer::new(Default::default());
+    let timing_analyzer = TimingAnalyzer::new(Default::default())?;
     let timing_result = timing_analyzer.analyze("https://example.com").await;
     writeln!(temp_file, "Timing analysis result: {:?}", timing_result.is_ok())?;
     if let Ok(evidence) = &timing_result {
@@ -0,0 +1,72 @@
+mod common;
+
+use common::engine;
+use waf_detector::agents::{Agent, AgentEndpoint, Coordinator};
+use waf_detector::providers::default_providers;
+use waf_detector::DetectionOptions;
+
+/// Serve an agent on an ephemeral port and return its base URL
+async fn serve_agent(region: &str, token: &str) -> String {
+    let app = Agent::new(engine(default_providers()), region).with_token(token).router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_coordinator_merges_agent_scans() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let endpoint = |region: &str, url: &str, token: &str| AgentEndpoint {
+        region: region.to_string(),
+        url: url.to_string(),
+        token: Some(token.to_string()),
+    };
+    let eu = serve_agent("eu-west", "secret").await;
+    let us = serve_agent("us-east", "secret").await;
+
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let report = Coordinator::new(vec![endpoint("eu-west", &eu, "secret"), endpoint("us-east", &us, "secret")])
+        .scan(&target.url(), Some(&options))
+        .await;
+    assert!(report.consistent(), "{:?}", report.differences);
+    assert_eq!(report.providers["CloudFlare"], vec!["eu-west", "us-east"]);
+
+    let report = Coordinator::new(vec![endpoint("eu-west", &eu, "secret"), endpoint("us-east", &us, "wrong")])
+        .scan(&target.url(), Some(&options))
+        .await;
+    assert_eq!(report.regions[1].error.as_deref(), Some(format!("agent {} rejected the token", us).as_str()));
+    assert!(!report.consistent());
+}
+
+#[tokio::test]
+async fn test_compare_direct_scan_with_agent() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let agent = AgentEndpoint::parse(&format!("eu-west={}", serve_agent("eu-west", "secret").await)).unwrap();
+    let agent = AgentEndpoint { token: Some("secret".to_string()), ..agent };
+
+    let coordinator = Coordinator::new(vec![agent]).with_local(engine(default_providers()));
+    assert_eq!(coordinator.vantage_count(), 2);
+    let report = coordinator.scan(&target.url(), None).await;
+
+    let regions: Vec<&str> = report.regions.iter().map(|scan| scan.region.as_str()).collect();
+    assert_eq!(regions, vec!["direct", "eu-west"]);
+    assert!(report.regions.iter().all(|scan| scan.addresses.is_some()));
+    assert!(!report.split_horizon_dns);
+    assert!(report.consistent(), "{:?}", report.differences);
+}
@@ -1,5 +1,5 @@
 use waf_detector::*;
-use std::collections::HashMap;
+use waf_detector::http::Headers;
 
 #[tokio::test]
 async fn test_akamai_provider_creation() {
@@ -14,14 +14,15 @@ async fn test_akamai_provider_creation() {
 async fn test_akamai_server_header_detection() {
     let provider = providers::akamai::AkamaiProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("server".to_string(), "AkamaiGHost".to_string());
+    let mut headers = Headers::new();
+    headers.insert("server", "AkamaiGHost".to_string());
     
     let response = http::HttpResponse {
         status: 200,
         headers,
         body: String::new(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.check_headers(&response).await;
@@ -36,15 +37,16 @@ async fn test_akamai_server_header_detection() {
 async fn test_akamai_x_cache_header_detection() {
     let provider = providers::akamai::AkamaiProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-cache".to_string(), "TCP_HIT from a23-45-67-89.deploy.akamaitechnologies.com".to_string());
-    headers.insert("x-cache-remote".to_string(), "TCP_HIT from a12-34-56-78.deploy.akamaitechnologies.com".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-cache", "TCP_HIT from a23-45-67-89.deploy.akamaitechnologies.com".to_string());
+    headers.insert("x-cache-remote", "TCP_HIT from a12-34-56-78.deploy.akamaitechnologies.com".to_string());
     
     let response = http::HttpResponse {
         status: 200,
         headers,
         body: String::new(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.check_headers(&response).await;
@@ -61,15 +63,16 @@ async fn test_akamai_x_cache_header_detection() {
 async fn test_akamai_reference_header_detection() {
     let provider = providers::akamai::AkamaiProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-akamai-request-id".to_string(), "1a2b3c4d".to_string());
-    headers.insert("x-akamai-session-info".to_string(), "name=AKA_PM_TD_FD_CACHE; value=hit".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-akamai-request-id", "1a2b3c4d".to_string());
+    headers.insert("x-akamai-session-info", "name=AKA_PM_TD_FD_CACHE; value=hit".to_string());
     
     let response = http::HttpResponse {
         status: 200,
         headers,
         body: String::new(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.check_headers(&response).await;
@@ -85,7 +88,7 @@ async fn test_akamai_error_page_detection() {
     
     let response = http::HttpResponse {
         status: 403,
-        headers: HashMap::new(),
+        headers: Default::default(),
         body: r#"
             <HTML><HEAD><TITLE>Access Denied</TITLE></HEAD>
             <BODY>
@@ -96,6 +99,7 @@ async fn test_akamai_error_page_detection() {
             </BODY></HTML>
         "#.to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.check_body_patterns(&response).await;
@@ -111,9 +115,10 @@ async fn test_akamai_reference_id_pattern() {
     
     let response = http::HttpResponse {
         status: 403,
-        headers: HashMap::new(),
+        headers: Default::default(),
         body: "Reference #18.7f123456.1703123456.2a3b4c5d - Access denied".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.check_body_patterns(&response).await;
@@ -127,15 +132,16 @@ async fn test_akamai_reference_id_pattern() {
 async fn test_akamai_multiple_detection_methods() {
     let provider = providers::akamai::AkamaiProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("server".to_string(), "AkamaiGHost".to_string());
-    headers.insert("x-cache".to_string(), "TCP_HIT from a23-45-67-89.deploy.akamaitechnologies.com".to_string());
+    let mut headers = Headers::new();
+    headers.insert("server", "AkamaiGHost".to_string());
+    headers.insert("x-cache", "TCP_HIT from a23-45-67-89.deploy.akamaitechnologies.com".to_string());
     
     let response = http::HttpResponse {
         status: 200,
         headers,
         body: String::new(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let header_evidence = provider.check_headers(&response).await;
@@ -159,15 +165,16 @@ async fn test_akamai_no_false_positives() {
     let provider = providers::akamai::AkamaiProvider::new();
     
     // Test with non-Akamai response
-    let mut headers = HashMap::new();
-    headers.insert("server".to_string(), "nginx".to_string());
-    headers.insert("x-powered-by".to_string(), "PHP".to_string());
+    let mut headers = Headers::new();
+    headers.insert("server", "nginx".to_string());
+    headers.insert("x-powered-by", "PHP".to_string());
     
     let response = http::HttpResponse {
         status: 200,
         headers,
         body: "Regular website content".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.check_headers(&response).await;
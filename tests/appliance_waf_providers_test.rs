@@ -0,0 +1,87 @@
+mod common;
+
+use common::response;
+use waf_detector::*;
+
+#[tokio::test]
+async fn test_barracuda_session_cookie() {
+    let provider = providers::barracuda::BarracudaProvider::new();
+    let response = response(200, &[
+        ("set-cookie", "JSESSIONID=abc; Path=/\nbarra_counter_session=1234567890; path=/; HttpOnly"),
+    ], "");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].signature_matched, "barracuda-cookie");
+    assert_eq!(evidence[0].confidence, 0.95);
+    assert_eq!(evidence[0].raw_data, "barra_counter_session");
+}
+
+#[tokio::test]
+async fn test_barracuda_block_page() {
+    let provider = providers::barracuda::BarracudaProvider::new();
+    let response = response(403, &[], "<html><body>You have been blocked by the Barracuda Web Application Firewall.</body></html>");
+
+    let evidence = provider.check_body_patterns(&response).await;
+
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].signature_matched, "barracuda-block-page");
+    assert_eq!(evidence[0].confidence, 0.85);
+}
+
+#[tokio::test]
+async fn test_fortiweb_cookie_and_block_page() {
+    let provider = providers::fortiweb::FortiWebProvider::new();
+    let response = response(403, &[
+        ("set-cookie", "FORTIWAFSID=ABCDEF0123456789; path=/"),
+    ], "<html><style>.fgd_icon { }</style><p>Attack ID: 20000051</p></html>");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    let signatures: Vec<&str> = evidence.iter().map(|e| e.signature_matched.as_str()).collect();
+    assert!(signatures.contains(&"fortiweb-cookie"));
+    assert!(signatures.contains(&"fortiweb-block-page"));
+    assert!(signatures.contains(&"fortiweb-attack-id"));
+}
+
+#[tokio::test]
+async fn test_netscaler_cookie_and_header_anomalies() {
+    let provider = providers::netscaler::NetScalerProvider::new();
+    let response = response(200, &[
+        ("set-cookie", "ns_af=xyz; path=/\nNSC_vsrv_web=ffffffff0909; path=/"),
+        ("cneonction", "close"),
+        ("nncoection", "close"),
+    ], "");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    let cookies: Vec<&Evidence> = evidence.iter().filter(|e| e.signature_matched == "netscaler-cookie").collect();
+    assert_eq!(cookies.len(), 2);
+    assert_eq!(cookies.iter().find(|e| e.raw_data == "ns_af").unwrap().confidence, 0.95);
+    assert_eq!(evidence.iter().filter(|e| e.signature_matched == "netscaler-connection-anomaly").count(), 2);
+}
+
+#[tokio::test]
+async fn test_netscaler_block_page() {
+    let provider = providers::netscaler::NetScalerProvider::new();
+    let response = response(200, &[("via", "NS-CACHE-10.0: 1")], "Request rejected. NS Transaction ID: 1234");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    assert!(evidence.iter().any(|e| e.signature_matched == "netscaler-via-header"));
+    let block = evidence.iter().find(|e| e.signature_matched == "netscaler-block-page").unwrap();
+    assert_eq!(block.confidence, 0.65);
+}
+
+#[tokio::test]
+async fn test_appliance_providers_ignore_plain_responses() {
+    let response = response(200, &[
+        ("server", "nginx"),
+        ("set-cookie", "session=abc; path=/"),
+    ], "<html><body>Hello</body></html>");
+
+    assert!(providers::barracuda::BarracudaProvider::new().passive_detect(&response).await.unwrap().is_empty());
+    assert!(providers::fortiweb::FortiWebProvider::new().passive_detect(&response).await.unwrap().is_empty());
+    assert!(providers::netscaler::NetScalerProvider::new().passive_detect(&response).await.unwrap().is_empty());
+}
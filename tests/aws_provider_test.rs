@@ -8,7 +8,7 @@ use waf_detector::{
     MethodType,
     ProviderType
 };
-use std::collections::HashMap;
+use waf_detector::http::Headers;
 
 #[tokio::test]
 async fn test_aws_provider_basic_metadata() {
@@ -27,14 +27,15 @@ async fn test_aws_provider_basic_metadata() {
 async fn test_aws_waf_request_id_header_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amzn-requestid".to_string(), "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amzn-requestid", "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -53,14 +54,15 @@ async fn test_aws_waf_request_id_header_detection() {
 async fn test_aws_error_type_header_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amzn-errortype".to_string(), "AccessDeniedException".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amzn-errortype", "AccessDeniedException".to_string());
     
     let response = HttpResponse {
         status: 403,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -79,14 +81,15 @@ async fn test_aws_error_type_header_detection() {
 async fn test_cloudfront_id_header_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amz-cf-id".to_string(), "abcd1234-EFGH-5678-IJKL-9012mnopqrst".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amz-cf-id", "abcd1234-EFGH-5678-IJKL-9012mnopqrst".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -104,14 +107,15 @@ async fn test_cloudfront_id_header_detection() {
 async fn test_cloudfront_pop_header_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amz-cf-pop".to_string(), "DFW3-C1".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amz-cf-pop", "DFW3-C1".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -130,14 +134,15 @@ async fn test_cloudfront_pop_header_detection() {
 async fn test_cloudfront_via_header_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("via".to_string(), "1.1 abcd1234.cloudfront.net (CloudFront)".to_string());
+    let mut headers = Headers::new();
+    headers.insert("via", "1.1 abcd1234.cloudfront.net (CloudFront)".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -155,14 +160,15 @@ async fn test_cloudfront_via_header_detection() {
 async fn test_cloudfront_cache_header_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-cache".to_string(), "Hit from cloudfront".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-cache", "Hit from cloudfront".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -181,7 +187,7 @@ async fn test_cloudfront_cache_header_detection() {
 async fn test_aws_waf_blocked_page_body_detection() {
     let provider = AwsProvider::new();
     
-    let headers = HashMap::new();
+    let headers = Headers::new();
     let body = r#"
         <html>
         <head><title>Access Denied</title></head>
@@ -198,6 +204,7 @@ async fn test_aws_waf_blocked_page_body_detection() {
         headers,
         body,
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -215,7 +222,7 @@ async fn test_aws_waf_blocked_page_body_detection() {
 async fn test_aws_waf_json_error_body_detection() {
     let provider = AwsProvider::new();
     
-    let headers = HashMap::new();
+    let headers = Headers::new();
     let body = r#"
         {
             "__type": "AccessDeniedException",
@@ -229,6 +236,7 @@ async fn test_aws_waf_json_error_body_detection() {
         headers,
         body,
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -247,14 +255,15 @@ async fn test_aws_waf_json_error_body_detection() {
 async fn test_aws_waf_403_status_with_signatures() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amzn-requestid".to_string(), "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amzn-requestid", "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
     
     let response = HttpResponse {
         status: 403,
         headers,
         body: "Access Denied".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -273,14 +282,15 @@ async fn test_aws_waf_403_status_with_signatures() {
 async fn test_aws_waf_429_rate_limit_detection() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amzn-requestid".to_string(), "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amzn-requestid", "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
     
     let response = HttpResponse {
         status: 429,
         headers,
         body: "Too Many Requests".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -299,17 +309,18 @@ async fn test_aws_waf_429_rate_limit_detection() {
 async fn test_multiple_aws_headers_combined_confidence() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amzn-requestid".to_string(), "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
-    headers.insert("x-amz-cf-id".to_string(), "abcd1234-EFGH-5678-IJKL-9012mnopqrst".to_string());
-    headers.insert("x-amz-cf-pop".to_string(), "DFW3-C1".to_string());
-    headers.insert("via".to_string(), "1.1 abcd1234.cloudfront.net (CloudFront)".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amzn-requestid", "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
+    headers.insert("x-amz-cf-id", "abcd1234-EFGH-5678-IJKL-9012mnopqrst".to_string());
+    headers.insert("x-amz-cf-pop", "DFW3-C1".to_string());
+    headers.insert("via", "1.1 abcd1234.cloudfront.net (CloudFront)".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -328,15 +339,16 @@ async fn test_multiple_aws_headers_combined_confidence() {
 async fn test_no_false_positives_for_non_aws() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("server".to_string(), "nginx/1.18.0".to_string());
-    headers.insert("x-powered-by".to_string(), "Express".to_string());
+    let mut headers = Headers::new();
+    headers.insert("server", "nginx/1.18.0".to_string());
+    headers.insert("x-powered-by", "Express".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "Hello World".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let evidence = provider.passive_detect(&response).await.unwrap();
@@ -349,14 +361,15 @@ async fn test_no_false_positives_for_non_aws() {
 async fn test_aws_provider_integration_with_detection_context() {
     let provider = AwsProvider::new();
     
-    let mut headers = HashMap::new();
-    headers.insert("x-amzn-requestid".to_string(), "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
+    let mut headers = Headers::new();
+    headers.insert("x-amzn-requestid", "1234abcd-12ab-34cd-56ef-1234567890ab".to_string());
     
     let response = HttpResponse {
         status: 200,
         headers,
         body: "".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     let context = DetectionContext {
@@ -0,0 +1,69 @@
+mod common;
+
+use common::response;
+use waf_detector::*;
+
+#[tokio::test]
+async fn test_azure_provider_creation() {
+    let provider = providers::azure::AzureProvider::new();
+
+    assert_eq!(provider.name(), "Azure");
+    assert_eq!(provider.provider_type(), ProviderType::Both);
+    assert_eq!(provider.confidence_base(), 0.90);
+}
+
+#[tokio::test]
+async fn test_azure_front_door_headers() {
+    let provider = providers::azure::AzureProvider::new();
+    let response = response(200, &[
+        ("x-azure-ref", "20240101T120000Z-16bd8c8f5dfqvkzshC1CHIabcd0000000000000"),
+        ("x-fd-healthprobe", "1"),
+        ("x-cache", "CONFIG_NOCACHE"),
+    ], "");
+
+    let evidence = provider.check_headers(&response).await;
+
+    assert_eq!(evidence.len(), 3);
+    let azure_ref = evidence.iter().find(|e| e.signature_matched == "azure-ref-header").unwrap();
+    assert_eq!(azure_ref.confidence, 0.97);
+    assert_eq!(azure_ref.method_type, MethodType::Header("x-azure-ref".to_string()));
+}
+
+#[tokio::test]
+async fn test_azure_msedge_ref_header() {
+    let provider = providers::azure::AzureProvider::new();
+    let response = response(200, &[("x-msedge-ref", "Ref A: 0123456789ABCDEF Ref B: AMS04EDGE1234")], "");
+
+    let evidence = provider.check_headers(&response).await;
+
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].signature_matched, "azure-msedge-ref-header");
+}
+
+#[tokio::test]
+async fn test_azure_application_gateway_block_page() {
+    let provider = providers::azure::AzureProvider::new();
+    let body = "<html><head><title>403 Forbidden</title></head><body><center><h1>403 Forbidden</h1></center><hr><center>Microsoft-Azure-Application-Gateway/v2</center></body></html>";
+    let response = response(403, &[("server", "Microsoft-Azure-Application-Gateway/v2")], body);
+
+    let context = DetectionContext {
+        url: "https://example.com".to_string(),
+        response: Some(response),
+        dns_info: None,
+        user_agent: "test".to_string(),
+    };
+    let evidence = provider.detect(&context).await.unwrap();
+
+    assert!(evidence.iter().any(|e| e.signature_matched == "azure-app-gateway-server-header"));
+    assert!(evidence.iter().any(|e| e.signature_matched == "azure-app-gateway-body"));
+    assert!(evidence.iter().any(|e| e.method_type == MethodType::StatusCode(403)));
+}
+
+#[tokio::test]
+async fn test_azure_no_false_positive_on_plain_response() {
+    let provider = providers::azure::AzureProvider::new();
+    let response = response(200, &[("server", "nginx"), ("x-cache", "HIT")], "<html>Hello</html>");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+    assert!(evidence.is_empty());
+}
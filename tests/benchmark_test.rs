@@ -0,0 +1,46 @@
+//! `waf-detect benchmark` scoring against fixtures served from local mock servers
+
+mod common;
+
+use common::engine;
+use waf_detector::providers::default_providers;
+use waf_detector::testing::{Benchmark, BenchmarkTargets, Fixture, GroundTruth, MockServer};
+
+fn fixture(path: &str) -> Fixture {
+    Fixture::load(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), path)).unwrap()
+}
+
+fn truth(url: String, provider: Option<&str>) -> GroundTruth {
+    GroundTruth { url, known_waf: None, known_cdn: provider.map(str::to_string), confidence_level: 1.0, notes: None }
+}
+
+#[tokio::test]
+async fn test_benchmark_scores_providers() {
+    let cloudflare = MockServer::serving(&fixture("CloudFlare/block_page.http")).await.unwrap();
+    let fastly = MockServer::serving(&fixture("Fastly/cached_page.http")).await.unwrap();
+    let origin = MockServer::serving(&fixture("origin/nginx.http")).await.unwrap();
+    let targets = BenchmarkTargets {
+        version: "test".to_string(),
+        targets: vec![
+            truth(cloudflare.url(), Some("CloudFlare")),
+            truth(fastly.url(), Some("Fastly")),
+            // Labelled wrongly on purpose: Fastly is missed, CloudFlare a false positive
+            truth(cloudflare.url(), Some("Fastly")),
+            truth(origin.url(), None),
+        ],
+    };
+
+    let mut scanned = 0;
+    let report = Benchmark::new(engine(default_providers()), targets).run(|_| scanned += 1).await;
+    assert_eq!(scanned, 4);
+    assert_eq!(report.passed(), 3, "{}", report.to_markdown());
+    assert_eq!(report.accuracy, 0.75);
+
+    let score = |name: &str| report.providers.iter().find(|score| score.provider == name).unwrap().clone();
+    assert_eq!((score("CloudFlare").detected, score("CloudFlare").false_positives), (1, 1));
+    assert_eq!((score("Fastly").targets, score("Fastly").detected, score("Fastly").missed), (2, 1, 1));
+
+    let markdown = report.to_markdown();
+    assert!(markdown.contains("target list test"));
+    assert!(markdown.contains("## Mismatches"));
+}
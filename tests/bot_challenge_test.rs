@@ -0,0 +1,41 @@
+mod common;
+
+use common::engine;
+use waf_detector::challenge::ChallengeKind;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider};
+
+#[tokio::test]
+async fn test_datadome_captcha_is_reported_separately() {
+    let mut server = mockito::Server::new_async().await;
+    let _captcha = server.mock("GET", "/")
+        .with_status(403)
+        .with_header("x-datadome", "protected")
+        .with_header("set-cookie", "datadome=abc123; Path=/")
+        .with_body("<html><script src=\"https://ct.captcha-delivery.com/c.js\"></script></html>")
+        .create_async()
+        .await;
+
+    let result = engine([Provider::CloudFlare(CloudFlareProvider::new())]).detect(&server.url()).await.unwrap();
+    let challenge = result.bot_challenge.expect("bot challenge");
+
+    assert_eq!(challenge.vendor, "DataDome");
+    assert_eq!(challenge.kind, ChallengeKind::Captcha);
+    assert!(challenge.interstitial);
+    assert_eq!(challenge.evidence.len(), 3);
+    assert!(result.detected_waf.is_none());
+}
+
+#[tokio::test]
+async fn test_no_challenge_is_omitted_from_json() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_body("<html>Welcome</html>")
+        .create_async()
+        .await;
+
+    let result = engine([Provider::CloudFlare(CloudFlareProvider::new())]).detect(&server.url()).await.unwrap();
+
+    assert!(result.bot_challenge.is_none());
+    assert!(serde_json::to_value(&result).unwrap().get("bot_challenge").is_none());
+}
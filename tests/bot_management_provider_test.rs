@@ -0,0 +1,47 @@
+mod common;
+
+use common::{engine, response};
+use waf_detector::*;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider, datadome::DataDomeProvider, perimeterx::PerimeterXProvider};
+
+#[tokio::test]
+async fn test_bot_manager_is_reported_next_to_waf() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .with_header("x-datadome", "protected")
+        .with_header("set-cookie", "datadome=abc123; Path=/")
+        .with_body("<html>Welcome</html>")
+        .create_async()
+        .await;
+
+    let engine = engine([
+        Provider::CloudFlare(CloudFlareProvider::new()),
+        Provider::DataDome(DataDomeProvider::new()),
+        Provider::PerimeterX(PerimeterXProvider::new()),
+    ]);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+
+    assert_eq!(result.waf_name(), Some("CloudFlare"));
+    assert_eq!(result.bot_manager_name(), Some("DataDome"));
+    assert!(result.detected_bot_manager.as_ref().unwrap().confidence > 0.5);
+    assert_ne!(result.cdn_name(), Some("DataDome"));
+}
+
+#[tokio::test]
+async fn test_perimeterx_sensor_and_cookies() {
+    let provider = PerimeterXProvider::new();
+    let response = response(200, &[
+        ("set-cookie", "_pxhd=abc; Path=/\nsession=1"),
+    ], "<script>window._pxAppId = 'PXa1b2c3d4';</script>");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    let signatures: Vec<&str> = evidence.iter().map(|e| e.signature_matched.as_str()).collect();
+    assert_eq!(signatures, vec!["perimeterx-cookie", "perimeterx-app-id"]);
+    assert_eq!(evidence[1].raw_data, "PXa1b2c3d4");
+    assert_eq!(provider.provider_type(), ProviderType::BotManagement);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{fixture, response, signatures};
+use waf_detector::*;
+use waf_detector::providers::{alibaba_cloud::AlibabaCloudProvider, huawei_cloud::HuaweiCloudProvider, tencent_cloud::TencentCloudProvider};
+
+#[tokio::test]
+async fn test_alibaba_cloud_cdn_headers() {
+    let provider = AlibabaCloudProvider::new();
+    assert_eq!((provider.name(), provider.provider_type()), ("AlibabaCloud", ProviderType::Both));
+
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "Tengine"),
+        ("eagleid", "7ce1d69d16430950389458286e"),
+        ("x-swift-cachetime", "3600"),
+        ("via", "cache13.l2cn1809[0,0,200-0,H], cache7.cn2465[1,0]"),
+    ], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["alibaba-eagleid-header", "alibaba-swift-header", "alibaba-via-header"]);
+}
+
+#[tokio::test]
+async fn test_alibaba_cloud_waf_block() {
+    let provider = AlibabaCloudProvider::new();
+    let evidence = provider.passive_detect(&fixture("AlibabaCloud/waf_block.http")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["alibaba-eagleid-header", "alibaba-waf-cookie", "alibaba-waf-block-page"]);
+}
+
+#[tokio::test]
+async fn test_tencent_edgeone() {
+    let provider = TencentCloudProvider::new();
+    let evidence = provider.passive_detect(&fixture("TencentCloud/edgeone_page.http")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["tencent-edgeone-header", "tencent-edgeone-header"]);
+
+    let evidence = provider.passive_detect(&response(200, &[("server", "NWS_SP"), ("x-nws-log-uuid", "8459437466012834857")], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["tencent-nws-header", "tencent-server-header"]);
+}
+
+#[tokio::test]
+async fn test_huawei_cloud_waf() {
+    let provider = HuaweiCloudProvider::new();
+    let evidence = provider.passive_detect(&fixture("HuaweiCloud/waf_block.http")).await.unwrap();
+    assert_eq!(
+        signatures(&evidence),
+        ["huawei-waf-server-header", "huawei-waf-cookie", "huawei-waf-cookie", "huawei-waf-block-page"]
+    );
+
+    let evidence = provider.passive_detect(&response(200, &[("x-ccdn-cachettl", "2592000")], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["huawei-cdn-header"]);
+}
+
+#[tokio::test]
+async fn test_plain_origin_is_not_a_chinese_cloud() {
+    let origin = fixture("origin/nginx.http");
+    assert!(AlibabaCloudProvider::new().passive_detect(&origin).await.unwrap().is_empty());
+    assert!(TencentCloudProvider::new().passive_detect(&origin).await.unwrap().is_empty());
+    assert!(HuaweiCloudProvider::new().passive_detect(&origin).await.unwrap().is_empty());
+}
@@ -0,0 +1,32 @@
+use waf_detector::cli;
+
+#[test]
+fn test_completions_cover_real_arguments() {
+    let matches = cli::build_cli().try_get_matches_from(["waf-detect", "completions", "bash"]).unwrap();
+    let (name, completions_matches) = matches.subcommand().unwrap();
+    assert_eq!(name, "completions");
+    assert_eq!(completions_matches.get_one::<clap_complete::Shell>("shell"), Some(&clap_complete::Shell::Bash));
+    assert!(cli::build_cli().try_get_matches_from(["waf-detect", "completions", "tcsh"]).is_err());
+
+    let mut script = Vec::new();
+    clap_complete::generate(clap_complete::Shell::Bash, &mut cli::build_cli(), "waf-detect", &mut script);
+    let script = String::from_utf8(script).unwrap();
+    assert!(script.contains("--smoke-test"));
+    assert!(script.contains("--merge-www"));
+    assert!(script.contains("enum"));
+}
+
+#[test]
+fn test_man_pages_per_subcommand() {
+    let mut page = Vec::new();
+    clap_mangen::Man::new(cli::build_cli()).render(&mut page).unwrap();
+    let page = String::from_utf8(page).unwrap();
+    assert!(page.starts_with(".ie"));
+    assert!(page.contains("smoke\\-test"));
+
+    let dir = tempfile::tempdir().unwrap();
+    clap_mangen::generate_to(cli::build_cli(), dir.path()).unwrap();
+    assert!(dir.path().join("waf-detect.1").exists());
+    assert!(dir.path().join("waf-detect-enum.1").exists());
+    assert!(dir.path().join("waf-detect-scoring-dump.1").exists());
+}
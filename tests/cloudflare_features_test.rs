@@ -0,0 +1,95 @@
+mod common;
+
+use common::response;
+use waf_detector::*;
+use waf_detector::providers::cloudflare::CloudFlareProvider;
+use waf_detector::registry::{Analyzer, ProviderRegistry};
+
+fn feature<'a>(features: &'a [ProviderFeature], category: &str) -> Option<&'a ProviderFeature> {
+    features.iter().find(|f| f.category == category)
+}
+
+#[test]
+fn test_challenge_types() {
+    let provider = CloudFlareProvider::new();
+    let cases = [
+        ("<script>window._cf_chl_opt={cvId: '3', cType: 'managed', cNounce: '1'};</script>", "managed"),
+        ("<script>window._cf_chl_opt={cType: 'non-interactive'};</script>", "js"),
+        ("<form id=\"challenge-form\"><input name=\"jschl_vc\"/></form>", "js"),
+        ("<div class=\"cf-turnstile\" data-sitekey=\"0x4AAA\"></div>", "turnstile"),
+    ];
+
+    for (body, expected) in cases {
+        let features = provider.features(&response(403, &[("cf-ray", "8a1b2c3d4e5f6a7b-LHR"), ("server", "cloudflare")], body));
+        assert_eq!(feature(&features, "challenge").unwrap().value, expected, "body: {}", body);
+    }
+}
+
+#[test]
+fn test_bot_fight_mode_on_self_serve_zone() {
+    let provider = CloudFlareProvider::new();
+    let features = provider.features(&response(200, &[
+        ("cf-ray", "8a1b2c3d4e5f6a7b-LHR"),
+        ("server", "cloudflare"),
+        ("set-cookie", "__cf_bm=abc.def; path=/; HttpOnly; Secure"),
+    ], ""));
+
+    assert_eq!(feature(&features, "bot").unwrap().value, "bot_fight_mode");
+    assert!(feature(&features, "plan").is_none());
+    assert!(feature(&features, "challenge").is_none());
+}
+
+#[test]
+fn test_enterprise_plan_hint() {
+    let provider = CloudFlareProvider::new();
+    let features = provider.features(&response(200, &[
+        ("cf-ray", "8a1b2c3d4e5f6a7b-LHR"),
+        ("server", "custom-edge"),
+        ("set-cookie", "__cf_bm=abc.def; path=/"),
+    ], ""));
+
+    let plan = feature(&features, "plan").unwrap();
+    assert_eq!(plan.value, "enterprise");
+    assert_eq!(plan.evidence, "server: custom-edge");
+    assert_eq!(feature(&features, "bot").unwrap().value, "bot_management");
+}
+
+#[test]
+fn test_pro_plan_hint_from_polish() {
+    let provider = CloudFlareProvider::new();
+    let features = provider.features(&response(200, &[
+        ("cf-ray", "8a1b2c3d4e5f6a7b-LHR"),
+        ("server", "cloudflare"),
+        ("cf-polished", "origSize=12345"),
+    ], ""));
+
+    assert_eq!(feature(&features, "plan").unwrap().value, "pro_or_higher");
+}
+
+#[tokio::test]
+async fn test_features_attached_to_detection() {
+    let registry = ProviderRegistry::new();
+    registry.register_provider(providers::Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    for analyzer in Analyzer::ALL {
+        registry.set_analyzer_enabled(analyzer, false);
+    }
+
+    let context = DetectionContext {
+        url: "https://example.com".to_string(),
+        response: Some(response(403, &[
+            ("cf-ray", "8a1b2c3d4e5f6a7b-LHR"),
+            ("server", "cloudflare"),
+            ("cf-mitigated", "challenge"),
+        ], "<script>window._cf_chl_opt={cType: 'managed'};</script>")),
+        dns_info: None,
+        user_agent: "test-agent".to_string(),
+    };
+
+    let result = registry.detect_all(&context).await.unwrap();
+    let waf = result.detected_waf.as_ref().unwrap();
+    assert_eq!(waf.name, "CloudFlare");
+    assert_eq!(feature(&waf.features, "challenge").unwrap().value, "managed");
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["detected_waf"]["features"][0]["category"], "challenge");
+}
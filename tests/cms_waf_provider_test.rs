@@ -0,0 +1,64 @@
+mod common;
+
+use common::response;
+use waf_detector::*;
+
+#[tokio::test]
+async fn test_cms_waf_provider_creation() {
+    let provider = providers::cms_waf::CmsWafProvider::new();
+
+    assert_eq!(provider.name(), "CmsWaf");
+    assert_eq!(provider.provider_type(), ProviderType::WAF);
+}
+
+#[tokio::test]
+async fn test_wordfence_block_page_and_cookie() {
+    let provider = providers::cms_waf::CmsWafProvider::new();
+    let response = response(403, &[
+        ("x-powered-by", "PHP/8.1.2"),
+        ("set-cookie", "wfwaf-authcookie-0123abcd=1%7Cadmin; path=/"),
+    ], "<h1>Your access to this site has been limited by the site owner</h1><p>Generated by Wordfence at Mon, 1 Jan 2024</p>");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    let block = evidence.iter().find(|e| e.signature_matched == "wordfence-block-page").unwrap();
+    assert_eq!(block.confidence, 0.92);
+    assert!(evidence.iter().any(|e| e.signature_matched == "wordfence-cookie"));
+    assert!(!evidence.iter().any(|e| e.signature_matched == "cms-waf-powered-by"));
+}
+
+#[tokio::test]
+async fn test_sitelock_headers() {
+    let provider = providers::cms_waf::CmsWafProvider::new();
+    let response = response(200, &[("x-sitelock-id", "abc123")], "");
+
+    let evidence = provider.check_headers(&response).await;
+
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].signature_matched, "sitelock-header");
+    assert_eq!(evidence[0].method_type, MethodType::Header("x-sitelock-id".to_string()));
+}
+
+#[tokio::test]
+async fn test_generic_plugin_block_page_requires_block_status() {
+    let provider = providers::cms_waf::CmsWafProvider::new();
+    let body = "<title>NinjaFirewall: 403 Forbidden</title>";
+
+    let blocked = provider.check_body_patterns(&response(403, &[], body)).await;
+    let allowed = provider.check_body_patterns(&response(200, &[], body)).await;
+
+    assert_eq!(blocked.len(), 1);
+    assert_eq!(blocked[0].signature_matched, "cms-waf-plugin-block-page");
+    assert!(allowed.is_empty());
+}
+
+#[tokio::test]
+async fn test_plain_wordpress_site_not_detected() {
+    let provider = providers::cms_waf::CmsWafProvider::new();
+    let response = response(200, &[
+        ("x-powered-by", "PHP/8.1.2"),
+        ("link", "<https://example.com/wp-json/>; rel=\"https://api.w.org/\""),
+    ], "<html><body class=\"home blog\">Just another WordPress site</body></html>");
+
+    assert!(provider.passive_detect(&response).await.unwrap().is_empty());
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use waf_detector::engine::DetectionEngine;
+use waf_detector::http::HttpResponse;
+use waf_detector::providers::Provider;
+use waf_detector::registry::ProviderRegistry;
+use waf_detector::testing::Fixture;
+use waf_detector::{DetectionOptions, Evidence};
+
+/// URL every helper response claims to come from
+pub const URL: &str = "https://example.com/";
+
+/// Response to [`URL`] with the headers in the order given
+pub fn response(status: u16, headers: &[(&str, &str)], body: &str) -> HttpResponse {
+    HttpResponse::from_parts(status, headers.iter().copied(), body, URL)
+}
+
+/// Recorded response at tests/fixtures/`path`
+pub fn fixture(path: &str) -> HttpResponse {
+    Fixture::load(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), path))
+        .and_then(|fixture| fixture.response(URL))
+        .unwrap_or_else(|e| panic!("fixture {}: {}", path, e))
+}
+
+/// Matched signature names, in evidence order
+pub fn signatures(evidence: &[Evidence]) -> Vec<&str> {
+    evidence.iter().map(|e| e.signature_matched.as_str()).collect()
+}
+
+/// Engine with `providers` and the default options minus DNS, so tests against a
+/// local server never resolve anything
+pub fn engine(providers: impl IntoIterator<Item = Provider>) -> DetectionEngine {
+    let registry = ProviderRegistry::new();
+    for provider in providers {
+        registry.register_provider(provider).unwrap();
+    }
+    DetectionEngine::new(registry).with_options(DetectionOptions { dns: false, ..DetectionOptions::default() })
+}
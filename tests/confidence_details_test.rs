@@ -0,0 +1,33 @@
+use waf_detector::*;
+use waf_detector::confidence::{ConfidenceLevel, EvidenceCategory};
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider};
+use waf_detector::registry::ProviderRegistry;
+
+#[tokio::test]
+async fn test_confidence_details_explain_each_score() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let result = DetectionEngine::new(registry).with_options(options).detect(&server.url()).await.unwrap();
+
+    let details = &result.confidence_details["CloudFlare"];
+    assert_eq!(details.score, result.provider_scores["CloudFlare"]);
+    assert!(matches!(details.level, ConfidenceLevel::Absolute | ConfidenceLevel::NearCertain | ConfidenceLevel::VeryHigh));
+    assert!(details.evidence_breakdown[&EvidenceCategory::Headers] > 0.0);
+    assert!(details.explanation.contains("Confidence Analysis for CloudFlare"));
+
+    // Survives a JSON round trip (category keys serialize as strings)
+    let json = serde_json::to_value(&result).unwrap();
+    assert!(json["confidence_details"]["CloudFlare"]["evidence_breakdown"]["Headers"].as_f64().unwrap() > 0.0);
+    let parsed: DetectionResult = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.confidence_details["CloudFlare"].positive_evidence_count, details.positive_evidence_count);
+}
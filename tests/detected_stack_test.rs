@@ -0,0 +1,47 @@
+use waf_detector::*;
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::{Provider, aws::AwsProvider, cloudflare::CloudFlareProvider, fortiweb::FortiWebProvider};
+use waf_detector::registry::ProviderRegistry;
+
+#[tokio::test]
+async fn test_stack_lists_every_layer_outermost_first() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .with_header("x-amzn-requestid", "0d9a2c1e-6c7e-4d0b-9f0e-2f8a1b3c4d5e")
+        .with_header("set-cookie", "FORTIWAFSID=ABCDEF0123456789; path=/")
+        .with_body("<html>Welcome</html>")
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::FortiWeb(FortiWebProvider::new())).unwrap();
+    registry.register_provider(Provider::AWS(AwsProvider::new())).unwrap();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let engine = DetectionEngine::new(registry).with_options(options);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+
+    let stack: Vec<(&str, Option<StackLayer>)> = result.detected_stack
+        .iter()
+        .map(|d| (d.name.as_str(), d.layer))
+        .collect();
+    assert_eq!(stack, vec![
+        ("CloudFlare", Some(StackLayer::Edge)),
+        ("AWS", Some(StackLayer::CloudWaf)),
+        ("FortiWeb", Some(StackLayer::Origin)),
+    ]);
+    // The single best detections keep their existing meaning
+    assert!(result.detected_waf.is_some());
+    assert!(result.detected_waf.unwrap().layer.is_none());
+}
+
+#[test]
+fn test_default_layer_from_provider_type() {
+    assert_eq!(StackLayer::from_provider_type(&ProviderType::CDN), StackLayer::Edge);
+    assert_eq!(StackLayer::from_provider_type(&ProviderType::WAF), StackLayer::Origin);
+    assert!(StackLayer::Edge < StackLayer::CloudWaf && StackLayer::CloudWaf < StackLayer::Origin);
+}
@@ -0,0 +1,100 @@
+use waf_detector::*;
+use mockito::Matcher;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_passive_detector_sends_one_request() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .with_header("server", "cloudflare")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let detector = Detector::builder()
+        .with_default_providers()
+        .with_options(DetectionOptions { dns: false, ..DetectionOptions::default() })
+        .passive_only()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let result = detector.detect(&server.url()).await.unwrap();
+
+    assert_eq!(result.detected_waf.unwrap().name, "CloudFlare");
+    page.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_passive_detector_ignores_retries() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server.mock("GET", "/")
+        .with_status(502)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let detector = Detector::builder()
+        .with_default_providers()
+        .with_options(DetectionOptions { dns: false, ..DetectionOptions::default() })
+        .passive_only()
+        .retries(3)
+        .build()
+        .unwrap();
+    detector.detect(&server.url()).await.unwrap();
+    page.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_detect_batch_keeps_input_order() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let detector = Detector::builder()
+        .with_default_providers()
+        .with_options(DetectionOptions { dns: false, ..DetectionOptions::default() })
+        .passive_only()
+        .concurrency(4)
+        .build()
+        .unwrap();
+    let urls: Vec<String> = ["/c", "/a", "/b"].iter().map(|path| format!("{}{}", server.url(), path)).collect();
+    let url_refs: Vec<&str> = urls.iter().map(|u| u.as_str()).collect();
+    let results = detector.detect_batch(&url_refs).await.unwrap();
+
+    let result_urls: Vec<&str> = results.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(result_urls, url_refs);
+}
+
+#[tokio::test]
+async fn test_requests_go_through_the_proxy() {
+    let mut proxy = mockito::Server::new_async().await;
+    let proxied = proxy.mock("GET", Matcher::Any)
+        .with_status(200)
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let detector = Detector::builder()
+        .with_default_providers()
+        .with_options(DetectionOptions { dns: false, ..DetectionOptions::default() })
+        .passive_only()
+        .proxy(proxy.url())
+        .build()
+        .unwrap();
+    let result = detector.detect("http://target.invalid/").await.unwrap();
+
+    assert!(result.detected_waf.is_some());
+    proxied.assert_async().await;
+}
+
+#[test]
+fn test_build_errors() {
+    assert!(Detector::builder().build().is_err());
+    assert!(Detector::builder().with_default_providers().proxy("not a url").build().is_err());
+}
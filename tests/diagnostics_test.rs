@@ -0,0 +1,54 @@
+//! Per-technique diagnostics: durations, request counts and errors
+
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::{Provider, aws::AwsProvider, cloudflare::CloudFlareProvider};
+use waf_detector::registry::ProviderRegistry;
+use waf_detector::{DetectionOptions, TechniqueKind};
+
+#[tokio::test]
+async fn test_diagnostics_attribute_requests_to_techniques() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_body("<html></html>")
+        .expect(5)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::AWS(AwsProvider::new())).unwrap();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+
+    let options = DetectionOptions {
+        dns: false,
+        active_probes: true,
+        ..DetectionOptions::default()
+    };
+    let engine = DetectionEngine::new(registry).with_options(options);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+    mock.assert_async().await;
+
+    let diagnostics = &result.diagnostics;
+    assert_eq!(diagnostics.techniques.len(), 3);
+    let providers = diagnostics.techniques.iter().filter(|t| t.kind == TechniqueKind::Provider).count();
+    assert_eq!(providers, 2);
+
+    // Block-page matching works on the fetched body only
+    let block_page = diagnostics.techniques.iter().find(|t| t.name == "BlockPageAnalysis").unwrap();
+    assert_eq!((block_page.kind, block_page.requests), (TechniqueKind::Analyzer, 0));
+
+    let aws = diagnostics.techniques.iter().find(|t| t.name == "AWS").unwrap();
+    assert_eq!(aws.requests, 4);
+    assert!(aws.error.is_none());
+
+    let cloudflare = diagnostics.techniques.iter().find(|t| t.name == "CloudFlare").unwrap();
+    assert_eq!(cloudflare.requests, 0);
+
+    // Initial GET plus the AWS probe paths
+    assert_eq!(diagnostics.total_requests, 5);
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["diagnostics"]["total_requests"], 5);
+}
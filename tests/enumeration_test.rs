@@ -0,0 +1,114 @@
+use waf_detector::*;
+use waf_detector::enumeration::{CoverageMatrix, Subdomain, SubdomainEnumerator, SubdomainSource, subdomain_url};
+use std::collections::HashMap;
+
+fn result(url: &str, waf: Option<&str>) -> DetectionResult {
+    DetectionResult {
+        url: url.to_string(),
+        detected_waf: waf.map(|name| ProviderDetection {
+            name: name.to_string(),
+            confidence: 0.9,
+            features: Vec::new(),
+            layer: None,
+        }),
+        detected_cdn: None,
+        detected_bot_manager: None,
+        detected_stack: Vec::new(),
+        bot_challenge: None,
+        edge_location: None,
+        provider_scores: HashMap::new(),
+        evidence_map: HashMap::new(),
+        negative_evidence: HashMap::new(),
+        confidence_details: HashMap::new(),
+        detection_time_ms: 0,
+        metadata: DetectionMetadata {
+            timestamp: chrono::Utc::now(),
+            version: "0.1.0".to_string(),
+            user_agent: "test".to_string(),
+            retries: 0,
+            preflight: None,
+            signature_bundle: None,
+            pattern_counts: Default::default(),
+            body_truncation: None,
+        },
+        diagnostics: Default::default(),
+        error: None,
+        ruleset_info: None,
+        waf_mode: None,
+        rate_limit: None,
+        dual_stack: None,
+        idn: None,
+        evidence_groups: HashMap::new(),
+        explanation: None,
+        consensus: None,
+        browser_fallback: None,
+        session: None,
+        origin_bypass: None,
+    }
+}
+
+#[tokio::test]
+async fn test_certificate_transparency_subdomains() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server.mock("GET", "/")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("q".to_string(), "%.example.com".to_string()),
+            mockito::Matcher::UrlEncoded("output".to_string(), "json".to_string()),
+        ]))
+        .with_status(200)
+        .with_body(r#"[{"common_name": "origin.example.com", "name_value": "origin.example.com\n*.example.com"}]"#)
+        .create_async()
+        .await;
+
+    let enumerator = SubdomainEnumerator::new()
+        .unwrap()
+        .with_wordlist(vec!["www".to_string(), "origin".to_string()])
+        .with_ct_url(&server.url())
+        .with_resolution(false);
+
+    let subdomains = enumerator.enumerate("example.com").await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(subdomains, vec![
+        Subdomain { name: "example.com".to_string(), source: SubdomainSource::Apex },
+        Subdomain { name: "origin.example.com".to_string(), source: SubdomainSource::CertificateTransparency },
+        Subdomain { name: "www.example.com".to_string(), source: SubdomainSource::Wordlist },
+    ]);
+}
+
+#[test]
+fn test_coverage_matrix_flags_unprotected_subdomains() {
+    let subdomains = vec![
+        Subdomain { name: "example.com".to_string(), source: SubdomainSource::Apex },
+        Subdomain { name: "www.example.com".to_string(), source: SubdomainSource::Wordlist },
+        Subdomain { name: "origin.example.com".to_string(), source: SubdomainSource::CertificateTransparency },
+    ];
+    let mut results = HashMap::new();
+    for (name, waf) in [("example.com", Some("CloudFlare")), ("www.example.com", Some("CloudFlare")), ("origin.example.com", None)] {
+        let url = subdomain_url(name);
+        results.insert(url.clone(), result(&url, waf));
+    }
+
+    let matrix = CoverageMatrix::from_results("example.com", &subdomains, &results);
+
+    let unprotected: Vec<&str> = matrix.unprotected().iter().map(|r| r.subdomain.as_str()).collect();
+    assert_eq!(unprotected, vec!["origin.example.com"]);
+    assert_eq!(matrix.vendors().get("CloudFlare"), Some(&2));
+}
+
+#[test]
+fn test_enum_subcommand_parses() {
+    let matches = cli::build_cli()
+        .try_get_matches_from(["waf-detect", "enum", "example.com", "--no-ct", "--limit", "5"])
+        .unwrap();
+    let (name, enum_matches) = matches.subcommand().unwrap();
+
+    assert_eq!(name, "enum");
+    assert_eq!(enum_matches.get_one::<String>("domain").unwrap(), "example.com");
+    assert!(enum_matches.get_flag("no-ct"));
+    assert_eq!(enum_matches.get_one::<usize>("limit"), Some(&5));
+
+    // Plain scans still take positional targets
+    let matches = cli::build_cli().try_get_matches_from(["waf-detect", "example.com"]).unwrap();
+    assert!(matches.subcommand().is_none());
+}
@@ -0,0 +1,53 @@
+use waf_detector::confidence::ConfidenceThresholds;
+use waf_detector::http::HttpResponse;
+use waf_detector::policy::{FailCondition, FailPolicy, EXIT_OK, EXIT_POLICY_VIOLATION, EXIT_SCAN_FAILED};
+use waf_detector::{Detector, DetectionResult};
+
+async fn offline_results() -> (Vec<DetectionResult>, ConfidenceThresholds) {
+    let detector = Detector::builder().with_default_providers().passive_only().build().unwrap();
+    let protected = HttpResponse::from_parts(200, [("cf-ray", "8a1b2c3d4e5f6a7b-AMS"), ("server", "cloudflare")], "", "https://shop.example.com/");
+    let exposed = HttpResponse::from_parts(200, [("server", "nginx")], "", "https://origin.example.com/");
+    let results = vec![
+        detector.detect_from_response("https://shop.example.com/", protected).await.unwrap(),
+        detector.detect_from_response("https://origin.example.com/", exposed).await.unwrap(),
+    ];
+    let thresholds = detector.engine().registry().advanced_scoring().thresholds().clone();
+    (results, thresholds)
+}
+
+#[tokio::test]
+async fn test_no_waf_flags_unprotected_targets() {
+    let (results, thresholds) = offline_results().await;
+
+    let report = FailPolicy::new(vec![FailCondition::NoWaf]).check_detections(&results, &thresholds).unwrap();
+
+    assert_eq!(report.violations.len(), 1);
+    assert_eq!(report.violations[0].url, "https://origin.example.com/");
+    assert_eq!(report.exit_code(), EXIT_POLICY_VIOLATION);
+    assert_eq!(FailPolicy::new(vec![FailCondition::NoWaf]).check_detections(&results[..1], &thresholds).unwrap().exit_code(), EXIT_OK);
+}
+
+#[tokio::test]
+async fn test_low_confidence_threshold() {
+    let (results, thresholds) = offline_results().await;
+    let confidence = results[0].detected_waf.as_ref().unwrap().confidence * 100.0;
+
+    let strict = FailPolicy::new(vec![FailCondition::LowConfidence(Some(confidence + 0.5))]);
+    let lenient = FailPolicy::new(vec![FailCondition::LowConfidence(Some(confidence - 0.5))]);
+
+    assert_eq!(strict.check_detections(&results, &thresholds).unwrap().violations.len(), 1);
+    assert!(lenient.check_detections(&results, &thresholds).unwrap().violations.is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_scans_and_inapplicable_conditions() {
+    let detector = Detector::builder().with_default_providers().passive_only().retries(0).build().unwrap();
+    let results = detector.detect_batch(&["http://127.0.0.1:1/"]).await.unwrap();
+    let thresholds = ConfidenceThresholds { minimum: 0.6, high: 0.9, very_high: 0.95, absolute: 0.98 };
+
+    let report = FailPolicy::new(vec![FailCondition::NoWaf]).check_detections(&results, &thresholds).unwrap();
+    assert!(report.violations.is_empty());
+    assert_eq!(report.exit_code(), EXIT_SCAN_FAILED);
+
+    assert!(FailPolicy::new(vec![FailCondition::Effectiveness(80.0)]).check_detections(&results, &thresholds).is_err());
+}
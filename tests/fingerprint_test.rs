@@ -0,0 +1,76 @@
+use waf_detector::*;
+use waf_detector::engine::DetectionEngine;
+use waf_detector::fingerprint::{PARANOIA_PROBES, CRS4_PROBE};
+use waf_detector::providers::{Provider, azure::AzureProvider};
+use waf_detector::registry::ProviderRegistry;
+use mockito::Matcher;
+
+#[tokio::test]
+async fn test_fingerprint_reports_crs_paranoia_level() {
+    let mut server = mockito::Server::new_async().await;
+    let _baseline = server.mock("GET", "/")
+        .match_query(Matcher::Exact(String::new()))
+        .with_status(200)
+        .with_header("x-azure-ref", "20240101T120000Z-16bd8c8f5dfqvkzshC1CHIabcd")
+        .create_async()
+        .await;
+    for (probe, status) in PARANOIA_PROBES.iter().zip([403, 403, 200, 200]) {
+        server.mock("GET", "/")
+            .match_query(Matcher::UrlEncoded("q".to_string(), probe.value.to_string()))
+            .with_status(status)
+            .with_body(r#"The request is blocked. [id "942100"]"#)
+            .create_async()
+            .await;
+    }
+    let crs4 = server.mock("GET", "/")
+        .match_query(Matcher::UrlEncoded("q".to_string(), CRS4_PROBE.value.to_string()))
+        .with_status(200)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::Azure(AzureProvider::new())).unwrap();
+    let options = DetectionOptions {
+        dns: false,
+        fingerprint: true,
+        ..DetectionOptions::default()
+    };
+    let engine = DetectionEngine::new(registry).with_options(options);
+
+    let url = format!("{}/", server.url());
+    let result = engine.detect(&url).await.unwrap();
+    let ruleset = result.ruleset_info.expect("ruleset info");
+
+    assert_eq!(ruleset.vendor, "Azure");
+    assert_eq!(ruleset.ruleset.as_deref(), Some("OWASP CRS"));
+    assert_eq!(ruleset.paranoia_level, Some(2));
+    assert_eq!(ruleset.generation.as_deref(), Some("3.x"));
+    assert_eq!(ruleset.rule_ids, vec!["crs:942100"]);
+    // Three ladder probes (stops at PL3) plus the generation probe
+    assert_eq!(ruleset.probes.len(), 4);
+    crs4.assert_async().await;
+
+    let diagnostics = result.diagnostics.techniques.iter().find(|t| t.name == "Fingerprint").unwrap();
+    assert_eq!(diagnostics.requests, 4);
+}
+
+#[tokio::test]
+async fn test_fingerprint_is_opt_in() {
+    let mut server = mockito::Server::new_async().await;
+    let _baseline = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("x-azure-ref", "20240101T120000Z-16bd8c8f5dfqvkzshC1CHIabcd")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::Azure(AzureProvider::new())).unwrap();
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let engine = DetectionEngine::new(registry).with_options(options);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+
+    assert!(result.ruleset_info.is_none());
+    assert!(serde_json::to_value(&result).unwrap().get("ruleset_info").is_none());
+}
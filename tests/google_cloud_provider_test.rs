@@ -0,0 +1,65 @@
+mod common;
+
+use common::response;
+use waf_detector::*;
+
+#[tokio::test]
+async fn test_google_cloud_provider_creation() {
+    let provider = providers::google_cloud::GoogleCloudProvider::new();
+
+    assert_eq!(provider.name(), "GoogleCloud");
+    assert_eq!(provider.provider_type(), ProviderType::Both);
+}
+
+#[tokio::test]
+async fn test_google_cloud_cdn_headers() {
+    let provider = providers::google_cloud::GoogleCloudProvider::new();
+    let response = response(200, &[
+        ("via", "1.1 google"),
+        ("age", "120"),
+        ("x-goog-generation", "1700000000000000"),
+        ("x-goog-storage-class", "STANDARD"),
+    ], "");
+
+    let evidence = provider.check_headers(&response).await;
+
+    assert!(evidence.iter().any(|e| e.signature_matched == "gcp-via-google-header"));
+    assert!(evidence.iter().any(|e| e.signature_matched == "gcp-cloud-cdn-cache"));
+    let goog = evidence.iter().find(|e| e.signature_matched == "gcp-goog-header").unwrap();
+    assert!(goog.raw_data.contains("x-goog-generation"));
+    assert!(goog.raw_data.contains("x-goog-storage-class"));
+}
+
+#[tokio::test]
+async fn test_google_cloud_armor_block() {
+    let provider = providers::google_cloud::GoogleCloudProvider::new();
+    let body = "<!doctype html><meta charset=\"utf-8\"><meta name=viewport content=\"width=device-width, initial-scale=1\"><title>403</title>403 Forbidden";
+    let response = response(403, &[("via", "1.1 google")], body);
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    let block = evidence.iter().find(|e| e.signature_matched == "gcp-cloud-armor-403").unwrap();
+    assert_eq!(block.confidence, 0.90);
+    assert_eq!(block.method_type, MethodType::StatusCode(403));
+}
+
+#[tokio::test]
+async fn test_google_front_end_error_page() {
+    let provider = providers::google_cloud::GoogleCloudProvider::new();
+    let body = "<p><b>403.</b> <ins>That’s an error.</ins><p>Your client does not have permission to get URL <code>/</code> from this server.";
+    let response = response(403, &[("server", "Google Frontend")], body);
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+
+    assert!(evidence.iter().any(|e| e.signature_matched == "gcp-error-body"));
+    assert!(evidence.iter().any(|e| e.signature_matched == "gcp-google-frontend-server"));
+}
+
+#[tokio::test]
+async fn test_google_cloud_ignores_other_via_headers() {
+    let provider = providers::google_cloud::GoogleCloudProvider::new();
+    let response = response(200, &[("via", "1.1 varnish"), ("age", "10")], "<html></html>");
+
+    let evidence = provider.passive_detect(&response).await.unwrap();
+    assert!(evidence.is_empty());
+}
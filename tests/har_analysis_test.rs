@@ -0,0 +1,74 @@
+use waf_detector::capture::{analyze_captured, har::parse_har};
+use waf_detector::engine::DetectionEngine;
+use waf_detector::http::count_requests;
+use waf_detector::providers::default_providers;
+use waf_detector::registry::ProviderRegistry;
+
+const HAR: &str = r#"{"log": {"version": "1.2", "entries": [
+    {"request": {"url": "https://www.example.com/"},
+     "response": {"status": 200, "headers": [{"name": "cf-ray", "value": "8a1b2c3d4e5f6a7b-AMS"}, {"name": "server", "value": "cloudflare"}],
+                  "content": {"text": "<html>Shop</html>"}}},
+    {"request": {"url": "https://www.example.com/api/cart"},
+     "response": {"status": 200, "headers": [{"name": "cf-ray", "value": "8a1b2c3d4e5f6a7c-AMS"}, {"name": "cf-cache-status", "value": "DYNAMIC"}],
+                  "content": {"text": "{}"}}},
+    {"request": {"url": "https://static.example.com/app.js"},
+     "response": {"status": 200, "headers": [{"name": "x-amz-cf-pop", "value": "FRA56-P1"}, {"name": "via", "value": "1.1 abc.cloudfront.net (CloudFront)"}],
+                  "content": {"text": "console.log(1)"}}},
+    {"request": {"url": "https://plain.example.org/"},
+     "response": {"status": 200, "headers": [{"name": "server", "value": "nginx"}], "content": {"text": "ok"}}}
+]}}"#;
+
+#[tokio::test]
+async fn test_har_report_aggregates_per_host() {
+    let registry = ProviderRegistry::new();
+    for provider in default_providers() {
+        registry.register_provider(provider).unwrap();
+    }
+    let engine = DetectionEngine::new(registry);
+
+    let (captured, skipped) = parse_har(HAR).unwrap();
+    let (report, requests) = count_requests(analyze_captured(&engine, "traffic.har", captured, skipped)).await;
+    let report = report.unwrap();
+
+    assert_eq!(requests, 0);
+    assert_eq!(report.responses, 4);
+    let hosts: Vec<&str> = report.hosts.iter().map(|h| h.host.as_str()).collect();
+    assert_eq!(hosts, vec!["plain.example.org", "static.example.com", "www.example.com"]);
+
+    let www = &report.hosts[2];
+    assert_eq!(www.responses, 2);
+    assert_eq!(www.waf.as_ref().unwrap().name, "CloudFlare");
+    let cloudflare = www.providers.iter().find(|p| p.name == "CloudFlare").unwrap();
+    assert_eq!(cloudflare.responses, 2);
+    assert!(cloudflare.signatures.contains(&"cf-cache-status-header".to_string()));
+
+    assert_eq!(report.hosts[1].cdn.as_ref().unwrap().name, "AWS");
+    assert!(report.hosts[0].waf.is_none() && report.hosts[0].providers.is_empty());
+}
+
+#[test]
+fn test_load_capture_detects_format() {
+    let dir = std::env::temp_dir().join(format!("waf-capture-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let har = dir.join("traffic.har");
+    std::fs::write(&har, HAR).unwrap();
+    let capture = waf_detector::capture::load_capture(&har).unwrap();
+    assert_eq!(capture.responses.len(), 4);
+    assert!(capture.tls.is_empty());
+
+    // Empty little-endian libpcap file with Ethernet link type
+    let pcap = dir.join("empty.pcap");
+    let mut header = vec![0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+    header.extend_from_slice(&[0; 8]);
+    header.extend_from_slice(&[0xff, 0xff, 0, 0, 1, 0, 0, 0]);
+    std::fs::write(&pcap, header).unwrap();
+    let result = waf_detector::capture::load_capture(&pcap);
+    if cfg!(feature = "pcap") {
+        assert!(result.unwrap().responses.is_empty());
+    } else {
+        assert!(result.unwrap_err().to_string().contains("pcap"));
+    }
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -1,18 +1,19 @@
 use waf_detector::*;
-use std::collections::HashMap;
+use waf_detector::http::Headers;
 
 #[tokio::test]
 async fn test_cloudflare_detection_integration() {
     // Create a mock HTTP response that looks like CloudFlare
-    let mut headers = HashMap::new();
-    headers.insert("cf-ray".to_string(), "1234567890abcdef-DFW".to_string());
-    headers.insert("server".to_string(), "cloudflare".to_string());
+    let mut headers = Headers::new();
+    headers.insert("cf-ray", "1234567890abcdef-DFW".to_string());
+    headers.insert("server", "cloudflare".to_string());
     
     let _response = http::HttpResponse {
         status: 200,
         headers,
         body: "<!DOCTYPE html><html>".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     // Test CloudFlare provider directly
@@ -82,14 +83,15 @@ async fn test_http_client() {
     assert!(client.is_ok());
     
     // Test HTTP response structure
-    let mut headers = HashMap::new();
-    headers.insert("content-type".to_string(), "text/html".to_string());
+    let mut headers = Headers::new();
+    headers.insert("content-type", "text/html".to_string());
     
     let response = http::HttpResponse {
         status: 200,
         headers,
         body: "<html></html>".to_string(),
         url: "https://example.com".to_string(),
+        ..Default::default()
     };
     
     assert_eq!(response.status, 200);
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{response, signatures};
+use waf_detector::*;
+use waf_detector::providers::{bunnycdn::BunnyCdnProvider, cachefly::CacheflyProvider, cdn77::Cdn77Provider, stackpath::StackPathProvider};
+
+#[tokio::test]
+async fn test_bunnycdn_headers() {
+    let provider = BunnyCdnProvider::new();
+    assert_eq!((provider.name(), provider.provider_type()), ("BunnyCDN", ProviderType::CDN));
+
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "BunnyCDN-DE1-1043"),
+        ("cdn-pullzone", "123456"),
+        ("cdn-requestid", "9f1c2b3a4d5e6f708192a3b4c5d6e7f8"),
+        ("cdn-cache", "HIT"),
+    ], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["bunnycdn-server-header", "bunnycdn-pullzone-header"]);
+    assert!(evidence[1].raw_data.contains("cdn-pullzone: 123456"));
+}
+
+#[tokio::test]
+async fn test_stackpath_headers() {
+    let provider = StackPathProvider::new();
+    let evidence = provider.passive_detect(&response(200, &[
+        ("x-sp-url", "https://example.com/"),
+        ("x-sp-edge-host", "fra-edge-01"),
+        ("x-hw", "1700000000.cds051.fr2.hn,1700000000.cds207.fr2.c"),
+    ], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["stackpath-sp-header", "stackpath-hw-header"]);
+}
+
+#[tokio::test]
+async fn test_cdn77_headers() {
+    let provider = Cdn77Provider::new();
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "CDN77-Turbo"),
+        ("x-77-nzt", "AwcBAQ"),
+        ("x-77-cache", "HIT"),
+    ], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["cdn77-server-header", "cdn77-edge-header"]);
+}
+
+#[tokio::test]
+async fn test_cachefly_headers() {
+    let provider = CacheflyProvider::new();
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "CFS 0215"),
+        ("x-cf1", "28002:fP.jfk2:co:1700000000:cacheN.jfk2-01:M"),
+        ("x-cf2", "H"),
+    ], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["cachefly-server-header", "cachefly-cache-header"]);
+    assert_eq!(evidence[1].confidence, 0.93);
+}
+
+#[tokio::test]
+async fn test_unrelated_responses_are_not_mid_tier_cdns() {
+    let origin = response(200, &[("server", "nginx/1.25.3"), ("cf-ray", "8a1b2c3d4e5f6789-FRA"), ("x-cache", "HIT")], "");
+    assert!(BunnyCdnProvider::new().passive_detect(&origin).await.unwrap().is_empty());
+    assert!(StackPathProvider::new().passive_detect(&origin).await.unwrap().is_empty());
+    assert!(Cdn77Provider::new().passive_detect(&origin).await.unwrap().is_empty());
+    assert!(CacheflyProvider::new().passive_detect(&origin).await.unwrap().is_empty());
+}
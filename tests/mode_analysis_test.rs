@@ -0,0 +1,77 @@
+mod common;
+
+use waf_detector::*;
+use waf_detector::engine::DetectionEngine;
+use waf_detector::engine::waf_mode_detector::{PayloadType, WafMode};
+use waf_detector::providers::{Provider, azure::AzureProvider};
+use mockito::Matcher;
+
+const SQLI_PAYLOADS: &[&str] = &[
+    "' OR '1'='1",
+    "'; DROP TABLE users; --",
+    "1' UNION SELECT NULL,NULL,NULL--",
+    "1' AND (SELECT COUNT(*) FROM information_schema.tables)>0--",
+    "'; WAITFOR DELAY '00:00:05'--",
+    "' OR 1=1 LIMIT 1 OFFSET 0--",
+];
+
+fn azure_engine() -> DetectionEngine {
+    common::engine([Provider::Azure(AzureProvider::new())])
+}
+
+#[tokio::test]
+async fn test_mode_analysis_reports_mode_per_category() {
+    let mut server = mockito::Server::new_async().await;
+    let _baseline = server.mock("GET", "/")
+        .match_query(Matcher::Exact(String::new()))
+        .with_status(200)
+        .with_header("x-azure-ref", "20240101T120000Z-16bd8c8f5dfqvkzshC1CHIabcd")
+        .create_async()
+        .await;
+    for payload in SQLI_PAYLOADS {
+        server.mock("GET", "/")
+            .match_query(Matcher::UrlEncoded("test".to_string(), payload.to_string()))
+            .with_status(403)
+            .create_async()
+            .await;
+    }
+    let _allowed = server.mock("GET", "/")
+        .match_query(Matcher::Regex("test=".to_string()))
+        .with_status(200)
+        .with_body("ok")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let result = azure_engine()
+        .with_options(DetectionOptions { dns: false, mode_analysis: true, ..DetectionOptions::default() })
+        .detect(&format!("{}/", server.url())).await.unwrap();
+    let mode = result.waf_mode.expect("waf mode");
+
+    assert_eq!(mode.mode, WafMode::Mixed);
+    let mode_of = |payload_type: PayloadType| {
+        mode.category_modes.iter().find(|c| c.payload_type == payload_type).map(|c| c.mode.clone())
+    };
+    assert_eq!(mode_of(PayloadType::SqlInjectionBasic), Some(WafMode::Blocking));
+    assert_eq!(mode_of(PayloadType::SqlInjectionAdvanced), Some(WafMode::Blocking));
+    assert_eq!(mode_of(PayloadType::XssBasic), Some(WafMode::Monitoring));
+
+    let diagnostics = result.diagnostics.techniques.iter().find(|t| t.name == "ModeAnalysis").unwrap();
+    assert_eq!(diagnostics.requests, mode.test_results.len());
+}
+
+#[tokio::test]
+async fn test_mode_analysis_is_opt_in() {
+    let mut server = mockito::Server::new_async().await;
+    let _baseline = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("x-azure-ref", "20240101T120000Z-16bd8c8f5dfqvkzshC1CHIabcd")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let result = azure_engine().detect(&server.url()).await.unwrap();
+
+    assert!(result.waf_mode.is_none());
+    assert!(serde_json::to_value(&result).unwrap().get("waf_mode").is_none());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::engine;
+use waf_detector::providers::{Provider, aws::AwsProvider};
+
+#[tokio::test]
+async fn test_google_front_end_rules_out_cloudfront() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("server", "gws")
+        .with_header("x-amz-cf-pop", "FRA56-P1")
+        .with_body("<html>Welcome</html>")
+        .create_async()
+        .await;
+
+    let result = engine([Provider::AWS(AwsProvider::new())]).detect(&server.url()).await.unwrap();
+
+    assert!(result.detected_waf.is_none());
+    assert!(result.detected_cdn.is_none());
+    assert_eq!(result.provider_scores.get("AWS"), Some(&0.0));
+    let hits = &result.negative_evidence["AWS"];
+    assert_eq!(hits.len(), 1);
+    assert_eq!((hits[0].header.as_str(), hits[0].value.as_str()), ("server", "gws"));
+}
+
+#[tokio::test]
+async fn test_uncontradicted_detection_has_no_negative_evidence() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("x-amz-cf-pop", "FRA56-P1")
+        .create_async()
+        .await;
+
+    let result = engine([Provider::AWS(AwsProvider::new())]).detect(&server.url()).await.unwrap();
+
+    assert!(result.detected_cdn.is_some());
+    assert!(result.negative_evidence.is_empty());
+    assert!(serde_json::to_value(&result).unwrap().get("negative_evidence").is_none());
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::engine;
+use waf_detector::*;
+use waf_detector::http::{count_requests, HttpResponse};
+use waf_detector::providers::default_providers;
+
+#[tokio::test]
+async fn test_detect_from_captured_response_without_network() {
+    let response = HttpResponse::from_parts(
+        403,
+        [("CF-RAY", "8a1b2c3d4e5f6a7b-AMS"), ("Server", "cloudflare"), ("Set-Cookie", "__cf_bm=abc; path=/"), ("Set-Cookie", "a=b")],
+        "<html>Attention Required! | Cloudflare</html>",
+        "https://offline.invalid/",
+    );
+    assert_eq!(response.headers.get_all("set-cookie").collect::<Vec<_>>(), ["__cf_bm=abc; path=/", "a=b"]);
+    assert_eq!(response.headers.to_map()["set-cookie"], "__cf_bm=abc; path=/\na=b");
+
+    // Probing options are ignored for captured responses
+    let engine = engine(default_providers()).with_options(DetectionOptions::full());
+    let (result, requests) = count_requests(engine.detect_from_response("https://offline.invalid/", response)).await;
+    let result = result.unwrap();
+
+    assert_eq!(requests, 0);
+    assert_eq!(result.detected_waf.unwrap().name, "CloudFlare");
+    assert!(!result.evidence_map.contains_key("DnsAnalysis"));
+}
+
+#[tokio::test]
+async fn test_detect_from_response_rejects_invalid_url() {
+    let response = HttpResponse::from_parts(200, Vec::<(String, String)>::new(), "", "");
+    assert!(engine(default_providers()).detect_from_response("not a url", response).await.is_err());
+}
+
+#[tokio::test]
+async fn test_restyled_block_page_matches_corpus() {
+    let sucuri = waf_detector::blockpage::CORPUS.iter().find(|page| page.vendor == "Sucuri").unwrap();
+    let body = sucuri.body.replace("Access Denied - Sucuri Website Firewall", "Request blocked by Example Corp security");
+    let response = HttpResponse::from_parts(403, [("Server", "nginx")], body, "https://offline.invalid/");
+
+    let result = engine(default_providers()).detect_from_response("https://offline.invalid/", response).await.unwrap();
+    let evidence = &result.evidence_map["BlockPageAnalysis"];
+
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].signature_matched, "Sucuri");
+    assert_eq!(evidence[0].method_type, DetectionMethod::Body("block-page".to_string()));
+}
@@ -0,0 +1,164 @@
+//! Probe traffic must be opt-in, and strict passive mode must only ever send the
+//! single initial GET request
+
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider, aws::AwsProvider};
+use waf_detector::registry::{Analyzer, ProviderRegistry};
+use waf_detector::DetectionOptions;
+
+#[tokio::test]
+async fn test_passive_mode_sends_exactly_one_request() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("cf-ray", "8a1b2c3d4e5f6789-DFW")
+        .with_body("<html></html>")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    registry.register_provider(Provider::AWS(AwsProvider::new())).unwrap();
+    registry.set_analyzer_enabled(Analyzer::Dns, false);
+
+    let engine = DetectionEngine::new(registry).with_passive_mode();
+
+    // Re-enabling probing analyzers must not break the passive guarantee
+    engine.registry().set_analyzer_enabled(Analyzer::Payload, true);
+    engine.registry().set_analyzer_enabled(Analyzer::Timing, true);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(engine.request_count(), 1);
+    assert!(engine.is_passive());
+    assert!(!result.evidence_map.contains_key("PayloadAnalysis"));
+    assert!(!result.evidence_map.contains_key("TimingAnalysis"));
+}
+
+#[tokio::test]
+async fn test_passive_mode_never_retries() {
+    let mut server = mockito::Server::new_async().await;
+    // 502 is retried by the default policy everywhere but in strict passive mode
+    let mock = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(502)
+        .with_header("server", "cloudflare")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    registry.set_analyzer_enabled(Analyzer::Dns, false);
+    let engine = DetectionEngine::new(registry).with_passive_mode();
+
+    engine.detect(&server.url()).await.unwrap();
+    mock.assert_async().await;
+    assert_eq!(engine.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_passive_mode_does_not_follow_redirects() {
+    let mut server = mockito::Server::new_async().await;
+    let redirect = server
+        .mock("GET", "/")
+        .with_status(301)
+        .with_header("location", "/home")
+        .with_header("cf-ray", "8a1b2c3d4e5f6789-DFW")
+        .expect(1)
+        .create_async()
+        .await;
+    let home = server.mock("GET", "/home").with_status(200).expect(0).create_async().await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    registry.set_analyzer_enabled(Analyzer::Dns, false);
+    let engine = DetectionEngine::new(registry).with_passive_mode();
+
+    let result = engine.detect(&format!("{}/", server.url())).await.unwrap();
+    redirect.assert_async().await;
+    home.assert_async().await;
+    assert_eq!(engine.request_count(), 1);
+    // The edge answered the redirect itself
+    assert_eq!(result.detected_waf.map(|waf| waf.name).as_deref(), Some("CloudFlare"));
+}
+
+#[tokio::test]
+async fn test_default_detection_sends_no_probe_traffic() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_body("<html></html>")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::AWS(AwsProvider::new())).unwrap();
+    registry.set_analyzer_enabled(Analyzer::Dns, false);
+
+    let engine = DetectionEngine::new(registry);
+    assert!(!engine.options().sends_probe_traffic());
+
+    engine.detect(&server.url()).await.unwrap();
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_active_probes_are_opt_in() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_body("<html></html>")
+        .expect_at_least(2)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::AWS(AwsProvider::new())).unwrap();
+
+    let options = DetectionOptions {
+        dns: false,
+        active_probes: true,
+        ..DetectionOptions::default()
+    };
+    let engine = DetectionEngine::new(registry).with_options(options);
+
+    engine.detect(&server.url()).await.unwrap();
+    mock.assert_async().await;
+}
+
+#[test]
+fn test_disable_probing_covers_every_probe() {
+    let mut options = DetectionOptions {
+        dns: true,
+        timing: true,
+        payload: true,
+        active_probes: true,
+        preflight: true,
+        respect_robots: true,
+        fingerprint: true,
+        mode_analysis: true,
+        ratelimit_probe: true,
+        protocol: true,
+        header_order: true,
+        block_page: true,
+        favicon: true,
+        cache: true,
+        dual_stack: true,
+        browser_fallback: true,
+        origin_check: true,
+        ct: true,
+        host_intel: true,
+    };
+    assert!(options.sends_probe_traffic());
+
+    options.disable_probing();
+    assert!(!options.sends_probe_traffic());
+    assert!(!options.dual_stack && !options.browser_fallback && !options.origin_check);
+}
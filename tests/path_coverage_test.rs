@@ -0,0 +1,62 @@
+use waf_detector::*;
+use waf_detector::engine::DetectionEngine;
+use waf_detector::paths::PathScanner;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider};
+use waf_detector::registry::ProviderRegistry;
+use mockito::Matcher;
+
+#[tokio::test]
+async fn test_per_path_protection_differences() {
+    let mut server = mockito::Server::new_async().await;
+    let _root = server.mock("GET", "/")
+        .match_query(Matcher::Exact(String::new()))
+        .with_status(200)
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-LHR")
+        .with_header("server", "cloudflare")
+        .create_async()
+        .await;
+    let _root_blocked = server.mock("GET", "/")
+        .match_query(Matcher::Regex(".+".to_string()))
+        .with_status(403)
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-LHR")
+        .with_header("server", "cloudflare")
+        .create_async()
+        .await;
+    let _api = server.mock("GET", "/api")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body("{}")
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let engine = DetectionEngine::new(registry).with_options(options);
+
+    let scanner = PathScanner::new(&engine).with_smoke_test(1).unwrap();
+    let report = scanner
+        .scan(&server.url(), &["/".to_string(), "/api".to_string()])
+        .await
+        .unwrap();
+
+    assert_eq!(report.paths.len(), 2);
+    assert_eq!(report.paths[0].detected_waf.as_deref(), Some("CloudFlare"));
+    assert_eq!(report.paths[1].detected_waf, None);
+    assert!(report.paths[0].effectiveness_percentage.unwrap() > report.paths[1].effectiveness_percentage.unwrap());
+    assert!(report.paths[1].allowed_categories.contains(&"SqlInjectionBasic".to_string()));
+    assert_eq!(report.differences[0], "/api is not behind a WAF, but / is behind CloudFlare");
+    assert!(report.differences[1].starts_with("/api allows "));
+}
+
+#[test]
+fn test_paths_flag_parses() {
+    let matches = cli::build_cli()
+        .try_get_matches_from(["waf-detect", "example.com", "--paths", "/,/api", "--path-payloads", "0"])
+        .unwrap();
+    assert_eq!(matches.get_one::<String>("paths").unwrap(), "/,/api");
+    assert_eq!(matches.get_one::<usize>("path-payloads"), Some(&0));
+
+    let matches = cli::build_cli().try_get_matches_from(["waf-detect", "example.com", "--paths"]).unwrap();
+    assert_eq!(matches.get_one::<String>("paths").unwrap(), "");
+}
@@ -0,0 +1,56 @@
+use waf_detector::engine::waf_mode_detector::PayloadType;
+use waf_detector::payload::waf_smoke_test::{PayloadClassification, SmokeTestConfig, WafSmokeTest};
+use waf_detector::payload::PayloadCorpus;
+use mockito::Matcher;
+
+#[test]
+fn test_load_corpus_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("sqli.txt"), "' OR 1=1--\n# category: rce\n;id\n").unwrap();
+    std::fs::write(dir.path().join("extra.yaml"), "xss:\n  - <svg onload=alert(1)>\n").unwrap();
+    std::fs::write(dir.path().join(".hidden"), "ignored\n").unwrap();
+
+    let corpus = PayloadCorpus::load(dir.path()).unwrap();
+    let grouped = corpus.by_type();
+
+    assert_eq!(corpus.len(), 3);
+    assert_eq!(grouped[&PayloadType::SqlInjectionBasic], vec!["' OR 1=1--"]);
+    assert_eq!(grouped[&PayloadType::CommandInjection], vec![";id"]);
+    assert_eq!(grouped[&PayloadType::XssBasic], vec!["<svg onload=alert(1)>"]);
+}
+
+#[test]
+fn test_load_rejects_uncategorized_payloads() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("mixed.txt");
+    std::fs::write(&file, "<script>\n").unwrap();
+
+    let error = PayloadCorpus::load(&file).unwrap_err();
+    assert!(format!("{:#}", error).contains("no category"));
+    assert!(PayloadCorpus::load(dir.path().join("missing")).is_err());
+}
+
+#[tokio::test]
+async fn test_smoke_test_sends_only_corpus_payloads() {
+    let mut server = mockito::Server::new_async().await;
+    let custom = server.mock("GET", "/")
+        .match_query(Matcher::UrlEncoded("test".to_string(), "custom-canary".to_string()))
+        .with_status(403)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let corpus = PayloadCorpus::parse_text("custom-canary\n", Some(PayloadType::XssBasic)).unwrap();
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        quiet: true,
+        ..SmokeTestConfig::default()
+    };
+    let smoke_test = WafSmokeTest::new(config).unwrap().with_corpus(&corpus);
+
+    let result = smoke_test.run_test(&server.url()).await.unwrap();
+
+    custom.assert_async().await;
+    assert_eq!(result.test_results.len(), 1);
+    assert_eq!(result.test_results[0].classification, PayloadClassification::Blocked);
+}
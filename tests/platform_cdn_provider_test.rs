@@ -0,0 +1,59 @@
+mod common;
+
+use common::{response, signatures};
+use waf_detector::*;
+use waf_detector::providers::{fly::FlyProvider, netlify::NetlifyProvider, render::RenderProvider};
+
+#[tokio::test]
+async fn test_netlify_headers() {
+    let provider = NetlifyProvider::new();
+    assert_eq!((provider.name(), provider.provider_type()), ("Netlify", ProviderType::CDN));
+
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "Netlify"),
+        ("x-nf-request-id", "01HQ3Z8J5K7M9N1P3R5T7V9X1Z"),
+        ("cache-status", "\"Netlify Edge\"; fwd=miss"),
+    ], "")).await.unwrap();
+
+    assert_eq!(signatures(&evidence), ["netlify-server-header", "netlify-request-id-header", "netlify-cache-status"]);
+}
+
+#[tokio::test]
+async fn test_render_behind_cloudflare() {
+    let provider = RenderProvider::new();
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "cloudflare"),
+        ("cf-ray", "8a1b2c3d4e5f6789-FRA"),
+        ("x-render-origin-server", "Render"),
+        ("rndr-id", "4b2a9c1e-7f3d-4a1b"),
+    ], "")).await.unwrap();
+    assert_eq!(evidence.len(), 2);
+
+    // Cloudflare alone is not Render
+    let evidence = provider.passive_detect(&response(200, &[("server", "cloudflare"), ("cf-ray", "8a1b2c3d4e5f6789-FRA")], "")).await.unwrap();
+    assert!(evidence.is_empty());
+}
+
+#[tokio::test]
+async fn test_fly_headers() {
+    let provider = FlyProvider::new();
+    assert_eq!(provider.name(), "Fly.io");
+
+    let evidence = provider.passive_detect(&response(200, &[
+        ("server", "Fly/9e5f3e8f (2024-01-15)"),
+        ("via", "1.1 fly.io"),
+        ("fly-request-id", "01HQ3Z8J5K7M9N1P3R5T7V9X1Z-fra"),
+    ], "")).await.unwrap();
+    assert_eq!(signatures(&evidence), ["fly-server-header", "fly-request-id-header", "fly-via-header"]);
+
+    let evidence = provider.passive_detect(&response(200, &[("server", "nginx"), ("via", "1.1 butterfly.io")], "")).await.unwrap();
+    assert!(evidence.is_empty());
+}
+
+#[tokio::test]
+async fn test_platforms_are_registered_by_default() {
+    let names: Vec<String> = providers::default_providers().iter().map(|p| p.name().to_string()).collect();
+    for platform in ["Netlify", "Render", "Fly.io"] {
+        assert!(names.iter().any(|name| name == platform), "{} missing from {:?}", platform, names);
+    }
+}
@@ -0,0 +1,80 @@
+//! robots.txt / security.txt pre-flight behaviour against a mock server
+
+use mockito::{Matcher, Server};
+use waf_detector::engine::DetectionEngine;
+use waf_detector::registry::ProviderRegistry;
+use waf_detector::DetectionOptions;
+
+#[tokio::test]
+async fn test_preflight_records_robots_and_security_txt() {
+    let mut server = Server::new_async().await;
+    let _root = server.mock("GET", "/").with_status(200).create_async().await;
+    let _robots = server
+        .mock("GET", "/robots.txt")
+        .with_status(200)
+        .with_body("User-agent: *\nDisallow: /private\n")
+        .create_async()
+        .await;
+    let _security = server
+        .mock("GET", "/.well-known/security.txt")
+        .with_status(200)
+        .with_body("Contact: mailto:security@example.com\n")
+        .create_async()
+        .await;
+
+    let options = DetectionOptions {
+        dns: false,
+        preflight: true,
+        ..DetectionOptions::default()
+    };
+    let engine = DetectionEngine::new(ProviderRegistry::new()).with_options(options);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+    let preflight = result.metadata.preflight.expect("preflight metadata");
+
+    assert!(preflight.target_allowed);
+    assert!(!preflight.probing_skipped);
+    assert_eq!(preflight.robots_txt.unwrap().groups.len(), 1);
+    assert_eq!(preflight.security_txt.unwrap().contact, vec!["mailto:security@example.com"]);
+}
+
+#[tokio::test]
+async fn test_respect_robots_skips_probing_on_disallowed_path() {
+    let mut server = Server::new_async().await;
+    // Only the initial GET may reach the scanned path; payload probes would add query strings
+    let target = server
+        .mock("GET", "/")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+    let _robots = server
+        .mock("GET", "/robots.txt")
+        .with_status(200)
+        .with_body("User-agent: *\nDisallow: /\n")
+        .create_async()
+        .await;
+    let _security = server
+        .mock("GET", "/.well-known/security.txt")
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let options = DetectionOptions {
+        dns: false,
+        payload: true,
+        respect_robots: true,
+        ..DetectionOptions::default()
+    };
+    let engine = DetectionEngine::new(ProviderRegistry::new()).with_options(options);
+
+    let result = engine.detect(&server.url()).await.unwrap();
+    let preflight = result.metadata.preflight.expect("preflight metadata");
+
+    assert!(!preflight.target_allowed);
+    assert!(preflight.probing_skipped);
+    assert!(preflight.security_txt.is_none());
+    assert!(!result.evidence_map.contains_key("PayloadAnalysis"));
+    target.assert_async().await;
+}
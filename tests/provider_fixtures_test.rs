@@ -0,0 +1,92 @@
+//! Every built-in provider against its recorded responses in tests/fixtures, served
+//! from a local mock server
+
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::{default_providers, Provider};
+use waf_detector::registry::ProviderRegistry;
+use waf_detector::testing::{load_fixtures, Fixture, MockServer};
+use waf_detector::*;
+
+fn fixtures() -> Vec<Fixture> {
+    load_fixtures(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")).unwrap()
+}
+
+fn provider(name: &str) -> Provider {
+    default_providers().into_iter().find(|p| p.name() == name).unwrap_or_else(|| panic!("no provider named {}", name))
+}
+
+#[test]
+fn test_every_provider_has_a_fixture() {
+    let fixtures = fixtures();
+    for provider in default_providers() {
+        assert!(
+            fixtures.iter().any(|f| f.provider.as_deref() == Some(provider.name())),
+            "no fixture under tests/fixtures/{}",
+            provider.name()
+        );
+    }
+    assert!(fixtures.iter().any(|f| f.provider.is_none()), "no origin fixtures");
+}
+
+#[tokio::test]
+async fn test_passive_detect_matches_fixtures() {
+    for fixture in fixtures() {
+        let response = fixture.response("https://www.example.com/").unwrap();
+        for provider in default_providers() {
+            let evidence = provider.passive_detect(&response).await.unwrap();
+            if fixture.provider.as_deref() == Some(provider.name()) {
+                assert!(!evidence.is_empty(), "{} found nothing in its {} fixture", provider.name(), fixture.name);
+            } else if fixture.provider.is_none() {
+                assert!(evidence.is_empty(), "{} matched the {} origin fixture: {:?}", provider.name(), fixture.name, evidence);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_detect_matches_fixtures() {
+    for fixture in fixtures().into_iter().filter(|f| f.provider.is_some()) {
+        let provider = provider(fixture.provider.as_deref().unwrap());
+        let context = DetectionContext {
+            url: "https://www.example.com/".to_string(),
+            response: Some(fixture.response("https://www.example.com/").unwrap()),
+            dns_info: None,
+            user_agent: "waf-detector-test".to_string(),
+        };
+        let evidence = provider.detect(&context).await.unwrap();
+        assert!(!evidence.is_empty(), "{} detect found nothing in {}", provider.name(), fixture.name);
+    }
+}
+
+#[tokio::test]
+async fn test_active_detect_against_mock_server() {
+    let client = http::HttpClient::new().unwrap();
+    for fixture in fixtures().into_iter().filter(|f| f.provider.is_some()) {
+        let server = MockServer::serving(&fixture).await.unwrap();
+        let provider = provider(fixture.provider.as_deref().unwrap());
+        let result = provider.active_detect(&client, &server.url()).await;
+        assert!(result.is_ok(), "{} active_detect failed on {}: {:?}", provider.name(), fixture.name, result.err());
+    }
+}
+
+#[tokio::test]
+async fn test_engine_detects_each_fixture_end_to_end() {
+    for fixture in fixtures() {
+        let server = MockServer::serving(&fixture).await.unwrap();
+        let registry = ProviderRegistry::new();
+        for provider in default_providers() {
+            registry.register_provider(provider).unwrap();
+        }
+        let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+        let engine = DetectionEngine::new(registry).with_options(options);
+
+        let result = engine.detect(&server.url()).await.unwrap();
+
+        let stack: Vec<&str> = result.detected_stack.iter().map(|p| p.name.as_str()).collect();
+        match fixture.provider.as_deref() {
+            Some(name) => assert!(stack.contains(&name), "{} not in {:?} for {}", name, stack, fixture.name),
+            None => assert!(stack.is_empty(), "origin fixture {} detected as {:?}", fixture.name, stack),
+        }
+        assert!(!server.requests().is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use waf_detector::http::HttpClient;
+use waf_detector::ratelimit_probe::{RateLimitProbe, RateLimitProbeConfig, RateLimitScope};
+use mockito::Matcher;
+use std::time::Duration;
+
+fn fast_config() -> RateLimitProbeConfig {
+    RateLimitProbeConfig {
+        rates: vec![20.0],
+        requests_per_step: 10,
+        max_requests: 20,
+        ban_check_interval: Duration::from_millis(50),
+        max_ban_wait: Duration::from_millis(200),
+    }
+}
+
+#[tokio::test]
+async fn test_probe_finds_per_ip_threshold() {
+    let mut server = mockito::Server::new_async().await;
+    let _allowed = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("x-ratelimit-limit", "4")
+        .expect(4)
+        .create_async()
+        .await;
+    let _limited = server.mock("GET", "/")
+        .with_status(429)
+        .with_header("retry-after", "60")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let client = HttpClient::new().unwrap();
+    let profile = RateLimitProbe::new(&client)
+        .with_config(fast_config())
+        .probe(&format!("{}/", server.url()))
+        .await
+        .unwrap();
+
+    assert!(profile.limited);
+    assert_eq!(profile.threshold_rps, Some(20.0));
+    assert_eq!(profile.requests_before_limit, Some(4));
+    assert_eq!(profile.limit_status, Some(429));
+    assert_eq!(profile.retry_after.as_deref(), Some("60"));
+    assert_eq!(profile.scope, RateLimitScope::PerIp);
+    assert_eq!(profile.ban_duration_secs, None);
+    assert_eq!(profile.limit_headers, vec!["retry-after", "x-ratelimit-limit"]);
+    assert!(profile.requests_sent <= 20);
+}
+
+#[tokio::test]
+async fn test_probe_detects_session_scoped_limit() {
+    let mut server = mockito::Server::new_async().await;
+    let fresh_session = server.mock("GET", "/")
+        .match_header("cookie", Matcher::Regex("^session=".to_string()))
+        .with_status(200)
+        .create_async()
+        .await;
+    let _allowed = server.mock("GET", "/")
+        .with_status(200)
+        .expect(2)
+        .create_async()
+        .await;
+    let _limited = server.mock("GET", "/")
+        .with_status(429)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let client = HttpClient::new().unwrap();
+    let profile = RateLimitProbe::new(&client)
+        .with_config(fast_config())
+        .probe(&format!("{}/", server.url()))
+        .await
+        .unwrap();
+
+    assert_eq!(profile.requests_before_limit, Some(2));
+    assert_eq!(profile.scope, RateLimitScope::PerSession);
+    fresh_session.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_probe_respects_request_cap() {
+    let mut server = mockito::Server::new_async().await;
+    let _allowed = server.mock("GET", "/")
+        .with_status(200)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let config = RateLimitProbeConfig { max_requests: 6, ..fast_config() };
+    let client = HttpClient::new().unwrap();
+    let profile = RateLimitProbe::new(&client)
+        .with_config(config)
+        .probe(&format!("{}/", server.url()))
+        .await
+        .unwrap();
+
+    assert!(!profile.limited);
+    assert!(profile.stopped_by_cap);
+    assert_eq!(profile.requests_sent, 6);
+}
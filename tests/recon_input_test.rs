@@ -0,0 +1,30 @@
+use waf_detector::recon::load_scan_targets;
+
+#[test]
+fn test_load_scan_targets_from_nmap_and_masscan() {
+    let dir = std::env::temp_dir().join(format!("waf-recon-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let nmap = dir.join("scan.xml");
+    std::fs::write(&nmap, r#"<?xml version="1.0"?>
+<nmaprun scanner="nmap">
+<host><status state="up"/><address addr="198.51.100.4" addrtype="ipv4"/>
+<hostnames><hostname name="www.example.com" type="user"/></hostnames>
+<ports>
+<port protocol="tcp" portid="80"><state state="open"/><service name="http"/></port>
+<port protocol="tcp" portid="443"><state state="open"/><service name="https"/></port>
+<port protocol="tcp" portid="3306"><state state="open"/><service name="mysql"/></port>
+</ports></host>
+</nmaprun>"#).unwrap();
+    assert_eq!(load_scan_targets(&nmap).unwrap(), vec!["http://www.example.com/", "https://www.example.com/"]);
+
+    let masscan = dir.join("scan.json");
+    std::fs::write(&masscan, concat!(
+        "{\"ip\": \"198.51.100.5\", \"ports\": [{\"port\": 443, \"proto\": \"tcp\", \"status\": \"open\"}]}\n",
+        "{\"ip\": \"198.51.100.5\", \"ports\": [{\"port\": 443, \"proto\": \"tcp\", \"status\": \"open\"}]}\n",
+    )).unwrap();
+    assert_eq!(load_scan_targets(&masscan).unwrap(), vec!["https://198.51.100.5/"]);
+
+    assert!(load_scan_targets(dir.join("missing.xml")).is_err());
+    std::fs::remove_dir_all(&dir).ok();
+}
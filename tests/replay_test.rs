@@ -0,0 +1,51 @@
+//! Scans recorded against a mock server and replayed after it is gone
+
+mod common;
+
+use common::engine;
+use waf_detector::providers::default_providers;
+use waf_detector::replay::{Session, Tape};
+use waf_detector::testing::{Fixture, MockServer};
+use waf_detector::*;
+
+fn stack(result: &DetectionResult) -> Vec<String> {
+    result.detected_stack.iter().map(|p| p.name.clone()).collect()
+}
+
+#[tokio::test]
+async fn test_replayed_scan_matches_recorded_scan() {
+    let fixture = Fixture::load(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/CloudFlare/block_page.http")).unwrap();
+    let server = MockServer::serving(&fixture).await.unwrap();
+    let url = server.url();
+
+    let options = DetectionOptions { dns: false, active_probes: true, ..DetectionOptions::default() };
+    let tape = Tape::record();
+    let recorded = engine(default_providers()).with_options(options.clone()).with_tape(tape.clone()).detect(&url).await.unwrap();
+    let sent = server.requests().len();
+    drop(server);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.json");
+    tape.session().save(&path).unwrap();
+    let session = Session::load(&path).unwrap();
+    assert_eq!(session.targets, std::slice::from_ref(&recorded.url));
+    assert_eq!(session.http_exchanges().count(), sent);
+    assert!(session.http_exchanges().all(|exchange| exchange.response.is_some()));
+
+    let replay = Tape::replay(session);
+    let replayed = engine(default_providers()).with_options(options).with_tape(replay.clone()).detect(&url).await.unwrap();
+    assert!(stack(&recorded).contains(&"CloudFlare".to_string()));
+    assert_eq!(stack(&replayed), stack(&recorded));
+    assert_eq!(replayed.detected_waf.map(|waf| waf.name), recorded.detected_waf.map(|waf| waf.name));
+    assert_eq!(replayed.diagnostics.total_requests, recorded.diagnostics.total_requests);
+    assert_eq!(replay.replayed(), sent);
+}
+
+#[tokio::test]
+async fn test_replay_never_touches_the_network() {
+    let server = MockServer::start().await.unwrap();
+    let replay = Tape::replay(Session::default());
+    let result = engine(default_providers()).with_tape(replay).detect(&server.url()).await;
+    assert!(result.map_or(true, |result| result.failed()));
+    assert!(server.requests().is_empty());
+}
@@ -0,0 +1,42 @@
+use waf_detector::*;
+use waf_detector::confidence::{AdvancedScoring, ScoringConfig};
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider};
+use waf_detector::registry::ProviderRegistry;
+
+#[tokio::test]
+async fn test_scoring_overrides_change_detection() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", "/")
+        .with_status(200)
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let registry = ProviderRegistry::new();
+    registry.register_provider(Provider::CloudFlare(CloudFlareProvider::new())).unwrap();
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let engine = DetectionEngine::new(registry).with_options(options);
+    assert!(engine.detect(&server.url()).await.unwrap().detected_waf.is_some());
+
+    let weights = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(weights.path(), "category_multipliers:\n  Headers: 0.0\n").unwrap();
+    let config = ScoringConfig::from_file(weights.path()).unwrap();
+    engine.registry().set_advanced_scoring(AdvancedScoring::new().with_config(config).unwrap());
+
+    let result = engine.detect(&server.url()).await.unwrap();
+    assert!(result.detected_waf.is_none());
+    assert_eq!(result.provider_scores["CloudFlare"], 0.0);
+}
+
+#[test]
+fn test_dump_round_trips_through_scoring_file() {
+    let dumped = serde_yaml::to_string(&AdvancedScoring::new().to_config()).unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &dumped).unwrap();
+
+    let loaded = ScoringConfig::from_file(file.path()).unwrap();
+    let scoring = AdvancedScoring::new().with_config(loaded).unwrap();
+    assert_eq!(serde_yaml::to_string(&scoring.to_config()).unwrap(), dumped);
+}
@@ -0,0 +1,35 @@
+use waf_detector::*;
+use waf_detector::engine::DetectionEngine;
+use waf_detector::registry::ProviderRegistry;
+use mockito::Matcher;
+
+#[tokio::test]
+async fn test_session_compares_first_time_and_returning_clients() {
+    let mut server = mockito::Server::new_async().await;
+    let first_visit = server.mock("GET", "/")
+        .match_header("cookie", Matcher::Missing)
+        .with_status(200)
+        .with_header("set-cookie", "waf_state=abc123; Path=/")
+        .create_async()
+        .await;
+    let returning = server.mock("GET", "/")
+        .match_header("cookie", "waf_state=abc123")
+        .with_status(403)
+        .create_async()
+        .await;
+
+    let options = DetectionOptions { dns: false, ..DetectionOptions::default() };
+    let engine = DetectionEngine::new(ProviderRegistry::new()).with_options(options).with_cookie_session();
+
+    let url = format!("{}/", server.url());
+    let session = engine.detect(&url).await.unwrap().session.expect("session comparison");
+    assert_eq!(session.cookies, vec!["waf_state"]);
+    assert_eq!(session.stateless_status, 200);
+    assert_eq!(session.stateful_status, Some(403));
+    assert!(session.differs());
+
+    // A second scan starts without cookies again
+    engine.detect(&url).await.unwrap();
+    first_visit.expect(2).assert_async().await;
+    returning.expect(2).assert_async().await;
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+use waf_detector::payload::waf_smoke_test::{SmokeTestConfig, WafSmokeTest};
+use mockito::Matcher;
+
+fn config(max_concurrent_requests: usize, delay_between_requests_ms: u64) -> SmokeTestConfig {
+    SmokeTestConfig {
+        delay_between_requests_ms,
+        max_concurrent_requests,
+        payloads_per_category: Some(1),
+        quiet: true,
+        ..SmokeTestConfig::default()
+    }
+}
+
+async fn server() -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_payloads_run_concurrently() {
+    let server = server().await;
+    // One payload per category is ~10 requests; sequentially the delays alone take 3s
+    let smoke_test = WafSmokeTest::new(config(16, 300)).unwrap();
+
+    let result = smoke_test.run_test(&server.url()).await.unwrap();
+
+    assert!(!result.cancelled);
+    assert!(result.summary.total_tests >= 8);
+    assert!(result.total_time_ms < 1500, "took {}ms", result.total_time_ms);
+}
+
+#[tokio::test]
+async fn test_cancellation_returns_partial_result() {
+    let server = server().await;
+    let smoke_test = WafSmokeTest::new(config(1, 200)).unwrap();
+
+    let result = smoke_test
+        .run_test_until(&server.url(), tokio::time::sleep(Duration::from_millis(500)))
+        .await
+        .unwrap();
+
+    assert!(result.cancelled);
+    let completed = result.test_results.len() + result.control_results.len();
+    assert!((1..6).contains(&completed), "completed {}", completed);
+    assert_eq!(result.summary.total_tests, result.test_results.len());
+}
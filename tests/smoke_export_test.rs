@@ -0,0 +1,80 @@
+use waf_detector::payload::export::{export, ExportFormat};
+use waf_detector::payload::waf_smoke_test::{SmokeTestConfig, SmokeTestResult, WafSmokeTest};
+use mockito::Matcher;
+
+async fn smoke_result() -> SmokeTestResult {
+    let mut server = mockito::Server::new_async().await;
+    let _blocked = server.mock("GET", Matcher::Any)
+        .with_status(403)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        ..SmokeTestConfig::default()
+    };
+    WafSmokeTest::new(config).unwrap().run_test(&server.url()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_export_writes_requested_path() {
+    let result = smoke_result().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.json");
+    std::fs::write(&path, "stale").unwrap();
+
+    WafSmokeTest::default().export_json(&result, path.to_str().unwrap()).unwrap();
+
+    let exported: SmokeTestResult = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(exported.summary.total_tests, result.summary.total_tests);
+    // Only the target file is left behind, no temporary files
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_export_csv_and_html() {
+    let result = smoke_result().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let csv_path = dir.path().join("results.csv");
+    export(&result, &csv_path, ExportFormat::Csv).unwrap();
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("kind,category,location,payload,classification,response_status,response_time_ms"));
+    assert!(csv.lines().any(|line| line.starts_with("control,Benign,query,")));
+    assert_eq!(csv.lines().count(), 1 + result.test_results.len() + result.control_results.len());
+
+    let html_path = dir.path().join("report.html");
+    export(&result, &html_path, ExportFormat::Html).unwrap();
+    let html = std::fs::read_to_string(&html_path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Coverage by category"));
+    assert!(!html.contains("<script>"));
+}
+
+#[test]
+fn test_export_to_missing_directory_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing").join("results.json");
+    let result: SmokeTestResult = serde_json::from_value(serde_json::json!({
+        "url": "https://example.com",
+        "test_results": [],
+        "summary": {
+            "total_tests": 0, "blocked_count": 0, "allowed_count": 0, "error_count": 0,
+            "rate_limited_count": 0, "challenge_count": 0,
+            "effectiveness_percentage": 0.0, "average_response_time_ms": 0.0
+        },
+        "waf_mode": null,
+        "detected_waf": null,
+        "detected_cdn": null,
+        "recommendations": [],
+        "total_time_ms": 0,
+        "timestamp": "2024-01-01T00:00:00Z",
+        "is_smoke_test": true
+    })).unwrap();
+
+    assert!(export(&result, &path, ExportFormat::Json).is_err());
+}
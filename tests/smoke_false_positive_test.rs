@@ -0,0 +1,55 @@
+use waf_detector::engine::waf_mode_detector::PayloadType;
+use waf_detector::payload::waf_smoke_test::{SmokeTestConfig, WafSmokeTest};
+use mockito::Matcher;
+
+fn config(benign_controls: bool) -> SmokeTestConfig {
+    SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        benign_controls,
+        ..SmokeTestConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_blocked_benign_controls_are_reported_as_false_positives() {
+    let mut server = mockito::Server::new_async().await;
+    // The catch-all expects no hits, so mockito always prefers the more specific mocks below
+    let _allowed = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let over_blocking = server.mock("GET", "/")
+        .match_query(Matcher::UrlEncoded("test".to_string(), "O'Brien".to_string()))
+        .with_status(403)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let result = WafSmokeTest::new(config(true)).unwrap().run_test(&server.url()).await.unwrap();
+
+    over_blocking.assert_async().await;
+    assert_eq!(result.control_results.len(), 1);
+    assert!(result.test_results.iter().all(|t| t.payload_type != PayloadType::Benign));
+    assert_eq!(result.summary.control_count, 1);
+    assert_eq!(result.summary.false_positive_count, 1);
+    assert_eq!(result.summary.false_positive_percentage, 100.0);
+    assert!(result.recommendations.iter().any(|r| r.contains("false positives")));
+}
+
+#[tokio::test]
+async fn test_benign_controls_can_be_disabled() {
+    let mut server = mockito::Server::new_async().await;
+    let _allowed = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .create_async()
+        .await;
+
+    let result = WafSmokeTest::new(config(false)).unwrap().run_test(&server.url()).await.unwrap();
+
+    assert!(result.control_results.is_empty());
+    assert_eq!(result.summary.control_count, 0);
+    assert_eq!(result.summary.false_positive_percentage, 0.0);
+}
@@ -0,0 +1,101 @@
+use waf_detector::payload::waf_smoke_test::{PayloadClassification, PayloadLocation, SmokeTestConfig, WafSmokeTest};
+use mockito::Matcher;
+
+#[tokio::test]
+async fn test_body_only_waf_rules_are_exercised() {
+    let mut server = mockito::Server::new_async().await;
+    let _get = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .create_async()
+        .await;
+    let json = server.mock("POST", "/")
+        .match_header("content-type", "application/json")
+        .match_body(Matcher::Regex(r#"^\{"test":"#.to_string()))
+        .with_status(403)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+    let graphql = server.mock("POST", "/")
+        .match_header("content-type", "application/json")
+        .match_body(Matcher::Regex(r#"^\{"query":"query \{ search"#.to_string()))
+        .with_status(403)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        payload_locations: vec![PayloadLocation::Query, PayloadLocation::JsonBody, PayloadLocation::GraphQl],
+        ..SmokeTestConfig::default()
+    };
+    let smoke_test = WafSmokeTest::new(config).unwrap();
+
+    let result = smoke_test.run_test(&server.url()).await.unwrap();
+
+    json.assert_async().await;
+    graphql.assert_async().await;
+    for test in &result.test_results {
+        let expected = match test.location {
+            PayloadLocation::Query => PayloadClassification::Allowed,
+            _ => PayloadClassification::Blocked,
+        };
+        assert_eq!(test.classification, expected, "{:?} at {}", test.payload_type, test.location);
+    }
+
+    let query_tests = result.test_results.iter().filter(|t| t.location == PayloadLocation::Query).count();
+    let json_tests = result.test_results.iter().filter(|t| t.location == PayloadLocation::JsonBody).count();
+    // Scanner detection is User-Agent based and only runs once
+    assert_eq!(query_tests, json_tests + 1);
+}
+
+#[tokio::test]
+async fn test_header_cookie_and_path_results_are_reported_per_location() {
+    let mut server = mockito::Server::new_async().await;
+    // The catch-all expects no hits, so mockito always prefers the more specific mocks below
+    let _allowed = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let cookie = server.mock("GET", "/")
+        .match_header("cookie", Matcher::Regex("^test=".to_string()))
+        .with_status(403)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+    let path = server.mock("GET", Matcher::Regex("^/app/.+".to_string()))
+        .with_status(406)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        payload_locations: vec![PayloadLocation::XForwardedFor, PayloadLocation::Cookie],
+        ..SmokeTestConfig::default()
+    };
+    let result = WafSmokeTest::new(config).unwrap().run_test(&server.url()).await.unwrap();
+
+    cookie.assert_async().await;
+    let summaries: Vec<(PayloadLocation, f64)> = result.location_summaries
+        .iter()
+        .map(|l| (l.location, l.summary.effectiveness_percentage))
+        .collect();
+    assert_eq!(summaries, vec![(PayloadLocation::XForwardedFor, 0.0), (PayloadLocation::Cookie, 100.0)]);
+
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        payload_locations: vec![PayloadLocation::Path],
+        ..SmokeTestConfig::default()
+    };
+    let result = WafSmokeTest::new(config).unwrap().run_test(&format!("{}/app", server.url())).await.unwrap();
+
+    path.assert_async().await;
+    assert!(result.test_results.iter().all(|t| t.classification == PayloadClassification::Blocked));
+}
@@ -0,0 +1,64 @@
+use waf_detector::payload::waf_smoke_test::{PayloadClassification, SmokeTestConfig, WafSmokeTest};
+use mockito::Matcher;
+
+const HOME_PAGE: &str = "<html><head><title>Acme Store</title></head><body><h1>Welcome to Acme</h1>\
+    <p>Browse our catalogue of tools, garden furniture and seasonal offers.</p></body></html>";
+const BLOCK_PAGE: &str = "<html><head><title>Request Rejected</title></head><body>\
+    <p>The requested URL was rejected. This request was blocked by the security policy.</p>\
+    <p>Your support ID is: 1234567890</p></body></html>";
+
+#[tokio::test]
+async fn test_soft_block_pages_are_classified_as_blocked() {
+    let mut server = mockito::Server::new_async().await;
+    let _blocked = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .with_body(BLOCK_PAGE)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    // The clean baseline request (empty test parameter) gets the real page
+    let _baseline = server.mock("GET", "/")
+        .match_query(Matcher::UrlEncoded("test".to_string(), String::new()))
+        .with_status(200)
+        .with_body(HOME_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        benign_controls: false,
+        ..SmokeTestConfig::default()
+    };
+    let result = WafSmokeTest::new(config).unwrap().run_test(&server.url()).await.unwrap();
+
+    assert!(!result.test_results.is_empty());
+    for test in &result.test_results {
+        assert_eq!(test.classification, PayloadClassification::Blocked, "{:?}", test.evidence);
+    }
+    assert_eq!(result.summary.effectiveness_percentage, 100.0);
+}
+
+#[tokio::test]
+async fn test_unchanged_pages_stay_allowed() {
+    let mut server = mockito::Server::new_async().await;
+    let _page = server.mock("GET", Matcher::Any)
+        .with_status(200)
+        .with_body(HOME_PAGE)
+        .expect_at_least(0)
+        .create_async()
+        .await;
+
+    let config = SmokeTestConfig {
+        delay_between_requests_ms: 0,
+        payloads_per_category: Some(1),
+        quiet: true,
+        ..SmokeTestConfig::default()
+    };
+    let result = WafSmokeTest::new(config).unwrap().run_test(&server.url()).await.unwrap();
+
+    assert!(result.test_results.iter().all(|t| t.classification == PayloadClassification::Allowed));
+    assert_eq!(result.summary.false_positive_count, 0);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::engine;
+use waf_detector::providers::{Provider, cloudflare::CloudFlareProvider};
+use waf_detector::testing::{load_ground_truth, ValidationFramework};
+use std::io::Write;
+
+#[tokio::test]
+async fn test_validate_reports_calibration_and_weight_suggestions() {
+    let mut server = mockito::Server::new_async().await;
+    // Both targets send cf-ray, but only one is labelled CloudFlare
+    for path in ["/protected", "/origin"] {
+        server.mock("GET", path)
+            .with_status(200)
+            .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+            .with_body("<html>Welcome</html>")
+            .create_async()
+            .await;
+    }
+
+    let mut truth = tempfile::NamedTempFile::new().unwrap();
+    write!(truth, "- url: {0}/protected\n  waf: CloudFlare\n  cdn: CloudFlare\n- url: {0}/origin\n  notes: self-hosted\n", server.url()).unwrap();
+    let ground_truth = load_ground_truth(truth.path()).unwrap();
+    assert_eq!(ground_truth[0].known_waf.as_deref(), Some("CloudFlare"));
+    assert_eq!(ground_truth[1].confidence_level, 1.0);
+
+    let engine = engine([Provider::CloudFlare(CloudFlareProvider::new())]);
+    let report = ValidationFramework::with_ground_truth(engine.clone(), ground_truth)
+        .validate_all()
+        .await
+        .unwrap();
+
+    assert_eq!(report.total_tests, 2);
+    let cloudflare = report.calibration.iter().find(|c| c.provider == "CloudFlare").unwrap();
+    assert_eq!(cloudflare.labelled, 1);
+    assert_eq!((cloudflare.true_positives, cloudflare.false_positives, cloudflare.false_negatives), (1, 1, 0));
+    assert_eq!(cloudflare.precision, 0.5);
+    assert_eq!(cloudflare.recall, 1.0);
+    assert!(cloudflare.brier_score > 0.0);
+
+    let cf_ray = report.weight_suggestions.iter().find(|s| s.signature == "cf-ray-header").unwrap();
+    assert_eq!((cf_ray.fired, cf_ray.correct), (2, 1));
+    assert_eq!(cf_ray.suggested_reliability, 0.5);
+    assert!(cf_ray.current_reliability > cf_ray.suggested_reliability);
+
+    let weights = report.adjusted_weights(&engine.registry().advanced_scoring());
+    assert_eq!(weights["cf-ray-header"].reliability, 0.5);
+}
+
+#[test]
+fn test_empty_ground_truth_is_rejected() {
+    let truth = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(truth.path(), "[]").unwrap();
+    assert!(load_ground_truth(truth.path()).is_err());
+}
@@ -0,0 +1,241 @@
+use waf_detector::engine::DetectionEngine;
+use waf_detector::providers::default_providers;
+use waf_detector::registry::ProviderRegistry;
+use waf_detector::web::WebServer;
+use waf_detector::DetectionOptions;
+
+/// Serve the web app on an ephemeral port and return its base URL
+async fn serve() -> String {
+    let registry = ProviderRegistry::new();
+    for provider in default_providers() {
+        registry.register_provider(provider).unwrap();
+    }
+    let engine = DetectionEngine::new(registry).with_options(DetectionOptions { dns: false, ..DetectionOptions::default() });
+    let app = WebServer::new(engine).router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_batch_scan_reports_each_url() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let base = serve().await;
+    let client = reqwest::Client::new();
+
+    let urls = [format!("{}/a", target.url()), "http://127.0.0.1:1/".to_string(), format!("{}/b", target.url())];
+    let response = client.post(format!("{}/api/batch-scan", base)).json(&serde_json::json!({ "urls": urls })).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+
+    let results = body["results"].as_array().unwrap();
+    let outcome: Vec<(&str, bool)> = results.iter().map(|r| (r["url"].as_str().unwrap(), r["success"].as_bool().unwrap())).collect();
+    assert_eq!(outcome, vec![(urls[0].as_str(), true), (urls[1].as_str(), false), (urls[2].as_str(), true)]);
+    assert_eq!(results[0]["result"]["detected_waf"]["name"], "CloudFlare");
+    assert!(results[1]["error"].as_str().unwrap().contains("127.0.0.1:1"));
+
+    let response = client.post(format!("{}/api/batch-scan", base)).json(&serde_json::json!({ "urls": [] })).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_providers_come_from_the_registry_and_can_be_disabled() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let base = serve().await;
+    let client = reqwest::Client::new();
+
+    let body: serde_json::Value = client.get(format!("{}/api/providers", base)).send().await.unwrap().json().await.unwrap();
+    let names: Vec<&str> = body["providers"].as_array().unwrap().iter().map(|p| p["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"Fastly") && names.contains(&"Vercel"), "{:?}", names);
+
+    let response = client.post(format!("{}/api/providers/cloudflare", base)).json(&serde_json::json!({ "enabled": false })).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["provider"]["name"], "CloudFlare");
+    assert_eq!(body["provider"]["enabled"], false);
+
+    let body: serde_json::Value = client.post(format!("{}/api/scan", base)).json(&serde_json::json!({ "url": target.url() })).send().await.unwrap().json().await.unwrap();
+    assert!(body["result"]["detected_waf"].is_null());
+
+    let response = client.post(format!("{}/api/providers/nope", base)).json(&serde_json::json!({ "enabled": false })).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_dashboard_assets_are_embedded() {
+    let base = serve().await;
+    let client = reqwest::Client::new();
+
+    let page = client.get(format!("{}/dashboard", base)).send().await.unwrap().text().await.unwrap();
+    assert!(page.contains(r#"<script src="/static/dashboard.js"></script>"#));
+
+    let response = client.get(format!("{}/static/dashboard.js", base)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "text/javascript; charset=utf-8");
+    assert!(response.text().await.unwrap().contains("function loadHistory"));
+
+    let response = client.get(format!("{}/static/missing.css", base)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_history_pages_are_rendered_on_the_server() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let base = serve().await;
+    let client = reqwest::Client::new();
+    client.post(format!("{}/api/scan", base)).json(&serde_json::json!({ "url": target.url() })).send().await.unwrap();
+
+    let page = client.get(format!("{}/history", base)).send().await.unwrap().text().await.unwrap();
+    assert!(page.contains(r#"<a href="/history/1">"#), "{}", page);
+    assert!(page.contains("1 scan(s), page 1 of 1"));
+
+    let page = client.get(format!("{}/history/1", base)).send().await.unwrap().text().await.unwrap();
+    assert!(page.contains("<th>WAF</th><td>CloudFlare ("), "{}", page);
+    assert!(page.contains("<h3>Evidence</h3>") && page.contains("<td>header</td>"));
+
+    let response = client.get(format!("{}/history/99", base)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_projects_group_scans() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let base = serve().await;
+    let client = reqwest::Client::new();
+    let project = serde_json::json!({ "name": "Acme Corp", "targets": [target.url()], "options": { "dns": false } });
+
+    let response = client.post(format!("{}/api/projects", base)).json(&project).send().await.unwrap();
+    assert_eq!(response.status(), 201);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["project"]["id"], "acme-corp");
+    let response = client.post(format!("{}/api/projects", base)).json(&project).send().await.unwrap();
+    assert_eq!(response.status(), 409);
+
+    let body: serde_json::Value = client.post(format!("{}/api/projects/acme-corp/scan", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["results"][0]["result"]["detected_waf"]["name"], "CloudFlare");
+    client.post(format!("{}/api/scan", base)).json(&serde_json::json!({ "url": target.url(), "project": "acme-corp" })).send().await.unwrap();
+    client.post(format!("{}/api/scan", base)).json(&serde_json::json!({ "url": target.url() })).send().await.unwrap();
+    let response = client.post(format!("{}/api/scan", base)).json(&serde_json::json!({ "url": target.url(), "project": "nope" })).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    let body: serde_json::Value = client.get(format!("{}/api/projects/acme-corp/results", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["history"]["total"], 2);
+    assert!(body["history"]["entries"].as_array().unwrap().iter().all(|e| e["project"] == "acme-corp"));
+    let body: serde_json::Value = client.get(format!("{}/api/results", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["history"]["total"], 3);
+
+    assert_eq!(client.delete(format!("{}/api/projects/acme-corp", base)).send().await.unwrap().status(), 200);
+    assert_eq!(client.get(format!("{}/api/projects/acme-corp/results", base)).send().await.unwrap().status(), 404);
+}
+
+#[cfg(feature = "grpc")]
+#[tokio::test]
+async fn test_grpc_detect() {
+    use waf_detector::web::grpc::{self, proto};
+
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let base = serve().await;
+    let client = reqwest::Client::builder().http2_prior_knowledge().build().unwrap();
+    let call = |url: String| {
+        let mut request = proto::Message::default();
+        request.string(1, &url);
+        client
+            .post(format!("{}{}/Detect", base, grpc::SERVICE))
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .body(grpc::frame(&request.into_bytes()))
+            .send()
+    };
+
+    let response = call(target.url()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "application/grpc");
+    let body = response.bytes().await.unwrap();
+    assert_eq!(u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize, body.len() - 5);
+    let fields = proto::fields(&body[5..]).unwrap();
+    assert!(fields.contains(&(1, proto::Value::Bytes(target.url().as_bytes()))));
+    let waf = fields.iter().find_map(|(field, value)| match value {
+        proto::Value::Bytes(bytes) if *field == 2 => Some(*bytes),
+        _ => None,
+    });
+    assert!(proto::fields(waf.unwrap()).unwrap().contains(&(1, proto::Value::Bytes(b"CloudFlare"))));
+
+    let response = call("not a url".to_string()).await.unwrap();
+    assert_eq!(response.headers()["grpc-status"], "3");
+}
+
+#[tokio::test]
+async fn test_api_only_passive_and_detect() {
+    let mut target = mockito::Server::new_async().await;
+    let _page = target.mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("server", "cloudflare")
+        .with_header("cf-ray", "8a1b2c3d4e5f6a7b-AMS")
+        .expect_at_least(0)
+        .create_async()
+        .await;
+    let registry = ProviderRegistry::new();
+    for provider in default_providers() {
+        registry.register_provider(provider).unwrap();
+    }
+    let engine = DetectionEngine::new(registry).with_options(DetectionOptions { dns: false, ..DetectionOptions::default() });
+    let app = WebServer::new(engine).api_router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = reqwest::Client::new();
+
+    let raw = "HTTP/1.1 403 Forbidden\r\nServer: cloudflare\r\nCF-RAY: 8a1b2c3d4e5f6a7b-AMS\r\n\r\nAttention Required!";
+    let body: serde_json::Value = client
+        .post(format!("{}/api/passive", base))
+        .json(&serde_json::json!({ "url": "https://shop.example.com/", "response_base64": waf_detector::utils::base64_encode(raw.as_bytes()) }))
+        .send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["result"]["detected_waf"]["name"], "CloudFlare");
+
+    let response = client.post(format!("{}/api/passive", base)).json(&serde_json::json!({ "url": "https://shop.example.com/", "response": "garbage" })).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+
+    let body: serde_json::Value = client.post(format!("{}/api/detect", base)).json(&serde_json::json!({ "host": target.url() })).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["result"]["detected_waf"]["name"], "CloudFlare");
+
+    // No dashboard, and a rebinding host name is turned away
+    assert_eq!(client.get(format!("{}/dashboard", base)).send().await.unwrap().status(), 404);
+    let response = client.get(format!("{}/api/status", base)).header("host", "attacker.example").send().await.unwrap();
+    assert_eq!(response.status(), 403);
+}
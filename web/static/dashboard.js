@@ -0,0 +1,768 @@
+let allResults = [];
+
+// HTML escaping utility to prevent XSS
+function escapeHtml(str) {
+    if (typeof str !== 'string') return str;
+    return str.replace(/[&<>\"'`=\\/]/g, function(s) {
+        return ({
+            '&': '&amp;',
+            '<': '&lt;',
+            '>': '&gt;',
+            '"': '&quot;',
+            "'": '&#39;',
+            '`': '&#96;',
+            '=': '&#61;',
+            '/': '&#47;'
+        })[s];
+    });
+}
+
+// Helper function to display status codes safely
+function displayStatusCode(val) {
+    if (val === undefined || val === null || isNaN(val)) return 'N/A';
+    return String(val);
+}
+
+// Single URL scan
+document.getElementById('singleScanForm').addEventListener('submit', async (e) => {
+    e.preventDefault();
+    const url = document.getElementById('singleUrl').value;
+    await scanSingleUrl(url);
+});
+
+// Batch URL scan
+document.getElementById('batchScanForm').addEventListener('submit', async (e) => {
+    e.preventDefault();
+    const urlsText = document.getElementById('batchUrls').value;
+    const urls = urlsText.split('\n').filter(url => url.trim()).map(url => url.trim());
+    await scanBatchUrls(urls);
+});
+
+// WAF Smoke Testing
+document.getElementById('smokeTestForm').addEventListener('submit', async (e) => {
+    e.preventDefault();
+    const url = document.getElementById('smokeTestUrl').value;
+    await runSmokeTest(url);
+});
+
+async function scanSingleUrl(url) {
+    const btn = document.querySelector('#singleScanForm button');
+    const icon = document.getElementById('singleScanIcon');
+    const text = document.getElementById('singleScanText');
+    
+    setButtonLoading(btn, icon, text, true);
+    
+    try {
+        const response = await fetch('/api/scan', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ url, debug: true })
+        });
+        
+        const data = await response.json();
+        
+        if (data.success && data.result) {
+            allResults.unshift(data.result);
+            displayResults();
+            showSuccessMessage(`Successfully scanned ${url}`);
+        } else {
+            showErrorMessage(data.error || 'Scan failed');
+        }
+    } catch (error) {
+        showErrorMessage(`Error: ${error.message}`);
+    } finally {
+        setButtonLoading(btn, icon, text, false);
+    }
+}
+
+async function scanBatchUrls(urls) {
+    const btn = document.querySelector('#batchScanForm button');
+    const icon = document.getElementById('batchScanIcon');
+    const text = document.getElementById('batchScanText');
+    
+    setButtonLoading(btn, icon, text, true);
+    
+    try {
+        const response = await fetch('/api/batch-scan', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ urls, debug: true })
+        });
+        
+        const data = await response.json();
+        
+        if (data.success && data.results) {
+            const scanned = data.results.filter(entry => entry.success).map(entry => entry.result);
+            const failed = data.results.filter(entry => !entry.success);
+            allResults.unshift(...scanned.reverse());
+            displayResults();
+            if (failed.length === 0) {
+                showSuccessMessage(`Successfully scanned ${urls.length} URLs`);
+            } else {
+                showErrorMessage(`Scanned ${scanned.length} of ${urls.length} URLs; failed: ` +
+                    failed.map(entry => `${entry.url} (${entry.error})`).join(', '));
+            }
+        } else {
+            showErrorMessage(data.error || 'Batch scan failed');
+        }
+    } catch (error) {
+        showErrorMessage(`Error: ${error.message}`);
+    } finally {
+        setButtonLoading(btn, icon, text, false);
+    }
+}
+
+async function runSmokeTest(url) {
+    const btn = document.querySelector('#smokeTestForm button');
+    const icon = document.getElementById('smokeTestIcon');
+    const text = document.getElementById('smokeTestText');
+    
+    setButtonLoading(btn, icon, text, true, 'Testing...');
+    
+    try {
+        const response = await fetch('/api/smoke-test', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ url })
+        });
+        
+        const data = await response.json();
+        
+        if (data.success && data.result) {
+            // Mark this as a smoke test result explicitly
+            data.result.is_smoke_test = true;
+            allResults.unshift(data.result);
+            displayResults();
+            showSuccessMessage(`Smoke test completed for ${url} - ${data.result.test_results ? data.result.test_results.length : 0} payloads tested`);
+        } else {
+            showErrorMessage(data.error || 'Smoke test failed');
+        }
+    } catch (error) {
+        showErrorMessage(`Error: ${error.message}`);
+    } finally {
+        setButtonLoading(btn, icon, text, false, 'Run Smoke Test');
+    }
+}
+
+function setButtonLoading(btn, icon, text, loading, customText = null) {
+    if (loading) {
+        btn.disabled = true;
+        icon.innerHTML = '<div class="spinner"></div>';
+        text.textContent = customText || 'Scanning...';
+    } else {
+        btn.disabled = false;
+        const buttonId = btn.closest('form').id;
+        if (buttonId === 'batchScanForm') {
+            icon.innerHTML = '⚡';
+            text.textContent = 'Scan Batch';
+        } else if (buttonId === 'smokeTestForm') {
+            icon.innerHTML = '🛡️';
+            text.textContent = customText || 'Run Smoke Test';
+        } else {
+            icon.innerHTML = '🚀';
+            text.textContent = 'Scan URL';
+        }
+    }
+}
+
+function displayResults() {
+    const section = document.getElementById('resultsSection');
+    const container = document.getElementById('resultsContainer');
+    const count = document.getElementById('resultsCount');
+    
+    section.style.display = 'block';
+    count.textContent = `${allResults.length} result${allResults.length !== 1 ? 's' : ''}`;
+    
+    container.innerHTML = allResults.map(result => createResultCard(result)).join('');
+    loadHistory(1);
+}
+
+// Server-side scan history with per-host deltas
+let historyPage = 1;
+
+async function loadHistory(page) {
+    const search = document.getElementById('historyHost').value.trim();
+    const provider = document.getElementById('historyProvider').value.trim();
+    const detected = document.getElementById('historyDetected').value;
+    const sort = document.getElementById('historySort').value;
+    const params = new URLSearchParams({ page, per_page: 20, sort, order: sort === 'host' ? 'asc' : 'desc' });
+    if (search) params.set('search', search);
+    if (provider) params.set('provider', provider);
+    if (detected) params.set('detected', detected);
+    try {
+        const data = await (await fetch(`/api/results?${params}`)).json();
+        if (!data.success) return;
+        const history = data.history;
+        historyPage = history.page;
+        const pages = Math.max(1, Math.ceil(history.total / history.per_page));
+        document.getElementById('historyCount').textContent = `${history.total} scan${history.total !== 1 ? 's' : ''}`;
+        document.getElementById('historyPage').textContent = `Page ${history.page} of ${pages}`;
+        document.getElementById('historyPrev').disabled = history.page <= 1;
+        document.getElementById('historyNext').disabled = history.page >= pages;
+        document.getElementById('historyRows').innerHTML = history.entries.map(entry => {
+            const waf = entry.error ? '⚠️ scan failed' : escapeHtml(entry.waf || 'None');
+            const effectiveness = entry.effectiveness != null ? ` (${entry.effectiveness.toFixed(1)}%)` : '';
+            const changes = entry.changes && entry.changes.length
+                ? `<strong>${entry.changes.map(escapeHtml).join('<br>')}</strong>` : '—';
+            return `<tr>
+                <td>${escapeHtml(new Date(entry.scanned_at).toLocaleString())}</td>
+                <td><a href="/history/${entry.id}">${escapeHtml(entry.host)}</a></td>
+                <td>${escapeHtml(entry.kind)}</td>
+                <td>${waf}${effectiveness}</td>
+                <td>${escapeHtml(entry.cdn || 'None')}</td>
+                <td>${changes}</td>
+            </tr>`;
+        }).join('');
+    } catch (error) {
+        console.error('Failed to load scan history:', error);
+    }
+}
+
+document.getElementById('historyForm').addEventListener('submit', e => {
+    e.preventDefault();
+    loadHistory(1);
+});
+document.getElementById('historyPrev').addEventListener('click', () => loadHistory(historyPage - 1));
+document.getElementById('historyNext').addEventListener('click', () => loadHistory(historyPage + 1));
+window.addEventListener('load', () => loadHistory(1));
+
+function createResultCard(result) {
+    // Ensure result is defined
+    if (!result) {
+        console.error("Result is undefined");
+        return '<div class="result-card">Error: Invalid result data</div>';
+    }
+    
+    // Check if this is a smoke test result (has test_results property AND other smoke test indicators)
+    const isSmokeTest = (result.test_results !== undefined && Array.isArray(result.test_results) && result.test_results.length > 0) || 
+                       result.is_smoke_test === true ||
+                       (result.summary && result.summary.effectiveness_percentage !== undefined);
+    
+    console.log("Creating result card for:", result);
+    console.log("isSmokeTest:", isSmokeTest, "is_smoke_test flag:", result.is_smoke_test, "detected_waf:", result.detected_waf, "detected_cdn:", result.detected_cdn);
+    console.log("test_results array:", Array.isArray(result.test_results), "length:", result.test_results ? result.test_results.length : 0);
+    // Check if this is a combined result (has detection_result property)
+    const isCombined = result.detection_result !== undefined;
+    const detectionData = isCombined && result.detection_result ? result.detection_result : result;
+    
+    // Initialize variables safely
+    let wafDetected = false;
+    let cdnDetected = false;
+    let wafName = 'Not Detected';
+    let cdnName = 'Not Detected';
+    let wafConfidence = 0;
+    let cdnConfidence = 0;
+    
+    // For smoke test results, use the detected_waf property directly
+    if (isSmokeTest) {
+        // Handle detected_waf safely (can be null, string, or object)
+        if (result.detected_waf && result.detected_waf !== null && result.detected_waf !== 'Unknown') {
+            wafDetected = true;
+            if (typeof result.detected_waf === 'string') {
+                wafName = result.detected_waf;
+            } else if (typeof result.detected_waf === 'object' && result.detected_waf.name) {
+                wafName = result.detected_waf.name;
+                wafConfidence = result.detected_waf.confidence ? (result.detected_waf.confidence * 100).toFixed(1) : 95;
+            } else {
+                wafName = 'Detected WAF';
+            }
+            if (!wafConfidence) wafConfidence = 95; // Default high confidence for smoke test detections
+        }
+        
+        // Handle detected_cdn safely (can be null, string, or object)
+        if (result.detected_cdn && result.detected_cdn !== null && result.detected_cdn !== 'Unknown') {
+            cdnDetected = true;
+            if (typeof result.detected_cdn === 'string') {
+                cdnName = result.detected_cdn;
+            } else if (typeof result.detected_cdn === 'object' && result.detected_cdn.name) {
+                cdnName = result.detected_cdn.name;
+                cdnConfidence = result.detected_cdn.confidence ? (result.detected_cdn.confidence * 100).toFixed(1) : 95;
+            } else {
+                cdnName = 'Detected CDN';
+            }
+            if (!cdnConfidence) cdnConfidence = 95; // Default high confidence
+        }
+    } 
+    // For regular detection results or combined results
+    else {
+        // Safely check if detected_waf exists and is not null
+        if (detectionData && detectionData.detected_waf && detectionData.detected_waf !== null) {
+            if (typeof detectionData.detected_waf === 'object' && detectionData.detected_waf.name) {
+                wafDetected = true;
+                wafName = detectionData.detected_waf.name;
+                wafConfidence = detectionData.detected_waf.confidence ? (detectionData.detected_waf.confidence * 100).toFixed(1) : 0;
+            } else if (typeof detectionData.detected_waf === 'string') {
+                wafDetected = true;
+                wafName = detectionData.detected_waf;
+                wafConfidence = 95; // Default confidence for string detection
+            }
+        }
+        
+        // Safely check if detected_cdn exists and is not null
+        if (detectionData && detectionData.detected_cdn && detectionData.detected_cdn !== null) {
+            if (typeof detectionData.detected_cdn === 'object' && detectionData.detected_cdn.name) {
+                cdnDetected = true;
+                cdnName = detectionData.detected_cdn.name;
+                cdnConfidence = detectionData.detected_cdn.confidence ? (detectionData.detected_cdn.confidence * 100).toFixed(1) : 0;
+            } else if (typeof detectionData.detected_cdn === 'string') {
+                cdnDetected = true;
+                cdnName = detectionData.detected_cdn;
+                cdnConfidence = 95; // Default confidence for string detection
+            }
+        }
+    }
+    
+    return `
+        <div class="result-card">
+            <div class="result-header">
+                <div class="result-url">${escapeHtml(isSmokeTest && result.url ? result.url : 
+                                       isCombined && result.url ? result.url : 
+                                       detectionData && detectionData.url ? detectionData.url : 
+                                       "Unknown URL")}</div>
+                <div class="result-time">${isSmokeTest && result.total_time_ms ? result.total_time_ms : 
+                                        isCombined && result.total_time_ms ? result.total_time_ms : 
+                                        detectionData && detectionData.detection_time_ms ? detectionData.detection_time_ms : 
+                                        "0"}ms</div>
+            </div>
+            ${isSmokeTest ? `
+                <div style="margin-bottom: 1rem; padding: 1rem; background: #f0f9ff; border-radius: 8px; border-left: 4px solid #0ea5e9;">
+                    <h4 style="margin: 0 0 0.5rem 0; color: #0369a1;">🛡️ WAF Smoke Test Results</h4>
+                    <p style="font-size: 0.85rem; color: #4b5563; margin-bottom: 0.5rem;">Note: Scanner Detection tests use realistic User-Agent headers instead of query parameters to test if the WAF blocks security scanning tools.</p>
+                    <div style="display: grid; grid-template-columns: 1fr 1fr 1fr 1fr; gap: 1rem; font-size: 0.9rem;">
+                        <div><strong>Effectiveness:</strong> ${result.summary && result.summary.effectiveness_percentage !== undefined ? result.summary.effectiveness_percentage.toFixed(1) : '0.0'}%</div>
+                        <div><strong>Blocked:</strong> ${result.summary && result.summary.blocked_count !== undefined ? result.summary.blocked_count : 0}</div>
+                        <div><strong>Allowed:</strong> ${result.summary && result.summary.allowed_count !== undefined ? result.summary.allowed_count : 0}</div>
+                        <div><strong>Errors:</strong> ${result.summary && result.summary.error_count !== undefined ? result.summary.error_count : 0}</div>
+                    </div>
+                    ${result.summary && Array.isArray(result.summary.category_coverage) && result.summary.category_coverage.length > 0 ? `
+                    <div style="margin-top: 1rem;">
+                        <table class="payload-table">
+                            <thead>
+                                <tr>
+                                    <th>OWASP Category</th>
+                                    <th>Attack Category</th>
+                                    <th>Tested</th>
+                                    <th>Blocked</th>
+                                    <th>Bypassed Examples</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                ${result.summary.category_coverage.map((coverage, index) => `<tr style="background: ${index % 2 === 0 ? '#ffffff' : '#f8fafc'};">
+                                        <td>${escapeHtml(coverage.owasp_category)}</td>
+                                        <td>${escapeHtml(coverage.category)}</td>
+                                        <td>${escapeHtml(String(coverage.tested))}</td>
+                                        <td style="color: ${coverage.blocked_percentage >= 100 ? '#22c55e' : coverage.blocked_percentage > 0 ? '#f59e0b' : '#ef4444'}; font-weight: 600;">${coverage.blocked_percentage.toFixed(1)}%</td>
+                                        <td style="font-family: monospace;">${(coverage.bypassed_examples || []).map(escapeHtml).join('<br>')}</td>
+                                    </tr>`).join('')}
+                            </tbody>
+                        </table>
+                    </div>
+                    ` : ''}
+                    <div style="margin-top: 1rem;">
+                        <table class="payload-table">
+                            <thead>
+                                <tr>
+                                    <th>Category</th>
+                                    <th>Payload</th>
+                                    <th>Status</th>
+                                    <th>HTTP</th>
+                                    <th>Time (ms)</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                ${result.test_results && Array.isArray(result.test_results) ? result.test_results.map((test, index) => {
+                                    const statusColor = test.classification === 'Blocked' || test.classification === 'Challenge' ? '#22c55e' : 
+                                          test.classification === 'Allowed' ? '#ef4444' : '#f59e0b';
+                                    const statusIcon = test.classification === 'Blocked' || test.classification === 'Challenge' ? '🛡️' : 
+                                          test.classification === 'Allowed' ? '⚠️' : '❓';
+                                    const rowColor = index % 2 === 0 ? '#ffffff' : '#f8fafc';
+                                    console.log('Test row data:', test);
+                                    // Add special tooltip for scanner detection tests
+                                    const isScanner = test.category === 'ScannerDetection';
+                                    const tooltipAttr = isScanner ? 
+                                        `title="Testing if WAF blocks ${escapeHtml(test.payload)} scanner signature via User-Agent header"` : '';
+                                    
+                                    return `<tr style="background: ${rowColor};" ${tooltipAttr}>
+                                        <td>${escapeHtml(test.category)}${isScanner ? ' 🔍' : ''}</td>
+                                        <td style="font-family: monospace;">${escapeHtml((test.payload !== undefined && test.payload !== null && test.payload !== '') ? test.payload : '(empty)')}</td>
+                                        <td><span style="background: ${statusColor}; color: white; padding: 0.125rem 0.5rem; border-radius: 12px; font-size: 0.75rem; font-weight: 600; display: inline-block;">${statusIcon} ${escapeHtml(test.classification)}</span></td>
+                                        <td>${escapeHtml(displayStatusCode(parseInt(test.response_status) || 0))}</td>
+                                        <td>${escapeHtml(String(test.response_time_ms))}</td>
+                                    </tr>`;
+                                }).join('') : ''}
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+            ` : isCombined ? `
+                <div style="margin-bottom: 1rem; padding: 1rem; background: #f0f9ff; border-radius: 8px; border-left: 4px solid #0ea5e9;">
+                    <h4 style="margin: 0 0 0.5rem 0; color: #0369a1;">🛡️ WAF Effectiveness Testing</h4>
+                    <div style="display: grid; grid-template-columns: 1fr 1fr 1fr; gap: 1rem; font-size: 0.9rem;">
+                        <div><strong>Effectiveness:</strong> ${result.effectiveness_result ? result.effectiveness_result.effectiveness_score.toFixed(1) : 'N/A'}%</div>
+                        <div><strong>Tests:</strong> ${result.effectiveness_result ? result.effectiveness_result.total_tests : 'N/A'}</div>
+                        <div><strong>Blocked:</strong> ${result.effectiveness_result ? result.effectiveness_result.blocked_tests : 'N/A'}</div>
+                    </div>
+                </div>
+            ` : ''}
+            
+            ${!isSmokeTest && !result.is_smoke_test ? `
+            <div class="detection-info">
+                <div class="detection-item ${wafDetected ? 'detected' : 'not-detected'}">
+                    <div class="detection-label">
+                        <span class="status-indicator ${wafDetected ? 'status-detected' : 'status-not-detected'}"></span>
+                        WAF Detection
+                    </div>
+                    <div class="detection-value">${wafName}</div>
+                    ${wafDetected ? `
+                        <div class="confidence-bar">
+                            <div class="confidence-fill" style="width: ${wafConfidence}%"></div>
+                        </div>
+                        <div style="font-size: 0.875rem; margin-top: 0.25rem;">${wafConfidence}% confidence</div>
+                    ` : ''}
+                </div>
+                
+                <div class="detection-item ${cdnDetected ? 'detected' : 'not-detected'}">
+                    <div class="detection-label">
+                        <span class="status-indicator ${cdnDetected ? 'status-detected' : 'status-not-detected'}"></span>
+                        CDN Detection
+                    </div>
+                    <div class="detection-value">${cdnName}</div>
+                    ${cdnDetected ? `
+                        <div class="confidence-bar">
+                            <div class="confidence-fill" style="width: ${cdnConfidence}%"></div>
+                        </div>
+                        <div style="font-size: 0.875rem; margin-top: 0.25rem;">${cdnConfidence}% confidence</div>
+                    ` : ''}
+                </div>
+                
+                ${detectionData && detectionData.detected_bot_manager && detectionData.detected_bot_manager.name ? `
+                <div class="detection-item detected">
+                    <div class="detection-label">
+                        <span class="status-indicator status-detected"></span>
+                        Bot Management
+                    </div>
+                    <div class="detection-value">${escapeHtml(detectionData.detected_bot_manager.name)}</div>
+                    <div class="confidence-bar">
+                        <div class="confidence-fill" style="width: ${(detectionData.detected_bot_manager.confidence * 100).toFixed(1)}%"></div>
+                    </div>
+                    <div style="font-size: 0.875rem; margin-top: 0.25rem;">${(detectionData.detected_bot_manager.confidence * 100).toFixed(1)}% confidence</div>
+                </div>
+                ` : ''}
+            </div>
+            ` : ''}
+            
+            ${!isSmokeTest && !result.is_smoke_test && detectionData && detectionData.evidence && Array.isArray(detectionData.evidence) && detectionData.evidence.length > 0 ? `
+                <div class="evidence-section">
+                    <button class="evidence-toggle" onclick="toggleEvidence(this)">
+                        View Evidence (${detectionData.evidence.length} items)
+                    </button>
+                    <div class="evidence-list">
+                        ${detectionData.evidence.map(ev => {
+                            if (!ev) return '';
+                            
+                            const isTiming = ev.method_type === 'Timing';
+                            const isDns = typeof ev.method_type === 'object' && ev.method_type && ev.method_type.DNS;
+                            const isPayload = ev.method_type === 'Payload';
+                            
+                            const timingIcon = isTiming ? '⏱️' : '';
+                            const dnsIcon = isDns ? '🌐' : '';
+                            const payloadIcon = isPayload ? '🛡️' : '';
+                            const icon = timingIcon || dnsIcon || payloadIcon;
+                            
+                            const timingClass = isTiming ? 'evidence-timing' : '';
+                            const dnsClass = isDns ? 'evidence-dns' : '';
+                            const payloadClass = isPayload ? 'evidence-payload' : '';
+                            const cssClass = timingClass || dnsClass || payloadClass;
+                            
+                            return `
+                                <div class="evidence-item ${cssClass}">
+                                    <strong>${icon} ${escapeHtml(ev.description || 'Unknown evidence')}</strong> 
+                                    <span class="confidence-badge confidence-${getConfidenceLevel(ev.confidence || 0)}">${((ev.confidence || 0) * 100).toFixed(1)}%</span>
+                                    <br><em>Method:</em> ${isDns ? 'DNS (CNAME)' : isPayload ? 'Payload (WAF Blocking)' : escapeHtml(ev.method_type || 'Unknown')}
+                                    ${isTiming ? `<br><em>Timing Data:</em> ${escapeHtml(ev.raw_data || 'N/A')}` : 
+                                      isDns ? `<br><em>DNS Record:</em> ${escapeHtml(ev.raw_data || 'N/A')}` :
+                                      isPayload ? `<br><em>Blocked Payloads:</em> ${escapeHtml(ev.raw_data || 'N/A')}` :
+                                      `<br><em>Data:</em> ${escapeHtml(ev.raw_data || 'N/A')}`}
+                                    ${isTiming ? `<br><span class="timing-info">⚡ WAF processing delay detected</span>` : ''}
+                                    ${isDns ? `<br><span class="dns-info">🔒 Infrastructure-level detection</span>` : ''}
+                                    ${isPayload ? `<br><span class="payload-info">🚫 Malicious payload blocked by WAF</span>` : ''}
+                                </div>
+                            `;
+                        }).join('')}
+                    </div>
+                </div>
+            ` : ''}
+            ${!isSmokeTest && !result.is_smoke_test && detectionData && detectionData.confidence_details && Object.keys(detectionData.confidence_details).length > 0 ? `
+                <div class="evidence-section">
+                    <button class="evidence-toggle" onclick="toggleEvidence(this)">
+                        View Confidence Details (${Object.keys(detectionData.confidence_details).length} providers)
+                    </button>
+                    <div class="evidence-list">
+                        ${Object.entries(detectionData.confidence_details)
+                            .sort((a, b) => (b[1].score || 0) - (a[1].score || 0))
+                            .map(([provider, detail]) => {
+                            const breakdown = Object.entries(detail.evidence_breakdown || {})
+                                .filter(([, score]) => score > 0)
+                                .sort((a, b) => b[1] - a[1])
+                                .map(([category, score]) => `${escapeHtml(category)} ${score.toFixed(3)}`)
+                                .join(', ');
+                            return `
+                                <div class="evidence-item">
+                                    <strong>${escapeHtml(provider)}</strong>
+                                    <span class="confidence-badge confidence-${getConfidenceLevel(detail.score || 0)}">${((detail.score || 0) * 100).toFixed(1)}%</span>
+                                    <br><em>Level:</em> ${escapeHtml(detail.level || 'Unknown')}
+                                    ${breakdown ? `<br><em>Breakdown:</em> ${breakdown}` : ''}
+                                    <pre style="white-space: pre-wrap; margin: 0.5rem 0 0 0; font-size: 0.8rem;">${escapeHtml(detail.explanation || '')}</pre>
+                                </div>
+                            `;
+                        }).join('')}
+                    </div>
+                </div>
+            ` : ''}
+            ${isCombined && result && result.recommendations && Array.isArray(result.recommendations) && result.recommendations.length > 0 ? `
+                <div class="evidence-section">
+                    <button class="evidence-toggle" onclick="toggleEvidence(this)">
+                        View Recommendations (${result.recommendations.length} items)
+                    </button>
+                    <div class="evidence-list">
+                        ${result.recommendations.map(rec => `
+                            <div class="evidence-item">
+                                ${escapeHtml(rec || 'No recommendation details available')}
+                            </div>
+                        `).join('')}
+                    </div>
+                </div>
+            ` : ''}
+        </div>
+    `;
+}
+
+function toggleEvidence(button) {
+    const evidenceList = button.nextElementSibling;
+    const isVisible = evidenceList.style.display === 'block';
+    evidenceList.style.display = isVisible ? 'none' : 'block';
+    button.textContent = isVisible ? 
+        button.textContent.replace('Hide Evidence', 'View Evidence') :
+        button.textContent.replace('View Evidence', 'Hide Evidence');
+}
+
+function getConfidenceLevel(confidence) {
+    if (confidence >= 0.8) return 'high';
+    if (confidence >= 0.6) return 'medium';
+    return 'low';
+}
+
+// Quick action functions
+
+function clearResults() {
+    allResults = [];
+    document.getElementById('resultsSection').style.display = 'none';
+    showSuccessMessage('Results cleared');
+}
+
+function showErrorMessage(message) {
+    showMessage(message, 'error');
+}
+
+function showSuccessMessage(message) {
+    showMessage(message, 'success');
+}
+
+function showMessage(message, type) {
+    const existing = document.querySelector('.error-message, .success-message');
+    if (existing) existing.remove();
+    
+    const div = document.createElement('div');
+    div.className = `${type}-message`;
+    div.textContent = message;
+    
+    document.querySelector('.container').insertBefore(div, document.querySelector('.dashboard-grid'));
+    
+    setTimeout(() => div.remove(), 5000);
+}
+
+function exportResults() {
+    if (allResults.length === 0) {
+        showErrorMessage('No results to export');
+        return;
+    }
+    
+    const dataStr = JSON.stringify(allResults, null, 2);
+    const dataUri = 'data:application/json;charset=utf-8,'+ encodeURIComponent(dataStr);
+    
+    const exportFileDefaultName = `waf-detector-results-${new Date().toISOString().split('T')[0]}.json`;
+    
+    const linkElement = document.createElement('a');
+    linkElement.setAttribute('href', dataUri);
+    linkElement.setAttribute('download', exportFileDefaultName);
+    linkElement.click();
+    
+    showSuccessMessage('Results exported successfully');
+}
+
+// Page load handler
+window.addEventListener('load', () => {
+    // Page loaded - ready for user input
+});
+
+// Fix for smoke test results display
+function fixSmokeTestDisplay() {
+    // Override the runSmokeTest function
+    if (typeof window.runSmokeTest === 'function') {
+        window.originalRunSmokeTest = window.runSmokeTest;
+    }
+    
+    window.runSmokeTest = async function(url) {
+        const btn = document.querySelector('#smokeTestForm button');
+        const icon = document.getElementById('smokeTestIcon');
+        const text = document.getElementById('smokeTestText');
+        const panel = document.getElementById('smokeTestProgress');
+        const progressText = document.getElementById('smokeTestProgressText');
+        const rows = document.getElementById('smokeTestPayloads');
+        
+        setButtonLoading(btn, icon, text, true, 'Testing...');
+        rows.innerHTML = '';
+        progressText.textContent = 'Queueing smoke test...';
+        panel.style.display = 'block';
+        
+        try {
+            // Run as a background job and follow its payloads live over a WebSocket
+            const response = await fetch('/api/jobs', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ kind: 'smoke-test', url })
+            });
+            const data = await response.json();
+            if (!data.success || !data.job) {
+                throw new Error(data.error || 'Smoke test failed');
+            }
+            
+            const job = await followJob(data.job.id, event => {
+                if (event.type === 'payload') {
+                    const r = event.result;
+                    const row = document.createElement('div');
+                    const mark = { Blocked: '🚫', Allowed: '✅', Challenge: '🧩', RateLimited: '⏱️' }[r.classification] || '⚠️';
+                    row.textContent = `${mark} ${r.response_status} ${r.category} ${r.payload}`;
+                    rows.insertBefore(row, rows.firstChild);
+                    progressText.textContent = `${event.completed}/${event.total} payloads tested`;
+                } else if (event.type === 'status' || event.type === 'snapshot') {
+                    progressText.textContent = `Smoke test ${event.status || event.job.status}...`;
+                }
+            });
+            
+            if (job.status === 'completed' && job.result) {
+                const processedResult = processRawSmokeTestResult(job.result);
+                allResults.unshift(processedResult);
+                displayResults();
+                showSuccessMessage(`Smoke test completed for ${url} - ${job.result.test_results.length} payloads tested`);
+            } else {
+                showErrorMessage(job.error || 'Smoke test failed');
+            }
+        } catch (error) {
+            showErrorMessage(`Error: ${error.message}`);
+        } finally {
+            panel.style.display = 'none';
+            setButtonLoading(btn, icon, text, false, 'Run Smoke Test');
+        }
+    };
+    
+    // Resolve with the finished job, passing live events to onEvent; falls back to
+    // polling /api/jobs/{id} when the WebSocket cannot be used
+    function followJob(id, onEvent) {
+        return new Promise(resolve => {
+            let done = false;
+            const finish = job => {
+                if (!done) {
+                    done = true;
+                    resolve(job);
+                }
+            };
+            const poll = async () => {
+                while (!done) {
+                    const data = await (await fetch(`/api/jobs/${id}`)).json();
+                    if (data.job && (data.job.status === 'completed' || data.job.status === 'failed')) {
+                        finish(data.job);
+                        return;
+                    }
+                    await new Promise(r => setTimeout(r, 1000));
+                }
+            };
+            
+            const scheme = location.protocol === 'https:' ? 'wss' : 'ws';
+            let socket;
+            try {
+                socket = new WebSocket(`${scheme}://${location.host}/ws/scan/${id}`);
+            } catch (error) {
+                poll();
+                return;
+            }
+            socket.onmessage = message => {
+                const event = JSON.parse(message.data);
+                if (event.type === 'finished') {
+                    finish(event.job);
+                } else {
+                    onEvent(event);
+                }
+            };
+            socket.onerror = () => poll();
+            socket.onclose = () => {
+                if (!done) {
+                    poll();
+                }
+            };
+        });
+    }
+    
+    // Function to process raw smoke test results
+    function processRawSmokeTestResult(rawResult) {
+        console.log("Processing smoke test result:", rawResult);
+        
+        // Ensure the result has the correct structure for smoke test display
+        let processedResult = {
+            url: rawResult.url,
+            // Flag this explicitly as a smoke test result
+            is_smoke_test: true,
+            test_results: [],
+            summary: rawResult.summary || {
+                effectiveness_percentage: 0,
+                total_tests: 0,
+                blocked_tests: 0
+            },
+            effectiveness_percentage: rawResult.summary ? 
+                rawResult.summary.effectiveness_percentage : 
+                (rawResult.effectiveness_percentage || 0),
+            waf_mode: rawResult.waf_mode || "Unknown",
+            // Handle null values safely
+            detected_waf: rawResult.detected_waf === null ? null : (rawResult.detected_waf || null),
+            detected_cdn: rawResult.detected_cdn === null ? null : (rawResult.detected_cdn || null),
+            total_time_ms: rawResult.total_time_ms || 0,
+            timestamp: rawResult.timestamp || new Date().toISOString()
+        };
+        
+        // Process test results with careful handling of response_status
+        if (Array.isArray(rawResult.test_results)) {
+            processedResult.test_results = rawResult.test_results.map(test => {
+                // Log each test result for debugging
+                console.log('Processing test result:', test);
+                
+                return {
+                    category: test.category || "Unknown",
+                    payload: (test.payload !== undefined && test.payload !== null) ? test.payload : '',
+                    response_status: parseInt(test.response_status) || 0,
+                    response_time_ms: test.response_time_ms || 0,
+                    classification: test.classification || "Unknown"
+                };
+            });
+        }
+        
+        return processedResult;
+    }
+    
+    console.log("Smoke test display fix applied");
+}
+
+// Apply the fix when the page loads
+document.addEventListener('DOMContentLoaded', function() {
+    fixSmokeTestDisplay();
+    console.log("Smoke test display fix applied on page load");
+});